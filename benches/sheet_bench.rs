@@ -0,0 +1,160 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use piptable_sheet::{CsvOptions, Sheet};
+
+const SIZES: [usize; 2] = [10_000, 100_000];
+
+/// Builds a CSV document with a header and `rows` data rows.
+fn csv_data(rows: usize) -> String {
+    let mut csv = String::from("id,name,amount,active,joined\n");
+    for i in 0..rows {
+        csv.push_str(&format!(
+            "{},customer {},{}.{},{},2024-{:02}-{:02}\n",
+            i,
+            i % 997,
+            i % 10_000,
+            i % 100,
+            i % 3 == 0,
+            i % 12 + 1,
+            i % 28 + 1
+        ));
+    }
+    csv
+}
+
+/// Reads the benchmark CSV into a columnar sheet.
+fn columnar_sheet(rows: usize) -> Sheet {
+    let options = CsvOptions::default().with_headers(true);
+    Sheet::from_csv_str_with_options(&csv_data(rows), options).unwrap()
+}
+
+/// The same sheet, switched to row storage.
+fn row_sheet(rows: usize) -> Sheet {
+    let mut sheet = columnar_sheet(rows);
+    let _ = sheet.data_mut();
+    sheet
+}
+
+/// Both storage layouts of the same sheet, labelled for benchmark ids.
+fn layouts(rows: usize) -> [(&'static str, Sheet); 2] {
+    [("rows", row_sheet(rows)), ("columns", columnar_sheet(rows))]
+}
+
+/// A lookup table keyed by customer name.
+fn regions(layout: &str) -> Sheet {
+    let mut data = vec![vec!["name".to_string(), "region".to_string()]];
+    for i in 0..997 {
+        data.push(vec![format!("customer {i}"), format!("region {}", i % 7)]);
+    }
+    let mut sheet = Sheet::from_data(data);
+    sheet.name_columns_by_row(0).unwrap();
+    if layout == "columns" {
+        sheet.compact();
+    }
+    sheet
+}
+
+/// Benchmarks reading CSV and Parquet files.
+fn bench_import(c: &mut Criterion) {
+    let mut group = c.benchmark_group("import");
+    group.sample_size(10);
+    let dir = tempfile::tempdir().unwrap();
+
+    for size in SIZES {
+        let csv = csv_data(size);
+        group.bench_with_input(BenchmarkId::new("csv", size), &csv, |b, csv| {
+            b.iter(|| {
+                let options = CsvOptions::default().with_headers(true);
+                Sheet::from_csv_str_with_options(black_box(csv), options).unwrap()
+            });
+        });
+
+        let path = dir.path().join(format!("bench_{size}.parquet"));
+        columnar_sheet(size).save_as_parquet(&path).unwrap();
+        group.bench_with_input(BenchmarkId::new("parquet", size), &path, |b, path| {
+            b.iter(|| Sheet::from_parquet(black_box(path)).unwrap());
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmarks filtering rows and reading a column on both storage layouts.
+fn bench_filter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("filter");
+    group.sample_size(10);
+
+    for size in SIZES {
+        for (layout, sheet) in layouts(size) {
+            group.bench_with_input(BenchmarkId::new(layout, size), &sheet, |b, sheet| {
+                b.iter_batched(
+                    || sheet.clone(),
+                    |mut sheet| {
+                        sheet.filter_rows(|i, row| {
+                            i == 0 || row[2].as_float().is_some_and(|v| v > 5000.0)
+                        });
+                        sheet
+                    },
+                    BatchSize::LargeInput,
+                );
+            });
+            group.bench_with_input(
+                BenchmarkId::new(format!("{layout}/column"), size),
+                &sheet,
+                |b, sheet| b.iter(|| sheet.column_by_name("amount").unwrap()),
+            );
+        }
+    }
+
+    group.finish();
+}
+
+/// Benchmarks a left join against a small lookup table.
+fn bench_join(c: &mut Criterion) {
+    let mut group = c.benchmark_group("join");
+    group.sample_size(10);
+
+    for size in SIZES {
+        for (layout, sheet) in layouts(size) {
+            let lookup = regions(layout);
+            group.bench_with_input(BenchmarkId::new(layout, size), &sheet, |b, sheet| {
+                b.iter(|| sheet.left_join(black_box(&lookup), "name").unwrap());
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Benchmarks writing CSV and Parquet on both storage layouts.
+fn bench_export(c: &mut Criterion) {
+    let mut group = c.benchmark_group("export");
+    group.sample_size(10);
+    let dir = tempfile::tempdir().unwrap();
+
+    for size in SIZES {
+        for (layout, sheet) in layouts(size) {
+            group.bench_with_input(
+                BenchmarkId::new(format!("csv/{layout}"), size),
+                &sheet,
+                |b, sheet| b.iter(|| sheet.to_csv_string().unwrap()),
+            );
+            let path = dir.path().join(format!("export_{layout}_{size}.parquet"));
+            group.bench_with_input(
+                BenchmarkId::new(format!("parquet/{layout}"), size),
+                &sheet,
+                |b, sheet| b.iter(|| sheet.save_as_parquet(black_box(&path)).unwrap()),
+            );
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_import,
+    bench_filter,
+    bench_join,
+    bench_export
+);
+criterion_main!(benches);
@@ -4,14 +4,16 @@
 
 use anyhow::{Context, Result};
 use arrow::util::pretty::pretty_format_batches;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use colored::Colorize;
 use piptable_core::Value;
 use piptable_interpreter::Interpreter;
 use piptable_parser::PipParser;
+use piptable_sheet::{Book, ConsolidateOptions, ProfileOptions};
 use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
-use std::path::PathBuf;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
 use tracing_subscriber::EnvFilter;
 
 /// CLI arguments for the piptable interpreter.
@@ -19,6 +21,9 @@ use tracing_subscriber::EnvFilter;
 #[command(name = "pip")]
 #[command(author, version, about = "VBA+SQL DSL for data processing", long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Script file to execute
     #[arg(value_name = "FILE")]
     file: Option<PathBuf>,
@@ -32,7 +37,7 @@ struct Cli {
     interactive: bool,
 
     /// Output format (json, csv, table)
-    #[arg(short = 'f', long = "format", default_value = "table")]
+    #[arg(short = 'f', long = "format", default_value = "table", global = true)]
     format: OutputFormat,
 
     /// Set variable (key=value)
@@ -44,6 +49,28 @@ struct Cli {
     verbose: bool,
 }
 
+/// CLI subcommands; without one, `pip` runs a script or the REPL.
+#[derive(Subcommand)]
+enum Command {
+    /// Profile every sheet of a data file
+    ///
+    /// Prints one row per column with its type, nulls, distinct values,
+    /// range, most frequent values and a histogram.
+    Inspect {
+        /// Data file (csv, tsv, xlsx, xls, json, jsonl, toon, parquet)
+        #[arg(value_name = "DATA")]
+        file: PathBuf,
+
+        /// Number of most frequent values listed per column
+        #[arg(long = "top", default_value_t = 5)]
+        top_k: usize,
+
+        /// Number of histogram bins for numeric columns
+        #[arg(long, default_value_t = 10)]
+        bins: usize,
+    },
+}
+
 /// Output format for CLI results.
 #[derive(Clone, Copy, Default, clap::ValueEnum)]
 enum OutputFormat {
@@ -69,6 +96,11 @@ async fn main() -> Result<()> {
             .init();
     }
 
+    if let Some(Command::Inspect { file, top_k, bins }) = &cli.command {
+        let options = ProfileOptions::new().with_top_k(*top_k).with_bins(*bins);
+        return inspect(file, &options, cli.format);
+    }
+
     // Create interpreter
     let mut interpreter = Interpreter::new();
 
@@ -139,6 +171,56 @@ async fn run_script(
     Ok(())
 }
 
+/// Load a data file as a book: every worksheet of an Excel file, or a
+/// single sheet named after the file otherwise.
+fn load_book(path: &Path) -> Result<Book> {
+    let excel = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| matches!(e.to_ascii_lowercase().as_str(), "xlsx" | "xls"));
+    let book = if excel {
+        Book::from_excel(path)
+    } else {
+        Book::from_files(&[path])
+    };
+    book.with_context(|| format!("Failed to load {}", path.display()))
+}
+
+/// Print a profile of every sheet in `path`. Tables get one section per
+/// sheet; CSV and JSON output stack the profiles with a `sheet` column.
+fn inspect(path: &Path, options: &ProfileOptions, format: OutputFormat) -> Result<()> {
+    let book = load_book(path)?;
+    let mut profiles = Book::new();
+    for (name, sheet) in book.sheets() {
+        profiles.add_sheet(name, sheet.profile_with_options(options)?)?;
+    }
+
+    match format {
+        OutputFormat::Table => {
+            for (idx, (name, sheet)) in book.sheets().enumerate() {
+                if idx > 0 {
+                    println!();
+                }
+                let rows = sheet.row_count() - sheet.data_start_row().min(sheet.row_count());
+                println!(
+                    "{} ({} rows, {} columns)",
+                    name.cyan().bold(),
+                    rows,
+                    sheet.col_count()
+                );
+                println!("{}", format_sheet_table(profiles.get_sheet(name)?));
+            }
+            Ok(())
+        }
+        OutputFormat::Csv | OutputFormat::Json => {
+            let stacked = profiles.consolidate_with_options(
+                ConsolidateOptions::default().with_source_column("sheet"),
+            )?;
+            print_value(&Value::Sheet(Box::new(stacked)), format)
+        }
+    }
+}
+
 /// Run the REPL.
 async fn run_repl(interpreter: &mut Interpreter, format: OutputFormat) -> Result<()> {
     println!(
@@ -317,6 +399,11 @@ fn print_value(value: &Value, format: OutputFormat) -> Result<()> {
                 }
             }
         },
+        Value::Sheet(sheet) => match format {
+            OutputFormat::Json => println!("{}", sheet.to_json_string_pretty()?),
+            OutputFormat::Csv => print!("{}", sheet.to_csv_string()?),
+            OutputFormat::Table => println!("{}", format_sheet_table(sheet)),
+        },
         Value::Book(book) => match format {
             OutputFormat::Json => {
                 let json = book_to_json(book)?;
@@ -382,9 +469,52 @@ fn format_value(value: &Value) -> String {
         Value::Lambda { params, .. } => {
             format!("<Lambda: |{}|>", params.join(", "))
         }
+        Value::Stream(stream) => {
+            format!(
+                "<Stream: {} ({} rows per chunk)>",
+                stream.path().display(),
+                stream.chunk_rows()
+            )
+        }
     }
 }
 
+/// Format a sheet as a bordered text table; a header row is set off by a rule.
+fn format_sheet_table(sheet: &piptable_sheet::Sheet) -> String {
+    let rows: Vec<Vec<String>> = sheet
+        .data()
+        .iter()
+        .map(|row| row.iter().map(piptable_sheet::CellValue::as_str).collect())
+        .collect();
+    let mut widths = vec![0; sheet.col_count()];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut rule = String::new();
+    for width in &widths {
+        let _ = write!(rule, "+{}", "-".repeat(width + 2));
+    }
+    rule.push('+');
+    let mut lines = vec![rule.clone()];
+    for (idx, row) in rows.iter().enumerate() {
+        let mut line = String::new();
+        for (col, width) in widths.iter().enumerate() {
+            let cell = row.get(col).map_or("", String::as_str);
+            let _ = write!(line, "| {cell:<width$} ");
+        }
+        line.push('|');
+        lines.push(line);
+        if idx + 1 == sheet.data_start_row() {
+            lines.push(rule.clone());
+        }
+    }
+    lines.push(rule);
+    lines.join("\n")
+}
+
 fn book_to_json(book: &piptable_sheet::Book) -> Result<serde_json::Value> {
     use piptable_sheet::CellValue;
     use serde_json::Value as JsonValue;
@@ -408,6 +538,9 @@ fn book_to_json(book: &piptable_sheet::Book) -> Result<serde_json::Value> {
                 }
                 JsonValue::Object(obj)
             }
+            CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_) => {
+                JsonValue::String(cell.as_str())
+            }
         }
     }
     let mut map = serde_json::Map::new();
@@ -708,6 +841,22 @@ mod tests {
         );
     }
 
+    /// Verifies table output for sheet values.
+    #[test]
+    fn test_format_sheet_table() {
+        let mut sheet =
+            piptable_sheet::Sheet::from_data(vec![vec!["id", "name"], vec!["1", "Alice"]]);
+        sheet.name_columns_by_row(0).unwrap();
+        assert_eq!(
+            format_sheet_table(&sheet),
+            "+----+-------+\n\
+             | id | name  |\n\
+             +----+-------+\n\
+             | 1  | Alice |\n\
+             +----+-------+"
+        );
+    }
+
     // ========================================================================
     // CSV escaping tests
     // ========================================================================
@@ -801,6 +950,34 @@ mod tests {
         assert!(cli.verbose);
     }
 
+    /// Verifies CLI parsing for the inspect subcommand.
+    #[test]
+    fn test_cli_parse_inspect() {
+        let cli = Cli::parse_from(["pip", "inspect", "data.xlsx", "--top", "3", "-f", "json"]);
+        assert!(matches!(cli.format, OutputFormat::Json));
+        let Some(Command::Inspect { file, top_k, bins }) = cli.command else {
+            panic!("Expected inspect");
+        };
+        assert_eq!(file, PathBuf::from("data.xlsx"));
+        assert_eq!((top_k, bins), (3, 10));
+        assert!(cli.file.is_none());
+
+        assert!(Cli::try_parse_from(["pip", "inspect"]).is_err());
+    }
+
+    /// Verifies loading a non-Excel file as a one-sheet book.
+    #[test]
+    fn test_load_book_and_inspect() {
+        let path = std::env::temp_dir().join(format!("pip_inspect_{}.csv", std::process::id()));
+        std::fs::write(&path, "name,qty\nwidget,3\ngadget,\n").unwrap();
+        let book = load_book(&path).unwrap();
+        assert_eq!(book.sheet_count(), 1);
+        assert!(inspect(&path, &ProfileOptions::new(), OutputFormat::Csv).is_ok());
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(load_book(Path::new("/nonexistent/data.csv")).is_err());
+    }
+
     // ========================================================================
     // Integration tests
     // ========================================================================
@@ -189,6 +189,13 @@ pub fn value_to_toon(value: &Value) -> ToonValue {
                 book.sheet_count()
             ),
         },
+        Value::Stream(stream) => ToonValue::Error {
+            code: "STREAM_UNSUPPORTED".to_string(),
+            msg: format!(
+                "Stream of '{}' cannot cross boundary directly",
+                stream.path().display()
+            ),
+        },
         Value::Function { name, .. } => ToonValue::Error {
             code: "FUNCTION_UNSUPPORTED".to_string(),
             msg: format!("Function '{}' cannot cross WASM boundary", name),
@@ -1,7 +1,7 @@
 //! Runtime value types for piptable.
 
 use arrow::array::RecordBatch;
-use piptable_sheet::{Book, Sheet};
+use piptable_sheet::{Book, Sheet, SheetStream};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -40,6 +40,8 @@ pub enum Value {
     Sheet(Box<Sheet>),
     /// Book data (piptable_sheet::Book).
     Book(Box<Book>),
+    /// A file read in chunks of sheets (piptable_sheet::SheetStream).
+    Stream(SheetStream),
 
     /// Function reference.
     Function {
@@ -73,6 +75,7 @@ impl Value {
             Self::Table(t) => !t.is_empty(),
             Self::Sheet(s) => s.row_count() > 0,
             Self::Book(b) => b.sheet_count() > 0,
+            Self::Stream(_) => true,
             Self::Function { .. } => true,
             Self::Lambda { .. } => true,
         }
@@ -92,6 +95,7 @@ impl Value {
             Self::Table(_) => "Table",
             Self::Sheet(_) => "Sheet",
             Self::Book(_) => "Book",
+            Self::Stream(_) => "Stream",
             Self::Function { .. } => "Function",
             Self::Lambda { .. } => "Lambda",
         }
@@ -284,6 +288,9 @@ impl Serialize for Value {
             Self::Book(_) => Err(serde::ser::Error::custom(
                 "Book values are not JSON-serializable",
             )),
+            Self::Stream(_) => Err(serde::ser::Error::custom(
+                "Stream values are not JSON-serializable",
+            )),
             Self::Function { name, .. } => Err(serde::ser::Error::custom(format!(
                 "Function '{name}' is not JSON-serializable"
             ))),
@@ -363,6 +370,7 @@ impl Value {
             Self::Table(_) => Err("Table values are not JSON-serializable"),
             Self::Sheet(_) => Err("Sheet values are not JSON-serializable"),
             Self::Book(_) => Err("Book values are not JSON-serializable"),
+            Self::Stream(_) => Err("Stream values are not JSON-serializable"),
             Self::Function { .. } => Err("Function values are not JSON-serializable"),
             Self::Lambda { .. } => Err("Lambda expressions are not JSON-serializable"),
         }
@@ -442,6 +450,8 @@ mod tests {
         assert_eq!(Value::Int(42).type_name(), "Int");
         assert_eq!(Value::Float(3.14).type_name(), "Float");
         assert_eq!(Value::String("test".to_string()).type_name(), "String");
+        let stream = piptable_sheet::SheetStream::new("big.csv", 10).unwrap();
+        assert_eq!(Value::Stream(stream).type_name(), "Stream");
         assert_eq!(Value::Array(vec![]).type_name(), "Array");
         assert_eq!(Value::Object(HashMap::new()).type_name(), "Object");
         assert_eq!(Value::Table(vec![]).type_name(), "Table");
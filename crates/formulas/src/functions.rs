@@ -1,24 +1,18 @@
 //! Standard spreadsheet functions implementation
 
-use chrono::{Local, TimeZone, Utc};
 use piptable_primitives::{ErrorValue, Value};
-use piptable_utils::datetime::datetime_to_excel_date;
 use piptable_utils::math as shared_math;
 
-fn local_to_excel(local_dt: Option<chrono::DateTime<Local>>) -> Value {
-    match local_dt {
-        Some(dt) => Value::Float(datetime_to_excel_date(dt.with_timezone(&Utc))),
-        None => Value::Error(ErrorValue::Value),
-    }
-}
-
-fn date_to_local(date: chrono::NaiveDate, hour: u32, minute: u32, second: u32) -> Value {
-    let Some(naive) = date.and_hms_opt(hour, minute, second) else {
-        return Value::Error(ErrorValue::Value);
-    };
-    let local_dt = Local.from_local_datetime(&naive).earliest();
-    local_to_excel(local_dt)
-}
+mod conditional;
+mod datetime;
+mod dynamic;
+mod statistics;
+mod text;
+pub use conditional::*;
+pub use datetime::*;
+pub use dynamic::*;
+pub use statistics::*;
+pub use text::*;
 
 fn walk_values(values: &[Value], f: &mut dyn FnMut(&Value)) {
     for value in values {
@@ -403,47 +397,10 @@ pub fn right(values: &[Value]) -> Value {
     }
 }
 
-/// TODAY implementation.
-pub fn today(_: &[Value]) -> Value {
-    let local = Local::now();
-    let date = local.date_naive();
-    date_to_local(date, 0, 0, 0)
-}
-
-/// NOW implementation.
-pub fn now(_: &[Value]) -> Value {
-    let local = Local::now();
-    local_to_excel(Some(local))
-}
-
-/// DATE implementation.
-pub fn date(values: &[Value]) -> Value {
-    let year = match read_required_number(values.first()) {
-        Ok(value) => value,
-        Err(err) => return Value::Error(err),
-    };
-    let month = match read_required_number(values.get(1)) {
-        Ok(value) => value,
-        Err(err) => return Value::Error(err),
-    };
-    let day = match read_required_number(values.get(2)) {
-        Ok(value) => value,
-        Err(err) => return Value::Error(err),
-    };
-
-    let year = year.floor() as i32;
-    let month = month.floor() as u32;
-    let day = day.floor() as u32;
-
-    let Some(date) = chrono::NaiveDate::from_ymd_opt(year, month, day) else {
-        return Value::Error(ErrorValue::Value);
-    };
-    date_to_local(date, 0, 0, 0)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use piptable_utils::datetime::DateSystem;
 
     #[test]
     fn test_concat_and_len() {
@@ -469,7 +426,10 @@ mod tests {
 
     #[test]
     fn test_date_returns_number() {
-        let result = date(&[Value::Int(2024), Value::Int(1), Value::Int(1)]);
+        let result = date(
+            &[Value::Int(2024), Value::Int(1), Value::Int(1)],
+            DateSystem::Excel1900,
+        );
         assert!(matches!(result, Value::Float(_)));
     }
 
@@ -541,10 +501,10 @@ mod tests {
 
     #[test]
     fn test_today_now_returns_number() {
-        let result = today(&[]);
+        let result = today(&[], DateSystem::Excel1900);
         assert!(matches!(result, Value::Float(_)));
 
-        let result = now(&[]);
+        let result = now(&[], DateSystem::Excel1900);
         assert!(matches!(result, Value::Float(_)));
     }
 
@@ -589,10 +549,13 @@ mod tests {
 
     #[test]
     fn test_date_invalid_inputs() {
-        let result = date(&[Value::Int(2024), Value::Int(13), Value::Int(1)]);
+        let result = date(
+            &[Value::Int(2024), Value::Int(13), Value::Int(1)],
+            DateSystem::Excel1900,
+        );
         assert_eq!(result, Value::Error(ErrorValue::Value));
 
-        let result = date(&[Value::Int(2024), Value::Int(1)]);
+        let result = date(&[Value::Int(2024), Value::Int(1)], DateSystem::Excel1900);
         assert_eq!(result, Value::Error(ErrorValue::Value));
     }
 
@@ -656,19 +619,6 @@ mod tests {
         assert_eq!(result, Value::Int(1));
     }
 
-    #[test]
-    fn test_local_to_excel_failure() {
-        let result = local_to_excel(None);
-        assert_eq!(result, Value::Error(ErrorValue::Value));
-    }
-
-    #[test]
-    fn test_date_to_local_invalid_time() {
-        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
-        let result = date_to_local(date, 24, 0, 0);
-        assert_eq!(result, Value::Error(ErrorValue::Value));
-    }
-
     #[test]
     fn test_right_error_and_or_float_cases() {
         let result = right(&[Value::Error(ErrorValue::Div0)]);
@@ -0,0 +1,517 @@
+//! Conditional aggregation: SUMIF(S), COUNTIF(S), AVERAGEIF(S), MAXIFS,
+//! MINIFS and SUMPRODUCT.
+//!
+//! Criteria follow Excel: a number or `"=5"` matches equal numbers, an
+//! operator prefix (`">=10"`, `"<>0"`) compares, text matches whole cells
+//! case-insensitively with `*`, `?` and `~` wildcards, `""` matches blanks
+//! and `"<>"` non-blanks. Criteria text holding a date is compared as a
+//! serial in the workbook's date system.
+
+use std::cmp::Ordering;
+
+use piptable_primitives::{ErrorValue, Value};
+use piptable_utils::datetime::DateSystem;
+use regex::{Regex, RegexBuilder};
+
+use super::datetime::parse_serial;
+use super::text::wildcard_source;
+use super::{from_grid, table_rows, to_number};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Op::Eq => ordering == Ordering::Equal,
+            Op::Ne => ordering != Ordering::Equal,
+            Op::Lt => ordering == Ordering::Less,
+            Op::Le => ordering != Ordering::Greater,
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// A parsed criteria argument.
+#[derive(Debug)]
+enum Criterion {
+    Number(Op, f64),
+    Bool(Op, bool),
+    /// Whole-cell text; `=` and `<>` match with the wildcard regex
+    Text(Op, String, Regex),
+    /// `""` or `"="` (blank) and `"<>"` (not blank)
+    Blank(bool),
+}
+
+fn num_cmp(a: f64, b: f64) -> Ordering {
+    if (a - b).abs() < f64::EPSILON {
+        Ordering::Equal
+    } else {
+        a.total_cmp(&b)
+    }
+}
+
+fn split_operator(text: &str) -> (Op, &str) {
+    for (prefix, op) in [
+        (">=", Op::Ge),
+        ("<=", Op::Le),
+        ("<>", Op::Ne),
+        (">", Op::Gt),
+        ("<", Op::Lt),
+        ("=", Op::Eq),
+    ] {
+        if let Some(rest) = text.strip_prefix(prefix) {
+            return (op, rest);
+        }
+    }
+    (Op::Eq, text)
+}
+
+impl Criterion {
+    fn parse(value: &Value, system: DateSystem) -> Result<Self, ErrorValue> {
+        let text = match value {
+            // An empty criteria cell is 0
+            Value::Empty => return Ok(Criterion::Number(Op::Eq, 0.0)),
+            Value::Int(n) => return Ok(Criterion::Number(Op::Eq, *n as f64)),
+            Value::Float(f) => return Ok(Criterion::Number(Op::Eq, *f)),
+            Value::Bool(b) => return Ok(Criterion::Bool(Op::Eq, *b)),
+            Value::Error(err) => return Err(err.clone()),
+            Value::Array(_) => return Err(ErrorValue::Value),
+            Value::String(text) => text,
+        };
+        let (op, operand) = split_operator(text);
+        if operand.is_empty() && matches!(op, Op::Eq | Op::Ne) {
+            return Ok(Criterion::Blank(op == Op::Eq));
+        }
+        if let Ok(n) = operand.trim().parse::<f64>() {
+            return Ok(Criterion::Number(op, n));
+        }
+        if operand.eq_ignore_ascii_case("TRUE") || operand.eq_ignore_ascii_case("FALSE") {
+            return Ok(Criterion::Bool(op, operand.eq_ignore_ascii_case("TRUE")));
+        }
+        if let Some(serial) = parse_serial(operand, system) {
+            return Ok(Criterion::Number(op, serial));
+        }
+        let regex = RegexBuilder::new(&format!("^(?:{})$", wildcard_source(operand)))
+            .case_insensitive(true)
+            .dot_matches_new_line(true)
+            .build()
+            .map_err(|_| ErrorValue::Value)?;
+        Ok(Criterion::Text(op, operand.to_lowercase(), regex))
+    }
+
+    fn matches(&self, cell: &Value) -> bool {
+        match self {
+            Criterion::Number(op, n) => {
+                let number = match cell {
+                    Value::Int(_) | Value::Float(_) => to_number(cell),
+                    // Numeric text only equals a number
+                    Value::String(s) if matches!(op, Op::Eq | Op::Ne) => s.trim().parse().ok(),
+                    _ => None,
+                };
+                match number {
+                    Some(number) => op.holds(num_cmp(number, *n)),
+                    None => *op == Op::Ne,
+                }
+            }
+            Criterion::Bool(op, b) => match cell {
+                Value::Bool(cell) => op.holds(cell.cmp(b)),
+                _ => *op == Op::Ne,
+            },
+            Criterion::Text(op, text, regex) => match (op, cell) {
+                (Op::Eq, Value::String(s)) => regex.is_match(s),
+                (Op::Ne, Value::String(s)) => !regex.is_match(s),
+                (Op::Ne, _) => true,
+                (_, Value::String(s)) => op.holds(s.to_lowercase().as_str().cmp(text.as_str())),
+                _ => false,
+            },
+            Criterion::Blank(blank) => {
+                let is_blank = match cell {
+                    Value::Empty => true,
+                    Value::String(s) => s.is_empty(),
+                    _ => false,
+                };
+                is_blank == *blank
+            }
+        }
+    }
+}
+
+/// The cells of a range argument as rows; a scalar is a single cell.
+fn grid(value: &Value) -> Vec<Vec<Value>> {
+    match value {
+        Value::Array(_) => table_rows(value).unwrap_or_default(),
+        other => vec![vec![other.clone()]],
+    }
+}
+
+/// A `(row, col)` position, or a `(rows, cols)` size.
+type Cell = (usize, usize);
+
+fn shape(rows: &[Vec<Value>]) -> Cell {
+    (rows.len(), rows.first().map_or(0, Vec::len))
+}
+
+/// Positions `(row, col)` of the cells matching every (range, criteria) pair.
+/// All ranges must have the shape of the first.
+fn matching_cells(pairs: &[Value], system: DateSystem) -> Result<(Vec<Cell>, Cell), ErrorValue> {
+    let mut first_shape = None;
+    let mut matched: Option<Vec<Cell>> = None;
+    for pair in pairs.chunks(2) {
+        let [range, criteria] = pair else {
+            return Err(ErrorValue::Value);
+        };
+        let rows = grid(range);
+        let this_shape = shape(&rows);
+        if *first_shape.get_or_insert(this_shape) != this_shape {
+            return Err(ErrorValue::Value);
+        }
+        let criterion = Criterion::parse(criteria, system)?;
+        let is_match = |&(r, c): &Cell| criterion.matches(rows[r].get(c).unwrap_or(&Value::Empty));
+        matched = Some(match matched {
+            Some(cells) => cells.into_iter().filter(is_match).collect(),
+            None => (0..this_shape.0)
+                .flat_map(|r| (0..this_shape.1).map(move |c| (r, c)))
+                .filter(is_match)
+                .collect(),
+        });
+    }
+    Ok((matched.unwrap_or_default(), first_shape.unwrap_or((0, 0))))
+}
+
+/// The numbers at `cells` of `values`, skipping text, logical values and
+/// blanks; an error in a matched cell is returned.
+fn numbers_at(values: &[Vec<Value>], cells: &[Cell]) -> Result<Vec<f64>, ErrorValue> {
+    let mut nums = Vec::new();
+    for &(r, c) in cells {
+        match values.get(r).and_then(|row| row.get(c)) {
+            Some(Value::Error(err)) => return Err(err.clone()),
+            Some(value) => nums.extend(to_number(value)),
+            None => {}
+        }
+    }
+    Ok(nums)
+}
+
+#[derive(Clone, Copy)]
+enum Aggregate {
+    Sum,
+    Count,
+    Average,
+    Max,
+    Min,
+}
+
+impl Aggregate {
+    fn apply(self, nums: &[f64], count: usize) -> Value {
+        match self {
+            Aggregate::Count => Value::Int(count as i64),
+            Aggregate::Sum => Value::Float(nums.iter().sum()),
+            Aggregate::Average if nums.is_empty() => Value::Error(ErrorValue::Div0),
+            Aggregate::Average => Value::Float(nums.iter().sum::<f64>() / nums.len() as f64),
+            // No matches give 0
+            Aggregate::Max => Value::Float(nums.iter().copied().reduce(f64::max).unwrap_or(0.0)),
+            Aggregate::Min => Value::Float(nums.iter().copied().reduce(f64::min).unwrap_or(0.0)),
+        }
+    }
+}
+
+/// SUMIF, COUNTIF and AVERAGEIF. An array of criteria gives an array of
+/// results, one per criterion.
+fn aggregate_if(values: &[Value], system: DateSystem, aggregate: Aggregate) -> Value {
+    let range = values.first().unwrap_or(&Value::Empty);
+    let criteria = values.get(1).unwrap_or(&Value::Empty);
+    let target = grid(values.get(2).unwrap_or(range));
+    let single = |criterion: &Value| {
+        match matching_cells(&[range.clone(), criterion.clone()], system) {
+            Ok((cells, _)) => match aggregate {
+                Aggregate::Count => aggregate.apply(&[], cells.len()),
+                // A sum range of another size is read from its top-left cell
+                _ => match numbers_at(&target, &cells) {
+                    Ok(nums) => aggregate.apply(&nums, cells.len()),
+                    Err(err) => Value::Error(err),
+                },
+            },
+            Err(err) => Value::Error(err),
+        }
+    };
+    match criteria {
+        Value::Array(_) => from_grid(
+            grid(criteria)
+                .iter()
+                .map(|row| row.iter().map(single).collect())
+                .collect(),
+        ),
+        criterion => single(criterion),
+    }
+}
+
+/// SUMIFS, AVERAGEIFS, MAXIFS and MINIFS: a target range then
+/// (range, criteria) pairs, all the same size.
+fn aggregate_ifs(values: &[Value], system: DateSystem, aggregate: Aggregate) -> Value {
+    let Some((target, pairs)) = values.split_first() else {
+        return Value::Error(ErrorValue::Value);
+    };
+    let target = grid(target);
+    match matching_cells(pairs, system) {
+        Ok((_, pair_shape)) if pair_shape != shape(&target) => Value::Error(ErrorValue::Value),
+        Ok((cells, _)) => match numbers_at(&target, &cells) {
+            Ok(nums) => aggregate.apply(&nums, cells.len()),
+            Err(err) => Value::Error(err),
+        },
+        Err(err) => Value::Error(err),
+    }
+}
+
+/// SUMIF(range, criteria, [sum_range])
+pub fn sumif(values: &[Value], system: DateSystem) -> Value {
+    aggregate_if(values, system, Aggregate::Sum)
+}
+
+/// COUNTIF(range, criteria)
+pub fn countif(values: &[Value], system: DateSystem) -> Value {
+    aggregate_if(values, system, Aggregate::Count)
+}
+
+/// AVERAGEIF(range, criteria, [average_range])
+pub fn averageif(values: &[Value], system: DateSystem) -> Value {
+    aggregate_if(values, system, Aggregate::Average)
+}
+
+/// SUMIFS(sum_range, criteria_range1, criteria1, ...)
+pub fn sumifs(values: &[Value], system: DateSystem) -> Value {
+    aggregate_ifs(values, system, Aggregate::Sum)
+}
+
+/// COUNTIFS(criteria_range1, criteria1, ...)
+pub fn countifs(values: &[Value], system: DateSystem) -> Value {
+    match matching_cells(values, system) {
+        Ok((cells, _)) => Aggregate::Count.apply(&[], cells.len()),
+        Err(err) => Value::Error(err),
+    }
+}
+
+/// AVERAGEIFS(average_range, criteria_range1, criteria1, ...)
+pub fn averageifs(values: &[Value], system: DateSystem) -> Value {
+    aggregate_ifs(values, system, Aggregate::Average)
+}
+
+/// MAXIFS(max_range, criteria_range1, criteria1, ...)
+pub fn maxifs(values: &[Value], system: DateSystem) -> Value {
+    aggregate_ifs(values, system, Aggregate::Max)
+}
+
+/// MINIFS(min_range, criteria_range1, criteria1, ...)
+pub fn minifs(values: &[Value], system: DateSystem) -> Value {
+    aggregate_ifs(values, system, Aggregate::Min)
+}
+
+/// SUMPRODUCT(array1, [array2], ...) - entries that are not numbers count as 0
+pub fn sumproduct(values: &[Value]) -> Value {
+    let arrays: Vec<Vec<Vec<Value>>> = values.iter().map(grid).collect();
+    let Some(first) = arrays.first() else {
+        return Value::Error(ErrorValue::Value);
+    };
+    let (rows, cols) = shape(first);
+    if arrays.iter().any(|array| shape(array) != (rows, cols)) {
+        return Value::Error(ErrorValue::Value);
+    }
+    let mut total = 0.0;
+    for r in 0..rows {
+        for c in 0..cols {
+            let mut product = 1.0;
+            for array in &arrays {
+                match array[r].get(c).unwrap_or(&Value::Empty) {
+                    Value::Error(err) => return Value::Error(err.clone()),
+                    value => product *= to_number(value).unwrap_or(0.0),
+                }
+            }
+            total += product;
+        }
+    }
+    Value::Float(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const S1900: DateSystem = DateSystem::Excel1900;
+
+    fn text(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    fn column(values: &[Value]) -> Value {
+        Value::Array(
+            values
+                .iter()
+                .map(|v| Value::Array(vec![v.clone()]))
+                .collect(),
+        )
+    }
+
+    fn count(range: &Value, criteria: Value) -> Value {
+        countif(&[range.clone(), criteria], S1900)
+    }
+
+    #[test]
+    fn test_criteria_syntax() {
+        let data = column(&[
+            Value::Int(5),
+            Value::Float(10.0),
+            Value::Int(15),
+            text("10"),
+            text("apple"),
+            text("Apricot"),
+            text("a*b"),
+            Value::Bool(true),
+            Value::Empty,
+            text(""),
+        ]);
+        assert_eq!(count(&data, Value::Int(10)), Value::Int(2));
+        assert_eq!(count(&data, text("10")), Value::Int(2));
+        assert_eq!(count(&data, text(">=10")), Value::Int(2));
+        assert_eq!(count(&data, text("<10")), Value::Int(1));
+        assert_eq!(count(&data, text("<>10")), Value::Int(8));
+        assert_eq!(count(&data, text("a*")), Value::Int(3));
+        assert_eq!(count(&data, text("AP???")), Value::Int(1));
+        assert_eq!(count(&data, text("a~*b")), Value::Int(1));
+        assert_eq!(count(&data, text("<>a*")), Value::Int(7));
+        assert_eq!(count(&data, text(">b")), Value::Int(0));
+        assert_eq!(count(&data, text("<b")), Value::Int(5));
+        assert_eq!(count(&data, Value::Bool(true)), Value::Int(1));
+        assert_eq!(count(&data, text("TRUE")), Value::Int(1));
+        assert_eq!(count(&data, text("")), Value::Int(2));
+        assert_eq!(count(&data, text("=")), Value::Int(2));
+        assert_eq!(count(&data, text("<>")), Value::Int(8));
+        // An empty criteria cell is 0
+        assert_eq!(count(&data, Value::Empty), Value::Int(0));
+    }
+
+    #[test]
+    fn test_date_criteria() {
+        let dates = column(&[Value::Int(45291), Value::Int(45306), Value::Int(45337)]);
+        assert_eq!(count(&dates, text(">=2024-01-15")), Value::Int(2));
+        assert_eq!(count(&dates, text("<1/1/2024")), Value::Int(1));
+        // In the 1904 system the same date text is a smaller serial
+        assert_eq!(
+            countif(&[dates, text("<2024-01-15")], DateSystem::Excel1904),
+            Value::Int(0)
+        );
+    }
+
+    #[test]
+    fn test_sumif_averageif() {
+        let regions = column(&[text("East"), text("West"), text("east"), text("North")]);
+        let amounts = column(&[Value::Int(10), Value::Int(20), Value::Int(30), text("n/a")]);
+        assert_eq!(
+            sumif(&[regions.clone(), text("east"), amounts.clone()], S1900),
+            Value::Float(40.0)
+        );
+        assert_eq!(
+            averageif(&[regions.clone(), text("<>West"), amounts.clone()], S1900),
+            Value::Float(20.0)
+        );
+        assert_eq!(
+            averageif(&[regions.clone(), text("South"), amounts.clone()], S1900),
+            Value::Error(ErrorValue::Div0)
+        );
+        // Without a sum range the criteria range is summed
+        assert_eq!(
+            sumif(&[amounts.clone(), text(">15")], S1900),
+            Value::Float(50.0)
+        );
+
+        // An array of criteria gives one result per criterion
+        let criteria = Value::Array(vec![Value::Array(vec![text("East"), text("West")])]);
+        assert_eq!(
+            sumif(&[regions.clone(), criteria, amounts], S1900),
+            Value::Array(vec![Value::Array(vec![
+                Value::Float(40.0),
+                Value::Float(20.0)
+            ])])
+        );
+
+        // Errors in matched sum cells are returned, unmatched ones ignored
+        let with_error = column(&[
+            Value::Int(1),
+            Value::Error(ErrorValue::NA),
+            Value::Int(3),
+            Value::Int(4),
+        ]);
+        assert_eq!(
+            sumif(&[regions.clone(), text("east"), with_error.clone()], S1900),
+            Value::Float(4.0)
+        );
+        assert_eq!(
+            sumif(&[regions, text("West"), with_error], S1900),
+            Value::Error(ErrorValue::NA)
+        );
+    }
+
+    #[test]
+    fn test_multi_criteria_functions() {
+        let amounts = column(&[
+            Value::Int(10),
+            Value::Int(20),
+            Value::Int(30),
+            Value::Int(40),
+        ]);
+        let regions = column(&[text("East"), text("West"), text("East"), text("East")]);
+        let qty = column(&[Value::Int(1), Value::Int(5), Value::Int(7), Value::Int(2)]);
+        let args = |first: Option<&Value>| {
+            let mut args: Vec<Value> = first.into_iter().cloned().collect();
+            args.extend([regions.clone(), text("East"), qty.clone(), text(">1")]);
+            args
+        };
+        assert_eq!(sumifs(&args(Some(&amounts)), S1900), Value::Float(70.0));
+        assert_eq!(countifs(&args(None), S1900), Value::Int(2));
+        assert_eq!(averageifs(&args(Some(&amounts)), S1900), Value::Float(35.0));
+        assert_eq!(maxifs(&args(Some(&amounts)), S1900), Value::Float(40.0));
+        assert_eq!(minifs(&args(Some(&amounts)), S1900), Value::Float(30.0));
+        assert_eq!(
+            maxifs(&[amounts.clone(), regions.clone(), text("North")], S1900),
+            Value::Float(0.0)
+        );
+
+        // Every range must be the same size
+        let short = column(&[Value::Int(1)]);
+        assert_eq!(
+            sumifs(&[amounts.clone(), short.clone(), text(">0")], S1900),
+            Value::Error(ErrorValue::Value)
+        );
+        assert_eq!(
+            countifs(&[regions, text("East"), short, text(">0")], S1900),
+            Value::Error(ErrorValue::Value)
+        );
+    }
+
+    #[test]
+    fn test_sumproduct() {
+        let a = Value::Array(vec![
+            Value::Array(vec![Value::Int(1), Value::Int(2)]),
+            Value::Array(vec![Value::Int(3), text("x")]),
+        ]);
+        let b = Value::Array(vec![
+            Value::Array(vec![Value::Int(4), Value::Int(5)]),
+            Value::Array(vec![Value::Float(0.5), Value::Int(7)]),
+        ]);
+        assert_eq!(sumproduct(&[a.clone(), b.clone()]), Value::Float(15.5));
+        assert_eq!(sumproduct(std::slice::from_ref(&b)), Value::Float(16.5));
+        assert_eq!(
+            sumproduct(&[a, column(&[Value::Int(1)])]),
+            Value::Error(ErrorValue::Value)
+        );
+        let with_error = Value::Array(vec![Value::Array(vec![Value::Error(ErrorValue::Div0)])]);
+        assert_eq!(sumproduct(&[with_error]), Value::Error(ErrorValue::Div0));
+    }
+}
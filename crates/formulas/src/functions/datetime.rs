@@ -0,0 +1,1001 @@
+//! Date and time functions over serial dates: the whole part of a serial
+//! counts days from the workbook's epoch and the fraction is the time of day.
+//!
+//! Every function takes the workbook's [`DateSystem`], so the 1900 system
+//! keeps Excel's phantom 1900-02-29 and the 1904 system counts from
+//! 1904-01-01. Text arguments are read as DATEVALUE reads them.
+
+use chrono::{Datelike, Local, NaiveDate, Timelike};
+use piptable_primitives::{ErrorValue, Value};
+use piptable_utils::datetime::DateSystem;
+
+use super::{coerce_to_bool, coerce_to_text, read_required_number, walk_values};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Largest hour, minute or second TIME accepts.
+const MAX_TIME_PART: i64 = 32_767;
+
+/// Date layouts accepted in text, tried in order. Slashed dates are
+/// month first.
+const DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d",
+    "%Y/%m/%d",
+    "%m/%d/%Y",
+    "%m-%d-%Y",
+    "%d-%B-%Y",
+    "%d %B %Y",
+    "%B %d, %Y",
+    "%B %d %Y",
+];
+
+fn serial_result(result: Result<f64, ErrorValue>) -> Value {
+    match result {
+        Ok(serial) => Value::Float(serial),
+        Err(err) => Value::Error(err),
+    }
+}
+
+fn int_result(result: Result<i64, ErrorValue>) -> Value {
+    match result {
+        Ok(n) => Value::Int(n),
+        Err(err) => Value::Error(err),
+    }
+}
+
+/// Whether `text` ends in AM or PM, ignoring case.
+fn meridiem(text: &str) -> Option<bool> {
+    let split = text.len().checked_sub(2)?;
+    if !text.is_char_boundary(split) {
+        return None;
+    }
+    match &text[split..] {
+        s if s.eq_ignore_ascii_case("AM") => Some(false),
+        s if s.eq_ignore_ascii_case("PM") => Some(true),
+        _ => None,
+    }
+}
+
+/// Split text into its date part and, when present, its time part, which
+/// starts at the digits before the first colon or before a trailing AM/PM.
+fn split_time(text: &str) -> (&str, Option<&str>) {
+    let anchor = match text.find(':') {
+        Some(colon) => colon,
+        None if meridiem(text).is_some() => text.len() - 2,
+        None => return (text, None),
+    };
+    let head = text[..anchor].trim_end();
+    let start = head.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let date = text[..start].trim_end();
+    let date = date.strip_suffix(['T', 't']).unwrap_or(date).trim_end();
+    (date, Some(&text[start..]))
+}
+
+/// Fraction of a day for `14:30`, `2:30:15 PM` or `10 AM`. Hours past 23
+/// wrap, as TIMEVALUE does.
+fn parse_time(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let pm = meridiem(text);
+    let clock = match pm {
+        Some(_) => text[..text.len() - 2].trim_end(),
+        None => text,
+    };
+    let mut parts = clock.split(':');
+    let hours: u32 = parts.next()?.parse().ok()?;
+    let minutes: u32 = match parts.next() {
+        Some(minutes) => minutes.parse().ok()?,
+        None if pm.is_some() => 0,
+        None => return None,
+    };
+    let seconds: f64 = match parts.next() {
+        Some(seconds) => seconds.parse().ok()?,
+        None => 0.0,
+    };
+    if parts.next().is_some() || minutes > 59 || !(0.0..60.0).contains(&seconds) {
+        return None;
+    }
+    let hours = match pm {
+        Some(pm) if hours <= 12 => hours % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hours,
+    };
+    let total = f64::from(hours) * 3600.0 + f64::from(minutes) * 60.0 + seconds;
+    Some((total / SECONDS_PER_DAY).fract())
+}
+
+/// Serial for date and time text such as `2024-01-15`, `Jan 15, 2024`,
+/// `1/15/2024 2:30 PM` or `14:30`. Text holding only a time is day 0.
+pub(super) fn parse_serial(text: &str, system: DateSystem) -> Option<f64> {
+    let (date, time) = split_time(text.trim());
+    let days = if date.is_empty() {
+        0
+    } else {
+        let date = DATE_FORMATS
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(date, format).ok())?;
+        system.date_to_serial(date)?
+    };
+    let time = match time {
+        Some(time) => parse_time(time)?,
+        None if date.is_empty() => return None,
+        None => 0.0,
+    };
+    Some(days as f64 + time)
+}
+
+/// A serial date argument. Blanks are 0 and text is read as DATEVALUE
+/// reads it; serials outside the date system are #NUM!.
+fn serial_arg(values: &[Value], idx: usize, system: DateSystem) -> Result<f64, ErrorValue> {
+    let serial = match values.get(idx).unwrap_or(&Value::Empty) {
+        Value::Empty => 0.0,
+        Value::Bool(b) => f64::from(u8::from(*b)),
+        Value::String(text) => parse_serial(text, system).ok_or(ErrorValue::Value)?,
+        Value::Error(err) => return Err(err.clone()),
+        Value::Array(_) => return Err(ErrorValue::Value),
+        other => read_required_number(Some(other))?,
+    };
+    if serial < 0.0 || serial >= (system.max_serial() + 1) as f64 {
+        Err(ErrorValue::Num)
+    } else {
+        Ok(serial)
+    }
+}
+
+/// The whole-day part of a serial date argument.
+fn day_arg(values: &[Value], idx: usize, system: DateSystem) -> Result<i64, ErrorValue> {
+    serial_arg(values, idx, system).map(|serial| serial.floor() as i64)
+}
+
+/// A whole-number argument, truncated; missing or blank is `default`.
+fn whole_arg(values: &[Value], idx: usize, default: i64) -> Result<i64, ErrorValue> {
+    match values.get(idx) {
+        None | Some(Value::Empty) => Ok(default),
+        Some(value) => read_required_number(Some(value)).map(|n| n.trunc() as i64),
+    }
+}
+
+fn ymd(system: DateSystem, day: i64) -> Result<(i32, u32, u32), ErrorValue> {
+    system.serial_to_ymd(day as f64).ok_or(ErrorValue::Num)
+}
+
+/// Serial of a date, accepting the 1900 system's phantom 1900-02-29.
+/// Dates that do not exist are #VALUE! and dates out of range #NUM!.
+fn serial_of(system: DateSystem, year: i32, month: u32, day: u32) -> Result<i64, ErrorValue> {
+    if system == DateSystem::Excel1900 && (year, month, day) == (1900, 2, 29) {
+        return Ok(60);
+    }
+    let date = NaiveDate::from_ymd_opt(year, month, day).ok_or(ErrorValue::Value)?;
+    system.date_to_serial(date).ok_or(ErrorValue::Num)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|date| date.pred_opt())
+        .map_or(31, |date| date.day())
+}
+
+fn days_in_year(year: i32) -> i64 {
+    if NaiveDate::from_ymd_opt(year, 2, 29).is_some() {
+        366
+    } else {
+        365
+    }
+}
+
+/// Day of the week, 0 for Sunday. Counted on 1900 serials so the 1900
+/// system's early days keep the weekdays Excel gives them.
+fn weekday_index(system: DateSystem, day: i64) -> i64 {
+    (system.to_1900_serial(day) - 1).rem_euclid(7)
+}
+
+/// Year and month `months` after the month of `day`, with its day of month.
+fn shift_months(system: DateSystem, day: i64, months: i64) -> Result<(i32, u32, u32), ErrorValue> {
+    let (year, month, dom) = ymd(system, day)?;
+    let total = i64::from(year) * 12 + i64::from(month) - 1 + months;
+    let year = i32::try_from(total.div_euclid(12)).map_err(|_| ErrorValue::Num)?;
+    Ok((year, total.rem_euclid(12) as u32 + 1, dom))
+}
+
+/// Weekend days indexed from Sunday, from a NETWORKDAYS.INTL/WORKDAY.INTL
+/// weekend code (1-7 for pairs, 11-17 for single days) or a seven-character
+/// Monday-first mask such as `"0000011"`.
+fn weekend_mask(value: Option<&Value>) -> Result<[bool; 7], ErrorValue> {
+    let mut mask = [false; 7];
+    let code = match value {
+        None | Some(Value::Empty) => 1,
+        Some(Value::String(text)) => {
+            if text.chars().count() != 7 || !text.chars().all(|c| c == '0' || c == '1') {
+                return Err(ErrorValue::Value);
+            }
+            for (i, c) in text.chars().enumerate() {
+                mask[(i + 1) % 7] = c == '1';
+            }
+            return Ok(mask);
+        }
+        Some(other) => read_required_number(Some(other))?.trunc() as i64,
+    };
+    match code {
+        1..=7 => {
+            mask[((code + 5) % 7) as usize] = true;
+            mask[((code + 6) % 7) as usize] = true;
+        }
+        11..=17 => mask[(code - 11) as usize] = true,
+        _ => return Err(ErrorValue::Num),
+    }
+    Ok(mask)
+}
+
+/// Sorted, distinct holiday days from a range, array or single date.
+fn holidays(value: Option<&Value>, system: DateSystem) -> Result<Vec<i64>, ErrorValue> {
+    let mut days = Vec::new();
+    let mut error = None;
+    if let Some(value) = value {
+        walk_values(std::slice::from_ref(value), &mut |cell| {
+            if matches!(cell, Value::Empty) {
+                return;
+            }
+            match day_arg(std::slice::from_ref(cell), 0, system) {
+                Ok(day) => days.push(day),
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        });
+    }
+    if let Some(err) = error {
+        return Err(err);
+    }
+    days.sort_unstable();
+    days.dedup();
+    Ok(days)
+}
+
+fn is_workday(system: DateSystem, day: i64, weekend: &[bool; 7], holidays: &[i64]) -> bool {
+    !weekend[weekday_index(system, day) as usize] && holidays.binary_search(&day).is_err()
+}
+
+/// Working days from `start` to `end` inclusive, negative when `end` is
+/// earlier.
+fn count_workdays(
+    system: DateSystem,
+    start: i64,
+    end: i64,
+    weekend: &[bool; 7],
+    holidays: &[i64],
+) -> i64 {
+    if start > end {
+        return -count_workdays(system, end, start, weekend, holidays);
+    }
+    let span = end - start + 1;
+    let per_week = weekend.iter().filter(|off| !**off).count() as i64;
+    let first = weekday_index(system, start);
+    let partial = (0..span % 7)
+        .filter(|i| !weekend[((first + i) % 7) as usize])
+        .count() as i64;
+    let off = holidays
+        .iter()
+        .filter(|day| (start..=end).contains(*day))
+        .filter(|day| !weekend[weekday_index(system, **day) as usize])
+        .count() as i64;
+    span / 7 * per_week + partial - off
+}
+
+/// The day `days` working days after `start`, or before it when negative.
+fn add_workdays(
+    system: DateSystem,
+    start: i64,
+    days: i64,
+    weekend: &[bool; 7],
+    holidays: &[i64],
+) -> Result<i64, ErrorValue> {
+    if weekend.iter().all(|off| *off) {
+        return Err(ErrorValue::Value);
+    }
+    let step = days.signum();
+    let mut day = start;
+    let mut left = days;
+    while left != 0 {
+        day += step;
+        if day < 0 || day > system.max_serial() {
+            return Err(ErrorValue::Num);
+        }
+        if is_workday(system, day, weekend, holidays) {
+            left -= step;
+        }
+    }
+    Ok(day)
+}
+
+/// Days between two dates on a 360-day calendar of 30-day months. The US
+/// (NASD) method moves month-end days to the 30th; the European method
+/// caps both days at 30.
+fn days_360(start: (i32, u32, u32), end: (i32, u32, u32), european: bool) -> i64 {
+    let (sy, sm, mut sd) = start;
+    let (ey, em, mut ed) = end;
+    if european {
+        sd = sd.min(30);
+        ed = ed.min(30);
+    } else {
+        let start_feb_end = sm == 2 && sd >= days_in_month(sy, 2);
+        let end_feb_end = em == 2 && ed >= days_in_month(ey, 2);
+        if start_feb_end && end_feb_end {
+            ed = 30;
+        }
+        if start_feb_end || sd == 31 {
+            sd = 30;
+        }
+        if ed == 31 && sd >= 30 {
+            ed = 30;
+        }
+    }
+    (i64::from(ey) - i64::from(sy)) * 360 + (i64::from(em) - i64::from(sm)) * 30 + i64::from(ed)
+        - i64::from(sd)
+}
+
+/// TODAY()
+pub fn today(_: &[Value], system: DateSystem) -> Value {
+    match system.date_to_serial(Local::now().date_naive()) {
+        Some(day) => Value::Float(day as f64),
+        None => Value::Error(ErrorValue::Num),
+    }
+}
+
+/// NOW()
+pub fn now(_: &[Value], system: DateSystem) -> Value {
+    let now = Local::now().naive_local();
+    match system.date_to_serial(now.date()) {
+        Some(day) => {
+            Value::Float(day as f64 + f64::from(now.num_seconds_from_midnight()) / SECONDS_PER_DAY)
+        }
+        None => Value::Error(ErrorValue::Num),
+    }
+}
+
+/// DATE(year, month, day) - years 0 to 1899 are counted from 1900
+pub fn date(values: &[Value], system: DateSystem) -> Value {
+    serial_result((|| {
+        let mut year = read_required_number(values.first())?.trunc();
+        let month = read_required_number(values.get(1))?.trunc();
+        let day = read_required_number(values.get(2))?.trunc();
+        if (0.0..1900.0).contains(&year) {
+            year += 1900.0;
+        }
+        if !(0.0..=9999.0).contains(&year) || month < 1.0 || day < 1.0 {
+            return Err(ErrorValue::Value);
+        }
+        serial_of(system, year as i32, month as u32, day as u32).map(|day| day as f64)
+    })())
+}
+
+/// TIME(hour, minute, second) - wraps past midnight
+pub fn time(values: &[Value], _: DateSystem) -> Value {
+    serial_result((|| {
+        let hour = whole_arg(values, 0, 0)?;
+        let minute = whole_arg(values, 1, 0)?;
+        let second = whole_arg(values, 2, 0)?;
+        if [hour, minute, second].iter().any(|n| *n > MAX_TIME_PART) {
+            return Err(ErrorValue::Num);
+        }
+        let total = hour * 3600 + minute * 60 + second;
+        if total < 0 {
+            return Err(ErrorValue::Num);
+        }
+        Ok((total % 86_400) as f64 / SECONDS_PER_DAY)
+    })())
+}
+
+/// DATEVALUE(date_text)
+pub fn datevalue(values: &[Value], system: DateSystem) -> Value {
+    serial_result(match values.first() {
+        Some(Value::String(text)) => parse_serial(text, system)
+            .map(f64::floor)
+            .ok_or(ErrorValue::Value),
+        Some(Value::Error(err)) => Err(err.clone()),
+        _ => Err(ErrorValue::Value),
+    })
+}
+
+/// TIMEVALUE(time_text)
+pub fn timevalue(values: &[Value], system: DateSystem) -> Value {
+    serial_result(match values.first() {
+        Some(Value::String(text)) => parse_serial(text, system)
+            .map(f64::fract)
+            .ok_or(ErrorValue::Value),
+        Some(Value::Error(err)) => Err(err.clone()),
+        _ => Err(ErrorValue::Value),
+    })
+}
+
+/// YEAR(serial_number)
+pub fn year(values: &[Value], system: DateSystem) -> Value {
+    int_result(day_arg(values, 0, system).and_then(|day| Ok(i64::from(ymd(system, day)?.0))))
+}
+
+/// MONTH(serial_number)
+pub fn month(values: &[Value], system: DateSystem) -> Value {
+    int_result(day_arg(values, 0, system).and_then(|day| Ok(i64::from(ymd(system, day)?.1))))
+}
+
+/// DAY(serial_number)
+pub fn day(values: &[Value], system: DateSystem) -> Value {
+    int_result(day_arg(values, 0, system).and_then(|day| Ok(i64::from(ymd(system, day)?.2))))
+}
+
+/// Seconds since midnight of a serial, rounded to the nearest second.
+fn seconds_of_day(values: &[Value], system: DateSystem) -> Result<i64, ErrorValue> {
+    let serial = serial_arg(values, 0, system)?;
+    Ok((serial.fract() * SECONDS_PER_DAY).round() as i64 % 86_400)
+}
+
+/// HOUR(serial_number)
+pub fn hour(values: &[Value], system: DateSystem) -> Value {
+    int_result(seconds_of_day(values, system).map(|s| s / 3600))
+}
+
+/// MINUTE(serial_number)
+pub fn minute(values: &[Value], system: DateSystem) -> Value {
+    int_result(seconds_of_day(values, system).map(|s| s / 60 % 60))
+}
+
+/// SECOND(serial_number)
+pub fn second(values: &[Value], system: DateSystem) -> Value {
+    int_result(seconds_of_day(values, system).map(|s| s % 60))
+}
+
+/// WEEKDAY(serial_number, [return_type])
+pub fn weekday(values: &[Value], system: DateSystem) -> Value {
+    int_result((|| {
+        let day = day_arg(values, 0, system)?;
+        // First day of the week (0 for Sunday) and the number it gets
+        let (first, base) = match whole_arg(values, 1, 1)? {
+            1 | 17 => (0, 1),
+            2 | 11 => (1, 1),
+            3 => (1, 0),
+            kind @ 12..=16 => (kind - 10, 1),
+            _ => return Err(ErrorValue::Num),
+        };
+        Ok((weekday_index(system, day) - first).rem_euclid(7) + base)
+    })())
+}
+
+/// WEEKNUM(serial_number, [return_type]) - week 1 holds January 1;
+/// return type 21 gives the ISO week
+pub fn weeknum(values: &[Value], system: DateSystem) -> Value {
+    int_result((|| {
+        let day = day_arg(values, 0, system)?;
+        let first = match whole_arg(values, 1, 1)? {
+            1 | 17 => 0,
+            2 | 11 => 1,
+            kind @ 12..=16 => kind - 10,
+            21 => return iso_week(system, day),
+            _ => return Err(ErrorValue::Num),
+        };
+        let jan1 = serial_of(system, ymd(system, day)?.0, 1, 1)?;
+        let offset = (weekday_index(system, jan1) - first).rem_euclid(7);
+        Ok((day - jan1 + offset).div_euclid(7) + 1)
+    })())
+}
+
+fn iso_week(system: DateSystem, day: i64) -> Result<i64, ErrorValue> {
+    let date = system.serial_to_date(day as f64).ok_or(ErrorValue::Num)?;
+    Ok(i64::from(date.iso_week().week()))
+}
+
+/// ISOWEEKNUM(date)
+pub fn isoweeknum(values: &[Value], system: DateSystem) -> Value {
+    int_result(day_arg(values, 0, system).and_then(|day| iso_week(system, day)))
+}
+
+/// EDATE(start_date, months) - the same day `months` later, or the end of
+/// that month when shorter
+pub fn edate(values: &[Value], system: DateSystem) -> Value {
+    serial_result((|| {
+        let start = day_arg(values, 0, system)?;
+        let (year, month, day) = shift_months(system, start, whole_arg(values, 1, 0)?)?;
+        let day = day.clamp(1, days_in_month(year, month));
+        serial_of(system, year, month, day)
+            .map(|day| day as f64)
+            .map_err(|_| ErrorValue::Num)
+    })())
+}
+
+/// EOMONTH(start_date, months)
+pub fn eomonth(values: &[Value], system: DateSystem) -> Value {
+    serial_result((|| {
+        let start = day_arg(values, 0, system)?;
+        let (year, month, _) = shift_months(system, start, whole_arg(values, 1, 0)?)?;
+        serial_of(system, year, month, days_in_month(year, month))
+            .map(|day| day as f64)
+            .map_err(|_| ErrorValue::Num)
+    })())
+}
+
+/// DATEDIF(start_date, end_date, unit) - units Y, M, D, MD, YM and YD
+pub fn datedif(values: &[Value], system: DateSystem) -> Value {
+    int_result((|| {
+        let start = day_arg(values, 0, system)?;
+        let end = day_arg(values, 1, system)?;
+        let unit = coerce_to_text(values.get(2).unwrap_or(&Value::Empty))?.to_ascii_uppercase();
+        if start > end {
+            return Err(ErrorValue::Num);
+        }
+        let (sy, sm, sd) = ymd(system, start)?;
+        let (ey, em, ed) = ymd(system, end)?;
+        let months = (i64::from(ey) - i64::from(sy)) * 12 + i64::from(em)
+            - i64::from(sm)
+            - i64::from(ed < sd);
+        match unit.as_str() {
+            "Y" => Ok(months / 12),
+            "M" => Ok(months),
+            "D" => Ok(end - start),
+            "YM" => Ok(months % 12),
+            "MD" if ed >= sd => Ok(i64::from(ed - sd)),
+            "MD" => {
+                // Days since the start's day of month in the month before the end
+                let (py, pm) = if em == 1 { (ey - 1, 12) } else { (ey, em - 1) };
+                let len = days_in_month(py, pm);
+                Ok(i64::from(ed + len - sd.min(len)))
+            }
+            "YD" => {
+                let year = if (sm, sd) <= (em, ed) { ey } else { ey - 1 };
+                let anchor = serial_of(system, year, sm, sd.clamp(1, days_in_month(year, sm)))?;
+                Ok(end - anchor)
+            }
+            _ => Err(ErrorValue::Num),
+        }
+    })())
+}
+
+/// DAYS(end_date, start_date)
+pub fn days(values: &[Value], system: DateSystem) -> Value {
+    int_result((|| {
+        Ok(day_arg(values, 0, system)? - day_arg(values, 1, system)?)
+    })())
+}
+
+/// DAYS360(start_date, end_date, [method]) - TRUE selects the European method
+pub fn days360(values: &[Value], system: DateSystem) -> Value {
+    int_result((|| {
+        let start = ymd(system, day_arg(values, 0, system)?)?;
+        let end = ymd(system, day_arg(values, 1, system)?)?;
+        let european = match values.get(2) {
+            None => false,
+            Some(value) => coerce_to_bool(value)?,
+        };
+        Ok(days_360(start, end, european))
+    })())
+}
+
+/// YEARFRAC(start_date, end_date, [basis]) - bases 0 (US 30/360),
+/// 1 (actual/actual), 2 (actual/360), 3 (actual/365) and 4 (European 30/360)
+pub fn yearfrac(values: &[Value], system: DateSystem) -> Value {
+    serial_result((|| {
+        let mut start = day_arg(values, 0, system)?;
+        let mut end = day_arg(values, 1, system)?;
+        let basis = whole_arg(values, 2, 0)?;
+        if start > end {
+            std::mem::swap(&mut start, &mut end);
+        }
+        let days = (end - start) as f64;
+        let (sy, sm, sd) = ymd(system, start)?;
+        let (ey, em, ed) = ymd(system, end)?;
+        match basis {
+            0 | 4 => Ok(days_360((sy, sm, sd), (ey, em, ed), basis == 4) as f64 / 360.0),
+            1 => {
+                let year_len = if sy == ey {
+                    days_in_year(sy) as f64
+                } else if ey == sy + 1 && (em, ed) <= (sm, sd) {
+                    // Within a year: 366 when the span holds a February 29
+                    let leap_day = (days_in_year(sy) == 366 && (sm, sd) <= (2, 29))
+                        || (days_in_year(ey) == 366 && (em, ed) >= (2, 29));
+                    if leap_day {
+                        366.0
+                    } else {
+                        365.0
+                    }
+                } else {
+                    let total: i64 = (sy..=ey).map(days_in_year).sum();
+                    total as f64 / f64::from(ey - sy + 1)
+                };
+                Ok(days / year_len)
+            }
+            2 => Ok(days / 360.0),
+            3 => Ok(days / 365.0),
+            _ => Err(ErrorValue::Num),
+        }
+    })())
+}
+
+fn networkdays_with(
+    values: &[Value],
+    system: DateSystem,
+    weekend: Option<&Value>,
+    holiday_arg: Option<&Value>,
+) -> Value {
+    int_result((|| {
+        let start = day_arg(values, 0, system)?;
+        let end = day_arg(values, 1, system)?;
+        let weekend = weekend_mask(weekend)?;
+        let holidays = holidays(holiday_arg, system)?;
+        Ok(count_workdays(system, start, end, &weekend, &holidays))
+    })())
+}
+
+/// NETWORKDAYS(start_date, end_date, [holidays])
+pub fn networkdays(values: &[Value], system: DateSystem) -> Value {
+    networkdays_with(values, system, None, values.get(2))
+}
+
+/// NETWORKDAYS.INTL(start_date, end_date, [weekend], [holidays])
+pub fn networkdays_intl(values: &[Value], system: DateSystem) -> Value {
+    networkdays_with(values, system, values.get(2), values.get(3))
+}
+
+fn workday_with(
+    values: &[Value],
+    system: DateSystem,
+    weekend: Option<&Value>,
+    holiday_arg: Option<&Value>,
+) -> Value {
+    serial_result((|| {
+        let start = day_arg(values, 0, system)?;
+        let days = whole_arg(values, 1, 0)?;
+        let weekend = weekend_mask(weekend)?;
+        let holidays = holidays(holiday_arg, system)?;
+        add_workdays(system, start, days, &weekend, &holidays).map(|day| day as f64)
+    })())
+}
+
+/// WORKDAY(start_date, days, [holidays])
+pub fn workday(values: &[Value], system: DateSystem) -> Value {
+    workday_with(values, system, None, values.get(2))
+}
+
+/// WORKDAY.INTL(start_date, days, [weekend], [holidays])
+pub fn workday_intl(values: &[Value], system: DateSystem) -> Value {
+    workday_with(values, system, values.get(2), values.get(3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const S1900: DateSystem = DateSystem::Excel1900;
+    const S1904: DateSystem = DateSystem::Excel1904;
+
+    fn text(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    fn serial(y: i32, m: u32, d: u32) -> Value {
+        date(
+            &[
+                Value::Int(y.into()),
+                Value::Int(m.into()),
+                Value::Int(d.into()),
+            ],
+            S1900,
+        )
+    }
+
+    fn assert_close(value: Value, expected: f64) {
+        match value {
+            Value::Float(f) => assert!((f - expected).abs() < 1e-9, "expected {expected}, got {f}"),
+            other => panic!("expected {expected}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_date_and_parts() {
+        assert_eq!(serial(2024, 1, 15), Value::Float(45306.0));
+        assert_eq!(serial(1900, 2, 29), Value::Float(60.0));
+        assert_eq!(serial(124, 1, 15), Value::Float(45306.0));
+        assert_eq!(
+            date(&[Value::Int(1904), Value::Int(1), Value::Int(1)], S1904),
+            Value::Float(0.0)
+        );
+        assert_eq!(
+            date(&[Value::Int(1903), Value::Int(12), Value::Int(31)], S1904),
+            Value::Error(ErrorValue::Num)
+        );
+
+        let stamp = [Value::Float(45306.75)];
+        assert_eq!(year(&stamp, S1900), Value::Int(2024));
+        assert_eq!(month(&stamp, S1900), Value::Int(1));
+        assert_eq!(day(&stamp, S1900), Value::Int(15));
+        assert_eq!(hour(&stamp, S1900), Value::Int(18));
+        assert_eq!(year(&stamp, S1904), Value::Int(2028));
+
+        // The 1900 system's phantom days
+        assert_eq!(day(&[Value::Int(60)], S1900), Value::Int(29));
+        assert_eq!(month(&[Value::Int(0)], S1900), Value::Int(1));
+        assert_eq!(day(&[Value::Int(0)], S1900), Value::Int(0));
+        assert_eq!(
+            year(&[Value::Int(-1)], S1900),
+            Value::Error(ErrorValue::Num)
+        );
+        assert_eq!(
+            year(&[Value::Int(2_958_466)], S1900),
+            Value::Error(ErrorValue::Num)
+        );
+    }
+
+    #[test]
+    fn test_time_functions() {
+        assert_close(
+            time(&[Value::Int(18), Value::Int(0), Value::Int(0)], S1900),
+            0.75,
+        );
+        assert_close(
+            time(&[Value::Int(25), Value::Int(-30), Value::Int(0)], S1900),
+            0.5 / 24.0,
+        );
+        assert_eq!(
+            time(&[Value::Int(0), Value::Int(0), Value::Int(-1)], S1900),
+            Value::Error(ErrorValue::Num)
+        );
+
+        let t = [Value::Float(0.604_166_678)];
+        assert_eq!(hour(&t, S1900), Value::Int(14));
+        assert_eq!(minute(&t, S1900), Value::Int(30));
+        assert_eq!(second(&t, S1900), Value::Int(0));
+        assert_eq!(minute(&[text("2:45:10 PM")], S1900), Value::Int(45));
+        assert_eq!(second(&[text("14:45:10")], S1900), Value::Int(10));
+    }
+
+    #[test]
+    fn test_datevalue_and_timevalue() {
+        for input in [
+            "2024-01-15",
+            "2024/01/15",
+            "1/15/2024",
+            "15-Jan-2024",
+            "15 January 2024",
+            "Jan 15, 2024",
+            "2024-01-15 10:30",
+            "2024-01-15T10:30:00",
+        ] {
+            assert_eq!(
+                datevalue(&[text(input)], S1900),
+                Value::Float(45306.0),
+                "{input}"
+            );
+        }
+        assert_eq!(
+            datevalue(&[text("2024-01-15")], S1904),
+            Value::Float(45306.0 - 1462.0)
+        );
+        assert_eq!(
+            datevalue(&[text("13/45/2024")], S1900),
+            Value::Error(ErrorValue::Value)
+        );
+        assert_eq!(
+            datevalue(&[Value::Int(45306)], S1900),
+            Value::Error(ErrorValue::Value)
+        );
+
+        assert_close(timevalue(&[text("18:00")], S1900), 0.75);
+        assert_close(timevalue(&[text("6:00 PM")], S1900), 0.75);
+        assert_close(timevalue(&[text("12 AM")], S1900), 0.0);
+        assert_close(timevalue(&[text("1/15/2024 6:00 am")], S1900), 0.25);
+        assert_eq!(
+            timevalue(&[text("25:61")], S1900),
+            Value::Error(ErrorValue::Value)
+        );
+    }
+
+    #[test]
+    fn test_weekday_and_weeknum() {
+        // 2024-01-15 is a Monday
+        let monday = [serial(2024, 1, 15)];
+        let kind = |k: i64| [serial(2024, 1, 15), Value::Int(k)];
+        assert_eq!(weekday(&monday, S1900), Value::Int(2));
+        assert_eq!(weekday(&kind(2), S1900), Value::Int(1));
+        assert_eq!(weekday(&kind(3), S1900), Value::Int(0));
+        assert_eq!(weekday(&kind(13), S1900), Value::Int(6));
+        assert_eq!(weekday(&kind(4), S1900), Value::Error(ErrorValue::Num));
+        // Excel counts 1900-01-01 as a Sunday
+        assert_eq!(weekday(&[Value::Int(1)], S1900), Value::Int(1));
+        // 1904-01-01 is a Friday
+        assert_eq!(weekday(&[Value::Int(0)], S1904), Value::Int(6));
+
+        assert_eq!(weeknum(&monday, S1900), Value::Int(3));
+        assert_eq!(
+            weeknum(&[serial(2024, 12, 31), Value::Int(1)], S1900),
+            Value::Int(53)
+        );
+        assert_eq!(
+            weeknum(&[serial(2021, 1, 3), Value::Int(2)], S1900),
+            Value::Int(1)
+        );
+        assert_eq!(
+            weeknum(&[serial(2021, 1, 3), Value::Int(21)], S1900),
+            Value::Int(53)
+        );
+        assert_eq!(isoweeknum(&[serial(2021, 1, 4)], S1900), Value::Int(1));
+    }
+
+    #[test]
+    fn test_month_arithmetic() {
+        let jan31 = serial(2024, 1, 31);
+        assert_eq!(
+            edate(&[jan31.clone(), Value::Int(1)], S1900),
+            serial(2024, 2, 29)
+        );
+        assert_eq!(
+            edate(&[jan31.clone(), Value::Int(-2)], S1900),
+            serial(2023, 11, 30)
+        );
+        assert_eq!(
+            eomonth(&[jan31.clone(), Value::Int(1)], S1900),
+            serial(2024, 2, 29)
+        );
+        assert_eq!(
+            eomonth(&[jan31, Value::Int(-1)], S1900),
+            serial(2023, 12, 31)
+        );
+        assert_eq!(
+            edate(&[Value::Int(1), Value::Int(-1)], S1900),
+            Value::Error(ErrorValue::Num)
+        );
+        // The phantom 1900-02-29 moves like a real month end
+        assert_eq!(
+            edate(&[Value::Int(60), Value::Int(12)], S1900),
+            serial(1901, 2, 28)
+        );
+        assert_eq!(
+            eomonth(&[Value::Int(0), Value::Int(0)], S1904),
+            Value::Float(30.0)
+        );
+    }
+
+    #[test]
+    fn test_datedif_and_day_counts() {
+        let dif = |start: Value, end: Value, unit: &str| datedif(&[start, end, text(unit)], S1900);
+        let start = serial(2020, 3, 15);
+        let end = serial(2024, 1, 10);
+        assert_eq!(dif(start.clone(), end.clone(), "Y"), Value::Int(3));
+        assert_eq!(dif(start.clone(), end.clone(), "M"), Value::Int(45));
+        assert_eq!(dif(start.clone(), end.clone(), "D"), Value::Int(1396));
+        assert_eq!(dif(start.clone(), end.clone(), "YM"), Value::Int(9));
+        assert_eq!(dif(start.clone(), end.clone(), "MD"), Value::Int(26));
+        assert_eq!(dif(start.clone(), end.clone(), "YD"), Value::Int(301));
+        assert_eq!(
+            dif(serial(2024, 1, 31), serial(2024, 3, 1), "MD"),
+            Value::Int(1)
+        );
+        assert_eq!(
+            dif(end.clone(), start.clone(), "D"),
+            Value::Error(ErrorValue::Num)
+        );
+        assert_eq!(
+            dif(start.clone(), end.clone(), "W"),
+            Value::Error(ErrorValue::Num)
+        );
+
+        assert_eq!(days(&[end.clone(), start.clone()], S1900), Value::Int(1396));
+        assert_eq!(
+            days(&[text("2024-03-01"), text("2024-02-01")], S1900),
+            Value::Int(29)
+        );
+
+        assert_eq!(
+            days360(&[serial(2024, 1, 31), serial(2024, 3, 31)], S1900),
+            Value::Int(60)
+        );
+        assert_eq!(
+            days360(&[serial(2023, 2, 28), serial(2023, 3, 31)], S1900),
+            Value::Int(30)
+        );
+        assert_eq!(
+            days360(
+                &[serial(2024, 1, 15), serial(2024, 3, 31), Value::Bool(true)],
+                S1900
+            ),
+            Value::Int(75)
+        );
+    }
+
+    #[test]
+    fn test_yearfrac_bases() {
+        let frac = |basis: i64| {
+            yearfrac(
+                &[serial(2012, 1, 1), serial(2012, 7, 30), Value::Int(basis)],
+                S1900,
+            )
+        };
+        assert_close(frac(0), 0.580_555_555_555_556);
+        assert_close(frac(1), 0.576_502_732_240_437);
+        assert_close(frac(2), 0.586_111_111_111_111);
+        assert_close(frac(3), 0.578_082_191_780_822);
+        assert_close(frac(4), 0.580_555_555_555_556);
+        assert_eq!(frac(5), Value::Error(ErrorValue::Num));
+
+        // Multi-year actual/actual averages the year lengths
+        assert_close(
+            yearfrac(
+                &[serial(2020, 6, 1), serial(2022, 6, 1), Value::Int(1)],
+                S1900,
+            ),
+            730.0 / (1096.0 / 3.0),
+        );
+        // Arguments may come in either order
+        assert_close(
+            yearfrac(&[serial(2012, 7, 30), serial(2012, 1, 1)], S1900),
+            0.580_555_555_555_556,
+        );
+    }
+
+    #[test]
+    fn test_networkdays_and_workday() {
+        let start = serial(2024, 1, 1);
+        let end = serial(2024, 1, 31);
+        let holidays = Value::Array(vec![
+            Value::Array(vec![serial(2024, 1, 1)]),
+            Value::Array(vec![serial(2024, 1, 15)]),
+            Value::Array(vec![serial(2024, 1, 20)]),
+            Value::Array(vec![Value::Empty]),
+        ]);
+        assert_eq!(
+            networkdays(&[start.clone(), end.clone()], S1900),
+            Value::Int(23)
+        );
+        assert_eq!(
+            networkdays(&[start.clone(), end.clone(), holidays.clone()], S1900),
+            Value::Int(21)
+        );
+        assert_eq!(
+            networkdays(&[end.clone(), start.clone()], S1900),
+            Value::Int(-23)
+        );
+        // Friday and Saturday weekends, and a Sunday-only weekend mask
+        assert_eq!(
+            networkdays_intl(&[start.clone(), end.clone(), Value::Int(7)], S1900),
+            Value::Int(23)
+        );
+        assert_eq!(
+            networkdays_intl(&[start.clone(), end.clone(), text("0000001")], S1900),
+            Value::Int(27)
+        );
+        assert_eq!(
+            networkdays_intl(&[start.clone(), end.clone(), text("0000002")], S1900),
+            Value::Error(ErrorValue::Value)
+        );
+
+        assert_eq!(
+            workday(&[serial(2024, 1, 12), Value::Int(1)], S1900),
+            serial(2024, 1, 15)
+        );
+        assert_eq!(
+            workday(
+                &[serial(2024, 1, 12), Value::Int(1), holidays.clone()],
+                S1900
+            ),
+            serial(2024, 1, 16)
+        );
+        assert_eq!(
+            workday(&[serial(2024, 1, 16), Value::Int(-1), holidays], S1900),
+            serial(2024, 1, 12)
+        );
+        assert_eq!(
+            workday_intl(&[serial(2024, 1, 12), Value::Int(1), Value::Int(11)], S1900),
+            serial(2024, 1, 13)
+        );
+        assert_eq!(
+            workday_intl(
+                &[serial(2024, 1, 12), Value::Int(1), text("1111111")],
+                S1900
+            ),
+            Value::Error(ErrorValue::Value)
+        );
+        assert_eq!(
+            workday(&[Value::Int(0), Value::Int(1)], S1904),
+            Value::Float(3.0)
+        );
+    }
+}
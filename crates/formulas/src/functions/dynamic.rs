@@ -0,0 +1,573 @@
+//! Dynamic array functions: SEQUENCE, RANDARRAY, TRANSPOSE, VSTACK, HSTACK,
+//! UNIQUE, FILTER, SORT and SORTBY.
+//!
+//! Results are 2D arrays (rows of row arrays) that spill into the cells
+//! below and to the right of the formula.
+
+use piptable_primitives::{ErrorValue, Value};
+
+use super::{coerce_to_bool, read_required_number, table_rows, to_index, to_number};
+
+/// Largest array a dynamic array function will build
+const MAX_ARRAY_CELLS: usize = 1 << 24;
+
+/// View a value as rows of cells; scalars are a 1x1 grid.
+pub(crate) fn to_grid(value: &Value) -> Vec<Vec<Value>> {
+    match value {
+        Value::Array(_) => table_rows(value).unwrap_or_default(),
+        other => vec![vec![other.clone()]],
+    }
+}
+
+/// Build a 2D array value from rows of cells.
+pub(crate) fn from_grid(rows: Vec<Vec<Value>>) -> Value {
+    Value::Array(rows.into_iter().map(Value::Array).collect())
+}
+
+fn transpose_grid(rows: &[Vec<Value>]) -> Vec<Vec<Value>> {
+    let cols = rows.iter().map(Vec::len).max().unwrap_or(0);
+    (0..cols)
+        .map(|c| {
+            rows.iter()
+                .map(|row| row.get(c).cloned().unwrap_or(Value::Error(ErrorValue::NA)))
+                .collect()
+        })
+        .collect()
+}
+
+fn read_optional_flag(value: Option<&Value>) -> Result<bool, ErrorValue> {
+    match value {
+        None => Ok(false),
+        Some(value) => coerce_to_bool(value),
+    }
+}
+
+/// Read an array dimension, which must be a positive whole number.
+pub(crate) fn read_dimension(value: Option<&Value>) -> Result<usize, ErrorValue> {
+    let n = match value {
+        None => 1.0,
+        Some(value) => read_required_number(Some(value))?.trunc(),
+    };
+    if n < 0.0 {
+        Err(ErrorValue::Value)
+    } else if n == 0.0 {
+        Err(ErrorValue::Calc)
+    } else if n > MAX_ARRAY_CELLS as f64 {
+        Err(ErrorValue::Num)
+    } else {
+        Ok(n as usize)
+    }
+}
+
+pub(crate) fn check_size(rows: usize, cols: usize) -> Result<(), ErrorValue> {
+    if rows.saturating_mul(cols) > MAX_ARRAY_CELLS {
+        Err(ErrorValue::Num)
+    } else {
+        Ok(())
+    }
+}
+
+fn first_error(rows: &[Vec<Value>]) -> Option<ErrorValue> {
+    rows.iter().flatten().find_map(|value| match value {
+        Value::Error(err) => Some(err.clone()),
+        _ => None,
+    })
+}
+
+/// Key used to compare cells for UNIQUE: text is case-insensitive and
+/// whole floats match integers.
+fn unique_key(value: &Value) -> String {
+    match value {
+        Value::Empty => String::new(),
+        Value::Int(n) => format!("n{}", *n as f64),
+        Value::Float(f) => format!("n{f}"),
+        Value::String(s) => format!("s{}", s.to_lowercase()),
+        Value::Bool(b) => format!("b{b}"),
+        Value::Error(err) => format!("e{}", err.label()),
+        Value::Array(_) => String::from("a"),
+    }
+}
+
+/// Excel sort order: numbers, text, logicals, errors, then blanks.
+fn sort_cmp(left: &Value, right: &Value) -> std::cmp::Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Int(_) | Value::Float(_) => 0,
+            Value::String(_) => 1,
+            Value::Bool(_) => 2,
+            Value::Error(_) | Value::Array(_) => 3,
+            Value::Empty => 4,
+        }
+    }
+    match (left, right) {
+        (Value::String(a), Value::String(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        _ => match (to_number(left), to_number(right)) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            _ => rank(left).cmp(&rank(right)),
+        },
+    }
+}
+
+fn read_sort_order(value: Option<&Value>) -> Result<bool, ErrorValue> {
+    let Some(value) = value else {
+        return Ok(true);
+    };
+    let order = read_required_number(Some(value))?;
+    if order == 1.0 {
+        Ok(true)
+    } else if order == -1.0 {
+        Ok(false)
+    } else {
+        Err(ErrorValue::Value)
+    }
+}
+
+fn sequence_grid(values: &[Value]) -> Result<Vec<Vec<Value>>, ErrorValue> {
+    let rows = read_dimension(values.first())?;
+    let cols = read_dimension(values.get(1))?;
+    check_size(rows, cols)?;
+    let start = match values.get(2) {
+        Some(value) => read_required_number(Some(value))?,
+        None => 1.0,
+    };
+    let step = match values.get(3) {
+        Some(value) => read_required_number(Some(value))?,
+        None => 1.0,
+    };
+    let whole = start.fract() == 0.0 && step.fract() == 0.0;
+    Ok((0..rows)
+        .map(|r| {
+            (0..cols)
+                .map(|c| {
+                    let n = start + (r * cols + c) as f64 * step;
+                    if whole {
+                        Value::Int(n as i64)
+                    } else {
+                        Value::Float(n)
+                    }
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// SEQUENCE function - returns a grid of sequential numbers
+pub fn sequence(values: &[Value]) -> Value {
+    sequence_grid(values).map_or_else(Value::Error, from_grid)
+}
+
+fn randarray_grid(values: &[Value]) -> Result<Vec<Vec<Value>>, ErrorValue> {
+    use rand::Rng;
+    let rows = read_dimension(values.first())?;
+    let cols = read_dimension(values.get(1))?;
+    check_size(rows, cols)?;
+    let min = match values.get(2) {
+        Some(value) => read_required_number(Some(value))?,
+        None => 0.0,
+    };
+    let max = match values.get(3) {
+        Some(value) => read_required_number(Some(value))?,
+        None => 1.0,
+    };
+    let whole = read_optional_flag(values.get(4))?;
+    if min > max || (whole && (min.fract() != 0.0 || max.fract() != 0.0)) {
+        return Err(ErrorValue::Value);
+    }
+    let mut rng = rand::thread_rng();
+    Ok((0..rows)
+        .map(|_| {
+            (0..cols)
+                .map(|_| {
+                    if whole {
+                        Value::Int(rng.gen_range(min as i64..=max as i64))
+                    } else if min == max {
+                        Value::Float(min)
+                    } else {
+                        Value::Float(rng.gen_range(min..max))
+                    }
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// RANDARRAY function - returns a grid of random numbers
+pub fn randarray(values: &[Value]) -> Value {
+    randarray_grid(values).map_or_else(Value::Error, from_grid)
+}
+
+/// TRANSPOSE function - swaps the rows and columns of an array
+pub fn transpose(values: &[Value]) -> Value {
+    let rows = to_grid(values.first().unwrap_or(&Value::Empty));
+    from_grid(transpose_grid(&rows))
+}
+
+/// VSTACK function - appends arrays vertically, padding with #N/A
+pub fn vstack(values: &[Value]) -> Value {
+    let grids: Vec<_> = values.iter().map(to_grid).collect();
+    let cols = grids.iter().flatten().map(Vec::len).max().unwrap_or(0);
+    let rows = grids
+        .into_iter()
+        .flatten()
+        .map(|mut row| {
+            row.resize(cols, Value::Error(ErrorValue::NA));
+            row
+        })
+        .collect();
+    from_grid(rows)
+}
+
+/// HSTACK function - appends arrays horizontally, padding with #N/A
+pub fn hstack(values: &[Value]) -> Value {
+    let grids: Vec<_> = values.iter().map(to_grid).collect();
+    let rows = grids.iter().map(Vec::len).max().unwrap_or(0);
+    let mut out = vec![Vec::new(); rows];
+    for grid in &grids {
+        let cols = grid.iter().map(Vec::len).max().unwrap_or(0);
+        for (r, out_row) in out.iter_mut().enumerate() {
+            for c in 0..cols {
+                let value = grid.get(r).and_then(|row| row.get(c)).cloned();
+                out_row.push(value.unwrap_or(Value::Error(ErrorValue::NA)));
+            }
+        }
+    }
+    from_grid(out)
+}
+
+fn unique_grid(values: &[Value]) -> Result<Vec<Vec<Value>>, ErrorValue> {
+    let by_col = read_optional_flag(values.get(1))?;
+    let exactly_once = read_optional_flag(values.get(2))?;
+    let mut items = to_grid(values.first().unwrap_or(&Value::Empty));
+    if by_col {
+        items = transpose_grid(&items);
+    }
+    let keys: Vec<Vec<String>> = items
+        .iter()
+        .map(|item| item.iter().map(unique_key).collect())
+        .collect();
+    let mut counts: std::collections::HashMap<&[String], usize> = std::collections::HashMap::new();
+    for key in &keys {
+        *counts.entry(key.as_slice()).or_default() += 1;
+    }
+    let mut seen = std::collections::HashSet::new();
+    let mut out = Vec::new();
+    for (item, key) in items.iter().zip(&keys) {
+        let keep = if exactly_once {
+            counts[key.as_slice()] == 1
+        } else {
+            seen.insert(key.as_slice())
+        };
+        if keep {
+            out.push(item.clone());
+        }
+    }
+    if out.is_empty() {
+        return Err(ErrorValue::Calc);
+    }
+    Ok(if by_col { transpose_grid(&out) } else { out })
+}
+
+/// UNIQUE function - returns the distinct rows (or columns) of an array
+pub fn unique(values: &[Value]) -> Value {
+    unique_grid(values).map_or_else(Value::Error, from_grid)
+}
+
+fn filter_grid(values: &[Value]) -> Result<Vec<Vec<Value>>, ErrorValue> {
+    let rows = to_grid(values.first().unwrap_or(&Value::Empty));
+    let include = to_grid(values.get(1).unwrap_or(&Value::Empty));
+    if let Some(err) = first_error(&include) {
+        return Err(err);
+    }
+    let height = rows.len();
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let flags = |cells: Vec<&Value>| -> Result<Vec<bool>, ErrorValue> {
+        cells.into_iter().map(coerce_to_bool).collect()
+    };
+    let out = if include.len() == height && include.iter().all(|row| row.len() == 1) {
+        let keep = flags(include.iter().map(|row| &row[0]).collect())?;
+        rows.into_iter()
+            .zip(keep)
+            .filter_map(|(row, keep)| keep.then_some(row))
+            .collect::<Vec<_>>()
+    } else if include.len() == 1 && include[0].len() == width {
+        let keep = flags(include[0].iter().collect())?;
+        let kept: Vec<_> = transpose_grid(&rows)
+            .into_iter()
+            .zip(keep)
+            .filter_map(|(col, keep)| keep.then_some(col))
+            .collect();
+        if kept.is_empty() {
+            Vec::new()
+        } else {
+            transpose_grid(&kept)
+        }
+    } else {
+        return Err(ErrorValue::Value);
+    };
+    if out.is_empty() {
+        return match values.get(2) {
+            Some(if_empty) => Ok(to_grid(if_empty)),
+            None => Err(ErrorValue::Calc),
+        };
+    }
+    Ok(out)
+}
+
+/// FILTER function - keeps the rows (or columns) of an array whose include
+/// value is true
+pub fn filter(values: &[Value]) -> Value {
+    filter_grid(values).map_or_else(Value::Error, from_grid)
+}
+
+/// Reorder the rows of `rows` by the given (keys, ascending) columns.
+fn sort_rows(rows: Vec<Vec<Value>>, keys: &[(Vec<Value>, bool)]) -> Vec<Vec<Value>> {
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    order.sort_by(|&a, &b| {
+        keys.iter()
+            .map(|(key, ascending)| {
+                let ord = sort_cmp(&key[a], &key[b]);
+                if *ascending {
+                    ord
+                } else {
+                    ord.reverse()
+                }
+            })
+            .find(|ord| ord.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let mut rows: Vec<Option<Vec<Value>>> = rows.into_iter().map(Some).collect();
+    order
+        .into_iter()
+        .filter_map(|idx| rows[idx].take())
+        .collect()
+}
+
+fn sort_grid(values: &[Value]) -> Result<Vec<Vec<Value>>, ErrorValue> {
+    let index = match values.get(1) {
+        Some(value) => to_index(value)?,
+        None => 1,
+    };
+    let ascending = read_sort_order(values.get(2))?;
+    let by_col = read_optional_flag(values.get(3))?;
+    let mut rows = to_grid(values.first().unwrap_or(&Value::Empty));
+    if by_col {
+        rows = transpose_grid(&rows);
+    }
+    let key = rows
+        .iter()
+        .map(|row| row.get(index - 1).cloned().ok_or(ErrorValue::Value))
+        .collect::<Result<Vec<_>, _>>()?;
+    let sorted = sort_rows(rows, &[(key, ascending)]);
+    Ok(if by_col {
+        transpose_grid(&sorted)
+    } else {
+        sorted
+    })
+}
+
+/// SORT function - sorts the rows (or columns) of an array by one index
+pub fn sort(values: &[Value]) -> Value {
+    sort_grid(values).map_or_else(Value::Error, from_grid)
+}
+
+fn sortby_grid(values: &[Value]) -> Result<Vec<Vec<Value>>, ErrorValue> {
+    let rows = to_grid(values.first().unwrap_or(&Value::Empty));
+    let height = rows.len();
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut keys = Vec::new();
+    let mut by_col = None;
+    let mut idx = 1;
+    while idx < values.len() {
+        let by = to_grid(&values[idx]);
+        let order = match values.get(idx + 1) {
+            Some(value) if !matches!(value, Value::Array(_)) => {
+                idx += 2;
+                Some(value)
+            }
+            _ => {
+                idx += 1;
+                None
+            }
+        };
+        let ascending = read_sort_order(order)?;
+        let (key, cols) = if by.len() == height && by.iter().all(|row| row.len() == 1) {
+            (by.into_iter().map(|mut row| row.remove(0)).collect(), false)
+        } else if by.len() == 1 && by[0].len() == width {
+            (by.into_iter().next().unwrap_or_default(), true)
+        } else {
+            return Err(ErrorValue::Value);
+        };
+        if *by_col.get_or_insert(cols) != cols {
+            return Err(ErrorValue::Value);
+        }
+        keys.push((key, ascending));
+    }
+    if by_col == Some(true) {
+        Ok(transpose_grid(&sort_rows(transpose_grid(&rows), &keys)))
+    } else {
+        Ok(sort_rows(rows, &keys))
+    }
+}
+
+/// SORTBY function - sorts an array by one or more other arrays
+pub fn sortby(values: &[Value]) -> Value {
+    sortby_grid(values).map_or_else(Value::Error, from_grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&[i64]]) -> Value {
+        from_grid(
+            rows.iter()
+                .map(|row| row.iter().map(|n| Value::Int(*n)).collect())
+                .collect(),
+        )
+    }
+
+    fn text_column(items: &[&str]) -> Value {
+        from_grid(
+            items
+                .iter()
+                .map(|s| vec![Value::String((*s).to_string())])
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_sequence_and_randarray() {
+        assert_eq!(
+            sequence(&[Value::Int(2), Value::Int(3)]),
+            grid(&[&[1, 2, 3], &[4, 5, 6]])
+        );
+        assert_eq!(
+            sequence(&[Value::Int(3), Value::Int(1), Value::Int(10), Value::Int(-5)]),
+            grid(&[&[10], &[5], &[0]])
+        );
+        assert_eq!(sequence(&[Value::Int(0)]), Value::Error(ErrorValue::Calc));
+
+        let rows = to_grid(&randarray(&[
+            Value::Int(4),
+            Value::Int(2),
+            Value::Int(1),
+            Value::Int(6),
+            Value::Bool(true),
+        ]));
+        assert_eq!(rows.len(), 4);
+        assert!(rows
+            .iter()
+            .flatten()
+            .all(|v| matches!(v, Value::Int(n) if (1..=6).contains(n))));
+        assert_eq!(
+            randarray(&[Value::Int(1), Value::Int(1), Value::Int(5), Value::Int(1)]),
+            Value::Error(ErrorValue::Value)
+        );
+    }
+
+    #[test]
+    fn test_transpose_and_stack() {
+        assert_eq!(
+            transpose(&[grid(&[&[1, 2, 3], &[4, 5, 6]])]),
+            grid(&[&[1, 4], &[2, 5], &[3, 6]])
+        );
+        assert_eq!(
+            vstack(&[grid(&[&[1, 2]]), grid(&[&[3]])]),
+            from_grid(vec![
+                vec![Value::Int(1), Value::Int(2)],
+                vec![Value::Int(3), Value::Error(ErrorValue::NA)],
+            ])
+        );
+        assert_eq!(
+            hstack(&[grid(&[&[1], &[2]]), Value::Int(3)]),
+            from_grid(vec![
+                vec![Value::Int(1), Value::Int(3)],
+                vec![Value::Int(2), Value::Error(ErrorValue::NA)],
+            ])
+        );
+    }
+
+    #[test]
+    fn test_unique() {
+        assert_eq!(
+            unique(&[text_column(&["a", "B", "A", "c", "b"])]),
+            text_column(&["a", "B", "c"])
+        );
+        assert_eq!(
+            unique(&[
+                text_column(&["a", "b", "a"]),
+                Value::Bool(false),
+                Value::Bool(true)
+            ]),
+            text_column(&["b"])
+        );
+        assert_eq!(
+            unique(&[grid(&[&[1, 2, 1]]), Value::Bool(true)]),
+            grid(&[&[1, 2]])
+        );
+    }
+
+    #[test]
+    fn test_filter() {
+        let data = grid(&[&[1, 10], &[2, 20], &[3, 30]]);
+        let include = from_grid(vec![
+            vec![Value::Bool(true)],
+            vec![Value::Bool(false)],
+            vec![Value::Bool(true)],
+        ]);
+        assert_eq!(
+            filter(&[data.clone(), include]),
+            grid(&[&[1, 10], &[3, 30]])
+        );
+
+        let none = from_grid(vec![vec![Value::Bool(false)]; 3]);
+        assert_eq!(
+            filter(&[data.clone(), none.clone()]),
+            Value::Error(ErrorValue::Calc)
+        );
+        assert_eq!(
+            filter(&[data.clone(), none, Value::String("none".to_string())]),
+            from_grid(vec![vec![Value::String("none".to_string())]])
+        );
+
+        let columns = from_grid(vec![vec![Value::Bool(false), Value::Bool(true)]]);
+        assert_eq!(
+            filter(&[data.clone(), columns]),
+            grid(&[&[10], &[20], &[30]])
+        );
+        assert_eq!(
+            filter(&[data, grid(&[&[1], &[0]])]),
+            Value::Error(ErrorValue::Value)
+        );
+    }
+
+    #[test]
+    fn test_sort_and_sortby() {
+        let data = grid(&[&[3, 1], &[1, 2], &[2, 3]]);
+        assert_eq!(
+            sort(std::slice::from_ref(&data)),
+            grid(&[&[1, 2], &[2, 3], &[3, 1]])
+        );
+        assert_eq!(
+            sort(&[data.clone(), Value::Int(2), Value::Int(-1)]),
+            grid(&[&[2, 3], &[1, 2], &[3, 1]])
+        );
+        assert_eq!(
+            sort(&[data.clone(), Value::Int(3)]),
+            Value::Error(ErrorValue::Value)
+        );
+        assert_eq!(
+            sort(&[text_column(&["b", "C", "a"])]),
+            text_column(&["a", "b", "C"])
+        );
+
+        let names = text_column(&["x", "y", "z"]);
+        let scores = grid(&[&[2], &[3], &[2]]);
+        let ages = grid(&[&[30], &[20], &[10]]);
+        assert_eq!(
+            sortby(&[names, scores, Value::Int(-1), ages]),
+            text_column(&["y", "z", "x"])
+        );
+    }
+}
@@ -0,0 +1,1128 @@
+//! Statistical functions: descriptive statistics, ranking, regression and
+//! the normal, Student's t and chi-squared distributions.
+//!
+//! Like SUM, these read numbers from their arguments and skip text, logical
+//! values and blanks inside arrays. Unlike SUM, an error anywhere in the data
+//! is returned, matching Excel.
+
+use piptable_primitives::{ErrorValue, Value};
+
+use super::{coerce_to_bool, from_grid, read_required_number, to_number, walk_values};
+
+/// Collect the numbers in `values`, failing on the first error.
+fn numbers(values: &[Value]) -> Result<Vec<f64>, ErrorValue> {
+    let mut nums = Vec::new();
+    let mut error = None;
+    walk_values(values, &mut |value| match value {
+        Value::Error(err) => {
+            error.get_or_insert_with(|| err.clone());
+        }
+        other => nums.extend(to_number(other)),
+    });
+    match error {
+        Some(err) => Err(err),
+        None => Ok(nums),
+    }
+}
+
+fn sorted_numbers(values: &[Value]) -> Result<Vec<f64>, ErrorValue> {
+    let mut nums = numbers(values)?;
+    nums.sort_by(f64::total_cmp);
+    Ok(nums)
+}
+
+/// Every cell of a value, row by row; scalars are a single cell.
+fn cells(value: &Value) -> Vec<Value> {
+    let mut out = Vec::new();
+    walk_values(std::slice::from_ref(value), &mut |cell| {
+        out.push(cell.clone())
+    });
+    out
+}
+
+/// Pair up the numbers at matching positions of two equally sized arrays,
+/// skipping positions where either side is not a number.
+fn paired_numbers(ys: &Value, xs: &Value) -> Result<(Vec<f64>, Vec<f64>), ErrorValue> {
+    let (ys, xs) = (cells(ys), cells(xs));
+    if ys.len() != xs.len() {
+        return Err(ErrorValue::NA);
+    }
+    let mut pairs = (Vec::new(), Vec::new());
+    for (y, x) in ys.iter().zip(&xs) {
+        if let Value::Error(err) = y {
+            return Err(err.clone());
+        }
+        if let Value::Error(err) = x {
+            return Err(err.clone());
+        }
+        if let (Some(y), Some(x)) = (to_number(y), to_number(x)) {
+            pairs.0.push(y);
+            pairs.1.push(x);
+        }
+    }
+    Ok(pairs)
+}
+
+fn mean(nums: &[f64]) -> f64 {
+    nums.iter().sum::<f64>() / nums.len() as f64
+}
+
+/// Sum of squared deviations from the mean.
+fn sum_sq_dev(nums: &[f64]) -> f64 {
+    let m = mean(nums);
+    nums.iter().map(|n| (n - m) * (n - m)).sum()
+}
+
+/// Sum of the products of paired deviations from each mean.
+fn sum_co_dev(ys: &[f64], xs: &[f64]) -> f64 {
+    let (my, mx) = (mean(ys), mean(xs));
+    ys.iter().zip(xs).map(|(y, x)| (y - my) * (x - mx)).sum()
+}
+
+fn number_result(result: Result<f64, ErrorValue>) -> Value {
+    match result {
+        Ok(n) if n.is_finite() => Value::Float(n),
+        Ok(_) => Value::Error(ErrorValue::Num),
+        Err(err) => Value::Error(err),
+    }
+}
+
+/// A whole-number argument such as degrees of freedom, truncated.
+fn read_whole(value: Option<&Value>) -> Result<f64, ErrorValue> {
+    read_required_number(value).map(f64::trunc)
+}
+
+/// A probability strictly between 0 and 1.
+fn read_probability(value: Option<&Value>) -> Result<f64, ErrorValue> {
+    let p = read_required_number(value)?;
+    if p <= 0.0 || p >= 1.0 {
+        Err(ErrorValue::Num)
+    } else {
+        Ok(p)
+    }
+}
+
+fn read_cumulative(value: Option<&Value>) -> Result<bool, ErrorValue> {
+    coerce_to_bool(value.unwrap_or(&Value::Empty))
+}
+
+// Descriptive statistics
+
+/// MEDIAN(number1, [number2], ...)
+pub fn median(values: &[Value]) -> Value {
+    number_result(sorted_numbers(values).and_then(|nums| {
+        let n = nums.len();
+        match n {
+            0 => Err(ErrorValue::Num),
+            _ if n % 2 == 1 => Ok(nums[n / 2]),
+            _ => Ok((nums[n / 2 - 1] + nums[n / 2]) / 2.0),
+        }
+    }))
+}
+
+/// The values that occur most often, in order of first appearance.
+fn modes(values: &[Value]) -> Result<Vec<f64>, ErrorValue> {
+    let nums = numbers(values)?;
+    let mut counts: Vec<(f64, usize)> = Vec::new();
+    for n in nums {
+        match counts.iter_mut().find(|(value, _)| *value == n) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((n, 1)),
+        }
+    }
+    let best = counts.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    if best < 2 {
+        return Err(ErrorValue::NA);
+    }
+    Ok(counts
+        .into_iter()
+        .filter(|(_, count)| *count == best)
+        .map(|(value, _)| value)
+        .collect())
+}
+
+/// MODE.SNGL(number1, [number2], ...)
+pub fn mode_sngl(values: &[Value]) -> Value {
+    number_result(modes(values).map(|modes| modes[0]))
+}
+
+/// MODE.MULT(number1, [number2], ...) - a vertical array of every mode
+pub fn mode_mult(values: &[Value]) -> Value {
+    match modes(values) {
+        Ok(modes) => from_grid(modes.into_iter().map(|m| vec![Value::Float(m)]).collect()),
+        Err(err) => Value::Error(err),
+    }
+}
+
+fn variance(values: &[Value], sample: bool) -> Result<f64, ErrorValue> {
+    let nums = numbers(values)?;
+    let n = nums.len().saturating_sub(usize::from(sample));
+    if n == 0 {
+        return Err(ErrorValue::Div0);
+    }
+    Ok(sum_sq_dev(&nums) / n as f64)
+}
+
+/// VAR.S(number1, [number2], ...) - sample variance
+pub fn var_s(values: &[Value]) -> Value {
+    number_result(variance(values, true))
+}
+
+/// VAR.P(number1, [number2], ...) - population variance
+pub fn var_p(values: &[Value]) -> Value {
+    number_result(variance(values, false))
+}
+
+/// STDEV.S(number1, [number2], ...) - sample standard deviation
+pub fn stdev_s(values: &[Value]) -> Value {
+    number_result(variance(values, true).map(f64::sqrt))
+}
+
+/// STDEV.P(number1, [number2], ...) - population standard deviation
+pub fn stdev_p(values: &[Value]) -> Value {
+    number_result(variance(values, false).map(f64::sqrt))
+}
+
+/// COUNTA(value1, [value2], ...) - counts values that are not blank
+pub fn counta(values: &[Value]) -> Value {
+    let mut count = 0i64;
+    walk_values(values, &mut |value| {
+        if !matches!(value, Value::Empty) {
+            count += 1;
+        }
+    });
+    Value::Int(count)
+}
+
+/// COUNTBLANK(range) - counts blank cells and empty text
+pub fn countblank(values: &[Value]) -> Value {
+    let mut count = 0i64;
+    walk_values(values, &mut |value| {
+        if matches!(value, Value::Empty) || matches!(value, Value::String(s) if s.is_empty()) {
+            count += 1;
+        }
+    });
+    Value::Int(count)
+}
+
+// Percentiles and ranking
+
+/// Interpolate at a zero-based fractional `rank` into sorted numbers.
+fn interpolate(nums: &[f64], rank: f64) -> f64 {
+    let lower = rank.floor() as usize;
+    let frac = rank - rank.floor();
+    match nums.get(lower + 1) {
+        Some(upper) if frac > 0.0 => nums[lower] + frac * (upper - nums[lower]),
+        _ => nums[lower],
+    }
+}
+
+fn percentile_inc_of(nums: &[f64], k: f64) -> Result<f64, ErrorValue> {
+    if nums.is_empty() || !(0.0..=1.0).contains(&k) {
+        return Err(ErrorValue::Num);
+    }
+    Ok(interpolate(nums, k * (nums.len() - 1) as f64))
+}
+
+fn percentile_exc_of(nums: &[f64], k: f64) -> Result<f64, ErrorValue> {
+    let rank = k * (nums.len() + 1) as f64;
+    if nums.is_empty() || k <= 0.0 || k >= 1.0 || rank < 1.0 || rank > nums.len() as f64 {
+        return Err(ErrorValue::Num);
+    }
+    Ok(interpolate(nums, rank - 1.0))
+}
+
+/// PERCENTILE.INC(array, k) - k from 0 to 1 inclusive
+pub fn percentile_inc(values: &[Value]) -> Value {
+    number_result(sorted_numbers(&values[..1]).and_then(|nums| {
+        let k = read_required_number(values.get(1))?;
+        percentile_inc_of(&nums, k)
+    }))
+}
+
+/// PERCENTILE.EXC(array, k) - k strictly between 0 and 1
+pub fn percentile_exc(values: &[Value]) -> Value {
+    number_result(sorted_numbers(&values[..1]).and_then(|nums| {
+        let k = read_required_number(values.get(1))?;
+        percentile_exc_of(&nums, k)
+    }))
+}
+
+/// QUARTILE.INC(array, quart) - quart from 0 (minimum) to 4 (maximum)
+pub fn quartile_inc(values: &[Value]) -> Value {
+    number_result(sorted_numbers(&values[..1]).and_then(|nums| {
+        let quart = read_whole(values.get(1))?;
+        if !(0.0..=4.0).contains(&quart) {
+            return Err(ErrorValue::Num);
+        }
+        percentile_inc_of(&nums, quart / 4.0)
+    }))
+}
+
+/// QUARTILE.EXC(array, quart) - quart from 1 to 3
+pub fn quartile_exc(values: &[Value]) -> Value {
+    number_result(sorted_numbers(&values[..1]).and_then(|nums| {
+        let quart = read_whole(values.get(1))?;
+        if !(1.0..=3.0).contains(&quart) {
+            return Err(ErrorValue::Num);
+        }
+        percentile_exc_of(&nums, quart / 4.0)
+    }))
+}
+
+/// The one-based positions `number` takes in `values[1]` sorted by the
+/// order in `values[2]` (0 or omitted for descending), as (first, ties).
+fn rank_of(values: &[Value]) -> Result<(usize, usize), ErrorValue> {
+    let number = read_required_number(values.first())?;
+    let nums = numbers(&values[1..2])?;
+    let ascending = match values.get(2) {
+        Some(order) => read_required_number(Some(order))? != 0.0,
+        None => false,
+    };
+    let ties = nums.iter().filter(|n| **n == number).count();
+    if ties == 0 {
+        return Err(ErrorValue::NA);
+    }
+    let ahead = nums
+        .iter()
+        .filter(|n| {
+            if ascending {
+                **n < number
+            } else {
+                **n > number
+            }
+        })
+        .count();
+    Ok((ahead + 1, ties))
+}
+
+/// RANK.EQ(number, ref, [order]) - the top rank among ties
+pub fn rank_eq(values: &[Value]) -> Value {
+    match rank_of(values) {
+        Ok((first, _)) => Value::Int(first as i64),
+        Err(err) => Value::Error(err),
+    }
+}
+
+/// RANK.AVG(number, ref, [order]) - the average rank among ties
+pub fn rank_avg(values: &[Value]) -> Value {
+    number_result(rank_of(values).map(|(first, ties)| first as f64 + (ties - 1) as f64 / 2.0))
+}
+
+/// The k-th number (one-based, fractions rounded up) in sorted order.
+fn kth(values: &[Value], largest: bool) -> Result<f64, ErrorValue> {
+    let nums = sorted_numbers(&values[..1])?;
+    let k = read_required_number(values.get(1))?.ceil();
+    if k < 1.0 || k > nums.len() as f64 {
+        return Err(ErrorValue::Num);
+    }
+    let k = k as usize;
+    Ok(if largest {
+        nums[nums.len() - k]
+    } else {
+        nums[k - 1]
+    })
+}
+
+/// LARGE(array, k)
+pub fn large(values: &[Value]) -> Value {
+    number_result(kth(values, true))
+}
+
+/// SMALL(array, k)
+pub fn small(values: &[Value]) -> Value {
+    number_result(kth(values, false))
+}
+
+// Correlation and regression
+
+fn covariance(values: &[Value], sample: bool) -> Result<f64, ErrorValue> {
+    let (ys, xs) = paired_numbers(&values[0], &values[1])?;
+    let n = ys.len().saturating_sub(usize::from(sample));
+    if n == 0 {
+        return Err(ErrorValue::Div0);
+    }
+    Ok(sum_co_dev(&ys, &xs) / n as f64)
+}
+
+/// COVARIANCE.P(array1, array2)
+pub fn covariance_p(values: &[Value]) -> Value {
+    number_result(covariance(values, false))
+}
+
+/// COVARIANCE.S(array1, array2)
+pub fn covariance_s(values: &[Value]) -> Value {
+    number_result(covariance(values, true))
+}
+
+fn correlation(ys: &Value, xs: &Value) -> Result<f64, ErrorValue> {
+    let (ys, xs) = paired_numbers(ys, xs)?;
+    if ys.is_empty() {
+        return Err(ErrorValue::Div0);
+    }
+    let denom = (sum_sq_dev(&ys) * sum_sq_dev(&xs)).sqrt();
+    if denom == 0.0 {
+        return Err(ErrorValue::Div0);
+    }
+    Ok(sum_co_dev(&ys, &xs) / denom)
+}
+
+/// CORREL(array1, array2)
+pub fn correl(values: &[Value]) -> Value {
+    number_result(correlation(&values[0], &values[1]))
+}
+
+/// RSQ(known_ys, known_xs) - the square of the correlation
+pub fn rsq(values: &[Value]) -> Value {
+    number_result(correlation(&values[0], &values[1]).map(|r| r * r))
+}
+
+/// Least-squares line through the pairs, as (slope, intercept).
+fn regression(ys: &Value, xs: &Value) -> Result<(f64, f64), ErrorValue> {
+    let (ys, xs) = paired_numbers(ys, xs)?;
+    if ys.is_empty() {
+        return Err(ErrorValue::Div0);
+    }
+    let sxx = sum_sq_dev(&xs);
+    if sxx == 0.0 {
+        return Err(ErrorValue::Div0);
+    }
+    let slope = sum_co_dev(&ys, &xs) / sxx;
+    Ok((slope, mean(&ys) - slope * mean(&xs)))
+}
+
+/// SLOPE(known_ys, known_xs)
+pub fn slope(values: &[Value]) -> Value {
+    number_result(regression(&values[0], &values[1]).map(|(slope, _)| slope))
+}
+
+/// INTERCEPT(known_ys, known_xs)
+pub fn intercept(values: &[Value]) -> Value {
+    number_result(regression(&values[0], &values[1]).map(|(_, intercept)| intercept))
+}
+
+/// FORECAST.LINEAR(x, known_ys, known_xs)
+pub fn forecast_linear(values: &[Value]) -> Value {
+    number_result(read_required_number(values.first()).and_then(|x| {
+        let (slope, intercept) = regression(&values[1], &values[2])?;
+        Ok(intercept + slope * x)
+    }))
+}
+
+// Special functions behind the distributions
+
+const EPS: f64 = 1e-15;
+const TINY: f64 = 1e-300;
+const MAX_ITERATIONS: usize = 1000;
+
+/// Regularized lower incomplete gamma function P(a, x).
+fn gamma_p(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        0.0
+    } else if x < a + 1.0 {
+        gamma_series(a, x)
+    } else {
+        1.0 - gamma_continued_fraction(a, x)
+    }
+}
+
+/// Regularized upper incomplete gamma function Q(a, x) = 1 - P(a, x).
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        1.0
+    } else if x < a + 1.0 {
+        1.0 - gamma_series(a, x)
+    } else {
+        gamma_continued_fraction(a, x)
+    }
+}
+
+fn gamma_prefix(a: f64, x: f64) -> f64 {
+    (a * x.ln() - x - libm::lgamma(a)).exp()
+}
+
+fn gamma_series(a: f64, x: f64) -> f64 {
+    let mut term = 1.0 / a;
+    let mut sum = term;
+    let mut ap = a;
+    for _ in 0..MAX_ITERATIONS {
+        ap += 1.0;
+        term *= x / ap;
+        sum += term;
+        if term.abs() < sum.abs() * EPS {
+            break;
+        }
+    }
+    sum * gamma_prefix(a, x)
+}
+
+/// Q(a, x) by Lentz's continued fraction, for x >= a + 1.
+fn gamma_continued_fraction(a: f64, x: f64) -> f64 {
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / TINY;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for i in 1..MAX_ITERATIONS {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = b + an / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPS {
+            break;
+        }
+    }
+    h * gamma_prefix(a, x)
+}
+
+/// Regularized incomplete beta function I_x(a, b).
+fn beta_inc(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (libm::lgamma(a + b) - libm::lgamma(a) - libm::lgamma(b) + a * x.ln() + b * (1.0 - x).ln())
+            .exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    let (qab, qap, qam) = (a + b, a + 1.0, a - 1.0);
+    let mut c = 1.0;
+    let mut d = 1.0 - qab * x / qap;
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+    for m in 1..MAX_ITERATIONS {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        h *= d * c;
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPS {
+            break;
+        }
+    }
+    h
+}
+
+/// Solve `f(x) = target` for an increasing `f`, widening `[lo, hi]` upward
+/// until it brackets the root.
+fn solve_increasing(f: impl Fn(f64) -> f64, target: f64, mut lo: f64, mut hi: f64) -> f64 {
+    while f(hi) < target && hi < 1e300 {
+        lo = hi;
+        hi *= 2.0;
+    }
+    for _ in 0..MAX_ITERATIONS {
+        let mid = lo + (hi - lo) / 2.0;
+        if mid <= lo || mid >= hi {
+            break;
+        }
+        if f(mid) < target {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    lo + (hi - lo) / 2.0
+}
+
+// Normal distribution
+
+fn std_normal_cdf(z: f64) -> f64 {
+    0.5 * libm::erfc(-z / std::f64::consts::SQRT_2)
+}
+
+fn std_normal_pdf(z: f64) -> f64 {
+    (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Inverse of the standard normal CDF: Acklam's rational approximation,
+/// refined with one Halley step to full double precision.
+fn std_normal_inv(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    let x = if p < LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    };
+    let e = std_normal_cdf(x) - p;
+    let u = e * (2.0 * std::f64::consts::PI).sqrt() * (x * x / 2.0).exp();
+    x - u / (1.0 + x * u / 2.0)
+}
+
+/// NORM.DIST(x, mean, standard_dev, cumulative)
+pub fn norm_dist(values: &[Value]) -> Value {
+    number_result((|| {
+        let x = read_required_number(values.first())?;
+        let mean = read_required_number(values.get(1))?;
+        let sd = read_required_number(values.get(2))?;
+        let cumulative = read_cumulative(values.get(3))?;
+        if sd <= 0.0 {
+            return Err(ErrorValue::Num);
+        }
+        let z = (x - mean) / sd;
+        Ok(if cumulative {
+            std_normal_cdf(z)
+        } else {
+            std_normal_pdf(z) / sd
+        })
+    })())
+}
+
+/// NORM.INV(probability, mean, standard_dev)
+pub fn norm_inv(values: &[Value]) -> Value {
+    number_result((|| {
+        let p = read_probability(values.first())?;
+        let mean = read_required_number(values.get(1))?;
+        let sd = read_required_number(values.get(2))?;
+        if sd <= 0.0 {
+            return Err(ErrorValue::Num);
+        }
+        Ok(mean + sd * std_normal_inv(p))
+    })())
+}
+
+/// NORM.S.DIST(z, cumulative)
+pub fn norm_s_dist(values: &[Value]) -> Value {
+    number_result((|| {
+        let z = read_required_number(values.first())?;
+        Ok(if read_cumulative(values.get(1))? {
+            std_normal_cdf(z)
+        } else {
+            std_normal_pdf(z)
+        })
+    })())
+}
+
+/// NORM.S.INV(probability)
+pub fn norm_s_inv(values: &[Value]) -> Value {
+    number_result(read_probability(values.first()).map(std_normal_inv))
+}
+
+// Student's t distribution
+
+/// Degrees of freedom, truncated, at least 1.
+fn read_degrees(value: Option<&Value>) -> Result<f64, ErrorValue> {
+    let df = read_whole(value)?;
+    if !(1.0..=1e10).contains(&df) {
+        Err(ErrorValue::Num)
+    } else {
+        Ok(df)
+    }
+}
+
+/// P(T > |t|) for one tail.
+fn t_tail(t: f64, df: f64) -> f64 {
+    0.5 * beta_inc(df / 2.0, 0.5, df / (df + t * t))
+}
+
+fn t_cdf(t: f64, df: f64) -> f64 {
+    if t > 0.0 {
+        1.0 - t_tail(t, df)
+    } else {
+        t_tail(t, df)
+    }
+}
+
+fn t_pdf(t: f64, df: f64) -> f64 {
+    let log_norm = libm::lgamma((df + 1.0) / 2.0)
+        - libm::lgamma(df / 2.0)
+        - 0.5 * (df * std::f64::consts::PI).ln();
+    (log_norm - (df + 1.0) / 2.0 * (1.0 + t * t / df).ln()).exp()
+}
+
+/// The t with P(T > t) = `p`, for p in (0, 1).
+fn t_inv_upper(p: f64, df: f64) -> f64 {
+    if p > 0.5 {
+        return -t_inv_upper(1.0 - p, df);
+    }
+    // Solve on -t so the tail probability grows with the unknown
+    solve_increasing(|t| -t_tail(t, df), -p, 0.0, 1.0)
+}
+
+/// T.DIST(x, deg_freedom, cumulative) - left-tailed
+pub fn t_dist(values: &[Value]) -> Value {
+    number_result((|| {
+        let x = read_required_number(values.first())?;
+        let df = read_degrees(values.get(1))?;
+        Ok(if read_cumulative(values.get(2))? {
+            t_cdf(x, df)
+        } else {
+            t_pdf(x, df)
+        })
+    })())
+}
+
+/// T.DIST.2T(x, deg_freedom) - two-tailed, x >= 0
+pub fn t_dist_2t(values: &[Value]) -> Value {
+    number_result((|| {
+        let x = read_required_number(values.first())?;
+        let df = read_degrees(values.get(1))?;
+        if x < 0.0 {
+            return Err(ErrorValue::Num);
+        }
+        Ok(2.0 * t_tail(x, df))
+    })())
+}
+
+/// T.DIST.RT(x, deg_freedom) - right-tailed
+pub fn t_dist_rt(values: &[Value]) -> Value {
+    number_result((|| {
+        let x = read_required_number(values.first())?;
+        let df = read_degrees(values.get(1))?;
+        Ok(1.0 - t_cdf(x, df))
+    })())
+}
+
+/// T.INV(probability, deg_freedom) - left-tailed inverse
+pub fn t_inv(values: &[Value]) -> Value {
+    number_result((|| {
+        let p = read_probability(values.first())?;
+        let df = read_degrees(values.get(1))?;
+        Ok(t_inv_upper(1.0 - p, df))
+    })())
+}
+
+/// T.INV.2T(probability, deg_freedom) - two-tailed inverse
+pub fn t_inv_2t(values: &[Value]) -> Value {
+    number_result((|| {
+        let p = read_required_number(values.first())?;
+        let df = read_degrees(values.get(1))?;
+        if p <= 0.0 || p > 1.0 {
+            return Err(ErrorValue::Num);
+        }
+        Ok(if p == 1.0 {
+            0.0
+        } else {
+            t_inv_upper(p / 2.0, df)
+        })
+    })())
+}
+
+// Chi-squared distribution
+
+fn chisq_pdf(x: f64, df: f64) -> Result<f64, ErrorValue> {
+    if x == 0.0 {
+        return match df {
+            _ if df < 2.0 => Err(ErrorValue::Num),
+            _ if df == 2.0 => Ok(0.5),
+            _ => Ok(0.0),
+        };
+    }
+    let k = df / 2.0;
+    Ok(((k - 1.0) * x.ln() - x / 2.0 - k * std::f64::consts::LN_2 - libm::lgamma(k)).exp())
+}
+
+fn read_chisq_args(values: &[Value]) -> Result<(f64, f64), ErrorValue> {
+    let x = read_required_number(values.first())?;
+    let df = read_degrees(values.get(1))?;
+    if x < 0.0 {
+        return Err(ErrorValue::Num);
+    }
+    Ok((x, df))
+}
+
+/// CHISQ.DIST(x, deg_freedom, cumulative) - left-tailed
+pub fn chisq_dist(values: &[Value]) -> Value {
+    number_result(read_chisq_args(values).and_then(|(x, df)| {
+        if read_cumulative(values.get(2))? {
+            Ok(gamma_p(df / 2.0, x / 2.0))
+        } else {
+            chisq_pdf(x, df)
+        }
+    }))
+}
+
+/// CHISQ.DIST.RT(x, deg_freedom) - right-tailed
+pub fn chisq_dist_rt(values: &[Value]) -> Value {
+    number_result(read_chisq_args(values).map(|(x, df)| gamma_q(df / 2.0, x / 2.0)))
+}
+
+fn read_chisq_inv_args(values: &[Value]) -> Result<(f64, f64), ErrorValue> {
+    let p = read_required_number(values.first())?;
+    let df = read_degrees(values.get(1))?;
+    if !(0.0..=1.0).contains(&p) {
+        return Err(ErrorValue::Num);
+    }
+    Ok((p, df))
+}
+
+/// CHISQ.INV(probability, deg_freedom) - left-tailed inverse
+pub fn chisq_inv(values: &[Value]) -> Value {
+    number_result(read_chisq_inv_args(values).and_then(|(p, df)| match p {
+        _ if p == 0.0 => Ok(0.0),
+        _ if p == 1.0 => Err(ErrorValue::Num),
+        _ => Ok(solve_increasing(|x| gamma_p(df / 2.0, x / 2.0), p, 0.0, df)),
+    }))
+}
+
+/// CHISQ.INV.RT(probability, deg_freedom) - right-tailed inverse
+pub fn chisq_inv_rt(values: &[Value]) -> Value {
+    number_result(read_chisq_inv_args(values).and_then(|(p, df)| match p {
+        _ if p == 0.0 => Err(ErrorValue::Num),
+        _ if p == 1.0 => Ok(0.0),
+        _ => Ok(solve_increasing(
+            |x| -gamma_q(df / 2.0, x / 2.0),
+            -p,
+            0.0,
+            df,
+        )),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nums(values: &[f64]) -> Value {
+        Value::Array(values.iter().map(|n| Value::Float(*n)).collect())
+    }
+
+    fn assert_close(value: Value, expected: f64, tolerance: f64) {
+        match value {
+            Value::Float(f) => assert!(
+                (f - expected).abs() <= tolerance,
+                "expected {expected}, got {f}"
+            ),
+            other => panic!("expected {expected}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_descriptive_statistics() {
+        let data = [nums(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0])];
+        assert_close(median(&data), 4.5, 1e-12);
+        assert_close(
+            median(&[Value::Int(3), Value::Int(1), Value::Int(2)]),
+            2.0,
+            1e-12,
+        );
+        assert_close(stdev_s(&data), 2.138_089_935_299_395, 1e-12);
+        assert_close(stdev_p(&data), 2.0, 1e-12);
+        assert_close(var_s(&data), 4.571_428_571_428_571, 1e-12);
+        assert_close(var_p(&data), 4.0, 1e-12);
+        assert_eq!(stdev_s(&[Value::Int(1)]), Value::Error(ErrorValue::Div0));
+        assert_eq!(
+            median(&[Value::String("a".to_string())]),
+            Value::Error(ErrorValue::Num)
+        );
+
+        // Text and blanks in arrays are skipped, errors are returned
+        let mixed = Value::Array(vec![
+            Value::Int(1),
+            Value::String("x".to_string()),
+            Value::Empty,
+            Value::Int(3),
+        ]);
+        assert_close(median(std::slice::from_ref(&mixed)), 2.0, 1e-12);
+        assert_eq!(counta(std::slice::from_ref(&mixed)), Value::Int(3));
+        let with_error = Value::Array(vec![Value::Int(1), Value::Error(ErrorValue::NA)]);
+        assert_eq!(var_p(&[with_error]), Value::Error(ErrorValue::NA));
+
+        let blanks = Value::Array(vec![
+            Value::Empty,
+            Value::String("".to_string()),
+            Value::Int(0),
+        ]);
+        assert_eq!(countblank(&[blanks]), Value::Int(2));
+    }
+
+    #[test]
+    fn test_modes() {
+        let data = [nums(&[1.0, 3.0, 2.0, 2.0, 3.0])];
+        assert_close(mode_sngl(&data), 3.0, 0.0);
+        assert_eq!(
+            mode_mult(&data),
+            from_grid(vec![vec![Value::Float(3.0)], vec![Value::Float(2.0)]])
+        );
+        assert_eq!(
+            mode_sngl(&[nums(&[1.0, 2.0])]),
+            Value::Error(ErrorValue::NA)
+        );
+    }
+
+    #[test]
+    fn test_percentiles_and_ranks() {
+        let data = nums(&[1.0, 2.0, 3.0, 4.0]);
+        assert_close(
+            percentile_inc(&[data.clone(), Value::Float(0.3)]),
+            1.9,
+            1e-12,
+        );
+        assert_close(
+            percentile_exc(&[data.clone(), Value::Float(0.3)]),
+            1.5,
+            1e-12,
+        );
+        assert_eq!(
+            percentile_exc(&[data.clone(), Value::Float(0.1)]),
+            Value::Error(ErrorValue::Num)
+        );
+        assert_eq!(
+            percentile_inc(&[data, Value::Float(1.5)]),
+            Value::Error(ErrorValue::Num)
+        );
+
+        let quartiles = nums(&[1.0, 2.0, 4.0, 7.0, 8.0, 9.0, 10.0, 12.0]);
+        assert_close(
+            quartile_inc(&[quartiles.clone(), Value::Int(1)]),
+            3.5,
+            1e-12,
+        );
+        assert_close(
+            quartile_exc(&[quartiles.clone(), Value::Int(1)]),
+            2.5,
+            1e-12,
+        );
+        assert_close(quartile_inc(&[quartiles, Value::Int(4)]), 12.0, 1e-12);
+
+        let ranked = nums(&[7.0, 3.5, 3.5, 1.0, 2.0]);
+        assert_eq!(rank_eq(&[Value::Float(3.5), ranked.clone()]), Value::Int(2));
+        assert_close(rank_avg(&[Value::Float(3.5), ranked.clone()]), 2.5, 0.0);
+        assert_eq!(
+            rank_eq(&[Value::Int(7), ranked.clone(), Value::Int(1)]),
+            Value::Int(5)
+        );
+        assert_eq!(
+            rank_eq(&[Value::Int(4), ranked]),
+            Value::Error(ErrorValue::NA)
+        );
+
+        let data = nums(&[3.0, 4.0, 5.0, 2.0, 3.0, 4.0, 5.0, 6.0, 4.0, 7.0]);
+        assert_close(large(&[data.clone(), Value::Int(3)]), 5.0, 0.0);
+        assert_close(small(&[data.clone(), Value::Int(4)]), 4.0, 0.0);
+        assert_eq!(
+            small(&[data, Value::Int(11)]),
+            Value::Error(ErrorValue::Num)
+        );
+    }
+
+    #[test]
+    fn test_correlation_and_regression() {
+        let a = nums(&[3.0, 2.0, 4.0, 5.0, 6.0]);
+        let b = nums(&[9.0, 7.0, 12.0, 15.0, 17.0]);
+        assert_close(
+            correl(&[a.clone(), b.clone()]),
+            0.997_054_485_501_582,
+            1e-12,
+        );
+        assert_close(covariance_p(&[a.clone(), b.clone()]), 5.2, 1e-12);
+        assert_close(covariance_s(&[a, b]), 6.5, 1e-12);
+
+        let ys = nums(&[2.0, 3.0, 9.0, 1.0, 8.0, 7.0, 5.0]);
+        let xs = nums(&[6.0, 5.0, 11.0, 7.0, 5.0, 4.0, 4.0]);
+        assert_close(
+            slope(&[ys.clone(), xs.clone()]),
+            0.305_555_555_555_556,
+            1e-12,
+        );
+        assert_close(rsq(&[ys.clone(), xs.clone()]), 0.057_950_191_570_881, 1e-12);
+        assert_close(intercept(&[ys, xs]), 3.166_666_666_666_667, 1e-12);
+
+        let known_y = nums(&[6.0, 7.0, 9.0, 15.0, 21.0]);
+        let known_x = nums(&[20.0, 28.0, 31.0, 38.0, 40.0]);
+        assert_close(
+            forecast_linear(&[Value::Int(30), known_y, known_x]),
+            10.607_253_086_419_8,
+            1e-9,
+        );
+
+        let short = nums(&[1.0, 2.0]);
+        assert_eq!(correl(&[short, nums(&[1.0])]), Value::Error(ErrorValue::NA));
+        assert_eq!(
+            slope(&[nums(&[1.0, 2.0]), nums(&[3.0, 3.0])]),
+            Value::Error(ErrorValue::Div0)
+        );
+    }
+
+    #[test]
+    fn test_normal_distribution() {
+        let args = |x: f64, cumulative: bool| {
+            [
+                Value::Float(x),
+                Value::Int(40),
+                Value::Float(1.5),
+                Value::Bool(cumulative),
+            ]
+        };
+        assert_close(norm_dist(&args(42.0, true)), 0.908_788_780_274_132, 1e-12);
+        assert_close(norm_dist(&args(42.0, false)), 0.109_340_049_783_996, 1e-12);
+        assert_close(
+            norm_inv(&[Value::Float(0.908_789), Value::Int(40), Value::Float(1.5)]),
+            42.000_002_009_566,
+            1e-8,
+        );
+        assert_close(
+            norm_s_dist(&[Value::Float(1.333_333), Value::Bool(true)]),
+            0.908_788_725_604_095,
+            1e-12,
+        );
+        assert_close(
+            norm_s_inv(&[Value::Float(0.908_789)]),
+            1.333_334_673_044_107,
+            1e-9,
+        );
+        assert_close(
+            norm_s_inv(&[Value::Float(1e-10)]),
+            -6.361_340_902_404_06,
+            1e-9,
+        );
+        assert_eq!(norm_s_inv(&[Value::Int(1)]), Value::Error(ErrorValue::Num));
+        assert_eq!(
+            norm_dist(&[
+                Value::Int(1),
+                Value::Int(0),
+                Value::Int(0),
+                Value::Bool(true)
+            ]),
+            Value::Error(ErrorValue::Num)
+        );
+    }
+
+    #[test]
+    fn test_t_distribution() {
+        let dist = |x: f64, df: i64, cumulative: bool| {
+            t_dist(&[Value::Float(x), Value::Int(df), Value::Bool(cumulative)])
+        };
+        assert_close(dist(60.0, 1, true), 0.994_695_326_367_17, 1e-12);
+        assert_close(dist(8.0, 3, false), 0.000_736_906_520_947, 1e-12);
+        assert_close(
+            t_dist_2t(&[Value::Float(1.959_999_998), Value::Int(60)]),
+            0.054_644_929_975_4,
+            1e-10,
+        );
+        assert_close(
+            t_dist_rt(&[Value::Float(1.959_999_998), Value::Int(60)]),
+            0.027_322_464_987_7,
+            1e-10,
+        );
+        assert_close(
+            t_inv(&[Value::Float(0.75), Value::Int(2)]),
+            0.816_496_580_927_726,
+            1e-10,
+        );
+        assert_close(
+            t_inv_2t(&[Value::Float(0.546_449), Value::Int(60)]),
+            0.606_533_075_825_3,
+            1e-9,
+        );
+        assert_eq!(
+            t_dist_2t(&[Value::Int(-1), Value::Int(2)]),
+            Value::Error(ErrorValue::Num)
+        );
+        assert_eq!(
+            t_inv(&[Value::Float(0.5), Value::Int(0)]),
+            Value::Error(ErrorValue::Num)
+        );
+    }
+
+    #[test]
+    fn test_chi_squared_distribution() {
+        let dist = |x: f64, df: i64, cumulative: bool| {
+            chisq_dist(&[Value::Float(x), Value::Int(df), Value::Bool(cumulative)])
+        };
+        assert_close(dist(0.5, 1, true), 0.520_499_877_813_047, 1e-12);
+        assert_close(dist(2.0, 3, false), 0.207_553_748_710_297, 1e-12);
+        assert_close(
+            chisq_dist_rt(&[Value::Float(18.307), Value::Int(10)]),
+            0.050_000_589_092_5,
+            1e-10,
+        );
+        assert_close(
+            chisq_inv(&[Value::Float(0.93), Value::Int(1)]),
+            3.283_020_286_759_54,
+            1e-9,
+        );
+        assert_close(
+            chisq_inv(&[Value::Float(0.6), Value::Int(2)]),
+            1.832_581_463_748_31,
+            1e-9,
+        );
+        assert_close(
+            chisq_inv_rt(&[Value::Float(0.050_001), Value::Int(10)]),
+            18.306_973_178_723,
+            1e-6,
+        );
+        assert_eq!(
+            chisq_dist(&[Value::Int(-1), Value::Int(2), Value::Bool(true)]),
+            Value::Error(ErrorValue::Num)
+        );
+    }
+}
@@ -0,0 +1,875 @@
+//! Text functions: searching, slicing, joining and splitting text, character
+//! codes, regular expressions, and TEXT, which formats numbers exactly as cell
+//! display does.
+//!
+//! Positions are one-based and count characters, not bytes.
+
+use piptable_formatting::ssf_format;
+use piptable_primitives::{ErrorValue, Value};
+use regex::{Regex, RegexBuilder};
+
+use super::{coerce_to_bool, coerce_to_text, from_grid, read_required_number, walk_values};
+
+/// Longest text a cell can hold; longer results are #VALUE!
+const MAX_TEXT_CHARS: usize = 32_767;
+
+fn text_arg(values: &[Value], idx: usize) -> Result<String, ErrorValue> {
+    coerce_to_text(values.get(idx).unwrap_or(&Value::Empty))
+}
+
+/// An optional whole-number argument, truncated.
+fn optional_whole(values: &[Value], idx: usize, default: f64) -> Result<f64, ErrorValue> {
+    match values.get(idx) {
+        None | Some(Value::Empty) => Ok(default),
+        Some(value) => read_required_number(Some(value)).map(f64::trunc),
+    }
+}
+
+fn optional_flag(values: &[Value], idx: usize) -> Result<bool, ErrorValue> {
+    match values.get(idx) {
+        None => Ok(false),
+        Some(value) => coerce_to_bool(value),
+    }
+}
+
+/// A one-based character position, at least 1.
+fn read_position(values: &[Value], idx: usize, default: f64) -> Result<usize, ErrorValue> {
+    let n = optional_whole(values, idx, default)?;
+    if n < 1.0 {
+        Err(ErrorValue::Value)
+    } else {
+        Ok(n.min(usize::MAX as f64) as usize)
+    }
+}
+
+/// A character count, at least 0.
+fn read_count(values: &[Value], idx: usize) -> Result<usize, ErrorValue> {
+    let n = optional_whole(values, idx, 0.0)?;
+    if n < 0.0 {
+        Err(ErrorValue::Value)
+    } else {
+        Ok(n.min(usize::MAX as f64) as usize)
+    }
+}
+
+fn text_result(result: Result<String, ErrorValue>) -> Value {
+    match result {
+        Ok(text) if text.chars().count() > MAX_TEXT_CHARS => Value::Error(ErrorValue::Value),
+        Ok(text) => Value::String(text),
+        Err(err) => Value::Error(err),
+    }
+}
+
+/// The texts in an argument that may be a single value or an array.
+fn text_list(value: &Value) -> Result<Vec<String>, ErrorValue> {
+    let mut texts = Vec::new();
+    let mut error = None;
+    walk_values(
+        std::slice::from_ref(value),
+        &mut |value| match coerce_to_text(value) {
+            Ok(text) => texts.push(text),
+            Err(err) => {
+                error.get_or_insert(err);
+            }
+        },
+    );
+    error.map_or(Ok(texts), Err)
+}
+
+fn char_eq(a: char, b: char, ignore_case: bool) -> bool {
+    a == b || (ignore_case && a.to_lowercase().eq(b.to_lowercase()))
+}
+
+/// Character index of each non-overlapping match of any delimiter, scanning
+/// left to right, as (start, length). Empty delimiters never match.
+fn find_delimiters(
+    text: &[char],
+    delimiters: &[Vec<char>],
+    ignore_case: bool,
+) -> Vec<(usize, usize)> {
+    let mut found = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let hit = delimiters.iter().filter(|d| !d.is_empty()).find(|d| {
+            text.len() - pos >= d.len()
+                && d.iter()
+                    .zip(&text[pos..])
+                    .all(|(a, b)| char_eq(*a, *b, ignore_case))
+        });
+        match hit {
+            Some(delimiter) => {
+                found.push((pos, delimiter.len()));
+                pos += delimiter.len();
+            }
+            None => pos += 1,
+        }
+    }
+    found
+}
+
+/// MID(text, start_num, num_chars)
+pub fn mid(values: &[Value]) -> Value {
+    text_result((|| {
+        let text = text_arg(values, 0)?;
+        let start = read_position(values, 1, 1.0)?;
+        let count = read_count(values, 2)?;
+        Ok(text.chars().skip(start - 1).take(count).collect())
+    })())
+}
+
+/// Character position of `needle` in `haystack` at or after `start`.
+fn find_from(haystack: &str, needle: &str, start: usize) -> Option<usize> {
+    let byte_start = haystack.char_indices().nth(start - 1).map(|(i, _)| i)?;
+    haystack[byte_start..]
+        .find(needle)
+        .map(|i| start + haystack[byte_start..byte_start + i].chars().count())
+}
+
+/// FIND(find_text, within_text, [start_num]) - case-sensitive, no wildcards
+pub fn find(values: &[Value]) -> Value {
+    let result = (|| {
+        let needle = text_arg(values, 0)?;
+        let haystack = text_arg(values, 1)?;
+        let start = read_position(values, 2, 1.0)?;
+        let len = haystack.chars().count();
+        if start > len + 1 {
+            return Err(ErrorValue::Value);
+        }
+        if needle.is_empty() {
+            return Ok(start);
+        }
+        find_from(&haystack, &needle, start).ok_or(ErrorValue::Value)
+    })();
+    match result {
+        Ok(pos) => Value::Int(pos as i64),
+        Err(err) => Value::Error(err),
+    }
+}
+
+/// Translate a wildcard pattern (`*`, `?`, `~` escapes) to regex source.
+pub(super) fn wildcard_source(pattern: &str) -> String {
+    let mut source = String::new();
+    let mut chars = pattern.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '*' => source.push_str(".*"),
+            '?' => source.push('.'),
+            '~' => match chars.next() {
+                Some(next) => source.push_str(&regex::escape(&next.to_string())),
+                None => source.push('~'),
+            },
+            other => source.push_str(&regex::escape(&other.to_string())),
+        }
+    }
+    source
+}
+
+/// Translate a SEARCH pattern to a case-insensitive regex.
+fn wildcard_regex(pattern: &str) -> Result<Regex, ErrorValue> {
+    RegexBuilder::new(&wildcard_source(pattern))
+        .case_insensitive(true)
+        .dot_matches_new_line(true)
+        .build()
+        .map_err(|_| ErrorValue::Value)
+}
+
+/// SEARCH(find_text, within_text, [start_num]) - case-insensitive, wildcards
+pub fn search(values: &[Value]) -> Value {
+    let result = (|| {
+        let needle = text_arg(values, 0)?;
+        let haystack = text_arg(values, 1)?;
+        let start = read_position(values, 2, 1.0)?;
+        let len = haystack.chars().count();
+        if start > len + 1 {
+            return Err(ErrorValue::Value);
+        }
+        if needle.is_empty() {
+            return Ok(start);
+        }
+        let byte_start = haystack
+            .char_indices()
+            .nth(start - 1)
+            .map_or(haystack.len(), |(i, _)| i);
+        let found = wildcard_regex(&needle)?
+            .find_at(&haystack, byte_start)
+            .ok_or(ErrorValue::Value)?;
+        Ok(haystack[..found.start()].chars().count() + 1)
+    })();
+    match result {
+        Ok(pos) => Value::Int(pos as i64),
+        Err(err) => Value::Error(err),
+    }
+}
+
+/// SUBSTITUTE(text, old_text, new_text, [instance_num])
+pub fn substitute(values: &[Value]) -> Value {
+    text_result((|| {
+        let text = text_arg(values, 0)?;
+        let old = text_arg(values, 1)?;
+        let new = text_arg(values, 2)?;
+        let instance = match values.get(3) {
+            Some(_) => Some(read_position(values, 3, 1.0)?),
+            None => None,
+        };
+        if old.is_empty() {
+            return Ok(text);
+        }
+        Ok(match instance {
+            None => text.replace(&old, &new),
+            Some(n) => match text.match_indices(&old).nth(n - 1) {
+                Some((at, _)) => format!("{}{new}{}", &text[..at], &text[at + old.len()..]),
+                None => text,
+            },
+        })
+    })())
+}
+
+/// REPLACE(old_text, start_num, num_chars, new_text)
+pub fn replace(values: &[Value]) -> Value {
+    text_result((|| {
+        let text: Vec<char> = text_arg(values, 0)?.chars().collect();
+        let start = read_position(values, 1, 1.0)?;
+        let count = read_count(values, 2)?;
+        let new = text_arg(values, 3)?;
+        let from = (start - 1).min(text.len());
+        let to = from.saturating_add(count).min(text.len());
+        let mut out: String = text[..from].iter().collect();
+        out.push_str(&new);
+        out.extend(&text[to..]);
+        Ok(out)
+    })())
+}
+
+/// REPT(text, number_times)
+pub fn rept(values: &[Value]) -> Value {
+    text_result((|| {
+        let text = text_arg(values, 0)?;
+        let times = read_count(values, 1)?;
+        if text.chars().count().saturating_mul(times) > MAX_TEXT_CHARS {
+            return Err(ErrorValue::Value);
+        }
+        Ok(text.repeat(times))
+    })())
+}
+
+/// TEXTJOIN(delimiter, ignore_empty, text1, [text2], ...)
+pub fn textjoin(values: &[Value]) -> Value {
+    text_result((|| {
+        let delimiters = text_list(values.first().unwrap_or(&Value::Empty))?;
+        let ignore_empty = coerce_to_bool(values.get(1).unwrap_or(&Value::Empty))?;
+        let mut parts = Vec::new();
+        for text in values.iter().skip(2).map(text_list) {
+            parts.extend(
+                text?
+                    .into_iter()
+                    .filter(|part| !(ignore_empty && part.is_empty())),
+            );
+        }
+        let mut out = String::new();
+        for (idx, part) in parts.iter().enumerate() {
+            if idx > 0 && !delimiters.is_empty() {
+                out.push_str(&delimiters[(idx - 1) % delimiters.len()]);
+            }
+            out.push_str(part);
+        }
+        Ok(out)
+    })())
+}
+
+/// Shared by TEXTBEFORE and TEXTAFTER: the chosen delimiter match as
+/// (start, length), or `None` when it does not occur.
+fn locate_delimiter(values: &[Value], text: &[char]) -> Result<Option<(usize, usize)>, ErrorValue> {
+    let delimiters: Vec<Vec<char>> = text_list(values.get(1).unwrap_or(&Value::Empty))?
+        .iter()
+        .map(|d| d.chars().collect())
+        .collect();
+    let instance = optional_whole(values, 2, 1.0)?;
+    let ignore_case = optional_whole(values, 3, 0.0)? != 0.0;
+    let match_end = optional_flag(values, 4)?;
+    if instance == 0.0 || instance.abs() > text.len().max(1) as f64 {
+        return Err(ErrorValue::Value);
+    }
+
+    let mut found = find_delimiters(text, &delimiters, ignore_case);
+    // An empty delimiter matches at the start (or end, counting backwards)
+    let empty = delimiters.iter().any(Vec::is_empty);
+    if instance > 0.0 {
+        if empty {
+            found.insert(0, (0, 0));
+        }
+        if match_end {
+            found.push((text.len(), 0));
+        }
+        Ok(found.get(instance as usize - 1).copied())
+    } else {
+        if empty {
+            found.push((text.len(), 0));
+        }
+        if match_end {
+            found.insert(0, (0, 0));
+        }
+        let back = (-instance) as usize;
+        Ok(found.len().checked_sub(back).map(|idx| found[idx]))
+    }
+}
+
+fn text_around(values: &[Value], before: bool) -> Value {
+    let text: Vec<char> = match text_arg(values, 0) {
+        Ok(text) => text.chars().collect(),
+        Err(err) => return Value::Error(err),
+    };
+    match locate_delimiter(values, &text) {
+        Ok(Some((start, len))) => Value::String(if before {
+            text[..start].iter().collect()
+        } else {
+            text[start + len..].iter().collect()
+        }),
+        Ok(None) => values
+            .get(5)
+            .cloned()
+            .unwrap_or(Value::Error(ErrorValue::NA)),
+        Err(err) => Value::Error(err),
+    }
+}
+
+/// TEXTBEFORE(text, delimiter, [instance_num], [match_mode], [match_end], [if_not_found])
+pub fn textbefore(values: &[Value]) -> Value {
+    text_around(values, true)
+}
+
+/// TEXTAFTER(text, delimiter, [instance_num], [match_mode], [match_end], [if_not_found])
+pub fn textafter(values: &[Value]) -> Value {
+    text_around(values, false)
+}
+
+/// Split `text` at each delimiter match, optionally dropping empty parts.
+fn split_chars(
+    text: &[char],
+    delimiters: &[Vec<char>],
+    ignore_case: bool,
+    ignore_empty: bool,
+) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut from = 0;
+    let found = find_delimiters(text, delimiters, ignore_case);
+    for (start, len) in found.into_iter().chain([(text.len(), 0)]) {
+        let part: String = text[from..start].iter().collect();
+        if !(ignore_empty && part.is_empty()) {
+            parts.push(part);
+        }
+        from = start + len;
+    }
+    parts
+}
+
+/// TEXTSPLIT(text, col_delimiter, [row_delimiter], [ignore_empty], [match_mode], [pad_with])
+pub fn textsplit(values: &[Value]) -> Value {
+    let result = (|| {
+        let text: Vec<char> = text_arg(values, 0)?.chars().collect();
+        let delimiters = |idx: usize| -> Result<Vec<Vec<char>>, ErrorValue> {
+            Ok(match values.get(idx) {
+                None | Some(Value::Empty) => Vec::new(),
+                Some(value) => text_list(value)?
+                    .iter()
+                    .map(|d| d.chars().collect())
+                    .collect(),
+            })
+        };
+        let cols = delimiters(1)?;
+        let rows = delimiters(2)?;
+        if cols.iter().chain(&rows).all(Vec::is_empty) {
+            return Err(ErrorValue::Value);
+        }
+        let ignore_empty = optional_flag(values, 3)?;
+        let ignore_case = optional_whole(values, 4, 0.0)? != 0.0;
+        let pad = values
+            .get(5)
+            .cloned()
+            .unwrap_or(Value::Error(ErrorValue::NA));
+
+        let lines = if rows.is_empty() {
+            vec![text.iter().collect::<String>()]
+        } else {
+            split_chars(&text, &rows, ignore_case, ignore_empty)
+        };
+        let grid: Vec<Vec<String>> = lines
+            .iter()
+            .map(|line| {
+                let line: Vec<char> = line.chars().collect();
+                split_chars(&line, &cols, ignore_case, ignore_empty)
+            })
+            .collect();
+        let width = grid.iter().map(Vec::len).max().unwrap_or(0).max(1);
+        Ok(grid
+            .into_iter()
+            .map(|row| {
+                let mut row: Vec<Value> = row.into_iter().map(Value::String).collect();
+                row.resize(width, pad.clone());
+                row
+            })
+            .collect())
+    })();
+    result.map_or_else(Value::Error, from_grid)
+}
+
+/// Parse text the way VALUE reads it: optional sign or parentheses for
+/// negatives, a leading `$`, thousands separators and a trailing `%`.
+fn parse_number(text: &str) -> Option<f64> {
+    let mut s = text.trim().to_string();
+    let mut negative = false;
+    if s.starts_with('(') && s.ends_with(')') {
+        negative = true;
+        s = s[1..s.len() - 1].trim().to_string();
+    }
+    if let Some(rest) = s.strip_prefix('-') {
+        negative = !negative;
+        s = rest.trim_start().to_string();
+    } else if let Some(rest) = s.strip_prefix('+') {
+        s = rest.trim_start().to_string();
+    }
+    if let Some(rest) = s.strip_prefix('$') {
+        s = rest.trim_start().to_string();
+    }
+    let mut scale = 1.0;
+    if let Some(rest) = s.strip_suffix('%') {
+        scale = 0.01;
+        s = rest.trim_end().to_string();
+    }
+    let digits = s.replace(',', "");
+    if digits.is_empty() || !digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let n: f64 = digits.parse().ok()?;
+    n.is_finite()
+        .then_some(if negative { -n * scale } else { n * scale })
+}
+
+/// VALUE(text)
+pub fn value_fn(values: &[Value]) -> Value {
+    match values.first().unwrap_or(&Value::Empty) {
+        Value::Empty => Value::Int(0),
+        Value::Int(n) => Value::Int(*n),
+        Value::Float(f) => Value::Float(*f),
+        Value::Error(err) => Value::Error(err.clone()),
+        Value::String(s) => parse_number(s).map_or(Value::Error(ErrorValue::Value), Value::Float),
+        Value::Array(_) | Value::Bool(_) => Value::Error(ErrorValue::Value),
+    }
+}
+
+/// TEXT(value, format_text) - formats with the same number formats as cells
+pub fn text_fn(values: &[Value]) -> Value {
+    let format = match text_arg(values, 1) {
+        Ok(format) => format,
+        Err(err) => return Value::Error(err),
+    };
+    let value = match values.first().unwrap_or(&Value::Empty) {
+        Value::Error(err) => return Value::Error(err.clone()),
+        Value::Array(_) => return Value::Error(ErrorValue::Value),
+        // Numeric text is formatted as the number it holds
+        Value::String(s) => parse_number(s).map_or_else(|| Value::String(s.clone()), Value::Float),
+        other => other.clone(),
+    };
+    Value::String(ssf_format(&format, &value, None))
+}
+
+/// EXACT(text1, text2) - case-sensitive comparison
+pub fn exact(values: &[Value]) -> Value {
+    match (text_arg(values, 0), text_arg(values, 1)) {
+        (Ok(a), Ok(b)) => Value::Bool(a == b),
+        (Err(err), _) | (_, Err(err)) => Value::Error(err),
+    }
+}
+
+/// Windows-1252 characters for bytes 0x80-0x9F; the rest match Latin-1.
+const CP1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{81}', '\u{201A}', '\u{192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2C6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8D}', '\u{17D}', '\u{8F}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2DC}', '\u{2122}', '\u{161}', '\u{203A}', '\u{153}', '\u{9D}', '\u{17E}', '\u{178}',
+];
+
+/// CHAR(number) - the Windows-1252 character for a code from 1 to 255
+pub fn char_fn(values: &[Value]) -> Value {
+    let code = match read_required_number(values.first()) {
+        Ok(n) => n.trunc(),
+        Err(err) => return Value::Error(err),
+    };
+    if !(1.0..=255.0).contains(&code) {
+        return Value::Error(ErrorValue::Value);
+    }
+    let code = code as u32;
+    let ch = match code {
+        0x80..=0x9F => CP1252_HIGH[(code - 0x80) as usize],
+        _ => char::from_u32(code).unwrap_or('?'),
+    };
+    Value::String(ch.to_string())
+}
+
+/// CODE(text) - the Windows-1252 code of the first character
+pub fn code(values: &[Value]) -> Value {
+    let first = match text_arg(values, 0) {
+        Ok(text) => text.chars().next(),
+        Err(err) => return Value::Error(err),
+    };
+    let Some(ch) = first else {
+        return Value::Error(ErrorValue::Value);
+    };
+    let code = match CP1252_HIGH.iter().position(|c| *c == ch) {
+        Some(idx) => 0x80 + idx as i64,
+        None if (ch as u32) < 0x100 => ch as i64,
+        None => '?' as i64,
+    };
+    Value::Int(code)
+}
+
+/// UNICHAR(number) - the character for a Unicode code point
+pub fn unichar(values: &[Value]) -> Value {
+    let code = match read_required_number(values.first()) {
+        Ok(n) => n.trunc(),
+        Err(err) => return Value::Error(err),
+    };
+    if !(1.0..=1_114_111.0).contains(&code) {
+        return Value::Error(ErrorValue::Value);
+    }
+    match char::from_u32(code as u32) {
+        Some(ch) => Value::String(ch.to_string()),
+        None => Value::Error(ErrorValue::NA),
+    }
+}
+
+/// UNICODE(text) - the code point of the first character
+pub fn unicode(values: &[Value]) -> Value {
+    match text_arg(values, 0).map(|text| text.chars().next()) {
+        Ok(Some(ch)) => Value::Int(ch as i64),
+        Ok(None) => Value::Error(ErrorValue::Value),
+        Err(err) => Value::Error(err),
+    }
+}
+
+/// CLEAN(text) - removes the non-printing control characters 0-31
+pub fn clean(values: &[Value]) -> Value {
+    text_result(text_arg(values, 0).map(|text| text.chars().filter(|c| *c as u32 >= 32).collect()))
+}
+
+/// Compile a pattern, case-insensitively when the flag argument at `idx` is 1.
+fn read_regex(values: &[Value], idx: usize) -> Result<Regex, ErrorValue> {
+    let pattern = text_arg(values, 1)?;
+    let ignore_case = optional_whole(values, idx, 0.0)? != 0.0;
+    RegexBuilder::new(&pattern)
+        .case_insensitive(ignore_case)
+        .build()
+        .map_err(|_| ErrorValue::Value)
+}
+
+/// REGEXMATCH(text, pattern, [case_sensitivity])
+pub fn regexmatch(values: &[Value]) -> Value {
+    match (text_arg(values, 0), read_regex(values, 2)) {
+        (Ok(text), Ok(regex)) => Value::Bool(regex.is_match(&text)),
+        (Err(err), _) | (_, Err(err)) => Value::Error(err),
+    }
+}
+
+/// REGEXEXTRACT(text, pattern, [return_mode], [case_sensitivity])
+///
+/// Mode 0 returns the first match, 1 every match as a column, and 2 the
+/// capture groups of the first match as a row.
+pub fn regexextract(values: &[Value]) -> Value {
+    let result = (|| {
+        let text = text_arg(values, 0)?;
+        let mode = optional_whole(values, 2, 0.0)?;
+        let regex = read_regex(values, 3)?;
+        match mode {
+            _ if mode == 0.0 => regex
+                .find(&text)
+                .map(|m| Value::String(m.as_str().to_string()))
+                .ok_or(ErrorValue::NA),
+            _ if mode == 1.0 => {
+                let matches: Vec<Vec<Value>> = regex
+                    .find_iter(&text)
+                    .map(|m| vec![Value::String(m.as_str().to_string())])
+                    .collect();
+                if matches.is_empty() {
+                    Err(ErrorValue::NA)
+                } else {
+                    Ok(from_grid(matches))
+                }
+            }
+            _ if mode == 2.0 => {
+                let caps = regex.captures(&text).ok_or(ErrorValue::NA)?;
+                let groups: Vec<Value> = caps
+                    .iter()
+                    .skip(1)
+                    .map(|group| {
+                        Value::String(group.map_or_else(String::new, |g| g.as_str().to_string()))
+                    })
+                    .collect();
+                if groups.is_empty() {
+                    Err(ErrorValue::NA)
+                } else {
+                    Ok(from_grid(vec![groups]))
+                }
+            }
+            _ => Err(ErrorValue::Value),
+        }
+    })();
+    result.unwrap_or_else(Value::Error)
+}
+
+/// REGEXREPLACE(text, pattern, replacement, [occurrence], [case_sensitivity])
+///
+/// Occurrence 0 replaces every match; a negative occurrence counts from the end.
+pub fn regexreplace(values: &[Value]) -> Value {
+    text_result((|| {
+        let text = text_arg(values, 0)?;
+        let replacement = text_arg(values, 2)?;
+        let occurrence = optional_whole(values, 3, 0.0)?;
+        let regex = read_regex(values, 4)?;
+        if occurrence == 0.0 {
+            return Ok(regex.replace_all(&text, replacement.as_str()).into_owned());
+        }
+        let matches: Vec<_> = regex.captures_iter(&text).collect();
+        let idx = if occurrence > 0.0 {
+            occurrence as usize - 1
+        } else {
+            match matches.len().checked_sub((-occurrence) as usize) {
+                Some(idx) => idx,
+                None => return Ok(text),
+            }
+        };
+        let Some(caps) = matches.get(idx) else {
+            return Ok(text);
+        };
+        let whole = caps.get(0).expect("group 0 always matches");
+        let mut expanded = String::new();
+        caps.expand(&replacement, &mut expanded);
+        Ok(format!(
+            "{}{expanded}{}",
+            &text[..whole.start()],
+            &text[whole.end()..]
+        ))
+    })())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s(text: &str) -> Value {
+        Value::String(text.to_string())
+    }
+
+    fn column(texts: &[&str]) -> Value {
+        from_grid(texts.iter().map(|t| vec![s(t)]).collect())
+    }
+
+    #[test]
+    fn test_mid_find_search() {
+        assert_eq!(
+            mid(&[s("Fluid Flow"), Value::Int(7), Value::Int(20)]),
+            s("Flow")
+        );
+        assert_eq!(mid(&[s("héllo"), Value::Int(2), Value::Int(3)]), s("éll"));
+        assert_eq!(
+            mid(&[s("abc"), Value::Int(0), Value::Int(1)]),
+            Value::Error(ErrorValue::Value)
+        );
+
+        assert_eq!(find(&[s("M"), s("Miriam McGovern")]), Value::Int(1));
+        assert_eq!(find(&[s("m"), s("Miriam McGovern")]), Value::Int(6));
+        assert_eq!(
+            find(&[s("M"), s("Miriam McGovern"), Value::Int(3)]),
+            Value::Int(8)
+        );
+        assert_eq!(find(&[s("z"), s("abc")]), Value::Error(ErrorValue::Value));
+        assert_eq!(find(&[s(""), s("abc"), Value::Int(2)]), Value::Int(2));
+
+        assert_eq!(
+            search(&[s("e"), s("Statements"), Value::Int(6)]),
+            Value::Int(7)
+        );
+        assert_eq!(search(&[s("MARGIN"), s("Profit Margin")]), Value::Int(8));
+        assert_eq!(search(&[s("p?o*t"), s("the profit")]), Value::Int(5));
+        assert_eq!(search(&[s("~?"), s("what? yes")]), Value::Int(5));
+    }
+
+    #[test]
+    fn test_substitute_replace_rept() {
+        assert_eq!(
+            substitute(&[s("Sales Data"), s("Sales"), s("Cost")]),
+            s("Cost Data")
+        );
+        assert_eq!(
+            substitute(&[s("Quarter 1, 2011"), s("1"), s("2"), Value::Int(3)]),
+            s("Quarter 1, 2012")
+        );
+        assert_eq!(substitute(&[s("a-b-c"), s("-"), s("")]), s("abc"));
+        assert_eq!(
+            replace(&[s("abcdefghijk"), Value::Int(6), Value::Int(5), s("*")]),
+            s("abcde*k")
+        );
+        assert_eq!(
+            replace(&[s("2009"), Value::Int(3), Value::Int(2), s("10")]),
+            s("2010")
+        );
+        assert_eq!(rept(&[s("-*"), Value::Int(3)]), s("-*-*-*"));
+        assert_eq!(
+            rept(&[s("ab"), Value::Int(20_000)]),
+            Value::Error(ErrorValue::Value)
+        );
+    }
+
+    #[test]
+    fn test_textjoin_before_after_split() {
+        let items = from_grid(vec![vec![s("a"), Value::Empty], vec![s("b"), s("c")]]);
+        assert_eq!(
+            textjoin(&[s(", "), Value::Bool(true), items.clone()]),
+            s("a, b, c")
+        );
+        assert_eq!(textjoin(&[s("-"), Value::Bool(false), items]), s("a--b-c"));
+
+        let text = s("Red riding hood's red hood");
+        assert_eq!(textbefore(&[text.clone(), s("hood")]), s("Red riding "));
+        assert_eq!(
+            textbefore(&[text.clone(), s("HOOD"), Value::Int(-1), Value::Int(1)]),
+            s("Red riding hood's red ")
+        );
+        assert_eq!(textafter(&[text.clone(), s("red ")]), s("hood"));
+        assert_eq!(
+            textafter(&[text.clone(), s("basket")]),
+            Value::Error(ErrorValue::NA)
+        );
+        assert_eq!(
+            textafter(&[
+                text.clone(),
+                s("basket"),
+                Value::Int(1),
+                Value::Int(0),
+                Value::Int(0),
+                s("none")
+            ]),
+            s("none")
+        );
+        assert_eq!(
+            textbefore(&[
+                s("a.b"),
+                s("."),
+                Value::Int(2),
+                Value::Int(0),
+                Value::Bool(true)
+            ]),
+            s("a.b")
+        );
+        assert_eq!(
+            textafter(&[text, s(" "), Value::Int(0)]),
+            Value::Error(ErrorValue::Value)
+        );
+
+        assert_eq!(
+            textsplit(&[s("a,b;c"), s(","), s(";")]),
+            from_grid(vec![
+                vec![s("a"), s("b")],
+                vec![s("c"), Value::Error(ErrorValue::NA)],
+            ])
+        );
+        assert_eq!(
+            textsplit(&[s("1,,2"), s(","), Value::Empty, Value::Bool(true)]),
+            from_grid(vec![vec![s("1"), s("2")]])
+        );
+    }
+
+    #[test]
+    fn test_value_and_text() {
+        assert_eq!(value_fn(&[s("$1,000")]), Value::Float(1000.0));
+        assert_eq!(value_fn(&[s(" -12.5% ")]), Value::Float(-0.125));
+        assert_eq!(value_fn(&[s("(3)")]), Value::Float(-3.0));
+        assert_eq!(value_fn(&[s("1e3")]), Value::Float(1000.0));
+        assert_eq!(value_fn(&[s("abc")]), Value::Error(ErrorValue::Value));
+        assert_eq!(value_fn(&[s("")]), Value::Error(ErrorValue::Value));
+
+        for (number, format) in [
+            (Value::Float(1234.567), "#,##0.00"),
+            (Value::Float(0.285), "0.0%"),
+            (Value::Int(45000), "yyyy-mm-dd"),
+            (s("12.5"), "0.00"),
+        ] {
+            let display = ssf_format(
+                format,
+                &match &number {
+                    Value::String(text) => Value::Float(text.parse().unwrap()),
+                    other => other.clone(),
+                },
+                None,
+            );
+            assert_eq!(text_fn(&[number, s(format)]), s(&display), "{format}");
+        }
+        assert_eq!(
+            text_fn(&[Value::Float(1234.567), s("#,##0.00")]),
+            s("1,234.57")
+        );
+        assert_eq!(
+            text_fn(&[Value::Error(ErrorValue::Div0), s("0")]),
+            Value::Error(ErrorValue::Div0)
+        );
+    }
+
+    #[test]
+    fn test_exact_and_character_codes() {
+        assert_eq!(exact(&[s("word"), s("word")]), Value::Bool(true));
+        assert_eq!(exact(&[s("Word"), s("word")]), Value::Bool(false));
+
+        assert_eq!(char_fn(&[Value::Int(65)]), s("A"));
+        assert_eq!(char_fn(&[Value::Int(128)]), s("€"));
+        assert_eq!(char_fn(&[Value::Int(256)]), Value::Error(ErrorValue::Value));
+        assert_eq!(code(&[s("Alphabet")]), Value::Int(65));
+        assert_eq!(code(&[s("€")]), Value::Int(128));
+        assert_eq!(code(&[s("")]), Value::Error(ErrorValue::Value));
+        assert_eq!(unichar(&[Value::Int(0x263A)]), s("☺"));
+        assert_eq!(unichar(&[Value::Int(0xD800)]), Value::Error(ErrorValue::NA));
+        assert_eq!(unicode(&[s("☺!")]), Value::Int(0x263A));
+        assert_eq!(clean(&[s("a\u{7}b\nc")]), s("abc"));
+    }
+
+    #[test]
+    fn test_regex_functions() {
+        assert_eq!(
+            regexmatch(&[s("Order 1234"), s(r"\d{4}")]),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            regexmatch(&[s("ABC"), s("abc"), Value::Int(1)]),
+            Value::Bool(true)
+        );
+        assert_eq!(regexmatch(&[s("ABC"), s("abc")]), Value::Bool(false));
+        assert_eq!(
+            regexmatch(&[s("x"), s("(")]),
+            Value::Error(ErrorValue::Value)
+        );
+
+        let text = s("Ann 555-1234, Bob 555-9876");
+        assert_eq!(
+            regexextract(&[text.clone(), s(r"\d{3}-\d{4}")]),
+            s("555-1234")
+        );
+        assert_eq!(
+            regexextract(&[text.clone(), s(r"\d{3}-\d{4}"), Value::Int(1)]),
+            column(&["555-1234", "555-9876"])
+        );
+        assert_eq!(
+            regexextract(&[text.clone(), s(r"(\w+) (\d+)"), Value::Int(2)]),
+            from_grid(vec![vec![s("Ann"), s("555")]])
+        );
+        assert_eq!(
+            regexextract(&[text.clone(), s("xyz")]),
+            Value::Error(ErrorValue::NA)
+        );
+
+        assert_eq!(
+            regexreplace(&[text.clone(), s(r"\d{3}-(\d{4})"), s("***-$1")]),
+            s("Ann ***-1234, Bob ***-9876")
+        );
+        assert_eq!(
+            regexreplace(&[text, s(r"\d+"), s("#"), Value::Int(-1)]),
+            s("Ann 555-1234, Bob 555-#")
+        );
+    }
+}
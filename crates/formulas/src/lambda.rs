@@ -0,0 +1,381 @@
+//! LET, LAMBDA and the LAMBDA helper functions.
+//!
+//! Names bound by LET and LAMBDA parameters are lexically scoped: a LAMBDA
+//! captures the bindings visible where it is written, and a parameter or LET
+//! name shadows a defined name of the same spelling.
+
+use std::rc::Rc;
+
+use piptable_primitives::{ErrorValue, Value};
+
+use crate::functions::{check_size, from_grid, read_dimension, to_grid};
+use crate::{parser, FormulaEngine, FormulaError, FormulaExpr, ValueResolver};
+
+/// Deepest chain of nested LAMBDA calls before evaluation gives up with #NUM!
+const MAX_LAMBDA_DEPTH: usize = 64;
+
+/// A value or function bound to a name by LET or a LAMBDA parameter.
+#[derive(Clone)]
+pub(crate) enum Binding {
+    Value(Value),
+    Lambda(Rc<Lambda>),
+}
+
+/// A LAMBDA together with the bindings it closed over.
+pub(crate) struct Lambda {
+    params: Vec<String>,
+    body: FormulaExpr,
+    scope: Scope,
+}
+
+struct Frame {
+    name: String,
+    binding: Binding,
+    parent: Option<Rc<Frame>>,
+}
+
+/// The names visible to an expression, innermost binding first.
+#[derive(Clone, Default)]
+pub(crate) struct Scope {
+    head: Option<Rc<Frame>>,
+    /// Number of LAMBDA calls on the stack
+    depth: usize,
+}
+
+impl Scope {
+    pub(crate) fn get(&self, name: &str) -> Option<&Binding> {
+        let mut frame = self.head.as_deref();
+        while let Some(current) = frame {
+            if current.name.eq_ignore_ascii_case(name) {
+                return Some(&current.binding);
+            }
+            frame = current.parent.as_deref();
+        }
+        None
+    }
+
+    fn bind(&self, name: &str, binding: Binding) -> Scope {
+        Scope {
+            head: Some(Rc::new(Frame {
+                name: name.to_string(),
+                binding,
+                parent: self.head.clone(),
+            })),
+            depth: self.depth,
+        }
+    }
+}
+
+/// The name a LET or LAMBDA argument binds, if `args[idx]` is one.
+///
+/// LET binds the names in its even positions before the final calculation;
+/// LAMBDA binds every argument but its body.
+pub(crate) fn bound_param<'a>(name: &str, args: &'a [FormulaExpr], idx: usize) -> Option<&'a str> {
+    let binds = if name.eq_ignore_ascii_case("LET") {
+        idx.is_multiple_of(2) && idx + 1 < args.len()
+    } else if name.eq_ignore_ascii_case("LAMBDA") {
+        idx + 1 < args.len()
+    } else {
+        false
+    };
+    match &args[idx] {
+        FormulaExpr::Name { sheet: None, name } if binds => Some(name),
+        _ => None,
+    }
+}
+
+/// Unwrap a LAMBDA result used as a single cell; only 1x1 arrays qualify.
+fn to_scalar(value: Value) -> Value {
+    match value {
+        Value::Array(_) => match to_grid(&value).as_slice() {
+            [row] if row.len() == 1 => row[0].clone(),
+            _ => Value::Error(ErrorValue::Calc),
+        },
+        other => other,
+    }
+}
+
+impl FormulaEngine {
+    /// Evaluate LET, LAMBDA and the LAMBDA helpers, which need their
+    /// arguments unevaluated. Returns `None` for any other function.
+    pub(crate) fn eval_special_form(
+        &self,
+        name: &str,
+        args: &[FormulaExpr],
+        context: &impl ValueResolver,
+        scope: &Scope,
+    ) -> Result<Option<Value>, FormulaError> {
+        let upper = name.to_ascii_uppercase();
+        let expected = match upper.as_str() {
+            "LET" => "3+",
+            "LAMBDA" => "1+",
+            "MAP" => "2+",
+            "REDUCE" | "SCAN" | "MAKEARRAY" => "3",
+            "BYROW" | "BYCOL" => "2",
+            _ => return Ok(None),
+        };
+        let valid = match upper.as_str() {
+            "LET" => args.len() >= 3 && args.len() % 2 == 1,
+            "LAMBDA" => !args.is_empty(),
+            "MAP" => args.len() >= 2,
+            "REDUCE" | "SCAN" | "MAKEARRAY" => args.len() == 3,
+            _ => args.len() == 2,
+        };
+        if !valid {
+            return Err(FormulaError::InvalidArgCount(
+                name.to_string(),
+                expected.to_string(),
+                args.len(),
+            ));
+        }
+
+        match upper.as_str() {
+            "LET" => self.eval_let(args, context, scope).map(Some),
+            // A LAMBDA that is never called has no value
+            "LAMBDA" => Ok(Some(match Self::lambda_from(args, scope) {
+                Some(_) => Value::Error(ErrorValue::Calc),
+                None => Value::Error(ErrorValue::Value),
+            })),
+            _ => {
+                let (last, inputs) = args.split_last().expect("arg count checked");
+                let Some(lambda) = self.callable(last, context, scope) else {
+                    return Ok(Some(Value::Error(ErrorValue::Value)));
+                };
+                let mut values = Vec::with_capacity(inputs.len());
+                for input in inputs {
+                    match self.eval_in(input, context, scope)? {
+                        Value::Error(err) => return Ok(Some(Value::Error(err))),
+                        value => values.push(value),
+                    }
+                }
+                self.eval_helper(&upper, &values, &lambda, context, scope)
+                    .map(Some)
+            }
+        }
+    }
+
+    /// LET(name1, value1, [name2, value2, ...], calculation)
+    fn eval_let(
+        &self,
+        args: &[FormulaExpr],
+        context: &impl ValueResolver,
+        scope: &Scope,
+    ) -> Result<Value, FormulaError> {
+        let (calculation, pairs) = args.split_last().expect("arg count checked");
+        let mut scope = scope.clone();
+        for pair in pairs.chunks(2) {
+            let FormulaExpr::Name { sheet: None, name } = &pair[0] else {
+                return Ok(Value::Error(ErrorValue::Value));
+            };
+            let binding = match self.callable(&pair[1], context, &scope) {
+                Some(lambda) => Binding::Lambda(lambda),
+                None => Binding::Value(self.eval_in(&pair[1], context, &scope)?),
+            };
+            scope = scope.bind(name, binding);
+        }
+        self.eval_in(calculation, context, &scope)
+    }
+
+    /// Build a closure from LAMBDA arguments; parameters must be distinct names.
+    fn lambda_from(args: &[FormulaExpr], scope: &Scope) -> Option<Rc<Lambda>> {
+        let (body, params) = args.split_last()?;
+        let mut names: Vec<String> = Vec::with_capacity(params.len());
+        for param in params {
+            let FormulaExpr::Name { sheet: None, name } = param else {
+                return None;
+            };
+            if names.iter().any(|seen| seen.eq_ignore_ascii_case(name)) {
+                return None;
+            }
+            names.push(name.clone());
+        }
+        Some(Rc::new(Lambda {
+            params: names,
+            body: body.clone(),
+            scope: Scope {
+                depth: 0,
+                ..scope.clone()
+            },
+        }))
+    }
+
+    /// Resolve an expression that denotes a function rather than a value:
+    /// a LAMBDA, or a name bound to one by LET, a parameter or a defined name.
+    fn callable(
+        &self,
+        expr: &FormulaExpr,
+        context: &impl ValueResolver,
+        scope: &Scope,
+    ) -> Option<Rc<Lambda>> {
+        match expr {
+            FormulaExpr::FunctionCall { name, args } if name.eq_ignore_ascii_case("LAMBDA") => {
+                Self::lambda_from(args, scope)
+            }
+            FormulaExpr::Name { sheet: None, name } => self.lambda_named(name, context, scope),
+            _ => None,
+        }
+    }
+
+    /// Find the LAMBDA bound to `name` in scope or as a defined name.
+    pub(crate) fn lambda_named(
+        &self,
+        name: &str,
+        context: &impl ValueResolver,
+        scope: &Scope,
+    ) -> Option<Rc<Lambda>> {
+        match scope.get(name) {
+            Some(Binding::Lambda(lambda)) => return Some(lambda.clone()),
+            Some(Binding::Value(_)) => return None,
+            None => {}
+        }
+        let definition = context.defined_name(None, name)?;
+        match parser::parse_formula(&definition).ok()? {
+            FormulaExpr::FunctionCall { name, args } if name.eq_ignore_ascii_case("LAMBDA") => {
+                // A defined name sees only other defined names
+                Self::lambda_from(&args, &Scope::default())
+            }
+            _ => None,
+        }
+    }
+
+    /// Call a LAMBDA with evaluated arguments from a call site in `caller`.
+    pub(crate) fn call_lambda(
+        &self,
+        lambda: &Lambda,
+        args: Vec<Value>,
+        context: &impl ValueResolver,
+        caller: &Scope,
+    ) -> Result<Value, FormulaError> {
+        if args.len() != lambda.params.len() {
+            return Ok(Value::Error(ErrorValue::Value));
+        }
+        if caller.depth >= MAX_LAMBDA_DEPTH {
+            return Ok(Value::Error(ErrorValue::Num));
+        }
+        let mut scope = Scope {
+            depth: caller.depth + 1,
+            ..lambda.scope.clone()
+        };
+        for (param, arg) in lambda.params.iter().zip(args) {
+            scope = scope.bind(param, Binding::Value(arg));
+        }
+        self.eval_in(&lambda.body, context, &scope)
+    }
+
+    fn eval_helper(
+        &self,
+        name: &str,
+        values: &[Value],
+        lambda: &Lambda,
+        context: &impl ValueResolver,
+        scope: &Scope,
+    ) -> Result<Value, FormulaError> {
+        let call = |args: Vec<Value>| self.call_lambda(lambda, args, context, scope);
+        match name {
+            // MAP(array1, [array2, ...], lambda)
+            "MAP" => {
+                let grids: Vec<_> = values.iter().map(to_grid).collect();
+                let rows = grids.iter().map(Vec::len).max().unwrap_or(0);
+                let cols = grids
+                    .iter()
+                    .flat_map(|grid| grid.iter().map(Vec::len))
+                    .max()
+                    .unwrap_or(0);
+                let mut out = Vec::with_capacity(rows);
+                for r in 0..rows {
+                    let mut row = Vec::with_capacity(cols);
+                    for c in 0..cols {
+                        let args = grids
+                            .iter()
+                            .map(|grid| {
+                                grid.get(r)
+                                    .and_then(|row| row.get(c))
+                                    .cloned()
+                                    .unwrap_or(Value::Error(ErrorValue::NA))
+                            })
+                            .collect();
+                        row.push(to_scalar(call(args)?));
+                    }
+                    out.push(row);
+                }
+                Ok(from_grid(out))
+            }
+            // REDUCE(initial, array, lambda(accumulator, value))
+            "REDUCE" => {
+                let mut acc = values[0].clone();
+                for value in to_grid(&values[1]).into_iter().flatten() {
+                    acc = call(vec![acc, value])?;
+                }
+                Ok(acc)
+            }
+            // SCAN(initial, array, lambda(accumulator, value))
+            "SCAN" => {
+                let mut acc = values[0].clone();
+                let mut out = Vec::new();
+                for row in to_grid(&values[1]) {
+                    let mut scanned = Vec::with_capacity(row.len());
+                    for value in row {
+                        acc = call(vec![acc, value])?;
+                        scanned.push(to_scalar(acc.clone()));
+                    }
+                    out.push(scanned);
+                }
+                Ok(from_grid(out))
+            }
+            // BYROW(array, lambda(row)) and BYCOL(array, lambda(column))
+            "BYROW" | "BYCOL" => {
+                let grid = to_grid(&values[0]);
+                let slices = if name == "BYROW" {
+                    grid
+                } else {
+                    let cols = grid.iter().map(Vec::len).max().unwrap_or(0);
+                    (0..cols)
+                        .map(|c| {
+                            grid.iter()
+                                .map(|row| row.get(c).cloned().unwrap_or(Value::Empty))
+                                .collect()
+                        })
+                        .collect()
+                };
+                let mut results = Vec::with_capacity(slices.len());
+                for slice in slices {
+                    let arg = if name == "BYROW" {
+                        from_grid(vec![slice])
+                    } else {
+                        from_grid(slice.into_iter().map(|value| vec![value]).collect())
+                    };
+                    results.push(to_scalar(call(vec![arg])?));
+                }
+                Ok(if name == "BYROW" {
+                    from_grid(results.into_iter().map(|value| vec![value]).collect())
+                } else {
+                    from_grid(vec![results])
+                })
+            }
+            // MAKEARRAY(rows, columns, lambda(row, column))
+            "MAKEARRAY" => {
+                let dims = read_dimension(Some(&values[0])).and_then(|rows| {
+                    let cols = read_dimension(Some(&values[1]))?;
+                    check_size(rows, cols).map(|()| (rows, cols))
+                });
+                let (rows, cols) = match dims {
+                    Ok(dims) => dims,
+                    Err(err) => return Ok(Value::Error(err)),
+                };
+                let mut out = Vec::with_capacity(rows);
+                for r in 1..=rows {
+                    let mut row = Vec::with_capacity(cols);
+                    for c in 1..=cols {
+                        row.push(to_scalar(call(vec![
+                            Value::Int(r as i64),
+                            Value::Int(c as i64),
+                        ])?));
+                    }
+                    out.push(row);
+                }
+                Ok(from_grid(out))
+            }
+            _ => unreachable!("not a LAMBDA helper: {name}"),
+        }
+    }
+}
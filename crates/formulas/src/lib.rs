@@ -9,8 +9,11 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 
+use lambda::{Binding, Scope};
+
 /// Built-in formula function implementations.
 pub mod functions;
+mod lambda;
 /// Formula parser and AST helpers.
 pub mod parser;
 /// Reference extraction and normalization helpers.
@@ -18,10 +21,12 @@ pub mod refs;
 /// Utility helpers for formula strings.
 pub mod utils;
 
+/// Re-export the serial date system date functions evaluate in.
+pub use piptable_utils::datetime::DateSystem;
 /// Re-export reference helpers and types.
 pub use refs::{
-    extract_references, formula_to_relative_reference, FormulaReference,
-    FormulaToRelativeReferenceOptions, ReferenceKind, ReferenceMode,
+    extract_references, formula_to_relative_reference, quote_sheet_name, rename_sheet_references,
+    FormulaReference, FormulaToRelativeReferenceOptions, ReferenceKind, ReferenceMode,
 };
 /// Re-export formula string utilities.
 pub use utils::{
@@ -87,6 +92,21 @@ pub enum FormulaExpr {
         start: R1C1Ref,
         end: R1C1Ref,
     },
+    /// Spill range reference (e.g., A1# or Sheet1!A1#): every cell the
+    /// array formula at `anchor` spills into
+    SpillRef {
+        sheet: Option<String>,
+        anchor: CellAddress,
+    },
+    /// Defined name (e.g., TaxRate or Sheet1!Regions)
+    Name { sheet: Option<String>, name: String },
+    /// Structured table reference (e.g., Table1[Amount] or [@Qty]); `table`
+    /// is `None` for the table containing the formula.
+    TableRef {
+        table: Option<String>,
+        area: TableArea,
+        columns: Option<(String, String)>,
+    },
     /// Function call
     FunctionCall {
         name: String,
@@ -105,6 +125,99 @@ pub enum FormulaExpr {
     },
 }
 
+/// Part of a table selected by a structured reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TableArea {
+    /// Data rows (the default, or `#Data`)
+    Data,
+    /// Header row (`#Headers`)
+    Headers,
+    /// Totals row (`#Totals`)
+    Totals,
+    /// Headers, data and totals (`#All`)
+    All,
+    /// The data row containing the formula (`@` or `#This Row`)
+    ThisRow,
+}
+
+/// Where a table sits, used to turn structured references into ranges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableLocation {
+    /// Table name
+    pub name: String,
+    /// Sheet holding the table, or `None` for the formula's own sheet
+    pub sheet: Option<String>,
+    /// Zero-based row of the header
+    pub header_row: u32,
+    /// Zero-based first column
+    pub first_col: u32,
+    /// Column names, left to right
+    pub columns: Vec<String>,
+    /// Number of data rows below the header
+    pub data_rows: u32,
+    /// Whether a totals row follows the data rows
+    pub totals_row: bool,
+}
+
+impl TableLocation {
+    /// Whether `addr` lies anywhere within the table, header and totals included.
+    pub fn contains(&self, addr: &CellAddress) -> bool {
+        let last_row = self.header_row + self.data_rows + u32::from(self.totals_row);
+        let last_col = self.first_col + self.columns.len() as u32;
+        (self.header_row..=last_row).contains(&addr.row)
+            && (self.first_col..last_col).contains(&addr.col)
+    }
+
+    /// Resolve an area and optional column span to cells. Returns `None` for
+    /// unknown columns, a missing totals row, no data rows, or a `ThisRow`
+    /// reference from outside the data rows.
+    pub fn resolve(
+        &self,
+        area: TableArea,
+        columns: Option<(&str, &str)>,
+        current: Option<CellAddress>,
+    ) -> Option<CellRange> {
+        let (start_col, end_col) = match columns {
+            Some((first, last)) => {
+                let first = self.column_index(first)?;
+                let last = self.column_index(last)?;
+                (first.min(last), first.max(last))
+            }
+            None if self.columns.is_empty() => return None,
+            None => (0, self.columns.len() as u32 - 1),
+        };
+        let first_data = self.header_row + 1;
+        let last_data = self.header_row + self.data_rows;
+        let (start_row, end_row) = match area {
+            TableArea::Headers => (self.header_row, self.header_row),
+            TableArea::Data if self.data_rows == 0 => return None,
+            TableArea::Data => (first_data, last_data),
+            TableArea::Totals if !self.totals_row => return None,
+            TableArea::Totals => (last_data + 1, last_data + 1),
+            TableArea::All => (self.header_row, last_data + u32::from(self.totals_row)),
+            TableArea::ThisRow => {
+                let row = current?.row;
+                if !(first_data..=last_data).contains(&row) {
+                    return None;
+                }
+                (row, row)
+            }
+        };
+        Some(CellRange::new(
+            CellAddress::new(start_row, self.first_col + start_col),
+            CellAddress::new(end_row, self.first_col + end_col),
+        ))
+    }
+
+    fn column_index(&self, name: &str) -> Option<u32> {
+        let name = name.trim();
+        self.columns
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name))
+            .map(|idx| idx as u32)
+    }
+}
+
 /// Binary operators
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BinaryOperator {
@@ -141,6 +254,8 @@ pub struct FormulaEngine {
     dag: Dag,
     /// Function registry
     functions: FunctionRegistry,
+    /// Cells each array formula spills into, linked in the DAG as its dependents
+    spills: HashMap<SheetCellAddress, Vec<CellAddress>>,
 }
 
 impl FormulaEngine {
@@ -150,6 +265,7 @@ impl FormulaEngine {
             cache: HashMap::new(),
             dag: Dag::new(),
             functions: FunctionRegistry::default(),
+            spills: HashMap::new(),
         }
     }
 
@@ -168,7 +284,27 @@ impl FormulaEngine {
     ) -> Result<CompiledFormula, FormulaError> {
         let ast = parser::parse_formula(formula)?;
         let mut deps = Vec::new();
-        collect_dependencies(&ast, sheet_id, resolver, base_cell, &mut deps);
+        let expanded = resolver.map(|resolver| {
+            let lookup = RefLookup {
+                names: &|sheet: Option<&str>, name: &str| {
+                    let id = match sheet {
+                        Some(sheet) => resolver.sheet_id(sheet)?,
+                        None => sheet_id,
+                    };
+                    resolver.defined_name(id, name)
+                },
+                tables: &|table: Option<&str>| resolver.table(sheet_id, table, base_cell),
+                current: base_cell,
+            };
+            lookup.expand(&ast, &mut Vec::new(), &mut Vec::new())
+        });
+        collect_dependencies(
+            expanded.as_ref().unwrap_or(&ast),
+            sheet_id,
+            resolver,
+            base_cell,
+            &mut deps,
+        );
         let hash = hash_formula(formula);
         Ok(CompiledFormula {
             source: formula.to_string(),
@@ -256,6 +392,54 @@ impl FormulaEngine {
         Ok(())
     }
 
+    /// Record the cells the formula at `anchor` (sheet 0) spills into.
+    pub fn set_spill(
+        &mut self,
+        anchor: CellAddress,
+        cells: &[CellAddress],
+    ) -> Result<(), FormulaError> {
+        self.set_spill_with_sheet(0, anchor, cells)
+    }
+
+    /// Record the cells an array formula at `anchor` spills into, replacing
+    /// its previous spill. The cells become dependents of the anchor in the
+    /// DAG, so formulas that read them recalculate after it. On error (the
+    /// anchor depends on one of the cells) no cells are linked.
+    pub fn set_spill_with_sheet(
+        &mut self,
+        sheet_id: u32,
+        anchor: CellAddress,
+        cells: &[CellAddress],
+    ) -> Result<(), FormulaError> {
+        let key = SheetCellAddress {
+            sheet_id,
+            addr: anchor,
+        };
+        let anchor_ref = NodeRef::Cell(cell_to_coordinate_with_sheet(sheet_id, anchor));
+        let spill_ref =
+            |cell: &CellAddress| NodeRef::Cell(cell_to_coordinate_with_sheet(sheet_id, *cell));
+        for cell in self.spills.remove(&key).unwrap_or_default() {
+            self.dag
+                .remove_node_input(spill_ref(&cell), anchor_ref.clone());
+        }
+        for (idx, cell) in cells.iter().enumerate() {
+            if let Err(err) = self
+                .dag
+                .add_node_input(spill_ref(cell), anchor_ref.clone(), true)
+            {
+                for linked in &cells[..idx] {
+                    self.dag
+                        .remove_node_input(spill_ref(linked), anchor_ref.clone());
+                }
+                return Err(map_dag_error(err));
+            }
+        }
+        if !cells.is_empty() {
+            self.spills.insert(key, cells.to_vec());
+        }
+        Ok(())
+    }
+
     /// Get compiled formula for a cell on sheet 0.
     pub fn get_formula(&self, cell: &CellAddress) -> Option<&CompiledFormula> {
         self.get_formula_with_sheet(0, cell)
@@ -440,6 +624,17 @@ impl FormulaEngine {
         &self,
         expr: &FormulaExpr,
         context: &impl ValueResolver,
+    ) -> Result<Value, FormulaError> {
+        self.eval_in(expr, context, &Scope::default())
+    }
+
+    /// Evaluate `expr` with the names bound by enclosing LET and LAMBDA
+    /// expressions in `scope`.
+    fn eval_in(
+        &self,
+        expr: &FormulaExpr,
+        context: &impl ValueResolver,
+        scope: &Scope,
     ) -> Result<Value, FormulaError> {
         match expr {
             FormulaExpr::Literal(value) => Ok(value.clone()),
@@ -468,6 +663,14 @@ impl FormulaEngine {
                     _ => Ok(Value::Error(ErrorValue::Ref)),
                 }
             }
+            FormulaExpr::SheetCellRef { sheet, .. }
+            | FormulaExpr::SheetR1C1Ref { sheet, .. }
+            | FormulaExpr::SheetRangeRef { sheet, .. }
+            | FormulaExpr::SheetR1C1RangeRef { sheet, .. }
+                if !context.has_sheet(sheet) =>
+            {
+                Ok(Value::Error(ErrorValue::Ref))
+            }
             FormulaExpr::SheetCellRef { sheet, addr } => Ok(context.get_sheet_cell(sheet, addr)),
             FormulaExpr::SheetR1C1Ref { sheet, addr } => {
                 let Some(base) = context.current_cell() else {
@@ -495,49 +698,115 @@ impl FormulaEngine {
                     _ => Ok(Value::Error(ErrorValue::Ref)),
                 }
             }
+            FormulaExpr::SpillRef { sheet, anchor } => {
+                if sheet
+                    .as_deref()
+                    .is_some_and(|sheet| !context.has_sheet(sheet))
+                {
+                    return Ok(Value::Error(ErrorValue::Ref));
+                }
+                let Some(range) = context.spill_range(sheet.as_deref(), anchor) else {
+                    return Ok(Value::Error(ErrorValue::Ref));
+                };
+                Ok(Value::Array(match sheet {
+                    Some(sheet) => context.get_sheet_range(sheet, &range),
+                    None => context.get_range(&range),
+                }))
+            }
+            FormulaExpr::Name { sheet: None, name } if scope.get(name).is_some() => {
+                Ok(match scope.get(name) {
+                    Some(Binding::Value(value)) => value.clone(),
+                    // A LAMBDA that is not called has no value
+                    _ => Value::Error(ErrorValue::Calc),
+                })
+            }
+            FormulaExpr::Name { .. } | FormulaExpr::TableRef { .. } => {
+                let lookup = RefLookup {
+                    names: &|sheet, name| context.defined_name(sheet, name),
+                    tables: &|table| context.table(table),
+                    current: context.current_cell(),
+                };
+                let expanded = lookup.expand(expr, &mut Vec::new(), &mut Vec::new());
+                self.eval_in(&expanded, context, scope)
+            }
             FormulaExpr::UnaryOp { op, expr } => {
-                let value = self.eval_expr(expr, context)?;
+                let value = self.eval_in(expr, context, scope)?;
                 if let Value::Error(err) = value {
                     return Ok(Value::Error(err));
                 }
                 Ok(eval_unary(*op, value))
             }
             FormulaExpr::BinaryOp { op, left, right } => {
-                let left_val = self.eval_expr(left, context)?;
+                let left_val = self.eval_in(left, context, scope)?;
                 if let Value::Error(err) = left_val {
                     return Ok(Value::Error(err));
                 }
-                let right_val = self.eval_expr(right, context)?;
+                let right_val = self.eval_in(right, context, scope)?;
                 if let Value::Error(err) = right_val {
                     return Ok(Value::Error(err));
                 }
                 Ok(eval_binary(*op, left_val, right_val))
             }
             FormulaExpr::FunctionCall { name, args } => {
-                let def = self
-                    .functions
-                    .get(name)
-                    .ok_or_else(|| FormulaError::UnknownFunction(name.clone()))?;
+                if let Some(value) = self.eval_special_form(name, args, context, scope)? {
+                    return Ok(value);
+                }
+                let Some(def) = self.functions.get(name) else {
+                    // A LAMBDA bound by LET or to a defined name is callable by name
+                    let Some(lambda) = self.lambda_named(name, context, scope) else {
+                        return Err(FormulaError::UnknownFunction(name.clone()));
+                    };
+                    let mut evaled_args = Vec::with_capacity(args.len());
+                    for arg in args {
+                        evaled_args.push(self.eval_in(arg, context, scope)?);
+                    }
+                    return self.call_lambda(&lambda, evaled_args, context, scope);
+                };
 
                 def.validate_arg_count(args.len()).map_err(|expected| {
                     FormulaError::InvalidArgCount(name.clone(), expected, args.len())
                 })?;
 
+                if name.eq_ignore_ascii_case("IF") {
+                    // Only the branch taken is evaluated, so a recursive
+                    // LAMBDA can stop at its base case
+                    let condition = self.eval_in(&args[0], context, scope)?;
+                    if let Value::Error(err) = condition {
+                        return Ok(Value::Error(err));
+                    }
+                    return match functions::coerce_to_bool(&condition) {
+                        Ok(true) => self.eval_in(&args[1], context, scope),
+                        Ok(false) => match args.get(2) {
+                            Some(branch) => self.eval_in(branch, context, scope),
+                            None => Ok(Value::Bool(false)),
+                        },
+                        Err(err) => Ok(Value::Error(err)),
+                    };
+                }
+
+                let sum_range = implicit_sum_range(name, args);
                 let mut evaled_args = Vec::with_capacity(args.len());
-                for arg in args {
-                    let value = self.eval_expr(arg, context)?;
+                for (index, arg) in args.iter().enumerate() {
+                    let arg = match &sum_range {
+                        Some(resized) if index == 2 => resized,
+                        _ => arg,
+                    };
+                    let value = self.eval_in(arg, context, scope)?;
                     if let Value::Error(err) = &value {
                         return Ok(Value::Error(err.clone()));
                     }
                     evaled_args.push(value);
                 }
 
-                Ok((def.eval)(&evaled_args))
+                Ok(def.call(&evaled_args, context.date_system()))
             }
         }
     }
 }
 
+/// Excel's argument limit, which caps the criteria pairs of SUMIFS and friends.
+const MAX_CRITERIA_ARGS: usize = 255;
+
 /// Registry of available functions
 #[derive(Debug, Clone)]
 pub struct FunctionRegistry {
@@ -600,204 +869,1020 @@ impl FunctionRegistry {
             FunctionDefinition::variadic(1, ParamType::Number, ReturnType::Number, functions::min),
         );
 
-        // Logical functions
+        // Statistical functions
         self.register(
-            "IF",
-            FunctionDefinition::range(
-                2,
-                3,
-                vec![ParamType::Logical, ParamType::Any, ParamType::Any],
+            "MEDIAN",
+            FunctionDefinition::variadic(
+                1,
+                ParamType::Number,
+                ReturnType::Number,
+                functions::median,
+            ),
+        );
+        self.register(
+            "MODE.SNGL",
+            FunctionDefinition::variadic(
+                1,
+                ParamType::Number,
+                ReturnType::Number,
+                functions::mode_sngl,
+            ),
+        );
+        self.register(
+            "MODE",
+            FunctionDefinition::variadic(
+                1,
+                ParamType::Number,
+                ReturnType::Number,
+                functions::mode_sngl,
+            ),
+        );
+        self.register(
+            "MODE.MULT",
+            FunctionDefinition::variadic(
+                1,
+                ParamType::Number,
                 ReturnType::Any,
-                functions::if_fn,
+                functions::mode_mult,
             ),
         );
         self.register(
-            "AND",
+            "STDEV.S",
             FunctionDefinition::variadic(
                 1,
-                ParamType::Logical,
-                ReturnType::Logical,
-                functions::and_fn,
+                ParamType::Number,
+                ReturnType::Number,
+                functions::stdev_s,
             ),
         );
         self.register(
-            "OR",
+            "STDEV",
             FunctionDefinition::variadic(
                 1,
-                ParamType::Logical,
-                ReturnType::Logical,
-                functions::or_fn,
+                ParamType::Number,
+                ReturnType::Number,
+                functions::stdev_s,
             ),
         );
         self.register(
-            "NOT",
-            FunctionDefinition::fixed(
-                vec![ParamType::Logical],
-                ReturnType::Logical,
-                functions::not_fn,
+            "STDEV.P",
+            FunctionDefinition::variadic(
+                1,
+                ParamType::Number,
+                ReturnType::Number,
+                functions::stdev_p,
             ),
         );
-
-        // Text functions
         self.register(
-            "CONCAT",
-            FunctionDefinition::variadic(1, ParamType::Any, ReturnType::Text, functions::concat),
+            "STDEVP",
+            FunctionDefinition::variadic(
+                1,
+                ParamType::Number,
+                ReturnType::Number,
+                functions::stdev_p,
+            ),
         );
         self.register(
-            "CONCATENATE",
-            FunctionDefinition::variadic(1, ParamType::Any, ReturnType::Text, functions::concat),
+            "VAR.S",
+            FunctionDefinition::variadic(
+                1,
+                ParamType::Number,
+                ReturnType::Number,
+                functions::var_s,
+            ),
         );
         self.register(
-            "LEN",
-            FunctionDefinition::fixed(vec![ParamType::Text], ReturnType::Number, functions::len),
+            "VAR",
+            FunctionDefinition::variadic(
+                1,
+                ParamType::Number,
+                ReturnType::Number,
+                functions::var_s,
+            ),
         );
         self.register(
-            "LEFT",
-            FunctionDefinition::range(
+            "VAR.P",
+            FunctionDefinition::variadic(
                 1,
-                2,
-                vec![ParamType::Text, ParamType::Number],
-                ReturnType::Text,
-                functions::left,
+                ParamType::Number,
+                ReturnType::Number,
+                functions::var_p,
             ),
         );
         self.register(
-            "RIGHT",
-            FunctionDefinition::range(
+            "VARP",
+            FunctionDefinition::variadic(
                 1,
-                2,
-                vec![ParamType::Text, ParamType::Number],
-                ReturnType::Text,
-                functions::right,
+                ParamType::Number,
+                ReturnType::Number,
+                functions::var_p,
             ),
         );
-
-        // Date functions
         self.register(
-            "TODAY",
-            FunctionDefinition::fixed(vec![], ReturnType::Number, functions::today),
+            "COUNTA",
+            FunctionDefinition::variadic(1, ParamType::Any, ReturnType::Number, functions::counta),
         );
         self.register(
-            "NOW",
-            FunctionDefinition::fixed(vec![], ReturnType::Number, functions::now),
+            "COUNTBLANK",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range],
+                ReturnType::Number,
+                functions::countblank,
+            ),
         );
         self.register(
-            "DATE",
+            "PERCENTILE.INC",
             FunctionDefinition::fixed(
-                vec![ParamType::Number, ParamType::Number, ParamType::Number],
+                vec![ParamType::Range, ParamType::Number],
                 ReturnType::Number,
-                functions::date,
+                functions::percentile_inc,
             ),
         );
-
-        // Lookup functions
         self.register(
-            "VLOOKUP",
-            FunctionDefinition::range(
-                3,
-                4,
-                vec![ParamType::Any, ParamType::Range, ParamType::Number],
-                ReturnType::Any,
-                functions::vlookup,
+            "PERCENTILE",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Number],
+                ReturnType::Number,
+                functions::percentile_inc,
             ),
         );
         self.register(
-            "HLOOKUP",
-            FunctionDefinition::range(
-                3,
-                4,
-                vec![ParamType::Any, ParamType::Range, ParamType::Number],
-                ReturnType::Any,
-                functions::hlookup,
+            "PERCENTILE.EXC",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Number],
+                ReturnType::Number,
+                functions::percentile_exc,
             ),
         );
         self.register(
-            "INDEX",
-            FunctionDefinition::range(
-                2,
-                3,
+            "QUARTILE.INC",
+            FunctionDefinition::fixed(
                 vec![ParamType::Range, ParamType::Number],
-                ReturnType::Any,
-                functions::index,
+                ReturnType::Number,
+                functions::quartile_inc,
             ),
         );
         self.register(
-            "MATCH",
-            FunctionDefinition::range(
-                2,
-                3,
-                vec![ParamType::Any, ParamType::Range],
+            "QUARTILE",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Number],
                 ReturnType::Number,
-                functions::match_fn,
+                functions::quartile_inc,
             ),
         );
         self.register(
-            "XLOOKUP",
-            FunctionDefinition::range(
-                3,
-                7,
-                vec![ParamType::Any, ParamType::Range, ParamType::Range],
-                ReturnType::Any,
-                functions::xlookup,
+            "QUARTILE.EXC",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Number],
+                ReturnType::Number,
+                functions::quartile_exc,
             ),
         );
         self.register(
-            "OFFSET",
-            FunctionDefinition::range(
-                3,
-                5,
-                vec![ParamType::Range, ParamType::Number, ParamType::Number],
-                ReturnType::Range,
-                functions::offset,
+            "LARGE",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Number],
+                ReturnType::Number,
+                functions::large,
             ),
         );
-
-        // Additional Math functions
         self.register(
-            "ABS",
-            FunctionDefinition::fixed(vec![ParamType::Number], ReturnType::Number, functions::abs),
+            "SMALL",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Number],
+                ReturnType::Number,
+                functions::small,
+            ),
         );
         self.register(
-            "ROUND",
+            "RANK.EQ",
             FunctionDefinition::range(
-                1,
                 2,
-                vec![ParamType::Number, ParamType::Number],
+                3,
+                vec![ParamType::Number, ParamType::Range, ParamType::Number],
                 ReturnType::Number,
-                functions::round,
+                functions::rank_eq,
             ),
         );
         self.register(
-            "ROUNDUP",
+            "RANK",
             FunctionDefinition::range(
-                1,
                 2,
-                vec![ParamType::Number, ParamType::Number],
+                3,
+                vec![ParamType::Number, ParamType::Range, ParamType::Number],
                 ReturnType::Number,
-                functions::roundup,
+                functions::rank_eq,
             ),
         );
         self.register(
-            "ROUNDDOWN",
+            "RANK.AVG",
             FunctionDefinition::range(
-                1,
                 2,
-                vec![ParamType::Number, ParamType::Number],
+                3,
+                vec![ParamType::Number, ParamType::Range, ParamType::Number],
                 ReturnType::Number,
-                functions::rounddown,
+                functions::rank_avg,
             ),
         );
         self.register(
-            "PRODUCT",
-            FunctionDefinition::variadic(
-                1,
-                ParamType::Number,
+            "CORREL",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Range],
                 ReturnType::Number,
-                functions::product,
+                functions::correl,
             ),
         );
         self.register(
-            "MOD",
+            "PEARSON",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Range],
+                ReturnType::Number,
+                functions::correl,
+            ),
+        );
+        self.register(
+            "COVARIANCE.P",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Range],
+                ReturnType::Number,
+                functions::covariance_p,
+            ),
+        );
+        self.register(
+            "COVAR",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Range],
+                ReturnType::Number,
+                functions::covariance_p,
+            ),
+        );
+        self.register(
+            "COVARIANCE.S",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Range],
+                ReturnType::Number,
+                functions::covariance_s,
+            ),
+        );
+        self.register(
+            "SLOPE",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Range],
+                ReturnType::Number,
+                functions::slope,
+            ),
+        );
+        self.register(
+            "INTERCEPT",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Range],
+                ReturnType::Number,
+                functions::intercept,
+            ),
+        );
+        self.register(
+            "RSQ",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range, ParamType::Range],
+                ReturnType::Number,
+                functions::rsq,
+            ),
+        );
+        self.register(
+            "FORECAST.LINEAR",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number, ParamType::Range, ParamType::Range],
+                ReturnType::Number,
+                functions::forecast_linear,
+            ),
+        );
+        self.register(
+            "FORECAST",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number, ParamType::Range, ParamType::Range],
+                ReturnType::Number,
+                functions::forecast_linear,
+            ),
+        );
+        self.register(
+            "NORM.DIST",
+            FunctionDefinition::fixed(
+                vec![
+                    ParamType::Number,
+                    ParamType::Number,
+                    ParamType::Number,
+                    ParamType::Logical,
+                ],
+                ReturnType::Number,
+                functions::norm_dist,
+            ),
+        );
+        self.register(
+            "NORM.INV",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number, ParamType::Number, ParamType::Number],
+                ReturnType::Number,
+                functions::norm_inv,
+            ),
+        );
+        self.register(
+            "NORM.S.DIST",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number, ParamType::Logical],
+                ReturnType::Number,
+                functions::norm_s_dist,
+            ),
+        );
+        self.register(
+            "NORM.S.INV",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number],
+                ReturnType::Number,
+                functions::norm_s_inv,
+            ),
+        );
+        self.register(
+            "T.DIST",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number, ParamType::Number, ParamType::Logical],
+                ReturnType::Number,
+                functions::t_dist,
+            ),
+        );
+        self.register(
+            "T.DIST.2T",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number, ParamType::Number],
+                ReturnType::Number,
+                functions::t_dist_2t,
+            ),
+        );
+        self.register(
+            "T.DIST.RT",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number, ParamType::Number],
+                ReturnType::Number,
+                functions::t_dist_rt,
+            ),
+        );
+        self.register(
+            "T.INV",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number, ParamType::Number],
+                ReturnType::Number,
+                functions::t_inv,
+            ),
+        );
+        self.register(
+            "T.INV.2T",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number, ParamType::Number],
+                ReturnType::Number,
+                functions::t_inv_2t,
+            ),
+        );
+        self.register(
+            "CHISQ.DIST",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number, ParamType::Number, ParamType::Logical],
+                ReturnType::Number,
+                functions::chisq_dist,
+            ),
+        );
+        self.register(
+            "CHISQ.DIST.RT",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number, ParamType::Number],
+                ReturnType::Number,
+                functions::chisq_dist_rt,
+            ),
+        );
+        self.register(
+            "CHISQ.INV",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number, ParamType::Number],
+                ReturnType::Number,
+                functions::chisq_inv,
+            ),
+        );
+        self.register(
+            "CHISQ.INV.RT",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number, ParamType::Number],
+                ReturnType::Number,
+                functions::chisq_inv_rt,
+            ),
+        );
+
+        // Logical functions
+        self.register(
+            "IF",
+            FunctionDefinition::range(
+                2,
+                3,
+                vec![ParamType::Logical, ParamType::Any, ParamType::Any],
+                ReturnType::Any,
+                functions::if_fn,
+            ),
+        );
+        self.register(
+            "AND",
+            FunctionDefinition::variadic(
+                1,
+                ParamType::Logical,
+                ReturnType::Logical,
+                functions::and_fn,
+            ),
+        );
+        self.register(
+            "OR",
+            FunctionDefinition::variadic(
+                1,
+                ParamType::Logical,
+                ReturnType::Logical,
+                functions::or_fn,
+            ),
+        );
+        self.register(
+            "NOT",
+            FunctionDefinition::fixed(
+                vec![ParamType::Logical],
+                ReturnType::Logical,
+                functions::not_fn,
+            ),
+        );
+
+        // Text functions
+        self.register(
+            "CONCAT",
+            FunctionDefinition::variadic(1, ParamType::Any, ReturnType::Text, functions::concat),
+        );
+        self.register(
+            "CONCATENATE",
+            FunctionDefinition::variadic(1, ParamType::Any, ReturnType::Text, functions::concat),
+        );
+        self.register(
+            "LEN",
+            FunctionDefinition::fixed(vec![ParamType::Text], ReturnType::Number, functions::len),
+        );
+        self.register(
+            "LEFT",
+            FunctionDefinition::range(
+                1,
+                2,
+                vec![ParamType::Text, ParamType::Number],
+                ReturnType::Text,
+                functions::left,
+            ),
+        );
+        self.register(
+            "RIGHT",
+            FunctionDefinition::range(
+                1,
+                2,
+                vec![ParamType::Text, ParamType::Number],
+                ReturnType::Text,
+                functions::right,
+            ),
+        );
+
+        // Date functions take the date system of the workbook
+        self.register(
+            "TODAY",
+            FunctionDefinition::dated(0, 0, vec![], ReturnType::Number, functions::today),
+        );
+        self.register(
+            "NOW",
+            FunctionDefinition::dated(0, 0, vec![], ReturnType::Number, functions::now),
+        );
+        self.register(
+            "DATE",
+            FunctionDefinition::dated(
+                3,
+                3,
+                vec![ParamType::Number, ParamType::Number, ParamType::Number],
+                ReturnType::Number,
+                functions::date,
+            ),
+        );
+        self.register(
+            "TIME",
+            FunctionDefinition::dated(
+                3,
+                3,
+                vec![ParamType::Number, ParamType::Number, ParamType::Number],
+                ReturnType::Number,
+                functions::time,
+            ),
+        );
+        self.register(
+            "DATEVALUE",
+            FunctionDefinition::dated(
+                1,
+                1,
+                vec![ParamType::Text],
+                ReturnType::Number,
+                functions::datevalue,
+            ),
+        );
+        self.register(
+            "TIMEVALUE",
+            FunctionDefinition::dated(
+                1,
+                1,
+                vec![ParamType::Text],
+                ReturnType::Number,
+                functions::timevalue,
+            ),
+        );
+        self.register(
+            "YEAR",
+            FunctionDefinition::dated(
+                1,
+                1,
+                vec![ParamType::Any],
+                ReturnType::Number,
+                functions::year,
+            ),
+        );
+        self.register(
+            "MONTH",
+            FunctionDefinition::dated(
+                1,
+                1,
+                vec![ParamType::Any],
+                ReturnType::Number,
+                functions::month,
+            ),
+        );
+        self.register(
+            "DAY",
+            FunctionDefinition::dated(
+                1,
+                1,
+                vec![ParamType::Any],
+                ReturnType::Number,
+                functions::day,
+            ),
+        );
+        self.register(
+            "HOUR",
+            FunctionDefinition::dated(
+                1,
+                1,
+                vec![ParamType::Any],
+                ReturnType::Number,
+                functions::hour,
+            ),
+        );
+        self.register(
+            "MINUTE",
+            FunctionDefinition::dated(
+                1,
+                1,
+                vec![ParamType::Any],
+                ReturnType::Number,
+                functions::minute,
+            ),
+        );
+        self.register(
+            "SECOND",
+            FunctionDefinition::dated(
+                1,
+                1,
+                vec![ParamType::Any],
+                ReturnType::Number,
+                functions::second,
+            ),
+        );
+        self.register(
+            "WEEKDAY",
+            FunctionDefinition::dated(
+                1,
+                2,
+                vec![ParamType::Any, ParamType::Number],
+                ReturnType::Number,
+                functions::weekday,
+            ),
+        );
+        self.register(
+            "WEEKNUM",
+            FunctionDefinition::dated(
+                1,
+                2,
+                vec![ParamType::Any, ParamType::Number],
+                ReturnType::Number,
+                functions::weeknum,
+            ),
+        );
+        self.register(
+            "ISOWEEKNUM",
+            FunctionDefinition::dated(
+                1,
+                1,
+                vec![ParamType::Any],
+                ReturnType::Number,
+                functions::isoweeknum,
+            ),
+        );
+        self.register(
+            "EDATE",
+            FunctionDefinition::dated(
+                2,
+                2,
+                vec![ParamType::Any, ParamType::Number],
+                ReturnType::Number,
+                functions::edate,
+            ),
+        );
+        self.register(
+            "EOMONTH",
+            FunctionDefinition::dated(
+                2,
+                2,
+                vec![ParamType::Any, ParamType::Number],
+                ReturnType::Number,
+                functions::eomonth,
+            ),
+        );
+        self.register(
+            "DATEDIF",
+            FunctionDefinition::dated(
+                3,
+                3,
+                vec![ParamType::Any, ParamType::Any, ParamType::Text],
+                ReturnType::Number,
+                functions::datedif,
+            ),
+        );
+        self.register(
+            "DAYS",
+            FunctionDefinition::dated(
+                2,
+                2,
+                vec![ParamType::Any, ParamType::Any],
+                ReturnType::Number,
+                functions::days,
+            ),
+        );
+        self.register(
+            "DAYS360",
+            FunctionDefinition::dated(
+                2,
+                3,
+                vec![ParamType::Any, ParamType::Any, ParamType::Logical],
+                ReturnType::Number,
+                functions::days360,
+            ),
+        );
+        self.register(
+            "YEARFRAC",
+            FunctionDefinition::dated(
+                2,
+                3,
+                vec![ParamType::Any, ParamType::Any, ParamType::Number],
+                ReturnType::Number,
+                functions::yearfrac,
+            ),
+        );
+        self.register(
+            "NETWORKDAYS",
+            FunctionDefinition::dated(
+                2,
+                3,
+                vec![ParamType::Any, ParamType::Any, ParamType::Any],
+                ReturnType::Number,
+                functions::networkdays,
+            ),
+        );
+        self.register(
+            "NETWORKDAYS.INTL",
+            FunctionDefinition::dated(
+                2,
+                4,
+                vec![
+                    ParamType::Any,
+                    ParamType::Any,
+                    ParamType::Any,
+                    ParamType::Any,
+                ],
+                ReturnType::Number,
+                functions::networkdays_intl,
+            ),
+        );
+        self.register(
+            "WORKDAY",
+            FunctionDefinition::dated(
+                2,
+                3,
+                vec![ParamType::Any, ParamType::Number, ParamType::Any],
+                ReturnType::Number,
+                functions::workday,
+            ),
+        );
+        self.register(
+            "WORKDAY.INTL",
+            FunctionDefinition::dated(
+                2,
+                4,
+                vec![
+                    ParamType::Any,
+                    ParamType::Number,
+                    ParamType::Any,
+                    ParamType::Any,
+                ],
+                ReturnType::Number,
+                functions::workday_intl,
+            ),
+        );
+
+        // Conditional aggregation functions
+        self.register(
+            "SUMIF",
+            FunctionDefinition::dated(
+                2,
+                3,
+                vec![ParamType::Range, ParamType::Any, ParamType::Range],
+                ReturnType::Number,
+                functions::sumif,
+            ),
+        );
+        self.register(
+            "SUMIFS",
+            FunctionDefinition::dated(
+                3,
+                MAX_CRITERIA_ARGS,
+                vec![ParamType::Range, ParamType::Range, ParamType::Any],
+                ReturnType::Number,
+                functions::sumifs,
+            ),
+        );
+        self.register(
+            "COUNTIF",
+            FunctionDefinition::dated(
+                2,
+                2,
+                vec![ParamType::Range, ParamType::Any],
+                ReturnType::Number,
+                functions::countif,
+            ),
+        );
+        self.register(
+            "COUNTIFS",
+            FunctionDefinition::dated(
+                2,
+                MAX_CRITERIA_ARGS,
+                vec![ParamType::Range, ParamType::Any],
+                ReturnType::Number,
+                functions::countifs,
+            ),
+        );
+        self.register(
+            "AVERAGEIF",
+            FunctionDefinition::dated(
+                2,
+                3,
+                vec![ParamType::Range, ParamType::Any, ParamType::Range],
+                ReturnType::Number,
+                functions::averageif,
+            ),
+        );
+        self.register(
+            "AVERAGEIFS",
+            FunctionDefinition::dated(
+                3,
+                MAX_CRITERIA_ARGS,
+                vec![ParamType::Range, ParamType::Range, ParamType::Any],
+                ReturnType::Number,
+                functions::averageifs,
+            ),
+        );
+        self.register(
+            "MAXIFS",
+            FunctionDefinition::dated(
+                3,
+                MAX_CRITERIA_ARGS,
+                vec![ParamType::Range, ParamType::Range, ParamType::Any],
+                ReturnType::Number,
+                functions::maxifs,
+            ),
+        );
+        self.register(
+            "MINIFS",
+            FunctionDefinition::dated(
+                3,
+                MAX_CRITERIA_ARGS,
+                vec![ParamType::Range, ParamType::Range, ParamType::Any],
+                ReturnType::Number,
+                functions::minifs,
+            ),
+        );
+        self.register(
+            "SUMPRODUCT",
+            FunctionDefinition::variadic(
+                1,
+                ParamType::Range,
+                ReturnType::Number,
+                functions::sumproduct,
+            ),
+        );
+
+        // Lookup functions
+        self.register(
+            "VLOOKUP",
+            FunctionDefinition::range(
+                3,
+                4,
+                vec![ParamType::Any, ParamType::Range, ParamType::Number],
+                ReturnType::Any,
+                functions::vlookup,
+            ),
+        );
+        self.register(
+            "HLOOKUP",
+            FunctionDefinition::range(
+                3,
+                4,
+                vec![ParamType::Any, ParamType::Range, ParamType::Number],
+                ReturnType::Any,
+                functions::hlookup,
+            ),
+        );
+        self.register(
+            "INDEX",
+            FunctionDefinition::range(
+                2,
+                3,
+                vec![ParamType::Range, ParamType::Number],
+                ReturnType::Any,
+                functions::index,
+            ),
+        );
+        self.register(
+            "MATCH",
+            FunctionDefinition::range(
+                2,
+                3,
+                vec![ParamType::Any, ParamType::Range],
+                ReturnType::Number,
+                functions::match_fn,
+            ),
+        );
+        self.register(
+            "XLOOKUP",
+            FunctionDefinition::range(
+                3,
+                7,
+                vec![ParamType::Any, ParamType::Range, ParamType::Range],
+                ReturnType::Any,
+                functions::xlookup,
+            ),
+        );
+        self.register(
+            "OFFSET",
+            FunctionDefinition::range(
+                3,
+                5,
+                vec![ParamType::Range, ParamType::Number, ParamType::Number],
+                ReturnType::Range,
+                functions::offset,
+            ),
+        );
+
+        // Dynamic array functions
+        self.register(
+            "FILTER",
+            FunctionDefinition::range(
+                2,
+                3,
+                vec![ParamType::Range, ParamType::Range, ParamType::Any],
+                ReturnType::Range,
+                functions::filter,
+            ),
+        );
+        self.register(
+            "SORT",
+            FunctionDefinition::range(
+                1,
+                4,
+                vec![
+                    ParamType::Range,
+                    ParamType::Number,
+                    ParamType::Number,
+                    ParamType::Logical,
+                ],
+                ReturnType::Range,
+                functions::sort,
+            ),
+        );
+        self.register(
+            "SORTBY",
+            FunctionDefinition::variadic(2, ParamType::Any, ReturnType::Range, functions::sortby),
+        );
+        self.register(
+            "UNIQUE",
+            FunctionDefinition::range(
+                1,
+                3,
+                vec![ParamType::Range, ParamType::Logical, ParamType::Logical],
+                ReturnType::Range,
+                functions::unique,
+            ),
+        );
+        self.register(
+            "SEQUENCE",
+            FunctionDefinition::range(
+                1,
+                4,
+                vec![
+                    ParamType::Number,
+                    ParamType::Number,
+                    ParamType::Number,
+                    ParamType::Number,
+                ],
+                ReturnType::Range,
+                functions::sequence,
+            ),
+        );
+        self.register(
+            "RANDARRAY",
+            FunctionDefinition::range(
+                0,
+                5,
+                vec![
+                    ParamType::Number,
+                    ParamType::Number,
+                    ParamType::Number,
+                    ParamType::Number,
+                    ParamType::Logical,
+                ],
+                ReturnType::Range,
+                functions::randarray,
+            ),
+        );
+        self.register(
+            "TRANSPOSE",
+            FunctionDefinition::fixed(
+                vec![ParamType::Range],
+                ReturnType::Range,
+                functions::transpose,
+            ),
+        );
+        self.register(
+            "VSTACK",
+            FunctionDefinition::variadic(1, ParamType::Range, ReturnType::Range, functions::vstack),
+        );
+        self.register(
+            "HSTACK",
+            FunctionDefinition::variadic(1, ParamType::Range, ReturnType::Range, functions::hstack),
+        );
+
+        // Additional Math functions
+        self.register(
+            "ABS",
+            FunctionDefinition::fixed(vec![ParamType::Number], ReturnType::Number, functions::abs),
+        );
+        self.register(
+            "ROUND",
+            FunctionDefinition::range(
+                1,
+                2,
+                vec![ParamType::Number, ParamType::Number],
+                ReturnType::Number,
+                functions::round,
+            ),
+        );
+        self.register(
+            "ROUNDUP",
+            FunctionDefinition::range(
+                1,
+                2,
+                vec![ParamType::Number, ParamType::Number],
+                ReturnType::Number,
+                functions::roundup,
+            ),
+        );
+        self.register(
+            "ROUNDDOWN",
+            FunctionDefinition::range(
+                1,
+                2,
+                vec![ParamType::Number, ParamType::Number],
+                ReturnType::Number,
+                functions::rounddown,
+            ),
+        );
+        self.register(
+            "PRODUCT",
+            FunctionDefinition::variadic(
+                1,
+                ParamType::Number,
+                ReturnType::Number,
+                functions::product,
+            ),
+        );
+        self.register(
+            "MOD",
             FunctionDefinition::fixed(
                 vec![ParamType::Number, ParamType::Number],
                 ReturnType::Number,
@@ -906,6 +1991,232 @@ impl FunctionRegistry {
             "PROPER",
             FunctionDefinition::fixed(vec![ParamType::Text], ReturnType::Text, functions::proper),
         );
+        self.register(
+            "MID",
+            FunctionDefinition::fixed(
+                vec![ParamType::Text, ParamType::Number, ParamType::Number],
+                ReturnType::Text,
+                functions::mid,
+            ),
+        );
+        self.register(
+            "FIND",
+            FunctionDefinition::range(
+                2,
+                3,
+                vec![ParamType::Text, ParamType::Text, ParamType::Number],
+                ReturnType::Number,
+                functions::find,
+            ),
+        );
+        self.register(
+            "SEARCH",
+            FunctionDefinition::range(
+                2,
+                3,
+                vec![ParamType::Text, ParamType::Text, ParamType::Number],
+                ReturnType::Number,
+                functions::search,
+            ),
+        );
+        self.register(
+            "SUBSTITUTE",
+            FunctionDefinition::range(
+                3,
+                4,
+                vec![
+                    ParamType::Text,
+                    ParamType::Text,
+                    ParamType::Text,
+                    ParamType::Number,
+                ],
+                ReturnType::Text,
+                functions::substitute,
+            ),
+        );
+        self.register(
+            "REPLACE",
+            FunctionDefinition::fixed(
+                vec![
+                    ParamType::Text,
+                    ParamType::Number,
+                    ParamType::Number,
+                    ParamType::Text,
+                ],
+                ReturnType::Text,
+                functions::replace,
+            ),
+        );
+        self.register(
+            "REPT",
+            FunctionDefinition::fixed(
+                vec![ParamType::Text, ParamType::Number],
+                ReturnType::Text,
+                functions::rept,
+            ),
+        );
+        self.register(
+            "TEXTJOIN",
+            FunctionDefinition::variadic(3, ParamType::Any, ReturnType::Text, functions::textjoin),
+        );
+        self.register(
+            "TEXTBEFORE",
+            FunctionDefinition::range(
+                2,
+                6,
+                vec![
+                    ParamType::Text,
+                    ParamType::Any,
+                    ParamType::Number,
+                    ParamType::Number,
+                    ParamType::Logical,
+                    ParamType::Any,
+                ],
+                ReturnType::Any,
+                functions::textbefore,
+            ),
+        );
+        self.register(
+            "TEXTAFTER",
+            FunctionDefinition::range(
+                2,
+                6,
+                vec![
+                    ParamType::Text,
+                    ParamType::Any,
+                    ParamType::Number,
+                    ParamType::Number,
+                    ParamType::Logical,
+                    ParamType::Any,
+                ],
+                ReturnType::Any,
+                functions::textafter,
+            ),
+        );
+        self.register(
+            "TEXTSPLIT",
+            FunctionDefinition::range(
+                2,
+                6,
+                vec![
+                    ParamType::Text,
+                    ParamType::Any,
+                    ParamType::Any,
+                    ParamType::Logical,
+                    ParamType::Number,
+                    ParamType::Any,
+                ],
+                ReturnType::Any,
+                functions::textsplit,
+            ),
+        );
+        self.register(
+            "VALUE",
+            FunctionDefinition::fixed(
+                vec![ParamType::Any],
+                ReturnType::Number,
+                functions::value_fn,
+            ),
+        );
+        self.register(
+            "TEXT",
+            FunctionDefinition::fixed(
+                vec![ParamType::Any, ParamType::Text],
+                ReturnType::Text,
+                functions::text_fn,
+            ),
+        );
+        self.register(
+            "EXACT",
+            FunctionDefinition::fixed(
+                vec![ParamType::Text, ParamType::Text],
+                ReturnType::Logical,
+                functions::exact,
+            ),
+        );
+        self.register(
+            "CHAR",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number],
+                ReturnType::Text,
+                functions::char_fn,
+            ),
+        );
+        self.register(
+            "CODE",
+            FunctionDefinition::fixed(vec![ParamType::Text], ReturnType::Number, functions::code),
+        );
+        self.register(
+            "UNICHAR",
+            FunctionDefinition::fixed(
+                vec![ParamType::Number],
+                ReturnType::Text,
+                functions::unichar,
+            ),
+        );
+        self.register(
+            "UNICODE",
+            FunctionDefinition::fixed(
+                vec![ParamType::Text],
+                ReturnType::Number,
+                functions::unicode,
+            ),
+        );
+        self.register(
+            "CLEAN",
+            FunctionDefinition::fixed(vec![ParamType::Text], ReturnType::Text, functions::clean),
+        );
+        self.register(
+            "REGEXMATCH",
+            FunctionDefinition::range(
+                2,
+                3,
+                vec![ParamType::Text, ParamType::Text, ParamType::Number],
+                ReturnType::Logical,
+                functions::regexmatch,
+            ),
+        );
+        self.register(
+            "REGEXTEST",
+            FunctionDefinition::range(
+                2,
+                3,
+                vec![ParamType::Text, ParamType::Text, ParamType::Number],
+                ReturnType::Logical,
+                functions::regexmatch,
+            ),
+        );
+        self.register(
+            "REGEXEXTRACT",
+            FunctionDefinition::range(
+                2,
+                4,
+                vec![
+                    ParamType::Text,
+                    ParamType::Text,
+                    ParamType::Number,
+                    ParamType::Number,
+                ],
+                ReturnType::Any,
+                functions::regexextract,
+            ),
+        );
+        self.register(
+            "REGEXREPLACE",
+            FunctionDefinition::range(
+                3,
+                5,
+                vec![
+                    ParamType::Text,
+                    ParamType::Text,
+                    ParamType::Text,
+                    ParamType::Number,
+                    ParamType::Number,
+                ],
+                ReturnType::Text,
+                functions::regexreplace,
+            ),
+        );
 
         // Information functions
         self.register(
@@ -992,7 +2303,16 @@ pub struct FunctionDefinition {
     pub min_args: usize,
     pub max_args: Option<usize>,
     pub metadata: FunctionMetadata,
-    pub eval: FunctionImpl,
+    pub eval: FunctionEval,
+}
+
+/// How a registered function is evaluated.
+#[derive(Debug, Clone, Copy)]
+pub enum FunctionEval {
+    /// Depends only on its arguments.
+    Plain(FunctionImpl),
+    /// Reads or returns serial dates, so also needs the date system.
+    Dated(DateFunctionImpl),
 }
 
 impl FunctionDefinition {
@@ -1007,36 +2327,56 @@ impl FunctionDefinition {
                 variadic: None,
                 return_type,
             },
-            eval,
+            eval: FunctionEval::Plain(eval),
         }
     }
 
     /// Variable number of arguments
     pub fn variadic(
         min: usize,
-        variadic: ParamType,
+        variadic: ParamType,
+        return_type: ReturnType,
+        eval: FunctionImpl,
+    ) -> Self {
+        Self {
+            min_args: min,
+            max_args: None,
+            metadata: FunctionMetadata {
+                params: Vec::new(),
+                variadic: Some(variadic),
+                return_type,
+            },
+            eval: FunctionEval::Plain(eval),
+        }
+    }
+
+    /// Range of arguments
+    pub fn range(
+        min: usize,
+        max: usize,
+        params: Vec<ParamType>,
         return_type: ReturnType,
         eval: FunctionImpl,
     ) -> Self {
         Self {
             min_args: min,
-            max_args: None,
+            max_args: Some(max),
             metadata: FunctionMetadata {
-                params: Vec::new(),
-                variadic: Some(variadic),
+                params,
+                variadic: None,
                 return_type,
             },
-            eval,
+            eval: FunctionEval::Plain(eval),
         }
     }
 
-    /// Range of arguments
-    pub fn range(
+    /// Range of arguments, for a function over serial dates
+    pub fn dated(
         min: usize,
         max: usize,
         params: Vec<ParamType>,
         return_type: ReturnType,
-        eval: FunctionImpl,
+        eval: DateFunctionImpl,
     ) -> Self {
         Self {
             min_args: min,
@@ -1046,7 +2386,16 @@ impl FunctionDefinition {
                 variadic: None,
                 return_type,
             },
-            eval,
+            eval: FunctionEval::Dated(eval),
+        }
+    }
+
+    /// Evaluate with already evaluated arguments, reading and returning
+    /// serial dates in `system`.
+    pub fn call(&self, args: &[Value], system: DateSystem) -> Value {
+        match self.eval {
+            FunctionEval::Plain(eval) => eval(args),
+            FunctionEval::Dated(eval) => eval(args, system),
         }
     }
 
@@ -1092,6 +2441,21 @@ pub enum FormulaError {
 pub trait SheetIdResolver {
     /// Return a numeric sheet ID for a sheet name, or None if unknown.
     fn sheet_id(&self, sheet_name: &str) -> Option<u32>;
+    /// Return what a defined name visible from a sheet refers to (e.g.
+    /// `Sheet1!$A$1:$A$10`), so references through names become dependencies.
+    fn defined_name(&self, _sheet_id: u32, _name: &str) -> Option<String> {
+        None
+    }
+    /// Locate a table by name, or when `name` is `None` the table on the
+    /// sheet that contains `cell`, so structured references become dependencies.
+    fn table(
+        &self,
+        _sheet_id: u32,
+        _name: Option<&str>,
+        _cell: Option<CellAddress>,
+    ) -> Option<TableLocation> {
+        None
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -1117,6 +2481,9 @@ pub enum ReturnType {
 /// Function implementation signature used by the registry.
 pub type FunctionImpl = fn(&[Value]) -> Value;
 
+/// Signature of functions that read or return serial dates.
+pub type DateFunctionImpl = fn(&[Value], DateSystem) -> Value;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 /// Metadata describing a formula function.
 pub struct FunctionMetadata {
@@ -1138,6 +2505,30 @@ pub trait ValueResolver {
     fn get_sheet_range(&self, _sheet: &str, range: &CellRange) -> Vec<Value> {
         self.get_range(range)
     }
+    /// Whether `sheet` exists; references to a missing sheet evaluate to `#REF!`.
+    fn has_sheet(&self, _sheet: &str) -> bool {
+        true
+    }
+    /// What a defined name refers to, looked up from `sheet` or, when `None`,
+    /// the current sheet. Unknown names evaluate to `#NAME?`.
+    fn defined_name(&self, _sheet: Option<&str>, _name: &str) -> Option<String> {
+        None
+    }
+    /// Locate a table by name, or when `None` the table containing the current
+    /// cell. Unresolvable structured references evaluate to `#REF!`.
+    fn table(&self, _name: Option<&str>) -> Option<TableLocation> {
+        None
+    }
+    /// The cells the formula at `anchor` spills into (on `sheet`, or the
+    /// current sheet), for `A1#` references. `None` evaluates to `#REF!`.
+    fn spill_range(&self, _sheet: Option<&str>, _anchor: &CellAddress) -> Option<CellRange> {
+        None
+    }
+    /// The workbook's date system, which date functions read and return
+    /// serials in.
+    fn date_system(&self) -> DateSystem {
+        DateSystem::Excel1900
+    }
 }
 
 #[derive(Default)]
@@ -1145,6 +2536,7 @@ pub trait ValueResolver {
 pub struct EvalContext {
     cells: HashMap<CellAddress, Value>,
     ranges: HashMap<CellRange, Vec<Value>>,
+    date_system: DateSystem,
 }
 
 impl EvalContext {
@@ -1152,17 +2544,23 @@ impl EvalContext {
     pub fn with_cells(cells: HashMap<CellAddress, Value>) -> Self {
         Self {
             cells,
-            ranges: HashMap::new(),
+            ..Self::default()
         }
     }
 
     /// Build a context from a map of range values.
     pub fn with_ranges(ranges: HashMap<CellRange, Vec<Value>>) -> Self {
         Self {
-            cells: HashMap::new(),
             ranges,
+            ..Self::default()
         }
     }
+
+    /// Evaluate date functions in `system` instead of the 1900 system.
+    pub fn with_date_system(mut self, system: DateSystem) -> Self {
+        self.date_system = system;
+        self
+    }
 }
 
 impl ValueResolver for EvalContext {
@@ -1195,6 +2593,10 @@ impl ValueResolver for EvalContext {
 
         values.clone()
     }
+
+    fn date_system(&self) -> DateSystem {
+        self.date_system
+    }
 }
 
 fn collect_dependencies(
@@ -1300,8 +2702,41 @@ fn collect_dependencies(
                 // R1C1 ranges need a base cell; resolve during evaluation.
             }
         }
-        FormulaExpr::FunctionCall { args, .. } => {
-            for arg in args {
+        FormulaExpr::SpillRef {
+            sheet: None,
+            anchor,
+        } => deps.push(FormulaDependency::Cell {
+            sheet_id,
+            addr: *anchor,
+        }),
+        FormulaExpr::SpillRef {
+            sheet: Some(sheet),
+            anchor,
+        } => {
+            // Spilled cells depend on their anchor, so depending on the anchor
+            // orders this formula after the whole spill range.
+            if let Some(resolver) = resolver.and_then(|r| r.sheet_id(sheet)) {
+                deps.push(FormulaDependency::Cell {
+                    sheet_id: resolver,
+                    addr: *anchor,
+                });
+            } else {
+                deps.push(FormulaDependency::SheetCell {
+                    sheet: sheet.clone(),
+                    addr: *anchor,
+                });
+            }
+        }
+        FormulaExpr::Name { .. } | FormulaExpr::TableRef { .. } => {
+            // Names and table references are expanded before dependencies are collected.
+        }
+        FormulaExpr::FunctionCall { name, args } => {
+            let sum_range = implicit_sum_range(name, args);
+            for (index, arg) in args.iter().enumerate() {
+                let arg = match &sum_range {
+                    Some(resized) if index == 2 => resized,
+                    _ => arg,
+                };
                 collect_dependencies(arg, sheet_id, resolver, base_cell, deps);
             }
         }
@@ -1315,6 +2750,159 @@ fn collect_dependencies(
     }
 }
 
+/// The sum range of SUMIF or AVERAGEIF resized to the criteria range, as
+/// Excel reads it: `SUMIF(A1:A3, ">1", B1)` sums B1:B3. `None` when the
+/// call has no sum range or either range is not a plain reference.
+fn implicit_sum_range(name: &str, args: &[FormulaExpr]) -> Option<FormulaExpr> {
+    if !name.eq_ignore_ascii_case("SUMIF") && !name.eq_ignore_ascii_case("AVERAGEIF") {
+        return None;
+    }
+    let [criteria_range, _, sum_range] = args else {
+        return None;
+    };
+    let (rows, cols) = match criteria_range {
+        FormulaExpr::RangeRef(range) | FormulaExpr::SheetRangeRef { range, .. } => {
+            (range.rows(), range.cols())
+        }
+        _ => return None,
+    };
+    let resized = |start: CellAddress| {
+        let end = CellAddress::new(
+            start.row.saturating_add(rows - 1),
+            start.col.saturating_add(cols - 1),
+        );
+        CellRange::new(start, end)
+    };
+    match sum_range {
+        FormulaExpr::CellRef(addr) => Some(FormulaExpr::RangeRef(resized(*addr))),
+        FormulaExpr::RangeRef(range) => {
+            Some(FormulaExpr::RangeRef(resized(range.normalized().start)))
+        }
+        FormulaExpr::SheetCellRef { sheet, addr } => Some(FormulaExpr::SheetRangeRef {
+            sheet: sheet.clone(),
+            range: resized(*addr),
+        }),
+        FormulaExpr::SheetRangeRef { sheet, range } => Some(FormulaExpr::SheetRangeRef {
+            sheet: sheet.clone(),
+            range: resized(range.normalized().start),
+        }),
+        _ => None,
+    }
+}
+
+/// Lookups used to expand defined names and structured table references.
+struct RefLookup<'a> {
+    names: &'a dyn Fn(Option<&str>, &str) -> Option<String>,
+    tables: &'a dyn Fn(Option<&str>) -> Option<TableLocation>,
+    current: Option<CellAddress>,
+}
+
+impl RefLookup<'_> {
+    /// Replace defined names and table references with the ranges they refer
+    /// to, recursively. Names that are unknown, refer to themselves, or do not
+    /// parse become `#NAME?` unless they name a table; table references that
+    /// do not resolve become `#REF!`.
+    /// Names bound by enclosing LET and LAMBDA expressions (`locals`) are
+    /// left as they are.
+    fn expand(
+        &self,
+        expr: &FormulaExpr,
+        visiting: &mut Vec<String>,
+        locals: &mut Vec<String>,
+    ) -> FormulaExpr {
+        match expr {
+            FormulaExpr::Name { sheet: None, name }
+                if locals.iter().any(|local| local.eq_ignore_ascii_case(name)) =>
+            {
+                expr.clone()
+            }
+            FormulaExpr::Name { sheet, name } => {
+                let key = name.to_lowercase();
+                let definition = if visiting.contains(&key) {
+                    None
+                } else {
+                    (self.names)(sheet.as_deref(), name)
+                        .and_then(|text| parser::parse_formula(&text).ok())
+                };
+                let Some(definition) = definition else {
+                    if sheet.is_none() {
+                        if let Some(table) = (self.tables)(Some(name)) {
+                            return self.table_range(&table, TableArea::Data, None);
+                        }
+                    }
+                    return FormulaExpr::Literal(Value::Error(ErrorValue::Name));
+                };
+                visiting.push(key);
+                // A definition cannot see the locals where the name is used
+                let expanded = self.expand(&definition, visiting, &mut Vec::new());
+                visiting.pop();
+                expanded
+            }
+            FormulaExpr::TableRef {
+                table,
+                area,
+                columns,
+            } => match (self.tables)(table.as_deref()) {
+                Some(location) => self.table_range(
+                    &location,
+                    *area,
+                    columns.as_ref().map(|(a, b)| (a.as_str(), b.as_str())),
+                ),
+                None => FormulaExpr::Literal(Value::Error(ErrorValue::Ref)),
+            },
+            FormulaExpr::FunctionCall { name, args } => {
+                let outer = locals.len();
+                let mut expanded = Vec::with_capacity(args.len());
+                for (idx, arg) in args.iter().enumerate() {
+                    match lambda::bound_param(name, args, idx) {
+                        Some(param) => {
+                            locals.push(param.to_string());
+                            expanded.push(arg.clone());
+                        }
+                        None => expanded.push(self.expand(arg, visiting, locals)),
+                    }
+                }
+                locals.truncate(outer);
+                FormulaExpr::FunctionCall {
+                    name: name.clone(),
+                    args: expanded,
+                }
+            }
+            FormulaExpr::BinaryOp { op, left, right } => FormulaExpr::BinaryOp {
+                op: *op,
+                left: Box::new(self.expand(left, visiting, locals)),
+                right: Box::new(self.expand(right, visiting, locals)),
+            },
+            FormulaExpr::UnaryOp { op, expr } => FormulaExpr::UnaryOp {
+                op: *op,
+                expr: Box::new(self.expand(expr, visiting, locals)),
+            },
+            other => other.clone(),
+        }
+    }
+
+    fn table_range(
+        &self,
+        table: &TableLocation,
+        area: TableArea,
+        columns: Option<(&str, &str)>,
+    ) -> FormulaExpr {
+        let Some(range) = table.resolve(area, columns, self.current) else {
+            return FormulaExpr::Literal(Value::Error(ErrorValue::Ref));
+        };
+        let single = range.start == range.end;
+        match (table.sheet.clone(), single) {
+            (None, true) => FormulaExpr::CellRef(range.start),
+            (None, false) => FormulaExpr::RangeRef(range),
+            (Some(sheet), true) => FormulaExpr::SheetCellRef {
+                sheet,
+                addr: range.start,
+            },
+            (Some(sheet), false) => FormulaExpr::SheetRangeRef { sheet, range },
+        }
+    }
+}
+
 fn hash_formula(formula: &str) -> u64 {
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
     formula.hash(&mut hasher);
@@ -1322,6 +2910,9 @@ fn hash_formula(formula: &str) -> u64 {
 }
 
 fn eval_unary(op: UnaryOperator, value: Value) -> Value {
+    if let (UnaryOperator::Negate | UnaryOperator::Percent, Value::Array(_)) = (op, &value) {
+        return array_map(&value, &|item| eval_unary(op, item.clone()));
+    }
     match op {
         UnaryOperator::Negate => match value {
             Value::Int(n) => Value::Int(-n),
@@ -1344,6 +2935,10 @@ fn eval_unary(op: UnaryOperator, value: Value) -> Value {
 }
 
 fn eval_binary(op: BinaryOperator, left: Value, right: Value) -> Value {
+    let logical = matches!(op, BinaryOperator::And | BinaryOperator::Or);
+    if !logical && (matches!(left, Value::Array(_)) || matches!(right, Value::Array(_))) {
+        return array_broadcast(&left, &right, |l, r| eval_binary(op, l, r));
+    }
     match op {
         BinaryOperator::Add => numeric_op(left, right, |l, r| l + r),
         BinaryOperator::Subtract => numeric_op(left, right, |l, r| l - r),
@@ -1380,6 +2975,54 @@ fn eval_binary(op: BinaryOperator, left: Value, right: Value) -> Value {
     }
 }
 
+/// Apply `f` to every element of an array value, keeping its shape.
+fn array_map(value: &Value, f: &dyn Fn(&Value) -> Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| match item {
+                    Value::Array(_) => array_map(item, f),
+                    other => f(other),
+                })
+                .collect(),
+        ),
+        other => f(other),
+    }
+}
+
+/// Combine two values element by element, like Excel's array arithmetic: a
+/// scalar, single row or single column repeats to the other operand's size,
+/// and cells outside both operands are `#N/A`.
+fn array_broadcast(left: &Value, right: &Value, f: impl Fn(Value, Value) -> Value) -> Value {
+    let left = functions::to_grid(left);
+    let right = functions::to_grid(right);
+    let rows = left.len().max(right.len());
+    let cols = left
+        .iter()
+        .chain(right.iter())
+        .map(Vec::len)
+        .max()
+        .unwrap_or(0);
+    let at = |grid: &Vec<Vec<Value>>, row: usize, col: usize| {
+        let row = if grid.len() == 1 { 0 } else { row };
+        let cells = grid.get(row)?;
+        let col = if cells.len() == 1 { 0 } else { col };
+        cells.get(col).cloned()
+    };
+    let out = (0..rows)
+        .map(|row| {
+            (0..cols)
+                .map(|col| match (at(&left, row, col), at(&right, row, col)) {
+                    (Some(l), Some(r)) => f(l, r),
+                    _ => Value::Error(ErrorValue::NA),
+                })
+                .collect()
+        })
+        .collect();
+    functions::from_grid(out)
+}
+
 fn numeric_op(left: Value, right: Value, op: fn(f64, f64) -> f64) -> Value {
     match (left, right) {
         (Value::Int(l), Value::Int(r)) => Value::Float(op(l as f64, r as f64)),
@@ -1581,7 +3224,11 @@ mod tests {
         );
         let mut cells = HashMap::new();
         cells.insert(CellAddress::new(0, 0), Value::Int(2));
-        let ctx = EvalContext { cells, ranges };
+        let ctx = EvalContext {
+            cells,
+            ranges,
+            ..EvalContext::default()
+        };
         let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
         assert!(matches!(value, Value::Float(f) if (f - 7.0).abs() < 1e-9));
     }
@@ -1754,4 +3401,603 @@ mod tests {
         let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
         assert_eq!(value, Value::Error(ErrorValue::Value));
     }
+
+    /// Cells of an [`EvalContext`] plus a table of defined names.
+    struct NamedContext {
+        cells: EvalContext,
+        names: HashMap<String, String>,
+    }
+
+    impl ValueResolver for NamedContext {
+        fn get_cell(&self, addr: &CellAddress) -> Value {
+            self.cells.get_cell(addr)
+        }
+        fn get_range(&self, range: &CellRange) -> Vec<Value> {
+            self.cells.get_range(range)
+        }
+        fn defined_name(&self, _sheet: Option<&str>, name: &str) -> Option<String> {
+            self.names.get(&name.to_lowercase()).cloned()
+        }
+    }
+
+    impl SheetIdResolver for NamedContext {
+        fn sheet_id(&self, _sheet_name: &str) -> Option<u32> {
+            None
+        }
+        fn defined_name(&self, _sheet_id: u32, name: &str) -> Option<String> {
+            ValueResolver::defined_name(self, None, name)
+        }
+    }
+
+    #[test]
+    fn test_defined_names() {
+        let mut cells = HashMap::new();
+        cells.insert(CellAddress::new(0, 0), Value::Int(200));
+        cells.insert(CellAddress::new(0, 1), Value::Float(0.5));
+        let names = [
+            ("price", "$A$1"),
+            ("rate", "$B$1"),
+            ("total", "Price*Rate"),
+            ("loop", "Loop+1"),
+        ];
+        let ctx = NamedContext {
+            cells: EvalContext::with_cells(cells),
+            names: names
+                .iter()
+                .map(|(name, text)| (name.to_string(), text.to_string()))
+                .collect(),
+        };
+
+        let mut engine = FormulaEngine::new();
+        let compiled = engine
+            .compile_with_context("=Total+1", 0, Some(&ctx), None)
+            .unwrap();
+        assert!(compiled.dependencies.contains(&FormulaDependency::Cell {
+            sheet_id: 0,
+            addr: CellAddress::new(0, 1),
+        }));
+        let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+        assert!(matches!(value, Value::Float(f) if (f - 101.0).abs() < 1e-9));
+
+        for formula in ["=Missing", "=Loop"] {
+            let compiled = engine.compile(formula).unwrap();
+            let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+            assert_eq!(value, Value::Error(ErrorValue::Name), "{formula}");
+        }
+    }
+
+    #[test]
+    fn test_statistical_functions_by_name() {
+        let data: Vec<Value> = [2, 4, 4, 4, 5, 5, 7, 9].map(Value::Int).into();
+        let mut padded = data.clone();
+        padded.extend([Value::Empty, Value::Empty]);
+        let column =
+            |rows: u32| CellRange::new(CellAddress::new(0, 0), CellAddress::new(rows - 1, 0));
+        let ctx =
+            EvalContext::with_ranges(HashMap::from([(column(8), data), (column(10), padded)]));
+        let mut engine = FormulaEngine::new();
+        let cases = [
+            ("=STDEV.P(A1:A8)", 2.0),
+            ("=MEDIAN(A1:A8)", 4.5),
+            ("=MODE.SNGL(A1:A8)", 4.0),
+            ("=QUARTILE(A1:A8, 3)", 5.5),
+            ("=RANK.AVG(4, A1:A8)", 6.0),
+            ("=T.DIST.2T(1, 2)", 0.422_649_730_810_374),
+            ("=NORM.S.DIST(0, TRUE)", 0.5),
+            ("=COUNTA(A1:A10)", 8.0),
+            ("=COUNTBLANK(A1:A10)", 2.0),
+        ];
+        for (formula, expected) in cases {
+            let compiled = engine.compile(formula).unwrap();
+            let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+            let actual = match value {
+                Value::Int(n) => n as f64,
+                Value::Float(f) => f,
+                other => panic!("{formula}: {other:?}"),
+            };
+            assert!((actual - expected).abs() < 1e-9, "{formula}: {actual}");
+        }
+    }
+
+    #[test]
+    fn test_text_functions_by_name() {
+        let ctx = EvalContext::default();
+        let mut engine = FormulaEngine::new();
+        let text = |s: &str| Value::String(s.to_string());
+        let cases = [
+            ("=MID(\"abcdef\", 2, 3)", text("bcd")),
+            ("=FIND(\"c\", \"abcabc\", 4)", Value::Int(6)),
+            ("=TEXTJOIN(\"-\", TRUE, \"a\", \"\", \"b\")", text("a-b")),
+            ("=TEXTAFTER(\"key=value\", \"=\")", text("value")),
+            ("=REGEXREPLACE(\"a1b22\", \"[0-9]+\", \"#\")", text("a#b#")),
+            ("=VALUE(\"1,000\")+1", Value::Float(1001.0)),
+            ("=EXACT(UPPER(\"a\"), CHAR(65))", Value::Bool(true)),
+        ];
+        for (formula, expected) in cases {
+            let compiled = engine.compile(formula).unwrap();
+            let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+            assert_eq!(value, expected, "{formula}");
+        }
+
+        // TEXT formats exactly as cell display does
+        for (number, format) in [(1234.5, "#,##0.00"), (0.125, "0.0%"), (45000.0, "d-mmm-yy")] {
+            let compiled = engine
+                .compile(&format!("=TEXT({number}, \"{format}\")"))
+                .unwrap();
+            let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+            let display = piptable_utils::format_value(&Value::Float(number), Some(format));
+            assert_eq!(value, Value::String(display), "{format}");
+        }
+    }
+
+    #[test]
+    fn test_date_functions_by_name() {
+        let holidays = CellRange::new(CellAddress::new(0, 0), CellAddress::new(1, 0));
+        let ctx = EvalContext::with_ranges(HashMap::from([(
+            holidays,
+            vec![Value::Float(45306.0), Value::Float(45307.0)],
+        )]));
+        let mut engine = FormulaEngine::new();
+        let cases = [
+            ("=YEAR(DATE(2024, 1, 15))", Value::Int(2024)),
+            ("=WEEKDAY(\"2024-01-15\", 2)", Value::Int(1)),
+            ("=EOMONTH(DATE(2024, 1, 15), 1)", Value::Float(45351.0)),
+            (
+                "=DATEDIF(\"2020-03-15\", \"2024-01-10\", \"M\")",
+                Value::Int(45),
+            ),
+            (
+                "=NETWORKDAYS(DATE(2024, 1, 15), DATE(2024, 1, 19), A1:A2)",
+                Value::Int(3),
+            ),
+            (
+                "=WORKDAY.INTL(DATE(2024, 1, 12), 1, 1, A1:A2)",
+                Value::Float(45308.0),
+            ),
+            ("=HOUR(TIME(18, 30, 0))", Value::Int(18)),
+            ("=DAY(60)", Value::Int(29)),
+        ];
+        for (formula, expected) in cases {
+            let compiled = engine.compile(formula).unwrap();
+            let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+            assert_eq!(value, expected, "{formula}");
+        }
+
+        // The same formulas read and return 1904 serials in a 1904 context
+        let ctx = EvalContext::default().with_date_system(DateSystem::Excel1904);
+        let cases = [
+            ("=DATE(2024, 1, 15)", Value::Float(45306.0 - 1462.0)),
+            ("=YEAR(0)", Value::Int(1904)),
+            ("=DAY(60)", Value::Int(1)),
+            ("=DATEVALUE(\"1904-01-02\")", Value::Float(1.0)),
+        ];
+        for (formula, expected) in cases {
+            let compiled = engine.compile(formula).unwrap();
+            let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+            assert_eq!(value, expected, "{formula}");
+        }
+    }
+
+    #[test]
+    fn test_conditional_functions_by_name() {
+        let column = |col: u32| CellRange::new(CellAddress::new(0, col), CellAddress::new(3, col));
+        let ctx = EvalContext {
+            cells: HashMap::from([(CellAddress::new(0, 3), Value::String("<3".to_string()))]),
+            ranges: HashMap::from([
+                (
+                    column(0),
+                    vec![Value::Int(1), Value::Int(2), Value::Int(3), Value::Int(4)],
+                ),
+                (
+                    column(1),
+                    vec![
+                        Value::String("East".to_string()),
+                        Value::String("West".to_string()),
+                        Value::String("East".to_string()),
+                        Value::String("Eastern".to_string()),
+                    ],
+                ),
+            ]),
+            ..EvalContext::default()
+        };
+        let mut engine = FormulaEngine::new();
+        let cases = [
+            ("=SUMIF(A1:A4, \">=2\")", Value::Float(9.0)),
+            ("=SUMIF(B1:B4, \"east\", A1:A4)", Value::Float(4.0)),
+            // The sum range takes the criteria range's size from its first cell
+            ("=SUMIF(B1:B4, \"East*\", A1)", Value::Float(8.0)),
+            ("=COUNTIF(A1:A4, D1)", Value::Int(2)),
+            ("=COUNTIFS(A1:A4, \">1\", B1:B4, \"East\")", Value::Int(1)),
+            (
+                "=AVERAGEIF(B1:B4, \"<>West\", A1:A4)",
+                Value::Float(8.0 / 3.0),
+            ),
+            ("=MAXIFS(A1:A4, B1:B4, \"East\")", Value::Float(3.0)),
+            ("=MINIFS(A1:A4, B1:B4, \"?est\")", Value::Float(2.0)),
+            (
+                "=SUMIFS(A1:A4, A1:A4, \">1\", A1:A4, \"<4\")",
+                Value::Float(5.0),
+            ),
+            ("=SUMPRODUCT(A1:A4, A1:A4)", Value::Float(30.0)),
+        ];
+        for (formula, expected) in cases {
+            let compiled = engine.compile(formula).unwrap();
+            let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+            assert_eq!(value, expected, "{formula}");
+        }
+
+        // The resized sum range is a dependency, so editing B3 recalculates C1
+        let c1 = CellAddress::new(0, 2);
+        let b3 = CellAddress::new(2, 1);
+        engine
+            .set_formula(c1, "=SUMIF(A1:A3, \">1\", B1)")
+            .expect("set ok");
+        engine.mark_dirty(&b3);
+        let dirty = engine.get_dirty_nodes().expect("dirty nodes");
+        assert!(dirty.contains(&c1));
+    }
+
+    fn named_context(names: &[(&str, &str)]) -> NamedContext {
+        NamedContext {
+            cells: EvalContext::default(),
+            names: names
+                .iter()
+                .map(|(name, text)| (name.to_string(), text.to_string()))
+                .collect(),
+        }
+    }
+
+    fn grid(rows: &[&[f64]]) -> Value {
+        Value::Array(
+            rows.iter()
+                .map(|row| Value::Array(row.iter().map(|n| Value::Float(*n)).collect()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_let_binds_names_lexically() {
+        let ctx = named_context(&[("price", "200")]);
+        let mut engine = FormulaEngine::new();
+        let cases = [
+            ("=LET(x, 2, y, x*3, x+y)", Value::Float(8.0)),
+            ("=LET(Price, 5, Price*2)", Value::Float(10.0)),
+            ("=LET(x, 1, LET(x, 2, x)+x)", Value::Float(3.0)),
+            (
+                "=LET(k, 10, add, LAMBDA(n, n+k), k, 1, add(5))",
+                Value::Float(15.0),
+            ),
+            ("=LET(sq, LAMBDA(n, n*n), sq(4))", Value::Float(16.0)),
+            (
+                "=LET(f, LAMBDA(a, b, a+b), f(1))",
+                Value::Error(ErrorValue::Value),
+            ),
+            ("=LET(1, 2, 3)", Value::Error(ErrorValue::Value)),
+            ("=LAMBDA(x, x)", Value::Error(ErrorValue::Calc)),
+            ("=LAMBDA(x, x, x)", Value::Error(ErrorValue::Value)),
+        ];
+        for (formula, expected) in cases {
+            let compiled = engine.compile(formula).unwrap();
+            let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+            assert_eq!(value, expected, "{formula}");
+        }
+
+        let compiled = engine.compile("=LET(x, 1)").unwrap();
+        let err = engine.evaluate(&compiled, &ctx).unwrap_err();
+        assert!(matches!(err, FormulaError::InvalidArgCount(name, _, 2) if name == "LET"));
+    }
+
+    #[test]
+    fn test_lambda_helper_functions() {
+        let ctx = EvalContext::default();
+        let mut engine = FormulaEngine::new();
+        let cases = [
+            (
+                "=MAP(SEQUENCE(2, 2), LAMBDA(v, v*10))",
+                grid(&[&[10.0, 20.0], &[30.0, 40.0]]),
+            ),
+            (
+                "=REDUCE(0, SEQUENCE(4), LAMBDA(a, v, a+v))",
+                Value::Float(10.0),
+            ),
+            (
+                "=SCAN(0, SEQUENCE(1, 3), LAMBDA(a, v, a+v))",
+                grid(&[&[1.0, 3.0, 6.0]]),
+            ),
+            (
+                "=BYROW(SEQUENCE(2, 3), LAMBDA(r, SUM(r)))",
+                grid(&[&[6.0], &[15.0]]),
+            ),
+            (
+                "=BYCOL(SEQUENCE(2, 3), LAMBDA(c, SUM(c)))",
+                grid(&[&[5.0, 7.0, 9.0]]),
+            ),
+            (
+                "=MAKEARRAY(2, 2, LAMBDA(r, c, r*c))",
+                grid(&[&[1.0, 2.0], &[2.0, 4.0]]),
+            ),
+            ("=MAP(SEQUENCE(2), 1)", Value::Error(ErrorValue::Value)),
+            (
+                "=BYROW(SEQUENCE(2, 2), LAMBDA(r, r))",
+                Value::Array(vec![
+                    Value::Array(vec![Value::Error(ErrorValue::Calc)]),
+                    Value::Array(vec![Value::Error(ErrorValue::Calc)]),
+                ]),
+            ),
+        ];
+        for (formula, expected) in cases {
+            let compiled = engine.compile(formula).unwrap();
+            let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+            assert_eq!(value, expected, "{formula}");
+        }
+    }
+
+    #[test]
+    fn test_defined_name_lambda_is_custom_function() {
+        let ctx = named_context(&[
+            ("double", "LAMBDA(x, x*2)"),
+            ("fact", "LAMBDA(n, IF(n<=1, 1, n*Fact(n-1)))"),
+            ("forever", "LAMBDA(n, Forever(n+1))"),
+        ]);
+        let mut engine = FormulaEngine::new();
+        let cases = [
+            ("=Double(21)", Value::Float(42.0)),
+            ("=Fact(5)", Value::Float(120.0)),
+            ("=Forever(1)", Value::Error(ErrorValue::Num)),
+            ("=MAP(SEQUENCE(3), Double)", grid(&[&[2.0], &[4.0], &[6.0]])),
+        ];
+        for (formula, expected) in cases {
+            let compiled = engine.compile(formula).unwrap();
+            let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+            assert_eq!(value, expected, "{formula}");
+        }
+
+        let compiled = engine.compile("=Missing(1)").unwrap();
+        let err = engine.evaluate(&compiled, &ctx).unwrap_err();
+        assert!(matches!(err, FormulaError::UnknownFunction(_)));
+    }
+
+    struct TableContext {
+        cells: HashMap<CellAddress, Value>,
+        table: TableLocation,
+        current: Option<CellAddress>,
+    }
+
+    impl ValueResolver for TableContext {
+        fn get_cell(&self, addr: &CellAddress) -> Value {
+            self.cells.get(addr).cloned().unwrap_or(Value::Empty)
+        }
+        fn get_range(&self, range: &CellRange) -> Vec<Value> {
+            (range.start.row..=range.end.row)
+                .map(|row| {
+                    Value::Array(
+                        (range.start.col..=range.end.col)
+                            .map(|col| self.get_cell(&CellAddress::new(row, col)))
+                            .collect(),
+                    )
+                })
+                .collect()
+        }
+        fn current_cell(&self) -> Option<CellAddress> {
+            self.current
+        }
+        fn table(&self, name: Option<&str>) -> Option<TableLocation> {
+            match name {
+                Some(name) if !name.eq_ignore_ascii_case(&self.table.name) => None,
+                Some(_) => Some(self.table.clone()),
+                None => self
+                    .current
+                    .filter(|cell| self.table.contains(cell))
+                    .map(|_| self.table.clone()),
+            }
+        }
+    }
+
+    impl SheetIdResolver for TableContext {
+        fn sheet_id(&self, _sheet_name: &str) -> Option<u32> {
+            None
+        }
+        fn table(
+            &self,
+            _sheet_id: u32,
+            name: Option<&str>,
+            _cell: Option<CellAddress>,
+        ) -> Option<TableLocation> {
+            ValueResolver::table(self, name)
+        }
+    }
+
+    #[test]
+    fn test_structured_references() {
+        // Header in row 1 (Item, Qty, Price), two data rows, then a totals row.
+        let mut cells = HashMap::new();
+        for (col, header) in ["Item", "Qty", "Price"].iter().enumerate() {
+            cells.insert(
+                CellAddress::new(0, col as u32),
+                Value::String(header.to_string()),
+            );
+        }
+        cells.insert(CellAddress::new(1, 1), Value::Int(2));
+        cells.insert(CellAddress::new(1, 2), Value::Int(10));
+        cells.insert(CellAddress::new(2, 1), Value::Int(3));
+        cells.insert(CellAddress::new(2, 2), Value::Int(20));
+        cells.insert(CellAddress::new(3, 1), Value::Int(99));
+        let table = TableLocation {
+            name: "Sales".to_string(),
+            sheet: None,
+            header_row: 0,
+            first_col: 0,
+            columns: vec!["Item".into(), "Qty".into(), "Price".into()],
+            data_rows: 2,
+            totals_row: true,
+        };
+        let mut ctx = TableContext {
+            cells,
+            table,
+            current: Some(CellAddress::new(2, 3)),
+        };
+
+        let mut engine = FormulaEngine::new();
+        let compiled = engine
+            .compile_with_context("=SUM(Sales[Qty])", 0, Some(&ctx), None)
+            .unwrap();
+        assert_eq!(
+            compiled.dependencies,
+            vec![FormulaDependency::Range {
+                sheet_id: 0,
+                range: CellRange::new(CellAddress::new(1, 1), CellAddress::new(2, 1)),
+            }]
+        );
+        let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+        assert_eq!(value, Value::Float(5.0));
+
+        // The current cell is D3, just right of the table, so [@Qty] is out of range.
+        let this_row = engine.compile("=[@Qty]*[@Price]").unwrap();
+        let value = engine.evaluate(&this_row, &ctx).expect("eval ok");
+        assert_eq!(value, Value::Error(ErrorValue::Ref));
+        ctx.table.columns.push("Total".into());
+        let value = engine.evaluate(&this_row, &ctx).expect("eval ok");
+        assert_eq!(value, Value::Float(60.0));
+
+        let cases = [
+            (
+                "=Sales[[#Headers],[Price]]",
+                Value::String("Price".to_string()),
+            ),
+            ("=Sales[[#Totals],[Qty]]", Value::Int(99)),
+            ("=COUNT(Sales[#All])", Value::Int(5)),
+            ("=SUM(Sales)", Value::Float(35.0)),
+            ("=Sales[Missing]", Value::Error(ErrorValue::Ref)),
+            ("=Other[Qty]", Value::Error(ErrorValue::Ref)),
+        ];
+        for (formula, expected) in cases {
+            let compiled = engine.compile(formula).unwrap();
+            let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+            assert_eq!(value, expected, "{formula}");
+        }
+    }
+
+    #[test]
+    fn test_array_arithmetic_broadcasts() {
+        let mut cells = HashMap::new();
+        for row in 0..3 {
+            cells.insert(CellAddress::new(row, 0), Value::Int(row as i64 + 1));
+        }
+        let ctx = EvalContext::with_cells(cells);
+        let mut engine = FormulaEngine::new();
+        let cases = [
+            (
+                "=SEQUENCE(3)*10",
+                vec![
+                    vec![Value::Float(10.0)],
+                    vec![Value::Float(20.0)],
+                    vec![Value::Float(30.0)],
+                ],
+            ),
+            (
+                "=SEQUENCE(1, 2)+SEQUENCE(2)",
+                vec![
+                    vec![Value::Float(2.0), Value::Float(3.0)],
+                    vec![Value::Float(3.0), Value::Float(4.0)],
+                ],
+            ),
+            (
+                "=SEQUENCE(2)>1",
+                vec![vec![Value::Bool(false)], vec![Value::Bool(true)]],
+            ),
+            (
+                "=SEQUENCE(2)+SEQUENCE(3)",
+                vec![
+                    vec![Value::Float(2.0)],
+                    vec![Value::Float(4.0)],
+                    vec![Value::Error(ErrorValue::NA)],
+                ],
+            ),
+            (
+                "=-SEQUENCE(2)",
+                vec![vec![Value::Int(-1)], vec![Value::Int(-2)]],
+            ),
+        ];
+        for (formula, expected) in cases {
+            let compiled = engine.compile(formula).unwrap();
+            let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+            assert_eq!(value, functions::from_grid(expected), "{formula}");
+        }
+    }
+
+    struct SpillContext {
+        cells: HashMap<CellAddress, Value>,
+        spill: CellRange,
+    }
+
+    impl ValueResolver for SpillContext {
+        fn get_cell(&self, addr: &CellAddress) -> Value {
+            self.cells.get(addr).cloned().unwrap_or(Value::Empty)
+        }
+        fn get_range(&self, range: &CellRange) -> Vec<Value> {
+            (range.start.row..=range.end.row)
+                .map(|row| {
+                    Value::Array(
+                        (range.start.col..=range.end.col)
+                            .map(|col| self.get_cell(&CellAddress::new(row, col)))
+                            .collect(),
+                    )
+                })
+                .collect()
+        }
+        fn spill_range(&self, sheet: Option<&str>, anchor: &CellAddress) -> Option<CellRange> {
+            (sheet.is_none() && *anchor == self.spill.start).then_some(self.spill)
+        }
+    }
+
+    #[test]
+    fn test_spill_reference() {
+        let mut cells = HashMap::new();
+        for row in 0..3 {
+            cells.insert(CellAddress::new(row, 1), Value::Int(row as i64 + 1));
+        }
+        let ctx = SpillContext {
+            cells,
+            spill: CellRange::new(CellAddress::new(0, 1), CellAddress::new(2, 1)),
+        };
+        let mut engine = FormulaEngine::new();
+        let compiled = engine.compile("=SUM(B1#)").unwrap();
+        assert_eq!(
+            compiled.dependencies,
+            vec![FormulaDependency::Cell {
+                sheet_id: 0,
+                addr: CellAddress::new(0, 1),
+            }]
+        );
+        let value = engine.evaluate(&compiled, &ctx).expect("eval ok");
+        assert_eq!(value, Value::Float(6.0));
+
+        let missing = engine.compile("=SUM(C1#)").unwrap();
+        let value = engine.evaluate(&missing, &ctx).expect("eval ok");
+        assert_eq!(value, Value::Error(ErrorValue::Ref));
+    }
+
+    #[test]
+    fn test_set_spill_links_spilled_cells_to_anchor() {
+        let mut engine = FormulaEngine::new();
+        let anchor = CellAddress::new(0, 0);
+        let spilled = [CellAddress::new(1, 0), CellAddress::new(2, 0)];
+        engine.set_formula(anchor, "=SEQUENCE(3)").unwrap();
+        engine.set_formula(CellAddress::new(0, 1), "=A3*2").unwrap();
+        engine.set_spill(anchor, &spilled).unwrap();
+        engine.get_dirty_nodes().unwrap();
+
+        engine.mark_dirty(&anchor);
+        let dirty = engine.get_dirty_nodes().unwrap();
+        assert!(dirty.contains(&CellAddress::new(0, 1)));
+
+        // A3 feeds B1, so spilling over it once the anchor reads B1 is a cycle
+        engine.set_spill(anchor, &[]).unwrap();
+        engine.set_formula(anchor, "=SEQUENCE(B1)").unwrap();
+        assert!(engine.set_spill(anchor, &spilled).is_err());
+        engine.get_dirty_nodes().unwrap();
+        engine.mark_dirty(&anchor);
+        let dirty = engine.get_dirty_nodes().unwrap();
+        assert!(!dirty.contains(&CellAddress::new(1, 0)));
+    }
 }
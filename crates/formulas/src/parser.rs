@@ -1,6 +1,6 @@
 //! Formula parser module
 
-use crate::{BinaryOperator, FormulaError, FormulaExpr, UnaryOperator};
+use crate::{BinaryOperator, FormulaError, FormulaExpr, TableArea, UnaryOperator};
 use piptable_primitives::{CellAddress, CellRange, ErrorValue, R1C1Ref, Value};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -11,6 +11,8 @@ enum TokenKind {
     SheetName(String),
     Error(ErrorValue),
     CellRef(String),
+    SpillRef(String),
+    Structured(String),
     LParen,
     RParen,
     Comma,
@@ -108,6 +110,7 @@ impl<'a> Lexer<'a> {
             }
             '"' => self.string_token()?,
             '\'' => self.sheet_name_token()?,
+            '[' => self.structured_token()?,
             '#' => self.error_token()?,
             '.' | '0'..='9' => self.number_token()?,
             '$' | 'A'..='Z' | 'a'..='z' | '_' => self.identifier_or_cell_token()?,
@@ -168,6 +171,32 @@ impl<'a> Lexer<'a> {
         ))
     }
 
+    /// Read a bracketed structured reference specifier such as `[Amount]`
+    /// or `[[#Headers],[Qty]]`, keeping the `'` escapes for the parser.
+    fn structured_token(&mut self) -> Result<TokenKind, FormulaError> {
+        self.advance(); // consume opening [
+        let start = self.pos;
+        let mut depth = 1;
+        while let Some(ch) = self.peek() {
+            self.advance();
+            match ch {
+                '\'' => self.advance(),
+                '[' => depth += 1,
+                ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        let text = self.slice(start, self.pos - 1);
+                        return Ok(TokenKind::Structured(text.to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(FormulaError::ParseError(
+            "Unterminated structured reference".to_string(),
+        ))
+    }
+
     fn error_token(&mut self) -> Result<TokenKind, FormulaError> {
         self.advance(); // consume '#'
         let mut literal = String::from("#");
@@ -245,6 +274,14 @@ impl<'a> Lexer<'a> {
                 let text = self.slice(start, self.pos);
                 return Ok(TokenKind::CellRef(text.to_string()));
             }
+            if !row_digits.is_empty()
+                && self.peek() == Some('#')
+                && is_boundary(self.chars.get(self.pos + 1).map(|(_, ch)| *ch))
+            {
+                let text = self.slice(start, self.pos).to_string();
+                self.advance(); // consume '#'
+                return Ok(TokenKind::SpillRef(text));
+            }
             // not a cell ref; fall through to identifier parsing
             self.pos = start;
         } else {
@@ -537,6 +574,8 @@ impl Parser {
             TokenKind::Identifier(name) => self.parse_identifier_or_ref(name),
             TokenKind::SheetName(name) => self.parse_sheet_ref(name),
             TokenKind::CellRef(text) => self.parse_cell_or_range(text, None),
+            TokenKind::SpillRef(text) => spill_ref(&text, None),
+            TokenKind::Structured(spec) => table_ref(None, &spec),
             TokenKind::LParen => {
                 let expr = self.parse_expression()?;
                 self.expect(TokenKind::RParen)?;
@@ -557,6 +596,11 @@ impl Parser {
             return self.parse_sheet_ref(name);
         }
 
+        if let TokenKind::Structured(spec) = self.peek_kind().clone() {
+            self.advance();
+            return table_ref(Some(name), &spec);
+        }
+
         if matches!(self.peek_kind(), TokenKind::LParen) {
             self.advance();
             let args = self.parse_arguments()?;
@@ -566,10 +610,7 @@ impl Parser {
         match name.to_uppercase().as_str() {
             "TRUE" => Ok(FormulaExpr::Literal(Value::Bool(true))),
             "FALSE" => Ok(FormulaExpr::Literal(Value::Bool(false))),
-            _ => Err(FormulaError::ParseError(format!(
-                "Unexpected identifier '{}'",
-                name
-            ))),
+            _ => Ok(FormulaExpr::Name { sheet: None, name }),
         }
     }
 
@@ -583,6 +624,11 @@ impl Parser {
         self.advance();
         match self.advance().kind.clone() {
             TokenKind::CellRef(text) => self.parse_cell_or_range(text, Some(sheet)),
+            TokenKind::SpillRef(text) => spill_ref(&text, Some(sheet)),
+            TokenKind::Identifier(name) if is_valid_name(&name) => Ok(FormulaExpr::Name {
+                sheet: Some(sheet),
+                name,
+            }),
             other => Err(FormulaError::ParseError(format!(
                 "Expected cell reference after sheet name, got {:?}",
                 other
@@ -736,6 +782,170 @@ impl Parser {
     }
 }
 
+fn spill_ref(text: &str, sheet: Option<String>) -> Result<FormulaExpr, FormulaError> {
+    let anchor = CellAddress::from_a1(text)
+        .map_err(|e| FormulaError::ParseError(format!("Invalid spill reference: {}", e)))?;
+    Ok(FormulaExpr::SpillRef { sheet, anchor })
+}
+
+fn table_ref(table: Option<String>, spec: &str) -> Result<FormulaExpr, FormulaError> {
+    let (area, columns) = parse_table_spec(spec)?;
+    Ok(FormulaExpr::TableRef {
+        table,
+        area,
+        columns,
+    })
+}
+
+/// Parse the inside of a structured reference: `Col`, `#Totals`, `@Col`,
+/// `@[Col Name]`, or a list such as `[#Headers],[Col]` or `[Col1]:[Col2]`.
+fn parse_table_spec(spec: &str) -> Result<(TableArea, Option<(String, String)>), FormulaError> {
+    let spec = spec.trim();
+    let (this_row, spec) = match spec.strip_prefix('@') {
+        Some(rest) => (true, rest.trim()),
+        None => (false, spec),
+    };
+
+    let items = if spec.starts_with('[') {
+        split_table_items(spec)?
+    } else if spec.is_empty() {
+        Vec::new()
+    } else {
+        vec![(spec.to_string(), false)]
+    };
+
+    let mut area = None;
+    let mut columns: Option<(String, String)> = None;
+    for (item, after_colon) in items {
+        let item = item.trim();
+        if item.starts_with('#') {
+            if area.is_some() || after_colon {
+                return Err(FormulaError::ParseError(format!(
+                    "Unsupported structured reference '[{}]'",
+                    spec
+                )));
+            }
+            area = Some(parse_table_area(item)?);
+            continue;
+        }
+        let name = unescape_table_column(item);
+        columns = match (columns, after_colon) {
+            (None, false) => Some((name.clone(), name)),
+            (Some((first, _)), true) => Some((first, name)),
+            _ => {
+                return Err(FormulaError::ParseError(format!(
+                    "Unsupported structured reference '[{}]'",
+                    spec
+                )))
+            }
+        };
+    }
+
+    let area = match (this_row, area) {
+        (true, None) => TableArea::ThisRow,
+        (true, Some(_)) => {
+            return Err(FormulaError::ParseError(format!(
+                "Unsupported structured reference '[@{}]'",
+                spec
+            )))
+        }
+        (false, area) => area.unwrap_or(TableArea::Data),
+    };
+    Ok((area, columns))
+}
+
+/// Split `[a],[b]:[c]` into its bracketed items, flagging items that follow `:`.
+fn split_table_items(spec: &str) -> Result<Vec<(String, bool)>, FormulaError> {
+    let chars: Vec<char> = spec.chars().collect();
+    let mut items = Vec::new();
+    let mut idx = 0;
+    let mut after_colon = false;
+    loop {
+        while chars.get(idx).is_some_and(|ch| ch.is_whitespace()) {
+            idx += 1;
+        }
+        if chars.get(idx) != Some(&'[') {
+            return Err(FormulaError::ParseError(format!(
+                "Expected '[' in structured reference '[{}]'",
+                spec
+            )));
+        }
+        idx += 1;
+        let mut item = String::new();
+        loop {
+            match chars.get(idx) {
+                Some('\'') => {
+                    item.push('\'');
+                    if let Some(ch) = chars.get(idx + 1) {
+                        item.push(*ch);
+                    }
+                    idx += 2;
+                }
+                Some(']') => {
+                    idx += 1;
+                    break;
+                }
+                Some(ch) => {
+                    item.push(*ch);
+                    idx += 1;
+                }
+                None => {
+                    return Err(FormulaError::ParseError(format!(
+                        "Unterminated item in structured reference '[{}]'",
+                        spec
+                    )))
+                }
+            }
+        }
+        items.push((item, after_colon));
+        while chars.get(idx).is_some_and(|ch| ch.is_whitespace()) {
+            idx += 1;
+        }
+        match chars.get(idx) {
+            None => return Ok(items),
+            Some(',') => after_colon = false,
+            Some(':') => after_colon = true,
+            Some(ch) => {
+                return Err(FormulaError::ParseError(format!(
+                    "Unexpected '{}' in structured reference '[{}]'",
+                    ch, spec
+                )))
+            }
+        }
+        idx += 1;
+    }
+}
+
+fn parse_table_area(item: &str) -> Result<TableArea, FormulaError> {
+    match item.to_lowercase().as_str() {
+        "#all" => Ok(TableArea::All),
+        "#data" => Ok(TableArea::Data),
+        "#headers" => Ok(TableArea::Headers),
+        "#totals" => Ok(TableArea::Totals),
+        "#this row" => Ok(TableArea::ThisRow),
+        _ => Err(FormulaError::ParseError(format!(
+            "Unknown table specifier '{}'",
+            item
+        ))),
+    }
+}
+
+/// Drop the `'` escapes Excel uses for `[`, `]`, `#` and `'` in column names.
+fn unescape_table_column(item: &str) -> String {
+    let mut out = String::new();
+    let mut chars = item.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\'' {
+            if let Some(next) = chars.next() {
+                out.push(next);
+            }
+        } else {
+            out.push(ch);
+        }
+    }
+    out.trim().to_string()
+}
+
 fn parse_error_literal(literal: &str) -> Result<ErrorValue, FormulaError> {
     match literal.to_uppercase().as_str() {
         "#DIV/0!" => Ok(ErrorValue::Div0),
@@ -745,6 +955,8 @@ fn parse_error_literal(literal: &str) -> Result<ErrorValue, FormulaError> {
         "#NULL!" => Ok(ErrorValue::Null),
         "#NUM!" => Ok(ErrorValue::Num),
         "#N/A" => Ok(ErrorValue::NA),
+        "#SPILL!" => Ok(ErrorValue::Spill),
+        "#CALC!" => Ok(ErrorValue::Calc),
         _ => Err(FormulaError::ParseError(format!(
             "Unknown error literal '{}'",
             literal
@@ -781,6 +993,22 @@ pub fn parse_formula(formula: &str) -> Result<FormulaExpr, FormulaError> {
     Ok(expr)
 }
 
+/// Whether `name` can be used as a defined name: a letter or underscore,
+/// then letters, digits, underscores or periods, and not `TRUE`, `FALSE` or
+/// something that reads as a cell reference (`TAX2023`, `R1C1`).
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    (first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '.')
+        && !name.eq_ignore_ascii_case("TRUE")
+        && !name.eq_ignore_ascii_case("FALSE")
+        && CellAddress::from_a1(name).is_err()
+        && R1C1Ref::from_r1c1(name).is_err()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1056,7 +1284,7 @@ mod tests {
 
     #[test]
     fn test_parse_errors() {
-        assert!(parse_formula("FOO").is_err());
+        assert!(parse_formula("FOO BAR").is_err());
         assert!(parse_formula("\"unterminated").is_err());
         assert!(parse_formula("'Sheet").is_err());
     }
@@ -1119,9 +1347,11 @@ mod tests {
 
     #[test]
     fn test_parse_identifier_with_dot() {
-        assert!(parse_formula("foo.bar").is_err());
-        assert!(parse_formula("_foo").is_err());
-        assert!(parse_formula("A1B").is_err());
+        // Bare identifiers are defined names
+        for text in ["foo.bar", "_foo", "A1B"] {
+            let expr = parse_formula(text).unwrap();
+            assert!(matches!(expr, FormulaExpr::Name { sheet: None, name } if name == text));
+        }
     }
 
     #[test]
@@ -1155,4 +1385,112 @@ mod tests {
         let expr = parse_formula("'シート'!A1").unwrap();
         assert!(matches!(expr, FormulaExpr::SheetCellRef { sheet, .. } if sheet == "シート"));
     }
+
+    #[test]
+    fn test_parse_defined_names() {
+        let expr = parse_formula("=SUM(Sales)*TaxRate").unwrap();
+        let FormulaExpr::BinaryOp { left, right, .. } = expr else {
+            panic!("Expected binary op");
+        };
+        assert!(matches!(*right, FormulaExpr::Name { sheet: None, ref name } if name == "TaxRate"));
+        assert!(matches!(*left, FormulaExpr::FunctionCall { ref args, .. }
+            if matches!(&args[0], FormulaExpr::Name { name, .. } if name == "Sales")));
+
+        let expr = parse_formula("='Q1 Data'!Regions").unwrap();
+        assert!(
+            matches!(expr, FormulaExpr::Name { sheet: Some(sheet), name }
+            if sheet == "Q1 Data" && name == "Regions")
+        );
+
+        assert!(is_valid_name("Tax_Rate.2024"));
+        assert!(is_valid_name("_total"));
+        for name in ["", "2x", "TAX2023", "R1C1", "RC", "true", "has space"] {
+            assert!(!is_valid_name(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_parse_spill_references() {
+        let spill = |formula: &str| match parse_formula(formula).unwrap() {
+            FormulaExpr::SpillRef { sheet, anchor } => (sheet, anchor),
+            FormulaExpr::FunctionCall { args, .. } => match &args[0] {
+                FormulaExpr::SpillRef { sheet, anchor } => (sheet.clone(), *anchor),
+                other => panic!("expected spill ref for {formula}, got {other:?}"),
+            },
+            other => panic!("expected spill ref for {formula}, got {other:?}"),
+        };
+
+        assert_eq!(spill("=SUM(A1#)"), (None, CellAddress::new(0, 0)));
+        assert_eq!(
+            spill("='My Data'!$B$2#"),
+            (Some("My Data".to_string()), CellAddress::new(1, 1))
+        );
+        assert!(matches!(
+            parse_formula("=#SPILL!").unwrap(),
+            FormulaExpr::Literal(Value::Error(ErrorValue::Spill))
+        ));
+    }
+
+    #[test]
+    fn test_parse_structured_references() {
+        let table = |formula: &str| match parse_formula(formula).unwrap() {
+            FormulaExpr::TableRef {
+                table,
+                area,
+                columns,
+            } => (table, area, columns),
+            other => panic!("expected table ref for {formula}, got {other:?}"),
+        };
+        let col = |name: &str| Some((name.to_string(), name.to_string()));
+
+        assert_eq!(
+            table("=Table1[Amount]"),
+            (Some("Table1".to_string()), TableArea::Data, col("Amount"))
+        );
+        assert_eq!(
+            table("=Table1[[#Headers],[Amount]]"),
+            (
+                Some("Table1".to_string()),
+                TableArea::Headers,
+                col("Amount")
+            )
+        );
+        assert_eq!(table("=[@Qty]"), (None, TableArea::ThisRow, col("Qty")));
+        assert_eq!(
+            table("=[@[Unit Price]]"),
+            (None, TableArea::ThisRow, col("Unit Price"))
+        );
+        assert_eq!(
+            table("=Sales[#Totals]"),
+            (Some("Sales".to_string()), TableArea::Totals, None)
+        );
+        assert_eq!(
+            table("=Sales[]"),
+            (Some("Sales".to_string()), TableArea::Data, None)
+        );
+        assert_eq!(
+            table("=Sales[[Qty]:[Price]]"),
+            (
+                Some("Sales".to_string()),
+                TableArea::Data,
+                Some(("Qty".to_string(), "Price".to_string()))
+            )
+        );
+        assert_eq!(
+            table("=Sales[Rate '[%']]"),
+            (Some("Sales".to_string()), TableArea::Data, col("Rate [%]"))
+        );
+
+        let expr = parse_formula("=SUM(Sales[Amount])*[@Qty]").unwrap();
+        let FormulaExpr::BinaryOp { left, right, .. } = expr else {
+            panic!("expected binary op");
+        };
+        assert!(matches!(*left, FormulaExpr::FunctionCall { ref args, .. }
+            if matches!(&args[0], FormulaExpr::TableRef { table: Some(t), .. } if t == "Sales")));
+        assert!(matches!(*right, FormulaExpr::TableRef { table: None, .. }));
+
+        assert!(parse_formula("=Sales[Amount").is_err());
+        assert!(parse_formula("=Sales[[#Bogus]]").is_err());
+        assert!(parse_formula("=[@[#Totals]]").is_err());
+    }
 }
@@ -226,7 +226,7 @@ pub fn extract_references(formula: &str) -> Vec<FormulaReference> {
         }
     }
 
-    matches.sort_by(|a, b| a.0.cmp(&b.0));
+    matches.sort_by_key(|(start, _)| *start);
     matches.into_iter().map(|(_, m)| m).collect()
 }
 
@@ -498,6 +498,81 @@ fn r1c1_mode(text: &str) -> ReferenceMode {
     }
 }
 
+/// Rewrite references qualified with sheet `old` (matched without regard to
+/// case) to name sheet `new`, quoting it when needed. Text inside string
+/// literals is left alone.
+pub fn rename_sheet_references(formula: &str, old: &str, new: &str) -> String {
+    let replacement = quote_sheet_name(new);
+    let old = old.to_lowercase();
+    replace_outside_quotes(formula, |segment| {
+        let chars: Vec<char> = segment.chars().collect();
+        let mut out = String::with_capacity(segment.len());
+        let mut i = 0;
+        while i < chars.len() {
+            let ch = chars[i];
+            let prev_is_word = i > 0 && is_sheet_name_char(chars[i - 1]);
+            let (name, end) = if ch == '\'' {
+                // Quoted name; '' stands for a quote
+                let mut name = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        Some('\'') if chars.get(j + 1) == Some(&'\'') => {
+                            name.push('\'');
+                            j += 2;
+                        }
+                        Some('\'') => break (Some(name), j + 1),
+                        Some(c) => {
+                            name.push(*c);
+                            j += 1;
+                        }
+                        None => break (None, i + 1),
+                    }
+                }
+            } else if !prev_is_word && (ch.is_ascii_alphabetic() || ch == '_') {
+                let mut j = i;
+                while j < chars.len() && is_sheet_name_char(chars[j]) {
+                    j += 1;
+                }
+                (Some(chars[i..j].iter().collect()), j)
+            } else {
+                (None, i + 1)
+            };
+            match name {
+                Some(name) if chars.get(end) == Some(&'!') && name.to_lowercase() == old => {
+                    out.push_str(&replacement);
+                }
+                _ => out.extend(&chars[i..end]),
+            }
+            i = end;
+        }
+        out
+    })
+}
+
+fn is_sheet_name_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_' || ch == '.'
+}
+
+/// A sheet name as written before `!`: bare when the tokenizer reads it as
+/// one identifier, quoted otherwise.
+pub fn quote_sheet_name(name: &str) -> String {
+    let bare = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(is_sheet_name_char)
+        // R1C1-looking names would be read as references
+        && !name
+            .chars()
+            .all(|c| matches!(c, 'R' | 'r' | 'C' | 'c' | '0'..='9'));
+    if bare {
+        name.to_string()
+    } else {
+        format!("'{}'", name.replace('\'', "''"))
+    }
+}
+
 fn split_formula_segments(formula: &str) -> Vec<(usize, usize, bool)> {
     let mut segments = Vec::new();
     let mut in_string = false;
@@ -648,6 +723,25 @@ mod tests {
         assert_eq!(out, "=XFE1048577");
     }
 
+    #[test]
+    fn test_rename_sheet_references() {
+        let out = rename_sheet_references(
+            "=Data!A1+SUM('data'!B1:B3)+OtherData!A1&\"Data!A1\"",
+            "Data",
+            "Q1 Sales",
+        );
+        assert_eq!(
+            out,
+            "='Q1 Sales'!A1+SUM('Q1 Sales'!B1:B3)+OtherData!A1&\"Data!A1\""
+        );
+        assert_eq!(
+            rename_sheet_references("='It''s'!A1", "It's", "Plan"),
+            "=Plan!A1"
+        );
+        assert_eq!(rename_sheet_references("=A1+B2", "A1", "X"), "=A1+B2");
+        assert_eq!(rename_sheet_references("=S!A1", "S", "R1C1"), "='R1C1'!A1");
+    }
+
     #[test]
     fn test_formula_to_relative_reference_mixed_range() {
         let source = CellAddress::new(0, 0);
@@ -6,7 +6,8 @@ use crate::book_conversions::{
 };
 use crate::Interpreter;
 use piptable_core::{PipError, PipResult, Value};
-use piptable_sheet::Book;
+use piptable_sheet::{Book, DefinedName, Table};
+use std::collections::HashMap;
 
 fn expect_book(value: &Value, line: usize) -> PipResult<&Book> {
     match value {
@@ -34,6 +35,12 @@ pub async fn call_book_builtin(
         "book_remove_sheet" => Some(book_remove_sheet(args, line)),
         "book_rename_sheet" => Some(book_rename_sheet(args, line)),
         "book_merge" => Some(book_merge(args, line)),
+        "book_set_formula" => Some(book_set_formula(args, line)),
+        "book_evaluate_formulas" => Some(book_evaluate_formulas(args, line)),
+        "book_define_name" => Some(book_define_name(args, line)),
+        "book_get_name" => Some(book_get_name(args, line)),
+        "book_names" => Some(book_names(args, line)),
+        "book_add_table" => Some(book_add_table(args, line)),
         "book_to_dict" => Some(book_to_dict(args, line)),
         "book_from_dict" => Some(book_from_dict(args, line)),
         "book_sheets" => Some(book_sheets(args, line)),
@@ -259,6 +266,162 @@ fn book_merge(args: Vec<Value>, line: usize) -> PipResult<Value> {
     Ok(Value::Book(Box::new(new_book)))
 }
 
+fn book_set_formula(args: Vec<Value>, line: usize) -> PipResult<Value> {
+    if args.len() != 4 {
+        return Err(PipError::runtime(
+            line,
+            "book_set_formula() takes exactly 4 arguments (book, sheet, notation, formula)",
+        ));
+    }
+    let book = expect_book(&args[0], line)?;
+    let (Some(sheet), Some(notation), Some(formula)) =
+        (args[1].as_str(), args[2].as_str(), args[3].as_str())
+    else {
+        return Err(PipError::runtime(
+            line,
+            "Sheet name, notation and formula must be strings",
+        ));
+    };
+    let mut new_book = book.clone();
+    new_book
+        .set_formula(sheet, notation, formula)
+        .map_err(|e| {
+            PipError::runtime(
+                line,
+                format!("Failed to set formula '{sheet}!{notation}': {e}"),
+            )
+        })?;
+    Ok(Value::Book(Box::new(new_book)))
+}
+
+fn book_evaluate_formulas(args: Vec<Value>, line: usize) -> PipResult<Value> {
+    if args.len() != 1 {
+        return Err(PipError::runtime(
+            line,
+            "book_evaluate_formulas() takes exactly 1 argument (book)",
+        ));
+    }
+    let book = expect_book(&args[0], line)?;
+    let mut new_book = book.clone();
+    new_book
+        .evaluate_formulas()
+        .map_err(|e| PipError::runtime(line, format!("Failed to evaluate formulas: {e}")))?;
+    Ok(Value::Book(Box::new(new_book)))
+}
+
+/// The optional sheet argument at `index` of a name builtin.
+fn optional_sheet_arg(args: &[Value], index: usize, line: usize) -> PipResult<Option<&str>> {
+    match args.get(index) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(sheet)) => Ok(Some(sheet)),
+        Some(_) => Err(PipError::runtime(line, "Sheet name must be a string")),
+    }
+}
+
+fn book_define_name(args: Vec<Value>, line: usize) -> PipResult<Value> {
+    if !(3..=4).contains(&args.len()) {
+        return Err(PipError::runtime(
+            line,
+            "book_define_name() takes 3 or 4 arguments (book, name, refers_to, [sheet])",
+        ));
+    }
+    let book = expect_book(&args[0], line)?;
+    let (Some(name), Some(refers_to)) = (args[1].as_str(), args[2].as_str()) else {
+        return Err(PipError::runtime(
+            line,
+            "Name and refers_to must be strings",
+        ));
+    };
+    let sheet = optional_sheet_arg(&args, 3, line)?;
+    let mut new_book = book.clone();
+    match sheet {
+        Some(sheet) => new_book.define_sheet_name(sheet, name, refers_to),
+        None => new_book.define_name(name, refers_to),
+    }
+    .map_err(|e| PipError::runtime(line, format!("Failed to define name '{name}': {e}")))?;
+    Ok(Value::Book(Box::new(new_book)))
+}
+
+fn book_get_name(args: Vec<Value>, line: usize) -> PipResult<Value> {
+    if !(2..=3).contains(&args.len()) {
+        return Err(PipError::runtime(
+            line,
+            "book_get_name() takes 2 or 3 arguments (book, name, [sheet])",
+        ));
+    }
+    let book = expect_book(&args[0], line)?;
+    let name = args[1]
+        .as_str()
+        .ok_or_else(|| PipError::runtime(line, "Name must be a string"))?;
+    let sheet = optional_sheet_arg(&args, 2, line)?;
+    let range = book
+        .name_range(sheet, name)
+        .map_err(|e| PipError::runtime(line, format!("Failed to get name '{name}': {e}")))?;
+    Ok(Value::Sheet(Box::new(range)))
+}
+
+fn book_names(args: Vec<Value>, line: usize) -> PipResult<Value> {
+    if args.len() != 1 {
+        return Err(PipError::runtime(
+            line,
+            "book_names() takes exactly 1 argument (book)",
+        ));
+    }
+    let book = expect_book(&args[0], line)?;
+    let record = |defined: &DefinedName, scope: Value| {
+        Value::Object(HashMap::from([
+            ("name".to_string(), Value::String(defined.name.clone())),
+            (
+                "refers_to".to_string(),
+                Value::String(defined.refers_to.clone()),
+            ),
+            ("scope".to_string(), scope),
+        ]))
+    };
+    let mut names: Vec<Value> = book
+        .names()
+        .iter()
+        .map(|defined| record(defined, Value::Null))
+        .collect();
+    for (sheet_name, sheet) in book.sheets() {
+        names.extend(
+            sheet
+                .names()
+                .iter()
+                .map(|defined| record(defined, Value::String(sheet_name.to_string()))),
+        );
+    }
+    Ok(Value::Array(names))
+}
+
+fn book_add_table(args: Vec<Value>, line: usize) -> PipResult<Value> {
+    if !(4..=5).contains(&args.len()) {
+        return Err(PipError::runtime(
+            line,
+            "book_add_table() takes 4 or 5 arguments (book, sheet, name, range, [totals_row])",
+        ));
+    }
+    let book = expect_book(&args[0], line)?;
+    let (Some(sheet), Some(name), Some(range)) =
+        (args[1].as_str(), args[2].as_str(), args[3].as_str())
+    else {
+        return Err(PipError::runtime(
+            line,
+            "Sheet, name and range must be strings",
+        ));
+    };
+    let totals_row = match args.get(4) {
+        None => false,
+        Some(Value::Bool(totals_row)) => *totals_row,
+        Some(_) => return Err(PipError::runtime(line, "totals_row must be a boolean")),
+    };
+    let mut new_book = book.clone();
+    Table::new(name, range)
+        .and_then(|table| new_book.add_table(sheet, table.with_totals_row(totals_row)))
+        .map_err(|e| PipError::runtime(line, format!("Failed to add table '{name}': {e}")))?;
+    Ok(Value::Book(Box::new(new_book)))
+}
+
 fn book_to_dict(args: Vec<Value>, line: usize) -> PipResult<Value> {
     if args.len() != 1 {
         return Err(PipError::runtime(
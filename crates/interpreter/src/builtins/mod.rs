@@ -16,6 +16,8 @@ mod string;
 use crate::Interpreter;
 use piptable_core::{PipResult, Value};
 
+pub(crate) use sheet::parse_sort_keys;
+
 /// Execute a built-in function.
 ///
 /// Returns `None` if the function is not a built-in, allowing the interpreter
@@ -96,6 +98,11 @@ pub fn is_builtin(name: &str) -> bool {
             | "sheet_eval_formula"
             | "sheet_set_formula"
             | "sheet_evaluate_formulas"
+            | "sheet_define_name"
+            | "sheet_get_name"
+            | "sheet_add_table"
+            | "sheet_get_table"
+            | "sheet_spill_range"
             | "sheet_set_a1"
             | "sheet_get_range"
             | "sheet_column_by_name"
@@ -106,6 +113,18 @@ pub fn is_builtin(name: &str) -> bool {
             | "sheet_map_range"
             | "sheet_map"
             | "sheet_filter_rows"
+            | "sheet_pivot"
+            | "sheet_group_by"
+            | "sheet_diff"
+            | "sheet_fuzzy_dedupe"
+            | "sheet_fuzzy_join"
+            | "sheet_profile"
+            | "sheet_sort"
+            | "sheet_infer_schema"
+            | "sheet_apply_schema"
+            | "sheet_schema_report"
+            | "sheet_unpivot"
+            | "stream"
             // book
             | "book_sheet_names"
             | "book_sheet_count"
@@ -118,6 +137,12 @@ pub fn is_builtin(name: &str) -> bool {
             | "book_remove_sheet"
             | "book_rename_sheet"
             | "book_merge"
+            | "book_set_formula"
+            | "book_evaluate_formulas"
+            | "book_define_name"
+            | "book_get_name"
+            | "book_names"
+            | "book_add_table"
             | "book_to_dict"
             | "book_from_dict"
             | "book_sheets"
@@ -2,7 +2,15 @@
 
 use crate::{formula, Interpreter};
 use piptable_core::{PipError, PipResult, Value};
-use piptable_sheet::{CellValue, CleanOptions, NullStrategy, ValidationRule};
+use piptable_sheet::{
+    Agg, Aggregation, CellValue, CleanOptions, Collation, ColumnSchema, ColumnType, CsvOptions,
+    DiffOptions, FuzzyOptions, NullOrder, NullStrategy, PivotOptions, ProfileOptions, SheetSchema,
+    SheetStream, Similarity, SortKey, StreamFormat, Table, ValidationRule,
+};
+use std::collections::HashMap;
+
+/// Rows per chunk when `stream()` is not given a size.
+const DEFAULT_CHUNK_ROWS: usize = 100_000;
 
 /// Convert a CellValue to a Value
 fn cell_to_value(cell: &CellValue) -> Value {
@@ -16,6 +24,9 @@ fn cell_to_value(cell: &CellValue) -> Value {
             Some(cached) => cell_to_value(cached),
             None => Value::String(formula.source.clone()),
         },
+        CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_) => {
+            Value::String(cell.as_str())
+        }
     }
 }
 
@@ -178,6 +189,470 @@ fn parse_clean_options(
     Ok(options)
 }
 
+/// Read a column name or array of column names.
+fn column_name_list(value: &Value, line: usize, what: &str) -> PipResult<Vec<String>> {
+    match value {
+        Value::String(name) => Ok(vec![name.clone()]),
+        Value::Array(values) => values
+            .iter()
+            .map(|v| match v {
+                Value::String(s) => Ok(s.clone()),
+                _ => Err(PipError::runtime(line, "Column names must be strings")),
+            })
+            .collect(),
+        _ => Err(PipError::runtime(
+            line,
+            format!("{what} must be a column name or array of names"),
+        )),
+    }
+}
+
+fn parse_pivot_options(value: &Value, line: usize) -> PipResult<PivotOptions> {
+    let Value::Object(fields) = value else {
+        return Err(PipError::runtime(line, "Pivot options must be an object"));
+    };
+    let mut options = PivotOptions::default();
+    for (key, value) in fields {
+        let Value::Bool(flag) = value else {
+            return Err(PipError::runtime(
+                line,
+                format!("Pivot option '{}' must be a boolean", key),
+            ));
+        };
+        match key.as_str() {
+            "grand_totals" => options.grand_totals = *flag,
+            "sort_columns" => options.sort_columns = *flag,
+            _ => {
+                return Err(PipError::runtime(
+                    line,
+                    format!(
+                        "Unknown pivot option '{}'. Supported: grand_totals, sort_columns",
+                        key
+                    ),
+                ))
+            }
+        }
+    }
+    Ok(options)
+}
+
+/// Read fuzzy matching options from an object. `cluster_column` is only
+/// accepted for deduplication and returned separately.
+fn parse_fuzzy_options(
+    value: &Value,
+    line: usize,
+    dedupe: bool,
+) -> PipResult<(FuzzyOptions, Option<String>)> {
+    let Value::Object(fields) = value else {
+        return Err(PipError::runtime(
+            line,
+            format!("Fuzzy options must be an object, got {}", value.type_name()),
+        ));
+    };
+    let (own, foreign) = if dedupe {
+        ("cluster_column", "score_column")
+    } else {
+        ("score_column", "cluster_column")
+    };
+    let mut options = FuzzyOptions::new();
+    let mut cluster_column = None;
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("similarity", Value::String(name)) => {
+                options.similarity = name
+                    .parse::<Similarity>()
+                    .map_err(|e| PipError::runtime(line, e.to_string()))?;
+            }
+            ("threshold", Value::Int(i)) => options.threshold = *i as f64,
+            ("threshold", Value::Float(f)) => options.threshold = *f,
+            ("block_on", columns) => {
+                options.block_on = column_name_list(columns, line, "Blocking columns")?;
+            }
+            ("block_prefix", Value::Int(n)) if *n >= 0 => options.block_prefix = *n as usize,
+            ("ignore_case", Value::Bool(flag)) => options.ignore_case = *flag,
+            ("score_column", Value::String(name)) if !dedupe => {
+                options.score_column.clone_from(name);
+            }
+            ("cluster_column", Value::String(name)) if dedupe => {
+                cluster_column = Some(name.clone());
+            }
+            (
+                "similarity" | "threshold" | "block_prefix" | "ignore_case" | "score_column"
+                | "cluster_column",
+                other,
+            ) if key != foreign => {
+                return Err(PipError::runtime(
+                    line,
+                    format!(
+                        "Fuzzy option '{}' has the wrong type: {}",
+                        key,
+                        other.type_name()
+                    ),
+                ))
+            }
+            _ => {
+                return Err(PipError::runtime(
+                    line,
+                    format!(
+                        "Unknown fuzzy option '{}'. Supported: similarity, threshold, \
+                         block_on, block_prefix, ignore_case, {}",
+                        key, own
+                    ),
+                ));
+            }
+        }
+    }
+    Ok((options, cluster_column))
+}
+
+/// Read diff options from an object. Returns the options and whether an
+/// HTML report was asked for with `format: "html"`.
+fn parse_diff_options(value: &Value, line: usize) -> PipResult<(DiffOptions, bool)> {
+    let Value::Object(fields) = value else {
+        return Err(PipError::runtime(
+            line,
+            format!("Diff options must be an object, got {}", value.type_name()),
+        ));
+    };
+    let mut options = DiffOptions::new();
+    let mut html = false;
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("tolerance", Value::Int(i)) => options.tolerance = *i as f64,
+            ("tolerance", Value::Float(f)) => options.tolerance = *f,
+            ("ignore_case", Value::Bool(flag)) => options.ignore_case = *flag,
+            ("ignore_columns", columns) => {
+                options.ignore_columns = column_name_list(columns, line, "Ignored columns")?;
+            }
+            ("format", Value::String(format)) => match format.to_ascii_lowercase().as_str() {
+                "sheet" => html = false,
+                "html" => html = true,
+                other => {
+                    return Err(PipError::runtime(
+                        line,
+                        format!("Unknown diff format '{}', expected sheet or html", other),
+                    ))
+                }
+            },
+            ("tolerance" | "ignore_case" | "format", other) => {
+                return Err(PipError::runtime(
+                    line,
+                    format!(
+                        "Diff option '{}' has the wrong type: {}",
+                        key,
+                        other.type_name()
+                    ),
+                ))
+            }
+            _ => {
+                return Err(PipError::runtime(
+                    line,
+                    format!(
+                        "Unknown diff option '{}'. Supported: tolerance, ignore_case, \
+                         ignore_columns, format",
+                        key
+                    ),
+                ))
+            }
+        }
+    }
+    Ok((options, html))
+}
+
+/// Parse `sheet_profile` options: `top_k` and `bins`.
+fn parse_profile_options(value: &Value, line: usize) -> PipResult<ProfileOptions> {
+    let Value::Object(fields) = value else {
+        return Err(PipError::runtime(
+            line,
+            format!(
+                "Profile options must be an object, got {}",
+                value.type_name()
+            ),
+        ));
+    };
+    let mut options = ProfileOptions::new();
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("top_k", Value::Int(n)) if *n >= 0 => options.top_k = *n as usize,
+            ("bins", Value::Int(n)) if *n >= 0 => options.bins = *n as usize,
+            ("top_k" | "bins", other) => {
+                return Err(PipError::runtime(
+                    line,
+                    format!(
+                        "Profile option '{}' must be a non-negative integer, got {}",
+                        key,
+                        other.type_name()
+                    ),
+                ))
+            }
+            _ => {
+                return Err(PipError::runtime(
+                    line,
+                    format!("Unknown profile option '{}'. Supported: top_k, bins", key),
+                ))
+            }
+        }
+    }
+    Ok(options)
+}
+
+/// Apply sort settings from an object to `key`. `column` and `order` are
+/// only accepted for a single key, not for the options shared by all keys.
+fn apply_sort_fields(
+    mut key: SortKey,
+    fields: &HashMap<String, Value>,
+    per_key: bool,
+    line: usize,
+) -> PipResult<SortKey> {
+    let text = |name: &str, value: &Value| match value {
+        Value::String(s) => Ok(s.clone()),
+        other => Err(PipError::runtime(
+            line,
+            format!(
+                "Sort option '{}' must be a string, got {}",
+                name,
+                other.type_name()
+            ),
+        )),
+    };
+    let flag = |name: &str, value: &Value| match value {
+        Value::Bool(b) => Ok(*b),
+        other => Err(PipError::runtime(
+            line,
+            format!(
+                "Sort option '{}' must be a boolean, got {}",
+                name,
+                other.type_name()
+            ),
+        )),
+    };
+    let sort_err = |e: piptable_sheet::SheetError| PipError::runtime(line, e.to_string());
+
+    for (name, value) in fields {
+        key = match name.as_str() {
+            "column" if per_key => key,
+            "order" if per_key => match text(name, value)?.to_ascii_lowercase().as_str() {
+                "asc" => key.descending(false),
+                "desc" => key.descending(true),
+                other => {
+                    return Err(PipError::runtime(
+                        line,
+                        format!("Unknown sort order '{}', expected asc or desc", other),
+                    ))
+                }
+            },
+            "nulls" => key.nulls(text(name, value)?.parse::<NullOrder>().map_err(sort_err)?),
+            "collation" => {
+                key.collation(text(name, value)?.parse::<Collation>().map_err(sort_err)?)
+            }
+            "natural" => key.natural(flag(name, value)?),
+            "ignore_case" => key.ignore_case(flag(name, value)?),
+            _ => {
+                let supported = if per_key {
+                    "column, order, nulls, natural, ignore_case, collation"
+                } else {
+                    "nulls, natural, ignore_case, collation"
+                };
+                return Err(PipError::runtime(
+                    line,
+                    format!("Unknown sort option '{}'. Supported: {}", name, supported),
+                ));
+            }
+        };
+    }
+    Ok(key)
+}
+
+/// Read sort keys from a key or an array of keys. A key is `"col"`,
+/// `"col desc"` or `{column: "col", order: "desc", ...}`; `options` holds
+/// defaults for every key.
+pub(crate) fn parse_sort_keys(
+    keys: &Value,
+    options: Option<&Value>,
+    line: usize,
+) -> PipResult<Vec<SortKey>> {
+    let defaults = match options {
+        None => None,
+        Some(Value::Object(fields)) => Some(fields),
+        Some(other) => {
+            return Err(PipError::runtime(
+                line,
+                format!("Sort options must be an object, got {}", other.type_name()),
+            ))
+        }
+    };
+    let parse_key = |value: &Value| -> PipResult<SortKey> {
+        let (key, fields) = match value {
+            Value::String(text) => (
+                text.parse::<SortKey>()
+                    .map_err(|e| PipError::runtime(line, e.to_string()))?,
+                None,
+            ),
+            Value::Object(fields) => match fields.get("column") {
+                Some(Value::String(column)) => (SortKey::asc(column.as_str()), Some(fields)),
+                _ => {
+                    return Err(PipError::runtime(
+                        line,
+                        "Sort key objects need a 'column' string",
+                    ))
+                }
+            },
+            other => {
+                return Err(PipError::runtime(
+                    line,
+                    format!(
+                        "Sort key must be a column name or an object, got {}",
+                        other.type_name()
+                    ),
+                ))
+            }
+        };
+        let key = match defaults {
+            Some(defaults) => apply_sort_fields(key, defaults, false, line)?,
+            None => key,
+        };
+        match fields {
+            Some(fields) => apply_sort_fields(key, fields, true, line),
+            None => Ok(key),
+        }
+    };
+
+    match keys {
+        Value::Array(items) if items.is_empty() => {
+            Err(PipError::runtime(line, "Sort needs at least one key"))
+        }
+        Value::Array(items) => items.iter().map(parse_key).collect(),
+        other => Ok(vec![parse_key(other)?]),
+    }
+}
+
+/// Read a schema from `{column: "int"}` or
+/// `{column: {type: "int", nullable: false, format: "0.00"}}`.
+fn parse_schema(value: &Value, line: usize) -> PipResult<SheetSchema> {
+    let Value::Object(columns) = value else {
+        return Err(PipError::runtime(
+            line,
+            format!("Schema must be an object, got {}", value.type_name()),
+        ));
+    };
+    let column_type = |name: &str, value: &Value| match value {
+        Value::String(text) => text
+            .parse::<ColumnType>()
+            .map_err(|e| PipError::runtime(line, e.to_string())),
+        other => Err(PipError::runtime(
+            line,
+            format!(
+                "Type of column '{}' must be a string, got {}",
+                name,
+                other.type_name()
+            ),
+        )),
+    };
+
+    let mut names: Vec<&String> = columns.keys().collect();
+    names.sort();
+    let mut schema = SheetSchema::default();
+    for name in names {
+        let column = match &columns[name] {
+            Value::Object(fields) => {
+                let ty = fields.get("type").ok_or_else(|| {
+                    PipError::runtime(line, format!("Column '{}' needs a 'type'", name))
+                })?;
+                let mut column = ColumnSchema::new(name.as_str(), column_type(name, ty)?);
+                for (key, value) in fields {
+                    column = match (key.as_str(), value) {
+                        ("type", _) => column,
+                        ("nullable", Value::Bool(b)) => column.nullable(*b),
+                        ("format", Value::String(format)) => column.format(format.as_str()),
+                        ("nullable" | "format", other) => {
+                            return Err(PipError::runtime(
+                                line,
+                                format!(
+                                    "Schema field '{}' of column '{}' has the wrong type ({})",
+                                    key,
+                                    name,
+                                    other.type_name()
+                                ),
+                            ))
+                        }
+                        _ => {
+                            return Err(PipError::runtime(
+                                line,
+                                format!(
+                                    "Unknown schema field '{}'. Supported: type, nullable, format",
+                                    key
+                                ),
+                            ))
+                        }
+                    };
+                }
+                column
+            }
+            other => ColumnSchema::new(name.as_str(), column_type(name, other)?),
+        };
+        schema = schema.with_column(column);
+    }
+    Ok(schema)
+}
+
+/// Describe a schema as `{column: {type, nullable, format?}}`.
+fn schema_to_value(schema: &SheetSchema) -> Value {
+    let columns = schema
+        .columns()
+        .iter()
+        .map(|column| {
+            let mut fields = HashMap::new();
+            fields.insert(
+                "type".to_string(),
+                Value::String(column.column_type().to_string()),
+            );
+            fields.insert("nullable".to_string(), Value::Bool(column.is_nullable()));
+            if let Some(format) = column.number_format() {
+                fields.insert("format".to_string(), Value::String(format.to_string()));
+            }
+            (column.name().to_string(), Value::Object(fields))
+        })
+        .collect();
+    Value::Object(columns)
+}
+
+/// Read aggregates from `{name: "sum(col)"}` (ordered by name), an array of
+/// `"sum(col) as name"` strings, or a single string.
+fn parse_aggregates(value: &Value, line: usize) -> PipResult<Vec<Agg>> {
+    let parse = |text: &str| {
+        text.parse::<Agg>()
+            .map_err(|e| PipError::runtime(line, e.to_string()))
+    };
+    match value {
+        Value::Object(fields) => {
+            let mut names: Vec<&String> = fields.keys().collect();
+            names.sort();
+            names
+                .into_iter()
+                .map(|name| match &fields[name] {
+                    Value::String(text) => Ok(parse(text)?.alias(name)),
+                    _ => Err(PipError::runtime(
+                        line,
+                        format!("Aggregate '{}' must be a string like \"sum(amount)\"", name),
+                    )),
+                })
+                .collect()
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::String(text) => parse(text),
+                _ => Err(PipError::runtime(line, "Aggregates must be strings")),
+            })
+            .collect(),
+        Value::String(text) => Ok(vec![parse(text)?]),
+        _ => Err(PipError::runtime(
+            line,
+            "Aggregates must be an object, an array of strings or a string",
+        )),
+    }
+}
+
 /// Handle sheet manipulation built-in functions.
 pub async fn call_sheet_builtin(
     interpreter: &Interpreter,
@@ -808,6 +1283,136 @@ pub async fn call_sheet_builtin(
             }
         }
 
+        "sheet_define_name" => {
+            if args.len() != 3 {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_define_name() takes exactly 3 arguments (sheet, name, refers_to)",
+                )));
+            }
+            match (&args[0], &args[1], &args[2]) {
+                (Value::Sheet(sheet), Value::String(name), Value::String(refers_to)) => {
+                    let mut sheet_clone = sheet.clone();
+                    match sheet_clone.define_name(name, refers_to) {
+                        Ok(()) => Some(Ok(Value::Sheet(sheet_clone))),
+                        Err(e) => Some(Err(PipError::runtime(
+                            line,
+                            format!("Failed to define name '{}': {}", name, e),
+                        ))),
+                    }
+                }
+                _ => Some(Err(PipError::runtime(
+                    line,
+                    "Arguments must be (sheet, string, string)",
+                ))),
+            }
+        }
+
+        "sheet_get_name" => {
+            if args.len() != 2 {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_get_name() takes exactly 2 arguments (sheet, name)",
+                )));
+            }
+            match (&args[0], &args[1]) {
+                (Value::Sheet(sheet), Value::String(name)) => match sheet.name_range(name) {
+                    Ok(range) => Some(Ok(Value::Sheet(Box::new(range)))),
+                    Err(e) => Some(Err(PipError::runtime(
+                        line,
+                        format!("Failed to get name '{}': {}", name, e),
+                    ))),
+                },
+                _ => Some(Err(PipError::runtime(
+                    line,
+                    "Arguments must be (sheet, string)",
+                ))),
+            }
+        }
+
+        "sheet_add_table" => {
+            if !(3..=4).contains(&args.len()) {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_add_table() takes 3 or 4 arguments (sheet, name, range, [totals_row])",
+                )));
+            }
+            let totals_row = match args.get(3) {
+                None => false,
+                Some(Value::Bool(totals_row)) => *totals_row,
+                Some(_) => {
+                    return Some(Err(PipError::runtime(line, "totals_row must be a boolean")))
+                }
+            };
+            match (&args[0], &args[1], &args[2]) {
+                (Value::Sheet(sheet), Value::String(name), Value::String(range)) => {
+                    let mut sheet_clone = sheet.clone();
+                    let added = Table::new(name, range)
+                        .and_then(|table| sheet_clone.add_table(table.with_totals_row(totals_row)));
+                    match added {
+                        Ok(()) => Some(Ok(Value::Sheet(sheet_clone))),
+                        Err(e) => Some(Err(PipError::runtime(
+                            line,
+                            format!("Failed to add table '{}': {}", name, e),
+                        ))),
+                    }
+                }
+                _ => Some(Err(PipError::runtime(
+                    line,
+                    "Arguments must be (sheet, string, string)",
+                ))),
+            }
+        }
+
+        "sheet_get_table" => {
+            if args.len() != 2 {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_get_table() takes exactly 2 arguments (sheet, name)",
+                )));
+            }
+            match (&args[0], &args[1]) {
+                (Value::Sheet(sheet), Value::String(name)) => match sheet.table_data(name) {
+                    Ok(table) => Some(Ok(Value::Sheet(Box::new(table)))),
+                    Err(e) => Some(Err(PipError::runtime(
+                        line,
+                        format!("Failed to get table '{}': {}", name, e),
+                    ))),
+                },
+                _ => Some(Err(PipError::runtime(
+                    line,
+                    "Arguments must be (sheet, string)",
+                ))),
+            }
+        }
+
+        "sheet_spill_range" => {
+            if args.len() != 2 {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_spill_range() takes exactly 2 arguments (sheet, cell)",
+                )));
+            }
+            match (&args[0], &args[1]) {
+                (Value::Sheet(sheet), Value::String(cell)) => match sheet.spill_range(cell) {
+                    Ok(Some(range)) => Some(Ok(Value::String(format!(
+                        "{}:{}",
+                        range.start.to_a1(),
+                        range.end.to_a1()
+                    )))),
+                    Ok(None) => Some(Ok(Value::Null)),
+                    Err(e) => Some(Err(PipError::runtime(
+                        line,
+                        format!("Failed to get spill range of '{}': {}", cell, e),
+                    ))),
+                },
+                _ => Some(Err(PipError::runtime(
+                    line,
+                    "Arguments must be (sheet, string)",
+                ))),
+            }
+        }
+
         "sheet_get_range" => {
             if args.len() != 2 {
                 return Some(Err(PipError::runtime(
@@ -1130,6 +1735,550 @@ pub async fn call_sheet_builtin(
             }
         }
 
+        "sheet_pivot" => {
+            if !(4..=6).contains(&args.len()) {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_pivot() takes 4 to 6 arguments (sheet, index_columns, column, value, aggregation?, options?)",
+                )));
+            }
+            let Value::Sheet(sheet) = &args[0] else {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "First argument must be a sheet",
+                )));
+            };
+            let index_cols = match column_name_list(&args[1], line, "Index columns") {
+                Ok(cols) => cols,
+                Err(e) => return Some(Err(e)),
+            };
+            let (Value::String(column), Value::String(value)) = (&args[2], &args[3]) else {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "Pivot column and value column must be strings",
+                )));
+            };
+            let agg = match args.get(4) {
+                None => Aggregation::Sum,
+                Some(Value::String(agg)) => match agg.parse::<Aggregation>() {
+                    Ok(agg) => agg,
+                    Err(e) => return Some(Err(PipError::runtime(line, e.to_string()))),
+                },
+                Some(_) => {
+                    return Some(Err(PipError::runtime(
+                        line,
+                        "Aggregation must be a string such as \"sum\" or \"count\"",
+                    )))
+                }
+            };
+            let options = match args.get(5).map(|v| parse_pivot_options(v, line)) {
+                None => PivotOptions::default(),
+                Some(Ok(options)) => options,
+                Some(Err(e)) => return Some(Err(e)),
+            };
+
+            let index_refs: Vec<&str> = index_cols.iter().map(String::as_str).collect();
+            match sheet.pivot_with_options(&index_refs, column, value, agg, &options) {
+                Ok(result) => Some(Ok(Value::Sheet(Box::new(result)))),
+                Err(e) => Some(Err(PipError::runtime(
+                    line,
+                    format!("Failed to pivot sheet: {}", e),
+                ))),
+            }
+        }
+
+        "sheet_group_by" => {
+            if args.len() != 3 {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_group_by() takes exactly 3 arguments (sheet, columns, aggregates)",
+                )));
+            }
+            let Value::Sheet(sheet) = &args[0] else {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "First argument must be a sheet",
+                )));
+            };
+            let columns = match column_name_list(&args[1], line, "Group columns") {
+                Ok(cols) => cols,
+                Err(e) => return Some(Err(e)),
+            };
+            let aggs = match parse_aggregates(&args[2], line) {
+                Ok(aggs) => aggs,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let column_refs: Vec<&str> = columns.iter().map(String::as_str).collect();
+            match sheet.group_by(&column_refs).agg(aggs) {
+                Ok(result) => Some(Ok(Value::Sheet(Box::new(result)))),
+                Err(e) => Some(Err(PipError::runtime(
+                    line,
+                    format!("Failed to group sheet: {}", e),
+                ))),
+            }
+        }
+
+        "sheet_diff" => {
+            if !(3..=4).contains(&args.len()) {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_diff() takes 3 or 4 arguments (old, new, keys, options?)",
+                )));
+            }
+            let (Value::Sheet(old), Value::Sheet(new)) = (&args[0], &args[1]) else {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "First two arguments must be sheets",
+                )));
+            };
+            let keys = match column_name_list(&args[2], line, "Key columns") {
+                Ok(keys) => keys,
+                Err(e) => return Some(Err(e)),
+            };
+            let (options, html) = match args.get(3).map(|v| parse_diff_options(v, line)) {
+                None => (DiffOptions::new(), false),
+                Some(Ok(parsed)) => parsed,
+                Some(Err(e)) => return Some(Err(e)),
+            };
+
+            let key_refs: Vec<&str> = keys.iter().map(String::as_str).collect();
+            let report = old.diff(new, &key_refs, &options).and_then(|diff| {
+                if html {
+                    Ok(Value::String(diff.to_html()))
+                } else {
+                    diff.to_sheet().map(|sheet| Value::Sheet(Box::new(sheet)))
+                }
+            });
+            Some(
+                report
+                    .map_err(|e| PipError::runtime(line, format!("Failed to diff sheets: {}", e))),
+            )
+        }
+
+        "sheet_fuzzy_dedupe" => {
+            if !(2..=3).contains(&args.len()) {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_fuzzy_dedupe() takes 2 or 3 arguments (sheet, keys, options?)",
+                )));
+            }
+            let Value::Sheet(sheet) = &args[0] else {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "First argument must be a sheet",
+                )));
+            };
+            let keys = match column_name_list(&args[1], line, "Key columns") {
+                Ok(keys) => keys,
+                Err(e) => return Some(Err(e)),
+            };
+            let (options, cluster_column) =
+                match args.get(2).map(|v| parse_fuzzy_options(v, line, true)) {
+                    None => (FuzzyOptions::new(), None),
+                    Some(Ok(parsed)) => parsed,
+                    Some(Err(e)) => return Some(Err(e)),
+                };
+
+            let key_refs: Vec<&str> = keys.iter().map(String::as_str).collect();
+            let mut result = (**sheet).clone();
+            let outcome = match cluster_column {
+                // Keep every row and number the clusters instead
+                Some(column) => sheet
+                    .fuzzy_clusters(&key_refs, &options)
+                    .and_then(|clusters| {
+                        let header = sheet.data_start_row() == 1;
+                        let mut ids = vec![CellValue::Null; sheet.row_count()];
+                        if header {
+                            ids[0] = CellValue::String(column);
+                        }
+                        for (n, cluster) in clusters.iter().enumerate() {
+                            for &row in cluster {
+                                ids[row] = CellValue::Int(n as i64 + 1);
+                            }
+                        }
+                        result.column_append(ids)?;
+                        if header {
+                            result.name_columns_by_row(0)?;
+                        }
+                        Ok(())
+                    }),
+                None => result
+                    .remove_fuzzy_duplicates(&key_refs, &options)
+                    .map(|_| ()),
+            };
+            match outcome {
+                Ok(()) => Some(Ok(Value::Sheet(Box::new(result)))),
+                Err(e) => Some(Err(PipError::runtime(
+                    line,
+                    format!("Failed to dedupe sheet: {}", e),
+                ))),
+            }
+        }
+
+        "sheet_fuzzy_join" => {
+            if !(3..=4).contains(&args.len()) {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_fuzzy_join() takes 3 or 4 arguments (left, right, key, options?)",
+                )));
+            }
+            let (Value::Sheet(left), Value::Sheet(right)) = (&args[0], &args[1]) else {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "First two arguments must be sheets",
+                )));
+            };
+            let (left_key, right_key) = match &args[2] {
+                Value::String(key) => (key.clone(), key.clone()),
+                Value::Array(keys) => match keys.as_slice() {
+                    [Value::String(l), Value::String(r)] => (l.clone(), r.clone()),
+                    _ => {
+                        return Some(Err(PipError::runtime(
+                            line,
+                            "Join keys must be a column name or [left_key, right_key]",
+                        )))
+                    }
+                },
+                other => {
+                    return Some(Err(PipError::runtime(
+                        line,
+                        format!(
+                            "Join keys must be a column name or [left_key, right_key], got {}",
+                            other.type_name()
+                        ),
+                    )))
+                }
+            };
+            let options = match args.get(3).map(|v| parse_fuzzy_options(v, line, false)) {
+                None => FuzzyOptions::new(),
+                Some(Ok((options, _))) => options,
+                Some(Err(e)) => return Some(Err(e)),
+            };
+            match left.fuzzy_join_on(right, &left_key, &right_key, &options) {
+                Ok(joined) => Some(Ok(Value::Sheet(Box::new(joined)))),
+                Err(e) => Some(Err(PipError::runtime(
+                    line,
+                    format!("Failed to join sheets: {}", e),
+                ))),
+            }
+        }
+
+        "sheet_profile" => {
+            if !(1..=2).contains(&args.len()) {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_profile() takes 1 or 2 arguments (sheet, options?)",
+                )));
+            }
+            let Value::Sheet(sheet) = &args[0] else {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "First argument must be a sheet",
+                )));
+            };
+            let options = match args.get(1).map(|v| parse_profile_options(v, line)) {
+                None => ProfileOptions::new(),
+                Some(Ok(options)) => options,
+                Some(Err(e)) => return Some(Err(e)),
+            };
+            Some(
+                sheet
+                    .profile_with_options(&options)
+                    .map(|profile| Value::Sheet(Box::new(profile)))
+                    .map_err(|e| {
+                        PipError::runtime(line, format!("Failed to profile sheet: {}", e))
+                    }),
+            )
+        }
+
+        "sheet_infer_schema" => {
+            if !(1..=2).contains(&args.len()) {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_infer_schema() takes 1 or 2 arguments (sheet, confidence?)",
+                )));
+            }
+            let Value::Sheet(sheet) = &args[0] else {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "First argument must be a sheet",
+                )));
+            };
+            let confidence = match args.get(1) {
+                None => 0.9,
+                Some(Value::Float(f)) => *f,
+                Some(Value::Int(i)) => *i as f64,
+                Some(other) => {
+                    return Some(Err(PipError::runtime(
+                        line,
+                        format!("Confidence must be a number, got {}", other.type_name()),
+                    )))
+                }
+            };
+            match sheet.infer_schema(confidence) {
+                Ok(schema) => Some(Ok(schema_to_value(&schema))),
+                Err(e) => Some(Err(PipError::runtime(
+                    line,
+                    format!("Failed to infer schema: {}", e),
+                ))),
+            }
+        }
+
+        "sheet_apply_schema" | "sheet_schema_report" => {
+            let apply = name == "sheet_apply_schema";
+            let valid = if apply {
+                (2..=3).contains(&args.len())
+            } else {
+                args.len() == 2
+            };
+            if !valid {
+                let usage = if apply {
+                    "sheet_apply_schema() takes 2 or 3 arguments (sheet, schema, options?)"
+                } else {
+                    "sheet_schema_report() takes exactly 2 arguments (sheet, schema)"
+                };
+                return Some(Err(PipError::runtime(line, usage)));
+            }
+            let Value::Sheet(sheet) = &args[0] else {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "First argument must be a sheet",
+                )));
+            };
+            let schema = match parse_schema(&args[1], line) {
+                Ok(schema) => schema,
+                Err(e) => return Some(Err(e)),
+            };
+            if !apply {
+                return Some(
+                    sheet
+                        .schema_report(&schema)
+                        .and_then(|report| report.to_sheet())
+                        .map(|report| Value::Sheet(Box::new(report)))
+                        .map_err(|e| {
+                            PipError::runtime(line, format!("Failed to check schema: {}", e))
+                        }),
+                );
+            }
+
+            let on_error = match args.get(2) {
+                None => "fail".to_string(),
+                Some(Value::Object(fields)) => {
+                    if let Some(key) = fields.keys().find(|key| *key != "on_error") {
+                        return Some(Err(PipError::runtime(
+                            line,
+                            format!("Unknown schema option '{}'. Supported: on_error", key),
+                        )));
+                    }
+                    match fields.get("on_error") {
+                        None => "fail".to_string(),
+                        Some(Value::String(mode)) => mode.to_ascii_lowercase(),
+                        Some(other) => {
+                            return Some(Err(PipError::runtime(
+                                line,
+                                format!("on_error must be a string, got {}", other.type_name()),
+                            )))
+                        }
+                    }
+                }
+                Some(other) => {
+                    return Some(Err(PipError::runtime(
+                        line,
+                        format!(
+                            "Schema options must be an object, got {}",
+                            other.type_name()
+                        ),
+                    )))
+                }
+            };
+            if !matches!(on_error.as_str(), "fail" | "null" | "keep") {
+                return Some(Err(PipError::runtime(
+                    line,
+                    format!(
+                        "Unknown on_error '{}', expected fail, null or keep",
+                        on_error
+                    ),
+                )));
+            }
+
+            let mut new_sheet = sheet.clone();
+            let report = match new_sheet.apply_schema(schema) {
+                Ok(report) => report,
+                Err(e) => {
+                    return Some(Err(PipError::runtime(
+                        line,
+                        format!("Failed to apply schema: {}", e),
+                    )))
+                }
+            };
+            if !report.is_clean() {
+                match on_error.as_str() {
+                    "fail" => {
+                        let shown: Vec<String> = report
+                            .failures
+                            .iter()
+                            .take(5)
+                            .map(ToString::to_string)
+                            .collect();
+                        let more = if report.failures.len() > 5 {
+                            "; ..."
+                        } else {
+                            ""
+                        };
+                        return Some(Err(PipError::runtime(
+                            line,
+                            format!(
+                                "{} cell(s) do not match the schema: {}{}",
+                                report.failures.len(),
+                                shown.join("; "),
+                                more
+                            ),
+                        )));
+                    }
+                    "null" => {
+                        let names = new_sheet.column_names().cloned().unwrap_or_default();
+                        for failure in &report.failures {
+                            if let Some(col) = names.iter().position(|n| *n == failure.column) {
+                                let _ = new_sheet.set(failure.row, col, CellValue::Null);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Some(Ok(Value::Sheet(new_sheet)))
+        }
+
+        "sheet_sort" => {
+            if !(2..=3).contains(&args.len()) {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_sort() takes 2 or 3 arguments (sheet, keys, options?)",
+                )));
+            }
+            let Value::Sheet(sheet) = &args[0] else {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "First argument must be a sheet",
+                )));
+            };
+            if matches!(args[1], Value::Lambda { .. }) {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_sort() takes sort keys; use sheet.sort((a, b) => ...) for a comparator",
+                )));
+            }
+            let keys = match parse_sort_keys(&args[1], args.get(2), line) {
+                Ok(keys) => keys,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let mut new_sheet = sheet.clone();
+            match new_sheet.sort(&keys) {
+                Ok(()) => Some(Ok(Value::Sheet(new_sheet))),
+                Err(e) => Some(Err(PipError::runtime(
+                    line,
+                    format!("Failed to sort sheet: {}", e),
+                ))),
+            }
+        }
+
+        "sheet_unpivot" => {
+            if !(2..=5).contains(&args.len()) {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "sheet_unpivot() takes 2 to 5 arguments (sheet, id_columns, value_columns?, var_name?, value_name?)",
+                )));
+            }
+            let Value::Sheet(sheet) = &args[0] else {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "First argument must be a sheet",
+                )));
+            };
+            let id_cols = match column_name_list(&args[1], line, "Id columns") {
+                Ok(cols) => cols,
+                Err(e) => return Some(Err(e)),
+            };
+            let value_cols = match args.get(2) {
+                None | Some(Value::Null) => Vec::new(),
+                Some(v) => match column_name_list(v, line, "Value columns") {
+                    Ok(cols) => cols,
+                    Err(e) => return Some(Err(e)),
+                },
+            };
+            let mut names = ["variable".to_string(), "value".to_string()];
+            for (slot, arg) in names.iter_mut().zip(args.iter().skip(3)) {
+                let Value::String(name) = arg else {
+                    return Some(Err(PipError::runtime(
+                        line,
+                        "Variable and value column names must be strings",
+                    )));
+                };
+                slot.clone_from(name);
+            }
+            let [var_name, value_name] = names;
+
+            let id_refs: Vec<&str> = id_cols.iter().map(String::as_str).collect();
+            let value_refs: Vec<&str> = value_cols.iter().map(String::as_str).collect();
+            match sheet.unpivot(&id_refs, &value_refs, &var_name, &value_name) {
+                Ok(result) => Some(Ok(Value::Sheet(Box::new(result)))),
+                Err(e) => Some(Err(PipError::runtime(
+                    line,
+                    format!("Failed to unpivot sheet: {}", e),
+                ))),
+            }
+        }
+
+        "stream" => {
+            if !(1..=3).contains(&args.len()) {
+                return Some(Err(PipError::runtime(
+                    line,
+                    "stream() takes 1 to 3 arguments (path, chunk_rows?, options?)",
+                )));
+            }
+            let Value::String(path) = &args[0] else {
+                return Some(Err(PipError::runtime(line, "Stream path must be a string")));
+            };
+            let chunk_rows = match args.get(1) {
+                None | Some(Value::Null) => DEFAULT_CHUNK_ROWS,
+                Some(Value::Int(n)) if *n > 0 => *n as usize,
+                Some(_) => {
+                    return Some(Err(PipError::runtime(
+                        line,
+                        "Chunk size must be a positive integer",
+                    )))
+                }
+            };
+            let stream = match SheetStream::new(path, chunk_rows) {
+                Ok(stream) => stream,
+                Err(e) => return Some(Err(PipError::runtime(line, e.to_string()))),
+            };
+            match args.get(2) {
+                None | Some(Value::Null) => Some(Ok(Value::Stream(stream))),
+                Some(Value::Object(map)) => match map.get("has_headers") {
+                    None => Some(Ok(Value::Stream(stream))),
+                    Some(Value::Bool(has_headers)) => {
+                        let options = match stream.format() {
+                            StreamFormat::Tsv => CsvOptions::tsv(),
+                            _ => CsvOptions::default(),
+                        }
+                        .with_headers(*has_headers);
+                        Some(Ok(Value::Stream(stream.with_csv_options(options))))
+                    }
+                    Some(_) => Some(Err(PipError::runtime(
+                        line,
+                        "has_headers must be a boolean",
+                    ))),
+                },
+                Some(_) => Some(Err(PipError::runtime(
+                    line,
+                    "Stream options must be an object",
+                ))),
+            }
+        }
+
         _ => None,
     }
 }
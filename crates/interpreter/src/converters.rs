@@ -22,6 +22,7 @@ pub fn value_to_string(val: &Value) -> String {
         Value::Table(_) => "[Table]".to_string(),
         Value::Sheet(_) => "[Sheet]".to_string(),
         Value::Book(_) => "[Book]".to_string(),
+        Value::Stream(stream) => format!("[Stream: {}]", stream.path().display()),
         Value::Function { name, .. } => format!("[Function: {name}]"),
         Value::Lambda { params, .. } => format!("[Lambda: |{}|]", params.join(", ")),
     }
@@ -97,7 +98,10 @@ pub fn sheet_to_arrow(sheet: &Sheet, skip_first_row: usize) -> PipResult<RecordB
                         CellValue::Float(_) => has_float = true,
                         CellValue::Bool(_) => has_bool = true,
                         CellValue::String(_) => has_string = true,
-                        CellValue::Formula(_) => has_string = true,
+                        CellValue::Formula(_)
+                        | CellValue::Date(_)
+                        | CellValue::DateTime(_)
+                        | CellValue::Duration(_) => has_string = true,
                         CellValue::Null => {}
                     }
                 }
@@ -121,6 +125,11 @@ pub fn sheet_to_arrow(sheet: &Sheet, skip_first_row: usize) -> PipResult<RecordB
                                 CellValue::Formula(formula) => {
                                     values.push(Some(formula.source.clone()));
                                 }
+                                temporal @ (CellValue::Date(_)
+                                | CellValue::DateTime(_)
+                                | CellValue::Duration(_)) => {
+                                    values.push(Some(temporal.as_str()));
+                                }
                             }
                         } else {
                             values.push(None);
@@ -343,6 +352,9 @@ pub fn consolidate_book(
                                     CellValue::Formula(formula) => {
                                         Value::String(formula.source.clone())
                                     }
+                                    temporal @ (CellValue::Date(_)
+                                    | CellValue::DateTime(_)
+                                    | CellValue::Duration(_)) => Value::String(temporal.as_str()),
                                 })
                                 .unwrap_or(Value::Null);
                             new_row.insert(col_name.clone(), val);
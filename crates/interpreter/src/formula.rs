@@ -1,7 +1,9 @@
 //! Formula integration helpers for the DSL runtime.
 
 use piptable_core::{PipError, PipResult, Value};
-use piptable_formulas::{CompiledFormula, FormulaEngine, FunctionRegistry, ValueResolver};
+use piptable_formulas::{
+    CompiledFormula, DateSystem, FormulaEngine, FunctionRegistry, TableLocation, ValueResolver,
+};
 use piptable_primitives::{CellAddress, CellRange, ErrorValue, Value as FormulaValue};
 use piptable_sheet::{CellValue, Sheet};
 use std::sync::OnceLock;
@@ -62,7 +64,7 @@ pub fn call_formula_function(name: &str, args: &[Value], line: usize) -> PipResu
 
     validate_arg_count(def.min_args, def.max_args, formula_args.len(), name, line)?;
 
-    let result = (def.eval)(&formula_args);
+    let result = def.call(&formula_args, DateSystem::default());
     formula_to_core(result, line)
 }
 
@@ -294,6 +296,27 @@ impl ValueResolver for SheetResolver<'_> {
     fn current_cell(&self) -> Option<CellAddress> {
         self.base_cell
     }
+
+    /// Resolves the sheet's defined names.
+    fn defined_name(&self, _sheet: Option<&str>, name: &str) -> Option<String> {
+        self.sheet
+            .names()
+            .get(name)
+            .map(|defined| defined.refers_to.clone())
+    }
+
+    /// Resolves the sheet's tables for structured references.
+    fn table(&self, name: Option<&str>) -> Option<TableLocation> {
+        self.sheet.resolve_table(name, self.base_cell)
+    }
+
+    /// Resolves `A1#` to the sheet's spill range for the anchor cell.
+    fn spill_range(&self, sheet: Option<&str>, anchor: &CellAddress) -> Option<CellRange> {
+        if sheet.is_some() {
+            return None;
+        }
+        self.sheet.spill_range(&anchor.to_a1()).ok().flatten()
+    }
 }
 
 /// Validates that a formula function receives the expected number of arguments.
@@ -358,6 +381,10 @@ fn cell_to_formula(cell: &CellValue) -> FormulaValue {
             Some(cached) => cell_to_formula(cached),
             None => FormulaValue::String(formula.source.clone()),
         },
+        // Serial numbers, as Excel sees them
+        CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_) => cell
+            .as_float()
+            .map_or(FormulaValue::Empty, FormulaValue::Float),
     }
 }
 
@@ -373,6 +400,9 @@ fn cell_to_core(cell: &CellValue) -> Value {
             Some(cached) => cell_to_core(cached),
             None => Value::String(formula.source.clone()),
         },
+        CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_) => {
+            Value::String(cell.as_str())
+        }
     }
 }
 
@@ -429,6 +459,10 @@ fn core_to_formula(value: &Value, line: usize) -> PipResult<FormulaValue> {
             "Formula arguments cannot be objects",
         )),
         Value::Book(_) => Err(PipError::runtime(line, "Formula arguments cannot be books")),
+        Value::Stream(_) => Err(PipError::runtime(
+            line,
+            "Formula arguments cannot be streams",
+        )),
         Value::Table(_) => Err(PipError::runtime(
             line,
             "Formula arguments cannot be tables",
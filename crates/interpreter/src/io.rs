@@ -1,9 +1,10 @@
 //! Import and export operations for files.
 
+use arrow::array::RecordBatch;
 use piptable_core::{ImportOptions, Value};
+use piptable_sheet::{Book, CellValue, CsvChunkWriter, CsvOptions, Sheet};
 #[cfg(not(target_arch = "wasm32"))]
-use piptable_sheet::XlsxReadOptions;
-use piptable_sheet::{Book, CellValue, CsvOptions, Sheet};
+use piptable_sheet::{SqliteWriteOptions, XlsxReadOptions};
 use std::path::Path;
 
 /// Convert a CellValue to a serde_json Value
@@ -32,6 +33,10 @@ fn cell_to_json_value(cell: CellValue) -> serde_json::Value {
             }
             JsonValue::Object(obj)
         }
+        // ISO 8601 text
+        temporal @ (CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_)) => {
+            JsonValue::String(temporal.as_str())
+        }
     }
 }
 
@@ -49,6 +54,67 @@ fn has_header_row(sheet: &Sheet) -> bool {
     }
 }
 
+/// Append a sheet's data rows to the end of an existing CSV file whose
+/// header matches the sheet's column names, without loading the file, so
+/// exporting chunks of a stream stays in bounded memory. Returns `false`
+/// when the file is missing or its header differs.
+fn append_csv_rows(sheet: &Sheet, path: &str, delimiter: u8) -> Result<bool, String> {
+    use std::fs::OpenOptions;
+    use std::io::{Read, Seek, SeekFrom, Write};
+
+    let Some(names) = sheet.column_names() else {
+        return Ok(false);
+    };
+    if !Path::new(path).exists() {
+        return Ok(false);
+    }
+
+    let options = CsvOptions::default()
+        .with_delimiter(delimiter)
+        .with_type_inference(false);
+    let first = Sheet::stream_csv(path, options.clone(), 1)
+        .map_err(|e| format!("Failed to read existing file: {}", e))?
+        .next()
+        .transpose()
+        .map_err(|e| format!("Failed to read existing file: {}", e))?;
+    let header_matches = first.is_some_and(|first| {
+        first.row(0).ok().is_some_and(|row| {
+            row.len() == names.len()
+                && row
+                    .iter()
+                    .zip(names.iter())
+                    .all(|(cell, name)| cell.as_str() == *name)
+        })
+    });
+    if !header_matches {
+        return Ok(false);
+    }
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open CSV file for append: {}", e))?;
+    // Start on a new line if the file does not end with one
+    let mut last = [0u8; 1];
+    file.seek(SeekFrom::End(-1))
+        .and_then(|_| file.read_exact(&mut last))
+        .map_err(|e| format!("Failed to read existing file: {}", e))?;
+    if last[0] != b'\n' {
+        file.write_all(b"\n")
+            .map_err(|e| format!("Failed to append data: {}", e))?;
+    }
+
+    let mut writer = CsvChunkWriter::appending(file, options);
+    writer
+        .write(sheet)
+        .map_err(|e| format!("Failed to append data: {}", e))?;
+    writer
+        .finish()
+        .map_err(|e| format!("Failed to append data: {}", e))?;
+    Ok(true)
+}
+
 /// Detect if a CSV file has headers by reading only the first few rows
 fn detect_csv_headers(path: &str, delimiter: u8) -> Result<bool, String> {
     use std::fs::File;
@@ -183,13 +249,15 @@ fn export_sheet_with_mode_impl(sheet: &Sheet, path: &str, mode: ExportMode) -> R
     // For append mode, we need to handle CSV specially
     if append && (path_lower.ends_with(".csv") || path_lower.ends_with(".tsv")) {
         // If file exists, load it first and append new data
-        if std::path::Path::new(path).exists() {
+        let delimiter = if path_lower.ends_with(".tsv") {
+            b'\t'
+        } else {
+            b','
+        };
+        if matches!(mode, ExportMode::Append) && append_csv_rows(sheet, path, delimiter)? {
+            Ok(())
+        } else if std::path::Path::new(path).exists() {
             // Efficiently detect if the existing file has headers by reading only the first few rows
-            let delimiter = if path_lower.ends_with(".tsv") {
-                b'\t'
-            } else {
-                b','
-            };
             let has_headers = detect_csv_headers(path, delimiter)?;
 
             // Load the file with proper header handling
@@ -355,6 +423,8 @@ fn export_sheet_with_mode_impl(sheet: &Sheet, path: &str, mode: ExportMode) -> R
             {
                 Err("Parquet export is not supported in the playground".to_string())
             }
+        } else if is_sqlite_path(path) {
+            export_sqlite(sheet, path, false, None)
         } else if path_lower.ends_with(".toon") {
             sheet
                 .save_as_toon(path)
@@ -665,11 +735,16 @@ pub fn import_sheet(
 
     let path_lower = path.to_lowercase();
     if path_lower.ends_with(".csv") || path_lower.ends_with(".tsv") {
+        let infer_types = options.infer_types.unwrap_or(true);
         let mut sheet = if path_lower.ends_with(".tsv") {
-            Sheet::from_csv_with_options(path, CsvOptions::tsv())
+            Sheet::from_csv_with_options(path, CsvOptions::tsv().with_type_inference(infer_types))
                 .map_err(|e| format!("Failed to import TSV: {}", e))?
         } else {
-            Sheet::from_csv(path).map_err(|e| format!("Failed to import CSV: {}", e))?
+            Sheet::from_csv_with_options(
+                path,
+                CsvOptions::default().with_type_inference(infer_types),
+            )
+            .map_err(|e| format!("Failed to import CSV: {}", e))?
         };
         if has_headers && !sheet.data().is_empty() {
             sheet
@@ -776,6 +851,22 @@ pub fn import_sheet(
         {
             Err("Markdown import is not supported in the playground".to_string())
         }
+    } else if is_sqlite_path(path) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let table = sheet_name.ok_or_else(|| {
+                format!(
+                    "SQLite database '{}' has no default table; use sheet \"<table>\" to pick one",
+                    path
+                )
+            })?;
+            Sheet::from_sqlite(path, table)
+                .map_err(|e| format!("Failed to import SQLite table '{}': {}", table, e))
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            Err("SQLite import is not supported in the playground".to_string())
+        }
     } else {
         Err(format!("Unsupported import format for '{}'", path))
     }
@@ -963,3 +1054,251 @@ pub fn import_markdown_book(path: &str, options: &ImportOptions) -> Result<Value
         Ok(Value::Book(Box::new(book)))
     }
 }
+
+/// Check whether a path names a SQLite database (`.db`, `.sqlite`, `.sqlite3`).
+pub fn is_sqlite_path(path: &str) -> bool {
+    let path_lower = path.to_lowercase();
+    [".db", ".sqlite", ".sqlite3"]
+        .iter()
+        .any(|ext| path_lower.ends_with(ext))
+}
+
+/// Import every table of a SQLite database into a book.
+pub fn import_sqlite_book(path: &str) -> Result<Value, String> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = path;
+        return Err("SQLite import is not supported in the playground".to_string());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let book =
+            Book::from_sqlite(path).map_err(|e| format!("Failed to import SQLite: {}", e))?;
+        Ok(Value::Book(Box::new(book)))
+    }
+}
+
+/// Import the result of a SQL query against a SQLite database as a sheet.
+pub fn import_sqlite_query(path: &str, sql: &str) -> Result<Sheet, String> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = (path, sql);
+        return Err("SQLite import is not supported in the playground".to_string());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Sheet::from_sqlite_query(path, sql).map_err(|e| format!("Failed to import SQLite: {}", e))
+    }
+}
+
+/// Read every table of a SQLite database as Arrow record batches for `attach`.
+pub fn attach_sqlite_tables(path: &str) -> Result<Vec<(String, Vec<RecordBatch>)>, String> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = path;
+        return Err("SQLite attach is not supported in the playground".to_string());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        if !Path::new(path).exists() {
+            return Err(format!("SQLite database '{}' does not exist", path));
+        }
+        let tables = Book::sqlite_record_batches(path)
+            .map_err(|e| format!("Failed to attach SQLite: {}", e))?;
+        Ok(tables
+            .into_iter()
+            .map(|(name, batch)| (name, vec![batch]))
+            .collect())
+    }
+}
+
+/// Export a sheet to a table in a SQLite database.
+///
+/// `options` is the `with {table, mode, key}` object of the export statement.
+/// The table defaults to the database file name, and `append` selects append
+/// mode unless a mode is given explicitly.
+pub fn export_sqlite(
+    sheet: &Sheet,
+    path: &str,
+    append: bool,
+    options: Option<&Value>,
+) -> Result<(), String> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = (sheet, path, append, options);
+        return Err("SQLite export is not supported in the playground".to_string());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let parsed = DatabaseExportOptions::parse(append, options)?;
+        let table = parsed.table.unwrap_or_else(|| {
+            Path::new(path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("data")
+                .to_string()
+        });
+        let mut write_options = SqliteWriteOptions::new(table);
+        if let Some(mode) = parsed.mode {
+            write_options.mode = mode.parse().map_err(|e| format!("{}", e))?;
+        }
+        write_options.key = parsed.key;
+
+        sheet
+            .save_as_sqlite(path, &write_options)
+            .map_err(|e| format!("Failed to export SQLite: {}", e))
+    }
+}
+
+/// Resolve a PostgreSQL connection string, expanding `${NAME}` environment
+/// variable references. Returns `None` when the source is not PostgreSQL.
+pub fn postgres_url(source: &str) -> Result<Option<String>, String> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = source;
+        Ok(None)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let url = if source.contains("${") {
+            piptable_postgres::expand_env(source).map_err(|e| e.to_string())?
+        } else {
+            source.to_string()
+        };
+        Ok(piptable_postgres::is_postgres_url(&url).then_some(url))
+    }
+}
+
+/// Import a PostgreSQL query result or table into a sheet.
+pub async fn import_postgres(
+    url: &str,
+    query: Option<&str>,
+    table: Option<&str>,
+) -> Result<Sheet, String> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = (url, query, table);
+        return Err("PostgreSQL import is not supported in the playground".to_string());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let client = piptable_postgres::PostgresClient::connect(url)
+            .await
+            .map_err(|e| format!("Failed to connect to PostgreSQL: {}", e))?;
+        let result = match (query, table) {
+            (Some(sql), None) => client.query_sheet(sql).await,
+            (None, Some(table)) => client.table_sheet(table).await,
+            _ => {
+                return Err(
+                    "PostgreSQL import needs either query \"SELECT ...\" or sheet \"<table>\""
+                        .to_string(),
+                )
+            }
+        };
+        result.map_err(|e| format!("Failed to import from PostgreSQL: {}", e))
+    }
+}
+
+/// Export a sheet to a PostgreSQL table with `COPY`.
+///
+/// `options` is the `with {table, mode, key}` object of the export statement;
+/// `table` is required.
+pub async fn export_postgres(
+    sheet: &Sheet,
+    url: &str,
+    append: bool,
+    options: Option<&Value>,
+) -> Result<(), String> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = (sheet, url, append, options);
+        return Err("PostgreSQL export is not supported in the playground".to_string());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let parsed = DatabaseExportOptions::parse(append, options)?;
+        let table = parsed
+            .table
+            .ok_or("PostgreSQL export requires a table: with {table: \"name\"}")?;
+        let mut write_options = piptable_postgres::WriteOptions::new(table);
+        if let Some(mode) = parsed.mode {
+            write_options.mode = mode.parse().map_err(|e| format!("{}", e))?;
+        }
+        write_options.key = parsed.key;
+
+        let mut client = piptable_postgres::PostgresClient::connect(url)
+            .await
+            .map_err(|e| format!("Failed to connect to PostgreSQL: {}", e))?;
+        client
+            .write_sheet(sheet, &write_options)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to export to PostgreSQL: {}", e))
+    }
+}
+
+/// Options shared by database exports: `with {table, mode, key}`.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct DatabaseExportOptions {
+    table: Option<String>,
+    mode: Option<String>,
+    key: Option<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DatabaseExportOptions {
+    /// Read the `with` object of an export statement. The `append` keyword
+    /// selects append mode unless a mode is given explicitly.
+    fn parse(append: bool, options: Option<&Value>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let fields = match options {
+            None => None,
+            Some(Value::Object(fields)) => Some(fields),
+            Some(other) => {
+                return Err(format!(
+                    "Export options must be an object, got {}",
+                    other.type_name()
+                ))
+            }
+        };
+
+        for (name, value) in fields.into_iter().flatten() {
+            let Value::String(text) = value else {
+                return Err(format!(
+                    "Export option '{}' must be a string, got {}",
+                    name,
+                    value.type_name()
+                ));
+            };
+            match name.as_str() {
+                "table" => parsed.table = Some(text.clone()),
+                "mode" => parsed.mode = Some(text.clone()),
+                "key" => parsed.key = Some(text.clone()),
+                _ => {
+                    return Err(format!(
+                        "Unknown export option '{}' (expected table, mode or key)",
+                        name
+                    ))
+                }
+            }
+        }
+
+        if append {
+            match &parsed.mode {
+                Some(mode) if !mode.eq_ignore_ascii_case("append") => {
+                    return Err(format!("Cannot combine 'append' with mode \"{}\"", mode));
+                }
+                _ => parsed.mode = Some("append".to_string()),
+            }
+        }
+        Ok(parsed)
+    }
+}
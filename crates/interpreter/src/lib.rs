@@ -21,6 +21,10 @@ pub mod io;
 pub mod sheet_conversions;
 /// SQL string builder helpers for DSL queries.
 mod sql_builder;
+/// Data-modifying SQL statements that write back to variables.
+mod sql_dml;
+
+pub use sql_builder::SqlParams;
 
 #[cfg(feature = "python")]
 /// Python UDF integration for the interpreter.
@@ -30,7 +34,10 @@ use crate::book_conversions::{
     active_sheet_name, book_to_value_dict, consolidate_options_from_value, value_to_sheet_for_book,
 };
 use crate::formula::CachedFormulaEngine;
-use crate::sheet_conversions::{build_sheet_arrow_array, cell_to_value, infer_sheet_column_type};
+use crate::sheet_conversions::{
+    build_sheet_arrow_array, cell_to_value, infer_sheet_column_type, sheet_column_names,
+    sheet_header_rows,
+};
 use async_recursion::async_recursion;
 use piptable_core::{
     BinaryOp, Expr, ImportOptions, LValue, Literal, Param, ParamMode, PipError, PipResult, Program,
@@ -67,6 +74,8 @@ pub struct Interpreter {
     functions: Arc<RwLock<HashMap<String, FunctionDef>>>,
     /// Registered sheet tables (maps variable name to table name)
     sheet_tables: Arc<RwLock<HashMap<String, String>>>,
+    /// Registered book schemas (maps variable name to schema name)
+    book_schemas: Arc<RwLock<HashMap<String, String>>>,
     /// Cached formula engine for sheet evaluation
     pub(crate) formula_engine: Arc<Mutex<CachedFormulaEngine>>,
     /// Python runtime (optional, with `python` feature)
@@ -334,6 +343,7 @@ impl Interpreter {
             output: Arc::new(RwLock::new(Vec::new())),
             functions: Arc::new(RwLock::new(HashMap::new())),
             sheet_tables: Arc::new(RwLock::new(HashMap::new())),
+            book_schemas: Arc::new(RwLock::new(HashMap::new())),
             formula_engine: Arc::new(Mutex::new(CachedFormulaEngine::new())),
             #[cfg(feature = "python")]
             python_runtime: match python::PythonRuntime::new() {
@@ -467,17 +477,18 @@ impl Interpreter {
                     .await
                     .map_err(|e| e.with_line(line))?;
 
-                let items = match iter_val {
-                    Value::Array(arr) => arr,
+                // Streams are read as the loop runs, one chunk in memory at a time
+                let items: Box<dyn Iterator<Item = PipResult<Value>> + Send> = match iter_val {
+                    Value::Array(arr) => Box::new(arr.into_iter().map(Ok)),
                     Value::Table(batches) => {
                         // Convert table rows to array of objects
-                        self.table_to_array(&batches)?
+                        Box::new(self.table_to_array(&batches)?.into_iter().map(Ok))
                     }
                     Value::Sheet(sheet) => {
                         // Convert sheet to array of objects for iteration
                         let value = sheet_conversions::sheet_to_value(&sheet);
                         match value {
-                            Value::Array(arr) => arr,
+                            Value::Array(arr) => Box::new(arr.into_iter().map(Ok)),
                             other => {
                                 return Err(PipError::runtime(
                                     line,
@@ -486,6 +497,18 @@ impl Interpreter {
                             }
                         }
                     }
+                    Value::Stream(stream) => {
+                        let chunks = stream.open().map_err(|e| {
+                            PipError::runtime(line, format!("Failed to open stream: {}", e))
+                        })?;
+                        Box::new(chunks.map(move |chunk| {
+                            chunk
+                                .map(|sheet| Value::Sheet(Box::new(sheet)))
+                                .map_err(|e| {
+                                    PipError::runtime(line, format!("Failed to read chunk: {}", e))
+                                })
+                        }))
+                    }
                     _ => {
                         return Err(PipError::runtime(
                             line,
@@ -497,6 +520,13 @@ impl Interpreter {
                 self.push_scope().await;
                 let mut loop_result: PipResult<()> = Ok(());
                 for item in items {
+                    let item = match item {
+                        Ok(item) => item,
+                        Err(e) => {
+                            loop_result = Err(e);
+                            break;
+                        }
+                    };
                     self.declare_var(&variable, item).await;
                     match self.eval_block(&body).await {
                         Ok(_) => {}
@@ -771,14 +801,6 @@ impl Interpreter {
                 options,
                 line,
             } => {
-                // Reject export options until they are implemented
-                if options.is_some() {
-                    return Err(PipError::Export(format!(
-                        "Line {}: export options are not supported yet",
-                        line
-                    )));
-                }
-
                 // Evaluate source to get data
                 let data = self
                     .eval_expr(&source)
@@ -801,10 +823,37 @@ impl Interpreter {
                     }
                 };
 
+                // Export options are only understood by database targets
+                let postgres_url = io::postgres_url(&path)
+                    .map_err(|e| PipError::Export(format!("Line {}: {}", line, e)))?;
+                let is_sqlite = io::is_sqlite_path(&path);
+                if options.is_some() && !is_sqlite && postgres_url.is_none() {
+                    return Err(PipError::Export(format!(
+                        "Line {}: export options are only supported for SQLite and PostgreSQL databases",
+                        line
+                    )));
+                }
+
                 // Convert Value to Sheet and export
                 let sheet = sheet_conversions::value_to_sheet(&data)
                     .map_err(|e| PipError::Export(format!("Line {}: {}", line, e)))?;
 
+                if is_sqlite || postgres_url.is_some() {
+                    let options = match &options {
+                        Some(expr) => {
+                            Some(self.eval_expr(expr).await.map_err(|e| e.with_line(line))?)
+                        }
+                        None => None,
+                    };
+                    if let Some(url) = postgres_url {
+                        io::export_postgres(&sheet, &url, append, options.as_ref()).await
+                    } else {
+                        io::export_sqlite(&sheet, &path, append, options.as_ref())
+                    }
+                    .map_err(|e| PipError::Export(format!("Line {}: {}", line, e)))?;
+                    return Ok(Value::Null);
+                }
+
                 // Determine format from file extension and export
                 let mode = if append {
                     io::ExportMode::Append
@@ -817,10 +866,45 @@ impl Interpreter {
                 Ok(Value::Null)
             }
 
+            Statement::Attach {
+                source,
+                alias,
+                line,
+            } => {
+                let src = self
+                    .eval_expr(&source)
+                    .await
+                    .map_err(|e| e.with_line(line))?;
+                let Value::String(path) = &src else {
+                    return Err(PipError::Import(format!(
+                        "Line {}: attach source must be a string, got {}",
+                        line,
+                        src.type_name()
+                    )));
+                };
+                if !io::is_sqlite_path(path) {
+                    return Err(PipError::Import(format!(
+                        "Line {}: attach expects a SQLite database (.db, .sqlite, .sqlite3), got '{}'",
+                        line, path
+                    )));
+                }
+
+                // Tables are snapshotted now; re-run attach to pick up changes
+                let tables = io::attach_sqlite_tables(path)
+                    .map_err(|e| PipError::Import(format!("Line {}: {}", line, e)))?;
+                self.sql
+                    .register_schema(&alias, tables)
+                    .await
+                    .map_err(|e| e.with_line(line))?;
+
+                Ok(Value::Null)
+            }
+
             Statement::Import {
                 sources,
                 target,
                 sheet_name,
+                query,
                 options,
                 line,
             } => {
@@ -883,8 +967,53 @@ impl Interpreter {
                     None
                 };
 
+                // Evaluate optional query for database sources
+                let query_str = match &query {
+                    Some(_) if paths.len() > 1 => {
+                        return Err(PipError::Import(format!(
+                            "Line {}: query clause is not supported for multi-file import",
+                            line
+                        )));
+                    }
+                    Some(query_expr) => {
+                        let val = self
+                            .eval_expr(query_expr)
+                            .await
+                            .map_err(|e| e.with_line(line))?;
+                        match val {
+                            Value::String(s) => Some(s),
+                            _ => {
+                                return Err(PipError::Import(format!(
+                                    "Line {}: query must be a string, got {}",
+                                    line,
+                                    val.type_name()
+                                )));
+                            }
+                        }
+                    }
+                    None => None,
+                };
+                let postgres_url = io::postgres_url(&paths[0])
+                    .map_err(|e| PipError::Import(format!("Line {}: {}", line, e)))?;
+                if query_str.is_some() && postgres_url.is_none() && !io::is_sqlite_path(&paths[0]) {
+                    return Err(PipError::Import(format!(
+                        "Line {}: query clause is only supported for SQLite and PostgreSQL sources",
+                        line
+                    )));
+                }
+
                 // Multi-file or single file import
-                let value = if paths.len() > 1 {
+                let value = if let Some(url) = postgres_url {
+                    let sheet =
+                        io::import_postgres(&url, query_str.as_deref(), sheet_name_str.as_deref())
+                            .await
+                            .map_err(|e| PipError::Import(format!("Line {}: {}", line, e)))?;
+                    Value::Sheet(Box::new(sheet))
+                } else if let Some(sql) = &query_str {
+                    let sheet = io::import_sqlite_query(&paths[0], sql)
+                        .map_err(|e| PipError::Import(format!("Line {}: {}", line, e)))?;
+                    Value::Sheet(Box::new(sheet))
+                } else if paths.len() > 1 {
                     // Multi-file import: use Book::from_files_with_options
                     io::import_multi_files(&paths, &options)
                         .map_err(|e| PipError::Import(format!("Line {}: {}", line, e)))?
@@ -938,6 +1067,10 @@ impl Interpreter {
                                 Value::Book(Box::new(book))
                             }
                         }
+                    } else if io::is_sqlite_path(&paths[0]) && sheet_name_str.is_none() {
+                        // Without a sheet clause every table is imported
+                        io::import_sqlite_book(&paths[0])
+                            .map_err(|e| PipError::Import(format!("Line {}: {}", line, e)))?
                     } else {
                         let sheet =
                             io::import_sheet(&paths[0], sheet_name_str.as_deref(), &options)
@@ -1072,17 +1205,18 @@ impl Interpreter {
                         // Determine whether a physical header row exists
                         // Only skip if column_names were set AND the first row matches those names
                         let header_offset = if let Some(names) = sheet.column_names() {
-                            if sheet.data().is_empty() {
-                                0
-                            } else {
-                                let first_row = &sheet.data()[0];
-                                let names_match = names.iter().enumerate().all(|(idx, name)| {
-                                    first_row
-                                        .get(idx)
-                                        .map(|cell| cell.as_str() == name.as_str())
-                                        .unwrap_or(false)
-                                });
-                                usize::from(names_match)
+                            match sheet.row(0) {
+                                Err(_) => 0,
+                                Ok(first_row) => {
+                                    let names_match =
+                                        names.iter().enumerate().all(|(idx, name)| {
+                                            first_row
+                                                .get(idx)
+                                                .map(|cell| cell.as_str() == name.as_str())
+                                                .unwrap_or(false)
+                                        });
+                                    usize::from(names_match)
+                                }
                             }
                         } else {
                             0
@@ -1260,6 +1394,7 @@ impl Interpreter {
             }
 
             Expr::Query(query) => self.eval_query(query).await,
+            Expr::Dml(dml) => self.eval_dml(dml).await,
 
             Expr::Fetch { url, options } => {
                 let url_val = self.eval_expr(url).await?;
@@ -1339,7 +1474,7 @@ impl Interpreter {
                 condition,
             } => {
                 use piptable_core::JoinCondition;
-                use piptable_sheet::SheetError;
+                use piptable_sheet::{AsOfDirection, SheetError};
 
                 // Evaluate both sides to get sheets
                 let left_val = self.eval_expr(left).await?;
@@ -1355,6 +1490,28 @@ impl Interpreter {
 
                 // Perform the join based on the type and condition
                 let result = match (join_type, condition) {
+                    (
+                        _,
+                        JoinCondition::AsOf {
+                            left: l,
+                            right: r,
+                            by,
+                            direction,
+                        },
+                    ) => {
+                        let by: Vec<&str> = by.iter().map(String::as_str).collect();
+                        let direction = match direction {
+                            piptable_core::AsOfDirection::Backward => AsOfDirection::Backward,
+                            piptable_core::AsOfDirection::Forward => AsOfDirection::Forward,
+                            piptable_core::AsOfDirection::Nearest => AsOfDirection::Nearest,
+                        };
+                        left_sheet
+                            .asof_join_on(&right_sheet, &by, l, r, direction)
+                            .map_err(|e: SheetError| PipError::runtime(0, e.to_string()))?
+                    }
+                    (_, JoinCondition::Between { column, start, end }) => left_sheet
+                        .range_join(&right_sheet, column, start, end)
+                        .map_err(|e: SheetError| PipError::runtime(0, e.to_string()))?,
                     (piptable_core::JoinType::Inner, JoinCondition::On(key)) => left_sheet
                         .inner_join(&right_sheet, key)
                         .map_err(|e: SheetError| PipError::runtime(0, e.to_string()))?,
@@ -1794,6 +1951,21 @@ impl Interpreter {
 
         // Functions that still need to be migrated to modules
         match name.to_lowercase().as_str() {
+            "query_sql" => {
+                let arg_vals = self.eval_args(args, line).await?;
+                // query_sql(sql) or query_sql(sql, [args])
+                let (sql, params) = match arg_vals.as_slice() {
+                    [Value::String(sql)] => (sql, &[][..]),
+                    [Value::String(sql), Value::Array(params)] => (sql, params.as_slice()),
+                    _ => {
+                        return Err(PipError::runtime(
+                            line,
+                            "query_sql() takes a SQL string and an optional array of parameters: query_sql(sql, [args])",
+                        ))
+                    }
+                };
+                self.eval_query_sql(sql, params).await
+            }
             "consolidate" => {
                 let arg_vals = self.eval_args(args, line).await?;
                 // consolidate(book) or consolidate(book, source = "_source")
@@ -2373,16 +2545,17 @@ impl Interpreter {
         use piptable_sheet::CellValue;
         use std::sync::Arc;
 
-        // Check if sheet has named columns
-        let column_names = match sheet.column_names() {
-            Some(names) => names.clone(),
-            None => {
-                // Generate default column names
-                (0..sheet.col_count())
-                    .map(|i| format!("column_{}", i))
-                    .collect()
-            }
+        // Declared column types win over inference
+        let sheet = &*sheet
+            .coerced_to_schema()
+            .map_err(|e| PipError::runtime(0, e.to_string()))?;
+        let declared_type = |name: &str| {
+            sheet
+                .schema()
+                .and_then(|schema| schema.column(name))
+                .map(|column| column.column_type().data_type())
         };
+        let column_names = sheet_column_names(sheet);
 
         if column_names.is_empty() {
             // Empty sheet - create schema with no fields
@@ -2391,21 +2564,7 @@ impl Interpreter {
             return Ok(vec![batch]);
         }
 
-        // Determine if we should skip the first row
-        // Only skip if column_names were set AND the first row matches those names
-        let should_skip_first = if sheet.column_names().is_some() && !sheet.data().is_empty() {
-            // Check if first row matches column names
-            let first_row = &sheet.data()[0];
-            let names_match = column_names.iter().enumerate().all(|(idx, name)| {
-                first_row
-                    .get(idx)
-                    .map(|cell| cell.as_str() == name.as_str())
-                    .unwrap_or(false)
-            });
-            usize::from(names_match)
-        } else {
-            0
-        };
+        let should_skip_first = sheet_header_rows(sheet);
 
         let data_rows: Vec<&Vec<CellValue>> = sheet.data().iter().skip(should_skip_first).collect();
 
@@ -2413,7 +2572,7 @@ impl Interpreter {
             // No data rows - create empty table with schema
             let fields: Vec<Field> = column_names
                 .iter()
-                .map(|name| Field::new(name, DataType::Utf8, true))
+                .map(|name| Field::new(name, declared_type(name).unwrap_or(DataType::Utf8), true))
                 .collect();
             let schema = Arc::new(Schema::new(fields));
             let batch = RecordBatch::new_empty(schema.clone());
@@ -2422,9 +2581,13 @@ impl Interpreter {
 
         let num_cols = column_names.len();
 
-        // Infer types for each column
-        let col_types: Vec<DataType> = (0..num_cols)
-            .map(|col_idx| infer_sheet_column_type(&data_rows, col_idx))
+        // Infer types for columns without a declared type
+        let col_types: Vec<DataType> = column_names
+            .iter()
+            .enumerate()
+            .map(|(col_idx, name)| {
+                declared_type(name).unwrap_or_else(|| infer_sheet_column_type(&data_rows, col_idx))
+            })
             .collect();
 
         // Build schema
@@ -2520,6 +2683,22 @@ impl Interpreter {
         Ok(table_name)
     }
 
+    /// Register every sheet of a Book as a table under a schema and return the schema name.
+    /// Note: Book variables are registered with a "book_" prefix, mirroring sheet tables.
+    /// Sheet names are kept verbatim, so `book.sheet` references must quote them.
+    async fn register_book_as_schema(&mut self, name: &str, book: &Book) -> PipResult<String> {
+        let schema_name = format!("book_{}", name.replace(['-', '.', ' '], "_"));
+
+        let mut tables = Vec::with_capacity(book.sheet_count());
+        for (sheet_name, sheet) in book.sheets() {
+            let batches = self.convert_sheet_to_batches(sheet, sheet_name)?;
+            tables.push((sheet_name.to_string(), batches));
+        }
+        self.sql.register_schema(&schema_name, tables).await?;
+
+        Ok(schema_name)
+    }
+
     /// Register a Value::Table variable as a table and return the table name.
     async fn register_table_variable(
         &mut self,
@@ -2797,21 +2976,14 @@ impl Interpreter {
             }
         };
 
-        // Clear any cached table for the variable being assigned.
-        let table_to_drop = {
-            let mut sheet_tables = self.sheet_tables.write().await;
-            match &resolved {
-                Some(ResolvedBinding::Value { name, .. }) => sheet_tables.remove(name),
-                Some(ResolvedBinding::RefTarget(target)) => sheet_tables.remove(&target.name),
-                Some(ResolvedBinding::RefLValue(ref_lvalue)) => {
-                    sheet_tables.remove(&ref_lvalue.base.name)
-                }
-                None => sheet_tables.remove(name),
-            }
+        // Clear any cached table or book schema for the variable being assigned.
+        let cache_key = match &resolved {
+            Some(ResolvedBinding::Value { name, .. }) => name.as_str(),
+            Some(ResolvedBinding::RefTarget(target)) => target.name.as_str(),
+            Some(ResolvedBinding::RefLValue(ref_lvalue)) => ref_lvalue.base.name.as_str(),
+            None => name,
         };
-        if let Some(table_name) = table_to_drop {
-            let _ = self.sql.deregister_table(&table_name).await;
-        }
+        self.forget_sql_tables(cache_key).await;
 
         let mut scopes = self.scopes.write().await;
 
@@ -2843,9 +3015,23 @@ impl Interpreter {
         Ok(())
     }
 
+    /// Drop the SQL table or book schema registered for a variable's old value.
+    async fn forget_sql_tables(&self, name: &str) {
+        let table_to_drop = self.sheet_tables.write().await.remove(name);
+        if let Some(table_name) = table_to_drop {
+            let _ = self.sql.deregister_table(&table_name).await;
+        }
+        let schema_to_drop = self.book_schemas.write().await.remove(name);
+        if let Some(schema_name) = schema_to_drop {
+            let _ = self.sql.deregister_schema(&schema_name).await;
+        }
+    }
+
     /// Declare a variable in the current scope only (shadows outer bindings).
     /// Use this for loop variables and function parameters.
     async fn declare_var(&self, name: &str, value: Value) {
+        // A loop variable gets a new value each pass; queries must not see the last one
+        self.forget_sql_tables(name).await;
         let mut scopes = self.scopes.write().await;
         if let Some(scope) = scopes.last_mut() {
             scope.insert(name.to_string(), VarBinding::Value(Box::new(value)));
@@ -2910,6 +3096,60 @@ impl Interpreter {
         self.pop_scope().await;
         result
     }
+
+    /// Stable merge sort of `rows` with a two-parameter comparator lambda
+    /// returning a negative, zero or positive number. Returns the new order.
+    async fn lambda_sort_order(
+        &mut self,
+        params: &[String],
+        body: &Expr,
+        rows: &[Value],
+    ) -> PipResult<Vec<usize>> {
+        let len = rows.len();
+        let mut order: Vec<usize> = (0..len).collect();
+        let mut merged = Vec::with_capacity(len);
+        let mut width = 1;
+        while width < len {
+            merged.clear();
+            for lo in (0..len).step_by(2 * width) {
+                let mid = (lo + width).min(len);
+                let hi = (lo + 2 * width).min(len);
+                let (mut i, mut j) = (lo, mid);
+                while i < mid && j < hi {
+                    let args = [rows[order[j]].clone(), rows[order[i]].clone()];
+                    let result = self.apply_lambda(params, body, &args).await.map_err(|e| {
+                        PipError::runtime(0, format!("Lambda error in sort(): {}", e))
+                    })?;
+                    // Take from the right only when strictly smaller, keeping ties stable
+                    let right_first = match result {
+                        Value::Int(n) => n < 0,
+                        Value::Float(f) if !f.is_nan() => f < 0.0,
+                        other => {
+                            return Err(PipError::runtime(
+                                0,
+                                format!(
+                                    "sort() comparator must return a number, got {}",
+                                    other.type_name()
+                                ),
+                            ))
+                        }
+                    };
+                    if right_first {
+                        merged.push(order[j]);
+                        j += 1;
+                    } else {
+                        merged.push(order[i]);
+                        i += 1;
+                    }
+                }
+                merged.extend_from_slice(&order[i..mid]);
+                merged.extend_from_slice(&order[j..hi]);
+            }
+            std::mem::swap(&mut order, &mut merged);
+            width *= 2;
+        }
+        Ok(order)
+    }
 }
 
 impl Default for Interpreter {
@@ -3095,17 +3335,18 @@ impl Interpreter {
                         // Apply lambda to each data row (skip header if present)
                         // Check if a physical header row exists by comparing first row with column names
                         let start_row = if let Some(names) = &column_names {
-                            if sheet.data().is_empty() {
-                                0
-                            } else {
-                                let first_row = &sheet.data()[0];
-                                let names_match = names.iter().enumerate().all(|(idx, name)| {
-                                    first_row
-                                        .get(idx)
-                                        .map(|cell| cell.as_str() == name.as_str())
-                                        .unwrap_or(false)
-                                });
-                                usize::from(names_match)
+                            match sheet.row(0) {
+                                Err(_) => 0,
+                                Ok(first_row) => {
+                                    let names_match =
+                                        names.iter().enumerate().all(|(idx, name)| {
+                                            first_row
+                                                .get(idx)
+                                                .map(|cell| cell.as_str() == name.as_str())
+                                                .unwrap_or(false)
+                                        });
+                                    usize::from(names_match)
+                                }
                             }
                         } else {
                             0
@@ -3204,22 +3445,23 @@ impl Interpreter {
                         // Determine which rows to keep (skip header if present)
                         // Check if a physical header row exists by comparing first row with column names
                         let start_row = if let Some(names) = sheet.column_names() {
-                            if sheet.data().is_empty() {
-                                0
-                            } else {
-                                let first_row = &sheet.data()[0];
-                                let names_match = names.iter().enumerate().all(|(idx, name)| {
-                                    first_row
-                                        .get(idx)
-                                        .map(|cell| cell.as_str() == name.as_str())
-                                        .unwrap_or(false)
-                                });
-                                if names_match {
-                                    // Physical header exists, keep it
-                                    rows_to_keep.insert(0);
-                                    1
-                                } else {
-                                    0
+                            match sheet.row(0) {
+                                Err(_) => 0,
+                                Ok(first_row) => {
+                                    let names_match =
+                                        names.iter().enumerate().all(|(idx, name)| {
+                                            first_row
+                                                .get(idx)
+                                                .map(|cell| cell.as_str() == name.as_str())
+                                                .unwrap_or(false)
+                                        });
+                                    if names_match {
+                                        // Physical header exists, keep it
+                                        rows_to_keep.insert(0);
+                                        1
+                                    } else {
+                                        0
+                                    }
                                 }
                             }
                         } else {
@@ -3282,6 +3524,55 @@ impl Interpreter {
                 }
             }
 
+            "sort" => {
+                if args.is_empty() || args.len() > 2 {
+                    return Err(PipError::runtime(
+                        0,
+                        "sort() takes sort keys and optional options, or a comparator lambda",
+                    ));
+                }
+
+                let mut new_sheet = sheet.clone();
+                if let Value::Lambda { params, body } = &args[0] {
+                    if params.len() != 2 || args.len() != 1 {
+                        return Err(PipError::runtime(
+                            0,
+                            "Comparator for sort() must be the only argument and take exactly 2 parameters",
+                        ));
+                    }
+                    // Rows are passed like map() and filter(): objects when columns are named
+                    let column_names = sheet.column_names();
+                    let data = sheet.data();
+                    let rows: Vec<Value> = data[sheet.data_start_row()..]
+                        .iter()
+                        .map(|cells| {
+                            let cell = |col_idx: usize| {
+                                sheet_conversions::cell_to_value(
+                                    cells.get(col_idx).cloned().unwrap_or(CellValue::Null),
+                                )
+                            };
+                            match column_names {
+                                Some(names) => Value::Object(
+                                    names
+                                        .iter()
+                                        .enumerate()
+                                        .map(|(col_idx, name)| (name.clone(), cell(col_idx)))
+                                        .collect(),
+                                ),
+                                None => Value::Array((0..cells.len()).map(cell).collect()),
+                            }
+                        })
+                        .collect();
+                    let order = self.lambda_sort_order(params, body, &rows).await?;
+                    new_sheet.reorder_rows(&order)
+                } else {
+                    let keys = builtins::parse_sort_keys(&args[0], args.get(1), 0)?;
+                    new_sheet.sort(&keys)
+                }
+                .map_err(|e| PipError::runtime(0, format!("Failed to sort sheet: {}", e)))?;
+                Ok(Value::Sheet(Box::new(new_sheet)))
+            }
+
             _ => Err(PipError::runtime(
                 0,
                 format!("Unknown sheet method: {}", method),
@@ -3872,7 +4163,7 @@ export data to "{}""#,
             assert_eq!(col_names[1], "age");
 
             // Check first data row
-            let row = &sheet.data()[1]; // row 0 is header, row 1 is first data row
+            let row = sheet.row(1).unwrap(); // row 0 is header, row 1 is first data row
             assert!(matches!(&row[0], piptable_sheet::CellValue::String(s) if s == "alice"));
         }
     }
@@ -4034,7 +4325,7 @@ export data to "{}""#,
             assert!(sheet.column_names().is_none());
 
             // First row should be "alice,30"
-            let row = &sheet.data()[0];
+            let row = sheet.row(0).unwrap();
             assert!(matches!(&row[0], piptable_sheet::CellValue::String(s) if s == "alice"));
             // CSV parser might parse "30" as an integer
             assert!(
@@ -271,6 +271,9 @@ fn value_to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
         Value::Lambda { .. } => Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
             "Lambda values cannot be converted to Python",
         )),
+        Value::Stream(_) => Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+            "Stream values cannot be converted to Python",
+        )),
         Value::Sheet(sheet) => {
             /// Converts a sheet cell to a Python object.
             fn cell_to_py(py: Python<'_>, cell: &piptable_sheet::CellValue) -> PyResult<PyObject> {
@@ -288,6 +291,15 @@ fn value_to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
                     piptable_sheet::CellValue::String(s) => {
                         Ok(s.into_pyobject(py)?.to_owned().into_any().unbind())
                     }
+                    piptable_sheet::CellValue::Date(d) => {
+                        Ok(d.into_pyobject(py)?.into_any().unbind())
+                    }
+                    piptable_sheet::CellValue::DateTime(dt) => {
+                        Ok(dt.into_pyobject(py)?.into_any().unbind())
+                    }
+                    piptable_sheet::CellValue::Duration(d) => {
+                        Ok(d.into_pyobject(py)?.into_any().unbind())
+                    }
                     piptable_sheet::CellValue::Formula(formula) => Ok(formula
                         .source
                         .clone()
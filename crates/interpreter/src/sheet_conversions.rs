@@ -1,8 +1,16 @@
 //! Conversions between Sheet, Arrow, and Value types.
 
-use arrow::array::{ArrayRef, BooleanArray, Float64Array, Int64Array, StringArray};
-use arrow::datatypes::DataType;
+use arrow::array::{
+    ArrayRef, AsArray, BooleanArray, Date32Array, DurationMicrosecondArray, Float64Array,
+    Int64Array, StringArray, TimestampMicrosecondArray,
+};
+use arrow::datatypes::{
+    DataType, Date32Type, Date64Type, DurationMicrosecondType, DurationMillisecondType,
+    DurationNanosecondType, DurationSecondType, TimeUnit, TimestampMicrosecondType,
+    TimestampMillisecondType, TimestampNanosecondType, TimestampSecondType,
+};
 use arrow::record_batch::RecordBatch;
+use chrono::NaiveDate;
 use piptable_core::Value;
 use piptable_sheet::{CellValue, Sheet};
 use std::collections::HashMap;
@@ -102,42 +110,37 @@ pub fn value_to_cell(value: &Value) -> CellValue {
 
 /// Convert Arrow RecordBatches to a Sheet.
 pub fn arrow_batches_to_sheet(batches: &[Arc<RecordBatch>]) -> Result<Sheet, String> {
-    if batches.is_empty() {
-        return Ok(Sheet::new());
-    }
-
-    let schema = batches[0].schema();
-    let field_names: Vec<String> = schema.fields().iter().map(|f| f.name().clone()).collect();
-
-    let mut sheet = Sheet::new();
-
-    // Add column names as the first row
-    let header_row: Vec<CellValue> = field_names
-        .iter()
-        .map(|name| CellValue::String(name.clone()))
-        .collect();
-    sheet.data_mut().push(header_row);
+    let batches: Vec<RecordBatch> = batches.iter().map(|batch| (**batch).clone()).collect();
+    // Field names become the header row; the arrays are kept as columns
+    Sheet::from_record_batches(&batches).map_err(|e| format!("Failed to build sheet: {}", e))
+}
 
-    // Add data rows
-    for batch in batches {
-        let num_rows = batch.num_rows();
-        for row_idx in 0..num_rows {
-            let mut row_cells = Vec::new();
-            for col_idx in 0..batch.num_columns() {
-                let array = batch.column(col_idx);
-                let cell = arrow_value_to_cell(array, row_idx);
-                row_cells.push(cell);
-            }
-            sheet.data_mut().push(row_cells);
-        }
+/// Column names used when a sheet is exposed as a table.
+///
+/// Sheets without named columns get `column_0`, `column_1`, ...
+pub fn sheet_column_names(sheet: &Sheet) -> Vec<String> {
+    match sheet.column_names() {
+        Some(names) => names.clone(),
+        None => (0..sheet.col_count())
+            .map(|i| format!("column_{}", i))
+            .collect(),
     }
+}
 
-    // Mark that the first row contains column names
-    sheet
-        .name_columns_by_row(0)
-        .map_err(|e| format!("Failed to name columns: {}", e))?;
-
-    Ok(sheet)
+/// Number of leading rows that hold column names rather than data (0 or 1).
+///
+/// Only a first row that matches the named columns is treated as a header.
+pub fn sheet_header_rows(sheet: &Sheet) -> usize {
+    let (Some(names), Some(first_row)) = (sheet.column_names(), sheet.data().first()) else {
+        return 0;
+    };
+    let names_match = names.iter().enumerate().all(|(idx, name)| {
+        first_row
+            .get(idx)
+            .map(|cell| cell.as_str() == name.as_str())
+            .unwrap_or(false)
+    });
+    usize::from(names_match)
 }
 
 /// Convert an Arrow array value at a specific row to CellValue.
@@ -210,6 +213,10 @@ pub fn arrow_value_to_cell(array: &Arc<dyn arrow::array::Array>, row: usize) ->
             let arr = array.as_any().downcast_ref::<LargeStringArray>().unwrap();
             CellValue::String(arr.value(row).to_string())
         }
+        DataType::Date32 | DataType::Date64 | DataType::Timestamp(_, _) | DataType::Duration(_) => {
+            arrow_temporal_to_cell(array, row)
+                .unwrap_or_else(|| CellValue::String(format!("<{}>", array.data_type())))
+        }
         _ => {
             // For other types, convert to string representation
             CellValue::String(format!("{:?}", array))
@@ -217,6 +224,52 @@ pub fn arrow_value_to_cell(array: &Arc<dyn arrow::array::Array>, row: usize) ->
     }
 }
 
+/// Convert a date, timestamp or duration element. Timestamps with a time
+/// zone become their UTC wall-clock time.
+fn arrow_temporal_to_cell(array: &ArrayRef, row: usize) -> Option<CellValue> {
+    match array.data_type() {
+        DataType::Date32 => array
+            .as_primitive::<Date32Type>()
+            .value_as_date(row)
+            .map(CellValue::Date),
+        DataType::Date64 => array
+            .as_primitive::<Date64Type>()
+            .value_as_date(row)
+            .map(CellValue::Date),
+        DataType::Timestamp(unit, _) => match unit {
+            TimeUnit::Second => array
+                .as_primitive::<TimestampSecondType>()
+                .value_as_datetime(row),
+            TimeUnit::Millisecond => array
+                .as_primitive::<TimestampMillisecondType>()
+                .value_as_datetime(row),
+            TimeUnit::Microsecond => array
+                .as_primitive::<TimestampMicrosecondType>()
+                .value_as_datetime(row),
+            TimeUnit::Nanosecond => array
+                .as_primitive::<TimestampNanosecondType>()
+                .value_as_datetime(row),
+        }
+        .map(CellValue::DateTime),
+        DataType::Duration(unit) => match unit {
+            TimeUnit::Second => array
+                .as_primitive::<DurationSecondType>()
+                .value_as_duration(row),
+            TimeUnit::Millisecond => array
+                .as_primitive::<DurationMillisecondType>()
+                .value_as_duration(row),
+            TimeUnit::Microsecond => array
+                .as_primitive::<DurationMicrosecondType>()
+                .value_as_duration(row),
+            TimeUnit::Nanosecond => array
+                .as_primitive::<DurationNanosecondType>()
+                .value_as_duration(row),
+        }
+        .map(CellValue::Duration),
+        _ => None,
+    }
+}
+
 /// Convert a Sheet to a Value (array of objects).
 pub fn sheet_to_value(sheet: &Sheet) -> Value {
     if let Some(column_names) = sheet.column_names() {
@@ -224,17 +277,17 @@ pub fn sheet_to_value(sheet: &Sheet) -> Value {
 
         // Determine if we should skip the first row
         // Only skip if the first row matches the column names
-        let should_skip_first = if sheet.data().is_empty() {
-            0
-        } else {
-            let first_row = &sheet.data()[0];
-            let names_match = column_names.iter().enumerate().all(|(idx, name)| {
-                first_row
-                    .get(idx)
-                    .map(|cell| cell.as_str() == name.as_str())
-                    .unwrap_or(false)
-            });
-            usize::from(names_match)
+        let should_skip_first = match sheet.row(0) {
+            Err(_) => 0,
+            Ok(first_row) => {
+                let names_match = column_names.iter().enumerate().all(|(idx, name)| {
+                    first_row
+                        .get(idx)
+                        .map(|cell| cell.as_str() == name.as_str())
+                        .unwrap_or(false)
+                });
+                usize::from(names_match)
+            }
         };
 
         // Iterate from the appropriate starting point
@@ -278,6 +331,10 @@ pub fn cell_to_value(cell: CellValue) -> Value {
             Some(cached) => cell_to_value(*cached),
             None => Value::String(formula.source),
         },
+        // ISO 8601 text, e.g. "2024-01-15"
+        temporal @ (CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_)) => {
+            Value::String(temporal.as_str())
+        }
     }
 }
 
@@ -290,6 +347,9 @@ pub fn infer_sheet_column_type(
     let mut has_float = false;
     let mut has_bool = false;
     let mut has_string = false;
+    let mut has_date = false;
+    let mut has_datetime = false;
+    let mut has_duration = false;
     let mut all_null = true;
 
     for row in rows {
@@ -317,10 +377,37 @@ pub fn infer_sheet_column_type(
                 has_string = true;
                 all_null = false;
             }
+            CellValue::Date(_) => {
+                has_date = true;
+                all_null = false;
+            }
+            CellValue::DateTime(_) => {
+                has_datetime = true;
+                all_null = false;
+            }
+            CellValue::Duration(_) => {
+                has_duration = true;
+                all_null = false;
+            }
             CellValue::Null => {}
         }
     }
 
+    // Temporal columns stay typed unless mixed with anything else; dates
+    // widen to timestamps
+    let has_instant = has_date || has_datetime;
+    if has_instant || has_duration {
+        return if has_string || has_int || has_float || has_bool || (has_instant && has_duration) {
+            DataType::Utf8
+        } else if has_duration {
+            DataType::Duration(TimeUnit::Microsecond)
+        } else if has_datetime {
+            DataType::Timestamp(TimeUnit::Microsecond, None)
+        } else {
+            DataType::Date32
+        };
+    }
+
     if all_null || has_string || (has_int && has_bool) || (has_float && has_bool) {
         DataType::Utf8
     } else if has_float {
@@ -393,11 +480,48 @@ pub fn build_sheet_arrow_array(
                             CellValue::Bool(b) => Some(b.to_string()),
                             CellValue::Null => None,
                             CellValue::Formula(_) => None,
+                            temporal @ (CellValue::Date(_)
+                            | CellValue::DateTime(_)
+                            | CellValue::Duration(_)) => Some(temporal.as_str()),
                         })
                 })
                 .collect();
             Ok(Arc::new(StringArray::from(values)))
         }
+        DataType::Date32 => {
+            let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid epoch");
+            let values: Vec<Option<i32>> = rows
+                .iter()
+                .map(|row| {
+                    row.get(col_idx)
+                        .and_then(CellValue::as_date)
+                        .and_then(|date| i32::try_from((date - epoch).num_days()).ok())
+                })
+                .collect();
+            Ok(Arc::new(Date32Array::from(values)))
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, None) => {
+            let values: Vec<Option<i64>> = rows
+                .iter()
+                .map(|row| {
+                    row.get(col_idx)
+                        .and_then(CellValue::as_datetime)
+                        .map(|dt| dt.and_utc().timestamp_micros())
+                })
+                .collect();
+            Ok(Arc::new(TimestampMicrosecondArray::from(values)))
+        }
+        DataType::Duration(TimeUnit::Microsecond) => {
+            let values: Vec<Option<i64>> = rows
+                .iter()
+                .map(|row| {
+                    row.get(col_idx)
+                        .and_then(CellValue::as_duration)
+                        .and_then(|d| d.num_microseconds())
+                })
+                .collect();
+            Ok(Arc::new(DurationMicrosecondArray::from(values)))
+        }
         _ => Err(format!("Unsupported data type: {:?}", dtype)),
     }
 }
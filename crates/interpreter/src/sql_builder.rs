@@ -3,54 +3,171 @@
 use crate::Interpreter;
 use async_recursion::async_recursion;
 use piptable_core::{
-    BinaryOp, Expr, FromClause, JoinClause, JoinType, Literal, OrderByItem, PipResult,
+    BinaryOp, Expr, FromClause, JoinClause, JoinType, Literal, OrderByItem, PipError, PipResult,
     SelectClause, SelectItem, SortDirection, SqlQuery, TableRef, UnaryOp, Value,
 };
+use std::collections::HashSet;
 use std::sync::Arc;
 
+/// Values bound while building a SQL string.
+///
+/// DSL values are never spliced into the SQL text. Each one is pushed here and
+/// referenced through a typed `$n` placeholder that DataFusion substitutes
+/// after parsing.
+#[derive(Debug, Default)]
+pub struct SqlParams {
+    /// Bound values; `$1` refers to the first entry.
+    values: Vec<Value>,
+    /// Column names and aliases in scope for the query being built.
+    /// These take precedence over DSL variables with the same name.
+    columns: HashSet<String>,
+}
+
+impl SqlParams {
+    /// Start with the given columns in scope.
+    pub(crate) fn with_columns(columns: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            values: Vec::new(),
+            columns: columns.into_iter().collect(),
+        }
+    }
+
+    /// Bound parameter values, in placeholder order.
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    /// Bind a runtime value and return the SQL that refers to it.
+    fn bind(&mut self, value: Value) -> PipResult<String> {
+        let sql_type = match &value {
+            Value::Null => return Ok("NULL".to_string()),
+            Value::Bool(_) => "BOOLEAN",
+            Value::Int(_) => "BIGINT",
+            Value::Float(_) => "DOUBLE",
+            Value::String(_) => "VARCHAR",
+            Value::Array(items) => {
+                // Arrays expand to a parenthesized list, e.g. for `IN`
+                let mut parts = Vec::with_capacity(items.len());
+                for item in items {
+                    parts.push(self.bind(item.clone())?);
+                }
+                return Ok(format!("({})", parts.join(", ")));
+            }
+            other => {
+                return Err(PipError::runtime(
+                    0,
+                    format!("Cannot use {} value in a SQL query", other.type_name()),
+                ))
+            }
+        };
+        self.values.push(value);
+        Ok(format!("CAST(${} AS {})", self.values.len(), sql_type))
+    }
+}
+
 impl Interpreter {
     /// Evaluate a SQL query by converting it to string and executing.
     pub async fn eval_query(&mut self, query: &SqlQuery) -> PipResult<Value> {
-        let sql = self.sql_query_to_string(query).await?;
-        let batches = self.sql.query(&sql).await?;
+        let mut params = SqlParams::default();
+        let sql = self.sql_query_to_string(query, &mut params).await?;
+        let batches = self.sql.query_with_params(&sql, params.values()).await?;
         Ok(Value::Table(batches.into_iter().map(Arc::new).collect()))
     }
 
-    /// Convert a SQL query AST to a SQL string.
+    /// Run raw SQL with `$1`, `$2`, ... bound to `args`.
+    ///
+    /// Sheet and Table variables referenced by name are registered for the
+    /// duration of the query, unless a table of that name already exists.
+    pub(crate) async fn eval_query_sql(&mut self, sql: &str, args: &[Value]) -> PipResult<Value> {
+        let mut temporary = Vec::new();
+        for name in self.sql.table_references(sql)? {
+            if self.sql.table_exists(&name) {
+                continue;
+            }
+            let batches = match self.get_var(&name).await {
+                Some(Value::Sheet(sheet)) => self.convert_sheet_to_batches(&sheet, &name)?,
+                Some(Value::Table(batches)) if !batches.is_empty() => {
+                    batches.iter().map(|b| (**b).clone()).collect()
+                }
+                _ => continue,
+            };
+            self.sql.register_table(&name, batches).await?;
+            temporary.push(name);
+        }
+
+        let result = self.sql.query_with_params(sql, args).await;
+        for name in temporary {
+            self.sql.deregister_table(&name).await?;
+        }
+        Ok(Value::Table(result?.into_iter().map(Arc::new).collect()))
+    }
+
+    /// Convert a SQL query AST to a SQL string, binding DSL values into `params`.
     #[async_recursion]
-    pub async fn sql_query_to_string(&mut self, query: &SqlQuery) -> PipResult<String> {
+    pub async fn sql_query_to_string(
+        &mut self,
+        query: &SqlQuery,
+        params: &mut SqlParams,
+    ) -> PipResult<String> {
         let mut sql = String::new();
 
         // WITH clause
         if let Some(_with) = &query.with_clause {
-            return Err(piptable_core::PipError::runtime(
+            return Err(PipError::runtime(
                 0,
                 "WITH clause (Common Table Expressions) is not yet supported",
             ));
         }
 
-        // SELECT clause
-        sql.push_str("SELECT ");
-        if query.select.distinct {
-            sql.push_str("DISTINCT ");
+        // FROM and JOIN tables are resolved first so their columns are known
+        // before any expression, ON conditions included, decides between a
+        // column and a DSL variable
+        let mut source = String::new();
+        if let Some(from) = &query.from {
+            source.push_str(" FROM ");
+            source.push_str(&self.from_clause_to_string(from, params).await?);
+        }
+        let mut join_tables = Vec::new();
+        for join in &query.joins {
+            join_tables.push(self.join_table_to_string(join, params).await?);
         }
-        sql.push_str(&self.select_clause_to_string(&query.select).await?);
 
-        // FROM clause
-        if let Some(from) = &query.from {
-            sql.push_str(" FROM ");
-            sql.push_str(&self.from_clause_to_string(from).await?);
+        let mut scope: HashSet<String> = query
+            .select
+            .items
+            .iter()
+            .filter_map(|item| item.alias.clone())
+            .collect();
+        if !source.is_empty() {
+            let mut tables = String::new();
+            for table in &join_tables {
+                tables.push_str(" CROSS JOIN ");
+                tables.push_str(table);
+            }
+            let columns = self
+                .sql
+                .query_columns(&format!("SELECT *{source}{tables}"), params.values())
+                .await?;
+            scope.extend(columns);
         }
+        let outer_scope = std::mem::replace(&mut params.columns, scope);
 
-        // JOIN clauses
-        for join in &query.joins {
-            sql.push_str(&self.join_clause_to_string(join).await?);
+        for (join, table) in query.joins.iter().zip(&join_tables) {
+            source.push_str(&self.join_clause_to_string(join, table, params).await?);
+        }
+
+        // SELECT clause
+        sql.push_str("SELECT ");
+        if query.select.distinct {
+            sql.push_str("DISTINCT ");
         }
+        sql.push_str(&self.select_clause_to_string(&query.select, params).await?);
+        sql.push_str(&source);
 
         // WHERE clause
         if let Some(where_expr) = &query.where_clause {
             sql.push_str(" WHERE ");
-            sql.push_str(&self.expr_to_sql(where_expr).await?);
+            sql.push_str(&self.expr_to_sql(where_expr, params).await?);
         }
 
         // GROUP BY
@@ -58,7 +175,7 @@ impl Interpreter {
             sql.push_str(" GROUP BY ");
             let mut exprs = Vec::new();
             for e in group_by {
-                exprs.push(self.expr_to_sql(e).await?);
+                exprs.push(self.expr_to_sql(e, params).await?);
             }
             sql.push_str(&exprs.join(", "));
         }
@@ -66,53 +183,77 @@ impl Interpreter {
         // HAVING
         if let Some(having) = &query.having {
             sql.push_str(" HAVING ");
-            sql.push_str(&self.expr_to_sql(having).await?);
+            sql.push_str(&self.expr_to_sql(having, params).await?);
         }
 
         // ORDER BY
         if let Some(order_by) = &query.order_by {
             sql.push_str(" ORDER BY ");
-            sql.push_str(&self.order_by_to_string(order_by).await?);
+            sql.push_str(&self.order_by_to_string(order_by, params).await?);
         }
 
         // LIMIT
         if let Some(limit) = &query.limit {
             sql.push_str(" LIMIT ");
-            sql.push_str(&self.expr_to_sql(limit).await?);
+            sql.push_str(&self.row_count_to_sql(limit, "LIMIT").await?);
         }
 
         // OFFSET
         if let Some(offset) = &query.offset {
             sql.push_str(" OFFSET ");
-            sql.push_str(&self.expr_to_sql(offset).await?);
+            sql.push_str(&self.row_count_to_sql(offset, "OFFSET").await?);
         }
 
+        params.columns = outer_scope;
         Ok(sql)
     }
 
+    /// Evaluate a LIMIT/OFFSET expression to a validated integer literal.
+    async fn row_count_to_sql(&mut self, expr: &Expr, clause: &str) -> PipResult<String> {
+        match self.eval_expr(expr).await? {
+            Value::Int(n) if n >= 0 => Ok(n.to_string()),
+            other => Err(PipError::runtime(
+                0,
+                format!(
+                    "{clause} must be a non-negative integer, got {}",
+                    other.type_name()
+                ),
+            )),
+        }
+    }
+
     pub(crate) async fn select_clause_to_string(
         &mut self,
         select: &SelectClause,
+        params: &mut SqlParams,
     ) -> PipResult<String> {
         let mut items = Vec::new();
         for item in &select.items {
-            items.push(self.select_item_to_string(item).await?);
+            items.push(self.select_item_to_string(item, params).await?);
         }
         Ok(items.join(", "))
     }
 
-    pub(crate) async fn select_item_to_string(&mut self, item: &SelectItem) -> PipResult<String> {
-        let expr_str = self.expr_to_sql(&item.expr).await?;
+    pub(crate) async fn select_item_to_string(
+        &mut self,
+        item: &SelectItem,
+        params: &mut SqlParams,
+    ) -> PipResult<String> {
+        let expr_str = self.expr_to_sql(&item.expr, params).await?;
         Ok(match &item.alias {
             Some(alias) => format!("{expr_str} AS {alias}"),
             None => expr_str,
         })
     }
 
-    pub(crate) async fn from_clause_to_string(&mut self, from: &FromClause) -> PipResult<String> {
+    pub(crate) async fn from_clause_to_string(
+        &mut self,
+        from: &FromClause,
+        params: &mut SqlParams,
+    ) -> PipResult<String> {
         // Pass the alias info to table_ref_to_string to avoid double aliasing
         let source = self
-            .table_ref_to_string_with_context(&from.source, from.alias.is_some())
+            .table_ref_to_string_with_context(&from.source, from.alias.is_some(), params)
             .await?;
         Ok(match &from.alias {
             Some(alias) => format!("{source} AS {alias}"),
@@ -125,6 +266,7 @@ impl Interpreter {
         &mut self,
         table_ref: &TableRef,
         has_external_alias: bool,
+        params: &mut SqlParams,
     ) -> PipResult<String> {
         match table_ref {
             TableRef::Table(name) => {
@@ -176,27 +318,83 @@ impl Interpreter {
                 // Otherwise, treat as regular table name
                 Ok(name.clone())
             }
+            TableRef::Qualified {
+                database,
+                schema: None,
+                table,
+            } => {
+                // `book.sheet` addresses a sheet of a Book variable
+                let Some(Value::Book(book)) = self.get_var(database).await else {
+                    // Tables of an attached database keep their original case
+                    let attached = self
+                        .sql
+                        .schema_table_names(database)
+                        .is_some_and(|tables| tables.iter().any(|t| t == table));
+                    if attached {
+                        let source =
+                            format!("{}.{}", quote_sql_ident(database), quote_sql_ident(table));
+                        if has_external_alias {
+                            return Ok(source);
+                        }
+                        return Ok(format!("{} AS {}", source, quote_sql_ident(table)));
+                    }
+                    return Ok(format!("{database}.{table}"));
+                };
+                if !book.has_sheet(table) {
+                    return Err(PipError::runtime(
+                        0,
+                        format!("Sheet '{table}' not found in book '{database}'"),
+                    ));
+                }
+
+                let existing_schema = self.book_schemas.read().await.get(database).cloned();
+                let schema_name = match existing_schema {
+                    Some(schema_name) => schema_name,
+                    None => {
+                        let schema_name = self.register_book_as_schema(database, &book).await?;
+                        let mut book_schemas = self.book_schemas.write().await;
+                        book_schemas.insert(database.to_string(), schema_name.clone());
+                        schema_name
+                    }
+                };
+
+                let source = format!(
+                    "{}.{}",
+                    quote_sql_ident(&schema_name),
+                    quote_sql_ident(table)
+                );
+                // Only auto-alias if no external alias is provided
+                if has_external_alias {
+                    return Ok(source);
+                }
+                Ok(format!("{} AS {}", source, quote_sql_ident(table)))
+            }
             // For other TableRef variants, delegate to the regular method
-            _ => self.table_ref_to_string(table_ref).await,
+            _ => self.table_ref_to_string(table_ref, params).await,
         }
     }
 
     #[async_recursion]
-    pub(crate) async fn table_ref_to_string(&mut self, table_ref: &TableRef) -> PipResult<String> {
+    pub(crate) async fn table_ref_to_string(
+        &mut self,
+        table_ref: &TableRef,
+        params: &mut SqlParams,
+    ) -> PipResult<String> {
         // Default to no external alias context
         match table_ref {
             TableRef::Table(_) => {
-                self.table_ref_to_string_with_context(table_ref, false)
+                self.table_ref_to_string_with_context(table_ref, false, params)
                     .await
             }
             TableRef::Qualified {
                 database,
-                schema,
+                schema: Some(s),
                 table,
-            } => Ok(match schema {
-                Some(s) => format!("{database}.{s}.{table}"),
-                None => format!("{database}.{table}"),
-            }),
+            } => Ok(format!("{database}.{s}.{table}")),
+            TableRef::Qualified { schema: None, .. } => {
+                self.table_ref_to_string_with_context(table_ref, false, params)
+                    .await
+            }
             TableRef::File(path) => {
                 // Register the file and return table name
                 let table_name = self.register_file(path).await?;
@@ -205,13 +403,13 @@ impl Interpreter {
             TableRef::Function { name, args } => {
                 let mut arg_strs = Vec::new();
                 for a in args {
-                    arg_strs.push(self.func_arg_to_string(a).await?);
+                    arg_strs.push(self.func_arg_to_string(a, params).await?);
                 }
                 Ok(format!("{}({})", name, arg_strs.join(", ")))
             }
             TableRef::Stdin => Ok("stdin".to_string()),
             TableRef::Subquery(query) => {
-                let sql = self.sql_query_to_string(query).await?;
+                let sql = self.sql_query_to_string(query, params).await?;
                 Ok(format!("({sql})"))
             }
         }
@@ -220,17 +418,40 @@ impl Interpreter {
     pub(crate) async fn func_arg_to_string(
         &mut self,
         arg: &piptable_core::FunctionArg,
+        params: &mut SqlParams,
     ) -> PipResult<String> {
         match arg {
-            piptable_core::FunctionArg::Positional(expr) => self.expr_to_sql(expr).await,
+            piptable_core::FunctionArg::Positional(expr) => self.expr_to_sql(expr, params).await,
             piptable_core::FunctionArg::Named { name, value } => {
-                let val_str = self.expr_to_sql(value).await?;
+                let val_str = self.expr_to_sql(value, params).await?;
                 Ok(format!("{name} => {val_str}"))
             }
         }
     }
 
-    pub(crate) async fn join_clause_to_string(&mut self, join: &JoinClause) -> PipResult<String> {
+    /// The joined table with its alias, without the join type or condition.
+    pub(crate) async fn join_table_to_string(
+        &mut self,
+        join: &JoinClause,
+        params: &mut SqlParams,
+    ) -> PipResult<String> {
+        // Pass alias context to avoid double aliasing
+        let table = self
+            .table_ref_to_string_with_context(&join.table, join.alias.is_some(), params)
+            .await?;
+        Ok(match &join.alias {
+            Some(alias) => format!("{table} AS {alias}"),
+            None => table,
+        })
+    }
+
+    /// A JOIN clause for `table`, as built by [`Self::join_table_to_string`].
+    pub(crate) async fn join_clause_to_string(
+        &mut self,
+        join: &JoinClause,
+        table: &str,
+        params: &mut SqlParams,
+    ) -> PipResult<String> {
         let join_type = match join.join_type {
             JoinType::Inner => " INNER JOIN ",
             JoinType::Left => " LEFT JOIN ",
@@ -238,20 +459,14 @@ impl Interpreter {
             JoinType::Full => " FULL OUTER JOIN ",
             JoinType::Cross => " CROSS JOIN ",
         };
-
-        // Pass alias context to avoid double aliasing
-        let table = self
-            .table_ref_to_string_with_context(&join.table, join.alias.is_some())
-            .await?;
         let mut result = format!("{join_type}{table}");
 
-        if let Some(alias) = &join.alias {
-            result.push_str(&format!(" AS {alias}"));
-        }
-
         if let Some(on) = &join.on_clause {
             result.push_str(" ON ");
-            result.push_str(&self.expr_to_sql(on).await?);
+            result.push_str(&self.expr_to_sql(on, params).await?);
+        } else if let Some(columns) = &join.using_columns {
+            let quoted: Vec<String> = columns.iter().map(|c| quote_sql_ident(c)).collect();
+            result.push_str(&format!(" USING ({})", quoted.join(", ")));
         }
 
         Ok(result)
@@ -260,16 +475,21 @@ impl Interpreter {
     pub(crate) async fn order_by_to_string(
         &mut self,
         order_by: &[OrderByItem],
+        params: &mut SqlParams,
     ) -> PipResult<String> {
         let mut items = Vec::new();
         for item in order_by {
-            items.push(self.order_item_to_string(item).await?);
+            items.push(self.order_item_to_string(item, params).await?);
         }
         Ok(items.join(", "))
     }
 
-    pub(crate) async fn order_item_to_string(&mut self, item: &OrderByItem) -> PipResult<String> {
-        let expr = self.expr_to_sql(&item.expr).await?;
+    pub(crate) async fn order_item_to_string(
+        &mut self,
+        item: &OrderByItem,
+        params: &mut SqlParams,
+    ) -> PipResult<String> {
+        let expr = self.expr_to_sql(&item.expr, params).await?;
         let dir = match item.direction {
             SortDirection::Asc => "ASC",
             SortDirection::Desc => "DESC",
@@ -278,51 +498,72 @@ impl Interpreter {
     }
 
     /// Convert an expression to SQL string.
+    ///
+    /// Names resolve to columns when a column or alias of that name is in
+    /// scope, and otherwise to scalar DSL variables. DSL values are bound as
+    /// parameters rather than inlined.
     #[async_recursion]
-    pub async fn expr_to_sql(&mut self, expr: &Expr) -> PipResult<String> {
+    pub async fn expr_to_sql(&mut self, expr: &Expr, params: &mut SqlParams) -> PipResult<String> {
         match expr {
             Expr::Literal(lit) => Ok(self.literal_to_sql(lit)),
             Expr::Variable(name) => {
                 if name == "*" {
-                    Ok("*".to_string())
-                } else {
-                    // Quote identifier to avoid conflicts with SQL keywords
-                    Ok(format!("\"{}\"", name))
+                    return Ok("*".to_string());
                 }
+                if !params.columns.contains(name) {
+                    if let Some(value) = self.get_var(name).await {
+                        if is_sql_bindable(&value) {
+                            return params.bind(value);
+                        }
+                    }
+                }
+                // Quote identifier to avoid conflicts with SQL keywords
+                Ok(quote_sql_ident(name))
             }
             Expr::Binary { left, op, right } => {
-                let l = self.expr_to_sql(left).await?;
-                let r = self.expr_to_sql(right).await?;
+                let l = self.expr_to_sql(left, params).await?;
+                let r = self.expr_to_sql(right, params).await?;
                 let op_str = self.binary_op_to_sql(*op);
                 Ok(format!("({l} {op_str} {r})"))
             }
             Expr::Unary { op, operand } => {
-                let val = self.expr_to_sql(operand).await?;
+                let val = self.expr_to_sql(operand, params).await?;
                 match op {
                     UnaryOp::Neg => Ok(format!("-{val}")),
                     UnaryOp::Not => Ok(format!("NOT {val}")),
                 }
             }
             Expr::FieldAccess { object, field } => {
-                let obj = self.expr_to_sql(object).await?;
+                if let Expr::Variable(name) = object.as_ref() {
+                    // `obj->field` on a DSL object is a value; otherwise it
+                    // qualifies a column with a table name or alias
+                    if !params.columns.contains(name)
+                        && matches!(self.get_var(name).await, Some(Value::Object(_)))
+                    {
+                        let val = self.eval_expr(expr).await?;
+                        return params.bind(val);
+                    }
+                    return Ok(format!("{}.{field}", quote_sql_ident(name)));
+                }
+                let obj = self.expr_to_sql(object, params).await?;
                 Ok(format!("{obj}.{field}"))
             }
             Expr::Call { function, args } => {
                 let mut arg_strs = Vec::new();
                 for a in args {
-                    arg_strs.push(self.expr_to_sql(a).await?);
+                    arg_strs.push(self.expr_to_sql(a, params).await?);
                 }
                 Ok(format!("{}({})", function, arg_strs.join(", ")))
             }
             Expr::CallExpr { .. } => {
                 // Fallback to evaluation for non-identifier callees (e.g., lambdas)
                 let val = self.eval_expr(expr).await?;
-                Ok(self.value_to_sql(&val))
+                params.bind(val)
             }
             _ => {
-                // For complex expressions, evaluate and inline the result
+                // For complex expressions, evaluate and bind the result
                 let val = self.eval_expr(expr).await?;
-                Ok(self.value_to_sql(&val))
+                params.bind(val)
             }
         }
     }
@@ -373,16 +614,25 @@ impl Interpreter {
             BinaryOp::In => "IN",
         }
     }
+}
 
-    /// Renders a runtime value to a SQL literal.
-    pub(crate) fn value_to_sql(&self, val: &Value) -> String {
-        match val {
-            Value::Null => "NULL".to_string(),
-            Value::Bool(b) => b.to_string().to_uppercase(),
-            Value::Int(n) => n.to_string(),
-            Value::Float(f) => f.to_string(),
-            Value::String(s) => format!("'{}'", s.replace('\'', "''")),
-            _ => "NULL".to_string(),
-        }
+/// Whether a DSL value can be bound as a SQL scalar or scalar list.
+fn is_sql_bindable(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => !items.is_empty() && items.iter().all(is_sql_scalar),
+        _ => is_sql_scalar(value),
     }
 }
+
+/// Whether a DSL value maps to a single SQL scalar.
+fn is_sql_scalar(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Null | Value::Bool(_) | Value::Int(_) | Value::Float(_) | Value::String(_)
+    )
+}
+
+/// Quote an identifier for SQL, preserving case and allowing spaces.
+pub(crate) fn quote_sql_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
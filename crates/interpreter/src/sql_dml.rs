@@ -0,0 +1,325 @@
+//! Data-modifying SQL (`CREATE TABLE AS`, `INSERT`, `UPDATE`, `DELETE`).
+//!
+//! Statements run against a DSL Sheet or Table variable and the result is
+//! assigned back to that variable. Row selection and new values are computed
+//! by DataFusion; the changes are then applied to a Sheet so that untouched
+//! rows and cells are preserved as they were.
+
+use crate::sheet_conversions::{
+    arrow_batches_to_sheet, arrow_value_to_cell, sheet_column_names, sheet_header_rows,
+    value_to_cell,
+};
+use crate::sql_builder::{quote_sql_ident, SqlParams};
+use crate::Interpreter;
+use arrow::array::{Array, ArrayRef, Int64Array, RecordBatch};
+use arrow::datatypes::{DataType, Field, Schema};
+use piptable_core::{Expr, InsertSource, PipError, PipResult, SqlDml, Value};
+use piptable_sheet::{CellValue, Sheet};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Hidden column carrying each data row's position in the target.
+const ROW_ID_COLUMN: &str = "__pip_row";
+
+/// Kind of variable a statement writes back to.
+#[derive(Clone, Copy)]
+enum TargetKind {
+    Sheet,
+    Table,
+}
+
+impl Interpreter {
+    /// Execute a data-modifying SQL statement and return the affected row count.
+    pub async fn eval_dml(&mut self, dml: &SqlDml) -> PipResult<Value> {
+        match dml {
+            SqlDml::CreateTableAs { name, query } => {
+                let result = self.eval_query(query).await?;
+                let rows = match &result {
+                    Value::Table(batches) => batches.iter().map(|b| b.num_rows()).sum(),
+                    _ => 0,
+                };
+                self.set_var(name, result).await?;
+                Ok(Value::Int(rows as i64))
+            }
+            SqlDml::Insert {
+                table,
+                columns,
+                source,
+            } => self.eval_insert(table, columns.as_deref(), source).await,
+            SqlDml::Update {
+                table,
+                assignments,
+                where_clause,
+            } => {
+                self.eval_update(table, assignments, where_clause.as_deref())
+                    .await
+            }
+            SqlDml::Delete {
+                table,
+                where_clause,
+            } => self.eval_delete(table, where_clause.as_deref()).await,
+        }
+    }
+
+    async fn eval_insert(
+        &mut self,
+        table: &str,
+        columns: Option<&[String]>,
+        source: &InsertSource,
+    ) -> PipResult<Value> {
+        let (mut sheet, kind) = self.load_dml_target(table).await?;
+        let target_columns = sheet_column_names(&sheet);
+
+        let rows: Vec<Vec<CellValue>> = match source {
+            InsertSource::Values(rows) => {
+                let mut cells = Vec::with_capacity(rows.len());
+                for row in rows {
+                    let mut values = Vec::with_capacity(row.len());
+                    for expr in row {
+                        values.push(value_to_cell(&self.eval_expr(expr).await?));
+                    }
+                    cells.push(values);
+                }
+                cells
+            }
+            InsertSource::Query(query) => match self.eval_query(query).await? {
+                Value::Table(batches) => batches_to_rows(&batches),
+                _ => Vec::new(),
+            },
+        };
+
+        // Map each supplied value to its target column
+        let positions: Vec<usize> = match columns {
+            Some(columns) => columns
+                .iter()
+                .map(|column| column_position(&target_columns, column, table))
+                .collect::<PipResult<_>>()?,
+            None => (0..target_columns.len()).collect(),
+        };
+
+        let count = rows.len();
+        for row in rows {
+            let full_row = if target_columns.is_empty() {
+                // Inserting into an empty sheet defines its width
+                row
+            } else {
+                if row.len() != positions.len() {
+                    return Err(PipError::runtime(
+                        0,
+                        format!(
+                            "INSERT into '{table}' expects {} values per row, got {}",
+                            positions.len(),
+                            row.len()
+                        ),
+                    ));
+                }
+                let mut full_row = vec![CellValue::Null; target_columns.len()];
+                for (position, cell) in positions.iter().zip(row) {
+                    full_row[*position] = cell;
+                }
+                full_row
+            };
+            sheet
+                .row_append(full_row)
+                .map_err(|e| PipError::runtime(0, e.to_string()))?;
+        }
+
+        self.store_dml_target(table, sheet, kind).await?;
+        Ok(Value::Int(count as i64))
+    }
+
+    async fn eval_update(
+        &mut self,
+        table: &str,
+        assignments: &[(String, Expr)],
+        where_clause: Option<&Expr>,
+    ) -> PipResult<Value> {
+        let (mut sheet, kind) = self.load_dml_target(table).await?;
+        let target_columns = sheet_column_names(&sheet);
+
+        let mut positions = Vec::with_capacity(assignments.len());
+        let mut select = vec![quote_sql_ident(ROW_ID_COLUMN)];
+        let mut params = SqlParams::with_columns(target_columns.iter().cloned());
+        for (column, expr) in assignments {
+            positions.push(column_position(&target_columns, column, table)?);
+            select.push(self.expr_to_sql(expr, &mut params).await?);
+        }
+
+        let batches = self
+            .query_dml_rows(table, &sheet, &select.join(", "), where_clause, params)
+            .await?;
+
+        let header_rows = sheet_header_rows(&sheet);
+        let mut count = 0;
+        for batch in &batches {
+            let row_ids = row_id_array(batch)?;
+            for row in 0..batch.num_rows() {
+                let sheet_row = header_rows + row_ids.value(row) as usize;
+                for (value_col, position) in positions.iter().enumerate() {
+                    let cell = arrow_value_to_cell(batch.column(value_col + 1), row);
+                    sheet
+                        .set(sheet_row, *position, cell)
+                        .map_err(|e| PipError::runtime(0, e.to_string()))?;
+                }
+                count += 1;
+            }
+        }
+
+        self.store_dml_target(table, sheet, kind).await?;
+        Ok(Value::Int(count))
+    }
+
+    async fn eval_delete(&mut self, table: &str, where_clause: Option<&Expr>) -> PipResult<Value> {
+        let (mut sheet, kind) = self.load_dml_target(table).await?;
+        let params = SqlParams::with_columns(sheet_column_names(&sheet));
+
+        let batches = self
+            .query_dml_rows(
+                table,
+                &sheet,
+                &quote_sql_ident(ROW_ID_COLUMN),
+                where_clause,
+                params,
+            )
+            .await?;
+
+        let mut deleted = HashSet::new();
+        for batch in &batches {
+            let row_ids = row_id_array(batch)?;
+            deleted.extend((0..batch.num_rows()).map(|row| row_ids.value(row) as usize));
+        }
+
+        let header_rows = sheet_header_rows(&sheet);
+        sheet.filter_rows(|idx, _| idx < header_rows || !deleted.contains(&(idx - header_rows)));
+
+        self.store_dml_target(table, sheet, kind).await?;
+        Ok(Value::Int(deleted.len() as i64))
+    }
+
+    /// Run `SELECT <select> FROM <target> [WHERE ...]` against the target's
+    /// data rows, with the hidden row id column available.
+    async fn query_dml_rows(
+        &mut self,
+        table: &str,
+        sheet: &Sheet,
+        select: &str,
+        where_clause: Option<&Expr>,
+        mut params: SqlParams,
+    ) -> PipResult<Vec<RecordBatch>> {
+        let mut sql = format!("SELECT {select}");
+        let filter = match where_clause {
+            Some(expr) => Some(self.expr_to_sql(expr, &mut params).await?),
+            None => None,
+        };
+
+        let batches = with_row_ids(self.convert_sheet_to_batches(sheet, table)?)?;
+        let temp_name = format!("dml_{}", table.replace(['-', '.', ' '], "_"));
+        self.sql.register_table(&temp_name, batches).await?;
+
+        sql.push_str(&format!(
+            " FROM {} AS {}",
+            quote_sql_ident(&temp_name),
+            quote_sql_ident(table)
+        ));
+        if let Some(filter) = filter {
+            sql.push_str(&format!(" WHERE {filter}"));
+        }
+
+        let result = self.sql.query_with_params(&sql, params.values()).await;
+        self.sql.deregister_table(&temp_name).await?;
+        result
+    }
+
+    /// Load a Sheet or Table variable as a Sheet for modification.
+    async fn load_dml_target(&self, name: &str) -> PipResult<(Sheet, TargetKind)> {
+        match self.get_var(name).await {
+            Some(Value::Sheet(sheet)) => Ok((*sheet, TargetKind::Sheet)),
+            Some(Value::Table(batches)) => {
+                let sheet =
+                    arrow_batches_to_sheet(&batches).map_err(|e| PipError::runtime(0, e))?;
+                Ok((sheet, TargetKind::Table))
+            }
+            Some(other) => Err(PipError::runtime(
+                0,
+                format!(
+                    "Cannot modify '{name}' with SQL: expected Sheet or Table, got {}",
+                    other.type_name()
+                ),
+            )),
+            None => Err(PipError::runtime(0, format!("Undefined variable: {name}"))),
+        }
+    }
+
+    /// Assign a modified Sheet back to its variable in the original form.
+    async fn store_dml_target(&self, name: &str, sheet: Sheet, kind: TargetKind) -> PipResult<()> {
+        let value = match kind {
+            TargetKind::Sheet => Value::Sheet(Box::new(sheet)),
+            TargetKind::Table => {
+                let batches = self.convert_sheet_to_batches(&sheet, name)?;
+                Value::Table(batches.into_iter().map(Arc::new).collect())
+            }
+        };
+        self.set_var(name, value).await
+    }
+}
+
+/// Find a column by name in the target of a statement.
+fn column_position(columns: &[String], column: &str, table: &str) -> PipResult<usize> {
+    columns
+        .iter()
+        .position(|c| c == column)
+        .ok_or_else(|| PipError::runtime(0, format!("Column '{column}' not found in '{table}'")))
+}
+
+/// Append a 0-based row id column to a set of batches.
+fn with_row_ids(batches: Vec<RecordBatch>) -> PipResult<Vec<RecordBatch>> {
+    let mut offset = 0i64;
+    let mut result = Vec::with_capacity(batches.len());
+    for batch in batches {
+        let rows = batch.num_rows() as i64;
+        let mut fields: Vec<Field> = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.as_ref().clone())
+            .collect();
+        fields.push(Field::new(ROW_ID_COLUMN, DataType::Int64, false));
+
+        let mut columns: Vec<ArrayRef> = batch.columns().to_vec();
+        columns.push(Arc::new(Int64Array::from_iter_values(
+            offset..offset + rows,
+        )));
+
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+            .map_err(|e| PipError::runtime(0, format!("Failed to add row ids: {e}")))?;
+        result.push(batch);
+        offset += rows;
+    }
+    Ok(result)
+}
+
+/// The row id column, always selected first.
+fn row_id_array(batch: &RecordBatch) -> PipResult<&Int64Array> {
+    batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .ok_or_else(|| PipError::runtime(0, "Row id column has an unexpected type"))
+}
+
+/// Flatten query results into rows of cells.
+fn batches_to_rows(batches: &[Arc<RecordBatch>]) -> Vec<Vec<CellValue>> {
+    let mut rows = Vec::new();
+    for batch in batches {
+        for row in 0..batch.num_rows() {
+            rows.push(
+                batch
+                    .columns()
+                    .iter()
+                    .map(|array| arrow_value_to_cell(array, row))
+                    .collect(),
+            );
+        }
+    }
+    rows
+}
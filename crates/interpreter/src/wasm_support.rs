@@ -38,11 +38,55 @@ impl SqlEngine {
         Ok(())
     }
 
+    pub async fn register_schema(
+        &self,
+        _name: &str,
+        _tables: Vec<(String, Vec<RecordBatch>)>,
+    ) -> PipResult<()> {
+        Err(PipError::Sql(
+            "SQL is not supported in the playground".into(),
+        ))
+    }
+
+    pub async fn deregister_schema(&self, _name: &str) -> PipResult<()> {
+        Ok(())
+    }
+
+    pub fn schema_table_names(&self, _name: &str) -> Option<Vec<String>> {
+        None
+    }
+
     pub async fn query(&self, _sql: &str) -> PipResult<Vec<RecordBatch>> {
         Err(PipError::Sql(
             "SQL is not supported in the playground".into(),
         ))
     }
+
+    pub async fn query_with_params(
+        &self,
+        _sql: &str,
+        _params: &[Value],
+    ) -> PipResult<Vec<RecordBatch>> {
+        Err(PipError::Sql(
+            "SQL is not supported in the playground".into(),
+        ))
+    }
+
+    pub async fn query_columns(&self, _sql: &str, _params: &[Value]) -> PipResult<Vec<String>> {
+        Err(PipError::Sql(
+            "SQL is not supported in the playground".into(),
+        ))
+    }
+
+    pub fn table_references(&self, _sql: &str) -> PipResult<Vec<String>> {
+        Err(PipError::Sql(
+            "SQL is not supported in the playground".into(),
+        ))
+    }
+
+    pub fn table_exists(&self, _name: &str) -> bool {
+        false
+    }
 }
 
 pub struct HttpClient;
@@ -0,0 +1,86 @@
+//! Sheet diff DSL tests.
+
+mod common {
+    include!("common_impl.txt");
+}
+use common::*;
+
+use piptable_core::Value;
+use piptable_sheet::CellValue;
+
+const YESTERDAY: &str = "id,name,amount\n1,Ann,10.00\n2,Bo,7\n3,Cy,4\n";
+const TODAY: &str = "id,name,amount\n1,ANN,10.004\n2,Bo,8\n4,Di,5\n";
+
+#[tokio::test]
+async fn test_sheet_diff_dsl() {
+    let old = create_temp_csv(YESTERDAY);
+    let new = create_temp_csv(TODAY);
+    let script = format!(
+        r#"
+        import "{}" into old
+        import "{}" into new
+        dim exact = sheet_diff(old, new, "id")
+        dim loose = sheet_diff(old, new, ["id"], {{tolerance: 0.01, ignore_case: true}})
+        dim report = sheet_diff(old, new, "id", {{ignore_columns: "name", format: "html"}})
+        "#,
+        old.path().display(),
+        new.path().display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    let Some(Value::Sheet(exact)) = interp.get_var("exact").await else {
+        panic!("Expected sheet");
+    };
+    assert_eq!(
+        exact.column_names().unwrap(),
+        &["change", "id", "column", "old", "new"]
+    );
+    // Three changed values, two values each for the added and removed rows
+    assert_eq!(exact.row_count(), 1 + 3 + 2 + 2);
+    let row = exact.row(1).unwrap();
+    assert_eq!(row[0].as_str(), "changed");
+    assert_eq!(row[1], CellValue::Int(1));
+    assert_eq!(row[2].as_str(), "name");
+
+    let Some(Value::Sheet(loose)) = interp.get_var("loose").await else {
+        panic!("Expected sheet");
+    };
+    let changed: Vec<String> = (1..loose.row_count())
+        .filter(|&r| loose.get(r, 0).unwrap().as_str() == "changed")
+        .map(|r| loose.get(r, 2).unwrap().as_str())
+        .collect();
+    assert_eq!(changed, vec!["amount".to_string()]);
+
+    let Some(Value::String(html)) = interp.get_var("report").await else {
+        panic!("Expected HTML string");
+    };
+    assert!(html.contains("<p>2 changed, 1 added, 1 removed</p>"));
+    assert!(!html.contains("ANN"));
+}
+
+#[tokio::test]
+async fn test_sheet_diff_errors() {
+    let old = create_temp_csv(YESTERDAY);
+    let script = format!(
+        r#"
+        import "{}" into old
+        dim d = sheet_diff(old, old, "missing")
+        "#,
+        old.path().display()
+    );
+    let err = run_script_err(&script).await;
+    assert!(
+        err.contains("Key column 'missing' not found in old sheet"),
+        "{err}"
+    );
+
+    let script = format!(
+        r#"
+        import "{}" into old
+        dim d = sheet_diff(old, old, "id", {{format: "pdf"}})
+        "#,
+        old.path().display()
+    );
+    let err = run_script_err(&script).await;
+    assert!(err.contains("Unknown diff format 'pdf'"), "{err}");
+}
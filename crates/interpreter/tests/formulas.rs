@@ -280,3 +280,340 @@ async fn test_sheet_set_formula_invalid_formula() {
         .to_string();
     assert!(err.contains("Failed to set formula 'A1'"));
 }
+
+#[tokio::test]
+async fn test_book_cross_sheet_formulas() {
+    let mut interp = Interpreter::new();
+    let mut book = piptable_sheet::Book::new();
+    book.add_sheet("Data", Sheet::from_data(vec![vec![1, 2], vec![3, 4]]))
+        .expect("add sheet");
+    book.add_sheet("Report", Sheet::from_data(vec![vec![CellValue::Null]]))
+        .expect("add sheet");
+    interp
+        .set_var("b", Value::Book(Box::new(book)))
+        .await
+        .expect("set book");
+
+    let script = r#"
+        dim b = book_set_formula(b, "Report", "A1", "=SUM(Data!A1:B2)")
+        dim b = book_evaluate_formulas(b)
+        dim total = sheet_get_cell(book_get_sheet(b, "Report"), "A1")
+        dim b = book_remove_sheet(b, "Data")
+        dim b = book_evaluate_formulas(b)
+        dim missing = sheet_get_cell(book_get_sheet(b, "Report"), "A1")
+    "#;
+    let program = PipParser::parse_str(script).expect("parse script");
+    interp.eval(program).await.expect("eval script");
+
+    assert!(matches!(
+        interp.get_var("total").await,
+        Some(Value::Float(f)) if (f - 10.0).abs() < 1e-9
+    ));
+    assert!(matches!(
+        interp.get_var("missing").await,
+        Some(Value::String(s)) if s == "#REF!"
+    ));
+}
+
+#[tokio::test]
+async fn test_defined_names_in_dsl() {
+    let mut interp = Interpreter::new();
+    let mut book = piptable_sheet::Book::new();
+    book.add_sheet("Data", Sheet::from_data(vec![vec![100], vec![250]]))
+        .expect("add sheet");
+    book.add_sheet("Report", Sheet::from_data(vec![vec![CellValue::Null]]))
+        .expect("add sheet");
+    interp
+        .set_var("b", Value::Book(Box::new(book)))
+        .await
+        .expect("set book");
+
+    let script = r#"
+        dim b = book_define_name(b, "Sales", "Data!$A$1:$A$2")
+        dim b = book_define_name(b, "TaxRate", "0.1", "Report")
+        dim b = book_set_formula(b, "Report", "A1", "=SUM(Sales)*TaxRate")
+        dim b = book_evaluate_formulas(b)
+        dim total = sheet_get_cell(book_get_sheet(b, "Report"), "A1")
+        dim sales = book_get_name(b, "sales")
+        dim names = book_names(b)
+
+        dim s = sheet_define_name(book_get_sheet(b, "Data"), "Top", "$A$2")
+        dim top = sheet_eval_formula(s, "Top * 2")
+        dim top_range = sheet_get_name(s, "Top")
+    "#;
+    let program = PipParser::parse_str(script).expect("parse script");
+    interp.eval(program).await.expect("eval script");
+
+    assert!(matches!(
+        interp.get_var("total").await,
+        Some(Value::Float(f)) if (f - 35.0).abs() < 1e-9
+    ));
+    let Some(Value::Sheet(sales)) = interp.get_var("sales").await else {
+        panic!("Expected sheet");
+    };
+    assert_eq!(sales.row_count(), 2);
+    let Some(Value::Array(names)) = interp.get_var("names").await else {
+        panic!("Expected array");
+    };
+    assert_eq!(names.len(), 2);
+    assert!(matches!(
+        &names[1],
+        Value::Object(fields) if matches!(fields.get("scope"), Some(Value::String(s)) if s == "Report")
+    ));
+    assert!(matches!(
+        interp.get_var("top").await,
+        Some(Value::Float(f)) if (f - 500.0).abs() < 1e-9
+    ));
+    assert!(matches!(
+        interp.get_var("top_range").await,
+        Some(Value::Sheet(_))
+    ));
+
+    let program =
+        PipParser::parse_str(r#"dim bad = book_define_name(b, "A1", "1")"#).expect("parse");
+    let err = interp
+        .eval(program)
+        .await
+        .expect_err("expected error")
+        .to_string();
+    assert!(err.contains("Failed to define name 'A1'"), "{err}");
+}
+
+#[tokio::test]
+async fn test_structured_references_in_dsl() {
+    let mut interp = Interpreter::new();
+    let sheet = Sheet::from_data(vec![
+        vec![
+            CellValue::from("Qty"),
+            CellValue::from("Price"),
+            CellValue::from("Total"),
+        ],
+        vec![CellValue::from(2), CellValue::from(10), CellValue::Null],
+        vec![CellValue::from(3), CellValue::from(20), CellValue::Null],
+    ]);
+    interp
+        .set_var("s", Value::Sheet(Box::new(sheet)))
+        .await
+        .expect("set sheet");
+
+    let script = r#"
+        dim s = sheet_add_table(s, "Sales", "A1:C3")
+        dim s = sheet_set_formula(s, "C2", "=[@Qty]*[@Price]")
+        dim s = sheet_evaluate_formulas(s)
+        dim line_total = sheet_get_cell(s, "C2")
+        dim qty = sheet_eval_formula(s, "SUM(Sales[Qty])")
+        dim sales = sheet_get_table(s, "Sales")
+
+        dim b = book_add_table(book_from_dict({"Data": [["Amount"], [5], [7]], "Report": [[0]]}), "Data", "Amounts", "A1:A3")
+        dim b = book_set_formula(b, "Report", "A1", "=SUM(Amounts[Amount])")
+        dim b = book_evaluate_formulas(b)
+        dim amount = sheet_get_cell(book_get_sheet(b, "Report"), "A1")
+    "#;
+    let program = PipParser::parse_str(script).expect("parse script");
+    interp.eval(program).await.expect("eval script");
+
+    assert!(matches!(
+        interp.get_var("line_total").await,
+        Some(Value::Float(f)) if (f - 20.0).abs() < 1e-9
+    ));
+    assert!(matches!(
+        interp.get_var("qty").await,
+        Some(Value::Float(f)) if (f - 5.0).abs() < 1e-9
+    ));
+    let Some(Value::Sheet(sales)) = interp.get_var("sales").await else {
+        panic!("Expected sheet");
+    };
+    assert_eq!(sales.row_count(), 3);
+    assert!(matches!(
+        interp.get_var("amount").await,
+        Some(Value::Float(f)) if (f - 12.0).abs() < 1e-9
+    ));
+
+    let program =
+        PipParser::parse_str(r#"dim bad = sheet_add_table(s, "Other", "A1:Z9")"#).expect("parse");
+    let err = interp
+        .eval(program)
+        .await
+        .expect_err("expected error")
+        .to_string();
+    assert!(err.contains("Failed to add table 'Other'"), "{err}");
+}
+
+#[tokio::test]
+async fn test_dynamic_arrays_spill_in_dsl() {
+    let mut interp = Interpreter::new();
+    let sheet = Sheet::from_data(vec![
+        vec![
+            CellValue::from("b"),
+            CellValue::from(3),
+            CellValue::Null,
+            CellValue::Null,
+        ],
+        vec![
+            CellValue::from("a"),
+            CellValue::from(1),
+            CellValue::Null,
+            CellValue::Null,
+        ],
+        vec![
+            CellValue::from("c"),
+            CellValue::from(2),
+            CellValue::Null,
+            CellValue::Null,
+        ],
+        vec![CellValue::Null; 4],
+    ]);
+    interp
+        .set_var("s", Value::Sheet(Box::new(sheet)))
+        .await
+        .expect("set sheet");
+
+    let script = r#"
+        dim s = sheet_set_formula(s, "C1", "=SORTBY(A1:A3, B1:B3)")
+        dim s = sheet_set_formula(s, "D1", "=SEQUENCE(3)*B1:B3")
+        dim s = sheet_set_formula(s, "A4", "=SUM(D1#)")
+        dim s = sheet_evaluate_formulas(s)
+        dim first = sheet_get_cell(s, "C1")
+        dim last = sheet_get_cell(s, "C3")
+        dim spill = sheet_spill_range(s, "C1")
+        dim products = sheet_get_cell(s, "A4")
+        dim total = sheet_eval_formula(s, "SUM(FILTER(B1:B3, B1:B3>1))")
+
+        dim s = sheet_set_a1(s, "C2", "x")
+        dim s = sheet_evaluate_formulas(s)
+        dim blocked = sheet_get_cell(s, "C1")
+        dim none = sheet_spill_range(s, "C1")
+    "#;
+    let program = PipParser::parse_str(script).expect("parse script");
+    interp.eval(program).await.expect("eval script");
+
+    assert!(matches!(interp.get_var("first").await, Some(Value::String(s)) if s == "a"));
+    assert!(matches!(interp.get_var("last").await, Some(Value::String(s)) if s == "b"));
+    assert!(matches!(interp.get_var("spill").await, Some(Value::String(s)) if s == "C1:C3"));
+    assert!(matches!(
+        interp.get_var("products").await,
+        Some(Value::Float(f)) if (f - 11.0).abs() < 1e-9
+    ));
+    assert!(matches!(
+        interp.get_var("total").await,
+        Some(Value::Float(f)) if (f - 5.0).abs() < 1e-9
+    ));
+    assert!(matches!(interp.get_var("blocked").await, Some(Value::String(s)) if s == "#SPILL!"));
+    assert!(matches!(interp.get_var("none").await, Some(Value::Null)));
+}
+
+#[tokio::test]
+async fn test_let_and_lambda_in_dsl() {
+    let mut interp = Interpreter::new();
+    let sheet = Sheet::from_data(vec![vec![2, 0], vec![3, 0], vec![4, 0]]);
+    interp
+        .set_var("s", Value::Sheet(Box::new(sheet)))
+        .await
+        .expect("set sheet");
+
+    let script = r#"
+        dim s = sheet_define_name(s, "Square", "=LAMBDA(x, x*x)")
+        dim s = sheet_set_formula(s, "B1", "=Square(A1)")
+        dim s = sheet_evaluate_formulas(s)
+        dim squared = sheet_get_cell(s, "B1")
+        dim total = sheet_eval_formula(s, "LET(xs, A1:A3, REDUCE(0, xs, LAMBDA(acc, v, acc + Square(v))))")
+        dim rows = sheet_eval_formula(s, "SUM(MAKEARRAY(2, 3, LAMBDA(r, c, r*c)))")
+    "#;
+    let program = PipParser::parse_str(script).expect("parse script");
+    interp.eval(program).await.expect("eval script");
+
+    assert!(matches!(
+        interp.get_var("squared").await,
+        Some(Value::Float(f)) if (f - 4.0).abs() < 1e-9
+    ));
+    assert!(matches!(
+        interp.get_var("total").await,
+        Some(Value::Float(f)) if (f - 29.0).abs() < 1e-9
+    ));
+    assert!(matches!(
+        interp.get_var("rows").await,
+        Some(Value::Float(f)) if (f - 18.0).abs() < 1e-9
+    ));
+}
+
+#[tokio::test]
+async fn test_text_functions_in_dsl() {
+    let mut interp = Interpreter::new();
+    let sheet = Sheet::from_data(vec![vec![
+        CellValue::from("ann@example.com"),
+        CellValue::from(1234.5),
+    ]]);
+    interp
+        .set_var("s", Value::Sheet(Box::new(sheet)))
+        .await
+        .expect("set sheet");
+
+    let script = r##"
+        dim domain = sheet_eval_formula(s, "TEXTAFTER(A1, \"@\")")
+        dim label = sheet_eval_formula(s, "TEXT(B1, \"#,##0.00\") & \" due\"")
+        dim user = sheet_eval_formula(s, "UPPER(REGEXEXTRACT(A1, \"^[a-z]+\"))")
+    "##;
+    let program = PipParser::parse_str(script).expect("parse script");
+    interp.eval(program).await.expect("eval script");
+
+    assert!(matches!(interp.get_var("domain").await, Some(Value::String(s)) if s == "example.com"));
+    assert!(matches!(interp.get_var("label").await, Some(Value::String(s)) if s == "1,234.50 due"));
+    assert!(matches!(interp.get_var("user").await, Some(Value::String(s)) if s == "ANN"));
+}
+
+#[tokio::test]
+async fn test_date_functions_in_dsl() {
+    let mut interp = Interpreter::new();
+    let sheet = Sheet::from_data(vec![vec![
+        CellValue::from("2024-01-31"),
+        CellValue::from("2024-02-12"),
+    ]]);
+    interp
+        .set_var("s", Value::Sheet(Box::new(sheet)))
+        .await
+        .expect("set sheet");
+
+    let script = r##"
+        dim month_end = sheet_eval_formula(s, "EOMONTH(A1, 1)")
+        dim workdays = sheet_eval_formula(s, "NETWORKDAYS(A1, B1)")
+        dim months = sheet_eval_formula(s, "DATEDIF(B1, EDATE(B1, 13), \"M\")")
+    "##;
+    let program = PipParser::parse_str(script).expect("parse script");
+    interp.eval(program).await.expect("eval script");
+
+    assert!(matches!(interp.get_var("month_end").await, Some(Value::Float(f)) if f == 45351.0));
+    assert!(matches!(
+        interp.get_var("workdays").await,
+        Some(Value::Int(9))
+    ));
+    assert!(matches!(
+        interp.get_var("months").await,
+        Some(Value::Int(13))
+    ));
+}
+
+#[tokio::test]
+async fn test_conditional_aggregation_in_dsl() {
+    let mut interp = Interpreter::new();
+    let sheet = Sheet::from_data(vec![
+        vec![CellValue::from("East"), CellValue::Int(10)],
+        vec![CellValue::from("West"), CellValue::Int(20)],
+        vec![CellValue::from("East"), CellValue::Int(30)],
+    ]);
+    interp
+        .set_var("s", Value::Sheet(Box::new(sheet)))
+        .await
+        .expect("set sheet");
+
+    let script = r##"
+        dim east = sheet_eval_formula(s, "SUMIF(A1:A3, \"east\", B1)")
+        dim big = sheet_eval_formula(s, "COUNTIFS(A1:A3, \"E*\", B1:B3, \">=20\")")
+        dim dot = sheet_eval_formula(s, "SUMPRODUCT(B1:B3, B1:B3)")
+    "##;
+    let program = PipParser::parse_str(script).expect("parse script");
+    interp.eval(program).await.expect("eval script");
+
+    assert!(matches!(interp.get_var("east").await, Some(Value::Float(f)) if f == 40.0));
+    assert!(matches!(interp.get_var("big").await, Some(Value::Int(1))));
+    assert!(matches!(interp.get_var("dot").await, Some(Value::Float(f)) if f == 1400.0));
+}
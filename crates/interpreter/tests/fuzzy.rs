@@ -0,0 +1,113 @@
+//! Fuzzy dedupe and fuzzy join DSL tests.
+
+mod common {
+    include!("common_impl.txt");
+}
+use common::*;
+
+use piptable_core::Value;
+use piptable_sheet::CellValue;
+
+const CUSTOMERS: &str =
+    "name,zip\nAcme Corporation,02139\nACME  Corporation,02139\nGlobex,10001\nAcme Corp,94105\n";
+const VENDORS: &str = "vendor,id\nCorporation Acme,V1\nGlobex Inc,V2\n";
+
+#[tokio::test]
+async fn test_sheet_fuzzy_dedupe_and_join_dsl() {
+    let customers = create_temp_csv(CUSTOMERS);
+    let vendors = create_temp_csv(VENDORS);
+    let script = format!(
+        r#"
+        import "{}" into customers
+        import "{}" into vendors
+        dim unique = sheet_fuzzy_dedupe(customers, "name")
+        dim blocked = sheet_fuzzy_dedupe(customers, ["name"], {{block_on: "zip", threshold: 0.9, cluster_column: "cluster"}})
+        dim joined = sheet_fuzzy_join(unique, vendors, ["name", "vendor"], {{similarity: "token_set", threshold: 0.7, score_column: "match"}})
+        "#,
+        customers.path().display(),
+        vendors.path().display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    let Some(Value::Sheet(unique)) = interp.get_var("unique").await else {
+        panic!("Expected sheet");
+    };
+    let names: Vec<String> = unique
+        .column_by_name("name")
+        .unwrap()
+        .iter()
+        .map(CellValue::as_str)
+        .collect();
+    assert_eq!(names, vec!["name", "Acme Corporation", "Globex"]);
+
+    let Some(Value::Sheet(blocked)) = interp.get_var("blocked").await else {
+        panic!("Expected sheet");
+    };
+    assert_eq!(blocked.column_names().unwrap(), &["name", "zip", "cluster"]);
+    let clusters = blocked.column_by_name("cluster").unwrap();
+    assert_eq!(
+        clusters[1..],
+        [
+            CellValue::Int(1),
+            CellValue::Int(1),
+            CellValue::Int(2),
+            CellValue::Int(3)
+        ]
+    );
+
+    let Some(Value::Sheet(joined)) = interp.get_var("joined").await else {
+        panic!("Expected sheet");
+    };
+    assert_eq!(
+        joined.column_names().unwrap(),
+        &["name", "zip", "vendor", "id", "match"]
+    );
+    assert_eq!(joined.get_by_name(1, "id").unwrap().as_str(), "V1");
+    assert_eq!(
+        joined.get_by_name(1, "match").unwrap(),
+        &CellValue::Float(1.0)
+    );
+    assert_eq!(joined.get_by_name(2, "id").unwrap().as_str(), "V2");
+}
+
+#[tokio::test]
+async fn test_sheet_fuzzy_errors() {
+    let customers = create_temp_csv(CUSTOMERS);
+    let path = customers.path().display();
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into customers
+        dim d = sheet_fuzzy_dedupe(customers, "name", {{similarity: "soundex"}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("Unknown similarity 'soundex'"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into customers
+        dim d = sheet_fuzzy_dedupe(customers, "name", {{score_column: "s"}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("Unknown fuzzy option 'score_column'"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into customers
+        dim d = sheet_fuzzy_join(customers, customers, "name", {{threshold: "high"}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("wrong type"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into customers
+        dim d = sheet_fuzzy_join(customers, customers, "missing")
+        "#
+    ))
+    .await;
+    assert!(err.contains("Failed to join sheets"), "{err}");
+}
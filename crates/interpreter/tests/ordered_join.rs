@@ -0,0 +1,92 @@
+//! As-of and range join DSL tests.
+
+mod common {
+    include!("common_impl.txt");
+}
+use common::*;
+
+use piptable_core::Value;
+use piptable_parser::PipParser;
+
+const TRADES: &str = "id,ccy,date\nt1,EUR,2024-01-05\nt2,GBP,2024-01-02\nt3,EUR,2024-01-03\n";
+const RATES: &str =
+    "ccy,date,rate\nEUR,2024-01-04,1.10\nGBP,2024-01-03,1.27\nEUR,2024-01-02,1.08\n";
+
+/// Values of `field` in each record of a join result, as text.
+fn field(value: &Value, field: &str) -> Vec<String> {
+    let Value::Array(records) = value else {
+        panic!("Expected records, got {value:?}");
+    };
+    records
+        .iter()
+        .map(|record| match record {
+            Value::Object(fields) => match fields.get(field) {
+                None | Some(Value::Null) => String::new(),
+                Some(Value::String(s)) => s.clone(),
+                Some(Value::Float(f)) => f.to_string(),
+                Some(other) => format!("{other:?}"),
+            },
+            other => panic!("Expected record, got {other:?}"),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_asof_and_range_join_dsl() {
+    let trades = create_temp_csv(TRADES);
+    let rates = create_temp_csv(RATES);
+    let script = format!(
+        r#"
+        import "{}" into trades
+        import "{}" into rates
+        dim latest = trades asof join rates on "date" by "ccy"
+        dim next = trades asof forward join rates on "date" = "date" by "ccy"
+        "#,
+        trades.path().display(),
+        rates.path().display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    let latest = interp.get_var("latest").await.unwrap();
+    assert_eq!(field(&latest, "rate"), ["1.1", "", "1.08"]);
+    let next = interp.get_var("next").await.unwrap();
+    assert_eq!(field(&next, "rate"), ["", "1.27", "1.1"]);
+    assert_eq!(field(&next, "date_right"), ["", "2024-01-03", "2024-01-04"]);
+}
+
+#[tokio::test]
+async fn test_range_join_sessions() {
+    let events = create_temp_csv("event,ts\ne1,15\ne2,5\ne3,40\n");
+    let sessions = create_temp_csv("session,start,end\nearly,0,10\nlate,10,20\n");
+    let script = format!(
+        r#"
+        import "{}" into events
+        import "{}" into sessions
+        dim tagged = events range join sessions on "ts" between "start" and "end"
+        "#,
+        events.path().display(),
+        sessions.path().display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    let tagged = interp.get_var("tagged").await.unwrap();
+    assert_eq!(field(&tagged, "event"), ["e1", "e2"]);
+    assert_eq!(field(&tagged, "session"), ["late", "early"]);
+}
+
+#[tokio::test]
+async fn test_ordered_join_errors() {
+    let trades = create_temp_csv(TRADES);
+    let err = run_script_err(&format!(
+        r#"
+        import "{}" into trades
+        dim bad = trades asof join trades on "date" by "region"
+        "#,
+        trades.path().display()
+    ))
+    .await;
+    assert!(err.contains("Key column 'region' not found"), "{err}");
+
+    let err = PipParser::parse_str(r#"dim bad = a range join b on "ts""#).unwrap_err();
+    assert!(err.to_string().contains("Range join needs"), "{err}");
+}
@@ -0,0 +1,72 @@
+//! PostgreSQL import and export tests for the PipTable interpreter.
+//!
+//! These are ignored by default; see `crates/postgres/tests/postgres.rs` for
+//! how to start a local server. The connection string is read from
+//! `PIPTABLE_TEST_POSTGRES_URL` through `${...}` expansion in the script.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+/// Shared test helpers.
+mod common {
+    include!("common_impl.txt");
+}
+use common::*;
+
+use piptable_core::Value;
+use piptable_sheet::CellValue;
+
+#[tokio::test]
+#[ignore = "requires PostgreSQL; set PIPTABLE_TEST_POSTGRES_URL"]
+async fn test_export_and_import_postgres() {
+    let script = r#"
+        dim customers = [
+            {"id": 1, "name": "Alice", "vip": true},
+            {"id": 2, "name": "Bob", "vip": false}
+        ]
+        dim changes = [
+            {"id": 2, "name": "Robert", "vip": true},
+            {"id": 3, "name": "Carol", "vip": false}
+        ]
+        export customers to "${PIPTABLE_TEST_POSTGRES_URL}" with {table: "pip_dsl_customers"}
+        export changes to "${PIPTABLE_TEST_POSTGRES_URL}" with {table: "pip_dsl_customers", mode: "upsert", key: "id"}
+        import "${PIPTABLE_TEST_POSTGRES_URL}" query "SELECT * FROM pip_dsl_customers ORDER BY id" into loaded
+        import "${PIPTABLE_TEST_POSTGRES_URL}" sheet "pip_dsl_customers" into whole
+    "#;
+
+    let (interp, _) = run_script(script).await;
+    let Some(Value::Sheet(loaded)) = interp.get_var("loaded").await else {
+        panic!("Expected Sheet");
+    };
+    let names: Vec<String> = loaded
+        .column_by_name("name")
+        .unwrap()
+        .iter()
+        .skip(1)
+        .map(CellValue::as_str)
+        .collect();
+    assert_eq!(names, vec!["Alice", "Robert", "Carol"]);
+
+    let Some(Value::Sheet(whole)) = interp.get_var("whole").await else {
+        panic!("Expected Sheet");
+    };
+    assert_eq!(whole.row_count(), 4);
+}
+
+#[tokio::test]
+async fn test_postgres_export_requires_table() {
+    let err = run_script_err(
+        r#"
+        dim rows = [{"id": 1}]
+        export rows to "postgres://localhost/db"
+        "#,
+    )
+    .await;
+    assert!(err.contains("requires a table"), "{err}");
+}
+
+#[tokio::test]
+async fn test_postgres_url_missing_env_var() {
+    let err =
+        run_script_err(r#"import "${PIPTABLE_UNSET_POSTGRES_URL}" query "SELECT 1" into q"#).await;
+    assert!(err.contains("PIPTABLE_UNSET_POSTGRES_URL"), "{err}");
+}
@@ -0,0 +1,81 @@
+//! Column profiling DSL tests.
+
+mod common {
+    include!("common_impl.txt");
+}
+use common::*;
+
+use piptable_core::Value;
+use piptable_sheet::CellValue;
+
+const ORDERS: &str = "id,status,amount\n1,open,10\n2,closed,20\n3,open,\n4,open,40\n";
+
+#[tokio::test]
+async fn test_sheet_profile_dsl() {
+    let orders = create_temp_csv(ORDERS);
+    let script = format!(
+        r#"
+        import "{}" into orders
+        dim profile = sheet_profile(orders)
+        dim short = sheet_profile(orders, {{top_k: 1, bins: 2}})
+        "#,
+        orders.path().display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    let Some(Value::Sheet(profile)) = interp.get_var("profile").await else {
+        panic!("Expected sheet");
+    };
+    assert_eq!(profile.row_count(), 4);
+    let columns: Vec<String> = profile
+        .column_by_name("column")
+        .unwrap()
+        .iter()
+        .map(CellValue::as_str)
+        .collect();
+    assert_eq!(columns, ["column", "id", "status", "amount"]);
+    assert_eq!(profile.get_by_name(3, "nulls").unwrap(), &CellValue::Int(1));
+    assert_eq!(
+        profile.get_by_name(2, "top_values").unwrap().as_str(),
+        "open (3), closed (1)"
+    );
+
+    let Some(Value::Sheet(short)) = interp.get_var("short").await else {
+        panic!("Expected sheet");
+    };
+    assert_eq!(
+        short.get_by_name(2, "top_values").unwrap().as_str(),
+        "open (3)"
+    );
+    assert_eq!(
+        short.get_by_name(3, "histogram").unwrap().as_str(),
+        "[10, 26): 2; [26, 40]: 1"
+    );
+}
+
+#[tokio::test]
+async fn test_sheet_profile_errors() {
+    let orders = create_temp_csv(ORDERS);
+    let path = orders.path().display();
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into orders
+        dim p = sheet_profile(orders, {{bins: -1}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("must be a non-negative integer"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into orders
+        dim p = sheet_profile(orders, {{depth: 2}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("Unknown profile option 'depth'"), "{err}");
+
+    let err = run_script_err("dim p = sheet_profile(42)").await;
+    assert!(err.contains("First argument must be a sheet"), "{err}");
+}
@@ -0,0 +1,173 @@
+//! Pivot, unpivot and group-by DSL tests.
+
+mod common {
+    include!("common_impl.txt");
+}
+use common::*;
+
+use piptable_core::Value;
+use piptable_sheet::{CellValue, Sheet};
+
+const SALES: &str = "region,month,amount\nEast,Jan,10\nEast,Feb,5\nWest,Jan,7\nEast,Jan,1\n";
+
+async fn sheet_var(interp: &piptable_interpreter::Interpreter, name: &str) -> Sheet {
+    match interp.get_var(name).await {
+        Some(Value::Sheet(sheet)) => *sheet,
+        other => panic!("Expected sheet for {name}, got {other:?}"),
+    }
+}
+
+fn row(sheet: &Sheet, index: usize) -> Vec<String> {
+    sheet
+        .row(index)
+        .unwrap()
+        .iter()
+        .map(CellValue::as_str)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_sheet_pivot_dsl() {
+    let csv = create_temp_csv(SALES);
+    let script = format!(
+        r#"
+        import "{}" into sales
+        dim totals = sheet_pivot(sales, "region", "month", "amount")
+        dim counts = sheet_pivot(sales, ["region"], "month", "amount", "count", {{grand_totals: true, sort_columns: true}})
+        "#,
+        csv.path().display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    let totals = sheet_var(&interp, "totals").await;
+    assert_eq!(totals.column_names().unwrap(), &["region", "Jan", "Feb"]);
+    assert_eq!(row(&totals, 1), vec!["East", "11", "5"]);
+    assert_eq!(row(&totals, 2), vec!["West", "7", ""]);
+
+    let counts = sheet_var(&interp, "counts").await;
+    assert_eq!(
+        counts.column_names().unwrap(),
+        &["region", "Feb", "Jan", "Total"]
+    );
+    assert_eq!(row(&counts, 3), vec!["Total", "1", "3", "4"]);
+}
+
+#[tokio::test]
+async fn test_sheet_unpivot_dsl() {
+    let csv = create_temp_csv("region,Jan,Feb\nEast,11,5\nWest,7,\n");
+    let script = format!(
+        r#"
+        import "{}" into wide
+        dim long = sheet_unpivot(wide, ["region"])
+        dim named = sheet_unpivot(wide, "region", ["Feb"], "month", "amount")
+        "#,
+        csv.path().display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    let long = sheet_var(&interp, "long").await;
+    assert_eq!(
+        long.column_names().unwrap(),
+        &["region", "variable", "value"]
+    );
+    assert_eq!(long.row_count(), 5);
+    assert_eq!(row(&long, 2), vec!["East", "Feb", "5"]);
+
+    let named = sheet_var(&interp, "named").await;
+    assert_eq!(
+        named.column_names().unwrap(),
+        &["region", "month", "amount"]
+    );
+    assert_eq!(row(&named, 2), vec!["West", "Feb", ""]);
+}
+
+#[tokio::test]
+async fn test_sheet_pivot_dsl_errors() {
+    let csv = create_temp_csv(SALES);
+    let path = csv.path().display();
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into sales
+        dim p = sheet_pivot(sales, "region", "month", "amount", "mode")
+        "#
+    ))
+    .await;
+    assert!(err.contains("Unknown aggregation 'mode'"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into sales
+        dim p = sheet_pivot(sales, "region", "month", "amount", "sum", {{totals: true}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("Unknown pivot option 'totals'"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into sales
+        dim p = sheet_pivot(sales, "region", "month", "region")
+        "#
+    ))
+    .await;
+    assert!(err.contains("non-numeric value 'East'"), "{err}");
+}
+
+#[tokio::test]
+async fn test_sheet_group_by_dsl() {
+    let csv = create_temp_csv("region,rep,amount\nEast,Ann,10\nWest,Bo,7\nEast,Cy,5\nEast,Ann,\n");
+    let script = format!(
+        r#"
+        import "{}" into sales
+        dim totals = sheet_group_by(sales, ["region"], {{total: "sum(amount)", orders: "count(*)", reps: "count_distinct(rep)"}})
+        dim listed = sheet_group_by(sales, "region", ["string_agg(rep, '/') as reps", "median(amount)"])
+        dim overall = sheet_group_by(sales, [], "max(amount)")
+        "#,
+        csv.path().display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    // Object aggregates are ordered by name
+    let totals = sheet_var(&interp, "totals").await;
+    assert_eq!(
+        totals.column_names().unwrap(),
+        &["region", "orders", "reps", "total"]
+    );
+    assert_eq!(row(&totals, 1), vec!["East", "3", "2", "15"]);
+    assert_eq!(row(&totals, 2), vec!["West", "1", "1", "7"]);
+
+    let listed = sheet_var(&interp, "listed").await;
+    assert_eq!(
+        listed.column_names().unwrap(),
+        &["region", "reps", "median_amount"]
+    );
+    assert_eq!(row(&listed, 1), vec!["East", "Ann/Cy/Ann", "7.5"]);
+
+    let overall = sheet_var(&interp, "overall").await;
+    assert_eq!(row(&overall, 1), vec!["10"]);
+}
+
+#[tokio::test]
+async fn test_sheet_group_by_dsl_errors() {
+    let csv = create_temp_csv(SALES);
+    let path = csv.path().display();
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into sales
+        dim g = sheet_group_by(sales, ["region"], {{total: "sum amount"}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("Invalid aggregate 'sum amount'"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into sales
+        dim g = sheet_group_by(sales, ["region"], {{total: 1}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("Aggregate 'total' must be a string"), "{err}");
+}
@@ -0,0 +1,124 @@
+//! Sheet schema DSL tests.
+
+mod common {
+    include!("common_impl.txt");
+}
+use common::*;
+
+use piptable_core::Value;
+use piptable_sheet::{CellValue, ColumnType, Sheet};
+
+const CUSTOMERS: &str = "name,zip,age\nAnn,00123,34\nBo,90210,x\nCy,02139,\n";
+
+async fn sheet_var(interp: &piptable_interpreter::Interpreter, name: &str) -> Sheet {
+    match interp.get_var(name).await {
+        Some(Value::Sheet(sheet)) => *sheet,
+        other => panic!("Expected sheet for {name}, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_sheet_apply_schema_dsl() {
+    let csv = create_temp_csv(CUSTOMERS);
+    let script = format!(
+        r#"
+        import "{}" into customers with {{infer_types: false}}
+        dim typed = sheet_apply_schema(customers, {{zip: "string", age: {{type: "int", nullable: true}}}}, {{on_error: "null"}})
+        dim kept = sheet_apply_schema(customers, {{age: "int"}}, {{on_error: "keep"}})
+        dim report = sheet_schema_report(customers, {{age: "int", name: {{type: "string", nullable: false}}}})
+        "#,
+        csv.path().display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    let typed = sheet_var(&interp, "typed").await;
+    assert_eq!(typed.get(1, 1).unwrap(), &CellValue::String("00123".into()));
+    assert_eq!(typed.get(1, 2).unwrap(), &CellValue::Int(34));
+    assert_eq!(typed.get(2, 2).unwrap(), &CellValue::Null);
+    let schema = typed.schema().expect("schema attached");
+    assert_eq!(schema.column("age").unwrap().column_type(), ColumnType::Int);
+
+    let kept = sheet_var(&interp, "kept").await;
+    assert_eq!(kept.get(2, 2).unwrap(), &CellValue::String("x".into()));
+
+    let report = sheet_var(&interp, "report").await;
+    assert_eq!(
+        report.column_names().unwrap(),
+        &["row", "column", "value", "expected"]
+    );
+    assert_eq!(report.row_count(), 2);
+    assert_eq!(report.get(1, 2).unwrap().as_str(), "x");
+}
+
+#[tokio::test]
+async fn test_sheet_infer_schema_dsl() {
+    let csv = create_temp_csv(CUSTOMERS);
+    let script = format!(
+        r#"
+        import "{}" into customers with {{infer_types: false}}
+        dim strict = sheet_infer_schema(customers)
+        dim loose = sheet_infer_schema(customers, 0.5)
+        dim zip_type = loose.zip.type
+        dim age_type = loose.age.type
+        dim strict_age = strict.age.type
+        dim age_nullable = loose.age.nullable
+        "#,
+        csv.path().display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    let text = |value: Option<Value>| match value {
+        Some(Value::String(s)) => s,
+        other => panic!("Expected string, got {other:?}"),
+    };
+    assert_eq!(text(interp.get_var("zip_type").await), "string");
+    assert_eq!(text(interp.get_var("age_type").await), "int");
+    assert_eq!(text(interp.get_var("strict_age").await), "string");
+    assert!(matches!(
+        interp.get_var("age_nullable").await,
+        Some(Value::Bool(true))
+    ));
+}
+
+#[tokio::test]
+async fn test_sheet_apply_schema_errors() {
+    let csv = create_temp_csv(CUSTOMERS);
+    let path = csv.path().display();
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into customers with {{infer_types: false}}
+        dim s = sheet_apply_schema(customers, {{age: "int"}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("1 cell(s) do not match the schema"), "{err}");
+    assert!(err.contains("'x' is not int"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into customers
+        dim s = sheet_apply_schema(customers, {{age: "decimal"}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("Unknown column type 'decimal'"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into customers
+        dim s = sheet_apply_schema(customers, {{age: {{kind: "int"}}}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("Column 'age' needs a 'type'"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into customers
+        dim s = sheet_apply_schema(customers, {{zip: "string"}}, {{on_error: "skip"}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("Unknown on_error 'skip'"), "{err}");
+}
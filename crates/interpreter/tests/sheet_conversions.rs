@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use arrow::array::{Array, Float64Array, Int64Array, StringArray, UInt64Array};
-use arrow::datatypes::{DataType, Field, Schema};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
 use arrow::record_batch::RecordBatch;
 use piptable_core::Value;
 use piptable_interpreter::sheet_conversions;
@@ -204,3 +204,49 @@ fn test_arrow_value_to_cell_uint64_overflow() {
     let cell = sheet_conversions::arrow_value_to_cell(&array, 0);
     assert_eq!(cell, CellValue::String(u64::MAX.to_string()));
 }
+
+#[test]
+fn test_temporal_columns_round_trip_through_arrow() {
+    let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let rows = [
+        vec![
+            CellValue::Date(date),
+            CellValue::Duration(chrono::TimeDelta::seconds(90)),
+            CellValue::Date(date),
+        ],
+        vec![
+            CellValue::DateTime(date.and_hms_opt(8, 30, 0).unwrap()),
+            CellValue::Null,
+            CellValue::Int(1),
+        ],
+    ];
+    let rows: Vec<&Vec<CellValue>> = rows.iter().collect();
+
+    let types: Vec<DataType> = (0..3)
+        .map(|idx| sheet_conversions::infer_sheet_column_type(&rows, idx))
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            DataType::Duration(TimeUnit::Microsecond),
+            DataType::Utf8,
+        ]
+    );
+
+    let array = sheet_conversions::build_sheet_arrow_array(&rows, 0, &types[0]).unwrap();
+    assert_eq!(
+        sheet_conversions::arrow_value_to_cell(&array, 0),
+        CellValue::DateTime(date.and_hms_opt(0, 0, 0).unwrap())
+    );
+    let array = sheet_conversions::build_sheet_arrow_array(&rows, 1, &types[1]).unwrap();
+    assert_eq!(
+        sheet_conversions::arrow_value_to_cell(&array, 0),
+        CellValue::Duration(chrono::TimeDelta::seconds(90))
+    );
+    let array = sheet_conversions::build_sheet_arrow_array(&rows, 2, &types[2]).unwrap();
+    assert_eq!(
+        sheet_conversions::arrow_value_to_cell(&array, 0),
+        CellValue::String("2024-01-15".to_string())
+    );
+}
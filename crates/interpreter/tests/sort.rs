@@ -0,0 +1,131 @@
+//! Sheet sorting DSL tests.
+
+mod common {
+    include!("common_impl.txt");
+}
+use common::*;
+
+use piptable_core::Value;
+use piptable_sheet::Sheet;
+
+const ITEMS: &str = "item,qty,price\nitem10,2,1.5\nItem9,,3\nitem1,2,4\nÉclair,5,2\neclair,2,2\n";
+
+async fn sheet_var(interp: &piptable_interpreter::Interpreter, name: &str) -> Sheet {
+    match interp.get_var(name).await {
+        Some(Value::Sheet(sheet)) => *sheet,
+        other => panic!("Expected sheet for {name}, got {other:?}"),
+    }
+}
+
+fn column(sheet: &Sheet, col: usize) -> Vec<String> {
+    (1..sheet.row_count())
+        .map(|r| sheet.get(r, col).unwrap().as_str())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_sheet_sort_keys() {
+    let csv = create_temp_csv(ITEMS);
+    let script = format!(
+        r#"
+        import "{}" into items
+        dim by_qty = sheet_sort(items, ["qty desc", "item"])
+        dim nulls_first = sheet_sort(items, {{column: "qty", nulls: "first"}})
+        dim natural = sheet_sort(items, "item", {{natural: true, ignore_case: true, collation: "unicode"}})
+        dim method = items.sort({{column: "price", order: "desc"}})
+        "#,
+        csv.path().display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    let by_qty = sheet_var(&interp, "by_qty").await;
+    assert_eq!(by_qty.get(0, 0).unwrap().as_str(), "item");
+    assert_eq!(
+        column(&by_qty, 0),
+        ["Éclair", "eclair", "item1", "item10", "Item9"]
+    );
+
+    let nulls_first = sheet_var(&interp, "nulls_first").await;
+    assert_eq!(
+        column(&nulls_first, 0),
+        ["Item9", "item10", "item1", "eclair", "Éclair"]
+    );
+
+    let natural = sheet_var(&interp, "natural").await;
+    assert_eq!(
+        column(&natural, 0),
+        ["eclair", "Éclair", "item1", "Item9", "item10"]
+    );
+
+    let method = sheet_var(&interp, "method").await;
+    assert_eq!(column(&method, 2), ["4", "3", "2", "2", "1.5"]);
+}
+
+#[tokio::test]
+async fn test_sheet_sort_comparator_lambda() {
+    let csv = create_temp_csv(ITEMS);
+    let script = format!(
+        r#"
+        import "{}" into items
+        dim by_total = items.sort((a, b) => b.price * 10 - a.price * 10)
+        dim by_len = items.sort((a, b) => len(a.item) - len(b.item))
+        "#,
+        csv.path().display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    let by_total = sheet_var(&interp, "by_total").await;
+    assert_eq!(
+        column(&by_total, 0),
+        ["item1", "Item9", "Éclair", "eclair", "item10"]
+    );
+
+    // Equal lengths keep their original order
+    let by_len = sheet_var(&interp, "by_len").await;
+    assert_eq!(
+        column(&by_len, 0),
+        ["Item9", "item1", "item10", "Éclair", "eclair"]
+    );
+}
+
+#[tokio::test]
+async fn test_sheet_sort_errors() {
+    let csv = create_temp_csv(ITEMS);
+    let path = csv.path().display();
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into items
+        dim s = sheet_sort(items, "missing")
+        "#
+    ))
+    .await;
+    assert!(err.contains("Column not found: missing"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into items
+        dim s = sheet_sort(items, "item", {{nulls: "middle"}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("Unknown null placement 'middle'"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into items
+        dim s = sheet_sort(items, "item", {{column: "qty"}})
+        "#
+    ))
+    .await;
+    assert!(err.contains("Unknown sort option 'column'"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"
+        import "{path}" into items
+        dim s = items.sort((a, b) => a.qty == b.qty)
+        "#
+    ))
+    .await;
+    assert!(err.contains("comparator must return a number"), "{err}");
+}
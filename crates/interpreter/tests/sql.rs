@@ -259,6 +259,53 @@ async fn test_xlsx_query() {
     }
 }
 
+#[tokio::test]
+async fn test_xlsx_date_query() {
+    use chrono::NaiveDate;
+    use piptable_sheet::{CellValue, Sheet};
+    use tempfile::NamedTempFile;
+
+    let file = NamedTempFile::with_suffix(".xlsx")
+        .expect("Failed to create temp file")
+        .into_temp_path();
+    let path = file.to_path_buf();
+
+    let day = |d| CellValue::Date(NaiveDate::from_ymd_opt(2024, 1, d).unwrap());
+    let mut sheet = Sheet::new();
+    *sheet.data_mut() = vec![
+        vec!["name".into(), "due".into()],
+        vec!["foo".into(), day(5)],
+        vec!["bar".into(), day(20)],
+    ];
+    sheet.save_as_xlsx(&path).unwrap();
+
+    let path_str = path.to_string_lossy().replace('\\', "/");
+    let script = format!(
+        r#"
+        import "{}" into events
+        dim cutoff = "2024-01-10"
+        dim late = query(SELECT name, due FROM events WHERE due > cutoff)
+        dim first_due = sheet_get_a1(events, "B2")
+    "#,
+        path_str
+    );
+    let (interp, _) = run_script(&script).await;
+
+    let Some(Value::Table(batches)) = interp.get_var("late").await else {
+        panic!("Expected table");
+    };
+    let late = piptable_interpreter::sheet_conversions::arrow_batches_to_sheet(&batches).unwrap();
+    assert_eq!(late.row_count(), 2);
+    assert_eq!(late.get(1, 0).unwrap().as_str(), "bar");
+    assert_eq!(late.get(1, 1).unwrap(), &day(20));
+
+    // Dates reach scripts as ISO 8601 text
+    assert!(matches!(
+        interp.get_var("first_due").await,
+        Some(Value::String(s)) if s == "2024-01-05"
+    ));
+}
+
 #[tokio::test]
 async fn test_xlsx_query_with_filter() {
     use rust_xlsxwriter::Workbook;
@@ -371,3 +418,339 @@ async fn test_xlsx_join_with_csv() {
         _ => panic!("Expected table"),
     }
 }
+
+/// Sums the row counts of a table value, panicking on any other value.
+fn table_rows(value: Option<Value>) -> usize {
+    match value {
+        Some(Value::Table(batches)) => batches.iter().map(|b| b.num_rows()).sum(),
+        other => panic!("Expected table, got {:?}", other.map(|v| v.type_name())),
+    }
+}
+
+#[tokio::test]
+async fn test_query_book_sheets_join_using() {
+    let script = r#"
+        dim sales = book_from_dict({
+            "Q1": [{id: 1, amount: 10}, {id: 2, amount: 20}, {id: 3, amount: 30}],
+            "Q2": [{id: 1, target: 15}, {id: 3, target: 25}]
+        })
+        dim joined = query(select * from sales.Q1 join sales.Q2 using (id))
+        dim filtered = query(SELECT Q1.amount FROM sales.Q1 WHERE Q1.amount > 15)
+    "#;
+    let (interp, _) = run_script(script).await;
+
+    assert_eq!(table_rows(interp.get_var("joined").await), 2);
+    assert_eq!(table_rows(interp.get_var("filtered").await), 2);
+}
+
+#[tokio::test]
+async fn test_query_book_sheet_with_spaces() {
+    let script = r#"
+        dim sales = book_from_dict({
+            "Q1 Actuals": [{id: 1, amount: 10}, {id: 2, amount: 20}]
+        })
+        dim quoted = query(SELECT a.amount FROM sales."Q1 Actuals" AS a)
+        dim backticked = query(SELECT * FROM sales.`Q1 Actuals`)
+    "#;
+    let (interp, _) = run_script(script).await;
+
+    assert_eq!(table_rows(interp.get_var("quoted").await), 2);
+    assert_eq!(table_rows(interp.get_var("backticked").await), 2);
+}
+
+#[tokio::test]
+async fn test_query_book_reflects_added_and_removed_sheets() {
+    let script = r#"
+        dim sales = book_from_dict({
+            "Q1": [{id: 1}],
+            "Q2": [{id: 2}]
+        })
+        dim before = query(SELECT * FROM sales.Q2)
+        sales = book_remove_sheet(sales, "Q2")
+        sales = book_add_sheet(sales, "Q3", [{id: 3}, {id: 4}])
+        dim after = query(SELECT * FROM sales.Q3)
+    "#;
+    let (interp, _) = run_script(script).await;
+    assert_eq!(table_rows(interp.get_var("before").await), 1);
+    assert_eq!(table_rows(interp.get_var("after").await), 2);
+
+    let err = run_script_err(
+        r#"
+        dim sales = book_from_dict({"Q1": [{id: 1}], "Q2": [{id: 2}]})
+        dim before = query(SELECT * FROM sales.Q2)
+        sales = book_remove_sheet(sales, "Q2")
+        dim after = query(SELECT * FROM sales.Q2)
+    "#,
+    )
+    .await;
+    assert!(err.contains("Sheet 'Q2' not found"), "{err}");
+}
+
+#[tokio::test]
+async fn test_query_binds_dsl_variables() {
+    let script = r#"
+        dim sales = book_get_sheet(book_from_dict({
+            "Sales": [
+                {region: "North", amount: 10},
+                {region: "O'Brien's", amount: 20},
+                {region: "Süd", amount: 30}
+            ]
+        }), "Sales")
+        dim who = "O'Brien's"
+        dim min_amount = 15
+        dim top = 1
+        dim regions = ["North", "Süd"]
+        dim by_name = query(SELECT * FROM sales WHERE region = who)
+        dim biggest = query(SELECT region, amount * 2 AS doubled FROM sales WHERE amount > min_amount ORDER BY amount DESC LIMIT top)
+        dim listed = query(SELECT * FROM sales WHERE region IN regions)
+        dim labelled = query(SELECT who AS label, amount FROM sales)
+    "#;
+    let (interp, _) = run_script(script).await;
+
+    assert_eq!(table_rows(interp.get_var("by_name").await), 1);
+    assert_eq!(table_rows(interp.get_var("biggest").await), 1);
+    assert_eq!(table_rows(interp.get_var("listed").await), 2);
+    match interp.get_var("labelled").await {
+        Some(Value::Table(batches)) => {
+            let labels = batches[0]
+                .column(0)
+                .as_any()
+                .downcast_ref::<arrow::array::StringArray>()
+                .expect("label column should be a string");
+            assert_eq!(labels.value(0), "O'Brien's");
+        }
+        _ => panic!("Expected table"),
+    }
+}
+
+#[tokio::test]
+async fn test_query_columns_shadow_dsl_variables() {
+    let script = r#"
+        dim sales = book_get_sheet(book_from_dict({
+            "Sales": [{region: "North", amount: 10}, {region: "South", amount: 20}]
+        }), "Sales")
+        dim amount = 999
+        dim total = 0
+        dim filtered = query(SELECT * FROM sales WHERE amount > 15)
+        dim summed = query(SELECT region, SUM(amount) AS total FROM sales GROUP BY region HAVING total > 15)
+    "#;
+    let (interp, _) = run_script(script).await;
+
+    assert_eq!(table_rows(interp.get_var("filtered").await), 1);
+    assert_eq!(table_rows(interp.get_var("summed").await), 1);
+}
+
+#[tokio::test]
+async fn test_query_bound_strings_are_not_sql() {
+    let script = r#"
+        dim sales = book_get_sheet(book_from_dict({
+            "Sales": [{region: "North", amount: 10}, {region: "South", amount: 20}]
+        }), "Sales")
+        dim evil = "x' OR '1'='1"
+        dim result = query(SELECT * FROM sales WHERE region = evil)
+    "#;
+    let (interp, _) = run_script(script).await;
+    assert_eq!(table_rows(interp.get_var("result").await), 0);
+}
+
+#[tokio::test]
+async fn test_query_limit_requires_integer() {
+    let err = run_script_err(
+        r#"
+        dim sales = book_get_sheet(book_from_dict({"Sales": [{amount: 10}]}), "Sales")
+        dim top = "1; DROP TABLE sales"
+        dim result = query(SELECT * FROM sales LIMIT top)
+    "#,
+    )
+    .await;
+    assert!(
+        err.contains("LIMIT must be a non-negative integer"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_query_sql_with_parameters() {
+    let script = r#"
+        dim sales = book_get_sheet(book_from_dict({
+            "Sales": [
+                {region: "North", amount: 10},
+                {region: "South", amount: 20},
+                {region: "Süd", amount: 30}
+            ]
+        }), "Sales")
+        dim filtered = query_sql("SELECT * FROM sales WHERE amount > $1 AND region <> $2", [15, "Süd"])
+        dim counted = query_sql("SELECT COUNT(*) AS n FROM filtered")
+        dim injected = query_sql("SELECT * FROM sales WHERE region = $1", ["North' OR '1'='1"])
+    "#;
+    let (interp, _) = run_script(script).await;
+
+    assert_eq!(table_rows(interp.get_var("filtered").await), 1);
+    assert_eq!(table_rows(interp.get_var("counted").await), 1);
+    assert_eq!(table_rows(interp.get_var("injected").await), 0);
+
+    let err = run_script_err(r#"dim result = query_sql("SELECT $1", 1)"#).await;
+    assert!(err.contains("query_sql() takes"), "{err}");
+}
+
+/// Reads one column of a sheet variable by name.
+async fn sheet_column(
+    interp: &piptable_interpreter::Interpreter,
+    name: &str,
+    column: &str,
+) -> Vec<Value> {
+    match interp.get_var(name).await {
+        Some(Value::Sheet(sheet)) => sheet
+            .column_by_name(column)
+            .expect("column should exist")
+            .into_iter()
+            .skip(1)
+            .map(piptable_interpreter::sheet_conversions::cell_to_value)
+            .collect(),
+        other => panic!("Expected sheet, got {:?}", other.map(|v| v.type_name())),
+    }
+}
+
+#[tokio::test]
+async fn test_query_create_table_as() {
+    let script = r#"
+        dim sales = book_get_sheet(book_from_dict({
+            "Sales": [{region: "North", amount: 10}, {region: "South", amount: 20}]
+        }), "Sales")
+        dim created = query(CREATE TABLE big AS SELECT * FROM sales WHERE amount > 15)
+    "#;
+    let (interp, _) = run_script(script).await;
+    assert!(matches!(
+        interp.get_var("created").await,
+        Some(Value::Int(1))
+    ));
+    assert_eq!(table_rows(interp.get_var("big").await), 1);
+}
+
+#[tokio::test]
+async fn test_query_update_writes_back_to_sheet() {
+    let script = r#"
+        dim sales = book_get_sheet(book_from_dict({
+            "Sales": [
+                {region: "North", amount: 10},
+                {region: "South", amount: 20},
+                {region: "North", amount: 30}
+            ]
+        }), "Sales")
+        dim bonus = 5
+        dim updated = query(UPDATE sales SET amount = amount + bonus WHERE region = "North")
+    "#;
+    let (interp, _) = run_script(script).await;
+    assert!(matches!(
+        interp.get_var("updated").await,
+        Some(Value::Int(2))
+    ));
+    let amounts = sheet_column(&interp, "sales", "amount").await;
+    assert!(matches!(
+        amounts.as_slice(),
+        [Value::Int(15), Value::Int(20), Value::Int(35)]
+    ));
+}
+
+#[tokio::test]
+async fn test_query_insert_and_delete_sheet_rows() {
+    let script = r#"
+        dim sales = book_get_sheet(book_from_dict({
+            "Sales": [{region: "North", amount: 10}, {region: "South", amount: 20}]
+        }), "Sales")
+        dim extra = book_get_sheet(book_from_dict({
+            "Extra": [{region: "East", amount: 40}]
+        }), "Extra")
+        dim inserted = query(INSERT INTO sales (region, amount) SELECT region, amount FROM extra)
+        dim added = query(INSERT INTO sales (amount, region) VALUES (50, "West"))
+        dim deleted = query(DELETE FROM sales WHERE amount < 15 OR region = "East")
+        dim remaining = query(SELECT * FROM sales)
+    "#;
+    let (interp, _) = run_script(script).await;
+    assert!(matches!(
+        interp.get_var("inserted").await,
+        Some(Value::Int(1))
+    ));
+    assert!(matches!(interp.get_var("added").await, Some(Value::Int(1))));
+    assert!(matches!(
+        interp.get_var("deleted").await,
+        Some(Value::Int(2))
+    ));
+    assert_eq!(table_rows(interp.get_var("remaining").await), 2);
+
+    let regions = sheet_column(&interp, "sales", "region").await;
+    assert!(
+        matches!(regions.as_slice(), [Value::String(a), Value::String(b)] if a == "South" && b == "West"),
+        "{regions:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_query_dml_on_table_variable() {
+    let script = r#"
+        dim sales = book_get_sheet(book_from_dict({
+            "Sales": [{region: "North", amount: 10}, {region: "South", amount: 20}]
+        }), "Sales")
+        dim summary = query(SELECT region, amount FROM sales)
+        dim updated = query(UPDATE summary SET amount = 0 WHERE region = "South")
+        dim deleted = query(DELETE FROM summary WHERE region = "North")
+        dim zeroed = query(SELECT * FROM summary WHERE amount = 0)
+    "#;
+    let (interp, _) = run_script(script).await;
+    assert!(matches!(
+        interp.get_var("updated").await,
+        Some(Value::Int(1))
+    ));
+    assert!(matches!(
+        interp.get_var("deleted").await,
+        Some(Value::Int(1))
+    ));
+    assert_eq!(table_rows(interp.get_var("summary").await), 1);
+    assert_eq!(table_rows(interp.get_var("zeroed").await), 1);
+}
+
+#[tokio::test]
+async fn test_query_dml_errors() {
+    let err = run_script_err(
+        r#"
+        dim sales = book_get_sheet(book_from_dict({"Sales": [{amount: 10}]}), "Sales")
+        dim result = query(UPDATE sales SET missing = 1)
+    "#,
+    )
+    .await;
+    assert!(err.contains("Column 'missing' not found"), "{err}");
+
+    let err = run_script_err(
+        r#"
+        dim sales = book_get_sheet(book_from_dict({"Sales": [{region: "North", amount: 10}]}), "Sales")
+        dim result = query(INSERT INTO sales VALUES (1))
+    "#,
+    )
+    .await;
+    assert!(err.contains("expects 2 values per row"), "{err}");
+
+    let err = run_script_err(
+        r#"
+        dim total = 3
+        dim result = query(DELETE FROM total)
+    "#,
+    )
+    .await;
+    assert!(err.contains("expected Sheet or Table"), "{err}");
+}
+
+#[tokio::test]
+async fn test_query_join_columns_shadow_dsl_variables() {
+    let script = r#"
+        dim sales = book_get_sheet(book_from_dict({
+            "Sales": [{region_id: 1, amount: 10}, {region_id: 2, amount: 20}]
+        }), "Sales")
+        dim regions = book_get_sheet(book_from_dict({
+            "Regions": [{id: 1, name: "North"}, {id: 2, name: "South"}]
+        }), "Regions")
+        dim region_id = 99
+        dim joined = query(SELECT name, amount FROM sales JOIN regions ON region_id = id)
+    "#;
+    let (interp, _) = run_script(script).await;
+    assert_eq!(table_rows(interp.get_var("joined").await), 2);
+}
@@ -0,0 +1,242 @@
+//! SQLite import, export and attach tests for the PipTable interpreter.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+/// Shared test helpers.
+mod common {
+    include!("common_impl.txt");
+}
+use common::*;
+
+use piptable_core::Value;
+use piptable_interpreter::sheet_conversions::value_to_sheet;
+use piptable_sheet::{CellValue, Sheet};
+use tempfile::tempdir;
+
+/// Values of a named column, skipping the header row.
+fn column(sheet: &Sheet, name: &str) -> Vec<CellValue> {
+    let idx = sheet
+        .column_names()
+        .and_then(|names| names.iter().position(|n| n == name))
+        .unwrap_or_else(|| panic!("column {name} not found"));
+    sheet
+        .data()
+        .iter()
+        .skip(1)
+        .map(|row| row[idx].clone())
+        .collect()
+}
+
+/// Convert a query result variable to a Sheet.
+async fn table_sheet(interp: &piptable_interpreter::Interpreter, name: &str) -> Sheet {
+    let value = interp.get_var(name).await.expect("variable exists");
+    assert!(matches!(value, Value::Table(_)), "Expected Table");
+    value_to_sheet(&value).expect("table converts to sheet")
+}
+
+const CUSTOMERS: &str = r#"
+    dim customers = [
+        {"id": 1, "name": "Alice", "vip": true},
+        {"id": 2, "name": "Bob", "vip": false}
+    ]
+"#;
+
+#[tokio::test]
+async fn test_export_and_import_sqlite_table() {
+    let dir = tempdir().unwrap();
+    let db = dir.path().join("app.db");
+    let script = format!(
+        r#"{CUSTOMERS}
+        export customers to "{db}" with {{table: "customers"}}
+        import "{db}" sheet "customers" into c
+        "#,
+        db = db.display()
+    );
+
+    let (interp, _) = run_script(&script).await;
+    let Some(Value::Sheet(sheet)) = interp.get_var("c").await else {
+        panic!("Expected Sheet");
+    };
+    assert_eq!(sheet.name(), "customers");
+    assert!(matches!(
+        column(&sheet, "id")[..],
+        [CellValue::Int(1), CellValue::Int(2)]
+    ));
+    assert!(matches!(
+        column(&sheet, "vip")[..],
+        [CellValue::Bool(true), CellValue::Bool(false)]
+    ));
+}
+
+#[tokio::test]
+async fn test_import_sqlite_query() {
+    let dir = tempdir().unwrap();
+    let db = dir.path().join("app.db");
+    let script = format!(
+        r#"{CUSTOMERS}
+        export customers to "{db}" with {{table: "customers"}}
+        import "{db}" query "SELECT name FROM customers WHERE vip" into vips
+        "#,
+        db = db.display()
+    );
+
+    let (interp, _) = run_script(&script).await;
+    let Some(Value::Sheet(sheet)) = interp.get_var("vips").await else {
+        panic!("Expected Sheet");
+    };
+    let names: Vec<String> = column(&sheet, "name")
+        .iter()
+        .map(CellValue::as_str)
+        .collect();
+    assert_eq!(names, vec!["Alice"]);
+}
+
+#[tokio::test]
+async fn test_import_sqlite_without_sheet_loads_every_table() {
+    let dir = tempdir().unwrap();
+    let db = dir.path().join("app.sqlite");
+    let script = format!(
+        r#"{CUSTOMERS}
+        export customers to "{db}" with {{table: "customers"}}
+        export customers to "{db}" with {{table: "archive"}}
+        import "{db}" into everything
+        "#,
+        db = db.display()
+    );
+
+    let (interp, _) = run_script(&script).await;
+    let Some(Value::Book(book)) = interp.get_var("everything").await else {
+        panic!("Expected Book");
+    };
+    assert!(book.has_sheet("customers"));
+    assert!(book.has_sheet("archive"));
+}
+
+#[tokio::test]
+async fn test_export_sqlite_modes() {
+    let dir = tempdir().unwrap();
+    let db = dir.path().join("modes.db");
+    let script = format!(
+        r#"{CUSTOMERS}
+        dim changes = [
+            {{"id": 2, "name": "Robert", "vip": true}},
+            {{"id": 3, "name": "Carol", "vip": false}}
+        ]
+        export customers to "{db}"
+        export customers to "{db}" append
+        import "{db}" sheet "modes" into appended
+        export customers to "{db}" with {{table: "modes", mode: "replace"}}
+        export changes to "{db}" with {{table: "modes", mode: "upsert", key: "id"}}
+        import "{db}" sheet "modes" into upserted
+        "#,
+        db = db.display()
+    );
+
+    let (interp, _) = run_script(&script).await;
+    let Some(Value::Sheet(appended)) = interp.get_var("appended").await else {
+        panic!("Expected Sheet");
+    };
+    assert_eq!(column(&appended, "id").len(), 4);
+
+    let Some(Value::Sheet(upserted)) = interp.get_var("upserted").await else {
+        panic!("Expected Sheet");
+    };
+    let names: Vec<String> = column(&upserted, "name")
+        .iter()
+        .map(CellValue::as_str)
+        .collect();
+    assert_eq!(names, vec!["Alice", "Robert", "Carol"]);
+}
+
+#[tokio::test]
+async fn test_attach_sqlite_in_query() {
+    let dir = tempdir().unwrap();
+    let db = dir.path().join("shop.db");
+    let orders = create_temp_csv("customer_id,amount\n2,40\n2,2\n1,5\n");
+    let script = format!(
+        r#"{CUSTOMERS}
+        import "{orders}" into orders
+        export customers to "{db}" with {{table: "Customers"}}
+        attach "{db}" as shop
+        dim vips = query(SELECT name FROM shop.Customers WHERE vip)
+        dim totals = query(
+            SELECT c.name, SUM(o.amount) AS total
+            FROM shop.Customers c
+            JOIN orders o ON c.id = o.customer_id
+            GROUP BY c.name
+            ORDER BY total DESC
+        )
+        "#,
+        db = db.display(),
+        orders = orders.path().display()
+    );
+
+    let (interp, _) = run_script(&script).await;
+    let vips = table_sheet(&interp, "vips").await;
+    assert_eq!(column(&vips, "name")[0].as_str(), "Alice");
+
+    let totals = table_sheet(&interp, "totals").await;
+    assert_eq!(column(&totals, "name")[0].as_str(), "Bob");
+    assert_eq!(column(&totals, "total")[0].as_int(), Some(42));
+}
+
+#[tokio::test]
+async fn test_sqlite_errors() {
+    let dir = tempdir().unwrap();
+    let db = dir.path().join("errors.db");
+    let csv = dir.path().join("out.csv");
+
+    let err = run_script_err(&format!(
+        r#"{CUSTOMERS}
+        export customers to "{}" with {{table: "t"}}
+        "#,
+        csv.display()
+    ))
+    .await;
+    assert!(
+        err.contains("only supported for SQLite and PostgreSQL"),
+        "{err}"
+    );
+
+    let err = run_script_err(&format!(
+        r#"{CUSTOMERS}
+        export customers to "{}" with {{mode: "upsert"}}
+        "#,
+        db.display()
+    ))
+    .await;
+    assert!(err.contains("requires a key column"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"{CUSTOMERS}
+        export customers to "{}" with {{mode: "merge"}}
+        "#,
+        db.display()
+    ))
+    .await;
+    assert!(err.contains("Unknown SQLite write mode"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"{CUSTOMERS}
+        export customers to "{db}" with {{table: "customers"}}
+        import "{db}" sheet "missing" into m
+        "#,
+        db = db.display()
+    ))
+    .await;
+    assert!(err.contains("no such table"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"import "{}" query "SELECT 1" into q"#,
+        csv.display()
+    ))
+    .await;
+    assert!(err.contains("query clause is only supported"), "{err}");
+
+    let err = run_script_err(&format!(
+        r#"attach "{}" as nothing"#,
+        dir.path().join("missing.db").display()
+    ))
+    .await;
+    assert!(err.contains("does not exist"), "{err}");
+}
@@ -0,0 +1,105 @@
+//! Chunked streaming DSL tests.
+
+mod common {
+    include!("common_impl.txt");
+}
+use common::*;
+
+use piptable_core::Value;
+use std::fs;
+use tempfile::tempdir;
+
+fn orders_csv(rows: usize) -> String {
+    let mut csv = String::from("id,status,amount\n");
+    for i in 0..rows {
+        let status = if i % 3 == 0 { "open" } else { "closed" };
+        csv.push_str(&format!("{i},{status},{}\n", i * 5));
+    }
+    csv
+}
+
+#[tokio::test]
+async fn test_for_each_chunk_filters_and_appends() {
+    let input = create_temp_csv(&orders_csv(10));
+    let dir = tempdir().unwrap();
+    let output = dir.path().join("open.csv");
+    let script = format!(
+        r#"
+        dim chunks = 0
+        dim rows = 0
+        for each chunk in stream("{}", 4)
+            chunks = chunks + 1
+            rows = rows + len(chunk)
+            dim picked = query(SELECT * FROM chunk WHERE status = "open")
+            if chunks = 1 then
+                export picked to "{out}"
+            else
+                export picked to "{out}" append
+            end if
+        next
+        "#,
+        input.path().display(),
+        out = output.display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    assert!(matches!(
+        interp.get_var("chunks").await,
+        Some(Value::Int(3))
+    ));
+    assert!(matches!(interp.get_var("rows").await, Some(Value::Int(10))));
+    assert_eq!(
+        fs::read_to_string(&output).unwrap(),
+        "id,status,amount\n0,open,0\n3,open,15\n6,open,30\n9,open,45\n"
+    );
+}
+
+#[tokio::test]
+async fn test_stream_value_and_exit_for() {
+    let input = create_temp_csv(&orders_csv(10));
+    let script = format!(
+        r#"
+        dim s = stream("{}", 3, {{has_headers: false}})
+        dim kind = type(s)
+        dim seen = 0
+        for each chunk in s
+            seen = seen + 1
+            if seen = 2 then
+                exit for
+            end if
+        next
+        dim again = 0
+        for each chunk in s
+            again = again + 1
+        next
+        "#,
+        input.path().display()
+    );
+    let (interp, _) = run_script(&script).await;
+
+    assert!(matches!(interp.get_var("kind").await, Some(Value::String(k)) if k == "Stream"));
+    assert!(matches!(interp.get_var("seen").await, Some(Value::Int(2))));
+    // The header line is data without headers: 11 rows in chunks of 3
+    assert!(matches!(interp.get_var("again").await, Some(Value::Int(4))));
+}
+
+#[tokio::test]
+async fn test_stream_errors() {
+    let err = run_script_err(r#"dim s = stream("book.xlsx", 10)"#).await;
+    assert!(err.contains("Cannot stream 'book.xlsx'"), "{err}");
+
+    let err = run_script_err(r#"dim s = stream("big.csv", 0)"#).await;
+    assert!(
+        err.contains("Chunk size must be a positive integer"),
+        "{err}"
+    );
+
+    let err = run_script_err(
+        r#"
+        for each chunk in stream("/nonexistent/big.csv", 10)
+        next
+        "#,
+    )
+    .await;
+    assert!(err.contains("Failed to open stream"), "{err}");
+}
@@ -2,9 +2,9 @@
 
 use pest::iterators::{Pair, Pairs};
 use piptable_core::{
-    BinaryOp, Expr, FromClause, ImportOptions, JoinCondition, JoinType, Literal, OrderByItem,
-    Param, ParamMode, Program, SelectClause, SelectItem, SortDirection, SqlQuery, Statement,
-    TableRef, UnaryOp,
+    AsOfDirection, BinaryOp, Expr, FromClause, ImportOptions, InsertSource, JoinCondition,
+    JoinType, Literal, OrderByItem, Param, ParamMode, Program, SelectClause, SelectItem,
+    SortDirection, SqlDml, SqlQuery, Statement, TableRef, UnaryOp,
 };
 
 use crate::Rule;
@@ -154,6 +154,7 @@ fn build_statement(pair: Pair<Rule>) -> BuildResult<Statement> {
         Rule::call_stmt => build_call_stmt(inner, line),
         Rule::export_stmt => build_export_stmt(inner, line),
         Rule::import_stmt => build_import_stmt(inner, line),
+        Rule::attach_stmt => build_attach_stmt(inner, line),
         Rule::append_stmt => build_append_stmt(inner, line),
         Rule::upsert_stmt => build_upsert_stmt(inner, line),
         _ => Err(BuildError::from_pair(
@@ -570,6 +571,7 @@ fn build_import_stmt(pair: Pair<Rule>, line: usize) -> BuildResult<Statement> {
 
     // Parse optional sheet clause, target identifier, and options
     let mut sheet_name = None;
+    let mut query = None;
     let mut target = String::new();
     let mut options = ImportOptions::default();
 
@@ -579,6 +581,9 @@ fn build_import_stmt(pair: Pair<Rule>, line: usize) -> BuildResult<Statement> {
                 let sheet_expr = p.into_inner().next().unwrap();
                 sheet_name = Some(build_expr(sheet_expr)?);
             }
+            Rule::query_clause => {
+                query = Some(build_expr(p.into_inner().next().unwrap())?);
+            }
             Rule::ident => {
                 target = p.as_str().to_string();
             }
@@ -598,11 +603,24 @@ fn build_import_stmt(pair: Pair<Rule>, line: usize) -> BuildResult<Statement> {
         sources,
         target,
         sheet_name,
+        query,
         options,
         line,
     })
 }
 
+fn build_attach_stmt(pair: Pair<Rule>, line: usize) -> BuildResult<Statement> {
+    let mut inner = pair.into_inner();
+    let source = build_expr(inner.next().unwrap())?;
+    let alias = inner.next().unwrap().as_str().to_string();
+
+    Ok(Statement::Attach {
+        source,
+        alias,
+        line,
+    })
+}
+
 fn build_append_stmt(pair: Pair<Rule>, line: usize) -> BuildResult<Statement> {
     let mut inner = pair.into_inner();
 
@@ -840,6 +858,17 @@ fn apply_import_option(
                 ))
             }
         }
+        "infer_types" => {
+            if let Expr::Literal(Literal::Bool(b)) = value {
+                options.infer_types = Some(b);
+                Ok(())
+            } else {
+                Err(BuildError::from_pair(
+                    pair,
+                    "infer_types option must be a boolean (true or false)",
+                ))
+            }
+        }
         _ => Err(BuildError::from_pair(
             pair,
             format!("Unknown import option: {key}"),
@@ -883,8 +912,24 @@ pub fn build_expr(pair: Pair<Rule>) -> BuildResult<Expr> {
     }
 }
 
+/// Read a join column name from a string literal.
+fn build_join_column(pair: Pair<Rule>, label: &str) -> BuildResult<String> {
+    let key_pair = pair.clone();
+    match build_literal(pair)? {
+        Literal::String(s) if !s.is_empty() => Ok(s),
+        Literal::String(_) => Err(BuildError::from_pair(
+            &key_pair,
+            format!("{label} cannot be empty"),
+        )),
+        _ => Err(BuildError::from_pair(
+            &key_pair,
+            format!("{label} must be a string"),
+        )),
+    }
+}
+
 fn build_join_expr(pair: Pair<Rule>) -> BuildResult<Expr> {
-    // join_expr = { or_expr ~ (join_op ~ or_expr ~ join_condition?)* }
+    // join_expr = { or_expr ~ (join_op ~ or_expr ~ join_condition)* }
     let mut inner = pair.into_inner();
     let mut left = build_or_expr(inner.next().unwrap())?;
 
@@ -892,13 +937,24 @@ fn build_join_expr(pair: Pair<Rule>) -> BuildResult<Expr> {
         if let Rule::join_op = pair.as_rule() {
             let join_inner_pair = pair.clone();
             let join_inner = pair.into_inner().next().unwrap();
+            // As-of joins keep every left row; range joins only matching pairs
+            let mut asof = None;
             let join_type = match join_inner.as_rule() {
-                Rule::inner_join => JoinType::Inner,
+                Rule::inner_join | Rule::range_join => JoinType::Inner,
                 Rule::left_join => JoinType::Left,
                 Rule::right_join => JoinType::Right,
                 Rule::full_join => JoinType::Full,
+                Rule::asof_join => {
+                    asof = Some(match join_inner.clone().into_inner().next() {
+                        Some(dir) if dir.as_str() == "forward" => AsOfDirection::Forward,
+                        Some(dir) if dir.as_str() == "nearest" => AsOfDirection::Nearest,
+                        _ => AsOfDirection::Backward,
+                    });
+                    JoinType::Left
+                }
                 _ => return Err(BuildError::from_pair(&join_inner_pair, "Unknown join type")),
             };
+            let range = join_inner.as_rule() == Rule::range_join;
 
             // Get the right side expression
             let right = build_or_expr(inner.next().unwrap())?;
@@ -906,69 +962,78 @@ fn build_join_expr(pair: Pair<Rule>) -> BuildResult<Expr> {
             // Check for join condition
             let condition = if let Some(cond_pair) = inner.next() {
                 if cond_pair.as_rule() == Rule::join_condition {
-                    let cond_inner = cond_pair.into_inner().next().unwrap();
-                    if cond_inner.as_rule() == Rule::join_key_pair {
-                        // Handle "col1" = "col2" syntax
-                        let mut key_inner = cond_inner.into_inner();
-                        let left_pair = key_inner.next().unwrap();
-                        let right_pair = key_inner.next().unwrap();
-
-                        // Parse join keys using build_literal for consistency
-                        let left_col = match build_literal(left_pair.clone())? {
-                            Literal::String(s) if !s.is_empty() => s,
-                            Literal::String(_) => {
-                                return Err(BuildError::from_pair(
-                                    &left_pair,
-                                    "Left join key cannot be empty",
-                                ))
-                            }
-                            _ => {
+                    let cond_span = cond_pair.clone();
+                    let mut cond_parts = cond_pair.into_inner();
+                    let cond_inner = cond_parts.next().unwrap();
+                    let by = cond_parts
+                        .next()
+                        .map(|by_pair| {
+                            if asof.is_none() {
                                 return Err(BuildError::from_pair(
-                                    &left_pair,
-                                    "Left join key must be a string",
-                                ))
+                                    &by_pair,
+                                    "'by' columns are only supported in asof joins",
+                                ));
                             }
-                        };
-
-                        let right_col = match build_literal(right_pair.clone())? {
-                            Literal::String(s) if !s.is_empty() => s,
-                            Literal::String(_) => {
+                            by_pair
+                                .into_inner()
+                                .map(|p| build_join_column(p, "Join key"))
+                                .collect::<BuildResult<Vec<_>>>()
+                        })
+                        .transpose()?
+                        .unwrap_or_default();
+
+                    let condition = match cond_inner.as_rule() {
+                        Rule::join_range => {
+                            let mut parts = cond_inner.clone().into_inner();
+                            let column = build_join_column(parts.next().unwrap(), "Join key")?;
+                            let start = build_join_column(parts.next().unwrap(), "Range start")?;
+                            let end = build_join_column(parts.next().unwrap(), "Range end")?;
+                            if !range {
                                 return Err(BuildError::from_pair(
-                                    &right_pair,
-                                    "Right join key cannot be empty",
-                                ))
+                                    &cond_inner,
+                                    "'between' conditions need a range join",
+                                ));
                             }
-                            _ => {
-                                return Err(BuildError::from_pair(
-                                    &right_pair,
-                                    "Right join key must be a string",
-                                ))
+                            JoinCondition::Between { column, start, end }
+                        }
+                        // Handle "col1" = "col2" syntax
+                        Rule::join_key_pair => {
+                            let mut key_inner = cond_inner.into_inner();
+                            let left_col =
+                                build_join_column(key_inner.next().unwrap(), "Left join key")?;
+                            let right_col =
+                                build_join_column(key_inner.next().unwrap(), "Right join key")?;
+                            JoinCondition::OnColumns {
+                                left: left_col,
+                                right: right_col,
                             }
-                        };
-
-                        JoinCondition::OnColumns {
-                            left: left_col,
-                            right: right_col,
                         }
-                    } else {
                         // Handle simple "id" syntax (cond_inner is a string rule)
-                        let key_pair = cond_inner.clone();
-                        let key = match build_literal(cond_inner)? {
-                            Literal::String(s) if !s.is_empty() => s,
-                            Literal::String(_) => {
-                                return Err(BuildError::from_pair(
-                                    &key_pair,
-                                    "Join key cannot be empty",
-                                ))
-                            }
-                            _ => {
-                                return Err(BuildError::from_pair(
-                                    &key_pair,
-                                    "Join key must be a string",
-                                ))
+                        _ => JoinCondition::On(build_join_column(cond_inner, "Join key")?),
+                    };
+
+                    match (condition, asof) {
+                        (JoinCondition::On(key), Some(direction)) => JoinCondition::AsOf {
+                            left: key.clone(),
+                            right: key,
+                            by,
+                            direction,
+                        },
+                        (JoinCondition::OnColumns { left, right }, Some(direction)) => {
+                            JoinCondition::AsOf {
+                                left,
+                                right,
+                                by,
+                                direction,
                             }
-                        };
-                        JoinCondition::On(key)
+                        }
+                        (JoinCondition::On(_) | JoinCondition::OnColumns { .. }, None) if range => {
+                            return Err(BuildError::from_pair(
+                                &cond_span,
+                                "Range join needs 'on \"column\" between \"start\" and \"end\"'",
+                            ))
+                        }
+                        (condition, _) => condition,
                     }
                 } else {
                     // Not a join condition, should not happen with correct grammar
@@ -1516,9 +1581,103 @@ fn build_lvalue(pair: Pair<Rule>) -> BuildResult<piptable_core::LValue> {
 }
 
 fn build_query_expr(pair: Pair<Rule>) -> BuildResult<Expr> {
-    let sql_query_pair = pair.into_inner().next().unwrap();
-    let query = build_sql_query(sql_query_pair)?;
-    Ok(Expr::Query(Box::new(query)))
+    let inner = pair.into_inner().next().unwrap();
+    let dml = match inner.as_rule() {
+        Rule::create_table_as => build_create_table_as(inner)?,
+        Rule::insert_stmt => build_insert_stmt(inner)?,
+        Rule::update_stmt => build_update_stmt(inner)?,
+        Rule::delete_stmt => build_delete_stmt(inner)?,
+        _ => return Ok(Expr::Query(Box::new(build_sql_query(inner)?))),
+    };
+    Ok(Expr::Dml(Box::new(dml)))
+}
+
+fn build_create_table_as(pair: Pair<Rule>) -> BuildResult<SqlDml> {
+    let mut inner = pair.into_inner();
+    let name = inner.next().unwrap().as_str().to_string();
+    let query = build_sql_query(inner.next().unwrap())?;
+    Ok(SqlDml::CreateTableAs {
+        name,
+        query: Box::new(query),
+    })
+}
+
+fn build_insert_stmt(pair: Pair<Rule>) -> BuildResult<SqlDml> {
+    let mut inner = pair.into_inner();
+    let table = inner.next().unwrap().as_str().to_string();
+    let mut columns = None;
+    let mut source = None;
+
+    for item in inner {
+        match item.as_rule() {
+            Rule::insert_columns => {
+                columns = Some(item.into_inner().map(|p| p.as_str().to_string()).collect());
+            }
+            Rule::insert_values => {
+                let mut rows = Vec::new();
+                for row in item.into_inner() {
+                    rows.push(
+                        row.into_inner()
+                            .map(build_expr)
+                            .collect::<BuildResult<_>>()?,
+                    );
+                }
+                source = Some(InsertSource::Values(rows));
+            }
+            Rule::sql_query => {
+                source = Some(InsertSource::Query(Box::new(build_sql_query(item)?)));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(SqlDml::Insert {
+        table,
+        columns,
+        source: source.expect("INSERT must have VALUES or a query"),
+    })
+}
+
+fn build_update_stmt(pair: Pair<Rule>) -> BuildResult<SqlDml> {
+    let mut inner = pair.into_inner();
+    let table = inner.next().unwrap().as_str().to_string();
+    let mut assignments = Vec::new();
+    let mut where_clause = None;
+
+    for item in inner {
+        match item.as_rule() {
+            Rule::set_item => {
+                let mut set_inner = item.into_inner();
+                let column = set_inner.next().unwrap().as_str().to_string();
+                let value = build_expr(set_inner.next().unwrap())?;
+                assignments.push((column, value));
+            }
+            Rule::where_clause => {
+                let expr = build_expr(item.into_inner().next().unwrap())?;
+                where_clause = Some(Box::new(expr));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(SqlDml::Update {
+        table,
+        assignments,
+        where_clause,
+    })
+}
+
+fn build_delete_stmt(pair: Pair<Rule>) -> BuildResult<SqlDml> {
+    let mut inner = pair.into_inner();
+    let table = inner.next().unwrap().as_str().to_string();
+    let where_clause = match inner.next() {
+        Some(item) => Some(Box::new(build_expr(item.into_inner().next().unwrap())?)),
+        None => None,
+    };
+    Ok(SqlDml::Delete {
+        table,
+        where_clause,
+    })
 }
 
 /// Build a SQL query from a pest pair.
@@ -1692,6 +1851,19 @@ fn build_from_clause(pair: Pair<Rule>) -> BuildResult<FromClause> {
     Ok(FromClause { source, alias })
 }
 
+/// Build one segment of a dotted name, stripping quotes from quoted segments.
+fn build_name_part(pair: Pair<Rule>) -> String {
+    let part = match pair.as_rule() {
+        Rule::name_part => pair.into_inner().next().unwrap(),
+        _ => pair,
+    };
+    let s = part.as_str();
+    match part.as_rule() {
+        Rule::quoted_ident | Rule::string => s[1..s.len() - 1].to_string(),
+        _ => s.to_string(),
+    }
+}
+
 fn build_table_ref(pair: Pair<Rule>) -> BuildResult<TableRef> {
     let inner = pair.into_inner().next().unwrap();
 
@@ -1710,7 +1882,7 @@ fn build_table_ref(pair: Pair<Rule>) -> BuildResult<TableRef> {
             Ok(TableRef::Function { name, args })
         }
         Rule::qualified_name => {
-            let parts: Vec<_> = inner.into_inner().map(|p| p.as_str().to_string()).collect();
+            let parts: Vec<_> = inner.into_inner().map(build_name_part).collect();
             if parts.len() == 1 {
                 Ok(TableRef::Table(parts.into_iter().next().unwrap()))
             } else if parts.len() == 2 {
@@ -1772,6 +1944,7 @@ fn build_join_clause(pair: Pair<Rule>) -> BuildResult<piptable_core::JoinClause>
     let mut table = TableRef::Table(String::new());
     let mut alias = None;
     let mut on_clause = None;
+    let mut using_columns = None;
 
     for inner in pair.into_inner() {
         match inner.as_rule() {
@@ -1792,12 +1965,15 @@ fn build_join_clause(pair: Pair<Rule>) -> BuildResult<piptable_core::JoinClause>
             Rule::table_ref => {
                 table = build_table_ref(inner)?;
             }
-            Rule::ident => {
+            Rule::ident | Rule::alias_ident => {
                 alias = Some(inner.as_str().to_string());
             }
             Rule::expr => {
                 on_clause = Some(Box::new(build_expr(inner)?));
             }
+            Rule::using_clause => {
+                using_columns = Some(inner.into_inner().map(|p| p.as_str().to_string()).collect());
+            }
             _ => {}
         }
     }
@@ -1807,6 +1983,7 @@ fn build_join_clause(pair: Pair<Rule>) -> BuildResult<piptable_core::JoinClause>
         table,
         alias,
         on_clause,
+        using_columns,
     })
 }
 
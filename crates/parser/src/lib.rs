@@ -93,8 +93,8 @@ impl LineColExt for pest::error::LineColLocation {
 mod tests {
     use super::*;
     use piptable_core::{
-        BinaryOp, Expr, JoinCondition, JoinType, Literal, ParamMode, SortDirection, Statement,
-        TableRef,
+        AsOfDirection, BinaryOp, Expr, InsertSource, JoinCondition, JoinType, Literal, ParamMode,
+        SortDirection, SqlDml, Statement, TableRef,
     };
 
     // ========================================================================
@@ -234,6 +234,47 @@ mod tests {
         assert_eq!(from.alias, Some("u".to_string()));
     }
 
+    #[test]
+    fn parse_from_qualified_name() {
+        let sql = "SELECT * FROM sales.Q1";
+        let query = PipParser::parse_sql(sql).unwrap();
+        let from = query.from.unwrap();
+        assert!(matches!(
+            from.source,
+            TableRef::Qualified { database, schema: None, table }
+            if database == "sales" && table == "Q1"
+        ));
+    }
+
+    #[test]
+    fn parse_from_qualified_quoted_name() {
+        for sql in [
+            r#"SELECT * FROM sales."Q1 Data""#,
+            "SELECT * FROM sales.`Q1 Data`",
+        ] {
+            let query = PipParser::parse_sql(sql).unwrap();
+            let from = query.from.unwrap();
+            assert!(matches!(
+                from.source,
+                TableRef::Qualified { database, schema: None, table }
+                if database == "sales" && table == "Q1 Data"
+            ));
+        }
+    }
+
+    #[test]
+    fn parse_join_using() {
+        let sql = "SELECT * FROM sales.Q1 JOIN sales.Q2 USING (id, region)";
+        let query = PipParser::parse_sql(sql).unwrap();
+        let join = &query.joins[0];
+        assert!(join.alias.is_none());
+        assert!(join.on_clause.is_none());
+        assert_eq!(
+            join.using_columns,
+            Some(vec!["id".to_string(), "region".to_string()])
+        );
+    }
+
     // ========================================================================
     // WHERE tests (Issue #15)
     // ========================================================================
@@ -358,6 +399,88 @@ mod tests {
         assert!(matches!(*offset, Expr::Literal(Literal::Int(20))));
     }
 
+    // ========================================================================
+    // DML/DDL tests
+    // ========================================================================
+
+    /// Parse `dim x = <expr>` and return the data-modifying statement.
+    fn parse_dml(expr: &str) -> SqlDml {
+        let program = PipParser::parse_str(&format!("dim result = {expr}")).unwrap();
+        match &program.statements[0] {
+            Statement::Dim {
+                value: Expr::Dml(dml),
+                ..
+            } => (**dml).clone(),
+            other => panic!("Expected DML expression, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_create_table_as() {
+        let dml = parse_dml("query(create table big as select * from sales where amount > 10)");
+        match dml {
+            SqlDml::CreateTableAs { name, query } => {
+                assert_eq!(name, "big");
+                assert!(query.where_clause.is_some());
+            }
+            other => panic!("Expected CREATE TABLE AS, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_insert_select_and_values() {
+        match parse_dml("query(INSERT INTO sales SELECT * FROM extra)") {
+            SqlDml::Insert {
+                table,
+                columns: None,
+                source: InsertSource::Query(_),
+            } => assert_eq!(table, "sales"),
+            other => panic!("Expected INSERT ... SELECT, got {other:?}"),
+        }
+
+        match parse_dml(
+            r#"query(INSERT INTO sales (region, amount) VALUES ("North", 1), ("South", 2))"#,
+        ) {
+            SqlDml::Insert {
+                columns: Some(columns),
+                source: InsertSource::Values(rows),
+                ..
+            } => {
+                assert_eq!(columns, vec!["region".to_string(), "amount".to_string()]);
+                assert_eq!(rows.len(), 2);
+                assert_eq!(rows[1].len(), 2);
+            }
+            other => panic!("Expected INSERT ... VALUES, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_update_and_delete() {
+        match parse_dml(
+            r#"query(UPDATE sales SET amount = amount * 2, flagged = true WHERE region = "North")"#,
+        ) {
+            SqlDml::Update {
+                table,
+                assignments,
+                where_clause,
+            } => {
+                assert_eq!(table, "sales");
+                assert_eq!(assignments.len(), 2);
+                assert_eq!(assignments[1].0, "flagged");
+                assert!(where_clause.is_some());
+            }
+            other => panic!("Expected UPDATE, got {other:?}"),
+        }
+
+        match parse_dml("query(DELETE FROM sales)") {
+            SqlDml::Delete {
+                table,
+                where_clause: None,
+            } => assert_eq!(table, "sales"),
+            other => panic!("Expected DELETE, got {other:?}"),
+        }
+    }
+
     // ========================================================================
     // Error tests (Issue #15)
     // ========================================================================
@@ -573,6 +696,38 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn parse_import_with_query() {
+        let code = r#"import "postgres://localhost/app" query "SELECT * FROM orders" into orders"#;
+        let result = PipParser::parse_str(code);
+        assert!(result.is_ok(), "Parse error: {:?}", result.err());
+        let program = result.unwrap();
+        assert!(matches!(
+            &program.statements[0],
+            Statement::Import { target, sheet_name, query, .. }
+            if target == "orders" && sheet_name.is_none() && query.is_some()
+        ));
+    }
+
+    #[test]
+    fn parse_attach() {
+        let code = r#"attach "app.db" as app"#;
+        let result = PipParser::parse_str(code);
+        assert!(result.is_ok(), "Parse error: {:?}", result.err());
+        let program = result.unwrap();
+        assert!(matches!(
+            &program.statements[0],
+            Statement::Attach { alias, .. } if alias == "app"
+        ));
+
+        // `attach` is not reserved and still works as a variable name
+        let program = PipParser::parse_str("attach = 1").unwrap();
+        assert!(matches!(
+            &program.statements[0],
+            Statement::Assignment { .. }
+        ));
+    }
+
     // ========================================================================
     // Join expression tests
     // ========================================================================
@@ -857,6 +1012,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_asof_join() {
+        let code = r#"dim priced = trades asof join rates on "date" by "ccy", "venue""#;
+        let program = PipParser::parse_str(code).unwrap();
+        let Statement::Dim {
+            value:
+                Expr::Join {
+                    join_type,
+                    condition,
+                    ..
+                },
+            ..
+        } = &program.statements[0]
+        else {
+            panic!("Expected Join expression");
+        };
+        assert!(matches!(join_type, JoinType::Left));
+        assert!(matches!(
+            condition,
+            JoinCondition::AsOf { left, right, by, direction: AsOfDirection::Backward }
+            if left == "date" && right == "date" && by == &["ccy", "venue"]
+        ));
+
+        let code = r#"result = trades asof nearest join rates on "traded" = "quoted""#;
+        let program = PipParser::parse_str(code).unwrap();
+        let Statement::Assignment {
+            value: Expr::Join { condition, .. },
+            ..
+        } = &program.statements[0]
+        else {
+            panic!("Expected Join expression");
+        };
+        assert!(matches!(
+            condition,
+            JoinCondition::AsOf { left, right, by, direction: AsOfDirection::Nearest }
+            if left == "traded" && right == "quoted" && by.is_empty()
+        ));
+    }
+
+    #[test]
+    fn parse_range_join() {
+        let code = r#"result = events range join sessions on "ts" between "start" and "end""#;
+        let program = PipParser::parse_str(code).unwrap();
+        let Statement::Assignment {
+            value:
+                Expr::Join {
+                    join_type,
+                    condition,
+                    ..
+                },
+            ..
+        } = &program.statements[0]
+        else {
+            panic!("Expected Join expression");
+        };
+        assert!(matches!(join_type, JoinType::Inner));
+        assert!(matches!(
+            condition,
+            JoinCondition::Between { column, start, end }
+            if column == "ts" && start == "start" && end == "end"
+        ));
+    }
+
+    #[test]
+    fn parse_join_condition_errors() {
+        for (code, message) in [
+            (
+                r#"result = events range join sessions on "ts""#,
+                "Range join needs",
+            ),
+            (
+                r#"result = events join sessions on "ts" between "a" and "b""#,
+                "need a range join",
+            ),
+            (
+                r#"result = trades left join rates on "date" by "ccy""#,
+                "only supported in asof joins",
+            ),
+        ] {
+            let err = PipParser::parse_str(code).unwrap_err();
+            assert!(err.to_string().contains(message), "{code}: {err}");
+        }
+    }
+
     // ========================================================================
     // Complex query tests
     // ========================================================================
@@ -468,6 +468,8 @@ pub enum ErrorValue {
     Null,  // #NULL!
     Num,   // #NUM!
     NA,    // #N/A
+    Spill, // #SPILL!
+    Calc,  // #CALC!
 }
 
 impl ErrorValue {
@@ -481,6 +483,8 @@ impl ErrorValue {
             Self::Null => "#NULL!",
             Self::Num => "#NUM!",
             Self::NA => "#N/A",
+            Self::Spill => "#SPILL!",
+            Self::Calc => "#CALC!",
         }
     }
 }
@@ -709,6 +713,7 @@ mod tests {
         assert_eq!(ErrorValue::Null.label(), "#NULL!");
         assert_eq!(ErrorValue::Num.label(), "#NUM!");
         assert_eq!(ErrorValue::NA.label(), "#N/A");
+        assert_eq!(ErrorValue::Spill.label(), "#SPILL!");
     }
 
     #[test]
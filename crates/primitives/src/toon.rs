@@ -291,6 +291,8 @@ fn error_message(e: &ErrorValue) -> String {
         ErrorValue::Null => "#NULL!".to_string(),
         ErrorValue::Num => "#NUM!".to_string(),
         ErrorValue::NA => "#N/A".to_string(),
+        ErrorValue::Spill => "#SPILL!".to_string(),
+        ErrorValue::Calc => "#CALC!".to_string(),
     }
 }
 
@@ -303,6 +305,8 @@ fn parse_error_code(code: &str) -> ErrorValue {
         "Null" => ErrorValue::Null,
         "Num" => ErrorValue::Num,
         "NA" => ErrorValue::NA,
+        "Spill" => ErrorValue::Spill,
+        "Calc" => ErrorValue::Calc,
         _ => ErrorValue::Value,
     }
 }
@@ -113,6 +113,31 @@ class Sheet:
         """Delete a column by name."""
         ...
 
+    def pivot(
+        self,
+        index_cols: list[str],
+        column_col: str,
+        value_col: str,
+        agg: str = "sum",
+        grand_totals: bool = False,
+        sort_columns: bool = False,
+    ) -> Sheet:
+        """Spread the values of column_col into columns, aggregating value_col.
+
+        agg is an aggregation name such as "sum", "count", "avg", "median" or "first".
+        """
+        ...
+
+    def unpivot(
+        self,
+        id_cols: list[str],
+        value_cols: Optional[list[str]] = None,
+        var_name: str = "variable",
+        value_name: str = "value",
+    ) -> Sheet:
+        """Turn columns into rows (melt). Defaults to every non-id column."""
+        ...
+
     def to_list(self) -> list[list[Any]]:
         """Convert to a 2D list."""
         ...
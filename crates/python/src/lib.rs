@@ -27,8 +27,10 @@
 //! sheet.save_as_xlsx("output.xlsx")
 //! ```
 
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
 use piptable_sheet::{
-    Book as RustBook, CellValue as RustCellValue, CsvOptions as RustCsvOptions, Sheet as RustSheet,
+    Aggregation as RustAggregation, Book as RustBook, CellValue as RustCellValue,
+    CsvOptions as RustCsvOptions, PivotOptions as RustPivotOptions, Sheet as RustSheet,
     XlsxReadOptions as RustXlsxReadOptions,
 };
 use pyo3::exceptions::{PyImportError, PyIndexError, PyRuntimeError, PyValueError};
@@ -36,8 +38,11 @@ use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 
 /// Convert a Rust CellValue to a Python object
-fn cell_value_to_py(py: Python<'_>, value: &RustCellValue) -> PyObject {
-    match value {
+///
+/// Dates, datetimes and durations outside Python's supported range raise
+/// instead of panicking.
+fn cell_value_to_py(py: Python<'_>, value: &RustCellValue) -> PyResult<PyObject> {
+    Ok(match value {
         RustCellValue::Null => py.None(),
         RustCellValue::Bool(b) => b.into_pyobject(py).unwrap().to_owned().into_any().unbind(),
         RustCellValue::Int(i) => i.into_pyobject(py).unwrap().to_owned().into_any().unbind(),
@@ -51,7 +56,19 @@ fn cell_value_to_py(py: Python<'_>, value: &RustCellValue) -> PyObject {
             .to_owned()
             .into_any()
             .unbind(),
-    }
+        RustCellValue::Date(d) => d.into_pyobject(py)?.into_any().unbind(),
+        RustCellValue::DateTime(dt) => dt.into_pyobject(py)?.into_any().unbind(),
+        RustCellValue::Duration(d) => d.into_pyobject(py)?.into_any().unbind(),
+    })
+}
+
+/// Convert a slice of CellValues to a Python list
+fn cells_to_py<'py>(py: Python<'py>, cells: &[RustCellValue]) -> PyResult<Bound<'py, PyList>> {
+    let values = cells
+        .iter()
+        .map(|v| cell_value_to_py(py, v))
+        .collect::<PyResult<Vec<_>>>()?;
+    PyList::new(py, values)
 }
 
 /// Convert a Python object to a Rust CellValue
@@ -71,6 +88,16 @@ fn py_to_cell_value(obj: &Bound<'_, PyAny>) -> PyResult<RustCellValue> {
     if let Ok(s) = obj.extract::<String>() {
         return Ok(RustCellValue::String(s));
     }
+    // datetime.datetime is a datetime.date, so check it first
+    if let Ok(dt) = obj.extract::<NaiveDateTime>() {
+        return Ok(RustCellValue::DateTime(dt));
+    }
+    if let Ok(d) = obj.extract::<NaiveDate>() {
+        return Ok(RustCellValue::Date(d));
+    }
+    if let Ok(d) = obj.extract::<TimeDelta>() {
+        return Ok(RustCellValue::Duration(d));
+    }
     Err(PyValueError::new_err(format!(
         "Cannot convert {} to CellValue",
         obj.get_type().name()?
@@ -269,7 +296,7 @@ impl Sheet {
             .inner
             .get(row, col)
             .map_err(|e| PyIndexError::new_err(e.to_string()))?;
-        Ok(cell_value_to_py(py, value))
+        cell_value_to_py(py, value)
     }
 
     /// Set a cell value by row and column index (0-based)
@@ -286,7 +313,7 @@ impl Sheet {
             .inner
             .get_by_name(row, col_name)
             .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
-        Ok(cell_value_to_py(py, value))
+        cell_value_to_py(py, value)
     }
 
     /// Get an entire row by index (0-based)
@@ -295,7 +322,7 @@ impl Sheet {
             .inner
             .row(index)
             .map_err(|e| PyIndexError::new_err(e.to_string()))?;
-        let list = PyList::new(py, row.iter().map(|v| cell_value_to_py(py, v)))?;
+        let list = cells_to_py(py, row)?;
         Ok(list.into())
     }
 
@@ -305,7 +332,7 @@ impl Sheet {
             .inner
             .column(index)
             .map_err(|e| PyIndexError::new_err(e.to_string()))?;
-        let list = PyList::new(py, col.iter().map(|v| cell_value_to_py(py, v)))?;
+        let list = cells_to_py(py, &col)?;
         Ok(list.into())
     }
 
@@ -315,7 +342,7 @@ impl Sheet {
             .inner
             .column_by_name(name)
             .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
-        let list = PyList::new(py, col.iter().map(|v| cell_value_to_py(py, v)))?;
+        let list = cells_to_py(py, &col)?;
         Ok(list.into())
     }
 
@@ -379,12 +406,73 @@ impl Sheet {
         Ok(())
     }
 
+    /// Spread the values of one column into columns, aggregating another
+    ///
+    /// Args:
+    ///     index_cols: Columns that identify each output row
+    ///     column_col: Column whose distinct values become columns
+    ///     value_col: Column whose values fill the cells
+    ///     agg: Aggregation name, e.g. "sum", "count", "avg", "median" or "first"
+    ///     grand_totals: Add a Total column and row
+    ///     sort_columns: Order pivot columns by value instead of first appearance
+    ///
+    /// Example:
+    ///     >>> report = sales.pivot(index_cols, "month", "amount", agg="sum")
+    #[pyo3(signature = (index_cols, column_col, value_col, agg="sum", grand_totals=false, sort_columns=false))]
+    fn pivot(
+        &self,
+        index_cols: Vec<String>,
+        column_col: &str,
+        value_col: &str,
+        agg: &str,
+        grand_totals: bool,
+        sort_columns: bool,
+    ) -> PyResult<Sheet> {
+        let agg: RustAggregation = agg
+            .parse()
+            .map_err(|e: piptable_sheet::SheetError| PyValueError::new_err(e.to_string()))?;
+        let options = RustPivotOptions::new()
+            .with_grand_totals(grand_totals)
+            .with_sort_columns(sort_columns);
+        let index_refs: Vec<&str> = index_cols.iter().map(String::as_str).collect();
+        let sheet = self
+            .inner
+            .pivot_with_options(&index_refs, column_col, value_col, agg, &options)
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        Ok(Sheet { inner: sheet })
+    }
+
+    /// Turn columns into rows (melt)
+    ///
+    /// Args:
+    ///     id_cols: Columns repeated on every output row
+    ///     value_cols: Columns to unpivot (default: every non-id column)
+    ///     var_name: Name of the column holding the source column names
+    ///     value_name: Name of the column holding the values
+    #[pyo3(signature = (id_cols, value_cols=None, var_name="variable", value_name="value"))]
+    fn unpivot(
+        &self,
+        id_cols: Vec<String>,
+        value_cols: Option<Vec<String>>,
+        var_name: &str,
+        value_name: &str,
+    ) -> PyResult<Sheet> {
+        let id_refs: Vec<&str> = id_cols.iter().map(String::as_str).collect();
+        let value_cols = value_cols.unwrap_or_default();
+        let value_refs: Vec<&str> = value_cols.iter().map(String::as_str).collect();
+        let sheet = self
+            .inner
+            .unpivot(&id_refs, &value_refs, var_name, value_name)
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        Ok(Sheet { inner: sheet })
+    }
+
     /// Convert to a 2D list
     fn to_list(&self, py: Python<'_>) -> PyResult<PyObject> {
         let data = self.inner.to_array();
         let mut rows: Vec<PyObject> = Vec::with_capacity(data.len());
         for row in &data {
-            let inner = PyList::new(py, row.iter().map(|v| cell_value_to_py(py, v)))?;
+            let inner = cells_to_py(py, row)?;
             rows.push(inner.into_any().unbind());
         }
         Ok(PyList::new(py, rows)?.into())
@@ -399,7 +487,7 @@ impl Sheet {
 
         let py_dict = PyDict::new(py);
         for (name, values) in dict {
-            let list = PyList::new(py, values.iter().map(|v| cell_value_to_py(py, v)))?;
+            let list = cells_to_py(py, &values)?;
             py_dict.set_item(name, list)?;
         }
         Ok(py_dict.into())
@@ -427,7 +515,7 @@ impl Sheet {
         for record in records {
             let py_dict = PyDict::new(py);
             for (name, value) in record {
-                py_dict.set_item(name, cell_value_to_py(py, &value))?;
+                py_dict.set_item(name, cell_value_to_py(py, &value)?)?;
             }
             py_list.append(py_dict)?;
         }
@@ -534,7 +622,7 @@ impl Sheet {
                     .inner
                     .column(i)
                     .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
-                let list = PyList::new(py, col.iter().map(|v| cell_value_to_py(py, v)))?;
+                let list = cells_to_py(py, &col)?;
                 py_dict.set_item(name, list)?;
             }
             pd.call_method1("DataFrame", (py_dict,))
@@ -544,7 +632,7 @@ impl Sheet {
             let data = self.inner.to_array();
             let mut rows: Vec<PyObject> = Vec::with_capacity(data.len());
             for row in &data {
-                let inner = PyList::new(py, row.iter().map(|v| cell_value_to_py(py, v)))?;
+                let inner = cells_to_py(py, row)?;
                 rows.push(inner.into_any().unbind());
             }
             let py_list = PyList::new(py, rows)?;
@@ -993,3 +1081,30 @@ fn piptable(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Book>()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_out_of_range_date_raises() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let date = NaiveDate::from_ymd_opt(10_000, 1, 1).unwrap();
+            assert!(cell_value_to_py(py, &RustCellValue::Date(date)).is_err());
+            let datetime = date.and_hms_opt(0, 0, 0).unwrap();
+            assert!(cell_value_to_py(py, &RustCellValue::DateTime(datetime)).is_err());
+
+            let sheet = Sheet {
+                inner: RustSheet::from_data(vec![vec![RustCellValue::Date(date)]]),
+            };
+            assert!(sheet.get(py, 0, 0).is_err());
+            assert!(sheet.row(py, 0).is_err());
+            assert!(sheet.to_list(py).is_err());
+
+            let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+            let value = cell_value_to_py(py, &RustCellValue::Date(date)).unwrap();
+            assert_eq!(value.bind(py).extract::<NaiveDate>().unwrap(), date);
+        });
+    }
+}
@@ -0,0 +1,647 @@
+//! Aggregations and native group-by for sheets.
+
+use crate::cell::CellValue;
+use crate::error::{Result, SheetError};
+use crate::sheet::Sheet;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// Separator used by `string_agg` when none is given.
+const DEFAULT_SEPARATOR: &str = ", ";
+
+/// How a set of values is combined into one.
+///
+/// Nulls are skipped by every aggregation, so an all-null input gives `Null`
+/// (or 0 for `Count` and `CountDistinct`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Aggregation {
+    /// Sum of numeric values. Stays an integer when every value is one.
+    #[default]
+    Sum,
+    /// Number of non-null values.
+    Count,
+    /// Mean of numeric values.
+    Avg,
+    /// Smallest value; numbers compare numerically, anything else as text.
+    Min,
+    /// Largest value; numbers compare numerically, anything else as text.
+    Max,
+    /// Middle numeric value, or the mean of the two middle values.
+    Median,
+    /// Number of distinct non-null values.
+    CountDistinct,
+    /// Values joined as text.
+    StringAgg,
+    /// First non-null value in row order.
+    First,
+    /// Last non-null value in row order.
+    Last,
+}
+
+impl FromStr for Aggregation {
+    type Err = SheetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "sum" => Ok(Self::Sum),
+            "count" => Ok(Self::Count),
+            "avg" | "average" | "mean" => Ok(Self::Avg),
+            "min" => Ok(Self::Min),
+            "max" => Ok(Self::Max),
+            "median" => Ok(Self::Median),
+            "count_distinct" | "distinct_count" | "nunique" => Ok(Self::CountDistinct),
+            "string_agg" => Ok(Self::StringAgg),
+            "first" => Ok(Self::First),
+            "last" => Ok(Self::Last),
+            _ => Err(SheetError::Aggregation(format!(
+                "Unknown aggregation '{s}', expected sum, count, avg, min, max, median, \
+                 count_distinct, string_agg, first or last"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Sum => "sum",
+            Self::Count => "count",
+            Self::Avg => "avg",
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Median => "median",
+            Self::CountDistinct => "count_distinct",
+            Self::StringAgg => "string_agg",
+            Self::First => "first",
+            Self::Last => "last",
+        };
+        f.write_str(name)
+    }
+}
+
+impl Aggregation {
+    /// Combine `values`; `column` is only used in error messages.
+    pub(crate) fn apply(self, values: &[&CellValue], column: &str) -> Result<CellValue> {
+        self.apply_with_separator(values, column, DEFAULT_SEPARATOR)
+    }
+
+    fn apply_with_separator(
+        self,
+        values: &[&CellValue],
+        column: &str,
+        separator: &str,
+    ) -> Result<CellValue> {
+        let values: Vec<&CellValue> = values
+            .iter()
+            .map(|v| v.cached_or_self())
+            .filter(|v| !v.is_null())
+            .collect();
+
+        match self {
+            Self::Count => Ok(CellValue::Int(values.len() as i64)),
+            Self::CountDistinct => {
+                let distinct: HashSet<String> = values.iter().map(|v| Sheet::cell_key(v)).collect();
+                Ok(CellValue::Int(distinct.len() as i64))
+            }
+            _ if values.is_empty() => Ok(CellValue::Null),
+            Self::First => Ok(values[0].clone()),
+            Self::Last => Ok(values[values.len() - 1].clone()),
+            Self::Sum => sum(&values, column),
+            Self::Avg => {
+                let total: f64 = numbers(&values, "average", column)?.iter().sum();
+                Ok(CellValue::Float(total / values.len() as f64))
+            }
+            Self::Median => median(&values, column),
+            Self::Min => Ok(extreme(&values, Ordering::Less)),
+            Self::Max => Ok(extreme(&values, Ordering::Greater)),
+            Self::StringAgg => Ok(CellValue::String(
+                values
+                    .iter()
+                    .map(|v| v.as_str())
+                    .collect::<Vec<_>>()
+                    .join(separator),
+            )),
+        }
+    }
+}
+
+fn numbers(values: &[&CellValue], verb: &str, column: &str) -> Result<Vec<f64>> {
+    values
+        .iter()
+        .map(|v| {
+            v.as_float().ok_or_else(|| {
+                SheetError::Aggregation(format!(
+                    "Cannot {verb} non-numeric value '{}' in column '{column}'",
+                    v.as_str()
+                ))
+            })
+        })
+        .collect()
+}
+
+fn sum(values: &[&CellValue], column: &str) -> Result<CellValue> {
+    if values.iter().all(|v| matches!(v, CellValue::Int(_))) {
+        let total = values.iter().try_fold(0i64, |acc, v| match v {
+            CellValue::Int(i) => acc.checked_add(*i),
+            _ => None,
+        });
+        if let Some(total) = total {
+            return Ok(CellValue::Int(total));
+        }
+    }
+    Ok(CellValue::Float(
+        numbers(values, "sum", column)?.iter().sum(),
+    ))
+}
+
+fn median(values: &[&CellValue], column: &str) -> Result<CellValue> {
+    let numbers = numbers(values, "take the median of", column)?;
+    let mut sorted: Vec<(f64, &CellValue)> =
+        numbers.into_iter().zip(values.iter().copied()).collect();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 1 {
+        return Ok(sorted[mid].1.clone());
+    }
+    Ok(CellValue::Float(f64::midpoint(
+        sorted[mid - 1].0,
+        sorted[mid].0,
+    )))
+}
+
+fn extreme<'a>(values: &[&'a CellValue], wanted: Ordering) -> CellValue {
+    let mut best: &'a CellValue = values[0];
+    for value in &values[1..] {
+        if compare_cells(value, best) == wanted {
+            best = value;
+        }
+    }
+    best.clone()
+}
+
+/// Compare two cells: numbers numerically and before anything else, which
+/// compares as text.
+pub(crate) fn compare_cells(a: &CellValue, b: &CellValue) -> Ordering {
+    let numeric = |c: &CellValue| match c.cached_or_self() {
+        CellValue::Int(i) => Some(*i as f64),
+        CellValue::Float(f) => Some(*f),
+        _ => None,
+    };
+    match (numeric(a), numeric(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.as_str().cmp(&b.as_str()),
+    }
+}
+
+/// Key identifying a combination of group values.
+pub(crate) fn group_key(values: &[CellValue]) -> String {
+    let mut key = String::new();
+    for value in values {
+        key.push_str(&Sheet::cell_key(value));
+        key.push('\x1f');
+    }
+    key
+}
+
+/// One output column of [`GroupBy::agg`].
+///
+/// Parses from strings such as `"sum(amount)"`, `"count(*)"`,
+/// `"string_agg(name, '; ')"` or `"max(price) as top"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Agg {
+    func: Aggregation,
+    /// Source column; `None` counts rows with `count(*)`.
+    column: Option<String>,
+    alias: Option<String>,
+    separator: String,
+}
+
+impl Agg {
+    /// Aggregate `column` with `func`.
+    pub fn new(func: Aggregation, column: &str) -> Self {
+        Self {
+            func,
+            column: Some(column.to_string()),
+            alias: None,
+            separator: DEFAULT_SEPARATOR.to_string(),
+        }
+    }
+
+    /// Sum of a column.
+    pub fn sum(column: &str) -> Self {
+        Self::new(Aggregation::Sum, column)
+    }
+
+    /// Number of non-null values in a column.
+    pub fn count(column: &str) -> Self {
+        Self::new(Aggregation::Count, column)
+    }
+
+    /// Number of rows in each group, nulls included (`count(*)`).
+    pub fn count_rows() -> Self {
+        Self {
+            column: None,
+            ..Self::new(Aggregation::Count, "")
+        }
+    }
+
+    /// Mean of a column.
+    pub fn mean(column: &str) -> Self {
+        Self::new(Aggregation::Avg, column)
+    }
+
+    /// Smallest value of a column.
+    pub fn min(column: &str) -> Self {
+        Self::new(Aggregation::Min, column)
+    }
+
+    /// Largest value of a column.
+    pub fn max(column: &str) -> Self {
+        Self::new(Aggregation::Max, column)
+    }
+
+    /// Median of a column.
+    pub fn median(column: &str) -> Self {
+        Self::new(Aggregation::Median, column)
+    }
+
+    /// Number of distinct non-null values in a column.
+    pub fn count_distinct(column: &str) -> Self {
+        Self::new(Aggregation::CountDistinct, column)
+    }
+
+    /// Values of a column joined with `separator`.
+    pub fn string_agg(column: &str, separator: &str) -> Self {
+        Self {
+            separator: separator.to_string(),
+            ..Self::new(Aggregation::StringAgg, column)
+        }
+    }
+
+    /// First non-null value of a column.
+    pub fn first(column: &str) -> Self {
+        Self::new(Aggregation::First, column)
+    }
+
+    /// Last non-null value of a column.
+    pub fn last(column: &str) -> Self {
+        Self::new(Aggregation::Last, column)
+    }
+
+    /// Name the output column.
+    #[must_use]
+    pub fn alias(mut self, name: &str) -> Self {
+        self.alias = Some(name.to_string());
+        self
+    }
+
+    /// Output column name: the alias, or `func_column` (`count` for `count(*)`).
+    pub fn output_name(&self) -> String {
+        match (&self.alias, &self.column) {
+            (Some(alias), _) => alias.clone(),
+            (None, Some(column)) => format!("{}_{}", self.func, column),
+            (None, None) => self.func.to_string(),
+        }
+    }
+}
+
+impl FromStr for Agg {
+    type Err = SheetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            SheetError::Aggregation(format!(
+                "Invalid aggregate '{s}', expected a form like \"sum(amount)\""
+            ))
+        };
+
+        // An alias follows the closing parenthesis: "sum(amount) as total"
+        let text = s.trim();
+        let close = text.rfind(')').ok_or_else(invalid)?;
+        let rest = text[close + 1..].trim();
+        let alias = if rest.is_empty() {
+            None
+        } else if rest.len() > 3 && rest[..3].eq_ignore_ascii_case("as ") {
+            Some(rest[3..].trim())
+        } else {
+            return Err(invalid());
+        };
+        let open = text.find('(').ok_or_else(invalid)?;
+        let expr = &text[..close];
+        let inner = &expr[open + 1..];
+        let func: Aggregation = expr[..open].trim().parse()?;
+
+        let (column, separator) = match inner.split_once(',') {
+            Some((column, separator)) if func == Aggregation::StringAgg => {
+                let separator = separator.trim();
+                let unquoted = separator
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| {
+                        separator
+                            .strip_prefix('"')
+                            .and_then(|s| s.strip_suffix('"'))
+                    })
+                    .ok_or_else(invalid)?;
+                (column.trim(), unquoted)
+            }
+            Some(_) => return Err(invalid()),
+            None => (inner.trim(), DEFAULT_SEPARATOR),
+        };
+
+        let mut agg = match column {
+            "*" if func == Aggregation::Count => Agg::count_rows(),
+            "" | "*" => return Err(invalid()),
+            column => Agg::new(func, column),
+        };
+        agg.separator = separator.to_string();
+        if let Some(alias) = alias {
+            agg = agg.alias(alias);
+        }
+        Ok(agg)
+    }
+}
+
+/// Rows of a sheet grouped by key columns, created by [`Sheet::group_by`].
+#[derive(Debug, Clone)]
+pub struct GroupBy<'a> {
+    sheet: &'a Sheet,
+    columns: Vec<String>,
+}
+
+impl GroupBy<'_> {
+    /// Compute one row per group: the key columns followed by one column per
+    /// aggregate. Groups keep the order in which they first appear; a null
+    /// key forms its own group. Without key columns the whole sheet is one
+    /// group.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a column is missing, two outputs share a name, or a
+    /// numeric aggregation meets a non-numeric value.
+    pub fn agg<I: IntoIterator<Item = Agg>>(self, aggs: I) -> Result<Sheet> {
+        let sheet = self.sheet;
+        let aggs: Vec<Agg> = aggs.into_iter().collect();
+        let key_idx = self
+            .columns
+            .iter()
+            .map(|name| sheet.column_index_by_name(name))
+            .collect::<Result<Vec<_>>>()?;
+        let agg_idx = aggs
+            .iter()
+            .map(|agg| {
+                agg.column
+                    .as_deref()
+                    .map(|c| sheet.column_index_by_name(c))
+                    .transpose()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut groups: Vec<(Vec<CellValue>, Vec<usize>)> = Vec::new();
+        let mut lookup: HashMap<String, usize> = HashMap::new();
+        let body = &sheet.data()[sheet.data_start_row()..];
+        for (row_idx, row) in body.iter().enumerate() {
+            let key_values: Vec<CellValue> = key_idx
+                .iter()
+                .map(|&i| row.get(i).cloned().unwrap_or(CellValue::Null))
+                .collect();
+            match lookup.get(&group_key(&key_values)) {
+                Some(&group) => groups[group].1.push(row_idx),
+                None => {
+                    lookup.insert(group_key(&key_values), groups.len());
+                    groups.push((key_values, vec![row_idx]));
+                }
+            }
+        }
+        if key_idx.is_empty() && groups.is_empty() {
+            groups.push((Vec::new(), Vec::new()));
+        }
+
+        let mut header: Vec<CellValue> = self.columns.iter().map(|c| c.as_str().into()).collect();
+        header.extend(aggs.iter().map(|agg| agg.output_name().into()));
+
+        let mut data = Vec::with_capacity(groups.len() + 1);
+        data.push(header);
+        for (key_values, rows) in groups {
+            let mut out = key_values;
+            for (agg, idx) in aggs.iter().zip(&agg_idx) {
+                let value = match idx {
+                    None => CellValue::Int(rows.len() as i64),
+                    Some(col) => {
+                        let values: Vec<&CellValue> = rows
+                            .iter()
+                            .map(|&r| body[r].get(*col).unwrap_or(&CellValue::Null))
+                            .collect();
+                        let column = agg.column.as_deref().unwrap_or_default();
+                        agg.func
+                            .apply_with_separator(&values, column, &agg.separator)?
+                    }
+                };
+                out.push(value);
+            }
+            data.push(out);
+        }
+
+        let mut result = Sheet::from_data(data);
+        result.set_name(&format!("{}_grouped", sheet.name()));
+        result.name_columns_by_row(0)?;
+        Ok(result)
+    }
+}
+
+impl Sheet {
+    /// Group rows by the values of `columns` for aggregation with
+    /// [`GroupBy::agg`]. Runs in memory without converting to Arrow.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{Agg, CellValue, Sheet};
+    ///
+    /// let mut sales = Sheet::from_data(vec![
+    ///     vec![CellValue::from("region"), "amount".into()],
+    ///     vec!["East".into(), CellValue::Int(10)],
+    ///     vec!["West".into(), CellValue::Int(7)],
+    ///     vec!["East".into(), CellValue::Int(5)],
+    /// ]);
+    /// sales.name_columns_by_row(0).unwrap();
+    ///
+    /// let totals = sales
+    ///     .group_by(&["region"])
+    ///     .agg([Agg::sum("amount").alias("total"), Agg::count_rows()])
+    ///     .unwrap();
+    /// assert_eq!(totals.column_names().unwrap(), &["region", "total", "count"]);
+    /// assert_eq!(totals.get(1, 1).unwrap().as_int(), Some(15));
+    /// ```
+    pub fn group_by(&self, columns: &[&str]) -> GroupBy<'_> {
+        GroupBy {
+            sheet: self,
+            columns: columns.iter().map(|c| (*c).to_string()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orders() -> Sheet {
+        let mut sheet = Sheet::from_data(vec![
+            vec![CellValue::from("region"), "rep".into(), "amount".into()],
+            vec!["East".into(), "Ann".into(), CellValue::Int(10)],
+            vec!["West".into(), "Bo".into(), CellValue::Float(7.5)],
+            vec!["East".into(), "Cy".into(), CellValue::Null],
+            vec!["East".into(), "Ann".into(), CellValue::Int(4)],
+            vec![CellValue::Null, "Di".into(), CellValue::Int(1)],
+            vec!["East".into(), CellValue::Null, CellValue::Int(1)],
+        ]);
+        sheet.name_columns_by_row(0).unwrap();
+        sheet
+    }
+
+    fn row(sheet: &Sheet, index: usize) -> Vec<String> {
+        sheet
+            .row(index)
+            .unwrap()
+            .iter()
+            .map(CellValue::as_str)
+            .collect()
+    }
+
+    #[test]
+    fn test_group_by_all_aggregations() {
+        let grouped = orders()
+            .group_by(&["region"])
+            .agg([
+                Agg::count_rows(),
+                Agg::count("amount"),
+                Agg::sum("amount"),
+                Agg::mean("amount"),
+                Agg::min("amount"),
+                Agg::max("amount"),
+                Agg::median("amount"),
+                Agg::count_distinct("rep"),
+                Agg::string_agg("rep", "/"),
+                Agg::first("rep"),
+                Agg::last("rep").alias("last"),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            grouped.column_names().unwrap(),
+            &[
+                "region",
+                "count",
+                "count_amount",
+                "sum_amount",
+                "avg_amount",
+                "min_amount",
+                "max_amount",
+                "median_amount",
+                "count_distinct_rep",
+                "string_agg_rep",
+                "first_rep",
+                "last",
+            ]
+        );
+        assert_eq!(
+            row(&grouped, 1),
+            vec![
+                "East",
+                "4",
+                "3",
+                "15",
+                "5",
+                "1",
+                "10",
+                "4",
+                "2",
+                "Ann/Cy/Ann",
+                "Ann",
+                "Ann"
+            ]
+        );
+        assert_eq!(
+            row(&grouped, 2),
+            vec!["West", "1", "1", "7.5", "7.5", "7.5", "7.5", "7.5", "1", "Bo", "Bo", "Bo"]
+        );
+        // Null keys form their own group
+        assert!(grouped.get(3, 0).unwrap().is_null());
+        assert!(matches!(grouped.get(1, 3).unwrap(), CellValue::Int(15)));
+    }
+
+    #[test]
+    fn test_group_by_nulls_and_empty_groups() {
+        let mut sheet = Sheet::from_data(vec![
+            vec![CellValue::from("k"), "v".into()],
+            vec!["a".into(), CellValue::Null],
+        ]);
+        sheet.name_columns_by_row(0).unwrap();
+        let grouped = sheet
+            .group_by(&["k"])
+            .agg([Agg::sum("v"), Agg::count("v"), Agg::median("v")])
+            .unwrap();
+        assert_eq!(row(&grouped, 1), vec!["a", "", "0", ""]);
+
+        let mut empty = sheet.get_range("A1:B1").unwrap();
+        empty.name_columns_by_row(0).unwrap();
+        let total = empty.group_by(&[]).agg([Agg::count_rows()]).unwrap();
+        assert_eq!(row(&total, 1), vec!["0"]);
+    }
+
+    #[test]
+    fn test_median_even_count() {
+        let values = [
+            CellValue::Int(4),
+            CellValue::Int(1),
+            CellValue::Int(3),
+            CellValue::Int(2),
+        ];
+        let refs: Vec<&CellValue> = values.iter().collect();
+        let median = Aggregation::Median.apply(&refs, "v").unwrap();
+        assert!(matches!(median, CellValue::Float(f) if (f - 2.5).abs() < f64::EPSILON));
+    }
+
+    #[test]
+    fn test_parse_agg() {
+        assert_eq!("sum(amount)".parse::<Agg>().unwrap(), Agg::sum("amount"));
+        assert_eq!("COUNT(*)".parse::<Agg>().unwrap(), Agg::count_rows());
+        assert_eq!(
+            "string_agg(name, '; ') as names".parse::<Agg>().unwrap(),
+            Agg::string_agg("name", "; ").alias("names")
+        );
+        assert_eq!(
+            "mean(total price)".parse::<Agg>().unwrap().output_name(),
+            "avg_total price"
+        );
+
+        for bad in ["sum", "sum(*)", "sum(a, b)", "string_agg(a, ;)", "total(a)"] {
+            assert!(bad.parse::<Agg>().is_err(), "{bad} should not parse");
+        }
+    }
+
+    #[test]
+    fn test_group_by_errors() {
+        let err = orders()
+            .group_by(&["region"])
+            .agg([Agg::sum("rep")])
+            .unwrap_err();
+        assert!(err.to_string().contains("non-numeric value 'Ann'"), "{err}");
+
+        let err = orders()
+            .group_by(&["nope"])
+            .agg([Agg::count_rows()])
+            .unwrap_err();
+        assert!(matches!(err, SheetError::ColumnNotFound { .. }));
+
+        let err = orders()
+            .group_by(&["region"])
+            .agg([Agg::sum("amount").alias("region")])
+            .unwrap_err();
+        assert!(matches!(err, SheetError::DuplicateColumnName { .. }));
+    }
+}
@@ -1,5 +1,7 @@
+use crate::book_formulas::BookFormulas;
 use crate::cell::CellValue;
 use crate::error::{Result, SheetError};
+use crate::names::DefinedNames;
 use crate::sheet::Sheet;
 use indexmap::{IndexMap, IndexSet};
 use std::collections::HashMap;
@@ -11,6 +13,9 @@ pub struct Book {
     name: String,
     sheets: IndexMap<String, Sheet>,
     active_sheet: Option<String>,
+    pub(crate) formulas: BookFormulas,
+    /// Workbook-scoped defined names
+    pub(crate) names: DefinedNames,
 }
 
 impl Book {
@@ -27,6 +32,8 @@ impl Book {
             name: name.to_string(),
             sheets: IndexMap::new(),
             active_sheet: None,
+            formulas: BookFormulas::default(),
+            names: DefinedNames::new(),
         }
     }
 
@@ -148,6 +155,7 @@ impl Book {
         let mut sheet = sheet;
         sheet.set_name(name);
         self.sheets.insert(name.to_string(), sheet);
+        self.formulas.sheet_added(name);
 
         // Set as active if first sheet
         if self.active_sheet.is_none() {
@@ -172,6 +180,8 @@ impl Book {
                 name: name.to_string(),
             })?;
 
+        self.formulas.sheet_removed(name);
+
         // Update active sheet if removed
         if self.active_sheet.as_deref() == Some(name) {
             self.active_sheet = self.sheets.keys().next().cloned();
@@ -204,6 +214,13 @@ impl Book {
             if self.active_sheet.as_deref() == Some(old_name) {
                 self.active_sheet = Some(new_name.to_string());
             }
+
+            // Formulas that name the sheet follow it
+            self.formulas.sheet_renamed(old_name, new_name);
+            self.names.rename_sheet_references(old_name, new_name);
+            for sheet in self.sheets.values_mut() {
+                sheet.rename_sheet_references(old_name, new_name)?;
+            }
         }
 
         Ok(())
@@ -282,6 +299,7 @@ impl Book {
 
             let mut sheet = sheet;
             sheet.set_name(&final_name);
+            self.formulas.sheet_added(&final_name);
             self.sheets.insert(final_name, sheet);
         }
     }
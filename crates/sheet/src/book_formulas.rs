@@ -0,0 +1,421 @@
+//! Book-level formulas: references across sheets (`Sheet2!A1`) resolved and
+//! recalculated in dependency order over every sheet of a book.
+
+use crate::book::Book;
+use crate::cell::CellValue;
+use crate::error::{Result, SheetError};
+use crate::sheet::{Sheet, SheetValueResolver};
+use crate::spills::MAX_SPILL_PASSES;
+use crate::tables::find_table;
+use piptable_formulas::{FormulaEngine, SheetIdResolver, TableLocation, ValueResolver};
+use piptable_primitives::{CellAddress, CellRange, ErrorValue, Value};
+use std::collections::HashMap;
+
+/// Sheet ids and the cross-sheet dependency graph of a [`Book`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BookFormulas {
+    /// Stable id per sheet name; a renamed sheet keeps its id
+    ids: HashMap<String, u32>,
+    next_id: u32,
+    engine: FormulaEngine,
+    /// Set when sheets are added, renamed or removed: the graph is rebuilt
+    /// and every formula recalculated on the next recalculation
+    stale: bool,
+}
+
+impl BookFormulas {
+    pub(crate) fn sheet_added(&mut self, name: &str) {
+        self.ids.insert(name.to_string(), self.next_id);
+        self.next_id += 1;
+        self.stale = true;
+    }
+
+    pub(crate) fn sheet_renamed(&mut self, old: &str, new: &str) {
+        if let Some(id) = self.ids.remove(old) {
+            self.ids.insert(new.to_string(), id);
+        }
+        self.stale = true;
+    }
+
+    pub(crate) fn sheet_removed(&mut self, name: &str) {
+        self.ids.remove(name);
+        self.stale = true;
+    }
+
+    /// Rebuild the graph and recalculate every formula on the next
+    /// recalculation, e.g. after names change.
+    pub(crate) fn invalidate(&mut self) {
+        self.stale = true;
+    }
+}
+
+/// Looks up sheet ids by name, exactly or else without regard to case, and
+/// the names and tables visible from each sheet.
+struct SheetIds<'a>(&'a Book);
+
+impl SheetIdResolver for SheetIds<'_> {
+    fn sheet_id(&self, sheet_name: &str) -> Option<u32> {
+        let ids = &self.0.formulas.ids;
+        ids.get(sheet_name).copied().or_else(|| {
+            let lower = sheet_name.to_lowercase();
+            ids.iter()
+                .find(|(name, _)| name.to_lowercase() == lower)
+                .map(|(_, id)| *id)
+        })
+    }
+
+    fn defined_name(&self, sheet_id: u32, name: &str) -> Option<String> {
+        let book = self.0;
+        book.formulas
+            .ids
+            .iter()
+            .find(|(_, id)| **id == sheet_id)
+            .and_then(|(sheet, _)| book.get_sheet(sheet).ok())
+            .and_then(|sheet| sheet.names.refers_to(name))
+            .or_else(|| book.names.refers_to(name))
+    }
+
+    fn table(
+        &self,
+        sheet_id: u32,
+        name: Option<&str>,
+        cell: Option<CellAddress>,
+    ) -> Option<TableLocation> {
+        let book = self.0;
+        if name.is_some() {
+            return find_table(&book.table_locations(), name, cell);
+        }
+        let (sheet_name, _) = book.formulas.ids.iter().find(|(_, id)| **id == sheet_id)?;
+        let sheet = book.get_sheet(sheet_name).ok()?;
+        find_table(&sheet.table_locations(Some(sheet_name)), None, cell)
+    }
+}
+
+/// Resolves plain references on the formula's own sheet and qualified ones
+/// on the named sheet of the book.
+struct BookValueResolver<'a> {
+    book: &'a Book,
+    local: SheetValueResolver<'a>,
+}
+
+impl ValueResolver for BookValueResolver<'_> {
+    fn get_cell(&self, addr: &CellAddress) -> Value {
+        self.local.get_cell(addr)
+    }
+
+    fn get_range(&self, range: &CellRange) -> Vec<Value> {
+        self.local.get_range(range)
+    }
+
+    fn current_cell(&self) -> Option<CellAddress> {
+        self.local.current_cell()
+    }
+
+    fn get_sheet_cell(&self, sheet: &str, addr: &CellAddress) -> Value {
+        match self.book.formula_sheet(sheet) {
+            Some(sheet) => SheetValueResolver::new(sheet, None).get_cell(addr),
+            None => Value::Error(ErrorValue::Ref),
+        }
+    }
+
+    fn get_sheet_range(&self, sheet: &str, range: &CellRange) -> Vec<Value> {
+        self.book
+            .formula_sheet(sheet)
+            .map(|sheet| SheetValueResolver::new(sheet, None).get_range(range))
+            .unwrap_or_default()
+    }
+
+    fn has_sheet(&self, sheet: &str) -> bool {
+        self.book.formula_sheet(sheet).is_some()
+    }
+
+    fn defined_name(&self, sheet: Option<&str>, name: &str) -> Option<String> {
+        let scoped = match sheet {
+            Some(sheet) => self
+                .book
+                .formula_sheet(sheet)
+                .and_then(|sheet| sheet.names.refers_to(name)),
+            None => self.local.defined_name(None, name),
+        };
+        scoped.or_else(|| self.book.names.refers_to(name))
+    }
+
+    fn table(&self, name: Option<&str>) -> Option<TableLocation> {
+        match name {
+            Some(_) => find_table(&self.book.table_locations(), name, None),
+            None => self.local.table(None),
+        }
+    }
+
+    fn spill_range(&self, sheet: Option<&str>, anchor: &CellAddress) -> Option<CellRange> {
+        match sheet {
+            Some(sheet) => self.book.formula_sheet(sheet)?.spill_region(*anchor),
+            None => self.local.spill_range(None, anchor),
+        }
+    }
+}
+
+impl Book {
+    /// The id formulas use for sheet `name`. Ids are assigned when sheets
+    /// are added and kept when they are renamed.
+    #[must_use]
+    pub fn sheet_id(&self, name: &str) -> Option<u32> {
+        self.formulas.ids.get(name).copied()
+    }
+
+    /// Set a formula on sheet `sheet`, e.g. `"=SUM(Data!A1:A3) * Rates!B1"`.
+    /// Sheet names in references match without regard to case. The cell is
+    /// marked dirty; call [`Book::recalculate`] to compute it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{Book, CellValue, Sheet};
+    ///
+    /// let mut book = Book::new();
+    /// book.add_sheet("Data", Sheet::from_data(vec![vec![10], vec![20]])).unwrap();
+    /// book.add_sheet("Summary", Sheet::from_data(vec![vec![CellValue::Null]])).unwrap();
+    ///
+    /// book.set_formula("Summary", "A1", "=SUM(Data!A1:A2)").unwrap();
+    /// book.recalculate().unwrap();
+    /// let total = book.get_sheet("Summary").unwrap().get(0, 0).unwrap();
+    /// assert_eq!(total.cached_or_self(), &CellValue::Float(30.0));
+    ///
+    /// book.set_a1("Data", "A2", 5).unwrap();
+    /// book.recalculate().unwrap();
+    /// let total = book.get_sheet("Summary").unwrap().get(0, 0).unwrap();
+    /// assert_eq!(total.cached_or_self(), &CellValue::Float(15.0));
+    /// ```
+    pub fn set_formula(&mut self, sheet: &str, notation: &str, formula: &str) -> Result<()> {
+        let target = self.get_sheet(sheet)?;
+        let addr = target.get_a1_addr(notation)?;
+        target.get(addr.row as usize, addr.col as usize)?;
+        let affected = target.spill_affected(addr);
+        if !self.formulas.stale {
+            // Compile first so a cycle through another sheet leaves the cell as it was
+            let id = self.sheet_ids_for(sheet)?;
+            let mut engine = std::mem::take(&mut self.formulas.engine);
+            let compiled = engine.set_formula_with_sheet(id, addr, formula, Some(&SheetIds(self)));
+            engine.mark_dirty_with_sheet(id, &addr);
+            for cell in &affected {
+                engine.mark_dirty_with_sheet(id, cell);
+            }
+            self.formulas.engine = engine;
+            compiled?;
+        }
+        self.get_sheet_mut(sheet)?.set_formula(notation, formula)
+    }
+
+    /// Set a cell value on sheet `sheet`, replacing any formula there, and
+    /// mark the formulas that depend on it, on any sheet, dirty.
+    pub fn set_a1<T: Into<CellValue>>(
+        &mut self,
+        sheet: &str,
+        notation: &str,
+        value: T,
+    ) -> Result<()> {
+        let target = self.get_sheet_mut(sheet)?;
+        let addr = target.get_a1_addr(notation)?;
+        let affected = target.spill_affected(addr);
+        target.set_a1(notation, value)?;
+        if self.formulas.stale {
+            return Ok(());
+        }
+        let id = self.sheet_ids_for(sheet)?;
+        let engine = &mut self.formulas.engine;
+        engine.remove_formula_with_sheet(id, &addr);
+        let _ = engine.set_spill_with_sheet(id, addr, &[]);
+        engine.mark_dirty_with_sheet(id, &addr);
+        for cell in &affected {
+            engine.mark_dirty_with_sheet(id, cell);
+        }
+        Ok(())
+    }
+
+    /// Recalculate dirty formulas on every sheet in dependency order,
+    /// caching each result in its formula cell. After sheets are added,
+    /// renamed or removed, every formula is recalculated. References to a
+    /// sheet that does not exist evaluate to `#REF!`.
+    pub fn recalculate(&mut self) -> Result<()> {
+        if self.formulas.stale {
+            self.rebuild_formulas()?;
+        }
+        let names: HashMap<u32, String> = self
+            .formulas
+            .ids
+            .iter()
+            .map(|(name, id)| (*id, name.clone()))
+            .collect();
+        let mut engine = std::mem::take(&mut self.formulas.engine);
+        let result = self.recalculate_with(&mut engine, &names);
+        self.formulas.engine = engine;
+        result
+    }
+
+    fn recalculate_with(
+        &mut self,
+        engine: &mut FormulaEngine,
+        names: &HashMap<u32, String>,
+    ) -> Result<()> {
+        for (id, name) in names {
+            for cell in self.get_sheet_mut(name)?.release_orphaned_spills() {
+                engine.mark_dirty_with_sheet(*id, &cell);
+            }
+        }
+        // Cells whose spilled values change are marked dirty, so formulas
+        // reading them run again in the next pass.
+        for _ in 0..MAX_SPILL_PASSES {
+            let dirty = engine.get_dirty_nodes_with_sheet()?;
+            if dirty.is_empty() {
+                break;
+            }
+            for cell in dirty {
+                let Some(name) = names.get(&cell.sheet_id) else {
+                    continue;
+                };
+                let Some(compiled) = engine.get_formula_with_sheet(cell.sheet_id, &cell.addr)
+                else {
+                    continue;
+                };
+                let resolver = BookValueResolver {
+                    book: self,
+                    local: SheetValueResolver::new(self.get_sheet(name)?, Some(cell.addr)),
+                };
+                let value = engine.evaluate(compiled, &resolver)?;
+                let changed = self.get_sheet_mut(name)?.store_formula_result(
+                    cell.addr,
+                    value,
+                    &mut |cells| {
+                        engine
+                            .set_spill_with_sheet(cell.sheet_id, cell.addr, cells)
+                            .is_ok()
+                    },
+                )?;
+                for addr in changed {
+                    engine.mark_dirty_with_sheet(cell.sheet_id, &addr);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Recalculate every formula in the book. Use after changing sheets
+    /// directly, e.g. through [`Book::get_sheet_mut`], which the book cannot
+    /// track.
+    pub fn evaluate_formulas(&mut self) -> Result<()> {
+        self.formulas.stale = true;
+        self.recalculate()
+    }
+
+    /// The sheet a formula reference names: an exact match, or else one
+    /// that matches without regard to case.
+    pub(crate) fn formula_sheet(&self, name: &str) -> Option<&Sheet> {
+        self.get_sheet(name).ok().or_else(|| {
+            let lower = name.to_lowercase();
+            self.sheets()
+                .find(|(sheet, _)| sheet.to_lowercase() == lower)
+                .map(|(_, sheet)| sheet)
+        })
+    }
+
+    fn sheet_ids_for(&self, sheet: &str) -> Result<u32> {
+        self.sheet_id(sheet)
+            .ok_or_else(|| SheetError::SheetNotFound {
+                name: sheet.to_string(),
+            })
+    }
+
+    /// Compile every formula cell of every sheet into a fresh dependency
+    /// graph, all marked dirty.
+    fn rebuild_formulas(&mut self) -> Result<()> {
+        let mut engine = FormulaEngine::new();
+        let ids = SheetIds(self);
+        for (name, sheet) in self.sheets() {
+            let id = self.sheet_ids_for(name)?;
+            for (row_idx, row) in sheet.data().iter().enumerate() {
+                for (col_idx, cell) in row.iter().enumerate() {
+                    if let CellValue::Formula(formula) = cell {
+                        let addr = CellAddress::new(row_idx as u32, col_idx as u32);
+                        engine.set_formula_with_sheet(id, addr, &formula.source, Some(&ids))?;
+                        engine.mark_dirty_with_sheet(id, &addr);
+                    }
+                }
+            }
+        }
+        self.formulas.engine = engine;
+        self.formulas.stale = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book() -> Book {
+        let mut book = Book::new();
+        book.add_sheet("Data", Sheet::from_data(vec![vec![1, 2], vec![3, 4]]))
+            .unwrap();
+        book.add_sheet(
+            "Report",
+            Sheet::from_data(vec![vec![CellValue::Null, CellValue::Null]]),
+        )
+        .unwrap();
+        book.set_formula("Report", "A1", "=SUM(Data!A1:B2)")
+            .unwrap();
+        book.set_formula("Report", "B1", "=A1 * data!B2").unwrap();
+        book.recalculate().unwrap();
+        book
+    }
+
+    fn report(book: &Book, notation: &str) -> CellValue {
+        book.get_sheet("Report")
+            .unwrap()
+            .get_a1(notation)
+            .unwrap()
+            .cached_or_self()
+            .clone()
+    }
+
+    #[test]
+    fn test_cross_sheet_recalculation() {
+        let mut book = book();
+        assert_eq!(report(&book, "A1"), CellValue::Float(10.0));
+        assert_eq!(report(&book, "B1"), CellValue::Float(40.0));
+
+        // A change on Data reaches both formulas, in order, on the next pass
+        book.set_a1("Data", "B2", 10).unwrap();
+        book.recalculate().unwrap();
+        assert_eq!(report(&book, "A1"), CellValue::Float(16.0));
+        assert_eq!(report(&book, "B1"), CellValue::Float(160.0));
+    }
+
+    #[test]
+    fn test_rename_and_remove_sheet() {
+        let mut book = book();
+        let id = book.sheet_id("Data");
+        book.rename_sheet("Data", "Q1 Data").unwrap();
+        assert_eq!(book.sheet_id("Q1 Data"), id);
+        let source = match book.get_sheet("Report").unwrap().get_a1("A1").unwrap() {
+            CellValue::Formula(formula) => formula.source.clone(),
+            other => panic!("Expected formula, got {other:?}"),
+        };
+        assert_eq!(source, "=SUM('Q1 Data'!A1:B2)");
+        book.recalculate().unwrap();
+        assert_eq!(report(&book, "B1"), CellValue::Float(40.0));
+
+        book.remove_sheet("Q1 Data").unwrap();
+        book.recalculate().unwrap();
+        assert_eq!(report(&book, "A1"), CellValue::String("#REF!".to_string()));
+        assert_eq!(report(&book, "B1"), CellValue::String("#REF!".to_string()));
+    }
+
+    #[test]
+    fn test_cross_sheet_cycle() {
+        let mut book = book();
+        let err = book.set_formula("Data", "B2", "=Report!B1").unwrap_err();
+        assert!(matches!(err, SheetError::Formula(_)), "{err}");
+        let data = book.get_sheet("Data").unwrap();
+        assert_eq!(data.get_a1("B2").unwrap(), &CellValue::Int(4));
+    }
+}
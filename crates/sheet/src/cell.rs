@@ -1,3 +1,4 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -21,6 +22,12 @@ pub enum CellValue {
     String(String),
     /// Formula cell storing the source and cached result.
     Formula(FormulaCell),
+    /// Calendar date without a time zone.
+    Date(NaiveDate),
+    /// Date and time without a time zone, as spreadsheets store them.
+    DateTime(NaiveDateTime),
+    /// Elapsed time, serialized as an ISO 8601 duration such as `PT90S`.
+    Duration(#[serde(with = "iso_duration")] TimeDelta),
 }
 
 impl CellValue {
@@ -63,8 +70,11 @@ impl CellValue {
             CellValue::Int(i) => Some(*i != 0),
             CellValue::Float(f) => Some(*f != 0.0),
             CellValue::String(s) => s.parse().ok(),
-            CellValue::Null => None,
-            CellValue::Formula(_) => None,
+            CellValue::Null
+            | CellValue::Formula(_)
+            | CellValue::Date(_)
+            | CellValue::DateTime(_)
+            | CellValue::Duration(_) => None,
         }
     }
 
@@ -76,6 +86,9 @@ impl CellValue {
             CellValue::Float(f) => Some(*f as i64),
             CellValue::Bool(b) => Some(i64::from(*b)),
             CellValue::String(s) => s.parse().ok(),
+            CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_) => {
+                self.as_float().map(|f| f.floor() as i64)
+            }
             CellValue::Null => None,
             CellValue::Formula(_) => None,
         }
@@ -89,6 +102,9 @@ impl CellValue {
             CellValue::Int(i) => Some(*i as f64),
             CellValue::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
             CellValue::String(s) => s.parse().ok(),
+            CellValue::Date(d) => Some(excel_serial(d.and_time(NaiveTime::MIN))),
+            CellValue::DateTime(dt) => Some(excel_serial(*dt)),
+            CellValue::Duration(d) => Some(duration_days(*d)),
             CellValue::Null => None,
             CellValue::Formula(_) => None,
         }
@@ -104,9 +120,96 @@ impl CellValue {
             CellValue::Float(f) => f.to_string(),
             CellValue::String(s) => s.clone(),
             CellValue::Formula(formula) => formula.source.clone(),
+            value @ (CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_)) => {
+                value.to_string()
+            }
+        }
+    }
+
+    /// The value as a date; date-times are truncated to their day.
+    #[must_use]
+    pub fn as_date(&self) -> Option<NaiveDate> {
+        match self.cached_or_self() {
+            CellValue::Date(d) => Some(*d),
+            CellValue::DateTime(dt) => Some(dt.date()),
+            _ => None,
+        }
+    }
+
+    /// The value as a date-time; dates are taken at midnight.
+    #[must_use]
+    pub fn as_datetime(&self) -> Option<NaiveDateTime> {
+        match self.cached_or_self() {
+            CellValue::Date(d) => Some(d.and_time(NaiveTime::MIN)),
+            CellValue::DateTime(dt) => Some(*dt),
+            _ => None,
+        }
+    }
+
+    /// The value as a duration.
+    #[must_use]
+    pub fn as_duration(&self) -> Option<TimeDelta> {
+        match self.cached_or_self() {
+            CellValue::Duration(d) => Some(*d),
+            _ => None,
         }
     }
 
+    /// Whether the value is a date, date-time or duration.
+    #[must_use]
+    pub fn is_temporal(&self) -> bool {
+        matches!(
+            self.cached_or_self(),
+            CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_)
+        )
+    }
+
+    /// Parse `s` as a date or date-time using chrono format strings, trying
+    /// `datetime_formats` first. Returns `None` when no format matches.
+    ///
+    /// ```
+    /// use piptable_sheet::CellValue;
+    ///
+    /// let dates = ["%d/%m/%Y"];
+    /// let datetimes = ["%d/%m/%Y %H:%M"];
+    /// let value = CellValue::parse_temporal("15/01/2024", &dates, &datetimes).unwrap();
+    /// assert_eq!(value.as_str(), "2024-01-15");
+    /// let value = CellValue::parse_temporal("15/01/2024 09:30", &dates, &datetimes).unwrap();
+    /// assert_eq!(value.as_str(), "2024-01-15T09:30:00");
+    /// ```
+    #[must_use]
+    pub fn parse_temporal<D: AsRef<str>, T: AsRef<str>>(
+        s: &str,
+        date_formats: &[D],
+        datetime_formats: &[T],
+    ) -> Option<CellValue> {
+        let trimmed = s.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+        datetime_formats
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(trimmed, format.as_ref()).ok())
+            .map(CellValue::DateTime)
+            .or_else(|| {
+                date_formats
+                    .iter()
+                    .find_map(|format| NaiveDate::parse_from_str(trimmed, format.as_ref()).ok())
+                    .map(CellValue::Date)
+            })
+    }
+
+    /// Parse ISO 8601 text: `2024-01-15`, `2024-01-15T10:30:00` (a space
+    /// also separates date and time) or a duration such as `PT1H30M`.
+    #[must_use]
+    pub fn parse_iso_temporal(s: &str) -> Option<CellValue> {
+        let trimmed = s.trim();
+        if let Some(duration) = iso_duration::parse(trimmed) {
+            return Some(CellValue::Duration(duration));
+        }
+        Self::parse_temporal(trimmed, &ISO_DATE_FORMATS, &ISO_DATETIME_FORMATS)
+    }
+
     /// Parse a string into a `CellValue` with type inference
     /// Tries: null -> bool -> int -> float -> string
     #[must_use]
@@ -160,6 +263,9 @@ impl fmt::Display for CellValue {
             CellValue::Float(fl) => write!(f, "{fl}"),
             CellValue::String(s) => write!(f, "{s}"),
             CellValue::Formula(formula) => write!(f, "{}", formula.source),
+            CellValue::Date(d) => write!(f, "{}", d.format("%Y-%m-%d")),
+            CellValue::DateTime(dt) => write!(f, "{}", dt.format("%Y-%m-%dT%H:%M:%S%.f")),
+            CellValue::Duration(d) => write!(f, "{d}"),
         }
     }
 }
@@ -206,6 +312,24 @@ impl From<&str> for CellValue {
     }
 }
 
+impl From<NaiveDate> for CellValue {
+    fn from(d: NaiveDate) -> Self {
+        CellValue::Date(d)
+    }
+}
+
+impl From<NaiveDateTime> for CellValue {
+    fn from(dt: NaiveDateTime) -> Self {
+        CellValue::DateTime(dt)
+    }
+}
+
+impl From<TimeDelta> for CellValue {
+    fn from(d: TimeDelta) -> Self {
+        CellValue::Duration(d)
+    }
+}
+
 impl<T: Into<CellValue>> From<Option<T>> for CellValue {
     fn from(opt: Option<T>) -> Self {
         match opt {
@@ -215,6 +339,93 @@ impl<T: Into<CellValue>> From<Option<T>> for CellValue {
     }
 }
 
+const ISO_DATE_FORMATS: [&str; 1] = ["%Y-%m-%d"];
+const ISO_DATETIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Excel serial number of `dt`: days since 1899-12-30, keeping Excel's
+/// phantom 1900-02-29 so serials before March 1900 match what Excel shows.
+fn excel_serial(dt: NaiveDateTime) -> f64 {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30)
+        .expect("valid epoch")
+        .and_time(NaiveTime::MIN);
+    let serial = duration_days(dt - epoch);
+    if serial < 61.0 {
+        serial - 1.0
+    } else {
+        serial
+    }
+}
+
+/// Length of `d` in days, the unit Excel uses for durations.
+fn duration_days(d: TimeDelta) -> f64 {
+    d.num_milliseconds() as f64 / 1000.0 / SECONDS_PER_DAY
+}
+
+/// ISO 8601 durations (`P1DT2H`, `PT90.5S`, `-PT5M`) for serde and parsing.
+mod iso_duration {
+    use chrono::TimeDelta;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(d: &TimeDelta, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(d)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TimeDelta, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse(&text).ok_or_else(|| D::Error::custom(format!("invalid duration '{text}'")))
+    }
+
+    /// Parse day, hour, minute and second components; years, months and
+    /// weeks are rejected because their length is not fixed.
+    pub fn parse(s: &str) -> Option<TimeDelta> {
+        let (negative, rest) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let rest = rest.strip_prefix('P')?;
+        let (date_part, time_part) = match rest.split_once('T') {
+            Some((date, time)) if !time.is_empty() => (date, Some(time)),
+            Some(_) => return None,
+            None => (rest, None),
+        };
+        if date_part.is_empty() && time_part.is_none() {
+            return None;
+        }
+
+        let mut millis = 0.0;
+        let mut add = |part: &str, units: &[(char, f64)]| -> Option<()> {
+            let mut number = String::new();
+            let mut allowed = units;
+            for c in part.chars() {
+                if c.is_ascii_digit() || c == '.' {
+                    number.push(c);
+                    continue;
+                }
+                let position = allowed.iter().position(|(unit, _)| *unit == c)?;
+                let value: f64 = number.parse().ok()?;
+                millis += value * allowed[position].1;
+                allowed = &allowed[position + 1..];
+                number.clear();
+            }
+            number.is_empty().then_some(())
+        };
+        add(date_part, &[('D', 86_400_000.0)])?;
+        if let Some(time) = time_part {
+            add(time, &[('H', 3_600_000.0), ('M', 60_000.0), ('S', 1_000.0)])?;
+        }
+
+        let duration = TimeDelta::milliseconds(millis.round() as i64);
+        Some(if negative { -duration } else { duration })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::approx_constant)]
@@ -265,6 +476,40 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_temporal_values() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let dt = date.and_hms_opt(12, 0, 0).unwrap();
+        assert_eq!(CellValue::Date(date).as_str(), "2024-01-15");
+        assert_eq!(CellValue::DateTime(dt).as_str(), "2024-01-15T12:00:00");
+        assert_eq!(CellValue::Date(date).as_float(), Some(45306.0));
+        assert_eq!(CellValue::DateTime(dt).as_float(), Some(45306.5));
+        assert_eq!(
+            CellValue::Date(NaiveDate::from_ymd_opt(1900, 1, 1).unwrap()).as_float(),
+            Some(1.0)
+        );
+
+        let duration = CellValue::Duration(TimeDelta::minutes(90));
+        assert_eq!(duration.as_str(), "PT5400S");
+        assert_eq!(CellValue::parse_iso_temporal("PT1H30M"), Some(duration));
+        assert_eq!(
+            CellValue::parse_iso_temporal("-P1DT0.5S"),
+            Some(CellValue::Duration(-TimeDelta::milliseconds(86_400_500)))
+        );
+        assert_eq!(CellValue::parse_iso_temporal("P1M"), None);
+        assert_eq!(
+            CellValue::parse_iso_temporal("2024-01-15 12:00"),
+            Some(CellValue::DateTime(dt))
+        );
+
+        let json = serde_json::to_string(&vec![
+            CellValue::Date(date),
+            CellValue::Duration(TimeDelta::seconds(90)),
+        ])
+        .unwrap();
+        assert_eq!(json, r#"["2024-01-15","PT90S"]"#);
+    }
+
     #[test]
     fn test_conversions() {
         assert_eq!(CellValue::Int(42).as_float(), Some(42.0));
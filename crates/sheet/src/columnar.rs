@@ -0,0 +1,1525 @@
+//! Column-oriented storage for sheets
+//!
+//! Sheets read from files keep each column as an Arrow array. A column
+//! edited in place turns into plain cells (the mutable overlay), and the
+//! row API is served from a row view that is built on first use. Column
+//! operations read the arrays directly and never build it.
+
+use crate::cell::CellValue;
+use crate::error::{Result, SheetError};
+use crate::sheet::Sheet;
+use arrow::array::{
+    new_null_array, Array, ArrayRef, AsArray, BooleanArray, BooleanBuilder, Date32Array,
+    Date32Builder, DurationMicrosecondArray, DurationMicrosecondBuilder, Float64Array,
+    Float64Builder, Int64Array, Int64Builder, RecordBatch, RecordBatchOptions, StringArray,
+    StringBuilder, TimestampMicrosecondArray, TimestampMicrosecondBuilder, UInt64Array,
+};
+use arrow::compute;
+use arrow::datatypes::{
+    DataType, Date32Type, Date64Type, DurationMicrosecondType, DurationMillisecondType,
+    DurationNanosecondType, DurationSecondType, Field, Schema, TimeUnit, TimestampMicrosecondType,
+    TimestampMillisecondType, TimestampNanosecondType, TimestampSecondType,
+};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta};
+use std::borrow::Cow;
+use std::sync::{Arc, OnceLock};
+
+/// The cells of a sheet, stored by row or by column.
+#[derive(Debug, Clone)]
+pub(crate) enum Storage {
+    /// One vector per row; rows may differ in length.
+    Rows(Vec<Vec<CellValue>>),
+    /// One array per column, with the header row kept aside.
+    Columns(Columns),
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Storage::Rows(Vec::new())
+    }
+}
+
+impl Storage {
+    /// Column storage, or empty rows when there are no rows to hold.
+    pub(crate) fn from_columns(columns: Columns) -> Self {
+        if columns.row_count() == 0 {
+            Storage::default()
+        } else {
+            Storage::Columns(columns)
+        }
+    }
+
+    pub(crate) fn row_count(&self) -> usize {
+        match self {
+            Storage::Rows(rows) => rows.len(),
+            Storage::Columns(columns) => columns.row_count(),
+        }
+    }
+
+    pub(crate) fn col_count(&self) -> usize {
+        match self {
+            Storage::Rows(rows) => rows.first().map_or(0, Vec::len),
+            Storage::Columns(columns) => columns.cols.len(),
+        }
+    }
+
+    /// All rows, building the row view of column storage on first use.
+    pub(crate) fn rows(&self) -> &Vec<Vec<CellValue>> {
+        match self {
+            Storage::Rows(rows) => rows,
+            Storage::Columns(columns) => columns.rows(),
+        }
+    }
+
+    /// All rows for editing, switching column storage back to rows.
+    pub(crate) fn rows_mut(&mut self) -> &mut Vec<Vec<CellValue>> {
+        if let Storage::Columns(columns) = self {
+            let rows = std::mem::take(columns).into_rows();
+            *self = Storage::Rows(rows);
+        }
+        match self {
+            Storage::Rows(rows) => rows,
+            Storage::Columns(_) => unreachable!("column storage was converted above"),
+        }
+    }
+
+    /// The first row, without building the row view.
+    pub(crate) fn first_row(&self) -> Option<Cow<'_, [CellValue]>> {
+        match self {
+            Storage::Rows(rows) => rows.first().map(|row| Cow::Borrowed(row.as_slice())),
+            Storage::Columns(columns) => columns.first_row(),
+        }
+    }
+
+    pub(crate) fn row(&self, index: usize) -> Option<&Vec<CellValue>> {
+        match self {
+            Storage::Rows(rows) => rows.get(index),
+            Storage::Columns(columns) => match &columns.header {
+                Some(header) if index == 0 => Some(header),
+                _ => columns.rows().get(index),
+            },
+        }
+    }
+
+    pub(crate) fn get(&self, row: usize, col: usize) -> Option<&CellValue> {
+        self.row(row).and_then(|r| r.get(col))
+    }
+
+    pub(crate) fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut CellValue> {
+        match self {
+            Storage::Rows(rows) => rows.get_mut(row).and_then(|r| r.get_mut(col)),
+            Storage::Columns(columns) => columns.get_mut(row, col),
+        }
+    }
+
+    /// Call `f` with each row index and row, without building the row view.
+    pub(crate) fn for_each_row<F>(&self, mut f: F)
+    where
+        F: FnMut(usize, &[CellValue]),
+    {
+        match self {
+            Storage::Rows(rows) => {
+                for (idx, row) in rows.iter().enumerate() {
+                    f(idx, row);
+                }
+            }
+            Storage::Columns(columns) => columns.for_each_row(f),
+        }
+    }
+
+    /// Cells of column `index`, which must be in range, header included.
+    pub(crate) fn column(&self, index: usize) -> Vec<CellValue> {
+        match self {
+            Storage::Rows(rows) => rows.iter().map(|row| row[index].clone()).collect(),
+            Storage::Columns(columns) => columns
+                .header
+                .iter()
+                .map(|header| header[index].clone())
+                .chain(columns.cols[index].cells().into_owned())
+                .collect(),
+        }
+    }
+
+    /// Call `f` with each row index and that row's cell in column `index`.
+    pub(crate) fn for_each_in_column<F>(&self, index: usize, mut f: F)
+    where
+        F: FnMut(usize, Option<&CellValue>),
+    {
+        match self {
+            Storage::Rows(rows) => {
+                for (idx, row) in rows.iter().enumerate() {
+                    f(idx, row.get(index));
+                }
+            }
+            Storage::Columns(columns) => {
+                let offset = columns.header_rows();
+                if let Some(header) = &columns.header {
+                    f(0, header.get(index));
+                }
+                let reader = columns.cols.get(index).map(Column::reader);
+                for idx in 0..columns.len {
+                    let cell = reader.as_ref().map(|reader| reader.get(idx));
+                    f(idx + offset, cell.as_deref());
+                }
+            }
+        }
+    }
+
+    /// Replace each cell of column `index` with `f` of it.
+    pub(crate) fn map_column<F>(&mut self, index: usize, f: F)
+    where
+        F: Fn(&CellValue) -> CellValue,
+    {
+        match self {
+            Storage::Rows(rows) => {
+                for row in rows {
+                    if let Some(cell) = row.get_mut(index) {
+                        *cell = f(cell);
+                    }
+                }
+            }
+            Storage::Columns(columns) => columns.map_column(index, f),
+        }
+    }
+
+    /// Replace every cell with `f` of it.
+    pub(crate) fn map_cells<F>(&mut self, f: F)
+    where
+        F: Fn(&CellValue) -> CellValue,
+    {
+        match self {
+            Storage::Rows(rows) => {
+                for row in rows {
+                    for cell in row {
+                        *cell = f(cell);
+                    }
+                }
+            }
+            Storage::Columns(columns) => {
+                for index in 0..columns.cols.len() {
+                    columns.map_column(index, &f);
+                }
+            }
+        }
+    }
+
+    /// Keep the rows whose flag is set in `keep`. Columns that Arrow fails
+    /// to filter are filtered as rows instead.
+    pub(crate) fn retain_rows(&mut self, keep: &[bool]) {
+        if let Storage::Columns(columns) = self {
+            if let Ok(filtered) = columns.filter(keep) {
+                *self = Storage::from_columns(filtered);
+                return;
+            }
+        }
+        let mut flags = keep.iter();
+        self.rows_mut()
+            .retain(|_| flags.next().copied().unwrap_or(false));
+    }
+
+    /// Keep the columns at `indices`, in that order. Rows too short for an
+    /// index just lose that cell.
+    pub(crate) fn select_columns(&mut self, indices: &[usize]) {
+        match self {
+            Storage::Rows(rows) => {
+                for row in rows {
+                    *row = indices
+                        .iter()
+                        .filter_map(|&i| row.get(i).cloned())
+                        .collect();
+                }
+            }
+            Storage::Columns(columns) => *columns = columns.select(indices),
+        }
+    }
+
+    /// Insert `cells`, one per row, as column `index` of a non-empty sheet.
+    pub(crate) fn insert_column(&mut self, index: usize, cells: Vec<CellValue>) {
+        match self {
+            Storage::Rows(rows) => {
+                for (row, cell) in rows.iter_mut().zip(cells) {
+                    row.insert(index, cell);
+                }
+            }
+            Storage::Columns(columns) => {
+                let (head, column) = columns.split_cells(cells);
+                if let (Some(header), Some(head)) = (&mut columns.header, head) {
+                    header.insert(index, head);
+                }
+                columns.rows.take();
+                columns.cols.insert(index, column);
+            }
+        }
+    }
+
+    /// Replace column `index` with `cells`, one per row.
+    pub(crate) fn set_column(&mut self, index: usize, cells: Vec<CellValue>) {
+        match self {
+            Storage::Rows(rows) => {
+                for (row, cell) in rows.iter_mut().zip(cells) {
+                    row[index] = cell;
+                }
+            }
+            Storage::Columns(columns) => {
+                let (head, column) = columns.split_cells(cells);
+                if let (Some(header), Some(head)) = (&mut columns.header, head) {
+                    header[index] = head;
+                }
+                columns.set_column(index, column);
+            }
+        }
+    }
+
+    /// Visit every cell that can hold a formula. Arrow columns cannot, so
+    /// only the header and edited columns are scanned.
+    pub(crate) fn for_each_cell_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, usize, &mut CellValue),
+    {
+        match self {
+            Storage::Rows(rows) => {
+                for (row_idx, row) in rows.iter_mut().enumerate() {
+                    for (col_idx, cell) in row.iter_mut().enumerate() {
+                        f(row_idx, col_idx, cell);
+                    }
+                }
+            }
+            Storage::Columns(columns) => {
+                // Callers may change cells, so the row view goes stale
+                columns.rows.take();
+                let offset = columns.header_rows();
+                if let Some(header) = &mut columns.header {
+                    for (col_idx, cell) in header.iter_mut().enumerate() {
+                        f(0, col_idx, cell);
+                    }
+                }
+                for (col_idx, column) in columns.cols.iter_mut().enumerate() {
+                    if let Column::Cells(cells) = column {
+                        for (row_idx, cell) in cells.iter_mut().enumerate() {
+                            f(row_idx + offset, col_idx, cell);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether every row has the same length, so columns can hold them.
+    fn is_rectangular(rows: &[Vec<CellValue>]) -> bool {
+        let width = rows.first().map_or(0, Vec::len);
+        rows.iter().all(|row| row.len() == width)
+    }
+
+    /// Move to column storage where the cells allow it. Row storage converts
+    /// when rectangular; edited columns go back to Arrow arrays and the row
+    /// view is dropped.
+    pub(crate) fn compact(&mut self, has_header: bool) {
+        match self {
+            Storage::Rows(rows) => {
+                if rows.is_empty() || !Self::is_rectangular(rows) {
+                    return;
+                }
+                let rows = std::mem::take(rows);
+                *self = Storage::Columns(Columns::from_rows(rows, has_header));
+            }
+            Storage::Columns(columns) => {
+                columns.rows.take();
+                for column in &mut columns.cols {
+                    column.compact();
+                }
+            }
+        }
+    }
+}
+
+/// Sheet columns plus an optional header row.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Columns {
+    header: Option<Vec<CellValue>>,
+    cols: Vec<Column>,
+    /// Number of data rows, excluding the header.
+    len: usize,
+    rows: OnceLock<Vec<Vec<CellValue>>>,
+}
+
+impl Columns {
+    /// Columns of `len` data rows each, below an optional header row.
+    pub(crate) fn new(header: Option<Vec<CellValue>>, columns: Vec<Column>, len: usize) -> Self {
+        debug_assert!(columns.iter().all(|column| column.len() == len));
+        debug_assert!(header.as_ref().is_none_or(|h| h.len() == columns.len()));
+        Columns {
+            header,
+            cols: columns,
+            len,
+            rows: OnceLock::new(),
+        }
+    }
+
+    /// Split rectangular rows into columns.
+    fn from_rows(rows: Vec<Vec<CellValue>>, has_header: bool) -> Self {
+        let width = rows.first().map_or(0, Vec::len);
+        let mut rows = rows.into_iter();
+        let header = if has_header { rows.next() } else { None };
+        let mut builders: Vec<ColumnBuilder> = (0..width).map(|_| ColumnBuilder::new()).collect();
+        let mut len = 0;
+        for row in rows {
+            for (builder, cell) in builders.iter_mut().zip(row) {
+                builder.push(cell);
+            }
+            len += 1;
+        }
+        let columns = builders.into_iter().map(ColumnBuilder::finish).collect();
+        Columns::new(header, columns, len)
+    }
+
+    pub(crate) fn columns(&self) -> &[Column] {
+        &self.cols
+    }
+
+    /// 1 when row 0 is the stored header row, else 0.
+    pub(crate) fn header_rows(&self) -> usize {
+        usize::from(self.header.is_some())
+    }
+
+    fn row_count(&self) -> usize {
+        self.len + self.header_rows()
+    }
+
+    fn first_row(&self) -> Option<Cow<'_, [CellValue]>> {
+        if let Some(header) = &self.header {
+            return Some(Cow::Borrowed(header.as_slice()));
+        }
+        if let Some(rows) = self.rows.get() {
+            return rows.first().map(|row| Cow::Borrowed(row.as_slice()));
+        }
+        (self.len > 0)
+            .then(|| Cow::Owned(self.cols.iter().map(|c| c.get(0).into_owned()).collect()))
+    }
+
+    fn rows(&self) -> &Vec<Vec<CellValue>> {
+        self.rows.get_or_init(|| self.build_rows())
+    }
+
+    fn build_rows(&self) -> Vec<Vec<CellValue>> {
+        let mut rows = Vec::with_capacity(self.row_count());
+        self.for_each_row(|_, row| rows.push(row.to_vec()));
+        rows
+    }
+
+    fn into_rows(mut self) -> Vec<Vec<CellValue>> {
+        match self.rows.take() {
+            Some(rows) => rows,
+            None => self.build_rows(),
+        }
+    }
+
+    fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut CellValue> {
+        let offset = self.header_rows();
+        if row >= self.row_count() || col >= self.cols.len() {
+            return None;
+        }
+        self.rows.take();
+        match &mut self.header {
+            Some(header) if row == 0 => header.get_mut(col),
+            _ => self.cols[col].make_mut().get_mut(row - offset),
+        }
+    }
+
+    fn for_each_row<F>(&self, mut f: F)
+    where
+        F: FnMut(usize, &[CellValue]),
+    {
+        if let Some(rows) = self.rows.get() {
+            for (idx, row) in rows.iter().enumerate() {
+                f(idx, row);
+            }
+            return;
+        }
+        let offset = self.header_rows();
+        if let Some(header) = &self.header {
+            f(0, header);
+        }
+        let readers: Vec<Reader<'_>> = self.cols.iter().map(Column::reader).collect();
+        let mut row = Vec::with_capacity(readers.len());
+        for idx in 0..self.len {
+            row.clear();
+            row.extend(readers.iter().map(|reader| reader.get(idx).into_owned()));
+            f(idx + offset, &row);
+        }
+    }
+
+    /// Keep the rows whose flag is set; `keep` covers the header row too.
+    pub(crate) fn filter(&self, keep: &[bool]) -> Result<Columns> {
+        let (header, keep_data) = match &self.header {
+            Some(header) => (keep[0].then(|| header.clone()), &keep[1..]),
+            None => (None, keep),
+        };
+        let mask = BooleanArray::from(keep_data.to_vec());
+        let len = mask.true_count();
+        let columns = self
+            .cols
+            .iter()
+            .map(|c| c.filter(&mask))
+            .collect::<Result<_>>()?;
+        Ok(Columns::new(header, columns, len))
+    }
+
+    /// Rearrange the data rows: row `i` of the result is data row
+    /// `indices[i]`, or all nulls for `None`.
+    pub(crate) fn take(&self, indices: &[Option<usize>]) -> Result<Columns> {
+        let indices = indices
+            .iter()
+            .map(|i| i.map(|i| i as u64))
+            .collect::<UInt64Array>();
+        let columns = self
+            .cols
+            .iter()
+            .map(|c| c.take(&indices))
+            .collect::<Result<_>>()?;
+        Ok(Columns::new(self.header.clone(), columns, indices.len()))
+    }
+
+    /// The columns of a join: all of `left`, then `right_columns` of
+    /// `right`, with rows paired up by `pairs` of sheet row indices. Rows
+    /// only on the right take their key from the right sheet.
+    pub(crate) fn join(
+        left: &Columns,
+        right: &Columns,
+        pairs: &[(Option<usize>, Option<usize>)],
+        (left_key, right_key): (usize, usize),
+        right_columns: &[usize],
+        header: Vec<CellValue>,
+    ) -> Result<Columns> {
+        let (left_offset, right_offset) = (left.header_rows(), right.header_rows());
+        let left_take = pairs
+            .iter()
+            .map(|(l, _)| l.map(|l| (l - left_offset) as u64))
+            .collect::<UInt64Array>();
+        let right_take = pairs
+            .iter()
+            .map(|(_, r)| r.map(|r| (r - right_offset) as u64))
+            .collect::<UInt64Array>();
+
+        let mut columns = left
+            .cols
+            .iter()
+            .map(|c| c.take(&left_take))
+            .collect::<Result<Vec<_>>>()?;
+        let right_keys = &right.cols[right_key];
+        let patches: Vec<(usize, CellValue)> = pairs
+            .iter()
+            .enumerate()
+            .filter_map(|(row, pair)| match pair {
+                (None, Some(r)) => Some((row, right_keys.get(r - right_offset).into_owned())),
+                _ => None,
+            })
+            .collect();
+        if !patches.is_empty() {
+            let cells = columns[left_key].make_mut();
+            for (row, cell) in patches {
+                cells[row] = cell;
+            }
+            columns[left_key].compact();
+        }
+        for &i in right_columns {
+            columns.push(right.cols[i].take(&right_take)?);
+        }
+        Ok(Columns::new(Some(header), columns, pairs.len()))
+    }
+
+    /// Keep the columns at `indices`, in that order.
+    pub(crate) fn select(&self, indices: &[usize]) -> Columns {
+        let header = self
+            .header
+            .as_ref()
+            .map(|header| indices.iter().map(|&i| header[i].clone()).collect());
+        let columns = indices.iter().map(|&i| self.cols[i].clone()).collect();
+        Columns::new(header, columns, self.len)
+    }
+
+    /// Split a full column of cells into its header cell and data column.
+    fn split_cells(&self, cells: Vec<CellValue>) -> (Option<CellValue>, Column) {
+        let mut cells = cells.into_iter();
+        let head = if self.header.is_some() {
+            cells.next()
+        } else {
+            None
+        };
+        (head, Column::from_cells(cells))
+    }
+
+    fn map_column<F>(&mut self, index: usize, f: F)
+    where
+        F: Fn(&CellValue) -> CellValue,
+    {
+        if let Some(header) = &mut self.header {
+            header[index] = f(&header[index]);
+        }
+        let reader = self.cols[index].reader();
+        let mut builder = ColumnBuilder::new();
+        for idx in 0..self.len {
+            builder.push(f(&reader.get(idx)));
+        }
+        self.set_column(index, builder.finish());
+    }
+
+    /// Replace the data cells of column `index`.
+    pub(crate) fn set_column(&mut self, index: usize, column: Column) {
+        debug_assert_eq!(column.len(), self.len);
+        self.rows.take();
+        self.cols[index] = column;
+    }
+}
+
+/// One column of data cells.
+#[derive(Debug, Clone)]
+pub(crate) enum Column {
+    /// An Arrow array of one of the canonical types (see [`is_canonical`]).
+    Array(ArrayRef),
+    /// Plain cells, for edited columns and values Arrow cannot hold exactly.
+    Cells(Vec<CellValue>),
+}
+
+impl Column {
+    /// Wrap an array, converting types outside the canonical set.
+    pub(crate) fn from_array(array: ArrayRef) -> Column {
+        if is_canonical(array.data_type()) {
+            return Column::Array(array);
+        }
+        let mut builder = ColumnBuilder::new();
+        for idx in 0..array.len() {
+            builder.push(array_cell(&array, idx));
+        }
+        builder.finish()
+    }
+
+    /// Concatenate arrays of one type into a single column.
+    pub(crate) fn concat(arrays: &[ArrayRef]) -> Result<Column> {
+        let array = match arrays {
+            [array] => array.clone(),
+            _ => {
+                let refs: Vec<&dyn Array> = arrays.iter().map(AsRef::as_ref).collect();
+                compute::concat(&refs)?
+            }
+        };
+        Ok(Column::from_array(array))
+    }
+
+    /// Store cells as an Arrow array when they fit one.
+    pub(crate) fn from_cells(cells: impl IntoIterator<Item = CellValue>) -> Column {
+        let mut builder = ColumnBuilder::new();
+        for cell in cells {
+            builder.push(cell);
+        }
+        builder.finish()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Column::Array(array) => array.len(),
+            Column::Cells(cells) => cells.len(),
+        }
+    }
+
+    /// The Arrow array, unless the column has been edited.
+    pub(crate) fn array(&self) -> Option<&ArrayRef> {
+        match self {
+            Column::Array(array) => Some(array),
+            Column::Cells(_) => None,
+        }
+    }
+
+    /// All cells; an Arrow column decodes them for this call only.
+    pub(crate) fn cells(&self) -> Cow<'_, [CellValue]> {
+        match self {
+            Column::Array(array) => {
+                let reader = Reader::new(array);
+                Cow::Owned(
+                    (0..array.len())
+                        .map(|idx| reader.get(idx).into_owned())
+                        .collect(),
+                )
+            }
+            Column::Cells(cells) => Cow::Borrowed(cells),
+        }
+    }
+
+    /// Cell `idx`, which must be in range.
+    fn get(&self, idx: usize) -> Cow<'_, CellValue> {
+        match self {
+            Column::Array(array) => Reader::new(array).get(idx),
+            Column::Cells(cells) => Cow::Borrowed(&cells[idx]),
+        }
+    }
+
+    fn reader(&self) -> Reader<'_> {
+        match self {
+            Column::Array(array) => Reader::new(array),
+            Column::Cells(cells) => Reader::Cells(cells),
+        }
+    }
+
+    fn make_mut(&mut self) -> &mut Vec<CellValue> {
+        if let Column::Array(_) = self {
+            *self = Column::Cells(self.cells().into_owned());
+        }
+        match self {
+            Column::Cells(cells) => cells,
+            Column::Array(_) => unreachable!("column was converted to cells above"),
+        }
+    }
+
+    fn filter(&self, mask: &BooleanArray) -> Result<Column> {
+        Ok(match self {
+            Column::Array(array) => Column::Array(compute::filter(array, mask)?),
+            Column::Cells(cells) => Column::Cells(
+                cells
+                    .iter()
+                    .zip(mask.values())
+                    .filter(|(_, keep)| *keep)
+                    .map(|(cell, _)| cell.clone())
+                    .collect(),
+            ),
+        })
+    }
+
+    fn take(&self, indices: &UInt64Array) -> Result<Column> {
+        Ok(match self {
+            Column::Array(array) => Column::Array(compute::take(array, indices, None)?),
+            Column::Cells(cells) => Column::Cells(
+                indices
+                    .iter()
+                    .map(|idx| match idx {
+                        None => Ok(CellValue::Null),
+                        Some(idx) => cells.get(idx as usize).cloned().ok_or(
+                            SheetError::RowIndexOutOfBounds {
+                                index: idx as usize,
+                                count: cells.len(),
+                            },
+                        ),
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+        })
+    }
+
+    fn compact(&mut self) {
+        if let Column::Cells(cells) = self {
+            *self = Column::from_cells(std::mem::take(cells));
+        }
+    }
+}
+
+/// Arrow types a column array is stored as; anything else is converted on
+/// the way in.
+fn is_canonical(dtype: &DataType) -> bool {
+    matches!(
+        dtype,
+        DataType::Boolean
+            | DataType::Int64
+            | DataType::Float64
+            | DataType::Utf8
+            | DataType::Date32
+            | DataType::Timestamp(TimeUnit::Microsecond, None)
+            | DataType::Duration(TimeUnit::Microsecond)
+    )
+}
+
+/// Typed access to a column, downcast once rather than per cell.
+enum Reader<'a> {
+    Bool(&'a BooleanArray),
+    Int(&'a Int64Array),
+    Float(&'a Float64Array),
+    Utf8(&'a StringArray),
+    Date(&'a Date32Array),
+    DateTime(&'a TimestampMicrosecondArray),
+    Duration(&'a DurationMicrosecondArray),
+    Cells(&'a [CellValue]),
+}
+
+impl<'a> Reader<'a> {
+    fn new(array: &'a ArrayRef) -> Self {
+        match array.data_type() {
+            DataType::Boolean => Reader::Bool(array.as_boolean()),
+            DataType::Int64 => Reader::Int(array.as_primitive()),
+            DataType::Float64 => Reader::Float(array.as_primitive()),
+            DataType::Utf8 => Reader::Utf8(array.as_string()),
+            DataType::Date32 => Reader::Date(array.as_primitive()),
+            DataType::Timestamp(TimeUnit::Microsecond, None) => {
+                Reader::DateTime(array.as_primitive())
+            }
+            DataType::Duration(TimeUnit::Microsecond) => Reader::Duration(array.as_primitive()),
+            other => unreachable!("column arrays are canonical, found {other}"),
+        }
+    }
+
+    fn get(&self, idx: usize) -> Cow<'a, CellValue> {
+        let cell = match self {
+            Reader::Cells(cells) => return Cow::Borrowed(&cells[idx]),
+            Reader::Bool(a) => a.is_valid(idx).then(|| CellValue::Bool(a.value(idx))),
+            Reader::Int(a) => a.is_valid(idx).then(|| CellValue::Int(a.value(idx))),
+            Reader::Float(a) => a.is_valid(idx).then(|| CellValue::Float(a.value(idx))),
+            Reader::Utf8(a) => a
+                .is_valid(idx)
+                .then(|| CellValue::String(a.value(idx).to_string())),
+            Reader::Date(a) => a
+                .is_valid(idx)
+                .then(|| a.value_as_date(idx).map(CellValue::Date))
+                .flatten(),
+            Reader::DateTime(a) => a
+                .is_valid(idx)
+                .then(|| a.value_as_datetime(idx).map(CellValue::DateTime))
+                .flatten(),
+            Reader::Duration(a) => a
+                .is_valid(idx)
+                .then(|| a.value_as_duration(idx).map(CellValue::Duration))
+                .flatten(),
+        };
+        Cow::Owned(cell.unwrap_or(CellValue::Null))
+    }
+}
+
+/// Builds a column cell by cell, typed while the cells agree and falling
+/// back to plain cells once they do not.
+pub(crate) struct ColumnBuilder {
+    state: BuilderState,
+    len: usize,
+    text_bytes: usize,
+}
+
+enum BuilderState {
+    Nulls,
+    Bool(BooleanBuilder),
+    Int(Int64Builder),
+    Float(Float64Builder),
+    Utf8(StringBuilder),
+    Date(Date32Builder),
+    DateTime(TimestampMicrosecondBuilder),
+    Duration(DurationMicrosecondBuilder),
+    Cells(Vec<CellValue>),
+}
+
+impl ColumnBuilder {
+    pub(crate) fn new() -> Self {
+        ColumnBuilder {
+            state: BuilderState::Nulls,
+            len: 0,
+            text_bytes: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, cell: CellValue) {
+        if matches!(self.state, BuilderState::Nulls) {
+            if let Some(state) = BuilderState::start(&cell, self.len) {
+                self.state = state;
+            }
+        }
+        let pushed = match (&mut self.state, &cell) {
+            (BuilderState::Cells(_), _) => false,
+            (state, CellValue::Null) => state.append_null(),
+            (BuilderState::Bool(b), CellValue::Bool(v)) => {
+                b.append_value(*v);
+                true
+            }
+            (BuilderState::Int(b), CellValue::Int(v)) => {
+                b.append_value(*v);
+                true
+            }
+            (BuilderState::Float(b), CellValue::Float(v)) => {
+                b.append_value(*v);
+                true
+            }
+            // String offsets are i32, so very large columns stay as cells
+            (BuilderState::Utf8(b), CellValue::String(s))
+                if i32::try_from(self.text_bytes + s.len()).is_ok() =>
+            {
+                self.text_bytes += s.len();
+                b.append_value(s);
+                true
+            }
+            (BuilderState::Date(b), CellValue::Date(d)) => {
+                b.append_value(Date32Type::from_naive_date(*d));
+                true
+            }
+            (BuilderState::DateTime(b), CellValue::DateTime(dt)) => match exact_micros(dt) {
+                Some(micros) => {
+                    b.append_value(micros);
+                    true
+                }
+                None => false,
+            },
+            (BuilderState::Duration(b), CellValue::Duration(d)) => {
+                match d
+                    .num_microseconds()
+                    .filter(|&m| TimeDelta::microseconds(m) == *d)
+                {
+                    Some(micros) => {
+                        b.append_value(micros);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => false,
+        };
+        if !pushed {
+            self.spill();
+            if let BuilderState::Cells(cells) = &mut self.state {
+                cells.push(cell);
+            }
+        }
+        self.len += 1;
+    }
+
+    /// Switch to plain cells, keeping what has been pushed so far.
+    fn spill(&mut self) {
+        let cells = match std::mem::replace(&mut self.state, BuilderState::Nulls) {
+            BuilderState::Cells(cells) => cells,
+            BuilderState::Nulls => vec![CellValue::Null; self.len],
+            typed => {
+                let array = typed.finish();
+                let reader = Reader::new(&array);
+                (0..array.len())
+                    .map(|idx| reader.get(idx).into_owned())
+                    .collect()
+            }
+        };
+        self.state = BuilderState::Cells(cells);
+    }
+
+    pub(crate) fn finish(self) -> Column {
+        match self.state {
+            BuilderState::Cells(cells) => Column::Cells(cells),
+            BuilderState::Nulls => Column::Array(new_null_array(&DataType::Utf8, self.len)),
+            typed => Column::Array(typed.finish()),
+        }
+    }
+}
+
+impl BuilderState {
+    /// A typed builder for `cell`, padded with `nulls` leading nulls.
+    fn start(cell: &CellValue, nulls: usize) -> Option<Self> {
+        let mut state = match cell {
+            CellValue::Bool(_) => BuilderState::Bool(BooleanBuilder::new()),
+            CellValue::Int(_) => BuilderState::Int(Int64Builder::new()),
+            CellValue::Float(_) => BuilderState::Float(Float64Builder::new()),
+            CellValue::String(_) => BuilderState::Utf8(StringBuilder::new()),
+            CellValue::Date(_) => BuilderState::Date(Date32Builder::new()),
+            CellValue::DateTime(_) => BuilderState::DateTime(TimestampMicrosecondBuilder::new()),
+            CellValue::Duration(_) => BuilderState::Duration(DurationMicrosecondBuilder::new()),
+            CellValue::Null | CellValue::Formula(_) => return None,
+        };
+        for _ in 0..nulls {
+            state.append_null();
+        }
+        Some(state)
+    }
+
+    fn append_null(&mut self) -> bool {
+        match self {
+            BuilderState::Nulls => {}
+            BuilderState::Bool(b) => b.append_null(),
+            BuilderState::Int(b) => b.append_null(),
+            BuilderState::Float(b) => b.append_null(),
+            BuilderState::Utf8(b) => b.append_null(),
+            BuilderState::Date(b) => b.append_null(),
+            BuilderState::DateTime(b) => b.append_null(),
+            BuilderState::Duration(b) => b.append_null(),
+            BuilderState::Cells(_) => return false,
+        }
+        true
+    }
+
+    fn finish(self) -> ArrayRef {
+        match self {
+            BuilderState::Bool(mut b) => Arc::new(b.finish()),
+            BuilderState::Int(mut b) => Arc::new(b.finish()),
+            BuilderState::Float(mut b) => Arc::new(b.finish()),
+            BuilderState::Utf8(mut b) => Arc::new(b.finish()),
+            BuilderState::Date(mut b) => Arc::new(b.finish()),
+            BuilderState::DateTime(mut b) => Arc::new(b.finish()),
+            BuilderState::Duration(mut b) => Arc::new(b.finish()),
+            BuilderState::Nulls | BuilderState::Cells(_) => {
+                unreachable!("only typed builders produce arrays")
+            }
+        }
+    }
+}
+
+/// Microseconds since the epoch, when that loses no precision.
+fn exact_micros(dt: &NaiveDateTime) -> Option<i64> {
+    let micros = dt.and_utc().timestamp_micros();
+    (DateTime::from_timestamp_micros(micros).map(|d| d.naive_utc()) == Some(*dt)).then_some(micros)
+}
+
+/// Convert an Arrow array element at a given index to a CellValue
+pub(crate) fn array_cell(array: &ArrayRef, idx: usize) -> CellValue {
+    if array.is_null(idx) {
+        return CellValue::Null;
+    }
+
+    match array.data_type() {
+        DataType::Boolean => {
+            let arr = array.as_any().downcast_ref::<BooleanArray>().unwrap();
+            CellValue::Bool(arr.value(idx))
+        }
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+            // Try to downcast to Int64Array first, then others
+            if let Some(arr) = array.as_any().downcast_ref::<Int64Array>() {
+                CellValue::Int(arr.value(idx))
+            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::Int32Array>() {
+                CellValue::Int(i64::from(arr.value(idx)))
+            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::Int16Array>() {
+                CellValue::Int(i64::from(arr.value(idx)))
+            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::Int8Array>() {
+                CellValue::Int(i64::from(arr.value(idx)))
+            } else {
+                CellValue::String(format!("<int:{}>", array.data_type()))
+            }
+        }
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+            if let Some(arr) = array.as_any().downcast_ref::<arrow::array::UInt64Array>() {
+                let val = arr.value(idx);
+                // Check for overflow: u64 values > i64::MAX cannot be represented as i64
+                if let Ok(i) = i64::try_from(val) {
+                    CellValue::Int(i)
+                } else {
+                    // Preserve large unsigned values as strings to avoid data corruption
+                    CellValue::String(val.to_string())
+                }
+            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::UInt32Array>() {
+                CellValue::Int(i64::from(arr.value(idx)))
+            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::UInt16Array>() {
+                CellValue::Int(i64::from(arr.value(idx)))
+            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::UInt8Array>() {
+                CellValue::Int(i64::from(arr.value(idx)))
+            } else {
+                CellValue::String(format!("<uint:{}>", array.data_type()))
+            }
+        }
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => {
+            if let Some(arr) = array.as_any().downcast_ref::<Float64Array>() {
+                CellValue::Float(arr.value(idx))
+            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::Float32Array>() {
+                CellValue::Float(f64::from(arr.value(idx)))
+            } else {
+                CellValue::String(format!("<float:{}>", array.data_type()))
+            }
+        }
+        DataType::Utf8 | DataType::LargeUtf8 => {
+            if let Some(arr) = array.as_any().downcast_ref::<StringArray>() {
+                CellValue::String(arr.value(idx).to_string())
+            } else if let Some(arr) = array
+                .as_any()
+                .downcast_ref::<arrow::array::LargeStringArray>()
+            {
+                CellValue::String(arr.value(idx).to_string())
+            } else {
+                CellValue::Null
+            }
+        }
+        DataType::Date32 | DataType::Date64 | DataType::Timestamp(_, _) | DataType::Duration(_) => {
+            temporal_to_cell(array, idx)
+                .unwrap_or_else(|| CellValue::String(format!("<{}>", array.data_type())))
+        }
+        // For other types, convert to string representation
+        _ => {
+            let formatted = arrow::util::display::array_value_to_string(array, idx);
+            match formatted {
+                Ok(s) => CellValue::String(s),
+                Err(_) => CellValue::String(format!("<{}>", array.data_type())),
+            }
+        }
+    }
+}
+
+/// Read a date, timestamp or duration element. Timestamps with a time zone
+/// become their UTC wall-clock time.
+fn temporal_to_cell(array: &ArrayRef, idx: usize) -> Option<CellValue> {
+    match array.data_type() {
+        DataType::Date32 => array
+            .as_primitive::<Date32Type>()
+            .value_as_date(idx)
+            .map(CellValue::Date),
+        DataType::Date64 => array
+            .as_primitive::<Date64Type>()
+            .value_as_date(idx)
+            .map(CellValue::Date),
+        DataType::Timestamp(unit, _) => match unit {
+            TimeUnit::Second => array
+                .as_primitive::<TimestampSecondType>()
+                .value_as_datetime(idx),
+            TimeUnit::Millisecond => array
+                .as_primitive::<TimestampMillisecondType>()
+                .value_as_datetime(idx),
+            TimeUnit::Microsecond => array
+                .as_primitive::<TimestampMicrosecondType>()
+                .value_as_datetime(idx),
+            TimeUnit::Nanosecond => array
+                .as_primitive::<TimestampNanosecondType>()
+                .value_as_datetime(idx),
+        }
+        .map(CellValue::DateTime),
+        DataType::Duration(unit) => match unit {
+            TimeUnit::Second => array
+                .as_primitive::<DurationSecondType>()
+                .value_as_duration(idx),
+            TimeUnit::Millisecond => array
+                .as_primitive::<DurationMillisecondType>()
+                .value_as_duration(idx),
+            TimeUnit::Microsecond => array
+                .as_primitive::<DurationMicrosecondType>()
+                .value_as_duration(idx),
+            TimeUnit::Nanosecond => array
+                .as_primitive::<DurationNanosecondType>()
+                .value_as_duration(idx),
+        }
+        .map(CellValue::Duration),
+        _ => None,
+    }
+}
+
+/// Infer the Arrow DataType for a column based on cell values
+pub(crate) fn infer_column_type(rows: &[&Vec<CellValue>], col_idx: usize) -> DataType {
+    infer_cells_type(rows.iter().filter_map(|row| row.get(col_idx)))
+}
+
+/// Infer the Arrow DataType that holds all of `cells`
+pub(crate) fn infer_cells_type<'a>(cells: impl IntoIterator<Item = &'a CellValue>) -> DataType {
+    let mut has_bool = false;
+    let mut has_int = false;
+    let mut has_float = false;
+    let mut has_string = false;
+    let mut has_date = false;
+    let mut has_datetime = false;
+    let mut has_duration = false;
+
+    for cell in cells {
+        match cell.cached_or_self() {
+            CellValue::Null => {}
+            CellValue::Bool(_) => has_bool = true,
+            CellValue::Int(_) => has_int = true,
+            CellValue::Float(_) => has_float = true,
+            CellValue::String(_) => has_string = true,
+            CellValue::Formula(_) => has_string = true,
+            CellValue::Date(_) => has_date = true,
+            CellValue::DateTime(_) => has_datetime = true,
+            CellValue::Duration(_) => has_duration = true,
+        }
+    }
+
+    // Temporal columns keep their type only when nothing else is mixed in;
+    // dates widen to timestamps
+    let has_number = has_bool || has_int || has_float;
+    let has_instant = has_date || has_datetime;
+    if has_instant || has_duration {
+        return if has_string || has_number || (has_instant && has_duration) {
+            DataType::Utf8
+        } else if has_duration {
+            DataType::Duration(TimeUnit::Microsecond)
+        } else if has_datetime {
+            DataType::Timestamp(TimeUnit::Microsecond, None)
+        } else {
+            DataType::Date32
+        };
+    }
+
+    // Priority: String > Float > Int > Bool (wider types win)
+    if has_string {
+        DataType::Utf8
+    } else if has_float {
+        DataType::Float64
+    } else if has_int {
+        DataType::Int64
+    } else if has_bool {
+        DataType::Boolean
+    } else {
+        DataType::Utf8 // Default to string for empty/null-only columns
+    }
+}
+
+/// Build an Arrow array from column data
+pub(crate) fn build_arrow_array(
+    rows: &[&Vec<CellValue>],
+    col_idx: usize,
+    dtype: &DataType,
+) -> ArrayRef {
+    build_cells_array(rows.iter().map(|row| row.get(col_idx)), dtype)
+}
+
+/// Build an Arrow array of `dtype` from cells; `None` and cells that do not
+/// convert become nulls
+pub(crate) fn build_cells_array<'a>(
+    cells: impl Iterator<Item = Option<&'a CellValue>>,
+    dtype: &DataType,
+) -> ArrayRef {
+    match dtype {
+        DataType::Boolean => Arc::new(
+            cells
+                .map(|cell| cell.and_then(CellValue::as_bool))
+                .collect::<BooleanArray>(),
+        ),
+        DataType::Int64 => Arc::new(
+            cells
+                .map(|cell| cell.and_then(CellValue::as_int))
+                .collect::<Int64Array>(),
+        ),
+        DataType::Float64 => Arc::new(
+            cells
+                .map(|cell| cell.and_then(CellValue::as_float))
+                .collect::<Float64Array>(),
+        ),
+        DataType::Date32 => {
+            let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid epoch");
+            Arc::new(
+                cells
+                    .map(|cell| {
+                        cell.and_then(CellValue::as_date)
+                            .and_then(|date| i32::try_from((date - epoch).num_days()).ok())
+                    })
+                    .collect::<Date32Array>(),
+            )
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, None) => Arc::new(
+            cells
+                .map(|cell| {
+                    cell.and_then(CellValue::as_datetime)
+                        .map(|dt| dt.and_utc().timestamp_micros())
+                })
+                .collect::<TimestampMicrosecondArray>(),
+        ),
+        DataType::Duration(TimeUnit::Microsecond) => Arc::new(
+            cells
+                .map(|cell| {
+                    cell.and_then(CellValue::as_duration)
+                        .and_then(|d| d.num_microseconds())
+                })
+                .collect::<DurationMicrosecondArray>(),
+        ),
+        _ => {
+            // Default to string for Utf8 and any other types
+            Arc::new(
+                cells
+                    .map(|cell| cell.filter(|cell| !cell.is_null()).map(CellValue::as_str))
+                    .collect::<StringArray>(),
+            )
+        }
+    }
+}
+
+impl Sheet {
+    /// Build a sheet from Arrow record batches, keeping the arrays as its
+    /// columns. The field names become a header row that names the columns.
+    ///
+    /// # Example
+    /// ```
+    /// use arrow::array::{Int64Array, RecordBatch, StringArray};
+    /// use arrow::datatypes::{DataType, Field, Schema};
+    /// use piptable_sheet::{CellValue, Sheet};
+    /// use std::sync::Arc;
+    ///
+    /// let schema = Arc::new(Schema::new(vec![
+    ///     Field::new("name", DataType::Utf8, false),
+    ///     Field::new("age", DataType::Int64, true),
+    /// ]));
+    /// let batch = RecordBatch::try_new(
+    ///     schema,
+    ///     vec![
+    ///         Arc::new(StringArray::from(vec!["Alice", "Bob"])),
+    ///         Arc::new(Int64Array::from(vec![Some(30), None])),
+    ///     ],
+    /// )
+    /// .unwrap();
+    ///
+    /// let sheet = Sheet::from_record_batches(&[batch]).unwrap();
+    /// assert!(sheet.is_columnar());
+    /// assert_eq!(sheet.get_by_name(1, "age").unwrap(), &CellValue::Int(30));
+    /// ```
+    pub fn from_record_batches(batches: &[RecordBatch]) -> Result<Sheet> {
+        let Some(first) = batches.first() else {
+            return Ok(Sheet::new());
+        };
+        let schema = first.schema();
+        let header: Vec<CellValue> = schema
+            .fields()
+            .iter()
+            .map(|f| CellValue::String(f.name().clone()))
+            .collect();
+        let columns = (0..schema.fields().len())
+            .map(|idx| {
+                let arrays: Vec<ArrayRef> = batches
+                    .iter()
+                    .map(|batch| batch.column(idx).clone())
+                    .collect();
+                Column::concat(&arrays)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let len = batches.iter().map(RecordBatch::num_rows).sum();
+
+        let mut sheet = Sheet::with_name("Sheet1");
+        sheet.set_storage(Storage::from_columns(Columns::new(
+            Some(header),
+            columns,
+            len,
+        )));
+        sheet.name_columns_by_row(0)?;
+        Ok(sheet)
+    }
+
+    /// Export the data rows as one Arrow record batch.
+    ///
+    /// Requires named columns. Column types come from the sheet's schema
+    /// when it has one; otherwise Arrow columns are shared as they are and
+    /// edited columns are inferred from their cells.
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        let sheet = self.coerced_to_schema()?;
+        let names = sheet.column_names().ok_or_else(|| {
+            SheetError::ColumnsNotNamed("Columns must be named to export as Arrow".to_string())
+        })?;
+        let start = sheet.data_start_row();
+
+        let stored = match sheet.storage() {
+            Storage::Columns(columns)
+                if columns.header_rows() == start && columns.columns().len() == names.len() =>
+            {
+                Some(columns)
+            }
+            _ => None,
+        };
+        let rows: Vec<&Vec<CellValue>> = match stored {
+            Some(_) => Vec::new(),
+            None => sheet.data().iter().skip(start).collect(),
+        };
+
+        let mut fields = Vec::with_capacity(names.len());
+        let mut arrays = Vec::with_capacity(names.len());
+        for (idx, name) in names.iter().enumerate() {
+            let declared = sheet.schema_data_type(name);
+            let array = match stored.map(|columns| &columns.columns()[idx]) {
+                Some(column) => match (column.array(), &declared) {
+                    (Some(array), None) => array.clone(),
+                    (Some(array), Some(dtype)) if array.data_type() == dtype => array.clone(),
+                    _ => {
+                        let cells = column.cells();
+                        let dtype = declared.unwrap_or_else(|| infer_cells_type(cells.iter()));
+                        build_cells_array(cells.iter().map(Some), &dtype)
+                    }
+                },
+                None => {
+                    let dtype = declared.unwrap_or_else(|| infer_column_type(&rows, idx));
+                    build_arrow_array(&rows, idx, &dtype)
+                }
+            };
+            fields.push(Field::new(
+                name,
+                array.data_type().clone(),
+                sheet.schema_nullable(name),
+            ));
+            arrays.push(array);
+        }
+
+        let options =
+            RecordBatchOptions::new().with_row_count(Some(sheet.row_count().saturating_sub(start)));
+        Ok(RecordBatch::try_new_with_options(
+            Arc::new(Schema::new(fields)),
+            arrays,
+            &options,
+        )?)
+    }
+
+    /// Whether the sheet currently stores its cells by column.
+    ///
+    /// Sheets read from CSV, Parquet or Arrow start out columnar. Editing
+    /// whole rows switches to row storage; [`Sheet::compact`] switches back.
+    pub fn is_columnar(&self) -> bool {
+        matches!(self.storage(), Storage::Columns(_))
+    }
+
+    /// Store the cells as Arrow columns, as file readers do.
+    ///
+    /// Rows must all have the same length; ragged sheets keep row storage.
+    /// A header row is kept apart from the typed columns. Columns mixing
+    /// types, or holding formulas, stay as plain cells. The row view built
+    /// by [`Sheet::data`], [`Sheet::get`] and [`Sheet::row`] is dropped.
+    pub fn compact(&mut self) {
+        let has_header = self.data_start_row() == 1;
+        self.storage_mut().compact(has_header);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn people() -> Sheet {
+        let mut sheet = Sheet::from_data(vec![
+            vec![
+                CellValue::from("name"),
+                CellValue::from("age"),
+                CellValue::from("score"),
+            ],
+            vec!["Alice".into(), CellValue::Int(30), CellValue::Float(1.5)],
+            vec!["Bob".into(), CellValue::Null, CellValue::Int(2)],
+            vec!["Carol".into(), CellValue::Int(41), CellValue::Float(3.0)],
+        ]);
+        sheet.name_columns_by_row(0).unwrap();
+        sheet
+    }
+
+    #[test]
+    fn test_compact_keeps_rows_and_types() {
+        let rows = people().data().clone();
+        let mut sheet = people();
+        sheet.compact();
+        assert!(sheet.is_columnar());
+        assert_eq!(sheet.data(), &rows);
+
+        let Storage::Columns(columns) = sheet.storage() else {
+            panic!("expected column storage");
+        };
+        assert_eq!(columns.header.as_ref(), Some(&rows[0]));
+        assert_eq!(
+            columns.columns()[1].array().map(|a| a.data_type().clone()),
+            Some(DataType::Int64)
+        );
+        // Mixed ints and floats keep their exact cells
+        assert!(columns.columns()[2].array().is_none());
+    }
+
+    #[test]
+    fn test_edits_overlay_one_column() {
+        let mut sheet = people();
+        sheet.compact();
+        sheet.set(2, 1, 35).unwrap();
+        assert!(sheet.is_columnar());
+        assert_eq!(sheet.get(2, 1).unwrap(), &CellValue::Int(35));
+        assert_eq!(sheet.row(2).unwrap()[1], CellValue::Int(35));
+
+        let Storage::Columns(columns) = sheet.storage() else {
+            panic!("expected column storage");
+        };
+        assert!(columns.columns()[0].array().is_some());
+        assert!(columns.columns()[1].array().is_none());
+
+        sheet.row_append(vec!["Dan", "50", "1"]).unwrap();
+        assert!(!sheet.is_columnar());
+        assert_eq!(sheet.row_count(), 5);
+    }
+
+    fn has_row_view(sheet: &Sheet) -> bool {
+        match sheet.storage() {
+            Storage::Columns(columns) => columns.rows.get().is_some(),
+            Storage::Rows(_) => panic!("expected column storage"),
+        }
+    }
+
+    #[test]
+    fn test_row_view_is_built_once() {
+        let mut sheet = people();
+        sheet.compact();
+        assert_eq!(sheet.row(0).unwrap()[0], CellValue::from("name"));
+        assert_eq!(sheet.data_start_row(), 1);
+        assert!(!has_row_view(&sheet));
+
+        // Every later read borrows from the same view
+        assert!(std::ptr::eq(sheet.data(), sheet.data()));
+        assert_eq!(sheet.row(3).unwrap().as_ptr(), sheet.data()[3].as_ptr());
+        assert!(has_row_view(&sheet));
+
+        sheet.set(3, 1, 42).unwrap();
+        assert!(!has_row_view(&sheet));
+        assert_eq!(sheet.data()[3][1], CellValue::Int(42));
+
+        sheet.compact();
+        assert!(!has_row_view(&sheet));
+    }
+
+    #[test]
+    fn test_builder_falls_back_to_cells() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let nanos = date.and_hms_nano_opt(1, 2, 3, 4).unwrap();
+        let cases = [
+            (vec![CellValue::Null, CellValue::Date(date)], true),
+            (vec![CellValue::DateTime(nanos)], false),
+            (vec![CellValue::Int(1), CellValue::from("x")], false),
+            (vec![CellValue::formula("=A1")], false),
+            (vec![CellValue::Null, CellValue::Null], true),
+        ];
+        for (cells, typed) in cases {
+            let column = Column::from_cells(cells.clone());
+            assert_eq!(column.array().is_some(), typed, "{cells:?}");
+            assert_eq!(column.cells(), cells.as_slice());
+        }
+    }
+
+    #[test]
+    fn test_filter_sort_and_join_stay_columnar() {
+        let mut sheet = people();
+        sheet.compact();
+        sheet.filter_rows(|idx, row| idx == 0 || row[1].as_int().is_some());
+        assert!(sheet.is_columnar());
+        assert_eq!(sheet.column_by_name("name").unwrap().len(), 3);
+
+        sheet.reorder_rows(&[1, 0]).unwrap();
+        assert_eq!(sheet.get(1, 0).unwrap().as_str(), "Carol");
+
+        let mut cities = Sheet::from_data(vec![
+            vec!["name", "city"],
+            vec!["Alice", "Paris"],
+            vec!["Eve", "Rome"],
+        ]);
+        cities.name_columns_by_row(0).unwrap();
+        cities.compact();
+
+        let joined = sheet.full_join(&cities, "name").unwrap();
+        assert!(joined.is_columnar());
+        assert_eq!(
+            joined.data(),
+            &vec![
+                vec![
+                    CellValue::from("name"),
+                    "age".into(),
+                    "score".into(),
+                    "city".into()
+                ],
+                vec![
+                    "Carol".into(),
+                    CellValue::Int(41),
+                    3.0.into(),
+                    CellValue::Null
+                ],
+                vec![
+                    "Alice".into(),
+                    CellValue::Int(30),
+                    1.5.into(),
+                    "Paris".into()
+                ],
+                vec![
+                    "Eve".into(),
+                    CellValue::Null,
+                    CellValue::Null,
+                    "Rome".into()
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_batch_round_trip() {
+        let mut sheet = people();
+        sheet.compact();
+        sheet.set(1, 0, "Alicia").unwrap();
+        let batch = sheet.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 3);
+        assert_eq!(batch.schema().field(1).data_type(), &DataType::Int64);
+        assert_eq!(batch.schema().field(2).data_type(), &DataType::Float64);
+
+        let back = Sheet::from_record_batches(&[batch]).unwrap();
+        assert_eq!(back.get_by_name(1, "name").unwrap().as_str(), "Alicia");
+        assert_eq!(
+            back.get_by_name(2, "score").unwrap(),
+            &CellValue::Float(2.0)
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use crate::book::Book;
 use crate::cell::CellValue;
-use crate::error::Result;
+use crate::columnar::{ColumnBuilder, Columns, Storage};
+use crate::error::{Result, SheetError};
+use crate::schema::SheetSchema;
 use crate::sheet::Sheet;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Read, Write};
@@ -17,6 +19,12 @@ pub struct CsvOptions {
     pub quote: u8,
     /// Whether to use type inference when reading
     pub infer_types: bool,
+    /// Column types to read with instead of inference (requires headers)
+    pub schema: Option<SheetSchema>,
+    /// chrono formats tried for dates when inferring types, e.g. `"%d/%m/%Y"`
+    pub date_formats: Vec<String>,
+    /// chrono formats tried for date-times before `date_formats`
+    pub datetime_formats: Vec<String>,
 }
 
 impl Default for CsvOptions {
@@ -26,6 +34,9 @@ impl Default for CsvOptions {
             has_headers: false,
             quote: b'"',
             infer_types: true,
+            schema: None,
+            date_formats: Vec::new(),
+            datetime_formats: Vec::new(),
         }
     }
 }
@@ -60,6 +71,151 @@ impl CsvOptions {
         self.infer_types = infer_types;
         self
     }
+
+    /// Parse fields matching one of these chrono formats as dates. Only
+    /// applies with type inference.
+    ///
+    /// ```
+    /// use piptable_sheet::{CellValue, CsvOptions, Sheet};
+    ///
+    /// let options = CsvOptions::default().with_date_formats(["%Y-%m-%d", "%d/%m/%Y"]);
+    /// let sheet = Sheet::from_csv_str_with_options("2024-01-15,15/01/2024", options).unwrap();
+    /// assert!(matches!(sheet.get(0, 1).unwrap(), CellValue::Date(_)));
+    /// ```
+    #[must_use]
+    pub fn with_date_formats<I, S>(mut self, formats: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.date_formats = formats.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Parse fields matching one of these chrono formats as date-times, for
+    /// example `"%Y-%m-%d %H:%M:%S"`. Only applies with type inference.
+    #[must_use]
+    pub fn with_datetime_formats<I, S>(mut self, formats: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.datetime_formats = formats.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Read declared columns with a schema, so "00123" stays text in a
+    /// string column. Reading fails if a value does not fit its column.
+    #[must_use]
+    pub fn with_schema(mut self, schema: SheetSchema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+}
+
+impl CsvOptions {
+    /// A csv reader for these options; header rows are handled by the caller.
+    pub(crate) fn reader<R: Read>(&self, reader: R) -> csv::Reader<R> {
+        csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .has_headers(false)
+            .from_reader(reader)
+    }
+}
+
+/// Turns CSV records into cells and finished sheets, the same way for whole
+/// files and for chunks of a stream.
+pub(crate) struct RecordParser {
+    pub(crate) options: CsvOptions,
+    /// Columns read as raw text for the schema to convert
+    declared: Vec<bool>,
+}
+
+impl RecordParser {
+    pub(crate) fn new(options: CsvOptions) -> Result<Self> {
+        if options.schema.is_some() && !options.has_headers {
+            return Err(SheetError::ColumnsNotNamed(
+                "Reading CSV with a schema requires headers".to_string(),
+            ));
+        }
+        Ok(RecordParser {
+            options,
+            declared: Vec::new(),
+        })
+    }
+
+    /// Look at the first record of the file, which names the columns when
+    /// the file has headers.
+    pub(crate) fn start(&mut self, first: &csv::StringRecord) {
+        if let Some(schema) = &self.options.schema {
+            self.declared = first
+                .iter()
+                .map(|name| schema.column(name).is_some())
+                .collect();
+        }
+    }
+
+    pub(crate) fn cells<'a>(
+        &'a self,
+        record: &'a csv::StringRecord,
+        is_header: bool,
+    ) -> impl Iterator<Item = CellValue> + 'a {
+        let options = &self.options;
+        record.iter().enumerate().map(move |(idx, field)| {
+            if self.declared.get(idx).copied().unwrap_or(false) && !is_header {
+                if field.is_empty() {
+                    CellValue::Null
+                } else {
+                    CellValue::String(field.to_string())
+                }
+            } else if options.infer_types {
+                CellValue::parse_temporal(field, &options.date_formats, &options.datetime_formats)
+                    .unwrap_or_else(|| CellValue::parse(field))
+            } else {
+                CellValue::String(field.to_string())
+            }
+        })
+    }
+
+    /// Build a sheet from the header and columns read so far, naming the
+    /// columns and applying the schema.
+    pub(crate) fn finish(
+        &self,
+        header: Option<Vec<CellValue>>,
+        builders: Vec<ColumnBuilder>,
+        len: usize,
+    ) -> Result<Sheet> {
+        let columns = builders.into_iter().map(ColumnBuilder::finish).collect();
+        let mut sheet = Sheet::with_name("Sheet1");
+        sheet.set_storage(Storage::from_columns(Columns::new(header, columns, len)));
+
+        if self.options.has_headers && sheet.row_count() > 0 {
+            sheet.name_columns_by_row(0)?;
+        }
+
+        if let Some(schema) = &self.options.schema {
+            // Only columns present in the file are applied
+            let present = SheetSchema::new(
+                schema
+                    .columns()
+                    .iter()
+                    .filter(|c| {
+                        sheet
+                            .column_names()
+                            .is_some_and(|names| names.iter().any(|n| n == c.name()))
+                    })
+                    .cloned()
+                    .collect(),
+            );
+            let report = sheet.apply_schema(present)?;
+            if !report.is_clean() {
+                return Err(report.into_error());
+            }
+        }
+
+        Ok(sheet)
+    }
 }
 
 impl Sheet {
@@ -87,37 +243,35 @@ impl Sheet {
 
     /// Load a sheet from a reader
     pub fn from_csv_reader<R: Read>(reader: R, options: CsvOptions) -> Result<Self> {
-        let mut csv_reader = csv::ReaderBuilder::new()
-            .delimiter(options.delimiter)
-            .quote(options.quote)
-            .has_headers(false) // We handle headers ourselves
-            .from_reader(reader);
-
-        let mut data: Vec<Vec<CellValue>> = Vec::new();
-
-        for result in csv_reader.records() {
-            let record = result?;
-            let row: Vec<CellValue> = record
-                .iter()
-                .map(|field| {
-                    if options.infer_types {
-                        CellValue::parse(field)
-                    } else {
-                        CellValue::String(field.to_string())
-                    }
-                })
-                .collect();
-            data.push(row);
-        }
-
-        let mut sheet = Sheet::with_name("Sheet1");
-        *sheet.data_mut() = data;
-
-        if options.has_headers && sheet.row_count() > 0 {
-            sheet.name_columns_by_row(0)?;
+        let mut csv_reader = options.reader(reader);
+        let mut parser = RecordParser::new(options)?;
+
+        // Columns are built as the file is read; the csv reader rejects
+        // rows of different lengths, so they always line up
+        let mut header: Option<Vec<CellValue>> = None;
+        let mut builders: Vec<ColumnBuilder> = Vec::new();
+        let mut len = 0;
+
+        let mut record = csv::StringRecord::new();
+        let mut first = true;
+        while csv_reader.read_record(&mut record)? {
+            if first {
+                parser.start(&record);
+                builders = (0..record.len()).map(|_| ColumnBuilder::new()).collect();
+            }
+            let is_header = first && parser.options.has_headers;
+            first = false;
+            if is_header {
+                header = Some(parser.cells(&record, true).collect());
+            } else {
+                for (builder, cell) in builders.iter_mut().zip(parser.cells(&record, false)) {
+                    builder.push(cell);
+                }
+                len += 1;
+            }
         }
 
-        Ok(sheet)
+        parser.finish(header, builders, len)
     }
 
     /// Save the sheet to a CSV file
@@ -143,10 +297,13 @@ impl Sheet {
             .quote(options.quote)
             .from_writer(writer);
 
-        for row in self.data() {
-            let record: Vec<String> = row.iter().map(CellValue::as_str).collect();
-            csv_writer.write_record(&record)?;
-        }
+        let mut result = Ok(());
+        self.storage().for_each_row(|_, row| {
+            if result.is_ok() {
+                result = csv_writer.write_record(row.iter().map(CellValue::as_str));
+            }
+        });
+        result?;
 
         csv_writer.flush()?;
         Ok(())
@@ -294,6 +451,33 @@ mod tests {
         assert_eq!(sheet.get(1, 4).unwrap(), &CellValue::Null);
     }
 
+    #[test]
+    fn test_date_formats() {
+        let csv = "when,at,plain\n2024-01-15,2024-01-15 08:30:00,2024-01-15";
+        let options = CsvOptions::default()
+            .with_date_formats(["%Y-%m-%d"])
+            .with_datetime_formats(["%Y-%m-%d %H:%M:%S"]);
+        let sheet = Sheet::from_csv_str_with_options(csv, options).unwrap();
+
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        assert_eq!(sheet.get(1, 0).unwrap(), &CellValue::Date(date));
+        assert_eq!(
+            sheet.get(1, 1).unwrap(),
+            &CellValue::DateTime(date.and_hms_opt(8, 30, 0).unwrap())
+        );
+        assert_eq!(
+            sheet.to_csv_string().unwrap(),
+            "when,at,plain\n2024-01-15,2024-01-15T08:30:00,2024-01-15\n"
+        );
+
+        // Without formats dates stay text
+        let sheet = Sheet::from_csv_str(csv).unwrap();
+        assert_eq!(
+            sheet.get(1, 0).unwrap(),
+            &CellValue::String("2024-01-15".to_string())
+        );
+    }
+
     #[test]
     fn test_to_csv_string() {
         let sheet = Sheet::from_data(vec![vec![1, 2, 3], vec![4, 5, 6]]);
@@ -360,4 +544,28 @@ mod tests {
         assert!(book.has_sheet("sheet1"));
         assert!(book.has_sheet("sheet2"));
     }
+
+    #[test]
+    fn test_read_with_schema() {
+        use crate::schema::{ColumnSchema, ColumnType};
+
+        let schema = SheetSchema::default()
+            .with_column(ColumnSchema::new("zip", ColumnType::String))
+            .with_column(ColumnSchema::new("qty", ColumnType::Float));
+        let options = CsvOptions::default()
+            .with_headers(true)
+            .with_schema(schema.clone());
+        let sheet =
+            Sheet::from_csv_str_with_options("zip,qty,n\n00123,4,5\n02134,,6\n", options).unwrap();
+
+        assert_eq!(sheet.get(1, 0).unwrap(), &CellValue::String("00123".into()));
+        assert_eq!(sheet.get(1, 1).unwrap(), &CellValue::Float(4.0));
+        assert!(sheet.get(2, 1).unwrap().is_null());
+        assert_eq!(sheet.get(1, 2).unwrap(), &CellValue::Int(5));
+        assert!(sheet.schema().is_some());
+
+        let options = CsvOptions::default().with_headers(true).with_schema(schema);
+        let err = Sheet::from_csv_str_with_options("zip,qty\n1,lots\n", options).unwrap_err();
+        assert!(err.to_string().contains("'lots' is not float"), "{err}");
+    }
 }
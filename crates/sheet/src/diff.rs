@@ -0,0 +1,665 @@
+//! Keyed comparison of two sheets.
+
+use crate::aggregate::group_key;
+use crate::cell::CellValue;
+use crate::error::{Result, SheetError};
+use crate::sheet::Sheet;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+/// Options for [`Sheet::diff`].
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Largest absolute difference at which two numbers still count as equal.
+    pub tolerance: f64,
+    /// Compare text, key values included, without regard to case.
+    pub ignore_case: bool,
+    /// Columns left out of the comparison.
+    pub ignore_columns: Vec<String>,
+}
+
+impl DiffOptions {
+    /// Create default options: exact numbers, case-sensitive text.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treat numbers within `tolerance` of each other as equal.
+    #[must_use]
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Compare text without regard to case.
+    #[must_use]
+    pub fn with_ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Leave `columns` out of the comparison.
+    #[must_use]
+    pub fn with_ignore_columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.ignore_columns = columns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn equal(&self, a: &CellValue, b: &CellValue) -> bool {
+        let (a, b) = (a.cached_or_self(), b.cached_or_self());
+        match (a, b) {
+            (CellValue::Int(x), CellValue::Int(y)) if x == y => true,
+            (CellValue::Int(_) | CellValue::Float(_), CellValue::Int(_) | CellValue::Float(_)) => {
+                match (a.as_float(), b.as_float()) {
+                    (Some(x), Some(y)) => (x - y).abs() <= self.tolerance,
+                    _ => false,
+                }
+            }
+            (CellValue::String(x), CellValue::String(y)) if self.ignore_case => {
+                x.to_lowercase() == y.to_lowercase()
+            }
+            _ => Sheet::cell_key(a) == Sheet::cell_key(b),
+        }
+    }
+
+    fn key(&self, values: &[CellValue]) -> String {
+        if !self.ignore_case {
+            return group_key(values);
+        }
+        let folded: Vec<CellValue> = values
+            .iter()
+            .map(|v| match v.cached_or_self() {
+                CellValue::String(s) => CellValue::String(s.to_lowercase()),
+                other => other.clone(),
+            })
+            .collect();
+        group_key(&folded)
+    }
+}
+
+/// A value that differs between the two sheets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellChange {
+    /// Column name.
+    pub column: String,
+    /// Value in the old sheet.
+    pub old: CellValue,
+    /// Value in the new sheet.
+    pub new: CellValue,
+}
+
+/// A row present in both sheets with at least one differing value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowChange {
+    /// Key values, as found in the old sheet.
+    pub key: Vec<CellValue>,
+    /// Differing values in column order.
+    pub changes: Vec<CellChange>,
+}
+
+/// Result of [`Sheet::diff`].
+#[derive(Debug, Clone)]
+pub struct SheetDiff {
+    /// Key columns the rows were matched on.
+    pub key_columns: Vec<String>,
+    /// Rows only in the new sheet, with its column names.
+    pub added: Sheet,
+    /// Rows only in the old sheet, with its column names.
+    pub removed: Sheet,
+    /// Rows in both sheets whose values differ, in old sheet order.
+    pub changed: Vec<RowChange>,
+    /// Columns only in the new sheet.
+    pub added_columns: Vec<String>,
+    /// Columns only in the old sheet.
+    pub removed_columns: Vec<String>,
+}
+
+impl SheetDiff {
+    /// True when no rows or columns were added, removed or changed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.row_count() <= self.added.data_start_row()
+            && self.removed.row_count() <= self.removed.data_start_row()
+            && self.changed.is_empty()
+            && self.added_columns.is_empty()
+            && self.removed_columns.is_empty()
+    }
+
+    fn body(sheet: &Sheet) -> &[Vec<CellValue>] {
+        &sheet.data()[sheet.data_start_row()..]
+    }
+
+    /// One row per differing value, with `change`, the key columns,
+    /// `column`, `old` and `new` columns. `change` is `added`, `removed` or
+    /// `changed`; added and removed rows list each of their non-key values.
+    ///
+    /// Fails with [`SheetError::DuplicateColumnName`] when a key column is
+    /// itself named `change`, `column`, `old` or `new`.
+    pub fn to_sheet(&self) -> Result<Sheet> {
+        let mut header = vec![CellValue::from("change")];
+        header.extend(self.key_columns.iter().map(|c| c.as_str().into()));
+        header.extend(["column", "old", "new"].map(CellValue::from));
+        let mut data = vec![header];
+
+        let mut push = |change: &str, key: &[CellValue], column: &str, old, new| {
+            let mut row = vec![CellValue::from(change)];
+            row.extend_from_slice(key);
+            row.extend([CellValue::from(column), old, new]);
+            data.push(row);
+        };
+        for row in &self.changed {
+            for change in &row.changes {
+                push(
+                    "changed",
+                    &row.key,
+                    &change.column,
+                    change.old.clone(),
+                    change.new.clone(),
+                );
+            }
+        }
+        for (change, sheet, old) in [
+            ("added", &self.added, false),
+            ("removed", &self.removed, true),
+        ] {
+            let names = sheet.column_names().cloned().unwrap_or_default();
+            let key_idx: Vec<usize> = self
+                .key_columns
+                .iter()
+                .filter_map(|k| names.iter().position(|n| n == k))
+                .collect();
+            for row in Self::body(sheet) {
+                let cell = |i: usize| row.get(i).cloned().unwrap_or(CellValue::Null);
+                let key: Vec<CellValue> = key_idx.iter().map(|&i| cell(i)).collect();
+                for (i, name) in names.iter().enumerate() {
+                    if key_idx.contains(&i) {
+                        continue;
+                    }
+                    let (before, after) = if old {
+                        (cell(i), CellValue::Null)
+                    } else {
+                        (CellValue::Null, cell(i))
+                    };
+                    push(change, &key, name, before, after);
+                }
+            }
+        }
+
+        let mut sheet = Sheet::from_data(data);
+        sheet.set_name("diff");
+        sheet.name_columns_by_row(0)?;
+        Ok(sheet)
+    }
+
+    /// A standalone HTML report with a summary line and tables of changed,
+    /// added and removed rows.
+    #[must_use]
+    pub fn to_html(&self) -> String {
+        let added = Self::body(&self.added).len();
+        let removed = Self::body(&self.removed).len();
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Sheet diff</title>\n\
+             <style>\nbody { font-family: sans-serif; }\n\
+             table { border-collapse: collapse; margin-bottom: 1.5em; }\n\
+             th, td { border: 1px solid #ccc; padding: 2px 8px; text-align: left; }\n\
+             .old { background: #fdd; }\n.new { background: #dfd; }\n</style>\n</head>\n<body>\n\
+             <h1>Sheet diff</h1>\n",
+        );
+        let _ = writeln!(
+            html,
+            "<p>{} changed, {} added, {} removed</p>",
+            self.changed.len(),
+            added,
+            removed
+        );
+        if !self.added_columns.is_empty() {
+            let _ = writeln!(
+                html,
+                "<p>New columns: {}</p>",
+                escape_html(&self.added_columns.join(", "))
+            );
+        }
+        if !self.removed_columns.is_empty() {
+            let _ = writeln!(
+                html,
+                "<p>Dropped columns: {}</p>",
+                escape_html(&self.removed_columns.join(", "))
+            );
+        }
+
+        if !self.changed.is_empty() {
+            html.push_str("<h2>Changed</h2>\n<table>\n<tr>");
+            for name in self
+                .key_columns
+                .iter()
+                .map(String::as_str)
+                .chain(["column", "old", "new"])
+            {
+                let _ = write!(html, "<th>{}</th>", escape_html(name));
+            }
+            html.push_str("</tr>\n");
+            for row in &self.changed {
+                for change in &row.changes {
+                    html.push_str("<tr>");
+                    for value in &row.key {
+                        let _ = write!(html, "<td>{}</td>", escape_html(&value.as_str()));
+                    }
+                    let _ = writeln!(
+                        html,
+                        "<td>{}</td><td class=\"old\">{}</td><td class=\"new\">{}</td></tr>",
+                        escape_html(&change.column),
+                        escape_html(&change.old.as_str()),
+                        escape_html(&change.new.as_str())
+                    );
+                }
+            }
+            html.push_str("</table>\n");
+        }
+        for (title, class, sheet) in [
+            ("Added", "new", &self.added),
+            ("Removed", "old", &self.removed),
+        ] {
+            if Self::body(sheet).is_empty() {
+                continue;
+            }
+            let _ = write!(html, "<h2>{title}</h2>\n<table>\n<tr>");
+            for name in sheet.column_names().into_iter().flatten() {
+                let _ = write!(html, "<th>{}</th>", escape_html(name));
+            }
+            html.push_str("</tr>\n");
+            for row in Self::body(sheet) {
+                let _ = write!(html, "<tr class=\"{class}\">");
+                for value in row {
+                    let _ = write!(html, "<td>{}</td>", escape_html(&value.as_str()));
+                }
+                html.push_str("</tr>\n");
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+impl Sheet {
+    /// Compare this sheet (the old one) with `other` (the new one), matching
+    /// rows on `key_cols`. Values are compared by column name, so column
+    /// order does not matter; columns in only one sheet are listed but not
+    /// compared. Ints and floats compare numerically.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{CellValue, DiffOptions, Sheet};
+    ///
+    /// let mut old = Sheet::from_data(vec![
+    ///     vec![CellValue::from("id"), "price".into()],
+    ///     vec![CellValue::Int(1), CellValue::Float(9.99)],
+    ///     vec![CellValue::Int(2), CellValue::Float(5.0)],
+    /// ]);
+    /// old.name_columns_by_row(0).unwrap();
+    /// let mut new = Sheet::from_data(vec![
+    ///     vec![CellValue::from("id"), "price".into()],
+    ///     vec![CellValue::Int(1), CellValue::Float(9.991)],
+    ///     vec![CellValue::Int(3), CellValue::Float(1.0)],
+    /// ]);
+    /// new.name_columns_by_row(0).unwrap();
+    ///
+    /// let diff = old
+    ///     .diff(&new, &["id"], &DiffOptions::new().with_tolerance(0.01))
+    ///     .unwrap();
+    /// assert!(diff.changed.is_empty());
+    /// assert_eq!(diff.added.row_count(), 2); // header and id 3
+    /// assert_eq!(diff.removed.get(1, 0).unwrap(), &CellValue::Int(2));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if columns are not named, a key column is missing
+    /// from either sheet, or a key appears twice in the same sheet.
+    pub fn diff(
+        &self,
+        other: &Sheet,
+        key_cols: &[&str],
+        options: &DiffOptions,
+    ) -> Result<SheetDiff> {
+        if key_cols.is_empty() {
+            return Err(SheetError::Diff(
+                "At least one key column is needed".to_string(),
+            ));
+        }
+        let key_index = |sheet: &Sheet, label: &str| {
+            key_cols
+                .iter()
+                .map(|key| {
+                    sheet
+                        .column_index_by_name(key)
+                        .map_err(|_| SheetError::JoinKeyNotFound {
+                            key: (*key).to_string(),
+                            sheet: label.to_string(),
+                        })
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+        let old_keys = key_index(self, "old sheet")?;
+        let new_keys = key_index(other, "new sheet")?;
+        let old_names = self.column_names().cloned().unwrap_or_default();
+        let new_names = other.column_names().cloned().unwrap_or_default();
+
+        let skipped: HashSet<&str> = key_cols
+            .iter()
+            .copied()
+            .chain(options.ignore_columns.iter().map(String::as_str))
+            .collect();
+        let compared: Vec<(usize, usize)> = old_names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| !skipped.contains(name.as_str()))
+            .filter_map(|(i, name)| new_names.iter().position(|n| n == name).map(|j| (i, j)))
+            .collect();
+        let only_in = |names: &[String], others: &[String]| -> Vec<String> {
+            names
+                .iter()
+                .filter(|n| !skipped.contains(n.as_str()) && !others.contains(n))
+                .cloned()
+                .collect()
+        };
+
+        let cell = |row: &[CellValue], i: usize| row.get(i).cloned().unwrap_or(CellValue::Null);
+        let index_rows = |sheet: &Sheet, keys: &[usize], label: &str| {
+            let start = sheet.data_start_row();
+            let mut lookup: HashMap<String, usize> = HashMap::new();
+            for (offset, row) in sheet.data()[start..].iter().enumerate() {
+                let values: Vec<CellValue> = keys.iter().map(|&i| cell(row, i)).collect();
+                if lookup
+                    .insert(options.key(&values), start + offset)
+                    .is_some()
+                {
+                    let shown: Vec<String> = values.iter().map(CellValue::as_str).collect();
+                    return Err(SheetError::Diff(format!(
+                        "Duplicate key ({}) in row {} of the {label}",
+                        shown.join(", "),
+                        start + offset
+                    )));
+                }
+            }
+            Ok(lookup)
+        };
+        let old_lookup = index_rows(self, &old_keys, "old sheet")?;
+        let new_lookup = index_rows(other, &new_keys, "new sheet")?;
+
+        let subset = |sheet: &Sheet, rows: Vec<usize>| {
+            let mut data = vec![sheet.column_names().map_or_else(Vec::new, |names| {
+                names.iter().map(|n| n.as_str().into()).collect()
+            })];
+            data.extend(rows.into_iter().map(|r| sheet.data()[r].clone()));
+            let mut result = Sheet::from_data(data);
+            result.set_name(sheet.name());
+            let _ = result.name_columns_by_row(0);
+            result
+        };
+
+        let mut changed = Vec::new();
+        let mut removed = Vec::new();
+        for row_idx in self.data_start_row()..self.row_count() {
+            let row = &self.data()[row_idx];
+            let key: Vec<CellValue> = old_keys.iter().map(|&i| cell(row, i)).collect();
+            let Some(&other_idx) = new_lookup.get(&options.key(&key)) else {
+                removed.push(row_idx);
+                continue;
+            };
+            let other_row = &other.data()[other_idx];
+            let changes: Vec<CellChange> = compared
+                .iter()
+                .filter_map(|&(i, j)| {
+                    let (old, new) = (cell(row, i), cell(other_row, j));
+                    (!options.equal(&old, &new)).then(|| CellChange {
+                        column: old_names[i].clone(),
+                        old,
+                        new,
+                    })
+                })
+                .collect();
+            if !changes.is_empty() {
+                changed.push(RowChange { key, changes });
+            }
+        }
+        let added: Vec<usize> = (other.data_start_row()..other.row_count())
+            .filter(|&r| {
+                let key: Vec<CellValue> = new_keys
+                    .iter()
+                    .map(|&i| cell(&other.data()[r], i))
+                    .collect();
+                !old_lookup.contains_key(&options.key(&key))
+            })
+            .collect();
+
+        Ok(SheetDiff {
+            key_columns: key_cols.iter().map(|k| (*k).to_string()).collect(),
+            added: subset(other, added),
+            removed: subset(self, removed),
+            changed,
+            added_columns: only_in(&new_names, &old_names),
+            removed_columns: only_in(&old_names, &new_names),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet(rows: Vec<Vec<CellValue>>) -> Sheet {
+        let mut sheet = Sheet::from_data(rows);
+        sheet.name_columns_by_row(0).unwrap();
+        sheet
+    }
+
+    fn yesterday() -> Sheet {
+        sheet(vec![
+            vec!["id".into(), "name".into(), "amount".into(), "loaded".into()],
+            vec![
+                CellValue::Int(1),
+                "Ann".into(),
+                CellValue::Float(10.0),
+                "mon".into(),
+            ],
+            vec![
+                CellValue::Int(2),
+                "Bo".into(),
+                CellValue::Int(7),
+                "mon".into(),
+            ],
+            vec![
+                CellValue::Int(3),
+                "Cy".into(),
+                CellValue::Int(4),
+                "mon".into(),
+            ],
+        ])
+    }
+
+    fn today() -> Sheet {
+        // Columns in another order, plus a new one
+        sheet(vec![
+            vec![
+                "amount".into(),
+                "id".into(),
+                "name".into(),
+                "loaded".into(),
+                "region".into(),
+            ],
+            vec![
+                CellValue::Int(10),
+                CellValue::Int(1),
+                "ANN".into(),
+                "tue".into(),
+                "E".into(),
+            ],
+            vec![
+                CellValue::Float(7.004),
+                CellValue::Int(2),
+                "Bo".into(),
+                "tue".into(),
+                "W".into(),
+            ],
+            vec![
+                CellValue::Int(5),
+                CellValue::Int(4),
+                "Di".into(),
+                "tue".into(),
+                "E".into(),
+            ],
+        ])
+    }
+
+    #[test]
+    fn test_diff_added_removed_changed() {
+        let diff = yesterday()
+            .diff(&today(), &["id"], &DiffOptions::new())
+            .unwrap();
+
+        assert_eq!(diff.removed.row_count(), 2);
+        assert_eq!(diff.removed.get(1, 1).unwrap().as_str(), "Cy");
+        assert_eq!(diff.added.row_count(), 2);
+        assert_eq!(diff.added.get(1, 2).unwrap().as_str(), "Di");
+        assert_eq!(diff.added_columns, vec!["region".to_string()]);
+        assert!(diff.removed_columns.is_empty());
+
+        // 10.0 and 10 are equal; name case and the load day differ
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.changed[0].key, vec![CellValue::Int(1)]);
+        assert_eq!(
+            diff.changed[0].changes,
+            vec![
+                CellChange {
+                    column: "name".to_string(),
+                    old: "Ann".into(),
+                    new: "ANN".into(),
+                },
+                CellChange {
+                    column: "loaded".to_string(),
+                    old: "mon".into(),
+                    new: "tue".into(),
+                },
+            ]
+        );
+        let columns: Vec<&str> = diff.changed[1]
+            .changes
+            .iter()
+            .map(|c| c.column.as_str())
+            .collect();
+        assert_eq!(columns, vec!["amount", "loaded"]);
+    }
+
+    #[test]
+    fn test_diff_options() {
+        let options = DiffOptions::new()
+            .with_tolerance(0.01)
+            .with_ignore_case(true)
+            .with_ignore_columns(["loaded"]);
+        let diff = yesterday().diff(&today(), &["id"], &options).unwrap();
+        assert!(diff.changed.is_empty());
+        assert!(!diff.is_empty());
+
+        let same = yesterday()
+            .diff(&yesterday(), &["id"], &DiffOptions::new())
+            .unwrap();
+        assert!(same.is_empty());
+
+        // Case-insensitive keys match across sheets
+        let old = sheet(vec![
+            vec!["code".into(), "qty".into()],
+            vec!["ab".into(), CellValue::Int(1)],
+        ]);
+        let new = sheet(vec![
+            vec!["code".into(), "qty".into()],
+            vec!["AB".into(), CellValue::Int(2)],
+        ]);
+        let diff = old
+            .diff(&new, &["code"], &DiffOptions::new().with_ignore_case(true))
+            .unwrap();
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.added.row_count(), 1);
+    }
+
+    #[test]
+    fn test_diff_errors() {
+        let err = yesterday()
+            .diff(&today(), &["missing"], &DiffOptions::new())
+            .unwrap_err();
+        assert!(matches!(err, SheetError::JoinKeyNotFound { .. }));
+
+        let duplicated = sheet(vec![
+            vec!["id".into(), "name".into()],
+            vec![CellValue::Int(1), "a".into()],
+            vec![CellValue::Int(1), "b".into()],
+        ]);
+        let err = duplicated
+            .diff(&yesterday(), &["id"], &DiffOptions::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("Duplicate key (1) in row 2"));
+    }
+
+    #[test]
+    fn test_diff_to_sheet_and_html() {
+        let diff = yesterday()
+            .diff(&today(), &["id"], &DiffOptions::new())
+            .unwrap();
+        let report = diff.to_sheet().unwrap();
+        assert_eq!(
+            report.column_names().unwrap(),
+            &["change", "id", "column", "old", "new"]
+        );
+        // 2 + 2 changed values, 4 values of the added row, 3 of the removed
+        assert_eq!(report.row_count(), 1 + 4 + 4 + 3);
+        assert_eq!(report.get(1, 0).unwrap().as_str(), "changed");
+        assert_eq!(report.get(5, 0).unwrap().as_str(), "added");
+        let removed = report.row(9).unwrap();
+        assert_eq!(removed[0].as_str(), "removed");
+        assert_eq!(removed[1], CellValue::Int(3));
+        assert_eq!(removed[2].as_str(), "name");
+        assert_eq!(removed[3].as_str(), "Cy");
+
+        let html = diff.to_html();
+        assert!(html.contains("<p>2 changed, 1 added, 1 removed</p>"));
+        assert!(html.contains("<td class=\"old\">Ann</td><td class=\"new\">ANN</td>"));
+        assert!(html.contains("<p>New columns: region</p>"));
+        assert_eq!(escape_html("<a & 'b'>"), "&lt;a &amp; &#39;b&#39;&gt;");
+    }
+
+    #[test]
+    fn test_diff_to_sheet_key_named_like_report_column() {
+        let old = sheet(vec![
+            vec!["column".into(), "value".into()],
+            vec!["a".into(), CellValue::Int(1)],
+        ]);
+        let new = sheet(vec![
+            vec!["column".into(), "value".into()],
+            vec!["a".into(), CellValue::Int(2)],
+        ]);
+        let diff = old.diff(&new, &["column"], &DiffOptions::new()).unwrap();
+        let err = diff.to_sheet().unwrap_err();
+        assert!(matches!(err, SheetError::DuplicateColumnName { .. }));
+    }
+}
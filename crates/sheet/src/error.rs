@@ -31,6 +31,18 @@ pub enum SheetError {
     #[error("Sheet already exists: {name}")]
     SheetAlreadyExists { name: String },
 
+    #[error("Name not found: {name}")]
+    NameNotFound { name: String },
+
+    #[error("Invalid name '{name}': {reason}")]
+    InvalidName { name: String, reason: String },
+
+    #[error("Table not found: {name}")]
+    TableNotFound { name: String },
+
+    #[error("Invalid table '{name}': {reason}")]
+    InvalidTable { name: String, reason: String },
+
     #[error("Columns not named: {0}")]
     ColumnsNotNamed(String),
 
@@ -55,6 +67,27 @@ pub enum SheetError {
     #[error("Column mismatch: sheets have different column counts ({left} vs {right})")]
     ColumnCountMismatch { left: usize, right: usize },
 
+    #[error("Aggregation error: {0}")]
+    Aggregation(String),
+
+    #[error("Schema error: {0}")]
+    Schema(String),
+
+    #[error("Sort error: {0}")]
+    Sort(String),
+
+    #[error("Diff error: {0}")]
+    Diff(String),
+
+    #[error("Stream error: {0}")]
+    Stream(String),
+
+    #[error("Fuzzy match error: {0}")]
+    Fuzzy(String),
+
+    #[error("Join error: {0}")]
+    Join(String),
+
     #[error("Key column '{key}' not found in {sheet}")]
     JoinKeyNotFound { key: String, sheet: String },
 
@@ -68,6 +101,10 @@ pub enum SheetError {
     #[error("Parquet error: {0}")]
     Parquet(#[from] parquet::errors::ParquetError),
 
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
     #[error("Arrow error: {0}")]
     Arrow(#[from] arrow::error::ArrowError),
 
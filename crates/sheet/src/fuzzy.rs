@@ -0,0 +1,732 @@
+//! Approximate matching: fuzzy deduplication and fuzzy joins.
+
+use crate::cell::CellValue;
+use crate::error::{Result, SheetError};
+use crate::sheet::Sheet;
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+
+/// String similarity measure, scoring from 0.0 (unrelated) to 1.0 (equal).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Similarity {
+    /// Jaro-Winkler: favours strings sharing a prefix, good for short names.
+    #[default]
+    JaroWinkler,
+    /// Edit distance divided by the longer length, subtracted from one.
+    Levenshtein,
+    /// Compares the sets of words, so word order and repeated or extra words
+    /// matter little: "Acme Corp Inc" and "Inc Acme Corp" score 1.0.
+    TokenSet,
+}
+
+impl Similarity {
+    /// Score two strings as they are; see [`FuzzyOptions`] for normalization.
+    #[must_use]
+    pub fn score(self, a: &str, b: &str) -> f64 {
+        match self {
+            Self::JaroWinkler => strsim::jaro_winkler(a, b),
+            Self::Levenshtein => strsim::normalized_levenshtein(a, b),
+            Self::TokenSet => token_set(a, b),
+        }
+    }
+}
+
+impl FromStr for Similarity {
+    type Err = SheetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().replace('-', "_").as_str() {
+            "jaro_winkler" | "jarowinkler" => Ok(Self::JaroWinkler),
+            "levenshtein" => Ok(Self::Levenshtein),
+            "token_set" => Ok(Self::TokenSet),
+            _ => Err(SheetError::Fuzzy(format!(
+                "Unknown similarity '{s}', expected jaro_winkler, levenshtein or token_set"
+            ))),
+        }
+    }
+}
+
+/// Token-set ratio: the shared words are compared with each side's full
+/// word set, and the best of those comparisons wins.
+fn token_set(a: &str, b: &str) -> f64 {
+    let words = |s: &str| -> BTreeSet<String> {
+        s.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() || b.is_empty() {
+        return if a == b { 1.0 } else { 0.0 };
+    }
+    let join = |words: Vec<&String>| -> String {
+        words
+            .iter()
+            .map(|w| w.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let shared = join(a.intersection(&b).collect());
+    let with_rest = |rest: Vec<&String>| {
+        let rest = join(rest);
+        match (shared.is_empty(), rest.is_empty()) {
+            (true, _) => rest,
+            (false, true) => shared.clone(),
+            (false, false) => format!("{shared} {rest}"),
+        }
+    };
+    let left = with_rest(a.difference(&b).collect());
+    let right = with_rest(b.difference(&a).collect());
+    let ratio = strsim::normalized_levenshtein;
+    if shared.is_empty() {
+        return ratio(&left, &right);
+    }
+    ratio(&shared, &left)
+        .max(ratio(&shared, &right))
+        .max(ratio(&left, &right))
+}
+
+/// Options for [`Sheet::fuzzy_clusters`] and [`Sheet::fuzzy_join`].
+#[derive(Debug, Clone)]
+pub struct FuzzyOptions {
+    /// How key values are compared.
+    pub similarity: Similarity,
+    /// Lowest score, from 0.0 to 1.0, that counts as a match.
+    pub threshold: f64,
+    /// Only rows with equal values in these columns are compared.
+    pub block_on: Vec<String>,
+    /// Only rows whose key values start with the same this-many characters
+    /// are compared; 0 turns prefix blocking off.
+    pub block_prefix: usize,
+    /// Compare text without regard to case.
+    pub ignore_case: bool,
+    /// Name of the score column a fuzzy join adds.
+    pub score_column: String,
+}
+
+impl Default for FuzzyOptions {
+    fn default() -> Self {
+        Self {
+            similarity: Similarity::default(),
+            threshold: 0.85,
+            block_on: Vec::new(),
+            block_prefix: 0,
+            ignore_case: true,
+            score_column: "score".to_string(),
+        }
+    }
+}
+
+impl FuzzyOptions {
+    /// Create default options: Jaro-Winkler, threshold 0.85, no blocking,
+    /// case-insensitive.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare key values with `similarity`.
+    #[must_use]
+    pub fn with_similarity(mut self, similarity: Similarity) -> Self {
+        self.similarity = similarity;
+        self
+    }
+
+    /// Count scores of at least `threshold` as a match.
+    #[must_use]
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Only compare rows that agree exactly on `columns`.
+    #[must_use]
+    pub fn with_block_on<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.block_on = columns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Only compare rows whose key values share their first `chars` characters.
+    #[must_use]
+    pub fn with_block_prefix(mut self, chars: usize) -> Self {
+        self.block_prefix = chars;
+        self
+    }
+
+    /// Compare text without regard to case.
+    #[must_use]
+    pub fn with_ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Name the score column a fuzzy join adds.
+    #[must_use]
+    pub fn with_score_column(mut self, name: &str) -> Self {
+        self.score_column = name.to_string();
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.threshold) {
+            return Err(SheetError::Fuzzy(format!(
+                "Threshold must be between 0 and 1, got {}",
+                self.threshold
+            )));
+        }
+        Ok(())
+    }
+
+    /// Trimmed text with runs of whitespace collapsed, folded to lowercase
+    /// when case is ignored.
+    fn normalize(&self, cell: &CellValue) -> String {
+        let text = cell.as_str();
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if self.ignore_case {
+            text.to_lowercase()
+        } else {
+            text
+        }
+    }
+
+    /// Mean similarity over the key columns. A column empty on both sides
+    /// scores 1.0, empty on one side 0.0.
+    fn score(&self, a: &[String], b: &[String]) -> f64 {
+        let total: f64 = a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| match (a.is_empty(), b.is_empty()) {
+                (true, true) => 1.0,
+                (true, false) | (false, true) => 0.0,
+                (false, false) if a == b => 1.0,
+                (false, false) => self.similarity.score(a, b),
+            })
+            .sum();
+        total / a.len().max(1) as f64
+    }
+}
+
+/// Normalized key and blocking values of every data row.
+struct Keys {
+    rows: Vec<usize>,
+    values: Vec<Vec<String>>,
+    blocks: Vec<String>,
+}
+
+impl Keys {
+    fn read(
+        sheet: &Sheet,
+        key_cols: &[usize],
+        block_cols: &[usize],
+        options: &FuzzyOptions,
+    ) -> Self {
+        let start = sheet.data_start_row();
+        let cell =
+            |row: &[CellValue], i: usize| options.normalize(row.get(i).unwrap_or(&CellValue::Null));
+        let mut keys = Keys {
+            rows: Vec::new(),
+            values: Vec::new(),
+            blocks: Vec::new(),
+        };
+        for (offset, row) in sheet.data()[start..].iter().enumerate() {
+            let key: Vec<String> = key_cols.iter().map(|&i| cell(row, i)).collect();
+            let mut block = String::new();
+            for &i in block_cols {
+                block.push_str(&cell(row, i));
+                block.push('\x1f');
+            }
+            if options.block_prefix > 0 {
+                for value in &key {
+                    block.extend(value.chars().take(options.block_prefix));
+                    block.push('\x1f');
+                }
+            }
+            keys.rows.push(start + offset);
+            keys.values.push(key);
+            keys.blocks.push(block);
+        }
+        keys
+    }
+}
+
+/// Disjoint-set forest over key groups.
+struct UnionFind(Vec<usize>);
+
+impl UnionFind {
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.0[i] != i {
+            self.0[i] = self.0[self.0[i]];
+            i = self.0[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            // Keep the earlier group as the root so clusters list in row order
+            self.0[a.max(b)] = a.min(b);
+        }
+    }
+}
+
+fn column_indices(sheet: &Sheet, columns: &[String], label: &str) -> Result<Vec<usize>> {
+    columns
+        .iter()
+        .map(|name| {
+            sheet
+                .column_index_by_name(name)
+                .map_err(|_| SheetError::JoinKeyNotFound {
+                    key: name.clone(),
+                    sheet: label.to_string(),
+                })
+        })
+        .collect()
+}
+
+impl Sheet {
+    /// Group the data rows whose `key_cols` values are similar, as judged by
+    /// `options`. Every data row is in exactly one cluster; clusters are
+    /// ordered by their first row and list row indices in order.
+    ///
+    /// Rows join a cluster when they score at least the threshold against
+    /// any of its rows, so a chain of close values can link two rows that
+    /// are further apart. Rows with every key value empty stay on their own.
+    /// Rows are compared pairwise within each block; set
+    /// [`FuzzyOptions::block_on`] or [`FuzzyOptions::block_prefix`] to keep
+    /// large sheets fast.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{FuzzyOptions, Sheet};
+    ///
+    /// let mut sheet = Sheet::from_data(vec![
+    ///     vec!["name", "city"],
+    ///     vec!["Acme Corp", "Boston"],
+    ///     vec!["ACME Corp.", "Boston"],
+    ///     vec!["Globex", "Boston"],
+    /// ]);
+    /// sheet.name_columns_by_row(0).unwrap();
+    ///
+    /// let clusters = sheet
+    ///     .fuzzy_clusters(&["name"], &FuzzyOptions::new().with_block_on(["city"]))
+    ///     .unwrap();
+    /// assert_eq!(clusters, vec![vec![1, 2], vec![3]]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if columns are not named, a key or blocking column
+    /// is missing, no key column is given, or the threshold is out of range.
+    pub fn fuzzy_clusters(
+        &self,
+        key_cols: &[&str],
+        options: &FuzzyOptions,
+    ) -> Result<Vec<Vec<usize>>> {
+        options.validate()?;
+        if key_cols.is_empty() {
+            return Err(SheetError::Fuzzy(
+                "At least one key column is needed".to_string(),
+            ));
+        }
+        let key_names: Vec<String> = key_cols.iter().map(|k| (*k).to_string()).collect();
+        let key_idx = column_indices(self, &key_names, "sheet")?;
+        let block_idx = column_indices(self, &options.block_on, "sheet")?;
+        let keys = Keys::read(self, &key_idx, &block_idx, options);
+
+        // Rows with identical keys form one group and are scored once
+        let mut groups: Vec<(usize, Vec<usize>)> = Vec::new();
+        let mut group_of: HashMap<(&str, &[String]), usize> = HashMap::new();
+        for (i, key) in keys.values.iter().enumerate() {
+            if key.iter().all(String::is_empty) {
+                groups.push((i, vec![i]));
+                continue;
+            }
+            let group = *group_of
+                .entry((keys.blocks[i].as_str(), key.as_slice()))
+                .or_insert_with(|| {
+                    groups.push((i, Vec::new()));
+                    groups.len() - 1
+                });
+            groups[group].1.push(i);
+        }
+
+        let mut blocks: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (group, (first, _)) in groups.iter().enumerate() {
+            if !keys.values[*first].iter().all(String::is_empty) {
+                blocks
+                    .entry(keys.blocks[*first].as_str())
+                    .or_default()
+                    .push(group);
+            }
+        }
+        let mut sets = UnionFind((0..groups.len()).collect());
+        for members in blocks.values() {
+            for (n, &a) in members.iter().enumerate() {
+                for &b in &members[n + 1..] {
+                    let score = options.score(&keys.values[groups[a].0], &keys.values[groups[b].0]);
+                    if score >= options.threshold {
+                        sets.union(a, b);
+                    }
+                }
+            }
+        }
+
+        let mut clusters: Vec<Vec<usize>> = Vec::new();
+        let mut cluster_of: HashMap<usize, usize> = HashMap::new();
+        for (group, (_, members)) in groups.iter().enumerate() {
+            let root = sets.find(group);
+            let cluster = *cluster_of.entry(root).or_insert_with(|| {
+                clusters.push(Vec::new());
+                clusters.len() - 1
+            });
+            clusters[cluster].extend(members.iter().map(|&i| keys.rows[i]));
+        }
+        for cluster in &mut clusters {
+            cluster.sort_unstable();
+        }
+        Ok(clusters)
+    }
+
+    /// Remove rows that fuzzily duplicate an earlier row, keeping the first
+    /// row of each cluster found by [`Sheet::fuzzy_clusters`].
+    /// Returns the number of rows removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Sheet::fuzzy_clusters`].
+    pub fn remove_fuzzy_duplicates(
+        &mut self,
+        key_cols: &[&str],
+        options: &FuzzyOptions,
+    ) -> Result<usize> {
+        let clusters = self.fuzzy_clusters(key_cols, options)?;
+        let mut keep = vec![true; self.row_count()];
+        let mut removed = 0;
+        for cluster in &clusters {
+            for &row in &cluster[1..] {
+                keep[row] = false;
+                removed += 1;
+            }
+        }
+        if removed > 0 {
+            self.filter_rows(|i, _| keep[i]);
+        }
+        Ok(removed)
+    }
+
+    /// Left join `other` on similar key values, using the same column name
+    /// in both sheets. See [`Sheet::fuzzy_join_on`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Sheet::fuzzy_join_on`].
+    pub fn fuzzy_join(&self, other: &Sheet, key: &str, options: &FuzzyOptions) -> Result<Sheet> {
+        self.fuzzy_join_on(other, key, key, options)
+    }
+
+    /// Left join `other`, pairing each row with the best scoring row of
+    /// `other` whose key is similar enough; ties go to the earlier row.
+    ///
+    /// The result has every left column, every right column (renamed with a
+    /// `_right` suffix where a name is taken) and a score column named by
+    /// [`FuzzyOptions::score_column`]. Rows without a match keep nulls on
+    /// the right and for the score. Blocking columns must exist under the
+    /// same name in both sheets.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{CellValue, FuzzyOptions, Similarity, Sheet};
+    ///
+    /// let mut orders = Sheet::from_data(vec![vec!["vendor"], vec!["Acme Inc"], vec!["Initech"]]);
+    /// orders.name_columns_by_row(0).unwrap();
+    /// let mut vendors = Sheet::from_data(vec![
+    ///     vec!["vendor", "id"],
+    ///     vec!["Inc Acme", "V1"],
+    ///     vec!["Globex", "V2"],
+    /// ]);
+    /// vendors.name_columns_by_row(0).unwrap();
+    ///
+    /// let options = FuzzyOptions::new().with_similarity(Similarity::TokenSet);
+    /// let joined = orders.fuzzy_join(&vendors, "vendor", &options).unwrap();
+    /// assert_eq!(
+    ///     joined.column_names().unwrap(),
+    ///     &["vendor", "vendor_right", "id", "score"]
+    /// );
+    /// assert_eq!(joined.get_by_name(1, "id").unwrap(), &CellValue::from("V1"));
+    /// assert!(joined.get_by_name(2, "score").unwrap().is_null());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if columns are not named, a key or blocking column
+    /// is missing, the score column name is taken, or the threshold is out
+    /// of range.
+    pub fn fuzzy_join_on(
+        &self,
+        other: &Sheet,
+        left_key: &str,
+        right_key: &str,
+        options: &FuzzyOptions,
+    ) -> Result<Sheet> {
+        options.validate()?;
+        let left_names = self.column_names().cloned().ok_or_else(|| {
+            SheetError::ColumnsNotNamed("Left sheet columns not named".to_string())
+        })?;
+        let right_names = other.column_names().cloned().ok_or_else(|| {
+            SheetError::ColumnsNotNamed("Right sheet columns not named".to_string())
+        })?;
+        let left_keys = Keys::read(
+            self,
+            &column_indices(self, &[left_key.to_string()], "left")?,
+            &column_indices(self, &options.block_on, "left")?,
+            options,
+        );
+        let right_keys = Keys::read(
+            other,
+            &column_indices(other, &[right_key.to_string()], "right")?,
+            &column_indices(other, &options.block_on, "right")?,
+            options,
+        );
+
+        let mut result_names = left_names.clone();
+        for name in &right_names {
+            if result_names.contains(name) {
+                result_names.push(format!("{name}_right"));
+            } else {
+                result_names.push(name.clone());
+            }
+        }
+        if result_names.contains(&options.score_column) {
+            return Err(SheetError::DuplicateColumnName {
+                name: options.score_column.clone(),
+            });
+        }
+        result_names.push(options.score_column.clone());
+
+        let mut blocks: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, block) in right_keys.blocks.iter().enumerate() {
+            if !right_keys.values[i][0].is_empty() {
+                blocks.entry(block.as_str()).or_default().push(i);
+            }
+        }
+        // Left rows repeating a key reuse its match
+        let mut best: HashMap<(&str, &str), Option<(usize, f64)>> = HashMap::new();
+        let mut data = vec![result_names
+            .iter()
+            .map(|n| CellValue::String(n.clone()))
+            .collect::<Vec<_>>()];
+        for (i, key) in left_keys.values.iter().enumerate() {
+            let block = left_keys.blocks[i].as_str();
+            let found = *best.entry((block, key[0].as_str())).or_insert_with(|| {
+                if key[0].is_empty() {
+                    return None;
+                }
+                let mut found: Option<(usize, f64)> = None;
+                for &j in blocks.get(block).map_or(&[][..], Vec::as_slice) {
+                    let score = options.score(key, &right_keys.values[j]);
+                    if score >= options.threshold && found.is_none_or(|(_, s)| score > s) {
+                        found = Some((j, score));
+                        if score >= 1.0 {
+                            break;
+                        }
+                    }
+                }
+                found
+            });
+
+            let mut row = self.data()[left_keys.rows[i]].clone();
+            row.resize(left_names.len(), CellValue::Null);
+            match found {
+                Some((j, score)) => {
+                    let right = &other.data()[right_keys.rows[j]];
+                    row.extend(
+                        (0..right_names.len())
+                            .map(|c| right.get(c).cloned().unwrap_or(CellValue::Null)),
+                    );
+                    row.push(CellValue::Float(score));
+                }
+                None => row.extend(vec![CellValue::Null; right_names.len() + 1]),
+            }
+            data.push(row);
+        }
+
+        let mut result = Sheet::from_data(data);
+        result.set_name(&format!("{}_joined", self.name()));
+        result.name_columns_by_row(0)?;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet(rows: Vec<Vec<&str>>) -> Sheet {
+        let mut sheet = Sheet::from_data(rows);
+        sheet.name_columns_by_row(0).unwrap();
+        sheet
+    }
+
+    #[test]
+    fn test_similarity_scores() {
+        let close = |score: f64, expected: f64| (score - expected).abs() < 1e-9;
+        assert!(close(
+            Similarity::JaroWinkler.score("martha", "martha"),
+            1.0
+        ));
+        assert!(Similarity::JaroWinkler.score("martha", "marhta") > 0.95);
+        assert!(close(
+            Similarity::Levenshtein.score("kitten", "sitting"),
+            1.0 - 3.0 / 7.0
+        ));
+        assert!(close(
+            Similarity::TokenSet.score("acme corp inc", "inc acme corp"),
+            1.0
+        ));
+        assert!(close(
+            Similarity::TokenSet.score("new york", "new york mets"),
+            1.0
+        ));
+        assert!(close(Similarity::TokenSet.score("", "acme"), 0.0));
+        assert_eq!(
+            "token-set".parse::<Similarity>().unwrap(),
+            Similarity::TokenSet
+        );
+        assert!("soundex".parse::<Similarity>().is_err());
+    }
+
+    #[test]
+    fn test_fuzzy_clusters_and_dedupe() {
+        let mut customers = sheet(vec![
+            vec!["name", "zip"],
+            vec!["Jonathan Smith", "02139"],
+            vec!["Globex", "10001"],
+            vec!["jonathan  smith", "02139"],
+            vec!["Jonathon Smith", "02139"],
+            vec!["Jonathan Smith", "94105"],
+            vec!["", "02139"],
+            vec!["", "02139"],
+        ]);
+        let options = FuzzyOptions::new().with_block_on(["zip"]);
+        let clusters = customers.fuzzy_clusters(&["name"], &options).unwrap();
+        assert_eq!(
+            clusters,
+            vec![vec![1, 3, 4], vec![2], vec![5], vec![6], vec![7]]
+        );
+
+        // Without blocking the other zip joins the cluster too
+        let clusters = customers
+            .fuzzy_clusters(&["name"], &FuzzyOptions::new())
+            .unwrap();
+        assert_eq!(clusters[0], vec![1, 3, 4, 5]);
+
+        // Case-sensitive, the lowercase variant lands in another prefix block
+        // and only the one-letter typo scores high enough
+        let strict = FuzzyOptions::new()
+            .with_similarity(Similarity::Levenshtein)
+            .with_threshold(0.95)
+            .with_ignore_case(false)
+            .with_block_prefix(1);
+        let clusters = customers.fuzzy_clusters(&["name", "zip"], &strict).unwrap();
+        assert_eq!(clusters[0], vec![1, 4]);
+
+        let removed = customers
+            .remove_fuzzy_duplicates(&["name"], &options)
+            .unwrap();
+        assert_eq!(removed, 2);
+        assert_eq!(customers.row_count(), 6);
+        assert_eq!(
+            customers.column_by_name("name").unwrap()[1..3],
+            [CellValue::from("Jonathan Smith"), CellValue::from("Globex")]
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_join() {
+        let orders = sheet(vec![
+            vec!["order", "customer", "region"],
+            vec!["1", "Acme Corporation", "east"],
+            vec!["2", "Globex Inc", "west"],
+            vec!["3", "Acme Corporation", "west"],
+            vec!["4", "Initech", "east"],
+        ]);
+        let customers = sheet(vec![
+            vec!["name", "region", "tier"],
+            vec!["ACME Corp", "west", "bronze"],
+            vec!["Acme Corporation Ltd", "east", "gold"],
+            vec!["Globex, Inc.", "west", "silver"],
+        ]);
+        let options = FuzzyOptions::new()
+            .with_threshold(0.8)
+            .with_block_on(["region"])
+            .with_score_column("match");
+        let joined = orders
+            .fuzzy_join_on(&customers, "customer", "name", &options)
+            .unwrap();
+        assert_eq!(
+            joined.column_names().unwrap(),
+            &[
+                "order",
+                "customer",
+                "region",
+                "name",
+                "region_right",
+                "tier",
+                "match"
+            ]
+        );
+        assert_eq!(joined.row_count(), 5);
+        assert_eq!(
+            joined.get_by_name(1, "tier").unwrap(),
+            &CellValue::from("gold")
+        );
+        assert_eq!(
+            joined.get_by_name(2, "tier").unwrap(),
+            &CellValue::from("silver")
+        );
+        assert_eq!(
+            joined.get_by_name(3, "name").unwrap(),
+            &CellValue::from("ACME Corp")
+        );
+        assert_eq!(
+            joined.get_by_name(3, "tier").unwrap(),
+            &CellValue::from("bronze")
+        );
+        assert!(joined.get_by_name(4, "name").unwrap().is_null());
+        assert!(joined.get_by_name(4, "match").unwrap().is_null());
+        let score = joined.get_by_name(1, "match").unwrap().as_float().unwrap();
+        assert!((0.8..1.0).contains(&score));
+    }
+
+    #[test]
+    fn test_fuzzy_errors() {
+        let people = sheet(vec![vec!["name"], vec!["Ann"]]);
+        let err = people
+            .fuzzy_clusters(&["name"], &FuzzyOptions::new().with_threshold(1.5))
+            .unwrap_err();
+        assert!(err.to_string().contains("between 0 and 1"));
+        assert!(people.fuzzy_clusters(&[], &FuzzyOptions::new()).is_err());
+        assert!(people
+            .fuzzy_clusters(&["age"], &FuzzyOptions::new())
+            .is_err());
+        let err = people
+            .fuzzy_join(
+                &people,
+                "name",
+                &FuzzyOptions::new().with_score_column("name"),
+            )
+            .unwrap_err();
+        assert!(matches!(err, SheetError::DuplicateColumnName { .. }));
+        let unnamed = Sheet::from_data(vec![vec!["Ann"]]);
+        assert!(unnamed
+            .fuzzy_join(&people, "name", &FuzzyOptions::new())
+            .is_err());
+    }
+}
@@ -176,33 +176,9 @@ impl Sheet {
 
         for (line_num, line_result) in buf_reader.lines().enumerate() {
             let line = line_result?;
-            let trimmed = line.trim();
-
-            // Skip empty lines
-            if trimmed.is_empty() {
-                continue;
+            if let Some(record) = parse_jsonl_line(&line, line_num, &mut column_names)? {
+                records.push(record);
             }
-
-            let value: Value = serde_json::from_str(trimmed).map_err(|e| {
-                SheetError::Parse(format!("Invalid JSON on line {}: {e}", line_num + 1))
-            })?;
-
-            let obj = value.as_object().ok_or_else(|| {
-                SheetError::Parse(format!("Line {} must be a JSON object", line_num + 1))
-            })?;
-
-            // Get column names from first object
-            if column_names.is_none() {
-                column_names = Some(obj.keys().cloned().collect());
-            }
-
-            let names = column_names.as_ref().unwrap();
-            let mut record = IndexMap::new();
-            for name in names {
-                let value = obj.get(name).unwrap_or(&Value::Null);
-                record.insert(name.clone(), json_value_to_cell(value));
-            }
-            records.push(record);
         }
 
         if records.is_empty() {
@@ -255,6 +231,37 @@ impl Sheet {
     }
 }
 
+/// Parse one JSONL line into a record keyed by the first object's fields.
+/// Blank lines give `None`.
+pub(crate) fn parse_jsonl_line(
+    line: &str,
+    line_num: usize,
+    column_names: &mut Option<Vec<String>>,
+) -> Result<Option<IndexMap<String, CellValue>>> {
+    let trimmed = line.trim();
+
+    // Skip empty lines
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+
+    let value: Value = serde_json::from_str(trimmed)
+        .map_err(|e| SheetError::Parse(format!("Invalid JSON on line {}: {e}", line_num + 1)))?;
+
+    let obj = value
+        .as_object()
+        .ok_or_else(|| SheetError::Parse(format!("Line {} must be a JSON object", line_num + 1)))?;
+
+    // Get column names from first object
+    let names = column_names.get_or_insert_with(|| obj.keys().cloned().collect());
+    let mut record = IndexMap::new();
+    for name in names.iter() {
+        let value = obj.get(name).unwrap_or(&Value::Null);
+        record.insert(name.clone(), json_value_to_cell(value));
+    }
+    Ok(Some(record))
+}
+
 /// Convert a serde_json Value to a CellValue
 fn json_value_to_cell(value: &Value) -> CellValue {
     match value {
@@ -309,6 +316,10 @@ fn cell_to_json_value(cell: &CellValue) -> Value {
             }
             Value::Object(obj)
         }
+        // ISO 8601 text
+        CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_) => {
+            Value::String(cell.as_str())
+        }
     }
 }
 
@@ -91,30 +91,76 @@
 //! recalculation when dependent cells change.
 
 mod a1_notation;
+mod aggregate;
 mod book;
+mod book_formulas;
 mod cell;
+mod columnar;
 mod csv;
+mod diff;
 mod error;
+mod fuzzy;
 #[cfg(not(target_arch = "wasm32"))]
 mod html;
 mod json;
+mod names;
+mod ordered_join;
 #[cfg(not(target_arch = "wasm32"))]
 mod parquet;
+mod pivot;
+mod profile;
+mod schema;
 mod sheet;
+mod sort;
+mod spills;
+#[cfg(not(target_arch = "wasm32"))]
+mod sqlite;
+mod stream;
+mod tables;
 mod toon;
 #[cfg(not(target_arch = "wasm32"))]
 mod xlsx;
 
+/// Re-export aggregation and group-by types.
+pub use aggregate::{Agg, Aggregation, GroupBy};
 /// Re-export book types and options.
 pub use book::{Book, ConsolidateOptions, FileLoadOptions};
 /// Re-export cell value type.
 pub use cell::CellValue;
 /// Re-export CSV options.
 pub use csv::CsvOptions;
+/// Re-export sheet diff types.
+pub use diff::{CellChange, DiffOptions, RowChange, SheetDiff};
 /// Re-export sheet error types.
 pub use error::{Result, SheetError};
+/// Re-export fuzzy matching types.
+pub use fuzzy::{FuzzyOptions, Similarity};
+/// Re-export defined name types.
+pub use names::{DefinedName, DefinedNames};
+/// Re-export as-of join direction.
+pub use ordered_join::AsOfDirection;
+/// Re-export pivot options.
+pub use pivot::PivotOptions;
+/// Re-export column profiling options.
+pub use profile::ProfileOptions;
+/// Re-export column schema types.
+pub use schema::{CoercionFailure, ColumnSchema, ColumnType, SchemaReport, SheetSchema};
 /// Re-export sheet type.
 pub use sheet::{CleanOptions, NullStrategy, Sheet, ValidationRule};
+/// Re-export sort keys and options.
+pub use sort::{Collation, NullOrder, SortKey};
+#[cfg(not(target_arch = "wasm32"))]
+/// Re-export SQLite column types and write options (non-WASM only).
+pub use sqlite::{SqliteColumnType, SqliteWriteMode, SqliteWriteOptions};
+/// Re-export chunked readers and writers.
+pub use stream::{
+    CsvChunkWriter, CsvChunks, JsonlChunkWriter, JsonlChunks, SheetStream, StreamFormat,
+};
+#[cfg(not(target_arch = "wasm32"))]
+/// Re-export Parquet chunk reader and writer (non-WASM only).
+pub use stream::{ParquetChunkWriter, ParquetChunks};
+/// Re-export table type.
+pub use tables::Table;
 #[cfg(not(target_arch = "wasm32"))]
 /// Re-export XLSX read options (non-WASM only).
 pub use xlsx::XlsxReadOptions;
@@ -0,0 +1,443 @@
+//! Defined names: named ranges and constants that formulas can use in place
+//! of references, scoped to a book or to one sheet.
+
+use crate::book::Book;
+use crate::error::{Result, SheetError};
+use crate::sheet::Sheet;
+use indexmap::IndexMap;
+use piptable_formulas::parser::{is_valid_name, parse_formula};
+use piptable_formulas::FormulaExpr;
+use piptable_primitives::CellRange;
+
+/// A defined name and what it refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinedName {
+    /// The name as defined, e.g. `TaxRate`
+    pub name: String,
+    /// Formula text the name stands for, without a leading `=`, e.g.
+    /// `Data!$B$2:$B$10` or `0.08`
+    pub refers_to: String,
+}
+
+impl DefinedName {
+    /// The range the name refers to, with the sheet it names if any, or
+    /// `None` when it refers to a constant or an expression.
+    #[must_use]
+    pub fn range(&self) -> Option<(Option<String>, CellRange)> {
+        match parse_formula(&self.refers_to).ok()? {
+            FormulaExpr::CellRef(addr) => Some((None, CellRange::new(addr, addr))),
+            FormulaExpr::RangeRef(range) => Some((None, range)),
+            FormulaExpr::SheetCellRef { sheet, addr } => {
+                Some((Some(sheet), CellRange::new(addr, addr)))
+            }
+            FormulaExpr::SheetRangeRef { sheet, range } => Some((Some(sheet), range)),
+            _ => None,
+        }
+    }
+}
+
+/// The defined names of a book or a sheet. Names match without regard to
+/// case and keep the order they were defined in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DefinedNames {
+    names: IndexMap<String, DefinedName>,
+}
+
+impl DefinedNames {
+    /// Create an empty set of names
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define `name`, or redefine it, as `refers_to`: a reference such as
+    /// `Data!$A$1:$A$10`, a constant, or any formula expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `name` could be read as something else in a
+    /// formula (see [`is_valid_name`]) or `refers_to` does not parse.
+    pub fn define(&mut self, name: &str, refers_to: &str) -> Result<()> {
+        if !is_valid_name(name) {
+            return Err(SheetError::InvalidName {
+                name: name.to_string(),
+                reason: "names start with a letter or underscore, contain only letters, \
+                         digits, underscores and periods, and cannot look like a cell reference"
+                    .to_string(),
+            });
+        }
+        let refers_to = refers_to.trim();
+        let refers_to = refers_to.strip_prefix('=').unwrap_or(refers_to);
+        parse_formula(refers_to).map_err(|e| SheetError::InvalidName {
+            name: name.to_string(),
+            reason: e.to_string(),
+        })?;
+        self.names.insert(
+            name.to_lowercase(),
+            DefinedName {
+                name: name.to_string(),
+                refers_to: refers_to.to_string(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Look up a name
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&DefinedName> {
+        self.names.get(&name.to_lowercase())
+    }
+
+    /// Remove a name, returning its definition
+    pub fn remove(&mut self, name: &str) -> Option<DefinedName> {
+        self.names.shift_remove(&name.to_lowercase())
+    }
+
+    /// Iterate over names in definition order
+    pub fn iter(&self) -> impl Iterator<Item = &DefinedName> {
+        self.names.values()
+    }
+
+    /// Number of names
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Check if there are no names
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// What `name` refers to, if it is defined.
+    pub(crate) fn refers_to(&self, name: &str) -> Option<String> {
+        self.get(name).map(|defined| defined.refers_to.clone())
+    }
+
+    /// Point definitions that reference sheet `old` at sheet `new`.
+    /// Returns whether any definition changed.
+    pub(crate) fn rename_sheet_references(&mut self, old: &str, new: &str) -> bool {
+        let mut changed = false;
+        for defined in self.names.values_mut() {
+            let refers_to =
+                piptable_formulas::rename_sheet_references(&defined.refers_to, old, new);
+            if refers_to != defined.refers_to {
+                defined.refers_to = refers_to;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+impl Sheet {
+    /// The sheet's own defined names. Its formulas see these names; formulas
+    /// in a [`Book`] also see the book's.
+    #[must_use]
+    pub fn names(&self) -> &DefinedNames {
+        &self.names
+    }
+
+    /// Define a sheet-scoped name, e.g. `define_name("Rates", "$B$2:$B$5")`.
+    /// Formulas are recompiled against the new definition; call
+    /// [`Sheet::evaluate_formulas`] to recompute them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{CellValue, Sheet};
+    ///
+    /// let mut sheet = Sheet::from_data(vec![vec![100, 0], vec![250, 0]]);
+    /// sheet.define_name("Sales", "$A$1:$A$2").unwrap();
+    /// sheet.define_name("TaxRate", "0.1").unwrap();
+    /// sheet.set_formula("B1", "=SUM(Sales)*TaxRate").unwrap();
+    /// sheet.evaluate_formulas().unwrap();
+    /// assert_eq!(sheet.get_a1("B1").unwrap().as_float(), Some(35.0));
+    ///
+    /// let sales = sheet.name_range("sales").unwrap();
+    /// assert_eq!(sales.row_count(), 2);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name or its definition is invalid.
+    pub fn define_name(&mut self, name: &str, refers_to: &str) -> Result<()> {
+        self.names.define(name, refers_to)?;
+        self.rebuild_formula_engine()
+    }
+
+    /// Remove a sheet-scoped name. Formulas that use it evaluate to `#NAME?`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not defined.
+    pub fn remove_name(&mut self, name: &str) -> Result<()> {
+        self.names
+            .remove(name)
+            .ok_or_else(|| SheetError::NameNotFound {
+                name: name.to_string(),
+            })?;
+        self.rebuild_formula_engine()
+    }
+
+    /// The cells a sheet-scoped name refers to, as a new sheet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not defined, does not refer to a
+    /// range, or the range is outside the sheet.
+    pub fn name_range(&self, name: &str) -> Result<Sheet> {
+        let defined = self
+            .names
+            .get(name)
+            .ok_or_else(|| SheetError::NameNotFound {
+                name: name.to_string(),
+            })?;
+        let (_, range) = defined_range(defined)?;
+        self.defined_range_sheet(defined, range)
+    }
+
+    fn defined_range_sheet(&self, defined: &DefinedName, range: CellRange) -> Result<Sheet> {
+        let range = range.normalized();
+        let mut sheet =
+            self.get_range(&format!("{}:{}", range.start.to_a1(), range.end.to_a1()))?;
+        sheet.set_name(&defined.name);
+        Ok(sheet)
+    }
+}
+
+impl Book {
+    /// The book's workbook-scoped defined names
+    #[must_use]
+    pub fn names(&self) -> &DefinedNames {
+        &self.names
+    }
+
+    /// Define a workbook-scoped name, e.g. `define_name("Sales",
+    /// "Data!$B$2:$B$10")`. References in the definition should name their
+    /// sheet. Every formula is recalculated on the next
+    /// [`Book::recalculate`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{Book, CellValue, Sheet};
+    ///
+    /// let mut book = Book::new();
+    /// book.add_sheet("Data", Sheet::from_data(vec![vec![100], vec![250]])).unwrap();
+    /// book.add_sheet("Summary", Sheet::from_data(vec![vec![CellValue::Null]])).unwrap();
+    /// book.define_name("Sales", "Data!$A$1:$A$2").unwrap();
+    /// book.define_name("TaxRate", "0.1").unwrap();
+    ///
+    /// book.set_formula("Summary", "A1", "=SUM(Sales)*TaxRate").unwrap();
+    /// book.recalculate().unwrap();
+    /// let total = book.get_sheet("Summary").unwrap().get(0, 0).unwrap();
+    /// assert_eq!(total.as_float(), Some(35.0));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name or its definition is invalid.
+    pub fn define_name(&mut self, name: &str, refers_to: &str) -> Result<()> {
+        self.names.define(name, refers_to)?;
+        self.formulas.invalidate();
+        Ok(())
+    }
+
+    /// Define a name scoped to sheet `sheet`. Formulas on that sheet see it
+    /// ahead of a workbook-scoped name of the same name; formulas elsewhere
+    /// reach it as `Sheet!Name`. Unqualified references in the definition
+    /// refer to `sheet`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sheet does not exist or the name or its
+    /// definition is invalid.
+    pub fn define_sheet_name(&mut self, sheet: &str, name: &str, refers_to: &str) -> Result<()> {
+        self.get_sheet_mut(sheet)?.define_name(name, refers_to)?;
+        self.formulas.invalidate();
+        Ok(())
+    }
+
+    /// Remove a workbook-scoped name. Formulas that use it evaluate to
+    /// `#NAME?`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not defined.
+    pub fn remove_name(&mut self, name: &str) -> Result<()> {
+        self.names
+            .remove(name)
+            .ok_or_else(|| SheetError::NameNotFound {
+                name: name.to_string(),
+            })?;
+        self.formulas.invalidate();
+        Ok(())
+    }
+
+    /// Remove a name scoped to sheet `sheet`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sheet does not exist or the name is not
+    /// defined on it.
+    pub fn remove_sheet_name(&mut self, sheet: &str, name: &str) -> Result<()> {
+        self.get_sheet_mut(sheet)?.remove_name(name)?;
+        self.formulas.invalidate();
+        Ok(())
+    }
+
+    /// The cells a name refers to, as a new sheet. With `sheet`, names
+    /// scoped to that sheet are looked up before workbook-scoped ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not defined, does not refer to a
+    /// range, or refers to a sheet or cells that do not exist.
+    pub fn name_range(&self, sheet: Option<&str>, name: &str) -> Result<Sheet> {
+        let scope = sheet.map(|sheet| self.get_sheet(sheet)).transpose()?;
+        let defined = scope
+            .and_then(|scope| scope.names.get(name))
+            .or_else(|| self.names.get(name))
+            .ok_or_else(|| SheetError::NameNotFound {
+                name: name.to_string(),
+            })?;
+        let (target, range) = defined_range(defined)?;
+        let target = match target {
+            Some(target) => self
+                .formula_sheet(&target)
+                .ok_or(SheetError::SheetNotFound { name: target })?,
+            None => scope.ok_or_else(|| SheetError::InvalidName {
+                name: defined.name.clone(),
+                reason: "refers to cells without naming their sheet".to_string(),
+            })?,
+        };
+        target.defined_range_sheet(defined, range)
+    }
+}
+
+fn defined_range(defined: &DefinedName) -> Result<(Option<String>, CellRange)> {
+    defined.range().ok_or_else(|| SheetError::InvalidName {
+        name: defined.name.clone(),
+        reason: format!("refers to '{}', not a range", defined.refers_to),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_define_and_lookup() {
+        let mut names = DefinedNames::new();
+        names.define("TaxRate", "=0.08").unwrap();
+        names.define("Sales", "'Q1 Data'!$B$2:$B$4").unwrap();
+        assert_eq!(names.get("taxrate").unwrap().refers_to, "0.08");
+        assert_eq!(names.get("TAXRATE").unwrap().range(), None);
+
+        let (sheet, range) = names.get("sales").unwrap().range().unwrap();
+        assert_eq!(sheet.as_deref(), Some("Q1 Data"));
+        assert_eq!(range.start.row, 1);
+        assert_eq!(range.end.row, 3);
+
+        assert!(names.rename_sheet_references("Q1 Data", "Data"));
+        assert_eq!(names.get("Sales").unwrap().refers_to, "Data!$B$2:$B$4");
+
+        // Redefining keeps one entry
+        names.define("taxrate", "0.1").unwrap();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names.remove("TaxRate").unwrap().refers_to, "0.1");
+        assert!(names.get("TaxRate").is_none());
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        let mut names = DefinedNames::new();
+        for name in ["A1", "Tax Rate", "1st", "TRUE"] {
+            let err = names.define(name, "1").unwrap_err();
+            assert!(matches!(err, SheetError::InvalidName { .. }), "{name}");
+        }
+        let err = names.define("Total", "SUM(").unwrap_err();
+        assert!(matches!(err, SheetError::InvalidName { .. }));
+        assert!(names.is_empty());
+    }
+
+    #[test]
+    fn test_book_names_and_scope() {
+        use crate::cell::CellValue;
+
+        let mut book = Book::new();
+        book.add_sheet("Data", Sheet::from_data(vec![vec![10], vec![20], vec![30]]))
+            .unwrap();
+        book.add_sheet(
+            "Report",
+            Sheet::from_data(vec![vec![
+                CellValue::Null,
+                CellValue::Null,
+                CellValue::Null,
+            ]]),
+        )
+        .unwrap();
+        book.define_name("Sales", "Data!$A$1:$A$3").unwrap();
+        book.define_name("TaxRate", "0.5").unwrap();
+        // The sheet-scoped rate wins on Data and is reachable as Data!TaxRate
+        book.define_sheet_name("Data", "TaxRate", "0.1").unwrap();
+        book.set_formula("Report", "A1", "=SUM(Sales)*TaxRate")
+            .unwrap();
+        book.set_formula("Report", "B1", "=SUM(Sales)*Data!TaxRate")
+            .unwrap();
+        book.set_formula("Report", "C1", "=Missing").unwrap();
+        book.recalculate().unwrap();
+        let report = |book: &Book, cell: &str| {
+            book.get_sheet("Report")
+                .unwrap()
+                .get_a1(cell)
+                .unwrap()
+                .cached_or_self()
+                .clone()
+        };
+        assert_eq!(report(&book, "A1").as_float(), Some(30.0));
+        assert_eq!(report(&book, "B1").as_float(), Some(6.0));
+        assert_eq!(report(&book, "C1").as_str(), "#NAME?");
+
+        // Names are dependencies: a change on Data reaches the formulas
+        book.set_a1("Data", "A3", 70).unwrap();
+        book.recalculate().unwrap();
+        assert_eq!(report(&book, "A1").as_float(), Some(50.0));
+
+        // Renaming the sheet rewrites the definition
+        book.rename_sheet("Data", "Q1 Data").unwrap();
+        assert_eq!(
+            book.names().get("sales").unwrap().refers_to,
+            "'Q1 Data'!$A$1:$A$3"
+        );
+        let sales = book.name_range(None, "Sales").unwrap();
+        assert_eq!(sales.row_count(), 3);
+        assert_eq!(sales.get(2, 0).unwrap(), &CellValue::Int(70));
+        let err = book.name_range(None, "TaxRate").unwrap_err();
+        assert!(matches!(err, SheetError::InvalidName { .. }), "{err}");
+
+        book.remove_name("TaxRate").unwrap();
+        book.recalculate().unwrap();
+        assert_eq!(report(&book, "A1").as_str(), "#NAME?");
+    }
+
+    #[test]
+    fn test_lambda_name_is_custom_function() {
+        let mut sheet = Sheet::from_data(vec![vec![4, 0, 0], vec![5, 0, 0]]);
+        sheet.define_name("Double", "=LAMBDA(x, x*2)").unwrap();
+        sheet.set_formula("B1", "=Double(A1)").unwrap();
+        sheet
+            .set_formula("C1", "=LET(d, Double(A2), d+A1)")
+            .unwrap();
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(sheet.get_a1("B1").unwrap().as_float(), Some(8.0));
+        assert_eq!(sheet.get_a1("C1").unwrap().as_float(), Some(14.0));
+
+        sheet.set_a1("A1", 10).unwrap();
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(sheet.get_a1("B1").unwrap().as_float(), Some(20.0));
+        assert_eq!(sheet.get_a1("C1").unwrap().as_float(), Some(20.0));
+    }
+}
@@ -0,0 +1,530 @@
+//! Joins on ordered values: as-of joins and range joins.
+
+use crate::aggregate::group_key;
+use crate::cell::CellValue;
+use crate::error::{Result, SheetError};
+use crate::sheet::Sheet;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::str::FromStr;
+
+/// Which right row an as-of join picks for each left row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AsOfDirection {
+    /// The last row at or before the left value.
+    #[default]
+    Backward,
+    /// The first row at or after the left value.
+    Forward,
+    /// The closest row either way; ties go to the earlier row.
+    Nearest,
+}
+
+impl FromStr for AsOfDirection {
+    type Err = SheetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "backward" => Ok(Self::Backward),
+            "forward" => Ok(Self::Forward),
+            "nearest" => Ok(Self::Nearest),
+            _ => Err(SheetError::Join(format!(
+                "Unknown as-of direction '{s}', expected backward, forward or nearest"
+            ))),
+        }
+    }
+}
+
+/// A cell as a sortable value. Numbers, dates, date-times, durations and
+/// text in those forms compare as numbers (dates as Excel serials); other
+/// text compares as text, after every number.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+enum OrderKey {
+    Number(f64),
+    Text(String),
+}
+
+impl OrderKey {
+    fn of(cell: &CellValue) -> Option<Self> {
+        match cell.cached_or_self() {
+            CellValue::Null => None,
+            CellValue::String(s) if s.trim().is_empty() => None,
+            CellValue::String(s) => Some(
+                match CellValue::parse_iso_temporal(s)
+                    .and_then(|value| value.as_float())
+                    .or_else(|| s.trim().parse().ok())
+                {
+                    Some(n) => Self::Number(n),
+                    None => Self::Text(s.clone()),
+                },
+            ),
+            other => other.as_float().map(Self::Number),
+        }
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Column names of a join result: every left column, then the right
+/// columns not in `skip`, suffixed with `_right` where a name is taken.
+fn joined_names(left: &[String], right: &[String], skip: &[usize]) -> Vec<(usize, String)> {
+    let mut names: Vec<String> = left.to_vec();
+    let mut right_cols = Vec::new();
+    for (i, name) in right.iter().enumerate() {
+        if skip.contains(&i) {
+            continue;
+        }
+        let name = if names.contains(name) {
+            format!("{name}_right")
+        } else {
+            name.clone()
+        };
+        names.push(name.clone());
+        right_cols.push((i, name));
+    }
+    right_cols
+}
+
+fn key_index(sheet: &Sheet, key: &str, label: &str) -> Result<usize> {
+    sheet
+        .column_index_by_name(key)
+        .map_err(|_| SheetError::JoinKeyNotFound {
+            key: key.to_string(),
+            sheet: label.to_string(),
+        })
+}
+
+/// An open interval, ordered by its end for the heap.
+struct Open(OrderKey, usize);
+
+impl PartialEq for Open {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Open {}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.compare(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+impl Sheet {
+    /// As-of join on a column with the same name in both sheets. See
+    /// [`Sheet::asof_join_on`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Sheet::asof_join_on`].
+    pub fn asof_join(
+        &self,
+        other: &Sheet,
+        by_keys: &[&str],
+        on_col: &str,
+        direction: AsOfDirection,
+    ) -> Result<Sheet> {
+        self.asof_join_on(other, by_keys, on_col, on_col, direction)
+    }
+
+    /// Left join `other` on ordered values: among the rows of `other` with
+    /// the same `by_keys` values, each row is paired with the one whose
+    /// `right_on` value is the latest at or before its `left_on` value
+    /// ([`AsOfDirection::Backward`]), the earliest at or after it
+    /// (`Forward`) or the closest (`Nearest`).
+    ///
+    /// The right sheet is sorted once and searched for each left row, so the
+    /// join takes O((n + m) log m) time. Of several right rows with the same
+    /// value, backward picks the last and forward the first. The result
+    /// keeps every left row in order, followed by the right columns other
+    /// than `by_keys` (renamed with a `_right` suffix where a name is
+    /// taken); rows without a match get nulls.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{AsOfDirection, CellValue, Sheet};
+    ///
+    /// let mut trades = Sheet::from_data(vec![
+    ///     vec!["ccy", "date"],
+    ///     vec!["EUR", "2024-01-03"],
+    ///     vec!["EUR", "2024-01-01"],
+    /// ]);
+    /// trades.name_columns_by_row(0).unwrap();
+    /// let mut rates = Sheet::from_data(vec![
+    ///     vec![CellValue::from("ccy"), "date".into(), "rate".into()],
+    ///     vec!["EUR".into(), "2024-01-02".into(), CellValue::Float(1.09)],
+    ///     vec!["USD".into(), "2024-01-02".into(), CellValue::Float(1.0)],
+    /// ]);
+    /// rates.name_columns_by_row(0).unwrap();
+    ///
+    /// let joined = trades
+    ///     .asof_join(&rates, &["ccy"], "date", AsOfDirection::Backward)
+    ///     .unwrap();
+    /// assert_eq!(joined.column_names().unwrap(), &["ccy", "date", "date_right", "rate"]);
+    /// assert_eq!(joined.get_by_name(1, "rate").unwrap(), &CellValue::Float(1.09));
+    /// assert!(joined.get_by_name(2, "rate").unwrap().is_null());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if columns are not named, a key column is missing,
+    /// or a nearest match compares text with text that is not a number or
+    /// date.
+    pub fn asof_join_on(
+        &self,
+        other: &Sheet,
+        by_keys: &[&str],
+        left_on: &str,
+        right_on: &str,
+        direction: AsOfDirection,
+    ) -> Result<Sheet> {
+        let left_names = self.column_names().cloned().ok_or_else(|| {
+            SheetError::ColumnsNotNamed("Left sheet columns not named".to_string())
+        })?;
+        let right_names = other.column_names().cloned().ok_or_else(|| {
+            SheetError::ColumnsNotNamed("Right sheet columns not named".to_string())
+        })?;
+        let left_on = key_index(self, left_on, "left")?;
+        let right_on = key_index(other, right_on, "right")?;
+        let left_by = by_keys
+            .iter()
+            .map(|key| key_index(self, key, "left"))
+            .collect::<Result<Vec<_>>>()?;
+        let right_by = by_keys
+            .iter()
+            .map(|key| key_index(other, key, "right"))
+            .collect::<Result<Vec<_>>>()?;
+        let right_cols = joined_names(&left_names, &right_names, &right_by);
+
+        let cell = |row: &[CellValue], i: usize| row.get(i).cloned().unwrap_or(CellValue::Null);
+        let group = |row: &[CellValue], keys: &[usize]| {
+            group_key(&keys.iter().map(|&i| cell(row, i)).collect::<Vec<_>>())
+        };
+
+        // Right rows by group, sorted on their as-of value
+        let mut groups: HashMap<String, Vec<(OrderKey, usize)>> = HashMap::new();
+        let right_rows = other.data();
+        for (r, row) in right_rows.iter().enumerate().skip(other.data_start_row()) {
+            if let Some(key) = row.get(right_on).and_then(OrderKey::of) {
+                groups
+                    .entry(group(row, &right_by))
+                    .or_default()
+                    .push((key, r));
+            }
+        }
+        for rows in groups.values_mut() {
+            rows.sort_by(|a, b| a.0.compare(&b.0));
+        }
+
+        let mut data = vec![left_names
+            .iter()
+            .chain(right_cols.iter().map(|(_, name)| name))
+            .map(|name| CellValue::String(name.clone()))
+            .collect::<Vec<_>>()];
+        for row in &self.data()[self.data_start_row()..] {
+            let matched = row
+                .get(left_on)
+                .and_then(OrderKey::of)
+                .zip(groups.get(&group(row, &left_by)))
+                .map(|(key, rows)| -> Result<Option<usize>> {
+                    let after = rows.partition_point(|(k, _)| k.compare(&key) != Ordering::Greater);
+                    let at = rows.partition_point(|(k, _)| k.compare(&key) == Ordering::Less);
+                    let backward = after.checked_sub(1).map(|i| &rows[i]);
+                    let forward = rows.get(at);
+                    Ok(match direction {
+                        AsOfDirection::Backward => backward.map(|(_, r)| *r),
+                        AsOfDirection::Forward => forward.map(|(_, r)| *r),
+                        AsOfDirection::Nearest => match (backward, forward) {
+                            (Some(b), Some(f)) => {
+                                let distance = |k: &OrderKey| match (k, &key) {
+                                    (OrderKey::Number(a), OrderKey::Number(b)) => Ok((a - b).abs()),
+                                    _ => Err(SheetError::Join(
+                                        "A nearest as-of join needs numbers, dates or times"
+                                            .to_string(),
+                                    )),
+                                };
+                                if distance(&f.0)? < distance(&b.0)? {
+                                    Some(f.1)
+                                } else {
+                                    Some(b.1)
+                                }
+                            }
+                            (b, f) => b.or(f).map(|(_, r)| *r),
+                        },
+                    })
+                })
+                .transpose()?
+                .flatten();
+
+            let mut out = row.clone();
+            out.resize(left_names.len(), CellValue::Null);
+            out.extend(
+                right_cols
+                    .iter()
+                    .map(|&(c, _)| matched.map_or(CellValue::Null, |r| cell(&right_rows[r], c))),
+            );
+            data.push(out);
+        }
+
+        let mut result = Sheet::from_data(data);
+        result.set_name(&format!("{}_joined", self.name()));
+        result.name_columns_by_row(0)?;
+        Ok(result)
+    }
+
+    /// Inner join `other` on intervals: each row is paired with every row
+    /// of `other` whose `right_start` to `right_end` range contains its
+    /// `left_col` value, both ends included.
+    ///
+    /// Both sides are sorted and swept once, keeping the open intervals in
+    /// a heap, so the join takes O((n + m) log m) time plus one step per
+    /// pair. Rows come out in left order, then right order; rows with an
+    /// empty value or bound are left out. The result has every left column
+    /// and every right column, renamed with a `_right` suffix where a name
+    /// is taken.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{CellValue, Sheet};
+    ///
+    /// let mut events = Sheet::from_data(vec![vec!["ts"], vec!["5"], vec!["12"], vec!["30"]]);
+    /// events.name_columns_by_row(0).unwrap();
+    /// let mut sessions = Sheet::from_data(vec![
+    ///     vec!["session", "start", "end"],
+    ///     vec!["a", "0", "10"],
+    ///     vec!["b", "10", "20"],
+    /// ]);
+    /// sessions.name_columns_by_row(0).unwrap();
+    ///
+    /// let joined = events.range_join(&sessions, "ts", "start", "end").unwrap();
+    /// assert_eq!(joined.row_count(), 3); // header, 5 in a, 12 in b
+    /// assert_eq!(joined.get_by_name(2, "session").unwrap(), &CellValue::from("b"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if columns are not named or a column is missing.
+    pub fn range_join(
+        &self,
+        other: &Sheet,
+        left_col: &str,
+        right_start: &str,
+        right_end: &str,
+    ) -> Result<Sheet> {
+        let left_names = self.column_names().cloned().ok_or_else(|| {
+            SheetError::ColumnsNotNamed("Left sheet columns not named".to_string())
+        })?;
+        let right_names = other.column_names().cloned().ok_or_else(|| {
+            SheetError::ColumnsNotNamed("Right sheet columns not named".to_string())
+        })?;
+        let left_col = key_index(self, left_col, "left")?;
+        let start_col = key_index(other, right_start, "right")?;
+        let end_col = key_index(other, right_end, "right")?;
+        let right_cols = joined_names(&left_names, &right_names, &[]);
+
+        let key = |sheet: &Sheet, r: usize, c: usize| sheet.data()[r].get(c).and_then(OrderKey::of);
+        let mut points: Vec<(OrderKey, usize)> = (self.data_start_row()..self.row_count())
+            .filter_map(|r| key(self, r, left_col).map(|k| (k, r)))
+            .collect();
+        points.sort_by(|a, b| a.0.compare(&b.0));
+        let mut intervals: Vec<(OrderKey, OrderKey, usize)> = (other.data_start_row()
+            ..other.row_count())
+            .filter_map(|r| Some((key(other, r, start_col)?, key(other, r, end_col)?, r)))
+            .collect();
+        intervals.sort_by(|a, b| a.0.compare(&b.0));
+
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        let mut open: BinaryHeap<Reverse<Open>> = BinaryHeap::new();
+        let mut next = 0;
+        for (value, l) in &points {
+            while let Some((start, end, r)) = intervals.get(next) {
+                if start.compare(value) == Ordering::Greater {
+                    break;
+                }
+                open.push(Reverse(Open(end.clone(), *r)));
+                next += 1;
+            }
+            while open
+                .peek()
+                .is_some_and(|Reverse(Open(end, _))| end.compare(value) == Ordering::Less)
+            {
+                open.pop();
+            }
+            pairs.extend(open.iter().map(|Reverse(Open(_, r))| (*l, *r)));
+        }
+        pairs.sort_unstable();
+
+        let mut data = vec![left_names
+            .iter()
+            .chain(right_cols.iter().map(|(_, name)| name))
+            .map(|name| CellValue::String(name.clone()))
+            .collect::<Vec<_>>()];
+        let (left_rows, right_rows) = (self.data(), other.data());
+        for (l, r) in pairs {
+            let mut row = left_rows[l].clone();
+            row.resize(left_names.len(), CellValue::Null);
+            row.extend(
+                right_cols
+                    .iter()
+                    .map(|&(c, _)| right_rows[r].get(c).cloned().unwrap_or(CellValue::Null)),
+            );
+            data.push(row);
+        }
+
+        let mut result = Sheet::from_data(data);
+        result.set_name(&format!("{}_joined", self.name()));
+        result.name_columns_by_row(0)?;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet(rows: Vec<Vec<CellValue>>) -> Sheet {
+        let mut sheet = Sheet::from_data(rows);
+        sheet.name_columns_by_row(0).unwrap();
+        sheet
+    }
+
+    fn text(values: &[&str]) -> Vec<CellValue> {
+        values.iter().map(|v| CellValue::from(*v)).collect()
+    }
+
+    fn column(sheet: &Sheet, name: &str) -> Vec<String> {
+        sheet.column_by_name(name).unwrap()[1..]
+            .iter()
+            .map(CellValue::as_str)
+            .collect()
+    }
+
+    fn trades() -> Sheet {
+        sheet(vec![
+            text(&["id", "ccy", "date"]),
+            text(&["t1", "EUR", "2024-01-05"]),
+            text(&["t2", "GBP", "2024-01-02"]),
+            text(&["t3", "EUR", "2024-01-01"]),
+            text(&["t4", "EUR", "2024-01-03"]),
+            text(&["t5", "EUR", ""]),
+        ])
+    }
+
+    fn rates() -> Sheet {
+        // Deliberately unsorted, with a date-typed row and a repeated date
+        sheet(vec![
+            text(&["ccy", "date", "rate"]),
+            text(&["EUR", "2024-01-04", "1.10"]),
+            text(&["EUR", "2024-01-02", "1.08"]),
+            vec![
+                "EUR".into(),
+                CellValue::Date(chrono::NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()),
+                "1.09".into(),
+            ],
+            text(&["GBP", "2024-01-03", "1.27"]),
+        ])
+    }
+
+    #[test]
+    fn test_asof_join_directions() {
+        let backward = trades()
+            .asof_join(&rates(), &["ccy"], "date", AsOfDirection::Backward)
+            .unwrap();
+        assert_eq!(
+            backward.column_names().unwrap(),
+            &["id", "ccy", "date", "date_right", "rate"]
+        );
+        assert_eq!(column(&backward, "id"), ["t1", "t2", "t3", "t4", "t5"]);
+        assert_eq!(column(&backward, "rate"), ["1.10", "", "", "1.09", ""]);
+
+        let forward = trades()
+            .asof_join(&rates(), &["ccy"], "date", AsOfDirection::Forward)
+            .unwrap();
+        assert_eq!(column(&forward, "rate"), ["", "1.27", "1.08", "1.10", ""]);
+
+        let nearest = trades()
+            .asof_join(&rates(), &["ccy"], "date", AsOfDirection::Nearest)
+            .unwrap();
+        assert_eq!(
+            column(&nearest, "rate"),
+            ["1.10", "1.27", "1.08", "1.09", ""]
+        );
+
+        // Without by keys every rate is a candidate
+        let any = trades()
+            .asof_join_on(&rates(), &[], "date", "date", AsOfDirection::Backward)
+            .unwrap();
+        assert_eq!(column(&any, "ccy_right"), ["EUR", "EUR", "", "GBP", ""]);
+    }
+
+    #[test]
+    fn test_asof_join_errors() {
+        let names = sheet(vec![text(&["name"]), text(&["b"])]);
+        let other = sheet(vec![text(&["name"]), text(&["a"]), text(&["c"])]);
+        let backward = names
+            .asof_join(&other, &[], "name", AsOfDirection::Backward)
+            .unwrap();
+        assert_eq!(column(&backward, "name_right"), ["a"]);
+        let err = names
+            .asof_join(&other, &[], "name", AsOfDirection::Nearest)
+            .unwrap_err();
+        assert!(err.to_string().contains("numbers, dates or times"));
+        assert!(matches!(
+            trades().asof_join(&rates(), &["region"], "date", AsOfDirection::Backward),
+            Err(SheetError::JoinKeyNotFound { .. })
+        ));
+        assert!("sideways".parse::<AsOfDirection>().is_err());
+        assert_eq!(
+            "NEAREST".parse::<AsOfDirection>().unwrap(),
+            AsOfDirection::Nearest
+        );
+    }
+
+    #[test]
+    fn test_range_join() {
+        let events = sheet(vec![
+            text(&["event", "ts"]),
+            vec!["e1".into(), CellValue::Int(15)],
+            vec!["e2".into(), CellValue::Int(5)],
+            vec!["e3".into(), CellValue::Int(40)],
+            vec!["e4".into(), CellValue::Int(10)],
+            vec!["e5".into(), CellValue::Null],
+        ]);
+        let sessions = sheet(vec![
+            text(&["session", "start", "end"]),
+            vec!["late".into(), CellValue::Int(10), CellValue::Float(20.0)],
+            vec!["early".into(), CellValue::Int(0), CellValue::Int(10)],
+            vec!["all".into(), CellValue::Int(0), CellValue::Int(30)],
+            vec!["open".into(), CellValue::Int(0), CellValue::Null],
+        ]);
+        let joined = events.range_join(&sessions, "ts", "start", "end").unwrap();
+        assert_eq!(
+            joined.column_names().unwrap(),
+            &["event", "ts", "session", "start", "end"]
+        );
+        assert_eq!(
+            column(&joined, "event"),
+            ["e1", "e1", "e2", "e2", "e4", "e4", "e4"]
+        );
+        assert_eq!(
+            column(&joined, "session"),
+            ["late", "all", "early", "all", "late", "early", "all"]
+        );
+
+        assert!(matches!(
+            events.range_join(&sessions, "ts", "from", "end"),
+            Err(SheetError::JoinKeyNotFound { .. })
+        ));
+    }
+}
@@ -4,18 +4,22 @@
 //! a columnar storage format with efficient compression.
 
 use crate::cell::CellValue;
+use crate::columnar::{Column, Columns, Storage};
 use crate::error::{Result, SheetError};
 use crate::sheet::Sheet;
-use arrow::array::{
-    Array, ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray,
-};
-use arrow::datatypes::{DataType, Field, Schema};
+use arrow::array::{Array, ArrayRef, AsArray, RecordBatch, RecordBatchOptions};
+use arrow::datatypes::{DataType, DurationMicrosecondType, Field, Int64Type, Schema, TimeUnit};
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 use parquet::arrow::ArrowWriter;
+use std::collections::HashMap;
 use std::fs::File;
 use std::path::Path;
 use std::sync::Arc;
 
+/// Field metadata marking an `Int64` column as microsecond durations, since
+/// Parquet cannot store Arrow's `Duration` type.
+const DURATION_METADATA_KEY: &str = "piptable:duration";
+
 impl Sheet {
     /// Load a sheet from a Parquet file
     ///
@@ -39,58 +43,13 @@ impl Sheet {
             batches.push(batch);
         }
 
-        // Extract column names from schema
-        let column_names: Vec<String> = schema.fields().iter().map(|f| f.name().clone()).collect();
-
-        if batches.is_empty() {
-            // Preserve schema even for empty files
-            if column_names.is_empty() {
-                return Ok(Sheet::new());
-            }
-            let header: Vec<CellValue> = column_names
-                .iter()
-                .map(|n| CellValue::String(n.clone()))
-                .collect();
-            let mut sheet = Sheet::with_name("Sheet1");
-            *sheet.data_mut() = vec![header];
-            sheet.name_columns_by_row(0)?;
-            return Ok(sheet);
-        }
-
-        // Build data rows with header
-        let mut data: Vec<Vec<CellValue>> = Vec::new();
-
-        // Add header row
-        let header: Vec<CellValue> = column_names
-            .iter()
-            .map(|n| CellValue::String(n.clone()))
-            .collect();
-        data.push(header);
-
-        // Process each batch
-        for batch in &batches {
-            let num_rows = batch.num_rows();
-            for row_idx in 0..num_rows {
-                let mut row: Vec<CellValue> = Vec::with_capacity(batch.num_columns());
-                for col_idx in 0..batch.num_columns() {
-                    let array = batch.column(col_idx);
-                    let cell = arrow_array_to_cell(array, row_idx);
-                    row.push(cell);
-                }
-                data.push(row);
-            }
-        }
-
-        let mut sheet = Sheet::with_name("Sheet1");
-        *sheet.data_mut() = data;
-        sheet.name_columns_by_row(0)?;
-
-        Ok(sheet)
+        batches_to_sheet(&schema, &batches)
     }
 
     /// Save the sheet to a Parquet file
     ///
-    /// Requires columns to be named.
+    /// Requires columns to be named. Column types come from the sheet's
+    /// schema when it has one, and are inferred from the data otherwise.
     ///
     /// # Example
     /// ```no_run
@@ -104,217 +63,123 @@ impl Sheet {
     /// sheet.save_as_parquet("output.parquet").unwrap();
     /// ```
     pub fn save_as_parquet<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let column_names = self.column_names().ok_or_else(|| {
-            SheetError::ColumnsNotNamed("Columns must be named to export as Parquet".to_string())
-        })?;
-
-        if self.row_count() <= 1 {
-            // Only header row or empty - create empty parquet with schema
-            let fields: Vec<Field> = column_names
-                .iter()
-                .map(|name| Field::new(name, DataType::Utf8, true))
-                .collect();
-            let schema = Arc::new(Schema::new(fields));
-            let file = File::create(path)?;
-            let writer = ArrowWriter::try_new(file, schema.clone(), None)?;
-            writer.close()?;
-            return Ok(());
+        if self.column_names().is_none() {
+            return Err(SheetError::ColumnsNotNamed(
+                "Columns must be named to export as Parquet".to_string(),
+            ));
         }
+        let batch = to_parquet_batch(&self.to_record_batch()?)?;
 
-        // Analyze column types from data (skip header row)
-        let data_rows: Vec<&Vec<CellValue>> = self.data().iter().skip(1).collect();
-        let num_cols = column_names.len();
-
-        // Infer types for each column
-        let col_types: Vec<DataType> = (0..num_cols)
-            .map(|col_idx| infer_column_type(&data_rows, col_idx))
-            .collect();
-
-        // Build schema
-        let fields: Vec<Field> = column_names
-            .iter()
-            .zip(col_types.iter())
-            .map(|(name, dtype)| Field::new(name, dtype.clone(), true))
-            .collect();
-        let schema = Arc::new(Schema::new(fields));
-
-        // Build Arrow arrays for each column
-        let arrays: Vec<ArrayRef> = (0..num_cols)
-            .map(|col_idx| build_arrow_array(&data_rows, col_idx, &col_types[col_idx]))
-            .collect();
-
-        // Create RecordBatch
-        let batch = RecordBatch::try_new(schema.clone(), arrays)?;
-
-        // Write to file
         let file = File::create(path)?;
-        let mut writer = ArrowWriter::try_new(file, schema, None)?;
-        writer.write(&batch)?;
+        let mut writer = ArrowWriter::try_new(file, batch.schema(), None)?;
+        if batch.num_rows() > 0 {
+            writer.write(&batch)?;
+        }
         writer.close()?;
 
         Ok(())
     }
 }
 
-/// Convert an Arrow array element at a given index to a CellValue
-fn arrow_array_to_cell(array: &ArrayRef, idx: usize) -> CellValue {
-    if array.is_null(idx) {
-        return CellValue::Null;
-    }
-
-    match array.data_type() {
-        DataType::Boolean => {
-            let arr = array.as_any().downcast_ref::<BooleanArray>().unwrap();
-            CellValue::Bool(arr.value(idx))
-        }
-        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
-            // Try to downcast to Int64Array first, then others
-            if let Some(arr) = array.as_any().downcast_ref::<Int64Array>() {
-                CellValue::Int(arr.value(idx))
-            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::Int32Array>() {
-                CellValue::Int(i64::from(arr.value(idx)))
-            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::Int16Array>() {
-                CellValue::Int(i64::from(arr.value(idx)))
-            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::Int8Array>() {
-                CellValue::Int(i64::from(arr.value(idx)))
-            } else {
-                CellValue::String(format!("<int:{}>", array.data_type()))
-            }
-        }
-        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
-            if let Some(arr) = array.as_any().downcast_ref::<arrow::array::UInt64Array>() {
-                let val = arr.value(idx);
-                // Check for overflow: u64 values > i64::MAX cannot be represented as i64
-                if let Ok(i) = i64::try_from(val) {
-                    CellValue::Int(i)
-                } else {
-                    // Preserve large unsigned values as strings to avoid data corruption
-                    CellValue::String(val.to_string())
-                }
-            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::UInt32Array>() {
-                CellValue::Int(i64::from(arr.value(idx)))
-            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::UInt16Array>() {
-                CellValue::Int(i64::from(arr.value(idx)))
-            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::UInt8Array>() {
-                CellValue::Int(i64::from(arr.value(idx)))
-            } else {
-                CellValue::String(format!("<uint:{}>", array.data_type()))
-            }
-        }
-        DataType::Float16 | DataType::Float32 | DataType::Float64 => {
-            if let Some(arr) = array.as_any().downcast_ref::<Float64Array>() {
-                CellValue::Float(arr.value(idx))
-            } else if let Some(arr) = array.as_any().downcast_ref::<arrow::array::Float32Array>() {
-                CellValue::Float(f64::from(arr.value(idx)))
-            } else {
-                CellValue::String(format!("<float:{}>", array.data_type()))
-            }
-        }
-        DataType::Utf8 | DataType::LargeUtf8 => {
-            if let Some(arr) = array.as_any().downcast_ref::<StringArray>() {
-                CellValue::String(arr.value(idx).to_string())
-            } else if let Some(arr) = array
-                .as_any()
-                .downcast_ref::<arrow::array::LargeStringArray>()
-            {
-                CellValue::String(arr.value(idx).to_string())
-            } else {
-                CellValue::Null
-            }
-        }
-        // For other types, convert to string representation
-        _ => {
-            let formatted = arrow::util::display::array_value_to_string(array, idx);
-            match formatted {
-                Ok(s) => CellValue::String(s),
-                Err(_) => CellValue::String(format!("<{}>", array.data_type())),
-            }
-        }
-    }
-}
-
-/// Infer the Arrow DataType for a column based on cell values
-fn infer_column_type(rows: &[&Vec<CellValue>], col_idx: usize) -> DataType {
-    let mut has_bool = false;
-    let mut has_int = false;
-    let mut has_float = false;
-    let mut has_string = false;
-
-    for row in rows {
-        if col_idx >= row.len() {
-            continue;
-        }
-        let cell = row[col_idx].cached_or_self();
-        match cell {
-            CellValue::Null => {}
-            CellValue::Bool(_) => has_bool = true,
-            CellValue::Int(_) => has_int = true,
-            CellValue::Float(_) => has_float = true,
-            CellValue::String(_) => has_string = true,
-            CellValue::Formula(_) => has_string = true,
+/// Build a sheet from batches read with `schema`: the arrays become columns
+/// below a header row of field names.
+pub(crate) fn batches_to_sheet(schema: &Schema, batches: &[RecordBatch]) -> Result<Sheet> {
+    // Extract column names from schema
+    let column_names: Vec<String> = schema.fields().iter().map(|f| f.name().clone()).collect();
+    let durations: Vec<bool> = schema
+        .fields()
+        .iter()
+        .map(|f| f.metadata().contains_key(DURATION_METADATA_KEY))
+        .collect();
+
+    if batches.is_empty() {
+        // Preserve schema even for empty files
+        if column_names.is_empty() {
+            return Ok(Sheet::new());
         }
+        let header: Vec<CellValue> = column_names
+            .iter()
+            .map(|n| CellValue::String(n.clone()))
+            .collect();
+        let mut sheet = Sheet::with_name("Sheet1");
+        *sheet.data_mut() = vec![header];
+        sheet.name_columns_by_row(0)?;
+        return Ok(sheet);
     }
 
-    // Priority: String > Float > Int > Bool (wider types win)
-    if has_string {
-        DataType::Utf8
-    } else if has_float {
-        DataType::Float64
-    } else if has_int {
-        DataType::Int64
-    } else if has_bool {
-        DataType::Boolean
-    } else {
-        DataType::Utf8 // Default to string for empty/null-only columns
-    }
-}
-
-/// Build an Arrow array from column data
-fn build_arrow_array(rows: &[&Vec<CellValue>], col_idx: usize, dtype: &DataType) -> ArrayRef {
-    match dtype {
-        DataType::Boolean => {
-            let values: Vec<Option<bool>> = rows
+    // Keep the arrays as columns, below a header row
+    let header: Vec<CellValue> = column_names
+        .iter()
+        .map(|n| CellValue::String(n.clone()))
+        .collect();
+    let columns = durations
+        .iter()
+        .enumerate()
+        .map(|(idx, is_duration)| {
+            let arrays: Vec<ArrayRef> = batches
                 .iter()
-                .map(|row| row.get(col_idx).and_then(|cell| cell.as_bool()))
-                .collect();
-            Arc::new(BooleanArray::from(values))
-        }
-        DataType::Int64 => {
-            let values: Vec<Option<i64>> = rows
-                .iter()
-                .map(|row| row.get(col_idx).and_then(|cell| cell.as_int()))
-                .collect();
-            Arc::new(Int64Array::from(values))
-        }
-        DataType::Float64 => {
-            let values: Vec<Option<f64>> = rows
-                .iter()
-                .map(|row| row.get(col_idx).and_then(|cell| cell.as_float()))
-                .collect();
-            Arc::new(Float64Array::from(values))
-        }
-        _ => {
-            // Default to string for Utf8 and any other types
-            let values: Vec<Option<String>> = rows
-                .iter()
-                .map(|row| {
-                    row.get(col_idx).and_then(|cell| {
-                        if cell.is_null() {
-                            None
-                        } else {
-                            Some(cell.as_str())
-                        }
-                    })
+                .map(|batch| {
+                    let array = batch.column(idx);
+                    match array.data_type() {
+                        DataType::Int64 if *is_duration => Arc::new(
+                            array
+                                .as_primitive::<Int64Type>()
+                                .reinterpret_cast::<DurationMicrosecondType>(),
+                        ),
+                        _ => array.clone(),
+                    }
                 })
                 .collect();
-            Arc::new(StringArray::from(values))
-        }
-    }
+            Column::concat(&arrays)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let len = batches.iter().map(RecordBatch::num_rows).sum();
+
+    let mut sheet = Sheet::with_name("Sheet1");
+    sheet.set_storage(Storage::from_columns(Columns::new(
+        Some(header),
+        columns,
+        len,
+    )));
+    sheet.name_columns_by_row(0)?;
+
+    Ok(sheet)
+}
+
+/// The batch as stored in Parquet: durations become tagged microsecond
+/// counts, since Parquet has no duration type.
+pub(crate) fn to_parquet_batch(batch: &RecordBatch) -> Result<RecordBatch> {
+    let (fields, arrays): (Vec<Field>, Vec<ArrayRef>) = batch
+        .schema()
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .map(|(field, array)| match array.data_type() {
+            DataType::Duration(TimeUnit::Microsecond) => (
+                Field::new(field.name(), DataType::Int64, field.is_nullable()).with_metadata(
+                    HashMap::from([(DURATION_METADATA_KEY.to_string(), "us".to_string())]),
+                ),
+                Arc::new(
+                    array
+                        .as_primitive::<DurationMicrosecondType>()
+                        .reinterpret_cast::<Int64Type>(),
+                ) as ArrayRef,
+            ),
+            _ => (field.as_ref().clone(), array.clone()),
+        })
+        .unzip();
+    let options = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+    Ok(RecordBatch::try_new_with_options(
+        Arc::new(Schema::new(fields)),
+        arrays,
+        &options,
+    )?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::NaiveDate;
     use tempfile::tempdir;
 
     #[test]
@@ -341,6 +206,95 @@ mod tests {
         assert!(loaded.column_names().is_some());
     }
 
+    #[test]
+    fn test_parquet_temporal_types() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("dates.parquet");
+
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let at = date.and_hms_micro_opt(9, 30, 0, 250).unwrap();
+        let mut sheet = Sheet::new();
+        *sheet.data_mut() = vec![
+            vec![
+                CellValue::from("due"),
+                CellValue::from("sent"),
+                CellValue::from("took"),
+            ],
+            vec![
+                CellValue::Date(date),
+                CellValue::DateTime(at),
+                CellValue::Duration(chrono::TimeDelta::seconds(90)),
+            ],
+            vec![CellValue::Null, CellValue::Date(date), CellValue::Null],
+        ];
+        sheet.name_columns_by_row(0).unwrap();
+        sheet.save_as_parquet(&file_path).unwrap();
+
+        let file = File::open(&file_path).unwrap();
+        let schema = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .schema()
+            .clone();
+        assert_eq!(schema.field(0).data_type(), &DataType::Date32);
+        assert_eq!(
+            schema.field(1).data_type(),
+            &DataType::Timestamp(TimeUnit::Microsecond, None)
+        );
+
+        let loaded = Sheet::from_parquet(&file_path).unwrap();
+        assert_eq!(loaded.get(1, 0).unwrap(), &CellValue::Date(date));
+        assert_eq!(loaded.get(1, 1).unwrap(), &CellValue::DateTime(at));
+        assert_eq!(
+            loaded.get(1, 2).unwrap(),
+            &CellValue::Duration(chrono::TimeDelta::seconds(90))
+        );
+        // Dates in a timestamp column come back at midnight
+        assert_eq!(
+            loaded.get(2, 1).unwrap(),
+            &CellValue::DateTime(date.and_hms_opt(0, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parquet_uses_schema_types() {
+        use crate::schema::{ColumnSchema, ColumnType, SheetSchema};
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("schema.parquet");
+
+        let mut sheet = Sheet::from_data(vec![
+            vec![CellValue::from("zip"), "qty".into()],
+            vec!["00123".into(), CellValue::Int(4)],
+            vec![CellValue::Int(94105), "5".into()],
+        ]);
+        sheet.name_columns_by_row(0).unwrap();
+        let schema = SheetSchema::new(vec![
+            ColumnSchema::new("zip", ColumnType::String),
+            ColumnSchema::new("qty", ColumnType::Float).nullable(false),
+        ]);
+        assert!(sheet.apply_schema(schema).unwrap().is_clean());
+        sheet.save_as_parquet(&file_path).unwrap();
+
+        let file = File::open(&file_path).unwrap();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+        let schema = builder.schema();
+        assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+        assert_eq!(schema.field(1).data_type(), &DataType::Float64);
+        assert!(!schema.field(1).is_nullable());
+
+        let loaded = Sheet::from_parquet(&file_path).unwrap();
+        assert_eq!(
+            loaded.get(2, 0).unwrap(),
+            &CellValue::String("94105".into())
+        );
+        assert_eq!(loaded.get(2, 1).unwrap(), &CellValue::Float(5.0));
+
+        // A value set after the schema was applied is caught before writing
+        sheet.set(1, 1, CellValue::from("many")).unwrap();
+        let err = sheet.save_as_parquet(&file_path).unwrap_err();
+        assert!(err.to_string().contains("'many' is not float"), "{err}");
+    }
+
     #[test]
     fn test_parquet_with_types() {
         let dir = tempdir().unwrap();
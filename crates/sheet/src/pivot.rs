@@ -0,0 +1,406 @@
+//! Pivot and unpivot (melt) reshaping for sheets.
+
+use crate::aggregate::{compare_cells, group_key, Aggregation};
+use crate::cell::CellValue;
+use crate::error::{Result, SheetError};
+use crate::sheet::Sheet;
+use std::collections::HashMap;
+
+/// Label used for the grand total row and column.
+const TOTAL_LABEL: &str = "Total";
+
+/// Header used for pivot columns whose value is empty.
+const BLANK_LABEL: &str = "(blank)";
+
+/// Options for [`Sheet::pivot_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct PivotOptions {
+    /// Add a `Total` column and a `Total` row aggregated from the source values.
+    pub grand_totals: bool,
+    /// Order pivot columns by value instead of by first appearance.
+    pub sort_columns: bool,
+}
+
+impl PivotOptions {
+    /// Create default options: no totals, columns in first-appearance order.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add grand totals.
+    #[must_use]
+    pub fn with_grand_totals(mut self, grand_totals: bool) -> Self {
+        self.grand_totals = grand_totals;
+        self
+    }
+
+    /// Sort pivot columns by value.
+    #[must_use]
+    pub fn with_sort_columns(mut self, sort_columns: bool) -> Self {
+        self.sort_columns = sort_columns;
+        self
+    }
+}
+
+impl Sheet {
+    /// Spread the distinct values of `column_col` into columns.
+    ///
+    /// Rows are grouped by `index_cols` and each cell holds `agg` applied to
+    /// the `value_col` values of the rows in that group and pivot column.
+    /// Groups and pivot columns keep the order in which they first appear.
+    /// The sheet must have named columns.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{Aggregation, CellValue, Sheet};
+    ///
+    /// let mut sales = Sheet::from_data(vec![
+    ///     vec![CellValue::from("region"), "month".into(), "amount".into()],
+    ///     vec!["East".into(), "Jan".into(), CellValue::Int(10)],
+    ///     vec!["East".into(), "Feb".into(), CellValue::Int(5)],
+    ///     vec!["West".into(), "Jan".into(), CellValue::Int(7)],
+    ///     vec!["East".into(), "Jan".into(), CellValue::Int(1)],
+    /// ]);
+    /// sales.name_columns_by_row(0).unwrap();
+    ///
+    /// let pivot = sales
+    ///     .pivot(&["region"], "month", "amount", Aggregation::Sum)
+    ///     .unwrap();
+    /// assert_eq!(pivot.column_names().unwrap(), &["region", "Jan", "Feb"]);
+    /// assert_eq!(pivot.get(1, 1).unwrap().as_int(), Some(11));
+    /// assert!(pivot.get(2, 2).unwrap().is_null());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if columns are not named, a column is missing, or a
+    /// numeric aggregation meets a non-numeric value.
+    pub fn pivot(
+        &self,
+        index_cols: &[&str],
+        column_col: &str,
+        value_col: &str,
+        agg: Aggregation,
+    ) -> Result<Sheet> {
+        self.pivot_with_options(
+            index_cols,
+            column_col,
+            value_col,
+            agg,
+            &PivotOptions::default(),
+        )
+    }
+
+    /// Pivot with grand totals or sorted pivot columns.
+    ///
+    /// Totals are computed from the source values, so an `Avg` total is the
+    /// mean of every value rather than the mean of the cell averages.
+    pub fn pivot_with_options(
+        &self,
+        index_cols: &[&str],
+        column_col: &str,
+        value_col: &str,
+        agg: Aggregation,
+        options: &PivotOptions,
+    ) -> Result<Sheet> {
+        let index_idx = index_cols
+            .iter()
+            .map(|name| self.column_index_by_name(name))
+            .collect::<Result<Vec<_>>>()?;
+        let column_idx = self.column_index_by_name(column_col)?;
+        let value_idx = self.column_index_by_name(value_col)?;
+
+        // Group keys and pivot columns in first-appearance order
+        let mut groups: Vec<Vec<CellValue>> = Vec::new();
+        let mut group_lookup: HashMap<String, usize> = HashMap::new();
+        let mut columns: Vec<CellValue> = Vec::new();
+        let mut column_lookup: HashMap<String, usize> = HashMap::new();
+        let mut cells: HashMap<(usize, usize), Vec<&CellValue>> = HashMap::new();
+
+        for row in &self.data()[self.data_start_row()..] {
+            let cell = |idx: usize| row.get(idx).unwrap_or(&CellValue::Null);
+
+            let key_values: Vec<CellValue> = index_idx.iter().map(|&i| cell(i).clone()).collect();
+            let key = group_key(&key_values);
+            let group = *group_lookup.entry(key).or_insert_with(|| {
+                groups.push(key_values);
+                groups.len() - 1
+            });
+
+            let pivot_value = cell(column_idx);
+            let column = *column_lookup
+                .entry(Sheet::cell_key(pivot_value))
+                .or_insert_with(|| {
+                    columns.push(pivot_value.clone());
+                    columns.len() - 1
+                });
+
+            cells
+                .entry((group, column))
+                .or_default()
+                .push(cell(value_idx));
+        }
+
+        let mut column_order: Vec<usize> = (0..columns.len()).collect();
+        if options.sort_columns {
+            column_order.sort_by(|&a, &b| compare_cells(&columns[a], &columns[b]));
+        }
+
+        let mut header: Vec<CellValue> = index_cols.iter().map(|&n| n.into()).collect();
+        header.extend(column_order.iter().map(|&c| column_label(&columns[c])));
+        if options.grand_totals {
+            header.push(TOTAL_LABEL.into());
+        }
+
+        let mut data = Vec::with_capacity(groups.len() + 2);
+        data.push(header);
+        for (group, key_values) in groups.iter().enumerate() {
+            let mut row = key_values.clone();
+            let mut row_values = Vec::new();
+            for &column in &column_order {
+                match cells.get(&(group, column)) {
+                    Some(values) => {
+                        row.push(agg.apply(values, value_col)?);
+                        row_values.extend(values.iter().copied());
+                    }
+                    None => row.push(CellValue::Null),
+                }
+            }
+            if options.grand_totals {
+                row.push(agg.apply(&row_values, value_col)?);
+            }
+            data.push(row);
+        }
+
+        if options.grand_totals && !index_cols.is_empty() {
+            let mut row = vec![CellValue::Null; index_cols.len()];
+            row[0] = TOTAL_LABEL.into();
+            let mut all_values = Vec::new();
+            for &column in &column_order {
+                let column_values: Vec<&CellValue> = (0..groups.len())
+                    .filter_map(|group| cells.get(&(group, column)))
+                    .flatten()
+                    .copied()
+                    .collect();
+                row.push(agg.apply(&column_values, value_col)?);
+                all_values.extend(column_values);
+            }
+            row.push(agg.apply(&all_values, value_col)?);
+            data.push(row);
+        }
+
+        let mut result = Sheet::from_data(data);
+        result.set_name(&format!("{}_pivot", self.name()));
+        result.name_columns_by_row(0)?;
+        Ok(result)
+    }
+
+    /// Turn columns into rows (also known as melt).
+    ///
+    /// Each data row produces one row per column in `value_cols`, holding the
+    /// `id_cols` values, the column name under `var_name` and the cell under
+    /// `value_name`. An empty `value_cols` unpivots every column that is not an
+    /// id column. Rows keep their source order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{CellValue, Sheet};
+    ///
+    /// let mut wide = Sheet::from_data(vec![
+    ///     vec![CellValue::from("region"), "Jan".into(), "Feb".into()],
+    ///     vec!["East".into(), CellValue::Int(10), CellValue::Int(5)],
+    /// ]);
+    /// wide.name_columns_by_row(0).unwrap();
+    ///
+    /// let long = wide.unpivot(&["region"], &[], "month", "amount").unwrap();
+    /// assert_eq!(long.column_names().unwrap(), &["region", "month", "amount"]);
+    /// assert_eq!(long.row_count(), 3);
+    /// assert_eq!(long.get(2, 1).unwrap().as_str(), "Feb");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if columns are not named or a column is missing.
+    pub fn unpivot(
+        &self,
+        id_cols: &[&str],
+        value_cols: &[&str],
+        var_name: &str,
+        value_name: &str,
+    ) -> Result<Sheet> {
+        let id_idx = id_cols
+            .iter()
+            .map(|name| self.column_index_by_name(name))
+            .collect::<Result<Vec<_>>>()?;
+        let value_columns: Vec<(usize, String)> = if value_cols.is_empty() {
+            let names = self
+                .column_names()
+                .ok_or_else(|| SheetError::ColumnsNotNamed("Cannot unpivot".to_string()))?;
+            names
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !id_idx.contains(i))
+                .map(|(i, name)| (i, name.clone()))
+                .collect()
+        } else {
+            value_cols
+                .iter()
+                .map(|&name| Ok((self.column_index_by_name(name)?, name.to_string())))
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        let mut header: Vec<CellValue> = id_cols.iter().map(|&n| n.into()).collect();
+        header.push(var_name.into());
+        header.push(value_name.into());
+
+        let body = &self.data()[self.data_start_row()..];
+        let mut data = Vec::with_capacity(body.len() * value_columns.len() + 1);
+        data.push(header);
+        for row in body {
+            let cell = |idx: usize| row.get(idx).cloned().unwrap_or(CellValue::Null);
+            for (idx, name) in &value_columns {
+                let mut out: Vec<CellValue> = id_idx.iter().map(|&i| cell(i)).collect();
+                out.push(name.as_str().into());
+                out.push(cell(*idx));
+                data.push(out);
+            }
+        }
+
+        let mut result = Sheet::from_data(data);
+        result.set_name(&format!("{}_unpivot", self.name()));
+        result.name_columns_by_row(0)?;
+        Ok(result)
+    }
+}
+
+fn column_label(value: &CellValue) -> CellValue {
+    if value.is_null() {
+        BLANK_LABEL.into()
+    } else {
+        value.as_str().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sales() -> Sheet {
+        let mut sheet = Sheet::from_data(vec![
+            vec![
+                CellValue::from("region"),
+                "rep".into(),
+                "month".into(),
+                "amount".into(),
+            ],
+            vec!["East".into(), "Ann".into(), "Feb".into(), CellValue::Int(5)],
+            vec!["West".into(), "Bo".into(), "Jan".into(), CellValue::Int(7)],
+            vec![
+                "East".into(),
+                "Ann".into(),
+                "Jan".into(),
+                CellValue::Int(10),
+            ],
+            vec![
+                "East".into(),
+                "Cy".into(),
+                "Jan".into(),
+                CellValue::Float(2.5),
+            ],
+            vec!["West".into(), "Bo".into(), "Jan".into(), CellValue::Null],
+        ]);
+        sheet.name_columns_by_row(0).unwrap();
+        sheet
+    }
+
+    fn row(sheet: &Sheet, index: usize) -> Vec<String> {
+        sheet
+            .row(index)
+            .unwrap()
+            .iter()
+            .map(CellValue::as_str)
+            .collect()
+    }
+
+    #[test]
+    fn test_pivot_sum_keeps_first_appearance_order() {
+        let pivot = sales()
+            .pivot(&["region"], "month", "amount", Aggregation::Sum)
+            .unwrap();
+        assert_eq!(pivot.column_names().unwrap(), &["region", "Feb", "Jan"]);
+        assert_eq!(row(&pivot, 1), vec!["East", "5", "12.5"]);
+        assert_eq!(row(&pivot, 2), vec!["West", "", "7"]);
+        assert!(matches!(pivot.get(2, 2).unwrap(), CellValue::Int(7)));
+    }
+
+    #[test]
+    fn test_pivot_aggregations() {
+        let sheet = sales();
+        let cell = |agg: Aggregation| {
+            let pivot = sheet.pivot(&["region"], "month", "amount", agg).unwrap();
+            pivot.get(1, 2).unwrap().clone()
+        };
+        assert!(matches!(cell(Aggregation::Count), CellValue::Int(2)));
+        assert!(matches!(cell(Aggregation::Avg), CellValue::Float(f) if (f - 6.25).abs() < 1e-9));
+        assert!(matches!(cell(Aggregation::Min), CellValue::Float(f) if (f - 2.5).abs() < 1e-9));
+        assert!(matches!(cell(Aggregation::Max), CellValue::Int(10)));
+        assert!(matches!(cell(Aggregation::First), CellValue::Int(10)));
+    }
+
+    #[test]
+    fn test_pivot_multiple_index_columns_with_totals() {
+        let options = PivotOptions::new()
+            .with_grand_totals(true)
+            .with_sort_columns(true);
+        let pivot = sales()
+            .pivot_with_options(
+                &["region", "rep"],
+                "month",
+                "amount",
+                Aggregation::Avg,
+                &options,
+            )
+            .unwrap();
+        assert_eq!(
+            pivot.column_names().unwrap(),
+            &["region", "rep", "Feb", "Jan", "Total"]
+        );
+        assert_eq!(row(&pivot, 1), vec!["East", "Ann", "5", "10", "7.5"]);
+        // Totals average the source values, not the cell averages
+        assert_eq!(row(&pivot, 4), vec!["Total", "", "5", "6.5", "6.125"]);
+    }
+
+    #[test]
+    fn test_pivot_errors() {
+        let err = sales()
+            .pivot(&["region"], "month", "rep", Aggregation::Sum)
+            .unwrap_err();
+        assert!(err.to_string().contains("non-numeric value 'Ann'"), "{err}");
+
+        let err = sales()
+            .pivot(&["missing"], "month", "amount", Aggregation::Sum)
+            .unwrap_err();
+        assert!(matches!(err, SheetError::ColumnNotFound { .. }));
+
+        let err = "mode".parse::<Aggregation>().unwrap_err();
+        assert!(err.to_string().contains("Unknown aggregation 'mode'"));
+    }
+
+    #[test]
+    fn test_unpivot_round_trips_pivot() {
+        let pivot = sales()
+            .pivot(&["region"], "month", "amount", Aggregation::Sum)
+            .unwrap();
+        let long = pivot
+            .unpivot(&["region"], &["Jan", "Feb"], "month", "total")
+            .unwrap();
+        assert_eq!(long.column_names().unwrap(), &["region", "month", "total"]);
+        assert_eq!(long.row_count(), 5);
+        assert_eq!(row(&long, 1), vec!["East", "Jan", "12.5"]);
+        assert_eq!(row(&long, 2), vec!["East", "Feb", "5"]);
+        assert_eq!(row(&long, 4), vec!["West", "Feb", ""]);
+    }
+}
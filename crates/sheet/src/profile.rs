@@ -0,0 +1,415 @@
+//! Column profiling: a one-row-per-column summary of a sheet's shape.
+
+use crate::a1_notation::column_index_to_letters;
+use crate::cell::CellValue;
+use crate::error::Result;
+use crate::schema::{infer_type, ColumnType};
+use crate::sheet::Sheet;
+use indexmap::IndexMap;
+use std::cmp::Ordering;
+
+/// Columns of the sheet returned by [`Sheet::profile`].
+const PROFILE_COLUMNS: [&str; 15] = [
+    "column",
+    "type",
+    "count",
+    "nulls",
+    "null_pct",
+    "distinct",
+    "min",
+    "max",
+    "mean",
+    "stddev",
+    "min_length",
+    "max_length",
+    "mean_length",
+    "top_values",
+    "histogram",
+];
+
+/// Options for [`Sheet::profile_with_options`].
+#[derive(Debug, Clone)]
+pub struct ProfileOptions {
+    /// Number of most frequent values listed per column; 0 leaves the
+    /// `top_values` column empty.
+    pub top_k: usize,
+    /// Number of equal-width histogram bins for numeric columns; 0 leaves
+    /// the `histogram` column empty.
+    pub bins: usize,
+}
+
+impl Default for ProfileOptions {
+    fn default() -> Self {
+        Self { top_k: 5, bins: 10 }
+    }
+}
+
+impl ProfileOptions {
+    /// Create default options: the top 5 values and 10 histogram bins.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// List the `top_k` most frequent values of each column.
+    #[must_use]
+    pub fn with_top_k(mut self, top_k: usize) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    /// Split numeric columns into `bins` histogram bins.
+    #[must_use]
+    pub fn with_bins(mut self, bins: usize) -> Self {
+        self.bins = bins;
+        self
+    }
+}
+
+/// A number rounded to four decimals for display in a summary string.
+fn short(value: f64) -> String {
+    let rounded = (value * 1e4).round() / 1e4;
+    format!("{}", rounded + 0.0)
+}
+
+/// Order two values already coerced to `column_type`.
+fn compare(column_type: ColumnType, a: &CellValue, b: &CellValue) -> Ordering {
+    match column_type {
+        ColumnType::Int | ColumnType::Float => a
+            .as_float()
+            .partial_cmp(&b.as_float())
+            .unwrap_or(Ordering::Equal),
+        ColumnType::Date | ColumnType::DateTime => a.as_datetime().cmp(&b.as_datetime()),
+        ColumnType::Duration => a.as_duration().cmp(&b.as_duration()),
+        ColumnType::Bool | ColumnType::String => a.as_str().cmp(&b.as_str()),
+    }
+}
+
+/// Mean and sample standard deviation; the deviation needs two values.
+fn mean_stddev(values: &[f64]) -> (Option<f64>, Option<f64>) {
+    if values.is_empty() {
+        return (None, None);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    if values.len() < 2 {
+        return (Some(mean), None);
+    }
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (Some(mean), Some(variance.sqrt()))
+}
+
+/// Equal-width bins between the smallest and largest value, rendered as
+/// `[lo, hi): n` with the last bin closed. Integer columns get whole-number
+/// bin widths, and no more bins than there are integers in the range.
+fn histogram(values: &[f64], bins: usize, integral: bool) -> String {
+    if values.is_empty() || bins == 0 {
+        return String::new();
+    }
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let (bins, width) = if max <= min {
+        // A constant column has nothing to split
+        (1, 0.0)
+    } else if integral {
+        let width = ((max - min + 1.0) / bins as f64).ceil();
+        (((max - min + 1.0) / width).ceil() as usize, width)
+    } else {
+        (bins, (max - min) / bins as f64)
+    };
+    let mut counts = vec![0usize; bins];
+    for value in values {
+        let bin = if width > 0.0 {
+            (((value - min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        counts[bin] += 1;
+    }
+    counts
+        .iter()
+        .enumerate()
+        .map(|(bin, count)| {
+            let lo = min + width * bin as f64;
+            let hi = if bin + 1 == bins { max } else { lo + width };
+            let close = if bin + 1 == bins { ']' } else { ')' };
+            format!("[{}, {}{close}: {count}", short(lo), short(hi))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// One row of the profile for the column `name` holding `values`.
+fn profile_column(name: &str, values: &[&CellValue], options: &ProfileOptions) -> Vec<CellValue> {
+    let present: Vec<&CellValue> = values
+        .iter()
+        .copied()
+        .filter(|v| !v.is_null() && !v.as_str().trim().is_empty())
+        .collect();
+    let column_type = infer_type(&present, 1.0);
+    // Every present value fits the inferred type, so coercion keeps them all
+    let typed: Vec<CellValue> = present
+        .iter()
+        .filter_map(|v| column_type.coerce(v))
+        .collect();
+
+    let nulls = values.len() - typed.len();
+    let null_pct = if values.is_empty() {
+        0.0
+    } else {
+        (nulls as f64 * 10_000.0 / values.len() as f64).round() / 100.0
+    };
+
+    let mut counts: IndexMap<String, usize> = IndexMap::new();
+    for value in &typed {
+        *counts.entry(value.as_str()).or_default() += 1;
+    }
+    let mut frequent: Vec<(&String, &usize)> = counts.iter().collect();
+    // Stable, so ties keep the order values first appeared in
+    frequent.sort_by(|a, b| b.1.cmp(a.1));
+    let top_values = frequent
+        .iter()
+        .take(options.top_k)
+        .map(|(value, count)| format!("{value} ({count})"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let min = typed
+        .iter()
+        .min_by(|a, b| compare(column_type, a, b))
+        .cloned()
+        .unwrap_or(CellValue::Null);
+    let max = typed
+        .iter()
+        .max_by(|a, b| compare(column_type, a, b))
+        .cloned()
+        .unwrap_or(CellValue::Null);
+
+    let numbers: Vec<f64> = if matches!(column_type, ColumnType::Int | ColumnType::Float) {
+        typed.iter().filter_map(CellValue::as_float).collect()
+    } else {
+        Vec::new()
+    };
+    let (mean, stddev) = mean_stddev(&numbers);
+
+    let lengths: Vec<usize> = if column_type == ColumnType::String {
+        typed.iter().map(|v| v.as_str().chars().count()).collect()
+    } else {
+        Vec::new()
+    };
+    let length =
+        |value: Option<&usize>| value.map_or(CellValue::Null, |n| CellValue::Int(*n as i64));
+    let mean_length = if lengths.is_empty() {
+        CellValue::Null
+    } else {
+        CellValue::Float(lengths.iter().sum::<usize>() as f64 / lengths.len() as f64)
+    };
+    let float = |value: Option<f64>| value.map_or(CellValue::Null, CellValue::Float);
+
+    vec![
+        CellValue::String(name.to_string()),
+        CellValue::String(column_type.to_string()),
+        CellValue::Int(typed.len() as i64),
+        CellValue::Int(nulls as i64),
+        CellValue::Float(null_pct),
+        CellValue::Int(counts.len() as i64),
+        min,
+        max,
+        float(mean),
+        float(stddev),
+        length(lengths.iter().min()),
+        length(lengths.iter().max()),
+        mean_length,
+        CellValue::String(top_values),
+        CellValue::String(histogram(
+            &numbers,
+            options.bins,
+            column_type == ColumnType::Int,
+        )),
+    ]
+}
+
+impl Sheet {
+    /// Profile every column with default options; see
+    /// [`Sheet::profile_with_options`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{CellValue, Sheet};
+    ///
+    /// let mut sheet = Sheet::from_data(vec![
+    ///     vec!["city", "temp"],
+    ///     vec!["Oslo", "4"],
+    ///     vec!["Lima", ""],
+    ///     vec!["Oslo", "18"],
+    /// ]);
+    /// sheet.name_columns_by_row(0).unwrap();
+    ///
+    /// let profile = sheet.profile().unwrap();
+    /// assert_eq!(profile.get_by_name(1, "top_values").unwrap().as_str(), "Oslo (2), Lima (1)");
+    /// assert_eq!(profile.get_by_name(2, "type").unwrap().as_str(), "int");
+    /// assert_eq!(profile.get_by_name(2, "mean").unwrap(), &CellValue::Float(11.0));
+    /// ```
+    pub fn profile(&self) -> Result<Sheet> {
+        self.profile_with_options(&ProfileOptions::default())
+    }
+
+    /// Summarize each column as one row of a new sheet: the inferred type
+    /// (as [`Sheet::infer_schema`] would with confidence 1.0), non-null
+    /// `count`, `nulls` and `null_pct` (blank text counts as null),
+    /// `distinct` values, and `min` and `max`. Numeric columns also get
+    /// `mean`, sample `stddev` and a `histogram`; string columns get
+    /// `min_length`, `max_length` and `mean_length` in characters.
+    /// `top_values` lists the most frequent values as `value (count)`.
+    /// Columns without names are labelled by letter.
+    pub fn profile_with_options(&self, options: &ProfileOptions) -> Result<Sheet> {
+        let rows = &self.data()[self.data_start_row().min(self.row_count())..];
+        let mut data = vec![PROFILE_COLUMNS.iter().map(|&c| c.into()).collect()];
+        for col in 0..self.col_count() {
+            let name = self
+                .column_names()
+                .and_then(|names| names.get(col).cloned())
+                .unwrap_or_else(|| column_index_to_letters(col));
+            let values: Vec<&CellValue> = rows
+                .iter()
+                .map(|row| {
+                    row.get(col)
+                        .map_or(&CellValue::Null, CellValue::cached_or_self)
+                })
+                .collect();
+            data.push(profile_column(&name, &values, options));
+        }
+
+        let mut profile = Sheet::from_data(data);
+        profile.set_name(&format!("{}_profile", self.name()));
+        profile.name_columns_by_row(0)?;
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: &CellValue, b: f64) -> bool {
+        a.as_float().is_some_and(|a| (a - b).abs() < 1e-9)
+    }
+
+    fn orders() -> Sheet {
+        let mut sheet = Sheet::from_data(vec![
+            vec!["id".into(), "status".into(), "amount".into(), "day".into()],
+            vec![
+                CellValue::Int(1),
+                "open".into(),
+                CellValue::Float(10.0),
+                "2024-01-03".into(),
+            ],
+            vec![
+                CellValue::Int(2),
+                "closed".into(),
+                CellValue::Float(20.0),
+                "2024-01-01".into(),
+            ],
+            vec![CellValue::Int(3), "open".into(), CellValue::Null, "".into()],
+            vec![
+                CellValue::Int(4),
+                "  ".into(),
+                CellValue::Float(40.0),
+                "2024-02-10".into(),
+            ],
+        ]);
+        sheet.name_columns_by_row(0).unwrap();
+        sheet
+    }
+
+    #[test]
+    fn test_profile_counts_and_types() {
+        let profile = orders().profile().unwrap();
+        assert_eq!(profile.column_names().unwrap(), &PROFILE_COLUMNS);
+        assert_eq!(profile.row_count(), 5);
+
+        let types: Vec<String> = profile
+            .column_by_name("type")
+            .unwrap()
+            .iter()
+            .skip(1)
+            .map(CellValue::as_str)
+            .collect();
+        assert_eq!(types, ["int", "string", "float", "date"]);
+
+        assert_eq!(profile.get_by_name(2, "nulls").unwrap(), &CellValue::Int(1));
+        assert!(close(profile.get_by_name(2, "null_pct").unwrap(), 25.0));
+        assert_eq!(
+            profile.get_by_name(2, "distinct").unwrap(),
+            &CellValue::Int(2)
+        );
+        assert_eq!(
+            profile.get_by_name(2, "top_values").unwrap().as_str(),
+            "open (2), closed (1)"
+        );
+        assert_eq!(
+            profile.get_by_name(2, "max_length").unwrap(),
+            &CellValue::Int(6)
+        );
+        assert!(profile.get_by_name(2, "mean").unwrap().is_null());
+
+        // Dates order by time, not by their text
+        assert_eq!(
+            profile.get_by_name(4, "min").unwrap().as_str(),
+            "2024-01-01"
+        );
+        assert_eq!(
+            profile.get_by_name(4, "max").unwrap().as_str(),
+            "2024-02-10"
+        );
+    }
+
+    #[test]
+    fn test_profile_numeric_stats() {
+        let profile = orders()
+            .profile_with_options(&ProfileOptions::new().with_bins(3).with_top_k(1))
+            .unwrap();
+        assert!(close(profile.get_by_name(3, "min").unwrap(), 10.0));
+        assert!(close(profile.get_by_name(3, "max").unwrap(), 40.0));
+        assert!(close(profile.get_by_name(3, "mean").unwrap(), 70.0 / 3.0));
+        assert!(close(
+            profile.get_by_name(3, "stddev").unwrap(),
+            (700.0_f64 / 3.0).sqrt()
+        ));
+        assert_eq!(
+            profile.get_by_name(3, "histogram").unwrap().as_str(),
+            "[10, 20): 1; [20, 30): 1; [30, 40]: 1"
+        );
+        assert_eq!(
+            profile.get_by_name(1, "top_values").unwrap().as_str(),
+            "1 (1)"
+        );
+    }
+
+    #[test]
+    fn test_profile_edge_cases() {
+        let profile = Sheet::from_data(vec![vec![CellValue::Int(5)], vec![CellValue::Int(5)]])
+            .profile()
+            .unwrap();
+        // Unnamed columns are labelled by letter and every row is data
+        assert_eq!(profile.get_by_name(1, "column").unwrap().as_str(), "A");
+        assert_eq!(profile.get_by_name(1, "count").unwrap(), &CellValue::Int(2));
+        assert!(close(profile.get_by_name(1, "stddev").unwrap(), 0.0));
+        assert_eq!(
+            profile.get_by_name(1, "histogram").unwrap().as_str(),
+            "[5, 5]: 2"
+        );
+
+        let ids = orders()
+            .profile_with_options(&ProfileOptions::new().with_bins(3))
+            .unwrap();
+        assert_eq!(
+            ids.get_by_name(1, "histogram").unwrap().as_str(),
+            "[1, 3): 2; [3, 4]: 2"
+        );
+
+        let empty = Sheet::new().profile().unwrap();
+        assert_eq!(empty.row_count(), 1);
+    }
+}
@@ -0,0 +1,688 @@
+//! Column schemas: per-column type, nullability and display format.
+//!
+//! A schema is optional. When a sheet has one, writers use its types instead
+//! of guessing from the data, so a zip code column declared as `String` stays
+//! text and a mixed column fails when the schema is applied rather than
+//! halfway through an export.
+
+use crate::cell::CellValue;
+use crate::error::{Result, SheetError};
+use crate::sheet::Sheet;
+use arrow::datatypes::{DataType, TimeUnit};
+use chrono::NaiveTime;
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+/// Number of failures spelled out in a schema error message.
+const FAILURES_IN_MESSAGE: usize = 5;
+
+/// The type of a column's values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Text; any value converts to its string form.
+    String,
+    /// 64-bit integers.
+    Int,
+    /// 64-bit floats.
+    Float,
+    /// Booleans.
+    Bool,
+    /// Calendar dates.
+    Date,
+    /// Dates with a time of day.
+    DateTime,
+    /// Elapsed time.
+    Duration,
+}
+
+impl FromStr for ColumnType {
+    type Err = SheetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "string" | "str" | "text" | "utf8" => Ok(Self::String),
+            "int" | "integer" | "int64" => Ok(Self::Int),
+            "float" | "double" | "number" | "float64" => Ok(Self::Float),
+            "bool" | "boolean" => Ok(Self::Bool),
+            "date" => Ok(Self::Date),
+            "datetime" | "timestamp" => Ok(Self::DateTime),
+            "duration" | "interval" => Ok(Self::Duration),
+            _ => Err(SheetError::Schema(format!(
+                "Unknown column type '{s}', expected string, int, float, bool, date, datetime or duration"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::String => "string",
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::Bool => "bool",
+            Self::Date => "date",
+            Self::DateTime => "datetime",
+            Self::Duration => "duration",
+        })
+    }
+}
+
+impl ColumnType {
+    /// The Arrow type used for this column when exporting.
+    #[must_use]
+    pub fn data_type(self) -> DataType {
+        match self {
+            Self::String => DataType::Utf8,
+            Self::Int => DataType::Int64,
+            Self::Float => DataType::Float64,
+            Self::Bool => DataType::Boolean,
+            Self::Date => DataType::Date32,
+            Self::DateTime => DataType::Timestamp(TimeUnit::Microsecond, None),
+            Self::Duration => DataType::Duration(TimeUnit::Microsecond),
+        }
+    }
+
+    /// Convert `cell` to this type, or `None` if it cannot be converted
+    /// without losing information. Nulls stay null, as do blank strings in
+    /// non-string columns.
+    #[must_use]
+    pub fn coerce(self, cell: &CellValue) -> Option<CellValue> {
+        let cell = cell.cached_or_self();
+        if cell.is_null() {
+            return Some(CellValue::Null);
+        }
+        match (self, cell) {
+            (Self::String, CellValue::String(_)) => Some(cell.clone()),
+            (Self::String, _) => Some(CellValue::String(cell.as_str())),
+            (_, CellValue::String(s)) if s.trim().is_empty() => Some(CellValue::Null),
+
+            (Self::Int, CellValue::Int(_)) => Some(cell.clone()),
+            (Self::Int, CellValue::Float(f)) if f.fract() == 0.0 && f.abs() < 9.0e15 => {
+                Some(CellValue::Int(*f as i64))
+            }
+            (Self::Int, CellValue::String(s)) => s.trim().parse().ok().map(CellValue::Int),
+
+            (Self::Float, CellValue::Float(_)) => Some(cell.clone()),
+            (Self::Float, CellValue::Int(i)) => Some(CellValue::Float(*i as f64)),
+            (Self::Float, CellValue::String(s)) => s.trim().parse().ok().map(CellValue::Float),
+
+            (Self::Bool, CellValue::Bool(_)) => Some(cell.clone()),
+            (Self::Bool, CellValue::Int(0)) => Some(CellValue::Bool(false)),
+            (Self::Bool, CellValue::Int(1)) => Some(CellValue::Bool(true)),
+            (Self::Bool, CellValue::String(s)) => match s.trim().to_lowercase().as_str() {
+                "true" | "yes" => Some(CellValue::Bool(true)),
+                "false" | "no" => Some(CellValue::Bool(false)),
+                _ => None,
+            },
+
+            // Text must be ISO 8601; a date-time converts to a date only at midnight
+            (Self::Date, CellValue::Date(_)) => Some(cell.clone()),
+            (Self::Date, CellValue::DateTime(dt)) if dt.time() == NaiveTime::MIN => {
+                Some(CellValue::Date(dt.date()))
+            }
+            (Self::DateTime, CellValue::Date(_) | CellValue::DateTime(_)) => {
+                cell.as_datetime().map(CellValue::DateTime)
+            }
+            (Self::Duration, CellValue::Duration(_)) => Some(cell.clone()),
+            (Self::Date | Self::DateTime | Self::Duration, CellValue::String(s)) => {
+                CellValue::parse_iso_temporal(s).and_then(|parsed| self.coerce(&parsed))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether a non-null cell looks like this type for inference. Stricter
+    /// than [`ColumnType::coerce`], which also accepts 3.0 as an int and 1 as
+    /// a bool.
+    fn fits(self, cell: &CellValue) -> bool {
+        match (self, cell) {
+            (Self::Int, CellValue::Float(_)) | (Self::Bool, CellValue::Int(_)) => false,
+            _ => self.coerce(cell).is_some(),
+        }
+    }
+}
+
+/// Text such as "00123" that would lose its leading zeros as a number.
+fn is_zero_padded(cell: &CellValue) -> bool {
+    let CellValue::String(s) = cell else {
+        return false;
+    };
+    let digits = s.trim();
+    digits.len() > 1 && digits.starts_with('0') && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// The most specific type that at least `confidence` of the `present`
+/// (non-null, non-blank) values fit; see [`Sheet::infer_schema`].
+pub(crate) fn infer_type(present: &[&CellValue], confidence: f64) -> ColumnType {
+    let identifiers = present.iter().any(|v| is_zero_padded(v));
+    [
+        ColumnType::Bool,
+        ColumnType::Int,
+        ColumnType::Float,
+        ColumnType::Date,
+        ColumnType::DateTime,
+        ColumnType::Duration,
+    ]
+    .into_iter()
+    .find(|column_type| {
+        let fits = present.iter().filter(|v| column_type.fits(v)).count();
+        !identifiers && !present.is_empty() && fits as f64 >= confidence * present.len() as f64
+    })
+    .unwrap_or(ColumnType::String)
+}
+
+/// Type, nullability and format of one column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSchema {
+    name: String,
+    column_type: ColumnType,
+    nullable: bool,
+    format: Option<String>,
+}
+
+impl ColumnSchema {
+    /// A nullable column of `column_type` with no format.
+    pub fn new(name: impl Into<String>, column_type: ColumnType) -> Self {
+        Self {
+            name: name.into(),
+            column_type,
+            nullable: true,
+            format: None,
+        }
+    }
+
+    /// Set whether nulls are allowed.
+    #[must_use]
+    pub fn nullable(mut self, nullable: bool) -> Self {
+        self.nullable = nullable;
+        self
+    }
+
+    /// Set an Excel number format such as `"0.00"` or `"#,##0"`, used when
+    /// writing XLSX files.
+    #[must_use]
+    pub fn format(mut self, format: impl Into<String>) -> Self {
+        self.format = Some(format.into());
+        self
+    }
+
+    /// Column name.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Column type.
+    #[must_use]
+    pub fn column_type(&self) -> ColumnType {
+        self.column_type
+    }
+
+    /// Whether nulls are allowed.
+    #[must_use]
+    pub fn is_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    /// Number format, if any.
+    #[must_use]
+    pub fn number_format(&self) -> Option<&str> {
+        self.format.as_deref()
+    }
+}
+
+/// Column schemas for a sheet, looked up by column name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SheetSchema {
+    columns: Vec<ColumnSchema>,
+}
+
+impl SheetSchema {
+    /// A schema with the given columns.
+    #[must_use]
+    pub fn new(columns: Vec<ColumnSchema>) -> Self {
+        Self { columns }
+    }
+
+    /// Add or replace a column.
+    #[must_use]
+    pub fn with_column(mut self, column: ColumnSchema) -> Self {
+        match self.columns.iter_mut().find(|c| c.name == column.name) {
+            Some(existing) => *existing = column,
+            None => self.columns.push(column),
+        }
+        self
+    }
+
+    /// All columns, in the order they were added.
+    #[must_use]
+    pub fn columns(&self) -> &[ColumnSchema] {
+        &self.columns
+    }
+
+    /// The schema for `name`, if declared.
+    #[must_use]
+    pub fn column(&self, name: &str) -> Option<&ColumnSchema> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+}
+
+/// A cell that did not fit its column's schema.
+#[derive(Debug, Clone)]
+pub struct CoercionFailure {
+    /// Row index in the sheet, counting the header row.
+    pub row: usize,
+    /// Column name.
+    pub column: String,
+    /// The value as found.
+    pub value: CellValue,
+    /// The declared type.
+    pub expected: ColumnType,
+}
+
+impl fmt::Display for CoercionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.value.is_null() {
+            write!(
+                f,
+                "row {}, column '{}': null in a non-nullable column",
+                self.row, self.column
+            )
+        } else {
+            write!(
+                f,
+                "row {}, column '{}': '{}' is not {}",
+                self.row,
+                self.column,
+                self.value.as_str(),
+                self.expected
+            )
+        }
+    }
+}
+
+/// Cells that failed coercion when a schema was checked or applied.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaReport {
+    /// Failures in row order.
+    pub failures: Vec<CoercionFailure>,
+}
+
+impl SchemaReport {
+    /// True when every cell fits the schema.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// The failures as a sheet with `row`, `column`, `value` and `expected`
+    /// columns.
+    pub fn to_sheet(&self) -> Result<Sheet> {
+        let mut data = vec![vec![
+            CellValue::from("row"),
+            "column".into(),
+            "value".into(),
+            "expected".into(),
+        ]];
+        data.extend(self.failures.iter().map(|failure| {
+            vec![
+                CellValue::Int(failure.row as i64),
+                CellValue::String(failure.column.clone()),
+                failure.value.clone(),
+                CellValue::String(failure.expected.to_string()),
+            ]
+        }));
+        let mut sheet = Sheet::from_data(data);
+        sheet.set_name("schema_report");
+        sheet.name_columns_by_row(0)?;
+        Ok(sheet)
+    }
+
+    pub(crate) fn into_error(self) -> SheetError {
+        let mut message = format!("{} cell(s) do not match the schema: ", self.failures.len());
+        let shown: Vec<String> = self
+            .failures
+            .iter()
+            .take(FAILURES_IN_MESSAGE)
+            .map(ToString::to_string)
+            .collect();
+        message.push_str(&shown.join("; "));
+        if self.failures.len() > FAILURES_IN_MESSAGE {
+            message.push_str("; ...");
+        }
+        SheetError::Schema(message)
+    }
+}
+
+impl Sheet {
+    /// Infer a schema from the data. A column gets the most specific type
+    /// (bool, then int, then float) that at least `confidence` of its
+    /// non-null values fit, and is string otherwise. `confidence` must be
+    /// in `(0, 1]`; 1.0 requires every value to fit. Columns with
+    /// zero-padded numbers such as "00123" are always strings.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{ColumnType, Sheet};
+    ///
+    /// let mut sheet = Sheet::from_data(vec![
+    ///     vec!["zip", "qty"],
+    ///     vec!["00123", "4"],
+    ///     vec!["10001", "n/a"],
+    ///     vec!["94105", "7"],
+    /// ]);
+    /// sheet.name_columns_by_row(0).unwrap();
+    ///
+    /// let schema = sheet.infer_schema(0.6).unwrap();
+    /// assert_eq!(schema.column("zip").unwrap().column_type(), ColumnType::String);
+    /// assert_eq!(schema.column("qty").unwrap().column_type(), ColumnType::Int);
+    /// ```
+    pub fn infer_schema(&self, confidence: f64) -> Result<SheetSchema> {
+        if !(confidence > 0.0 && confidence <= 1.0) {
+            return Err(SheetError::Schema(format!(
+                "Confidence must be greater than 0 and at most 1, got {confidence}"
+            )));
+        }
+        let names = self.column_names().ok_or_else(|| {
+            SheetError::ColumnsNotNamed("Call name_columns_by_row() first".to_string())
+        })?;
+        let rows = &self.data()[self.data_start_row()..];
+
+        let columns = names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                let values: Vec<&CellValue> = rows
+                    .iter()
+                    .map(|row| {
+                        row.get(idx)
+                            .map_or(&CellValue::Null, CellValue::cached_or_self)
+                    })
+                    .collect();
+                let present: Vec<&CellValue> = values
+                    .iter()
+                    .copied()
+                    .filter(|v| !v.is_null() && !v.as_str().trim().is_empty())
+                    .collect();
+                let column_type = infer_type(&present, confidence);
+                ColumnSchema::new(name.clone(), column_type).nullable(present.len() < values.len())
+            })
+            .collect();
+        Ok(SheetSchema::new(columns))
+    }
+
+    /// Check every cell against `schema` without changing the sheet.
+    pub fn schema_report(&self, schema: &SheetSchema) -> Result<SchemaReport> {
+        let mut copy = self.clone();
+        copy.coerce_columns(schema)
+    }
+
+    /// Convert each declared column to its type and attach `schema`, which
+    /// writers then use instead of inferring types. Cells that cannot be
+    /// converted, and nulls in non-nullable columns, are left as they are
+    /// and listed in the returned report.
+    pub fn apply_schema(&mut self, schema: SheetSchema) -> Result<SchemaReport> {
+        let columnar = self.is_columnar();
+        let report = self.coerce_columns(&schema)?;
+        if columnar {
+            // Coercion works on rows; the coerced columns now fit typed arrays
+            self.compact();
+        }
+        self.set_schema(schema);
+        Ok(report)
+    }
+
+    /// This sheet with its schema enforced, for writers. Borrowed when there
+    /// is no schema; an error listing the failures when cells do not fit.
+    pub fn coerced_to_schema(&self) -> Result<Cow<'_, Sheet>> {
+        let Some(schema) = self.schema() else {
+            return Ok(Cow::Borrowed(self));
+        };
+        // Columns dropped or renamed since the schema was applied are skipped
+        let present = SheetSchema::new(
+            schema
+                .columns()
+                .iter()
+                .filter(|c| self.column_index_by_name(&c.name).is_ok())
+                .cloned()
+                .collect(),
+        );
+        let mut copy = self.clone();
+        let report = copy.coerce_columns(&present)?;
+        if report.is_clean() {
+            Ok(Cow::Owned(copy))
+        } else {
+            Err(report.into_error())
+        }
+    }
+
+    /// Arrow type of `column`: the schema type when declared.
+    pub(crate) fn schema_data_type(&self, column: &str) -> Option<DataType> {
+        self.schema()
+            .and_then(|schema| schema.column(column))
+            .map(|c| c.column_type.data_type())
+    }
+
+    /// Whether `column` may hold nulls: false only when declared so.
+    pub(crate) fn schema_nullable(&self, column: &str) -> bool {
+        self.schema()
+            .and_then(|schema| schema.column(column))
+            .is_none_or(|c| c.nullable)
+    }
+
+    fn coerce_columns(&mut self, schema: &SheetSchema) -> Result<SchemaReport> {
+        let targets = schema
+            .columns()
+            .iter()
+            .map(|column| Ok((self.column_index_by_name(&column.name)?, column)))
+            .collect::<Result<Vec<_>>>()?;
+        let start = self.data_start_row();
+
+        let mut report = SchemaReport::default();
+        for (row_idx, row) in self.data_mut().iter_mut().enumerate().skip(start) {
+            for (col_idx, column) in &targets {
+                if row.len() <= *col_idx {
+                    row.resize(*col_idx + 1, CellValue::Null);
+                }
+                let cell = &mut row[*col_idx];
+                match column.column_type.coerce(cell) {
+                    Some(value) if value.is_null() && !column.nullable => {}
+                    Some(value) => {
+                        *cell = value;
+                        continue;
+                    }
+                    None => {}
+                }
+                report.failures.push(CoercionFailure {
+                    row: row_idx,
+                    column: column.name.clone(),
+                    value: cell.cached_or_self().clone(),
+                    expected: column.column_type,
+                });
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet(rows: Vec<Vec<CellValue>>) -> Sheet {
+        let mut sheet = Sheet::from_data(rows);
+        sheet.name_columns_by_row(0).unwrap();
+        sheet
+    }
+
+    fn orders() -> Sheet {
+        sheet(vec![
+            vec!["zip".into(), "qty".into(), "price".into(), "paid".into()],
+            vec!["00123".into(), "4".into(), CellValue::Int(3), "yes".into()],
+            vec![
+                "10001".into(),
+                "x".into(),
+                CellValue::Float(2.5),
+                CellValue::Null,
+            ],
+            vec![
+                CellValue::Int(94105),
+                CellValue::Int(7),
+                "1.25".into(),
+                "false".into(),
+            ],
+        ])
+    }
+
+    #[test]
+    fn test_coerce() {
+        assert!(matches!(
+            ColumnType::Int.coerce(&"42".into()),
+            Some(CellValue::Int(42))
+        ));
+        assert!(matches!(
+            ColumnType::Int.coerce(&CellValue::Float(3.0)),
+            Some(CellValue::Int(3))
+        ));
+        assert!(ColumnType::Int.coerce(&CellValue::Float(3.5)).is_none());
+        assert_eq!(
+            ColumnType::String
+                .coerce(&CellValue::Int(7))
+                .unwrap()
+                .as_str(),
+            "7"
+        );
+        assert!(matches!(
+            ColumnType::Bool.coerce(&"No".into()),
+            Some(CellValue::Bool(false))
+        ));
+        assert!(ColumnType::Float.coerce(&CellValue::Bool(true)).is_none());
+        assert!(ColumnType::Float
+            .coerce(&CellValue::Null)
+            .unwrap()
+            .is_null());
+    }
+
+    #[test]
+    fn test_coerce_temporal() {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        assert_eq!(
+            ColumnType::Date.coerce(&"2024-01-15".into()),
+            Some(CellValue::Date(date))
+        );
+        assert_eq!(
+            ColumnType::DateTime.coerce(&CellValue::Date(date)),
+            Some(CellValue::DateTime(date.and_hms_opt(0, 0, 0).unwrap()))
+        );
+        assert!(ColumnType::Date
+            .coerce(&"2024-01-15T10:00:00".into())
+            .is_none());
+        assert!(ColumnType::Duration.coerce(&"PT90S".into()).is_some());
+        assert!(ColumnType::Date.coerce(&"15/01/2024".into()).is_none());
+        assert_eq!(
+            "timestamp".parse::<ColumnType>().unwrap(),
+            ColumnType::DateTime
+        );
+
+        let mut sheet = Sheet::from_data(vec![
+            vec!["due", "at"],
+            vec!["2024-01-15", "2024-01-15"],
+            vec!["2024-02-01", "2024-02-01 08:00"],
+        ]);
+        sheet.name_columns_by_row(0).unwrap();
+        let schema = sheet.infer_schema(1.0).unwrap();
+        assert_eq!(
+            schema.column("due").unwrap().column_type(),
+            ColumnType::Date
+        );
+        assert_eq!(
+            schema.column("at").unwrap().column_type(),
+            ColumnType::DateTime
+        );
+    }
+
+    #[test]
+    fn test_infer_schema_with_confidence() {
+        let data = orders();
+        let strict = data.infer_schema(1.0).unwrap();
+        assert_eq!(
+            strict.column("zip").unwrap().column_type(),
+            ColumnType::String
+        );
+        assert_eq!(
+            strict.column("qty").unwrap().column_type(),
+            ColumnType::String
+        );
+        assert_eq!(
+            strict.column("price").unwrap().column_type(),
+            ColumnType::Float
+        );
+        assert_eq!(
+            strict.column("paid").unwrap().column_type(),
+            ColumnType::Bool
+        );
+        assert!(strict.column("paid").unwrap().is_nullable());
+        assert!(!strict.column("qty").unwrap().is_nullable());
+
+        let loose = data.infer_schema(0.6).unwrap();
+        assert_eq!(loose.column("qty").unwrap().column_type(), ColumnType::Int);
+        assert_eq!(
+            loose.column("zip").unwrap().column_type(),
+            ColumnType::String
+        );
+
+        assert!(data.infer_schema(0.0).is_err());
+    }
+
+    #[test]
+    fn test_apply_schema_reports_failures() {
+        let mut data = orders();
+        let schema = SheetSchema::new(vec![
+            ColumnSchema::new("zip", ColumnType::String),
+            ColumnSchema::new("qty", ColumnType::Int),
+            ColumnSchema::new("paid", ColumnType::Bool).nullable(false),
+        ]);
+        let report = data.apply_schema(schema).unwrap();
+
+        assert!(matches!(data.get(1, 0).unwrap(), CellValue::String(s) if s == "00123"));
+        assert!(matches!(data.get(3, 0).unwrap(), CellValue::String(s) if s == "94105"));
+        assert!(matches!(data.get(1, 1).unwrap(), CellValue::Int(4)));
+        assert!(matches!(data.get(1, 3).unwrap(), CellValue::Bool(true)));
+
+        let failures: Vec<String> = report.failures.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            failures,
+            [
+                "row 2, column 'qty': 'x' is not int",
+                "row 2, column 'paid': null in a non-nullable column",
+            ]
+        );
+        assert_eq!(report.to_sheet().unwrap().row_count(), 3);
+        assert!(data.schema().is_some());
+
+        // Failing cells are kept, and writers refuse them
+        assert_eq!(data.get(2, 1).unwrap().as_str(), "x");
+        let err = data.coerced_to_schema().unwrap_err().to_string();
+        assert!(err.contains("2 cell(s) do not match the schema"), "{err}");
+    }
+
+    #[test]
+    fn test_schema_report_leaves_sheet_unchanged() {
+        let data = orders();
+        let schema = SheetSchema::default().with_column(ColumnSchema::new("qty", ColumnType::Int));
+        let report = data.schema_report(&schema).unwrap();
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(data.get(1, 1).unwrap().as_str(), "4");
+        assert!(data.schema().is_none());
+
+        let missing =
+            SheetSchema::default().with_column(ColumnSchema::new("nope", ColumnType::Int));
+        assert!(matches!(
+            data.schema_report(&missing),
+            Err(SheetError::ColumnNotFound { .. })
+        ));
+    }
+}
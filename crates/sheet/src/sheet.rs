@@ -1,7 +1,12 @@
 use crate::cell::CellValue;
+use crate::columnar::{Columns, Storage};
 use crate::error::{Result, SheetError};
+use crate::names::DefinedNames;
+use crate::schema::SheetSchema;
+use crate::spills::{Spill, MAX_SPILL_PASSES};
+use crate::tables::{find_table, Table};
 use indexmap::IndexMap;
-use piptable_formulas::{FormulaEngine, ValueResolver};
+use piptable_formulas::{FormulaEngine, SheetIdResolver, TableLocation, ValueResolver};
 use piptable_primitives::{CellAddress, CellRange, ErrorValue, Value};
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
@@ -55,15 +60,25 @@ pub enum ValidationRule {
     Regex(String),
 }
 
-/// A sheet representing a 2D grid of cells (row-major storage)
+/// A sheet representing a 2D grid of cells
+///
+/// Cells are stored by row or, for sheets read from files, as Arrow
+/// columns; the row API works the same on both (see [`Sheet::compact`]).
 #[derive(Debug, Clone)]
 pub struct Sheet {
     name: String,
-    data: Vec<Vec<CellValue>>,
+    data: Storage,
     column_names: Option<Vec<String>>,
     column_index: Option<HashMap<String, usize>>,
     row_names: Option<HashMap<String, usize>>,
+    schema: Option<SheetSchema>,
     formula_engine: FormulaEngine,
+    /// Sheet-scoped defined names
+    pub(crate) names: DefinedNames,
+    /// Tables reachable through structured references
+    pub(crate) tables: Vec<Table>,
+    /// Array formula results spilled over neighbouring cells, by anchor
+    pub(crate) spills: HashMap<CellAddress, Spill>,
 }
 
 impl Sheet {
@@ -78,11 +93,15 @@ impl Sheet {
     pub fn with_name(name: &str) -> Self {
         Sheet {
             name: name.to_string(),
-            data: Vec::new(),
+            data: Storage::default(),
             column_names: None,
             column_index: None,
             row_names: None,
+            schema: None,
             formula_engine: FormulaEngine::new(),
+            names: DefinedNames::new(),
+            tables: Vec::new(),
+            spills: HashMap::new(),
         }
     }
 
@@ -96,11 +115,15 @@ impl Sheet {
 
         Sheet {
             name: "Sheet1".to_string(),
-            data: converted,
+            data: Storage::Rows(converted),
             column_names: None,
             column_index: None,
             row_names: None,
+            schema: None,
             formula_engine: FormulaEngine::new(),
+            names: DefinedNames::new(),
+            tables: Vec::new(),
+            spills: HashMap::new(),
         }
     }
 
@@ -118,19 +141,19 @@ impl Sheet {
     /// Get the number of rows
     #[must_use]
     pub fn row_count(&self) -> usize {
-        self.data.len()
+        self.data.row_count()
     }
 
     /// Get the number of columns
     #[must_use]
     pub fn col_count(&self) -> usize {
-        self.data.first().map_or(0, Vec::len)
+        self.data.col_count()
     }
 
     /// Check if the sheet is empty
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
+        self.data.row_count() == 0
     }
 
     /// Remove duplicate rows based on the provided column names.
@@ -146,15 +169,16 @@ impl Sheet {
                 .collect::<Result<Vec<_>>>()?
         };
 
+        let data = self.data();
         let mut seen = HashSet::new();
-        let mut new_data = Vec::with_capacity(self.data.len());
+        let mut new_data = Vec::with_capacity(data.len());
         let mut removed = 0usize;
 
-        if start_row == 1 && !self.data.is_empty() {
-            new_data.push(self.data[0].clone());
+        if start_row == 1 && !data.is_empty() {
+            new_data.push(data[0].clone());
         }
 
-        for (_row_idx, row) in self.data.iter().enumerate().skip(start_row) {
+        for row in data.iter().skip(start_row) {
             let mut key = String::new();
             for &col in &indices {
                 let cell = row.get(col).unwrap_or(&CellValue::Null);
@@ -168,7 +192,7 @@ impl Sheet {
             }
         }
 
-        self.data = new_data;
+        self.data = Storage::Rows(new_data);
         self.invalidate_row_names();
         self.rebuild_formula_engine()?;
         Ok(removed)
@@ -197,7 +221,7 @@ impl Sheet {
         };
 
         let mut invalid = Vec::new();
-        for (row_idx, row) in self.data.iter().enumerate().skip(start_row) {
+        for (row_idx, row) in self.data().iter().enumerate().skip(start_row) {
             let cell = row.get(col_index).unwrap_or(&CellValue::Null);
             if !Self::cell_matches_rule(cell, &rule, phone_regex.as_ref(), custom_regex.as_ref()) {
                 invalid.push(row_idx);
@@ -208,7 +232,7 @@ impl Sheet {
 
     /// Clean data in-place using the provided options.
     pub fn clean_data(&mut self, options: &CleanOptions) -> Result<()> {
-        for row in &mut self.data {
+        for row in self.data_mut() {
             for cell in row {
                 if options.preserve_formulas && matches!(cell, CellValue::Formula(_)) {
                     continue;
@@ -241,7 +265,7 @@ impl Sheet {
         }
 
         for row_idx in start_row..=end_row {
-            if let Some(row) = self.data.get_mut(row_idx) {
+            if let Some(row) = self.data_mut().get_mut(row_idx) {
                 for col_idx in start_col..=end_col {
                     if let Some(cell) = row.get_mut(col_idx) {
                         if options.preserve_formulas && matches!(cell, CellValue::Formula(_)) {
@@ -307,15 +331,12 @@ impl Sheet {
 
     /// Get a cell value by row and column index (0-based)
     pub fn get(&self, row: usize, col: usize) -> Result<&CellValue> {
-        self.data
-            .get(row)
-            .and_then(|r| r.get(col))
-            .ok_or(SheetError::IndexOutOfBounds {
-                row,
-                col,
-                rows: self.row_count(),
-                cols: self.col_count(),
-            })
+        self.data.get(row, col).ok_or(SheetError::IndexOutOfBounds {
+            row,
+            col,
+            rows: self.row_count(),
+            cols: self.col_count(),
+        })
     }
 
     /// Get a mutable cell value by row and column index (0-based)
@@ -323,8 +344,7 @@ impl Sheet {
         let rows = self.row_count();
         let cols = self.col_count();
         self.data
-            .get_mut(row)
-            .and_then(|r| r.get_mut(col))
+            .get_mut(row, col)
             .ok_or(SheetError::IndexOutOfBounds {
                 row,
                 col,
@@ -335,14 +355,19 @@ impl Sheet {
 
     /// Set a cell value by row and column index (0-based)
     pub fn set<T: Into<CellValue>>(&mut self, row: usize, col: usize, value: T) -> Result<()> {
+        let addr = CellAddress::new(row as u32, col as u32);
         if matches!(self.get(row, col)?, CellValue::Formula(_)) {
-            self.formula_engine
-                .remove_formula(&CellAddress::new(row as u32, col as u32));
+            self.formula_engine.remove_formula(&addr);
+            let _ = self.formula_engine.set_spill(addr, &[]);
         }
+        let affected = self.spill_affected(addr);
+        self.spill_cell_edited(addr);
         let cell = self.get_mut(row, col)?;
         *cell = value.into();
-        self.formula_engine
-            .mark_dirty(&CellAddress::new(row as u32, col as u32));
+        self.formula_engine.mark_dirty(&addr);
+        for cell in affected {
+            self.formula_engine.mark_dirty(&cell);
+        }
         Ok(())
     }
 
@@ -398,12 +423,26 @@ impl Sheet {
     pub fn set_formula(&mut self, notation: &str, formula: &str) -> Result<()> {
         let addr = self.get_a1_addr(notation)?;
         let _ = self.get(addr.row as usize, addr.col as usize)?;
+        let affected = self.spill_affected(addr);
+        self.spill_cell_edited(addr);
+        for cell in affected {
+            self.formula_engine.mark_dirty(&cell);
+        }
         self.set_cell_value_raw(
             addr.row as usize,
             addr.col as usize,
             CellValue::formula(formula.to_string()),
         )?;
-        self.formula_engine.set_formula(addr, formula)?;
+        let tables = self.table_locations(None);
+        self.formula_engine.set_formula_with_sheet(
+            0,
+            addr,
+            formula,
+            Some(&SheetNames {
+                names: &self.names,
+                tables: &tables,
+            }),
+        )?;
         self.formula_engine.mark_dirty(&addr);
         Ok(())
     }
@@ -411,22 +450,56 @@ impl Sheet {
     /// Evaluate dirty formulas in dependency order and update cached results.
     ///
     /// Formula cells keep their source string and store the computed value in the cache.
+    /// Array results spill into the empty cells below and to the right of the formula.
     pub fn evaluate_formulas(&mut self) -> Result<()> {
-        let dirty = self.formula_engine.get_dirty_nodes()?;
-        for cell in dirty {
-            let Some(compiled) = self.formula_engine.get_formula(&cell) else {
-                continue;
-            };
-            let resolver = SheetValueResolver::new(self, Some(cell));
-            let value = self.formula_engine.evaluate(compiled, &resolver)?;
-            let cell_value = formula_value_to_cell_value(value);
-            if let Ok(CellValue::Formula(formula)) =
-                self.get_mut(cell.row as usize, cell.col as usize)
-            {
-                formula.cached = Some(Box::new(cell_value));
-                continue;
+        let mut engine = std::mem::take(&mut self.formula_engine);
+        let result = self.evaluate_with(&mut engine);
+        self.formula_engine = engine;
+        result
+    }
+
+    fn evaluate_with(&mut self, engine: &mut FormulaEngine) -> Result<()> {
+        for cell in self.release_orphaned_spills() {
+            engine.mark_dirty(&cell);
+        }
+        // Cells whose spilled values change are marked dirty, so formulas
+        // reading them run again in the next pass.
+        for _ in 0..MAX_SPILL_PASSES {
+            let dirty = engine.get_dirty_nodes()?;
+            if dirty.is_empty() {
+                break;
+            }
+            for cell in dirty {
+                let Some(compiled) = engine.get_formula(&cell) else {
+                    continue;
+                };
+                let resolver = SheetValueResolver::new(self, Some(cell));
+                let value = engine.evaluate(compiled, &resolver)?;
+                let changed = self.store_formula_result(cell, value, &mut |cells| {
+                    engine.set_spill(cell, cells).is_ok()
+                })?;
+                for addr in changed {
+                    engine.mark_dirty(&addr);
+                }
             }
-            self.set_cell_value_raw(cell.row as usize, cell.col as usize, cell_value)?;
+        }
+        Ok(())
+    }
+
+    /// Point formula references to sheet `old` at sheet `new`.
+    pub(crate) fn rename_sheet_references(&mut self, old: &str, new: &str) -> Result<()> {
+        let mut changed = self.names.rename_sheet_references(old, new);
+        self.data.for_each_cell_mut(|_, _, cell| {
+            if let CellValue::Formula(formula) = cell {
+                let source = piptable_formulas::rename_sheet_references(&formula.source, old, new);
+                if source != formula.source {
+                    formula.source = source;
+                    changed = true;
+                }
+            }
+        });
+        if changed {
+            self.rebuild_formula_engine()?;
         }
         Ok(())
     }
@@ -452,8 +525,7 @@ impl Sheet {
 
         // Extract sub-sheet
         let mut data = Vec::new();
-        for row_idx in start_row..=end_row {
-            let row = &self.data[row_idx];
+        for row in &self.data()[start_row..=end_row] {
             let mut new_row = Vec::new();
             for cell in row.iter().take(end_col + 1).skip(start_col) {
                 new_row.push(cell.clone());
@@ -482,7 +554,12 @@ impl Sheet {
         Ok(sub_sheet)
     }
 
-    fn set_cell_value_raw(&mut self, row: usize, col: usize, value: CellValue) -> Result<()> {
+    pub(crate) fn set_cell_value_raw(
+        &mut self,
+        row: usize,
+        col: usize,
+        value: CellValue,
+    ) -> Result<()> {
         let cell = self.get_mut(row, col)?;
         *cell = value;
         Ok(())
@@ -507,7 +584,7 @@ impl Sheet {
 
     /// Get an entire row by index (0-based)
     pub fn row(&self, index: usize) -> Result<&Vec<CellValue>> {
-        self.data.get(index).ok_or(SheetError::RowIndexOutOfBounds {
+        self.data.row(index).ok_or(SheetError::RowIndexOutOfBounds {
             index,
             count: self.row_count(),
         })
@@ -524,16 +601,17 @@ impl Sheet {
         let row: Vec<CellValue> = data.into_iter().map(Into::into).collect();
 
         // Ensure consistent column count
-        if !self.data.is_empty() && row.len() != self.col_count() {
+        if !self.is_empty() && row.len() != self.col_count() {
             return Err(SheetError::LengthMismatch {
                 expected: self.col_count(),
                 actual: row.len(),
             });
         }
 
-        self.data.push(row);
+        let row_idx = self.row_count();
+        self.data_mut().push(row);
+        self.tables_row_appended(row_idx);
         if self.col_count() > 0 {
-            let row_idx = self.row_count().saturating_sub(1);
             self.mark_dirty_range(row_idx, 0, row_idx, self.col_count().saturating_sub(1));
         }
         self.rebuild_formula_engine()?;
@@ -552,14 +630,16 @@ impl Sheet {
         let row: Vec<CellValue> = data.into_iter().map(Into::into).collect();
 
         // Ensure consistent column count
-        if !self.data.is_empty() && row.len() != self.col_count() {
+        if !self.is_empty() && row.len() != self.col_count() {
             return Err(SheetError::LengthMismatch {
                 expected: self.col_count(),
                 actual: row.len(),
             });
         }
 
-        self.data.insert(index, row);
+        self.data_mut().insert(index, row);
+        self.tables_row_inserted(index);
+        self.spills_row_inserted(index);
         self.invalidate_row_names();
         if self.col_count() > 0 {
             self.mark_dirty_range(index, 0, index, self.col_count().saturating_sub(1));
@@ -586,7 +666,7 @@ impl Sheet {
             });
         }
 
-        self.data[index] = row;
+        self.data_mut()[index] = row;
         if self.col_count() > 0 {
             self.mark_dirty_range(index, 0, index, self.col_count().saturating_sub(1));
         }
@@ -614,7 +694,11 @@ impl Sheet {
         }
 
         self.invalidate_row_names();
-        let removed = self.data.remove(index);
+        let mut keep = vec![true; self.row_count()];
+        keep[index] = false;
+        let removed = self.data_mut().remove(index);
+        self.tables_rows_retained(&keep);
+        self.spills_rows_retained(&keep);
         self.rebuild_formula_engine()?;
         Ok(removed)
     }
@@ -624,6 +708,7 @@ impl Sheet {
         indices.sort_unstable();
         indices.reverse();
 
+        let mut keep = vec![true; self.row_count()];
         for index in indices {
             if index >= self.row_count() {
                 return Err(SheetError::RowIndexOutOfBounds {
@@ -631,8 +716,11 @@ impl Sheet {
                     count: self.row_count(),
                 });
             }
-            self.data.remove(index);
+            self.data_mut().remove(index);
+            keep[index] = false;
         }
+        self.tables_rows_retained(&keep);
+        self.spills_rows_retained(&keep);
         self.invalidate_row_names();
         self.rebuild_formula_engine()?;
         Ok(())
@@ -643,11 +731,15 @@ impl Sheet {
     where
         F: Fn(&[CellValue]) -> bool,
     {
-        let original_len = self.data.len();
-        self.data.retain(|row| !predicate(row));
+        let original_len = self.row_count();
+        let mut keep = Vec::with_capacity(original_len);
+        self.data.for_each_row(|_, row| keep.push(!predicate(row)));
+        self.data.retain_rows(&keep);
+        self.tables_rows_retained(&keep);
+        self.spills_rows_retained(&keep);
         self.invalidate_row_names();
         let _ = self.rebuild_formula_engine();
-        original_len - self.data.len()
+        original_len - self.row_count()
     }
 
     // ===== Column Operations =====
@@ -661,7 +753,7 @@ impl Sheet {
             });
         }
 
-        Ok(self.data.iter().map(|row| row[index].clone()).collect())
+        Ok(self.data.column(index))
     }
 
     /// Get an entire column by name
@@ -672,7 +764,7 @@ impl Sheet {
 
     /// Append a column to the end of each row
     pub fn column_append<T: Into<CellValue> + Clone>(&mut self, data: Vec<T>) -> Result<()> {
-        if !self.data.is_empty() && data.len() != self.row_count() {
+        if !self.is_empty() && data.len() != self.row_count() {
             return Err(SheetError::LengthMismatch {
                 expected: self.row_count(),
                 actual: data.len(),
@@ -680,14 +772,14 @@ impl Sheet {
         }
 
         // If sheet is empty, create rows
-        if self.data.is_empty() {
+        if self.is_empty() {
             for value in data {
-                self.data.push(vec![value.into()]);
+                self.data_mut().push(vec![value.into()]);
             }
         } else {
-            for (row, value) in self.data.iter_mut().zip(data.into_iter()) {
-                row.push(value.into());
-            }
+            let index = self.col_count();
+            self.data
+                .insert_column(index, data.into_iter().map(Into::into).collect());
         }
 
         self.invalidate_column_names();
@@ -712,15 +804,16 @@ impl Sheet {
             });
         }
 
-        if !self.data.is_empty() && data.len() != self.row_count() {
+        if !self.is_empty() && data.len() != self.row_count() {
             return Err(SheetError::LengthMismatch {
                 expected: self.row_count(),
                 actual: data.len(),
             });
         }
 
-        for (row, value) in self.data.iter_mut().zip(data.into_iter()) {
-            row.insert(index, value.into());
+        if !self.is_empty() {
+            self.data
+                .insert_column(index, data.into_iter().map(Into::into).collect());
         }
 
         self.invalidate_column_names();
@@ -747,9 +840,8 @@ impl Sheet {
             });
         }
 
-        for (row, value) in self.data.iter_mut().zip(data.into_iter()) {
-            row[index] = value.into();
-        }
+        self.data
+            .set_column(index, data.into_iter().map(Into::into).collect());
 
         if self.row_count() > 0 {
             self.mark_dirty_range(0, index, self.row_count().saturating_sub(1), index);
@@ -777,7 +869,9 @@ impl Sheet {
             });
         }
 
-        let removed: Vec<CellValue> = self.data.iter_mut().map(|row| row.remove(index)).collect();
+        let removed = self.data.column(index);
+        let keep: Vec<usize> = (0..self.col_count()).filter(|&i| i != index).collect();
+        self.data.select_columns(&keep);
 
         self.invalidate_column_names();
         self.rebuild_formula_engine()?;
@@ -792,26 +886,23 @@ impl Sheet {
 
     /// Delete multiple columns by names
     pub fn column_delete_multi_by_name(&mut self, names: &[&str]) -> Result<()> {
-        // Get indices in reverse order to maintain positions during deletion
-        let mut indices: Vec<usize> = names
+        let indices: Vec<usize> = names
             .iter()
             .map(|name| self.column_index_by_name(name))
             .collect::<Result<Vec<_>>>()?;
 
-        indices.sort_unstable();
-        indices.reverse();
-
-        for index in indices {
+        for &index in &indices {
             if index >= self.col_count() {
                 return Err(SheetError::ColumnIndexOutOfBounds {
                     index,
                     count: self.col_count(),
                 });
             }
-            for row in &mut self.data {
-                row.remove(index);
-            }
         }
+        let keep: Vec<usize> = (0..self.col_count())
+            .filter(|i| !indices.contains(i))
+            .collect();
+        self.data.select_columns(&keep);
 
         self.invalidate_column_names();
         self.rebuild_formula_engine()?;
@@ -855,6 +946,21 @@ impl Sheet {
         Ok(())
     }
 
+    /// The column schema attached by [`Sheet::apply_schema`], if any.
+    #[must_use]
+    pub fn schema(&self) -> Option<&SheetSchema> {
+        self.schema.as_ref()
+    }
+
+    /// Drop the attached schema, so writers infer column types again.
+    pub fn clear_schema(&mut self) {
+        self.schema = None;
+    }
+
+    pub(crate) fn set_schema(&mut self, schema: SheetSchema) {
+        self.schema = Some(schema);
+    }
+
     /// Get column names (if set)
     #[must_use]
     pub fn column_names(&self) -> Option<&Vec<String>> {
@@ -862,7 +968,7 @@ impl Sheet {
     }
 
     /// Get the column index by name
-    fn column_index_by_name(&self, name: &str) -> Result<usize> {
+    pub(crate) fn column_index_by_name(&self, name: &str) -> Result<usize> {
         self.column_index
             .as_ref()
             .ok_or_else(|| {
@@ -875,6 +981,22 @@ impl Sheet {
             })
     }
 
+    /// Index of the first data row: 1 when row 0 holds the column names, else 0.
+    #[must_use]
+    pub fn data_start_row(&self) -> usize {
+        match (&self.column_names, self.data.first_row()) {
+            (Some(names), Some(first))
+                if first
+                    .iter()
+                    .zip(names.iter())
+                    .all(|(c, n)| c.as_str() == *n) =>
+            {
+                1
+            }
+            _ => 0,
+        }
+    }
+
     /// Get the row index by name
     fn row_index_by_name(&self, name: &str) -> Result<usize> {
         self.row_names
@@ -904,7 +1026,7 @@ impl Sheet {
         out.trim().to_string()
     }
 
-    fn cell_key(cell: &CellValue) -> String {
+    pub(crate) fn cell_key(cell: &CellValue) -> String {
         match cell.cached_or_self() {
             CellValue::Null => "N".to_string(),
             CellValue::Bool(b) => format!("B{b}"),
@@ -912,6 +1034,9 @@ impl Sheet {
             CellValue::Float(f) => format!("F{f:?}"),
             CellValue::String(s) => format!("S{s}"),
             CellValue::Formula(formula) => format!("FML{}", formula.source),
+            CellValue::Date(d) => format!("D{d}"),
+            CellValue::DateTime(dt) => format!("T{dt}"),
+            CellValue::Duration(d) => format!("U{d}"),
         }
     }
 
@@ -949,24 +1074,29 @@ impl Sheet {
         self.row_names = None;
     }
 
-    fn rebuild_formula_engine(&mut self) -> Result<()> {
+    pub(crate) fn rebuild_formula_engine(&mut self) -> Result<()> {
         let mut engine = FormulaEngine::new();
         let mut first_error: Option<SheetError> = None;
-        for (row_idx, row) in self.data.iter_mut().enumerate() {
-            for (col_idx, cell) in row.iter_mut().enumerate() {
-                if let CellValue::Formula(formula) = cell {
-                    formula.cached = None;
-                    let addr = CellAddress::new(row_idx as u32, col_idx as u32);
-                    if let Err(err) = engine.set_formula(addr, &formula.source) {
-                        if first_error.is_none() {
-                            first_error = Some(err.into());
-                        }
-                        continue;
+        let tables = self.table_locations(None);
+        let names = SheetNames {
+            names: &self.names,
+            tables: &tables,
+        };
+        self.data.for_each_cell_mut(|row_idx, col_idx, cell| {
+            if let CellValue::Formula(formula) = cell {
+                formula.cached = None;
+                let addr = CellAddress::new(row_idx as u32, col_idx as u32);
+                if let Err(err) =
+                    engine.set_formula_with_sheet(0, addr, &formula.source, Some(&names))
+                {
+                    if first_error.is_none() {
+                        first_error = Some(err.into());
                     }
-                    engine.mark_dirty(&addr);
+                    return;
                 }
+                engine.mark_dirty(&addr);
             }
-        }
+        });
         self.formula_engine = engine;
         if let Some(err) = first_error {
             Err(err)
@@ -982,11 +1112,7 @@ impl Sheet {
     where
         F: Fn(&CellValue) -> CellValue,
     {
-        for row in &mut self.data {
-            for cell in row {
-                *cell = f(cell);
-            }
-        }
+        self.data.map_cells(f);
         if let Err(err) = self.rebuild_formula_engine() {
             eprintln!("Warning: formula engine rebuild failed: {err}");
         }
@@ -1014,7 +1140,7 @@ impl Sheet {
         }
 
         for row_idx in start_row..=end_row {
-            if let Some(row) = self.data.get_mut(row_idx) {
+            if let Some(row) = self.data_mut().get_mut(row_idx) {
                 for col_idx in start_col..=end_col {
                     if let Some(cell) = row.get_mut(col_idx) {
                         *cell = f(cell);
@@ -1040,9 +1166,7 @@ impl Sheet {
             });
         }
 
-        for row in &mut self.data {
-            row[col_index] = f(&row[col_index]);
-        }
+        self.data.map_column(col_index, f);
 
         self.rebuild_formula_engine()?;
         Ok(())
@@ -1063,19 +1187,72 @@ impl Sheet {
     where
         F: Fn(usize, &[CellValue]) -> bool,
     {
-        let mut keep = Vec::new();
-        for (i, row) in self.data.iter().enumerate() {
-            if predicate(i, row) {
-                keep.push(row.clone());
-            }
-        }
-        self.data = keep;
+        let mut keep = Vec::with_capacity(self.row_count());
+        self.data
+            .for_each_row(|i, row| keep.push(predicate(i, row)));
+        self.data.retain_rows(&keep);
+        self.tables_rows_retained(&keep);
+        self.spills_rows_retained(&keep);
         self.invalidate_row_names();
         if let Err(err) = self.rebuild_formula_engine() {
             eprintln!("Warning: formula engine rebuild failed: {err}");
         }
     }
 
+    /// Reorder the data rows, leaving a header row in place.
+    ///
+    /// `order[i]` is the current position, counted from the first data row,
+    /// of the row that moves to position `i`. Named rows follow their rows.
+    pub fn reorder_rows(&mut self, order: &[usize]) -> Result<()> {
+        let start = self.data_start_row();
+        let count = self.row_count() - start;
+        if order.len() != count {
+            return Err(SheetError::LengthMismatch {
+                expected: count,
+                actual: order.len(),
+            });
+        }
+
+        // new_position[old] = new, validating that order is a permutation
+        let mut new_position = vec![usize::MAX; count];
+        for (new, &old) in order.iter().enumerate() {
+            if old >= count {
+                return Err(SheetError::RowIndexOutOfBounds { index: old, count });
+            }
+            if new_position[old] != usize::MAX {
+                return Err(SheetError::Sort(format!(
+                    "row {old} appears twice in the order"
+                )));
+            }
+            new_position[old] = new;
+        }
+
+        match &mut self.data {
+            Storage::Columns(columns) if columns.header_rows() == start => {
+                let order: Vec<Option<usize>> = order.iter().copied().map(Some).collect();
+                *columns = columns.take(&order)?;
+            }
+            storage => {
+                let data = storage.rows_mut();
+                let mut rows: Vec<Option<Vec<CellValue>>> = data.drain(start..).map(Some).collect();
+                data.extend(
+                    order
+                        .iter()
+                        .map(|&old| rows[old].take().unwrap_or_default()),
+                );
+            }
+        }
+
+        if let Some(names) = &mut self.row_names {
+            for index in names.values_mut() {
+                if *index >= start {
+                    *index = start + new_position[*index - start];
+                }
+            }
+        }
+        self.rebuild_formula_engine()
+    }
+
     /// Remove columns at the specified indices
     pub fn remove_columns_at(&mut self, indices: &[usize]) -> Result<()> {
         for &index in indices {
@@ -1087,16 +1264,10 @@ impl Sheet {
             }
         }
 
-        // Sort indices in descending order for safe removal
-        let mut sorted_indices: Vec<usize> = indices.to_vec();
-        sorted_indices.sort_unstable();
-        sorted_indices.reverse();
-
-        for row in &mut self.data {
-            for &index in &sorted_indices {
-                row.remove(index);
-            }
-        }
+        let keep: Vec<usize> = (0..self.col_count())
+            .filter(|i| !indices.contains(i))
+            .collect();
+        self.data.select_columns(&keep);
 
         self.invalidate_column_names();
         self.rebuild_formula_engine()?;
@@ -1108,7 +1279,7 @@ impl Sheet {
     /// Convert to a 2D array (list of lists)
     #[must_use]
     pub fn to_array(&self) -> Vec<Vec<CellValue>> {
-        self.data.clone()
+        self.data().clone()
     }
 
     /// Convert to a dictionary (column name -> values)
@@ -1119,7 +1290,7 @@ impl Sheet {
         let mut dict = IndexMap::new();
 
         for (i, name) in names.iter().enumerate() {
-            let col: Vec<CellValue> = self.data.iter().map(|row| row[i].clone()).collect();
+            let col = self.data.column(i);
             dict.insert(name.clone(), col);
         }
 
@@ -1149,9 +1320,9 @@ impl Sheet {
     #[must_use]
     pub fn to_records(&self) -> Option<Vec<IndexMap<String, CellValue>>> {
         let names = self.column_names.as_ref()?;
-        let mut records = Vec::with_capacity(self.data.len());
+        let mut records = Vec::with_capacity(self.row_count());
 
-        for row in &self.data {
+        for row in self.data() {
             let mut record = IndexMap::new();
             for (i, name) in names.iter().enumerate() {
                 if i < row.len() {
@@ -1216,11 +1387,15 @@ impl Sheet {
 
         let mut sheet = Sheet {
             name: "Sheet1".to_string(),
-            data,
+            data: Storage::Rows(data),
             column_names: None,
             column_index: None,
             row_names: None,
+            schema: None,
             formula_engine: FormulaEngine::new(),
+            names: DefinedNames::new(),
+            tables: Vec::new(),
+            spills: HashMap::new(),
         };
 
         // Name columns by header row
@@ -1231,25 +1406,43 @@ impl Sheet {
 
     /// Get rows iterator (excluding header row if named)
     pub fn rows(&self) -> impl Iterator<Item = &Vec<CellValue>> {
-        self.data.iter()
+        self.data().iter()
     }
 
     /// Get mutable rows iterator
     pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut Vec<CellValue>> {
-        self.data.iter_mut()
+        self.data_mut().iter_mut()
     }
 
     /// Get internal data reference
+    ///
+    /// Columnar sheets build this row view on the first call and keep it
+    /// until the next edit.
     #[must_use]
     pub fn data(&self) -> &Vec<Vec<CellValue>> {
-        &self.data
+        self.data.rows()
     }
 
     /// Get mutable internal data reference
+    ///
+    /// Columnar sheets switch to row storage first.
     pub fn data_mut(&mut self) -> &mut Vec<Vec<CellValue>> {
+        self.data.rows_mut()
+    }
+
+    pub(crate) fn storage(&self) -> &Storage {
+        &self.data
+    }
+
+    pub(crate) fn storage_mut(&mut self) -> &mut Storage {
         &mut self.data
     }
 
+    /// Replace all cells, as readers do when filling a new sheet.
+    pub(crate) fn set_storage(&mut self, storage: Storage) {
+        self.data = storage;
+    }
+
     // ===== Join Operations =====
 
     /// Inner join with another sheet on a key column.
@@ -1340,13 +1533,7 @@ impl Sheet {
             }
 
             // Filter data columns
-            for row in &mut self.data {
-                let new_row: Vec<CellValue> = keep_indices
-                    .iter()
-                    .filter_map(|&i| row.get(i).cloned())
-                    .collect();
-                *row = new_row;
-            }
+            self.data.select_columns(&keep_indices);
 
             // Update column names and index
             self.column_names = Some(new_names);
@@ -1363,13 +1550,7 @@ impl Sheet {
                 }
             }
 
-            for row in &mut self.data {
-                let new_row: Vec<CellValue> = keep_indices
-                    .iter()
-                    .filter_map(|&i| row.get(i).cloned())
-                    .collect();
-                *row = new_row;
-            }
+            self.data.select_columns(&keep_indices);
             self.invalidate_row_names();
         }
         self.rebuild_formula_engine()?;
@@ -1388,11 +1569,7 @@ impl Sheet {
             });
         }
 
-        for row in &mut self.data {
-            if let Some(cell) = row.get_mut(col_index) {
-                *cell = f(cell);
-            }
-        }
+        self.data.map_column(col_index, f);
         self.rebuild_formula_engine()?;
         Ok(())
     }
@@ -1408,13 +1585,15 @@ impl Sheet {
 
     /// Remove empty rows (rows where all cells are null or empty strings)
     pub fn remove_empty_rows(&mut self) {
-        self.data.retain(|row| {
-            !row.iter().all(|cell| match cell.cached_or_self() {
+        let mut keep = Vec::with_capacity(self.row_count());
+        self.data.for_each_row(|_, row| {
+            keep.push(!row.iter().all(|cell| match cell.cached_or_self() {
                 CellValue::Null => true,
                 CellValue::String(s) if s.is_empty() => true,
                 _ => false,
-            })
+            }));
         });
+        self.data.retain_rows(&keep);
         self.invalidate_row_names();
         if let Err(err) = self.rebuild_formula_engine() {
             eprintln!("Warning: formula engine rebuild failed: {err}");
@@ -1423,23 +1602,24 @@ impl Sheet {
 
     /// Transpose the sheet (swap rows and columns)
     pub fn transpose(&mut self) {
-        if self.data.is_empty() {
+        if self.is_empty() {
             return;
         }
 
         let rows = self.row_count();
         let cols = self.col_count();
 
+        let data = self.data();
         let mut transposed = Vec::with_capacity(cols);
         for col in 0..cols {
             let mut new_row = Vec::with_capacity(rows);
-            for row in 0..rows {
-                new_row.push(self.data[row].get(col).cloned().unwrap_or(CellValue::Null));
+            for row in &data[..rows] {
+                new_row.push(row.get(col).cloned().unwrap_or(CellValue::Null));
             }
             transposed.push(new_row);
         }
 
-        self.data = transposed;
+        self.data = Storage::Rows(transposed);
 
         // Swap column names with first column if they exist
         if self.column_names.is_some() {
@@ -1465,13 +1645,7 @@ impl Sheet {
         let indices = indices?;
 
         // Create new data with only selected columns
-        for row in &mut self.data {
-            let new_row: Vec<CellValue> = indices
-                .iter()
-                .filter_map(|&i| row.get(i).cloned())
-                .collect();
-            *row = new_row;
-        }
+        self.data.select_columns(&indices);
 
         // Update column names
         if self.column_names.is_some() {
@@ -1507,13 +1681,7 @@ impl Sheet {
             }
 
             // Filter data columns
-            for row in &mut self.data {
-                let new_row: Vec<CellValue> = keep_indices
-                    .iter()
-                    .filter_map(|&i| row.get(i).cloned())
-                    .collect();
-                *row = new_row;
-            }
+            self.data.select_columns(&keep_indices);
 
             // Update column names and index
             self.column_names = Some(new_names);
@@ -1576,32 +1744,23 @@ impl Sheet {
 
         // Build right key -> row indices map (skip header row if present)
         // Use strict matching: first row must match ALL column names in order
-        let right_start = if other.data.first().is_some_and(|r| {
-            r.iter()
-                .zip(right_names.iter())
-                .all(|(c, n)| c.as_str() == *n)
-        }) {
-            1
-        } else {
-            0
-        };
-        let left_start = if self.data.first().is_some_and(|r| {
-            r.iter()
-                .zip(left_names.iter())
-                .all(|(c, n)| c.as_str() == *n)
-        }) {
-            1
-        } else {
-            0
+        let header_rows = |sheet: &Sheet, names: &[String]| {
+            usize::from(
+                sheet
+                    .data
+                    .first_row()
+                    .is_some_and(|r| r.iter().zip(names.iter()).all(|(c, n)| c.as_str() == *n)),
+            )
         };
+        let right_start = header_rows(other, right_names);
+        let left_start = header_rows(self, left_names);
 
         let mut right_map: HashMap<String, Vec<usize>> = HashMap::new();
-        for (i, row) in other.data.iter().enumerate().skip(right_start) {
-            if let Some(cell) = row.get(right_key_idx) {
-                let key_val = cell.as_str();
-                right_map.entry(key_val).or_default().push(i);
+        other.data.for_each_in_column(right_key_idx, |i, cell| {
+            if let Some(cell) = cell.filter(|_| i >= right_start) {
+                right_map.entry(cell.as_str()).or_default().push(i);
             }
-        }
+        });
 
         // Build result columns (left cols + right cols except key)
         let mut result_names: Vec<String> = left_names.clone();
@@ -1623,75 +1782,98 @@ impl Sheet {
             result_names.push(name.clone());
         }
 
-        // Build result data
-        let mut result_data: Vec<Vec<CellValue>> = Vec::new();
-
-        // Add header row
-        let header: Vec<CellValue> = result_names
-            .iter()
-            .map(|n| CellValue::String(n.clone()))
-            .collect();
-        result_data.push(header);
-
-        let left_col_count = left_names.len();
-        let right_col_count = right_cols_to_add.len();
+        // Pair up (left row, right row), with None on an unmatched side
+        let mut pairs: Vec<(Option<usize>, Option<usize>)> = Vec::new();
         let mut matched_right: HashSet<usize> = HashSet::new();
-
-        // Process left rows
-        for left_row in self.data.iter().skip(left_start) {
-            let left_key_val = left_row
-                .get(left_key_idx)
-                .map(|c| c.as_str())
-                .unwrap_or_default();
-
+        self.data.for_each_in_column(left_key_idx, |i, cell| {
+            if i < left_start {
+                return;
+            }
+            let left_key_val = cell.map(|c| c.as_str()).unwrap_or_default();
             if let Some(right_indices) = right_map.get(&left_key_val) {
                 // Matching rows found
                 for &right_idx in right_indices {
                     matched_right.insert(right_idx);
-                    let right_row = &other.data[right_idx];
-                    let mut new_row = left_row.clone();
-                    for (col_idx, _) in &right_cols_to_add {
-                        new_row.push(right_row.get(*col_idx).cloned().unwrap_or(CellValue::Null));
-                    }
-                    result_data.push(new_row);
+                    pairs.push((Some(i), Some(right_idx)));
                 }
             } else if matches!(join_type, JoinType::Left | JoinType::Full) {
                 // No match - include left row with nulls for right
-                let mut new_row = left_row.clone();
-                for _ in 0..right_col_count {
-                    new_row.push(CellValue::Null);
-                }
-                result_data.push(new_row);
+                pairs.push((Some(i), None));
             }
-        }
+        });
 
         // For right/full join, add unmatched right rows
         if matches!(join_type, JoinType::Right | JoinType::Full) {
-            for (i, right_row) in other.data.iter().enumerate().skip(right_start) {
+            for i in right_start..other.row_count() {
                 if !matched_right.contains(&i) {
-                    let mut new_row: Vec<CellValue> = vec![CellValue::Null; left_col_count];
-                    // Set the key column value from right
-                    if left_key_idx < new_row.len() {
-                        new_row[left_key_idx] = right_row
-                            .get(right_key_idx)
-                            .cloned()
-                            .unwrap_or(CellValue::Null);
-                    }
-                    for (col_idx, _) in &right_cols_to_add {
-                        new_row.push(right_row.get(*col_idx).cloned().unwrap_or(CellValue::Null));
+                    pairs.push((None, Some(i)));
+                }
+            }
+        }
+
+        let header: Vec<CellValue> = result_names
+            .iter()
+            .map(|n| CellValue::String(n.clone()))
+            .collect();
+        let right_cols: Vec<usize> = right_cols_to_add.iter().map(|(i, _)| *i).collect();
+
+        let data = match (&self.data, &other.data) {
+            (Storage::Columns(left), Storage::Columns(right))
+                if left.header_rows() == left_start && right.header_rows() == right_start =>
+            {
+                Storage::from_columns(Columns::join(
+                    left,
+                    right,
+                    &pairs,
+                    (left_key_idx, right_key_idx),
+                    &right_cols,
+                    header,
+                )?)
+            }
+            _ => {
+                let left_rows = self.data();
+                let right_rows = other.data();
+                let mut result_data: Vec<Vec<CellValue>> = Vec::with_capacity(pairs.len() + 1);
+                result_data.push(header);
+                for (left_idx, right_idx) in pairs {
+                    let mut new_row = match left_idx {
+                        Some(left_idx) => left_rows[left_idx].clone(),
+                        None => {
+                            let mut new_row = vec![CellValue::Null; left_names.len()];
+                            // Set the key column value from right
+                            if let Some(right_idx) = right_idx {
+                                new_row[left_key_idx] = right_rows[right_idx]
+                                    .get(right_key_idx)
+                                    .cloned()
+                                    .unwrap_or(CellValue::Null);
+                            }
+                            new_row
+                        }
+                    };
+                    for &col_idx in &right_cols {
+                        new_row.push(
+                            right_idx
+                                .and_then(|right_idx| right_rows[right_idx].get(col_idx).cloned())
+                                .unwrap_or(CellValue::Null),
+                        );
                     }
                     result_data.push(new_row);
                 }
+                Storage::Rows(result_data)
             }
-        }
+        };
 
         let mut result = Sheet {
             name: format!("{}_joined", self.name),
-            data: result_data,
+            data,
             column_names: None,
             column_index: None,
             row_names: None,
+            schema: None,
             formula_engine: FormulaEngine::new(),
+            names: DefinedNames::new(),
+            tables: Vec::new(),
+            spills: HashMap::new(),
         };
         result.name_columns_by_row(0)?;
 
@@ -1715,7 +1897,7 @@ impl Sheet {
 
         // If both have named columns, align by name
         if let (Some(self_names), Some(other_names)) = (&self.column_names, &other.column_names) {
-            let start_row = if other.data.first().is_some_and(|r| {
+            let start_row = if other.data().first().is_some_and(|r| {
                 r.iter()
                     .zip(other_names.iter())
                     .all(|(c, n)| c.as_str() == *n)
@@ -1725,7 +1907,8 @@ impl Sheet {
                 0
             };
 
-            for other_row in other.data.iter().skip(start_row) {
+            let mut new_rows = Vec::new();
+            for other_row in other.data().iter().skip(start_row) {
                 let mut new_row = vec![CellValue::Null; self_names.len()];
                 for (i, name) in other_names.iter().enumerate() {
                     if let Some(self_idx) = self.column_index.as_ref().and_then(|m| m.get(name)) {
@@ -1734,8 +1917,9 @@ impl Sheet {
                         }
                     }
                 }
-                self.data.push(new_row);
+                new_rows.push(new_row);
             }
+            self.data_mut().extend(new_rows);
         } else {
             // No named columns - must have same column count
             if self.col_count() != other.col_count() {
@@ -1744,8 +1928,8 @@ impl Sheet {
                     right: other.col_count(),
                 });
             }
-            for row in &other.data {
-                self.data.push(row.clone());
+            for row in other.data() {
+                self.data_mut().push(row.clone());
             }
         }
 
@@ -1771,7 +1955,7 @@ impl Sheet {
         // Build set of existing keys (skip header row if columns are named)
         let start_idx = if self.column_names.is_some() { 1 } else { 0 };
         let mut existing_keys: HashSet<String> = self
-            .data
+            .data()
             .iter()
             .skip(start_idx)
             .filter_map(|row| row.get(self_key_idx).map(|c| c.as_str()))
@@ -1780,7 +1964,7 @@ impl Sheet {
         let other_names = other.column_names.as_ref();
         let start_row = if let Some(names) = other_names {
             if other
-                .data
+                .data()
                 .first()
                 .is_some_and(|r| r.iter().zip(names.iter()).all(|(c, n)| c.as_str() == *n))
             {
@@ -1792,7 +1976,7 @@ impl Sheet {
             0
         };
 
-        for other_row in other.data.iter().skip(start_row) {
+        for other_row in other.data().iter().skip(start_row) {
             let other_key_val = other_row
                 .get(other_key_idx)
                 .map(|c| c.as_str())
@@ -1811,12 +1995,12 @@ impl Sheet {
                                 }
                             }
                         }
-                        self.data.push(new_row);
+                        self.data_mut().push(new_row);
                         existing_keys.insert(other_key_val);
                         continue;
                     }
                 }
-                self.data.push(other_row.clone());
+                self.data_mut().push(other_row.clone());
                 existing_keys.insert(other_key_val);
             }
         }
@@ -1839,7 +2023,7 @@ impl Sheet {
         }
 
         let mut data = Vec::with_capacity(self.row_count());
-        for (left, right) in self.data.iter().zip(other.data.iter()) {
+        for (left, right) in self.data().iter().zip(other.data().iter()) {
             let mut row = Vec::with_capacity(left.len() + right.len());
             row.extend(left.iter().cloned());
             row.extend(right.iter().cloned());
@@ -1909,7 +2093,7 @@ impl Sheet {
 
         // Build map of existing keys to row indices (skip header row)
         let mut key_to_row: HashMap<String, usize> = HashMap::new();
-        for (i, row) in self.data.iter().enumerate().skip(1) {
+        for (i, row) in self.data().iter().enumerate().skip(1) {
             if let Some(cell) = row.get(self_key_idx) {
                 key_to_row.insert(cell.as_str(), i);
             }
@@ -1918,7 +2102,7 @@ impl Sheet {
         let other_names = other.column_names.as_ref();
         let start_row = if let Some(names) = other_names {
             if other
-                .data
+                .data()
                 .first()
                 .is_some_and(|r| r.iter().zip(names.iter()).all(|(c, n)| c.as_str() == *n))
             {
@@ -1930,7 +2114,7 @@ impl Sheet {
             0
         };
 
-        for other_row in other.data.iter().skip(start_row) {
+        for other_row in other.data().iter().skip(start_row) {
             let other_key_val = other_row
                 .get(other_key_idx)
                 .map(|c| c.as_str())
@@ -1945,7 +2129,7 @@ impl Sheet {
                                 self.column_index.as_ref().and_then(|m| m.get(name))
                             {
                                 if let Some(val) = other_row.get(i) {
-                                    self.data[existing_idx][*self_idx] = val.clone();
+                                    self.data.rows_mut()[existing_idx][*self_idx] = val.clone();
                                 }
                             }
                         }
@@ -1965,14 +2149,14 @@ impl Sheet {
                                 }
                             }
                         }
-                        self.data.push(new_row.clone());
+                        self.data.rows_mut().push(new_row.clone());
                         // Update key map for subsequent duplicates in other
-                        key_to_row.insert(other_key_val, self.data.len() - 1);
+                        key_to_row.insert(other_key_val, self.row_count() - 1);
                         continue;
                     }
                 }
-                self.data.push(other_row.clone());
-                key_to_row.insert(other_key_val, self.data.len() - 1);
+                self.data.rows_mut().push(other_row.clone());
+                key_to_row.insert(other_key_val, self.row_count() - 1);
             }
         }
 
@@ -2017,13 +2201,13 @@ impl std::ops::BitOr<&Sheet> for &Sheet {
     }
 }
 
-struct SheetValueResolver<'a> {
+pub(crate) struct SheetValueResolver<'a> {
     sheet: &'a Sheet,
     current: Option<CellAddress>,
 }
 
 impl<'a> SheetValueResolver<'a> {
-    fn new(sheet: &'a Sheet, current: Option<CellAddress>) -> Self {
+    pub(crate) fn new(sheet: &'a Sheet, current: Option<CellAddress>) -> Self {
         Self { sheet, current }
     }
 }
@@ -2055,6 +2239,47 @@ impl ValueResolver for SheetValueResolver<'_> {
     fn current_cell(&self) -> Option<CellAddress> {
         self.current
     }
+
+    fn defined_name(&self, _sheet: Option<&str>, name: &str) -> Option<String> {
+        self.sheet.names.refers_to(name)
+    }
+
+    fn table(&self, name: Option<&str>) -> Option<TableLocation> {
+        self.sheet.resolve_table(name, self.current)
+    }
+
+    fn spill_range(&self, sheet: Option<&str>, anchor: &CellAddress) -> Option<CellRange> {
+        match sheet {
+            None => self.sheet.spill_region(*anchor),
+            Some(_) => None,
+        }
+    }
+}
+
+/// Resolves the sheet's own defined names and tables while compiling its
+/// formulas.
+struct SheetNames<'a> {
+    names: &'a DefinedNames,
+    tables: &'a [TableLocation],
+}
+
+impl SheetIdResolver for SheetNames<'_> {
+    fn sheet_id(&self, _sheet_name: &str) -> Option<u32> {
+        None
+    }
+
+    fn defined_name(&self, _sheet_id: u32, name: &str) -> Option<String> {
+        self.names.refers_to(name)
+    }
+
+    fn table(
+        &self,
+        _sheet_id: u32,
+        name: Option<&str>,
+        cell: Option<CellAddress>,
+    ) -> Option<TableLocation> {
+        find_table(self.tables, name, cell)
+    }
 }
 
 fn cell_value_to_formula_value(value: &CellValue) -> Value {
@@ -2065,10 +2290,14 @@ fn cell_value_to_formula_value(value: &CellValue) -> Value {
         CellValue::Float(v) => Value::Float(*v),
         CellValue::String(v) => Value::String(v.clone()),
         CellValue::Formula(_) => Value::Error(ErrorValue::Value),
+        // Formulas see dates and durations as serial numbers, like Excel
+        temporal @ (CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_)) => {
+            temporal.as_float().map_or(Value::Empty, Value::Float)
+        }
     }
 }
 
-fn formula_value_to_cell_value(value: Value) -> CellValue {
+pub(crate) fn formula_value_to_cell_value(value: Value) -> CellValue {
     match value {
         Value::Empty => CellValue::Null,
         Value::Bool(v) => CellValue::Bool(v),
@@ -0,0 +1,486 @@
+//! Stable multi-key sorting for sheets.
+
+use crate::cell::CellValue;
+use crate::error::{Result, SheetError};
+use crate::sheet::Sheet;
+use std::cmp::Ordering;
+use std::str::FromStr;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Where null values go, whatever the sort direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NullOrder {
+    /// Nulls before every other value.
+    First,
+    /// Nulls after every other value.
+    #[default]
+    Last,
+}
+
+impl FromStr for NullOrder {
+    type Err = SheetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "first" => Ok(Self::First),
+            "last" => Ok(Self::Last),
+            _ => Err(SheetError::Sort(format!(
+                "Unknown null placement '{s}', expected first or last"
+            ))),
+        }
+    }
+}
+
+/// How text values are compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Collation {
+    /// Compare Unicode code points, so "B" sorts before "a" and "é" after "z".
+    #[default]
+    Binary,
+    /// Language-neutral Unicode order: letters compare by their base form
+    /// first ("é" next to "e"), then by accents, then lowercase before
+    /// uppercase. The order ignores locale, so language rules such as
+    /// Swedish "å" after "z" are not applied.
+    Unicode,
+}
+
+impl FromStr for Collation {
+    type Err = SheetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "binary" => Ok(Self::Binary),
+            "unicode" => Ok(Self::Unicode),
+            _ => Err(SheetError::Sort(format!(
+                "Unknown collation '{s}', expected binary or unicode"
+            ))),
+        }
+    }
+}
+
+/// One column to sort by, with its direction and comparison options.
+///
+/// Numbers compare numerically and sort before text. Parses from `"amount"`,
+/// `"amount asc"` or `"amount desc"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    column: String,
+    descending: bool,
+    nulls: NullOrder,
+    ignore_case: bool,
+    natural: bool,
+    collation: Collation,
+}
+
+impl SortKey {
+    /// Sort `column` in ascending order.
+    pub fn asc(column: impl Into<String>) -> Self {
+        Self {
+            column: column.into(),
+            descending: false,
+            nulls: NullOrder::default(),
+            ignore_case: false,
+            natural: false,
+            collation: Collation::default(),
+        }
+    }
+
+    /// Sort `column` in descending order.
+    pub fn desc(column: impl Into<String>) -> Self {
+        Self::asc(column).descending(true)
+    }
+
+    /// Set the direction.
+    #[must_use]
+    pub fn descending(mut self, descending: bool) -> Self {
+        self.descending = descending;
+        self
+    }
+
+    /// Set where nulls go.
+    #[must_use]
+    pub fn nulls(mut self, nulls: NullOrder) -> Self {
+        self.nulls = nulls;
+        self
+    }
+
+    /// Compare text without regard to case.
+    #[must_use]
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Compare runs of digits inside text as numbers, so "item10" sorts
+    /// after "item9".
+    #[must_use]
+    pub fn natural(mut self, natural: bool) -> Self {
+        self.natural = natural;
+        self
+    }
+
+    /// Set the text collation.
+    #[must_use]
+    pub fn collation(mut self, collation: Collation) -> Self {
+        self.collation = collation;
+        self
+    }
+
+    /// Name of the column this key sorts by.
+    #[must_use]
+    pub fn column(&self) -> &str {
+        &self.column
+    }
+
+    fn compare(&self, a: &CellValue, b: &CellValue) -> Ordering {
+        let (a, b) = (a.cached_or_self(), b.cached_or_self());
+        match (a.is_null(), b.is_null()) {
+            (true, true) => return Ordering::Equal,
+            (true, false) => return self.null_ordering(),
+            (false, true) => return self.null_ordering().reverse(),
+            (false, false) => {}
+        }
+
+        let ordering = match (numeric(a), numeric(b)) {
+            (Some(x), Some(y)) => x.total_cmp(&y),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => self.compare_text(&a.as_str(), &b.as_str()),
+        };
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    fn null_ordering(&self) -> Ordering {
+        match self.nulls {
+            NullOrder::First => Ordering::Less,
+            NullOrder::Last => Ordering::Greater,
+        }
+    }
+
+    fn compare_text(&self, a: &str, b: &str) -> Ordering {
+        if !self.natural {
+            return self.compare_words(a, b);
+        }
+
+        let (mut left, mut right) = (chunks(a), chunks(b));
+        loop {
+            let ordering = match (left.next(), right.next()) {
+                (None, None) => return Ordering::Equal,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some(x), Some(y)) => match (is_digits(x), is_digits(y)) {
+                    (true, true) => compare_digits(x, y),
+                    // Digits sort before letters, as they do in ASCII
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                    (false, false) => self.compare_words(x, y),
+                },
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+    }
+
+    fn compare_words(&self, a: &str, b: &str) -> Ordering {
+        match (self.collation, self.ignore_case) {
+            (Collation::Binary, false) => a.cmp(b),
+            (Collation::Binary, true) => a.to_lowercase().cmp(&b.to_lowercase()),
+            (Collation::Unicode, ignore_case) => {
+                let ordering = base_letters(a)
+                    .cmp(base_letters(b))
+                    .then_with(|| a.to_lowercase().nfd().cmp(b.to_lowercase().nfd()));
+                if ignore_case {
+                    ordering
+                } else {
+                    ordering.then_with(|| case_pattern(a).cmp(case_pattern(b)))
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = SheetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let text = s.trim();
+        let (column, descending) = match text.rsplit_once(char::is_whitespace) {
+            Some((column, dir)) if dir.eq_ignore_ascii_case("desc") => (column, true),
+            Some((column, dir)) if dir.eq_ignore_ascii_case("asc") => (column, false),
+            _ => (text, false),
+        };
+        let column = column.trim();
+        if column.is_empty() {
+            return Err(SheetError::Sort(format!(
+                "Invalid sort key '{s}', expected a column name optionally followed by asc or desc"
+            )));
+        }
+        Ok(Self::asc(column).descending(descending))
+    }
+}
+
+fn numeric(value: &CellValue) -> Option<f64> {
+    match value {
+        CellValue::Int(i) => Some(*i as f64),
+        CellValue::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+/// Split text into alternating runs of ASCII digits and everything else.
+fn chunks(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let digits = first.is_ascii_digit();
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != digits)
+            .unwrap_or(rest.len());
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        Some(chunk)
+    })
+}
+
+fn is_digits(chunk: &str) -> bool {
+    chunk.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Compare digit runs by value without parsing, so long runs cannot overflow.
+fn compare_digits(a: &str, b: &str) -> Ordering {
+    let (x, y) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+    x.len()
+        .cmp(&y.len())
+        .then_with(|| x.cmp(y))
+        // "01" after "1" so equal values still have a fixed order
+        .then_with(|| a.len().cmp(&b.len()))
+}
+
+/// Lowercase letters with accents removed.
+fn base_letters(text: &str) -> impl Iterator<Item = char> + '_ {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+}
+
+/// Uppercase flags per letter; lowercase sorts first.
+fn case_pattern(text: &str) -> impl Iterator<Item = bool> + '_ {
+    text.chars().map(char::is_uppercase)
+}
+
+impl Sheet {
+    /// Stable sort of the data rows by one or more keys, leaving a header
+    /// row in place. Later keys break ties in earlier ones, and rows that
+    /// compare equal keep their order. Named rows follow their rows.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{CellValue, NullOrder, Sheet, SortKey};
+    ///
+    /// let mut sheet = Sheet::from_data(vec![
+    ///     vec![CellValue::from("item"), "qty".into()],
+    ///     vec!["item10".into(), CellValue::Int(1)],
+    ///     vec!["item9".into(), CellValue::Null],
+    ///     vec!["Item2".into(), CellValue::Int(1)],
+    /// ]);
+    /// sheet.name_columns_by_row(0).unwrap();
+    ///
+    /// sheet
+    ///     .sort(&[
+    ///         SortKey::desc("qty").nulls(NullOrder::First),
+    ///         SortKey::asc("item").natural(true).ignore_case(true),
+    ///     ])
+    ///     .unwrap();
+    /// let items: Vec<String> = (1..4).map(|r| sheet.get(r, 0).unwrap().as_str()).collect();
+    /// assert_eq!(items, ["item9", "Item2", "item10"]);
+    /// ```
+    pub fn sort(&mut self, keys: &[SortKey]) -> Result<()> {
+        let columns = keys
+            .iter()
+            .map(|key| self.column_index_by_name(&key.column))
+            .collect::<Result<Vec<_>>>()?;
+
+        self.sort_by(|a, b| {
+            keys.iter()
+                .zip(&columns)
+                .map(|(key, &col)| {
+                    let null = CellValue::Null;
+                    key.compare(a.get(col).unwrap_or(&null), b.get(col).unwrap_or(&null))
+                })
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+
+    /// Stable sort of the data rows with a comparator, leaving a header
+    /// row in place.
+    pub fn sort_by<F>(&mut self, mut compare: F) -> Result<()>
+    where
+        F: FnMut(&[CellValue], &[CellValue]) -> Ordering,
+    {
+        let start = self.data_start_row();
+        let rows = &self.data()[start..];
+        let mut order: Vec<usize> = (0..rows.len()).collect();
+        order.sort_by(|&a, &b| compare(&rows[a], &rows[b]));
+        self.reorder_rows(&order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet(rows: Vec<Vec<CellValue>>) -> Sheet {
+        let mut sheet = Sheet::from_data(rows);
+        sheet.name_columns_by_row(0).unwrap();
+        sheet
+    }
+
+    fn column(sheet: &Sheet, col: usize) -> Vec<String> {
+        (1..sheet.row_count())
+            .map(|r| sheet.get(r, col).unwrap().as_str())
+            .collect()
+    }
+
+    fn names(values: &[&str]) -> Sheet {
+        let mut rows = vec![vec![CellValue::from("name")]];
+        rows.extend(values.iter().map(|v| vec![CellValue::from(*v)]));
+        sheet(rows)
+    }
+
+    #[test]
+    fn test_multi_key_sort_is_stable() {
+        let mut sales = sheet(vec![
+            vec!["region".into(), "amount".into(), "id".into()],
+            vec!["West".into(), CellValue::Int(5), "a".into()],
+            vec!["East".into(), CellValue::Float(7.5), "b".into()],
+            vec!["East".into(), CellValue::Int(10), "c".into()],
+            vec!["West".into(), CellValue::Int(5), "d".into()],
+            vec!["East".into(), CellValue::Float(7.5), "e".into()],
+        ]);
+        sales
+            .sort(&[SortKey::asc("region"), SortKey::desc("amount")])
+            .unwrap();
+
+        assert_eq!(sales.get(0, 0).unwrap().as_str(), "region");
+        assert_eq!(column(&sales, 2), ["c", "b", "e", "a", "d"]);
+    }
+
+    #[test]
+    fn test_null_placement_ignores_direction() {
+        let mut data = sheet(vec![
+            vec!["v".into()],
+            vec![CellValue::Int(2)],
+            vec![CellValue::Null],
+            vec![CellValue::Int(1)],
+        ]);
+        data.sort(&[SortKey::desc("v")]).unwrap();
+        assert_eq!(column(&data, 0), ["2", "1", ""]);
+
+        data.sort(&[SortKey::asc("v").nulls(NullOrder::First)])
+            .unwrap();
+        assert_eq!(column(&data, 0), ["", "1", "2"]);
+    }
+
+    #[test]
+    fn test_numbers_sort_before_text() {
+        let mut data = sheet(vec![
+            vec!["v".into()],
+            vec!["b".into()],
+            vec![CellValue::Int(10)],
+            vec![CellValue::Float(9.5)],
+            vec!["a".into()],
+        ]);
+        data.sort(&[SortKey::asc("v")]).unwrap();
+        assert_eq!(column(&data, 0), ["9.5", "10", "a", "b"]);
+    }
+
+    #[test]
+    fn test_text_options() {
+        let values = ["item10", "Item2", "item9", "item02", "apple"];
+
+        let mut plain = names(&values);
+        plain.sort(&[SortKey::asc("name")]).unwrap();
+        assert_eq!(
+            column(&plain, 0),
+            ["Item2", "apple", "item02", "item10", "item9"]
+        );
+
+        let mut natural = names(&values);
+        natural
+            .sort(&[SortKey::asc("name").natural(true).ignore_case(true)])
+            .unwrap();
+        assert_eq!(
+            column(&natural, 0),
+            ["apple", "Item2", "item02", "item9", "item10"]
+        );
+    }
+
+    #[test]
+    fn test_unicode_collation() {
+        let values = ["zebra", "Éclair", "eclair", "apple", "Apple", "ecole"];
+
+        let mut binary = names(&values);
+        binary.sort(&[SortKey::asc("name")]).unwrap();
+        assert_eq!(
+            column(&binary, 0),
+            ["Apple", "apple", "eclair", "ecole", "zebra", "Éclair"]
+        );
+
+        let mut unicode = names(&values);
+        unicode
+            .sort(&[SortKey::asc("name").collation(Collation::Unicode)])
+            .unwrap();
+        assert_eq!(
+            column(&unicode, 0),
+            ["apple", "Apple", "eclair", "Éclair", "ecole", "zebra"]
+        );
+
+        assert_eq!("Unicode".parse::<Collation>().unwrap(), Collation::Unicode);
+        assert!("locale".parse::<Collation>().is_err());
+    }
+
+    #[test]
+    fn test_named_rows_follow_sort() {
+        let mut data = sheet(vec![
+            vec!["id".into(), "v".into()],
+            vec!["a".into(), CellValue::Int(3)],
+            vec!["b".into(), CellValue::Int(1)],
+            vec!["c".into(), CellValue::Int(2)],
+        ]);
+        data.name_rows_by_column(0).unwrap();
+        data.sort(&[SortKey::asc("v")]).unwrap();
+
+        assert_eq!(column(&data, 0), ["b", "c", "a"]);
+        assert_eq!(data.row_by_name("a").unwrap()[1].as_int(), Some(3));
+        assert_eq!(data.row_by_name("b").unwrap()[1].as_int(), Some(1));
+    }
+
+    #[test]
+    fn test_sort_key_parsing_and_errors() {
+        assert_eq!("amount".parse::<SortKey>().unwrap(), SortKey::asc("amount"));
+        assert_eq!(
+            "unit price DESC".parse::<SortKey>().unwrap(),
+            SortKey::desc("unit price")
+        );
+        assert!("  ".parse::<SortKey>().is_err());
+        assert!("sideways".parse::<Collation>().is_err());
+
+        let mut data = names(&["a"]);
+        assert!(matches!(
+            data.sort(&[SortKey::asc("missing")]),
+            Err(SheetError::ColumnNotFound { .. })
+        ));
+        assert!(matches!(
+            data.reorder_rows(&[0, 0]),
+            Err(SheetError::LengthMismatch { .. })
+        ));
+    }
+}
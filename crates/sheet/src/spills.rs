@@ -0,0 +1,426 @@
+//! Dynamic arrays: a formula whose result is an array spills it into the
+//! cells below and to the right, which `A1#` references read back. A spill
+//! blocked by a non-empty cell or the sheet edge shows `#SPILL!` instead.
+
+use crate::cell::CellValue;
+use crate::error::Result;
+use crate::sheet::{formula_value_to_cell_value, Sheet};
+use piptable_primitives::{CellAddress, CellRange, ErrorValue, Value};
+use std::collections::HashMap;
+
+/// How many times recalculation re-runs formulas reading cells whose spilled
+/// values changed before it stops
+pub(crate) const MAX_SPILL_PASSES: usize = 64;
+
+/// The array an anchor formula spilled, or tried to spill.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Spill {
+    /// Rows and columns of the array, including the anchor cell
+    rows: u32,
+    cols: u32,
+    /// Cells other than the anchor that hold spilled values, with the value
+    /// written; empty when the spill is blocked
+    cells: Vec<(CellAddress, CellValue)>,
+    blocked: bool,
+}
+
+impl Spill {
+    fn region(&self, anchor: CellAddress) -> CellRange {
+        CellRange::new(
+            anchor,
+            CellAddress::new(anchor.row + self.rows - 1, anchor.col + self.cols - 1),
+        )
+    }
+}
+
+/// Rows of an array result; a flat array is a single column.
+fn array_rows(items: Vec<Value>) -> Vec<Vec<Value>> {
+    if items.iter().all(|item| matches!(item, Value::Array(_))) {
+        items
+            .into_iter()
+            .map(|row| match row {
+                Value::Array(cells) => cells,
+                other => vec![other],
+            })
+            .collect()
+    } else {
+        items.into_iter().map(|item| vec![item]).collect()
+    }
+}
+
+impl Sheet {
+    /// The cells the array formula at `anchor` (e.g. `"A1"`) spills into,
+    /// including the anchor; what `A1#` refers to. `None` when the cell does
+    /// not hold an array result or its spill is blocked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `anchor` is not a valid cell reference.
+    pub fn spill_range(&self, anchor: &str) -> Result<Option<CellRange>> {
+        let anchor = self.get_a1_addr(anchor)?;
+        Ok(self.spill_region(anchor))
+    }
+
+    pub(crate) fn spill_region(&self, anchor: CellAddress) -> Option<CellRange> {
+        self.spills
+            .get(&anchor)
+            .filter(|spill| !spill.blocked)
+            .map(|spill| spill.region(anchor))
+    }
+
+    /// Cache a formula result in the formula cell at `cell`. An array result
+    /// spills over the empty cells below and to the right once `link` accepts
+    /// them as dependents of the anchor; when blocked the anchor caches
+    /// `#SPILL!`. Returns the cells, other than the anchor, whose values
+    /// changed.
+    pub(crate) fn store_formula_result(
+        &mut self,
+        cell: CellAddress,
+        value: Value,
+        link: &mut dyn FnMut(&[CellAddress]) -> bool,
+    ) -> Result<Vec<CellAddress>> {
+        if !matches!(
+            self.get(cell.row as usize, cell.col as usize),
+            Ok(CellValue::Formula(_))
+        ) {
+            return self
+                .set_cell_value_raw(
+                    cell.row as usize,
+                    cell.col as usize,
+                    formula_value_to_cell_value(value),
+                )
+                .map(|()| Vec::new());
+        }
+        let had_spill = self.spills.contains_key(&cell);
+        let mut previous: HashMap<CellAddress, CellValue> =
+            self.release_spill(cell).into_iter().collect();
+        let rows = match value {
+            Value::Array(items) => array_rows(items),
+            scalar => {
+                if had_spill {
+                    link(&[]);
+                }
+                self.cache_formula_value(cell, scalar);
+                return Ok(previous.into_keys().collect());
+            }
+        };
+        let height = rows.len() as u32;
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0) as u32;
+        if height == 0 || width == 0 {
+            if had_spill {
+                link(&[]);
+            }
+            self.cache_formula_value(cell, Value::Empty);
+            return Ok(previous.into_keys().collect());
+        }
+
+        let mut spill = Spill {
+            rows: height,
+            cols: width,
+            cells: Vec::new(),
+            blocked: false,
+        };
+        let region = spill.region(cell);
+        let in_bounds = (region.end.row as usize) < self.row_count()
+            && (region.end.col as usize) < self.col_count();
+        let targets: Vec<CellAddress> = (region.start.row..=region.end.row)
+            .flat_map(|row| (region.start.col..=region.end.col).map(move |col| (row, col)))
+            .map(|(row, col)| CellAddress::new(row, col))
+            .filter(|addr| *addr != cell)
+            .collect();
+        let empty = in_bounds
+            && targets.iter().all(|addr| {
+                matches!(
+                    self.get(addr.row as usize, addr.col as usize),
+                    Ok(CellValue::Null)
+                )
+            });
+        if !empty || !link(&targets) {
+            // A rejected link has already dropped the anchor's old edges
+            if !empty && had_spill {
+                link(&[]);
+            }
+            spill.blocked = true;
+            self.spills.insert(cell, spill);
+            self.cache_formula_value(cell, Value::Error(ErrorValue::Spill));
+            return Ok(previous.into_keys().collect());
+        }
+
+        let mut changed = Vec::new();
+        let mut top_left = Value::Empty;
+        for (r, row) in rows.into_iter().enumerate() {
+            let mut row = row.into_iter();
+            for c in 0..width {
+                let value = row.next().unwrap_or(Value::Error(ErrorValue::NA));
+                let addr = CellAddress::new(cell.row + r as u32, cell.col + c);
+                if addr == cell {
+                    top_left = value;
+                    continue;
+                }
+                let value = formula_value_to_cell_value(value);
+                if previous.remove(&addr).as_ref() != Some(&value) {
+                    changed.push(addr);
+                }
+                self.set_cell_value_raw(addr.row as usize, addr.col as usize, value.clone())?;
+                spill.cells.push((addr, value));
+            }
+        }
+        changed.extend(previous.into_keys());
+        self.spills.insert(cell, spill);
+        self.cache_formula_value(cell, top_left);
+        Ok(changed)
+    }
+
+    fn cache_formula_value(&mut self, cell: CellAddress, value: Value) {
+        if let Ok(CellValue::Formula(formula)) = self.get_mut(cell.row as usize, cell.col as usize)
+        {
+            formula.cached = Some(Box::new(formula_value_to_cell_value(value)));
+        }
+    }
+
+    /// Forget the spill of the formula at `anchor`, emptying the cells that
+    /// still hold the values it wrote. Returns the emptied cells with the
+    /// values they held.
+    fn release_spill(&mut self, anchor: CellAddress) -> Vec<(CellAddress, CellValue)> {
+        let Some(spill) = self.spills.remove(&anchor) else {
+            return Vec::new();
+        };
+        spill
+            .cells
+            .into_iter()
+            .filter(
+                |(addr, value)| match self.get_mut(addr.row as usize, addr.col as usize) {
+                    Ok(cell) if cell == value => {
+                        *cell = CellValue::Null;
+                        true
+                    }
+                    _ => false,
+                },
+            )
+            .collect()
+    }
+
+    /// Release the spills of anchors that no longer hold a formula, returning
+    /// the cells emptied.
+    pub(crate) fn release_orphaned_spills(&mut self) -> Vec<CellAddress> {
+        let orphaned: Vec<CellAddress> = self
+            .spills
+            .keys()
+            .filter(|anchor| {
+                !matches!(
+                    self.get(anchor.row as usize, anchor.col as usize),
+                    Ok(CellValue::Formula(_))
+                )
+            })
+            .copied()
+            .collect();
+        orphaned
+            .iter()
+            .flat_map(|anchor| self.release_spill(*anchor))
+            .map(|(addr, _)| addr)
+            .collect()
+    }
+
+    /// The cells to mark dirty when `addr` is about to be edited: the cells
+    /// spilled from `addr` if it is an anchor, and the anchors whose spill
+    /// covers `addr`.
+    pub(crate) fn spill_affected(&self, addr: CellAddress) -> Vec<CellAddress> {
+        let mut affected: Vec<CellAddress> = self
+            .spills
+            .get(&addr)
+            .map(|spill| spill.cells.iter().map(|(cell, _)| *cell).collect())
+            .unwrap_or_default();
+        affected.extend(self.spills.iter().filter_map(|(anchor, spill)| {
+            (*anchor != addr && spill.region(*anchor).contains(&addr)).then_some(*anchor)
+        }));
+        affected
+    }
+
+    /// Update spills for an edit of `addr`: an edited anchor releases its
+    /// spill, and an edited spill cell is no longer the anchor's to clear.
+    pub(crate) fn spill_cell_edited(&mut self, addr: CellAddress) {
+        self.release_spill(addr);
+        for spill in self.spills.values_mut() {
+            spill.cells.retain(|(cell, _)| *cell != addr);
+        }
+    }
+
+    /// Shift spills below a row inserted at `index`.
+    pub(crate) fn spills_row_inserted(&mut self, index: usize) {
+        let shift = |addr: CellAddress| {
+            if addr.row as usize >= index {
+                CellAddress::new(addr.row + 1, addr.col)
+            } else {
+                addr
+            }
+        };
+        self.spills = std::mem::take(&mut self.spills)
+            .into_iter()
+            .map(|(anchor, mut spill)| {
+                for (cell, _) in &mut spill.cells {
+                    *cell = shift(*cell);
+                }
+                (shift(anchor), spill)
+            })
+            .collect();
+    }
+
+    /// Shift spills after rows are removed; `keep[i]` says whether old row
+    /// `i` survived. The spill of a removed anchor is released.
+    pub(crate) fn spills_rows_retained(&mut self, keep: &[bool]) {
+        let moved = |addr: CellAddress| {
+            let row = addr.row as usize;
+            keep.get(row).copied().unwrap_or(true).then(|| {
+                let removed = keep.iter().take(row).filter(|kept| !**kept).count();
+                CellAddress::new((row - removed) as u32, addr.col)
+            })
+        };
+        let mut orphaned = Vec::new();
+        self.spills = std::mem::take(&mut self.spills)
+            .into_iter()
+            .filter_map(|(anchor, mut spill)| {
+                spill.cells = spill
+                    .cells
+                    .into_iter()
+                    .filter_map(|(cell, value)| Some((moved(cell)?, value)))
+                    .collect();
+                match moved(anchor) {
+                    Some(anchor) => Some((anchor, spill)),
+                    None => {
+                        orphaned.push(spill);
+                        None
+                    }
+                }
+            })
+            .collect();
+        for (addr, value) in orphaned.into_iter().flat_map(|spill| spill.cells) {
+            if let Ok(cell) = self.get_mut(addr.row as usize, addr.col as usize) {
+                if *cell == value {
+                    *cell = CellValue::Null;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::Book;
+
+    fn blank(rows: usize, cols: usize) -> Sheet {
+        Sheet::from_data(vec![vec![CellValue::Null; cols]; rows])
+    }
+
+    fn value(sheet: &Sheet, cell: &str) -> CellValue {
+        sheet.get_a1(cell).unwrap().cached_or_self().clone()
+    }
+
+    #[test]
+    fn test_array_formula_spills() {
+        let mut sheet = blank(4, 3);
+        sheet.set_formula("A1", "=SEQUENCE(3, 2)").unwrap();
+        sheet.evaluate_formulas().unwrap();
+
+        assert_eq!(value(&sheet, "A1"), CellValue::Int(1));
+        assert_eq!(value(&sheet, "B1"), CellValue::Int(2));
+        assert_eq!(value(&sheet, "B3"), CellValue::Int(6));
+        assert_eq!(value(&sheet, "A4"), CellValue::Null);
+        assert_eq!(
+            sheet.spill_range("A1").unwrap(),
+            Some(CellRange::new(
+                CellAddress::new(0, 0),
+                CellAddress::new(2, 1)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_blocked_spill_and_unblock() {
+        let mut sheet = blank(3, 2);
+        sheet.set_a1("A3", "x").unwrap();
+        sheet.set_formula("A1", "=SEQUENCE(3)").unwrap();
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(value(&sheet, "A1"), CellValue::String("#SPILL!".into()));
+        assert_eq!(value(&sheet, "A2"), CellValue::Null);
+        assert_eq!(sheet.spill_range("A1").unwrap(), None);
+
+        sheet.set_a1("A3", CellValue::Null).unwrap();
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(value(&sheet, "A3"), CellValue::Int(3));
+
+        // Typing into the spill range blocks it again
+        sheet.set_a1("A2", 9).unwrap();
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(value(&sheet, "A1"), CellValue::String("#SPILL!".into()));
+        assert_eq!(value(&sheet, "A2"), CellValue::Int(9));
+        assert_eq!(value(&sheet, "A3"), CellValue::Null);
+    }
+
+    #[test]
+    fn test_spill_past_sheet_edge_is_blocked() {
+        let mut sheet = blank(2, 1);
+        sheet.set_formula("A1", "=SEQUENCE(3)").unwrap();
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(value(&sheet, "A1"), CellValue::String("#SPILL!".into()));
+    }
+
+    #[test]
+    fn test_spill_shrinks_and_dependents_follow() {
+        let mut sheet = blank(4, 3);
+        sheet.set_a1("C1", 3).unwrap();
+        sheet.set_formula("A1", "=SEQUENCE(C1)").unwrap();
+        sheet.set_formula("B1", "=SUM(A1#)").unwrap();
+        sheet.set_formula("B2", "=A3*10").unwrap();
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(value(&sheet, "B1"), CellValue::Float(6.0));
+        assert_eq!(value(&sheet, "B2"), CellValue::Float(30.0));
+
+        sheet.set_a1("C1", 2).unwrap();
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(value(&sheet, "A3"), CellValue::Null);
+        assert_eq!(value(&sheet, "B1"), CellValue::Float(3.0));
+        assert_eq!(value(&sheet, "B2"), CellValue::String("#VALUE!".into()));
+    }
+
+    #[test]
+    fn test_replacing_anchor_clears_spill() {
+        let mut sheet = blank(3, 1);
+        sheet.set_formula("A1", "=SEQUENCE(3)").unwrap();
+        sheet.evaluate_formulas().unwrap();
+        sheet.set_a1("A1", "done").unwrap();
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(value(&sheet, "A2"), CellValue::Null);
+        assert_eq!(value(&sheet, "A3"), CellValue::Null);
+    }
+
+    #[test]
+    fn test_deleting_rows_moves_spill() {
+        let mut sheet = blank(5, 1);
+        sheet.set_formula("A2", "=SEQUENCE(2)").unwrap();
+        sheet.evaluate_formulas().unwrap();
+        sheet.row_delete(0).unwrap();
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(value(&sheet, "A1"), CellValue::Int(1));
+        assert_eq!(value(&sheet, "A2"), CellValue::Int(2));
+        assert_eq!(value(&sheet, "A3"), CellValue::Null);
+    }
+
+    #[test]
+    fn test_book_spill_reference_across_sheets() {
+        let mut book = Book::new();
+        book.add_sheet("Data", blank(3, 1)).unwrap();
+        book.add_sheet("Summary", blank(1, 1)).unwrap();
+        book.set_formula("Data", "A1", "=SEQUENCE(3, 1, 10)")
+            .unwrap();
+        book.set_formula("Summary", "A1", "=SUM(Data!A1#)").unwrap();
+        book.recalculate().unwrap();
+        let total = book.get_sheet("Summary").unwrap().get(0, 0).unwrap();
+        assert_eq!(total.cached_or_self(), &CellValue::Float(33.0));
+
+        book.set_a1("Data", "A3", "x").unwrap();
+        book.recalculate().unwrap();
+        let total = book.get_sheet("Summary").unwrap().get(0, 0).unwrap();
+        assert_eq!(total.cached_or_self(), &CellValue::String("#REF!".into()));
+    }
+}
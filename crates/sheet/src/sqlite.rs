@@ -0,0 +1,715 @@
+//! SQLite database support for Sheet and Book
+//!
+//! Tables are read into sheets with a header row, and sheets are written back
+//! as tables in replace, append or upsert mode. The declared type of each
+//! column decides how its values map to `CellValue` and Arrow types.
+
+use crate::book::Book;
+use crate::cell::CellValue;
+use crate::columnar::{build_arrow_array, infer_column_type};
+use crate::error::{Result, SheetError};
+use crate::sheet::Sheet;
+use arrow::array::{ArrayRef, RecordBatch};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use chrono::NaiveTime;
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params_from_iter, Connection, OpenFlags};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Type of a SQLite column, derived from its declared type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqliteColumnType {
+    /// `INTEGER`, `INT`, `BIGINT`, ... (Arrow `Int64`)
+    Integer,
+    /// `REAL`, `FLOAT`, `DOUBLE` (Arrow `Float64`)
+    Real,
+    /// `TEXT`, `VARCHAR`, `CLOB`, ... (Arrow `Utf8`)
+    Text,
+    /// `BOOLEAN`, stored as 0/1 (Arrow `Boolean`)
+    Boolean,
+    /// `BLOB`, read as lossy UTF-8 text (Arrow `Utf8`)
+    Blob,
+    /// `DATE`, stored as ISO 8601 text (Arrow `Date32`)
+    Date,
+    /// `DATETIME` or `TIMESTAMP`, stored as ISO 8601 text (Arrow `Timestamp`)
+    DateTime,
+    /// No declared type or `NUMERIC` affinity; typed by the stored values
+    Any,
+}
+
+impl SqliteColumnType {
+    /// Classify a declared column type using SQLite's affinity rules.
+    ///
+    /// `BOOL` is checked first so that `BOOLEAN` columns round-trip as booleans
+    /// rather than as the integers SQLite stores them as.
+    #[must_use]
+    pub fn from_declared(declared: Option<&str>) -> Self {
+        let Some(declared) = declared else {
+            return Self::Any;
+        };
+        let declared = declared.to_uppercase();
+        if declared.contains("BOOL") {
+            Self::Boolean
+        } else if declared.contains("INT") {
+            Self::Integer
+        } else if ["CHAR", "CLOB", "TEXT"]
+            .iter()
+            .any(|t| declared.contains(t))
+        {
+            Self::Text
+        } else if declared.contains("BLOB") {
+            Self::Blob
+        } else if ["REAL", "FLOA", "DOUB"]
+            .iter()
+            .any(|t| declared.contains(t))
+        {
+            Self::Real
+        } else if declared.contains("DATETIME") || declared.contains("TIMESTAMP") {
+            Self::DateTime
+        } else if declared.contains("DATE") {
+            Self::Date
+        } else {
+            Self::Any
+        }
+    }
+
+    /// Arrow type fixed by the declaration, or `None` for [`SqliteColumnType::Any`].
+    #[must_use]
+    pub fn arrow_type(self) -> Option<DataType> {
+        match self {
+            Self::Integer => Some(DataType::Int64),
+            Self::Real => Some(DataType::Float64),
+            Self::Text | Self::Blob => Some(DataType::Utf8),
+            Self::Boolean => Some(DataType::Boolean),
+            Self::Date => Some(DataType::Date32),
+            Self::DateTime => Some(DataType::Timestamp(TimeUnit::Microsecond, None)),
+            Self::Any => None,
+        }
+    }
+
+    /// Convert a stored value of this column to a cell.
+    fn to_cell(self, value: ValueRef<'_>) -> CellValue {
+        match value {
+            ValueRef::Null => CellValue::Null,
+            ValueRef::Integer(i) if self == Self::Boolean => CellValue::Bool(i != 0),
+            ValueRef::Integer(i) => CellValue::Int(i),
+            ValueRef::Real(f) => CellValue::Float(f),
+            // Text that does not parse stays text rather than failing the read
+            ValueRef::Text(bytes) if matches!(self, Self::Date | Self::DateTime) => {
+                let text = String::from_utf8_lossy(bytes);
+                match CellValue::parse_iso_temporal(&text) {
+                    Some(CellValue::DateTime(dt))
+                        if self == Self::Date && dt.time() == NaiveTime::MIN =>
+                    {
+                        CellValue::Date(dt.date())
+                    }
+                    Some(value @ (CellValue::Date(_) | CellValue::DateTime(_))) => value,
+                    _ => CellValue::String(text.into_owned()),
+                }
+            }
+            ValueRef::Text(bytes) | ValueRef::Blob(bytes) => {
+                CellValue::String(String::from_utf8_lossy(bytes).into_owned())
+            }
+        }
+    }
+}
+
+/// How a sheet is written into an existing SQLite table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SqliteWriteMode {
+    /// Drop the table if it exists and recreate it
+    #[default]
+    Replace,
+    /// Insert rows, creating the table if needed
+    Append,
+    /// Update rows whose key matches and insert the rest
+    Upsert,
+}
+
+impl FromStr for SqliteWriteMode {
+    type Err = SheetError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "replace" => Ok(Self::Replace),
+            "append" => Ok(Self::Append),
+            "upsert" => Ok(Self::Upsert),
+            _ => Err(SheetError::Parse(format!(
+                "Unknown SQLite write mode '{s}', expected replace, append or upsert"
+            ))),
+        }
+    }
+}
+
+/// Options for writing a sheet to a SQLite database
+#[derive(Debug, Clone)]
+pub struct SqliteWriteOptions {
+    /// Target table name
+    pub table: String,
+    /// Write mode
+    pub mode: SqliteWriteMode,
+    /// Column identifying rows for upserts
+    pub key: Option<String>,
+}
+
+impl SqliteWriteOptions {
+    /// Write to `table`, replacing it if it exists
+    #[must_use]
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            mode: SqliteWriteMode::default(),
+            key: None,
+        }
+    }
+
+    /// Set the write mode
+    #[must_use]
+    pub fn with_mode(mut self, mode: SqliteWriteMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the key column used by upserts
+    #[must_use]
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+}
+
+impl Sheet {
+    /// Load a table from a SQLite database
+    ///
+    /// # Example
+    /// ```no_run
+    /// use piptable_sheet::Sheet;
+    ///
+    /// let customers = Sheet::from_sqlite("app.db", "customers").unwrap();
+    /// ```
+    pub fn from_sqlite<P: AsRef<Path>>(path: P, table: &str) -> Result<Self> {
+        let conn = open_read_only(path.as_ref())?;
+        let sql = format!("SELECT * FROM {}", quote_ident(table));
+        let (sheet, _) = read_query(&conn, &sql, table)?;
+        Ok(sheet)
+    }
+
+    /// Load the result of a SQL query against a SQLite database
+    ///
+    /// # Example
+    /// ```no_run
+    /// use piptable_sheet::Sheet;
+    ///
+    /// let active = Sheet::from_sqlite_query(
+    ///     "app.db",
+    ///     "SELECT id, name FROM customers WHERE active",
+    /// ).unwrap();
+    /// ```
+    pub fn from_sqlite_query<P: AsRef<Path>>(path: P, sql: &str) -> Result<Self> {
+        let conn = open_read_only(path.as_ref())?;
+        let (sheet, _) = read_query(&conn, sql, "Sheet1")?;
+        Ok(sheet)
+    }
+
+    /// Save the sheet as a table in a SQLite database
+    ///
+    /// The database file is created if it does not exist. New tables get
+    /// `INTEGER`, `REAL`, `BOOLEAN` or `TEXT` columns from the sheet's schema,
+    /// or inferred from the data without one. Requires columns to be named.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use piptable_sheet::{Sheet, SqliteWriteMode, SqliteWriteOptions};
+    ///
+    /// let mut sheet = Sheet::from_data(vec![
+    ///     vec!["id", "name"],
+    ///     vec!["1", "Alice"],
+    /// ]);
+    /// sheet.name_columns_by_row(0).unwrap();
+    ///
+    /// let options = SqliteWriteOptions::new("customers")
+    ///     .with_mode(SqliteWriteMode::Upsert)
+    ///     .with_key("id");
+    /// sheet.save_as_sqlite("app.db", &options).unwrap();
+    /// ```
+    pub fn save_as_sqlite<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &SqliteWriteOptions,
+    ) -> Result<()> {
+        let sheet = self.coerced_to_schema()?;
+        let column_names = sheet.column_names().ok_or_else(|| {
+            SheetError::ColumnsNotNamed("Columns must be named to export as SQLite".to_string())
+        })?;
+
+        let key_idx = match (&options.key, options.mode) {
+            (Some(key), SqliteWriteMode::Upsert) => Some(
+                column_names
+                    .iter()
+                    .position(|name| name == key)
+                    .ok_or_else(|| SheetError::ColumnNotFound { name: key.clone() })?,
+            ),
+            (None, SqliteWriteMode::Upsert) => {
+                return Err(SheetError::Parse(
+                    "Upsert into SQLite requires a key column".to_string(),
+                ))
+            }
+            _ => None,
+        };
+
+        let rows: Vec<&Vec<CellValue>> = sheet.data().iter().skip(header_rows(&sheet)).collect();
+        let table = quote_ident(&options.table);
+        let columns: Vec<String> = column_names.iter().map(|name| quote_ident(name)).collect();
+
+        let mut conn = Connection::open(path)?;
+        let tx = conn.transaction()?;
+
+        if options.mode == SqliteWriteMode::Replace {
+            tx.execute(&format!("DROP TABLE IF EXISTS {table}"), [])?;
+        }
+        let definitions: Vec<String> = columns
+            .iter()
+            .enumerate()
+            .map(|(idx, column)| {
+                let name = &column_names[idx];
+                let dtype = sheet
+                    .schema_data_type(name)
+                    .unwrap_or_else(|| infer_column_type(&rows, idx));
+                let not_null = if sheet.schema_nullable(name) {
+                    ""
+                } else {
+                    " NOT NULL"
+                };
+                format!("{column} {}{not_null}", sql_type(&dtype))
+            })
+            .collect();
+        tx.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {table} ({})",
+                definitions.join(", ")
+            ),
+            [],
+        )?;
+
+        {
+            let placeholders = vec!["?"; columns.len()].join(", ");
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO {table} ({}) VALUES ({placeholders})",
+                columns.join(", ")
+            ))?;
+
+            // Upserts try an UPDATE by key first, so the target table does not
+            // need a unique constraint on the key column
+            let mut update = match key_idx {
+                Some(key_idx) => {
+                    let key = &columns[key_idx];
+                    let mut assignments: Vec<String> = columns
+                        .iter()
+                        .enumerate()
+                        .filter(|(idx, _)| *idx != key_idx)
+                        .map(|(_, column)| format!("{column} = ?"))
+                        .collect();
+                    if assignments.is_empty() {
+                        assignments.push(format!("{key} = {key}"));
+                    }
+                    let sql = format!(
+                        "UPDATE {table} SET {} WHERE {key} = ?",
+                        assignments.join(", ")
+                    );
+                    Some((key_idx, tx.prepare(&sql)?))
+                }
+                None => None,
+            };
+
+            for row in &rows {
+                let values: Vec<SqlValue> = (0..columns.len())
+                    .map(|idx| cell_to_sql(row.get(idx)))
+                    .collect();
+
+                if let Some((key_idx, update)) = update.as_mut() {
+                    let params = values
+                        .iter()
+                        .enumerate()
+                        .filter(|(idx, _)| idx != key_idx)
+                        .map(|(_, value)| value)
+                        .chain(std::iter::once(&values[*key_idx]));
+                    if update.execute(params_from_iter(params))? > 0 {
+                        continue;
+                    }
+                }
+                insert.execute(params_from_iter(values.iter()))?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+impl Book {
+    /// Load every table of a SQLite database, one sheet per table
+    ///
+    /// # Example
+    /// ```no_run
+    /// use piptable_sheet::Book;
+    ///
+    /// let book = Book::from_sqlite("app.db").unwrap();
+    /// ```
+    pub fn from_sqlite<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let conn = open_read_only(path.as_ref())?;
+        let mut book = Book::new();
+        for table in table_names(&conn)? {
+            let sql = format!("SELECT * FROM {}", quote_ident(&table));
+            let (sheet, _) = read_query(&conn, &sql, &table)?;
+            book.add_sheet(&table, sheet)?;
+        }
+        Ok(book)
+    }
+
+    /// List the tables of a SQLite database
+    pub fn sqlite_table_names<P: AsRef<Path>>(path: P) -> Result<Vec<String>> {
+        let conn = open_read_only(path.as_ref())?;
+        table_names(&conn)
+    }
+
+    /// Read every table of a SQLite database as an Arrow record batch
+    ///
+    /// Column types follow the declared SQLite types. Columns without a
+    /// declared type are typed from their values.
+    pub fn sqlite_record_batches<P: AsRef<Path>>(path: P) -> Result<Vec<(String, RecordBatch)>> {
+        let conn = open_read_only(path.as_ref())?;
+        let mut tables = Vec::new();
+        for table in table_names(&conn)? {
+            let sql = format!("SELECT * FROM {}", quote_ident(&table));
+            let (sheet, types) = read_query(&conn, &sql, &table)?;
+            tables.push((table, sheet_to_batch(&sheet, &types)?));
+        }
+        Ok(tables)
+    }
+}
+
+/// Open an existing database without creating it.
+fn open_read_only(path: &Path) -> Result<Connection> {
+    let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    Ok(Connection::open_with_flags(path, flags)?)
+}
+
+/// User tables in creation order.
+fn table_names(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master \
+         WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY rowid",
+    )?;
+    let names = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(names)
+}
+
+/// Run a query and collect its rows into a sheet with a header row.
+fn read_query(conn: &Connection, sql: &str, name: &str) -> Result<(Sheet, Vec<SqliteColumnType>)> {
+    let mut stmt = conn.prepare(sql)?;
+    let (names, types): (Vec<String>, Vec<SqliteColumnType>) = stmt
+        .columns()
+        .iter()
+        .map(|column| {
+            (
+                column.name().to_string(),
+                SqliteColumnType::from_declared(column.decl_type()),
+            )
+        })
+        .unzip();
+
+    if names.is_empty() {
+        return Ok((Sheet::with_name(name), types));
+    }
+
+    let mut data = vec![names.into_iter().map(CellValue::String).collect::<Vec<_>>()];
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let mut cells = Vec::with_capacity(types.len());
+        for (idx, column_type) in types.iter().enumerate() {
+            cells.push(column_type.to_cell(row.get_ref(idx)?));
+        }
+        data.push(cells);
+    }
+
+    let mut sheet = Sheet::with_name(name);
+    *sheet.data_mut() = data;
+    sheet.name_columns_by_row(0)?;
+    Ok((sheet, types))
+}
+
+/// Build a record batch from a sheet read by [`read_query`].
+fn sheet_to_batch(sheet: &Sheet, types: &[SqliteColumnType]) -> Result<RecordBatch> {
+    let names = sheet.column_names().cloned().unwrap_or_default();
+    let rows: Vec<&Vec<CellValue>> = sheet.data().iter().skip(1).collect();
+
+    let dtypes: Vec<DataType> = types
+        .iter()
+        .enumerate()
+        .map(|(idx, column_type)| {
+            let all_null = rows.iter().all(|row| row[idx].is_null());
+            match column_type.arrow_type() {
+                // Stored values win over the declaration, since SQLite does
+                // not enforce column types
+                Some(declared) if all_null => declared,
+                _ if all_null => DataType::Utf8,
+                _ => infer_column_type(&rows, idx),
+            }
+        })
+        .collect();
+
+    let fields: Vec<Field> = names
+        .iter()
+        .zip(&dtypes)
+        .map(|(name, dtype)| Field::new(name, dtype.clone(), true))
+        .collect();
+    let arrays: Vec<ArrayRef> = dtypes
+        .iter()
+        .enumerate()
+        .map(|(idx, dtype)| build_arrow_array(&rows, idx, dtype))
+        .collect();
+
+    Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?)
+}
+
+/// Number of leading rows that repeat the column names.
+fn header_rows(sheet: &Sheet) -> usize {
+    let (Some(names), Some(first_row)) = (sheet.column_names(), sheet.data().first()) else {
+        return 0;
+    };
+    let names_match = names.iter().enumerate().all(|(idx, name)| {
+        first_row
+            .get(idx)
+            .is_some_and(|cell| cell.as_str() == *name)
+    });
+    usize::from(names_match)
+}
+
+/// Column type used when creating a table.
+fn sql_type(dtype: &DataType) -> &'static str {
+    match dtype {
+        DataType::Boolean => "BOOLEAN",
+        DataType::Int64 => "INTEGER",
+        DataType::Float64 => "REAL",
+        DataType::Date32 => "DATE",
+        DataType::Timestamp(_, _) => "TIMESTAMP",
+        _ => "TEXT",
+    }
+}
+
+/// Convert a cell to a SQLite value, storing booleans as 0/1.
+fn cell_to_sql(cell: Option<&CellValue>) -> SqlValue {
+    match cell.map(CellValue::cached_or_self) {
+        None | Some(CellValue::Null) => SqlValue::Null,
+        Some(CellValue::Bool(b)) => SqlValue::Integer(i64::from(*b)),
+        Some(CellValue::Int(i)) => SqlValue::Integer(*i),
+        Some(CellValue::Float(f)) => SqlValue::Real(*f),
+        Some(CellValue::String(s)) => SqlValue::Text(s.clone()),
+        Some(CellValue::Formula(formula)) => SqlValue::Text(formula.source.clone()),
+        Some(value @ (CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_))) => {
+            SqlValue::Text(value.as_str())
+        }
+    }
+}
+
+/// Quote an identifier for use in SQL.
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn customers() -> Sheet {
+        let mut sheet = Sheet::new();
+        sheet.data_mut().push(vec![
+            CellValue::String("id".to_string()),
+            CellValue::String("name".to_string()),
+            CellValue::String("score".to_string()),
+            CellValue::String("active".to_string()),
+        ]);
+        sheet.data_mut().push(vec![
+            CellValue::Int(1),
+            CellValue::String("Alice".to_string()),
+            CellValue::Float(95.5),
+            CellValue::Bool(true),
+        ]);
+        sheet.data_mut().push(vec![
+            CellValue::Int(2),
+            CellValue::String("Bob".to_string()),
+            CellValue::Null,
+            CellValue::Bool(false),
+        ]);
+        sheet.name_columns_by_row(0).unwrap();
+        sheet
+    }
+
+    #[test]
+    fn test_declared_type_affinity() {
+        use SqliteColumnType::*;
+        assert_eq!(SqliteColumnType::from_declared(Some("BIGINT")), Integer);
+        assert_eq!(SqliteColumnType::from_declared(Some("varchar(20)")), Text);
+        assert_eq!(SqliteColumnType::from_declared(Some("DOUBLE")), Real);
+        assert_eq!(SqliteColumnType::from_declared(Some("BOOLEAN")), Boolean);
+        assert_eq!(SqliteColumnType::from_declared(Some("BLOB")), Blob);
+        assert_eq!(SqliteColumnType::from_declared(Some("DATE")), Date);
+        assert_eq!(SqliteColumnType::from_declared(Some("TIMESTAMP")), DateTime);
+        assert_eq!(SqliteColumnType::from_declared(Some("NUMERIC")), Any);
+        assert_eq!(SqliteColumnType::from_declared(None), Any);
+    }
+
+    #[test]
+    fn test_sqlite_roundtrip_preserves_types() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("app.db");
+
+        customers()
+            .save_as_sqlite(&path, &SqliteWriteOptions::new("customers"))
+            .unwrap();
+        let loaded = Sheet::from_sqlite(&path, "customers").unwrap();
+
+        assert_eq!(loaded.name(), "customers");
+        assert_eq!(loaded.row_count(), 3);
+        assert!(matches!(loaded.get(1, 0).unwrap(), CellValue::Int(1)));
+        assert!(
+            matches!(loaded.get(1, 2).unwrap(), CellValue::Float(f) if (f - 95.5).abs() < 0.001)
+        );
+        assert!(matches!(loaded.get(1, 3).unwrap(), CellValue::Bool(true)));
+        assert!(loaded.get(2, 2).unwrap().is_null());
+    }
+
+    #[test]
+    fn test_sqlite_roundtrip_dates() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("app.db");
+
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let at = date.and_hms_opt(9, 30, 0).unwrap();
+        let mut sheet = Sheet::new();
+        *sheet.data_mut() = vec![
+            vec![CellValue::from("due"), CellValue::from("sent")],
+            vec![CellValue::Date(date), CellValue::DateTime(at)],
+        ];
+        sheet.name_columns_by_row(0).unwrap();
+        sheet
+            .save_as_sqlite(&path, &SqliteWriteOptions::new("orders"))
+            .unwrap();
+
+        let loaded = Sheet::from_sqlite(&path, "orders").unwrap();
+        assert_eq!(loaded.get(1, 0).unwrap(), &CellValue::Date(date));
+        assert_eq!(loaded.get(1, 1).unwrap(), &CellValue::DateTime(at));
+    }
+
+    #[test]
+    fn test_sqlite_write_modes() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("modes.db");
+        let sheet = customers();
+
+        sheet
+            .save_as_sqlite(&path, &SqliteWriteOptions::new("t"))
+            .unwrap();
+        sheet
+            .save_as_sqlite(
+                &path,
+                &SqliteWriteOptions::new("t").with_mode(SqliteWriteMode::Append),
+            )
+            .unwrap();
+        assert_eq!(Sheet::from_sqlite(&path, "t").unwrap().row_count(), 5);
+
+        sheet
+            .save_as_sqlite(&path, &SqliteWriteOptions::new("t"))
+            .unwrap();
+        assert_eq!(Sheet::from_sqlite(&path, "t").unwrap().row_count(), 3);
+
+        let mut updates = Sheet::from_data(vec![
+            vec![
+                CellValue::String("id".into()),
+                CellValue::String("name".into()),
+            ],
+            vec![CellValue::Int(2), CellValue::String("Robert".into())],
+            vec![CellValue::Int(3), CellValue::String("Carol".into())],
+        ]);
+        updates.name_columns_by_row(0).unwrap();
+        updates
+            .save_as_sqlite(
+                &path,
+                &SqliteWriteOptions::new("t")
+                    .with_mode(SqliteWriteMode::Upsert)
+                    .with_key("id"),
+            )
+            .unwrap();
+
+        let loaded = Sheet::from_sqlite_query(&path, "SELECT name FROM t ORDER BY id").unwrap();
+        let names: Vec<String> = loaded
+            .data()
+            .iter()
+            .skip(1)
+            .map(|r| r[0].as_str())
+            .collect();
+        assert_eq!(names, vec!["Alice", "Robert", "Carol"]);
+    }
+
+    #[test]
+    fn test_sqlite_upsert_requires_key() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("upsert.db");
+        let options = SqliteWriteOptions::new("t").with_mode(SqliteWriteMode::Upsert);
+
+        assert!(customers().save_as_sqlite(&path, &options).is_err());
+        assert!(matches!(
+            customers().save_as_sqlite(&path, &options.with_key("missing")),
+            Err(SheetError::ColumnNotFound { .. })
+        ));
+        assert!("merge".parse::<SqliteWriteMode>().is_err());
+    }
+
+    #[test]
+    fn test_sqlite_book_and_record_batches() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("book.db");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE orders (id INTEGER, total REAL, note TEXT);
+                 INSERT INTO orders VALUES (1, 9.5, NULL);
+                 CREATE TABLE empty (flag BOOLEAN, raw);",
+            )
+            .unwrap();
+        }
+        customers()
+            .save_as_sqlite(&path, &SqliteWriteOptions::new("customers"))
+            .unwrap();
+
+        assert_eq!(
+            Book::sqlite_table_names(&path).unwrap(),
+            vec!["orders", "empty", "customers"]
+        );
+        let book = Book::from_sqlite(&path).unwrap();
+        assert_eq!(book.sheet_count(), 3);
+
+        let batches = Book::sqlite_record_batches(&path).unwrap();
+        let (_, orders) = &batches[0];
+        assert_eq!(orders.schema().field(0).data_type(), &DataType::Int64);
+        assert_eq!(orders.schema().field(1).data_type(), &DataType::Float64);
+        assert_eq!(orders.schema().field(2).data_type(), &DataType::Utf8);
+        let (_, empty) = &batches[1];
+        assert_eq!(empty.num_rows(), 0);
+        assert_eq!(empty.schema().field(0).data_type(), &DataType::Boolean);
+        assert_eq!(empty.schema().field(1).data_type(), &DataType::Utf8);
+    }
+
+    #[test]
+    fn test_sqlite_missing_database_is_not_created() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("missing.db");
+        assert!(Sheet::from_sqlite(&path, "t").is_err());
+        assert!(!path.exists());
+    }
+}
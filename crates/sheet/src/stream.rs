@@ -0,0 +1,752 @@
+//! Chunked reading and writing for files larger than memory
+//!
+//! Readers yield sheets of at most `chunk_rows` data rows. Each chunk starts
+//! with the file's header row and has its columns named, so it can be
+//! filtered, mapped and joined like any other sheet. Writers take chunks back
+//! one at a time and write the header only once.
+//!
+//! Types are inferred per chunk, so a column that is all numbers in one chunk
+//! may be text in the next. Read with a [`SheetSchema`](crate::SheetSchema)
+//! in [`CsvOptions`] when the types must stay fixed.
+
+use crate::cell::CellValue;
+use crate::columnar::ColumnBuilder;
+use crate::csv::{CsvOptions, RecordParser};
+use crate::error::{Result, SheetError};
+use crate::json::parse_jsonl_line;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::parquet::{batches_to_sheet, to_parquet_batch};
+use crate::sheet::Sheet;
+#[cfg(not(target_arch = "wasm32"))]
+use arrow::array::{new_null_array, ArrayRef, RecordBatch, RecordBatchOptions};
+#[cfg(not(target_arch = "wasm32"))]
+use arrow::compute::{cast_with_options, CastOptions};
+#[cfg(not(target_arch = "wasm32"))]
+use arrow::datatypes::SchemaRef;
+#[cfg(not(target_arch = "wasm32"))]
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+#[cfg(not(target_arch = "wasm32"))]
+use parquet::arrow::ArrowWriter;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines, Read, Write};
+use std::path::{Path, PathBuf};
+
+fn check_chunk_rows(chunk_rows: usize) -> Result<()> {
+    if chunk_rows == 0 {
+        return Err(SheetError::Stream(
+            "Chunk size must be at least one row".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Iterator over chunks of a CSV file, created by [`Sheet::stream_csv`] or
+/// [`CsvChunks::new`].
+pub struct CsvChunks<R: Read> {
+    reader: csv::Reader<R>,
+    parser: RecordParser,
+    chunk_rows: usize,
+    record: csv::StringRecord,
+    header: Option<Vec<CellValue>>,
+    width: usize,
+    started: bool,
+    done: bool,
+}
+
+impl<R: Read> CsvChunks<R> {
+    /// Read `reader` in chunks of `chunk_rows` data rows.
+    ///
+    /// ```
+    /// use piptable_sheet::{CsvChunks, CsvOptions};
+    ///
+    /// let csv = "id,name\n1,a\n2,b\n3,c\n";
+    /// let options = CsvOptions::default().with_headers(true);
+    /// let chunks: Vec<_> = CsvChunks::new(csv.as_bytes(), options, 2)
+    ///     .unwrap()
+    ///     .collect::<Result<_, _>>()
+    ///     .unwrap();
+    /// assert_eq!(chunks.len(), 2);
+    /// assert_eq!(chunks[1].row_count(), 2); // the header row and "3,c"
+    /// ```
+    pub fn new(reader: R, options: CsvOptions, chunk_rows: usize) -> Result<Self> {
+        check_chunk_rows(chunk_rows)?;
+        Ok(CsvChunks {
+            reader: options.reader(reader),
+            parser: RecordParser::new(options)?,
+            chunk_rows,
+            record: csv::StringRecord::new(),
+            header: None,
+            width: 0,
+            started: false,
+            done: false,
+        })
+    }
+
+    fn read_chunk(&mut self) -> Result<Option<Sheet>> {
+        let mut builders: Vec<ColumnBuilder> = Vec::new();
+        let mut len = 0;
+        while len < self.chunk_rows && self.reader.read_record(&mut self.record)? {
+            if !self.started {
+                self.started = true;
+                self.width = self.record.len();
+                self.parser.start(&self.record);
+                if self.parser.options.has_headers {
+                    self.header = Some(self.parser.cells(&self.record, true).collect());
+                    continue;
+                }
+            }
+            if builders.is_empty() {
+                builders = (0..self.width).map(|_| ColumnBuilder::new()).collect();
+            }
+            for (builder, cell) in builders
+                .iter_mut()
+                .zip(self.parser.cells(&self.record, false))
+            {
+                builder.push(cell);
+            }
+            len += 1;
+        }
+
+        if len == 0 {
+            return Ok(None);
+        }
+        self.parser
+            .finish(self.header.clone(), builders, len)
+            .map(Some)
+    }
+}
+
+impl<R: Read> Iterator for CsvChunks<R> {
+    type Item = Result<Sheet>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let chunk = self.read_chunk();
+        end_on_last(&mut self.done, chunk)
+    }
+}
+
+/// Iterator over chunks of a JSONL file, created by [`Sheet::stream_jsonl`]
+/// or [`JsonlChunks::new`]. Columns come from the first object, as with
+/// [`Sheet::from_jsonl`].
+pub struct JsonlChunks<R: BufRead> {
+    lines: Lines<R>,
+    line_num: usize,
+    column_names: Option<Vec<String>>,
+    chunk_rows: usize,
+    done: bool,
+}
+
+impl<R: BufRead> JsonlChunks<R> {
+    /// Read `reader` in chunks of `chunk_rows` objects.
+    pub fn new(reader: R, chunk_rows: usize) -> Result<Self> {
+        check_chunk_rows(chunk_rows)?;
+        Ok(JsonlChunks {
+            lines: reader.lines(),
+            line_num: 0,
+            column_names: None,
+            chunk_rows,
+            done: false,
+        })
+    }
+
+    fn read_chunk(&mut self) -> Result<Option<Sheet>> {
+        let mut records = Vec::new();
+        while records.len() < self.chunk_rows {
+            let Some(line) = self.lines.next() else {
+                break;
+            };
+            let line = line?;
+            self.line_num += 1;
+            if let Some(record) =
+                parse_jsonl_line(&line, self.line_num - 1, &mut self.column_names)?
+            {
+                records.push(record);
+            }
+        }
+
+        if records.is_empty() {
+            return Ok(None);
+        }
+        let mut sheet = Sheet::from_records(records)?;
+        sheet.compact();
+        Ok(Some(sheet))
+    }
+}
+
+impl<R: BufRead> Iterator for JsonlChunks<R> {
+    type Item = Result<Sheet>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let chunk = self.read_chunk();
+        end_on_last(&mut self.done, chunk)
+    }
+}
+
+/// Iterator over chunks of a Parquet file, created by
+/// [`Sheet::stream_parquet`]. Only one batch of rows is decoded at a time.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct ParquetChunks {
+    reader: ParquetRecordBatchReader,
+    schema: SchemaRef,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Iterator for ParquetChunks {
+    type Item = Result<Sheet>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch = self.reader.next()?;
+        Some(
+            batch
+                .map_err(SheetError::from)
+                .and_then(|batch| batches_to_sheet(&self.schema, &[batch])),
+        )
+    }
+}
+
+/// The iterator item for a chunk read, ending the stream after the last
+/// chunk or the first error.
+fn end_on_last(done: &mut bool, chunk: Result<Option<Sheet>>) -> Option<Result<Sheet>> {
+    match chunk {
+        Ok(Some(sheet)) => Some(Ok(sheet)),
+        Ok(None) => {
+            *done = true;
+            None
+        }
+        Err(e) => {
+            *done = true;
+            Some(Err(e))
+        }
+    }
+}
+
+impl Sheet {
+    /// Read a CSV file in chunks of `chunk_rows` data rows.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use piptable_sheet::{CsvChunkWriter, CsvOptions, Sheet};
+    /// use std::fs::File;
+    ///
+    /// let options = CsvOptions::default().with_headers(true);
+    /// let mut out = CsvChunkWriter::new(File::create("large.csv").unwrap(), CsvOptions::default());
+    /// for chunk in Sheet::stream_csv("big.csv", options, 100_000).unwrap() {
+    ///     let mut chunk = chunk.unwrap();
+    ///     chunk.filter_rows(|i, row| i == 0 || row[2].as_float().is_some_and(|v| v > 1000.0));
+    ///     out.write(&chunk).unwrap();
+    /// }
+    /// out.finish().unwrap();
+    /// ```
+    pub fn stream_csv<P: AsRef<Path>>(
+        path: P,
+        options: CsvOptions,
+        chunk_rows: usize,
+    ) -> Result<CsvChunks<BufReader<File>>> {
+        let file = File::open(path.as_ref())?;
+        CsvChunks::new(BufReader::new(file), options, chunk_rows)
+    }
+
+    /// Read a JSONL file in chunks of `chunk_rows` objects.
+    pub fn stream_jsonl<P: AsRef<Path>>(
+        path: P,
+        chunk_rows: usize,
+    ) -> Result<JsonlChunks<BufReader<File>>> {
+        let file = File::open(path.as_ref())?;
+        JsonlChunks::new(BufReader::new(file), chunk_rows)
+    }
+
+    /// Read a Parquet file in chunks of `chunk_rows` rows.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn stream_parquet<P: AsRef<Path>>(path: P, chunk_rows: usize) -> Result<ParquetChunks> {
+        check_chunk_rows(chunk_rows)?;
+        let file = File::open(path.as_ref())?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file)?.with_batch_size(chunk_rows);
+        let schema = builder.schema().clone();
+        Ok(ParquetChunks {
+            reader: builder.build()?,
+            schema,
+        })
+    }
+}
+
+/// Writes chunks to one CSV output. The first chunk is written whole; later
+/// chunks skip their header row.
+pub struct CsvChunkWriter<W: Write> {
+    writer: csv::Writer<W>,
+    wrote_header: bool,
+}
+
+impl<W: Write> CsvChunkWriter<W> {
+    /// Write chunks to `writer`, starting with the first chunk's header.
+    pub fn new(writer: W, options: CsvOptions) -> Self {
+        CsvChunkWriter {
+            writer: csv::WriterBuilder::new()
+                .delimiter(options.delimiter)
+                .quote(options.quote)
+                .from_writer(writer),
+            wrote_header: false,
+        }
+    }
+
+    /// Write chunks after output that already has a header row, such as a
+    /// CSV file opened for appending.
+    pub fn appending(writer: W, options: CsvOptions) -> Self {
+        CsvChunkWriter {
+            wrote_header: true,
+            ..Self::new(writer, options)
+        }
+    }
+
+    /// Write one chunk.
+    pub fn write(&mut self, chunk: &Sheet) -> Result<()> {
+        let skip = if self.wrote_header {
+            chunk.data_start_row()
+        } else {
+            0
+        };
+        let mut result = Ok(());
+        chunk.storage().for_each_row(|idx, row| {
+            if idx >= skip && result.is_ok() {
+                result = self.writer.write_record(row.iter().map(CellValue::as_str));
+            }
+        });
+        result?;
+        self.wrote_header |= chunk.row_count() > 0;
+        Ok(())
+    }
+
+    /// Flush everything written.
+    pub fn finish(mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes chunks to one JSONL output.
+pub struct JsonlChunkWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonlChunkWriter<W> {
+    /// Write chunks to `writer`.
+    pub fn new(writer: W) -> Self {
+        JsonlChunkWriter { writer }
+    }
+
+    /// Write one chunk; its columns must be named.
+    pub fn write(&mut self, chunk: &Sheet) -> Result<()> {
+        chunk.write_jsonl(&mut self.writer)
+    }
+
+    /// Flush everything written.
+    pub fn finish(mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes chunks to one Parquet file. Column types are fixed by the first
+/// chunk; later chunks are cast to them and fail if a value does not fit.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct ParquetChunkWriter<W: Write + Send> {
+    sink: Option<W>,
+    writer: Option<ArrowWriter<W>>,
+    schema: Option<SchemaRef>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<W: Write + Send> ParquetChunkWriter<W> {
+    /// Write chunks to `writer`.
+    pub fn new(writer: W) -> Self {
+        ParquetChunkWriter {
+            sink: Some(writer),
+            writer: None,
+            schema: None,
+        }
+    }
+
+    /// Write one chunk; its columns must be named.
+    pub fn write(&mut self, chunk: &Sheet) -> Result<()> {
+        if chunk.column_names().is_none() {
+            return Err(SheetError::ColumnsNotNamed(
+                "Columns must be named to export as Parquet".to_string(),
+            ));
+        }
+        let batch = to_parquet_batch(&chunk.to_record_batch()?)?;
+
+        let (writer, batch) = match (&mut self.writer, &self.schema) {
+            (Some(writer), Some(schema)) => (writer, conform_batch(&batch, schema)?),
+            _ => {
+                let sink = self.sink.take().expect("writer is created once");
+                let schema = batch.schema();
+                self.schema = Some(schema.clone());
+                (
+                    self.writer
+                        .insert(ArrowWriter::try_new(sink, schema, None)?),
+                    batch,
+                )
+            }
+        };
+        if batch.num_rows() > 0 {
+            writer.write(&batch)?;
+        }
+        Ok(())
+    }
+
+    /// Finish the file. Fails if no chunk was written, since the columns of
+    /// the file are not known.
+    pub fn finish(self) -> Result<()> {
+        let writer = self.writer.ok_or_else(|| {
+            SheetError::Stream("No chunks were written to the Parquet file".to_string())
+        })?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+/// Cast `batch` to the columns of the first chunk written.
+#[cfg(not(target_arch = "wasm32"))]
+fn conform_batch(batch: &RecordBatch, schema: &SchemaRef) -> Result<RecordBatch> {
+    let names = |schema: &SchemaRef| -> Vec<String> {
+        schema.fields().iter().map(|f| f.name().clone()).collect()
+    };
+    if names(&batch.schema()) != names(schema) {
+        return Err(SheetError::Stream(format!(
+            "Chunk columns {:?} do not match the first chunk's {:?}",
+            names(&batch.schema()),
+            names(schema)
+        )));
+    }
+
+    let options = CastOptions {
+        safe: false,
+        ..CastOptions::default()
+    };
+    let arrays = batch
+        .columns()
+        .iter()
+        .zip(schema.fields())
+        .map(|(array, field)| {
+            if array.data_type() == field.data_type() {
+                Ok(array.clone())
+            } else if array.null_count() == array.len() {
+                Ok(new_null_array(field.data_type(), array.len()))
+            } else {
+                cast_with_options(array, field.data_type(), &options).map_err(|e| {
+                    SheetError::Stream(format!(
+                        "Column '{}' is {} in this chunk but {} in the first chunk: {e}",
+                        field.name(),
+                        array.data_type(),
+                        field.data_type()
+                    ))
+                })
+            }
+        })
+        .collect::<Result<Vec<ArrayRef>>>()?;
+    let options = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+    Ok(RecordBatch::try_new_with_options(
+        schema.clone(),
+        arrays,
+        &options,
+    )?)
+}
+
+/// File formats a [`SheetStream`] can read, chosen by extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    /// Comma-separated values
+    Csv,
+    /// Tab-separated values
+    Tsv,
+    /// One JSON object per line
+    Jsonl,
+    /// Apache Parquet
+    Parquet,
+}
+
+impl StreamFormat {
+    /// The format for a file extension (`csv`, `tsv`, `jsonl`, `ndjson`,
+    /// `parquet`), ignoring case.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "csv" => Some(StreamFormat::Csv),
+            "tsv" => Some(StreamFormat::Tsv),
+            "jsonl" | "ndjson" => Some(StreamFormat::Jsonl),
+            "parquet" => Some(StreamFormat::Parquet),
+            _ => None,
+        }
+    }
+}
+
+/// A file to read in chunks, described without opening it. Each call to
+/// [`SheetStream::open`] starts again from the top of the file.
+#[derive(Debug, Clone)]
+pub struct SheetStream {
+    path: PathBuf,
+    format: StreamFormat,
+    chunk_rows: usize,
+    csv_options: Option<CsvOptions>,
+}
+
+impl SheetStream {
+    /// Stream `path` in chunks of `chunk_rows` rows. CSV and TSV files are
+    /// read with headers unless other options are given.
+    pub fn new<P: AsRef<Path>>(path: P, chunk_rows: usize) -> Result<Self> {
+        check_chunk_rows(chunk_rows)?;
+        let path = path.as_ref().to_path_buf();
+        let format = StreamFormat::from_path(&path).ok_or_else(|| {
+            SheetError::Stream(format!(
+                "Cannot stream '{}': expected a .csv, .tsv, .jsonl, .ndjson or .parquet file",
+                path.display()
+            ))
+        })?;
+        Ok(SheetStream {
+            path,
+            format,
+            chunk_rows,
+            csv_options: None,
+        })
+    }
+
+    /// Read CSV and TSV files with these options instead.
+    #[must_use]
+    pub fn with_csv_options(mut self, options: CsvOptions) -> Self {
+        self.csv_options = Some(options);
+        self
+    }
+
+    /// The file being streamed.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The format the file is read as.
+    pub fn format(&self) -> StreamFormat {
+        self.format
+    }
+
+    /// The number of data rows per chunk.
+    pub fn chunk_rows(&self) -> usize {
+        self.chunk_rows
+    }
+
+    /// Open the file and iterate over its chunks.
+    pub fn open(&self) -> Result<Box<dyn Iterator<Item = Result<Sheet>> + Send>> {
+        let csv_options = |default: CsvOptions| {
+            self.csv_options
+                .clone()
+                .unwrap_or_else(|| default.with_headers(true))
+        };
+        Ok(match self.format {
+            StreamFormat::Csv => Box::new(Sheet::stream_csv(
+                &self.path,
+                csv_options(CsvOptions::default()),
+                self.chunk_rows,
+            )?),
+            StreamFormat::Tsv => Box::new(Sheet::stream_csv(
+                &self.path,
+                csv_options(CsvOptions::tsv()),
+                self.chunk_rows,
+            )?),
+            StreamFormat::Jsonl => Box::new(Sheet::stream_jsonl(&self.path, self.chunk_rows)?),
+            #[cfg(not(target_arch = "wasm32"))]
+            StreamFormat::Parquet => Box::new(Sheet::stream_parquet(&self.path, self.chunk_rows)?),
+            #[cfg(target_arch = "wasm32")]
+            StreamFormat::Parquet => {
+                return Err(SheetError::Stream(
+                    "Parquet streaming is not available in this build".to_string(),
+                ))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn numbers_csv(rows: usize) -> String {
+        let mut csv = String::from("id,amount\n");
+        for i in 0..rows {
+            csv.push_str(&format!("{i},{}\n", i * 10));
+        }
+        csv
+    }
+
+    #[test]
+    fn test_csv_chunks_keep_header_and_names() {
+        let options = CsvOptions::default().with_headers(true);
+        let chunks: Vec<Sheet> = CsvChunks::new(numbers_csv(5).as_bytes(), options, 2)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(
+            chunks.iter().map(Sheet::row_count).collect::<Vec<_>>(),
+            vec![3, 3, 2]
+        );
+        for chunk in &chunks {
+            assert!(chunk.is_columnar());
+            assert_eq!(chunk.data_start_row(), 1);
+            assert_eq!(
+                chunk.column_names().unwrap(),
+                &vec!["id".to_string(), "amount".to_string()]
+            );
+        }
+        assert_eq!(chunks[2].get(1, 1).unwrap(), &CellValue::Int(40));
+    }
+
+    #[test]
+    fn test_csv_chunks_without_headers_and_errors() {
+        let chunks: Vec<Sheet> =
+            CsvChunks::new("1,2\n3,4\n5,6\n".as_bytes(), CsvOptions::default(), 2)
+                .unwrap()
+                .collect::<Result<_>>()
+                .unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[0].column_names().is_none());
+        assert_eq!(chunks[1].get(0, 0).unwrap(), &CellValue::Int(5));
+
+        assert!(CsvChunks::new("a\n".as_bytes(), CsvOptions::default(), 0).is_err());
+
+        // A ragged row fails its chunk and ends the stream
+        let mut chunks =
+            CsvChunks::new("1,2\n3,4\n5\n".as_bytes(), CsvOptions::default(), 1).unwrap();
+        assert!(chunks.next().unwrap().is_ok());
+        assert!(chunks.next().unwrap().is_ok());
+        assert!(chunks.next().unwrap().is_err());
+        assert!(chunks.next().is_none());
+    }
+
+    #[test]
+    fn test_jsonl_chunks() {
+        let jsonl = "{\"a\": 1, \"b\": \"x\"}\n\n{\"a\": 2}\n{\"b\": \"z\", \"a\": 3}\n";
+        let chunks: Vec<Sheet> = JsonlChunks::new(jsonl.as_bytes(), 2)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].row_count(), 3);
+        assert_eq!(chunks[0].get(2, 1).unwrap(), &CellValue::Null);
+        assert_eq!(
+            chunks[1].column_names().unwrap(),
+            &vec!["a".to_string(), "b".to_string()]
+        );
+        assert_eq!(chunks[1].get(1, 1).unwrap(), &CellValue::String("z".into()));
+    }
+
+    #[test]
+    fn test_csv_round_trip_through_chunks() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("in.csv");
+        let output = dir.path().join("out.csv");
+        std::fs::write(&input, numbers_csv(10)).unwrap();
+
+        let mut writer = CsvChunkWriter::new(File::create(&output).unwrap(), CsvOptions::default());
+        for chunk in SheetStream::new(&input, 3).unwrap().open().unwrap() {
+            let mut chunk = chunk.unwrap();
+            chunk.filter_rows(|i, row| i == 0 || row[0].as_int().is_some_and(|v| v % 2 == 0));
+            writer.write(&chunk).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let written = std::fs::read_to_string(&output).unwrap();
+        assert_eq!(written, "id,amount\n0,0\n2,20\n4,40\n6,60\n8,80\n");
+
+        // Appending skips each chunk's header
+        let file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&output)
+            .unwrap();
+        let mut writer = CsvChunkWriter::appending(file, CsvOptions::default());
+        let chunk = Sheet::from_csv_str_with_options(
+            "id,amount\n10,100\n",
+            CsvOptions::default().with_headers(true),
+        )
+        .unwrap();
+        writer.write(&chunk).unwrap();
+        writer.finish().unwrap();
+        assert!(std::fs::read_to_string(&output)
+            .unwrap()
+            .ends_with("8,80\n10,100\n"));
+    }
+
+    #[test]
+    fn test_parquet_chunks_round_trip() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("in.csv");
+        let output = dir.path().join("out.parquet");
+        std::fs::write(&input, numbers_csv(7)).unwrap();
+
+        let mut writer = ParquetChunkWriter::new(File::create(&output).unwrap());
+        for chunk in SheetStream::new(&input, 3).unwrap().open().unwrap() {
+            writer.write(&chunk.unwrap()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let chunks: Vec<Sheet> = Sheet::stream_parquet(&output, 4)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].row_count(), 5);
+        assert_eq!(chunks[1].get(3, 1).unwrap(), &CellValue::Int(60));
+
+        // Later chunks are cast to the first chunk's types
+        let mut writer = ParquetChunkWriter::new(Vec::new());
+        let first = Sheet::from_csv_str_with_options(
+            "id,amount\n1,2.5\n",
+            CsvOptions::default().with_headers(true),
+        )
+        .unwrap();
+        let second = Sheet::from_csv_str_with_options(
+            "id,amount\n2,3\n",
+            CsvOptions::default().with_headers(true),
+        )
+        .unwrap();
+        let text = Sheet::from_csv_str_with_options(
+            "id,amount\n3,lots\n",
+            CsvOptions::default().with_headers(true),
+        )
+        .unwrap();
+        writer.write(&first).unwrap();
+        writer.write(&second).unwrap();
+        assert!(writer.write(&text).is_err());
+        assert!(ParquetChunkWriter::new(Vec::new()).finish().is_err());
+    }
+
+    #[test]
+    fn test_sheet_stream_formats() {
+        assert_eq!(
+            StreamFormat::from_path("data/Big.CSV"),
+            Some(StreamFormat::Csv)
+        );
+        assert_eq!(
+            StreamFormat::from_path("events.ndjson"),
+            Some(StreamFormat::Jsonl)
+        );
+        assert_eq!(StreamFormat::from_path("book.xlsx"), None);
+        assert!(SheetStream::new("book.xlsx", 10).is_err());
+        assert!(SheetStream::new("big.csv", 0).is_err());
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("in.tsv");
+        std::fs::write(&path, "a\tb\n1\t2\n").unwrap();
+        let stream = SheetStream::new(&path, 10).unwrap();
+        let chunks: Vec<Sheet> = stream.open().unwrap().collect::<Result<_>>().unwrap();
+        assert_eq!(chunks[0].column_names().unwrap().len(), 2);
+
+        // Each open starts from the top again
+        assert_eq!(stream.open().unwrap().count(), 1);
+    }
+}
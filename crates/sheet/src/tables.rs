@@ -0,0 +1,567 @@
+//! Tables: a header row naming columns over a block of data rows, which
+//! formulas reach with structured references such as `Sales[Amount]`,
+//! `Sales[[#Headers],[Qty]]`, `[@Qty]` or `Sales[#Totals]`.
+
+use crate::book::Book;
+use crate::error::{Result, SheetError};
+use crate::sheet::Sheet;
+use piptable_formulas::parser::is_valid_name;
+use piptable_formulas::TableLocation;
+use piptable_primitives::{CellAddress, CellRange};
+
+/// A table on a sheet: a header row, the data rows below it and an optional
+/// totals row. Rows inserted or deleted within the table grow or shrink it,
+/// and formulas using structured references follow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    name: String,
+    header_row: usize,
+    first_col: usize,
+    last_col: usize,
+    last_row: usize,
+    totals_row: bool,
+}
+
+impl Table {
+    /// Create a table over `range` (e.g. `"A1:C10"`), whose first row holds
+    /// the column names and whose remaining rows hold data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name could be read as something else in a
+    /// formula or the range is invalid.
+    pub fn new(name: &str, range: &str) -> Result<Self> {
+        if !is_valid_name(name) {
+            return Err(SheetError::InvalidTable {
+                name: name.to_string(),
+                reason: "table names start with a letter or underscore, contain only \
+                         letters, digits, underscores and periods, and cannot look like a \
+                         cell reference"
+                    .to_string(),
+            });
+        }
+        let ((start_row, start_col), (end_row, end_col)) =
+            crate::a1_notation::parse_range_notation(range)?;
+        Ok(Self {
+            name: name.to_string(),
+            header_row: start_row,
+            first_col: start_col,
+            last_col: end_col,
+            last_row: end_row,
+            totals_row: false,
+        })
+    }
+
+    /// Treat the last row of the table as a totals row, reached with
+    /// `Table[#Totals]`.
+    #[must_use]
+    pub fn with_totals_row(mut self, totals_row: bool) -> Self {
+        self.totals_row = totals_row;
+        self
+    }
+
+    /// Table name
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the last row is a totals row
+    #[must_use]
+    pub fn has_totals_row(&self) -> bool {
+        self.totals_row
+    }
+
+    /// The whole table, header and totals included
+    #[must_use]
+    pub fn range(&self) -> CellRange {
+        self.cells(self.header_row, self.last_row)
+    }
+
+    /// The header row
+    #[must_use]
+    pub fn header_range(&self) -> CellRange {
+        self.cells(self.header_row, self.header_row)
+    }
+
+    /// The data rows, or `None` if the table has none
+    #[must_use]
+    pub fn data_range(&self) -> Option<CellRange> {
+        let last_data = self.last_data_row()?;
+        Some(self.cells(self.header_row + 1, last_data))
+    }
+
+    /// The totals row, if the table has one
+    #[must_use]
+    pub fn totals_range(&self) -> Option<CellRange> {
+        self.totals_row
+            .then(|| self.cells(self.last_row, self.last_row))
+    }
+
+    /// Number of data rows
+    #[must_use]
+    pub fn data_row_count(&self) -> usize {
+        self.last_row - self.header_row - usize::from(self.totals_row)
+    }
+
+    fn last_data_row(&self) -> Option<usize> {
+        (self.data_row_count() > 0).then(|| self.last_row - usize::from(self.totals_row))
+    }
+
+    fn cells(&self, start_row: usize, end_row: usize) -> CellRange {
+        CellRange::new(
+            CellAddress::new(start_row as u32, self.first_col as u32),
+            CellAddress::new(end_row as u32, self.last_col as u32),
+        )
+    }
+
+    fn overlaps(&self, other: &Table) -> bool {
+        self.header_row <= other.last_row
+            && other.header_row <= self.last_row
+            && self.first_col <= other.last_col
+            && other.first_col <= self.last_col
+    }
+
+    fn invalid(&self, reason: impl Into<String>) -> SheetError {
+        SheetError::InvalidTable {
+            name: self.name.clone(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Find a table by name or, when `name` is `None`, the one containing `cell`.
+pub(crate) fn find_table(
+    tables: &[TableLocation],
+    name: Option<&str>,
+    cell: Option<CellAddress>,
+) -> Option<TableLocation> {
+    match name {
+        Some(name) => tables
+            .iter()
+            .find(|table| table.name.eq_ignore_ascii_case(name)),
+        None => {
+            let cell = cell?;
+            tables.iter().find(|table| table.contains(&cell))
+        }
+    }
+    .cloned()
+}
+
+impl Sheet {
+    /// The sheet's tables
+    #[must_use]
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+
+    /// Look up a table by name, ignoring case
+    #[must_use]
+    pub fn table(&self, name: &str) -> Option<&Table> {
+        self.tables
+            .iter()
+            .find(|table| table.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Add a table. Its header cells name its columns for structured
+    /// references; formulas are recompiled so references to it resolve.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use piptable_sheet::{CellValue, Sheet, Table};
+    ///
+    /// let mut sheet = Sheet::from_data(vec![
+    ///     vec![CellValue::from("Qty"), CellValue::from("Price"), CellValue::from("Total")],
+    ///     vec![CellValue::from(2), CellValue::from(10), CellValue::Null],
+    ///     vec![CellValue::from(3), CellValue::from(20), CellValue::Null],
+    /// ]);
+    /// sheet.add_table(Table::new("Sales", "A1:C3").unwrap()).unwrap();
+    /// sheet.set_formula("C2", "=[@Qty]*[@Price]").unwrap();
+    /// sheet.set_formula("C3", "=SUM(Sales[Qty])").unwrap();
+    /// sheet.evaluate_formulas().unwrap();
+    /// assert_eq!(sheet.get_a1("C2").unwrap().as_float(), Some(20.0));
+    /// assert_eq!(sheet.get_a1("C3").unwrap().as_float(), Some(5.0));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table lies outside the sheet, overlaps or
+    /// shares a name with another table, or its header cells are blank or
+    /// repeated.
+    pub fn add_table(&mut self, table: Table) -> Result<()> {
+        if table.last_row >= self.row_count() || table.last_col >= self.col_count() {
+            return Err(table.invalid(format!(
+                "range {} is outside the sheet",
+                range_a1(table.range())
+            )));
+        }
+        if table.totals_row && table.last_row == table.header_row {
+            return Err(table.invalid("a totals row needs a row below the header"));
+        }
+        if self.table(&table.name).is_some() {
+            return Err(table.invalid("a table with this name already exists"));
+        }
+        if let Some(other) = self.tables.iter().find(|other| other.overlaps(&table)) {
+            return Err(table.invalid(format!("overlaps table '{}'", other.name)));
+        }
+        let columns = self.table_columns(&table);
+        for (idx, column) in columns.iter().enumerate() {
+            if column.trim().is_empty() {
+                return Err(table.invalid("header cells must not be blank"));
+            }
+            if columns[..idx]
+                .iter()
+                .any(|prev| prev.trim().eq_ignore_ascii_case(column.trim()))
+            {
+                return Err(table.invalid(format!("column '{column}' appears twice")));
+            }
+        }
+        self.tables.push(table);
+        self.rebuild_formula_engine()
+    }
+
+    /// Add a table spanning the whole sheet, using its first row as the
+    /// header. Handy for sheets whose columns are named by their first row.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sheet is empty or the table cannot be added.
+    pub fn add_table_from_columns(&mut self, name: &str) -> Result<()> {
+        if self.row_count() == 0 || self.col_count() == 0 {
+            return Err(SheetError::InvalidTable {
+                name: name.to_string(),
+                reason: "the sheet is empty".to_string(),
+            });
+        }
+        let end = CellAddress::new((self.row_count() - 1) as u32, (self.col_count() - 1) as u32);
+        self.add_table(Table::new(name, &format!("A1:{}", end.to_a1()))?)
+    }
+
+    /// Remove a table. Formulas that refer to it evaluate to `#REF!`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table does not exist.
+    pub fn remove_table(&mut self, name: &str) -> Result<Table> {
+        let idx = self
+            .tables
+            .iter()
+            .position(|table| table.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| SheetError::TableNotFound {
+                name: name.to_string(),
+            })?;
+        let table = self.tables.remove(idx);
+        self.rebuild_formula_engine()?;
+        Ok(table)
+    }
+
+    /// A table's header and data rows as a new sheet with named columns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table does not exist.
+    pub fn table_data(&self, name: &str) -> Result<Sheet> {
+        let table = self.table(name).ok_or_else(|| SheetError::TableNotFound {
+            name: name.to_string(),
+        })?;
+        let last_row = table.last_data_row().unwrap_or(table.header_row);
+        let mut sheet = self.get_range(&range_a1(table.cells(table.header_row, last_row)))?;
+        sheet.name_columns_by_row(0)?;
+        sheet.set_name(&table.name);
+        Ok(sheet)
+    }
+
+    fn table_columns(&self, table: &Table) -> Vec<String> {
+        (table.first_col..=table.last_col)
+            .map(|col| {
+                self.get(table.header_row, col)
+                    .map(|cell| cell.as_str())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Locate a table for a structured reference made from `cell`: by name,
+    /// or when `name` is `None` the table containing `cell`.
+    #[must_use]
+    pub fn resolve_table(
+        &self,
+        name: Option<&str>,
+        cell: Option<CellAddress>,
+    ) -> Option<TableLocation> {
+        find_table(&self.table_locations(None), name, cell)
+    }
+
+    /// Where each table sits, for resolving structured references. `sheet`
+    /// qualifies the locations for formulas on other sheets.
+    pub(crate) fn table_locations(&self, sheet: Option<&str>) -> Vec<TableLocation> {
+        self.tables
+            .iter()
+            .map(|table| TableLocation {
+                name: table.name.clone(),
+                sheet: sheet.map(str::to_string),
+                header_row: table.header_row as u32,
+                first_col: table.first_col as u32,
+                columns: self.table_columns(table),
+                data_rows: table.data_row_count() as u32,
+                totals_row: table.totals_row,
+            })
+            .collect()
+    }
+
+    /// Grow or shift tables for a row inserted at `index`. A row inserted
+    /// into the data rows, or just above the totals row, joins the table.
+    pub(crate) fn tables_row_inserted(&mut self, index: usize) {
+        for table in &mut self.tables {
+            if index <= table.header_row {
+                table.header_row += 1;
+                table.last_row += 1;
+            } else if index <= table.last_row {
+                table.last_row += 1;
+            }
+        }
+    }
+
+    /// Grow tables that end on the last row (without a totals row) when a
+    /// row is appended at `index`, the old row count.
+    pub(crate) fn tables_row_appended(&mut self, index: usize) {
+        for table in &mut self.tables {
+            if !table.totals_row && table.last_row + 1 == index {
+                table.last_row += 1;
+            }
+        }
+    }
+
+    /// Shrink or shift tables after rows are removed; `keep[i]` says whether
+    /// old row `i` survived. A table whose header row is removed is dropped,
+    /// and one whose totals row is removed loses it.
+    pub(crate) fn tables_rows_retained(&mut self, keep: &[bool]) {
+        let kept_before = |row: usize| keep.iter().take(row).filter(|kept| **kept).count();
+        let kept = |row: usize| keep.get(row).copied().unwrap_or(true);
+        self.tables.retain_mut(|table| {
+            if !kept(table.header_row) {
+                return false;
+            }
+            if table.totals_row && !kept(table.last_row) {
+                table.totals_row = false;
+            }
+            let kept_rows = (table.header_row + 1..=table.last_row)
+                .filter(|row| kept(*row))
+                .count();
+            table.header_row = kept_before(table.header_row);
+            table.last_row = table.header_row + kept_rows;
+            true
+        });
+    }
+}
+
+impl Book {
+    /// Add a table to sheet `sheet`. Table names are unique across the book,
+    /// and formulas on any sheet can refer to the table by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sheet does not exist, another sheet already
+    /// has a table with this name, or the table cannot be added.
+    pub fn add_table(&mut self, sheet: &str, table: Table) -> Result<()> {
+        if let Some((owner, _)) = self.table(&table.name) {
+            if !owner.eq_ignore_ascii_case(sheet) {
+                return Err(table.invalid(format!("sheet '{owner}' has a table with this name")));
+            }
+        }
+        self.get_sheet_mut(sheet)?.add_table(table)?;
+        self.formulas.invalidate();
+        Ok(())
+    }
+
+    /// Look up a table by name, with the name of the sheet holding it
+    #[must_use]
+    pub fn table(&self, name: &str) -> Option<(&str, &Table)> {
+        self.sheets()
+            .find_map(|(sheet_name, sheet)| Some((sheet_name, sheet.table(name)?)))
+    }
+
+    /// Remove a table from whichever sheet holds it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no sheet has the table.
+    pub fn remove_table(&mut self, name: &str) -> Result<Table> {
+        let sheet = self
+            .table(name)
+            .map(|(sheet, _)| sheet.to_string())
+            .ok_or_else(|| SheetError::TableNotFound {
+                name: name.to_string(),
+            })?;
+        let table = self.get_sheet_mut(&sheet)?.remove_table(name)?;
+        self.formulas.invalidate();
+        Ok(table)
+    }
+
+    /// Table locations across the book, qualified by sheet name.
+    pub(crate) fn table_locations(&self) -> Vec<TableLocation> {
+        self.sheets()
+            .flat_map(|(name, sheet)| sheet.table_locations(Some(name)))
+            .collect()
+    }
+}
+
+fn range_a1(range: CellRange) -> String {
+    format!("{}:{}", range.start.to_a1(), range.end.to_a1())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::CellValue;
+
+    fn sales() -> Sheet {
+        let mut sheet = Sheet::from_data(vec![
+            vec![
+                CellValue::from("Item"),
+                CellValue::from("Qty"),
+                CellValue::from("Price"),
+                CellValue::from("Total"),
+            ],
+            vec![
+                CellValue::from("a"),
+                CellValue::from(2),
+                CellValue::from(10),
+                CellValue::Null,
+            ],
+            vec![
+                CellValue::from("b"),
+                CellValue::from(3),
+                CellValue::from(20),
+                CellValue::Null,
+            ],
+            vec![
+                CellValue::from("Total"),
+                CellValue::Null,
+                CellValue::Null,
+                CellValue::Null,
+            ],
+        ]);
+        sheet
+            .add_table(Table::new("Sales", "A1:D4").unwrap().with_totals_row(true))
+            .unwrap();
+        sheet
+    }
+
+    fn value(sheet: &Sheet, cell: &str) -> Option<f64> {
+        sheet.get_a1(cell).unwrap().cached_or_self().as_float()
+    }
+
+    #[test]
+    fn test_table_ranges() {
+        let sheet = sales();
+        let table = sheet.table("sales").unwrap();
+        assert_eq!(range_a1(table.header_range()), "A1:D1");
+        assert_eq!(range_a1(table.data_range().unwrap()), "A2:D3");
+        assert_eq!(range_a1(table.totals_range().unwrap()), "A4:D4");
+        assert_eq!(table.data_row_count(), 2);
+
+        let data = sheet.table_data("Sales").unwrap();
+        assert_eq!(data.row_count(), 3);
+        assert_eq!(data.column_by_name("Qty").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_structured_references_follow_inserted_rows() {
+        let mut sheet = sales();
+        sheet.set_formula("D2", "=[@Qty]*[@Price]").unwrap();
+        sheet.set_formula("D3", "=[@Qty]*[@Price]").unwrap();
+        sheet.set_formula("B4", "=SUM(Sales[Qty])").unwrap();
+        sheet.set_formula("D4", "=SUM(Sales[Total])").unwrap();
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(value(&sheet, "B4"), Some(5.0));
+        assert_eq!(value(&sheet, "D4"), Some(80.0));
+
+        // A row inserted inside the table joins it; the totals move down
+        sheet
+            .row_insert(
+                2,
+                vec![
+                    CellValue::from("c"),
+                    CellValue::from(5),
+                    CellValue::from(4),
+                    CellValue::formula("=[@Qty]*[@Price]".to_string()),
+                ],
+            )
+            .unwrap();
+        assert_eq!(sheet.table("Sales").unwrap().data_row_count(), 3);
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(value(&sheet, "D3"), Some(20.0));
+        assert_eq!(value(&sheet, "B5"), Some(10.0));
+        assert_eq!(value(&sheet, "D5"), Some(100.0));
+
+        // Rows above the table shift it
+        sheet.row_insert(0, vec![CellValue::Null; 4]).unwrap();
+        assert_eq!(
+            range_a1(sheet.table("Sales").unwrap().header_range()),
+            "A2:D2"
+        );
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(value(&sheet, "B6"), Some(10.0));
+
+        // Deleting row "c" shrinks the table again
+        sheet.row_delete(3).unwrap();
+        assert_eq!(sheet.table("Sales").unwrap().data_row_count(), 2);
+        sheet.evaluate_formulas().unwrap();
+        assert_eq!(value(&sheet, "B5"), Some(5.0));
+        assert_eq!(value(&sheet, "D5"), Some(80.0));
+    }
+
+    #[test]
+    fn test_headers_totals_and_errors() {
+        let mut sheet = sales();
+        sheet.set_formula("D4", "=Sales[[#Headers],[Qty]]").unwrap();
+        sheet.set_formula("C4", "=Sales[[#Totals],[Item]]").unwrap();
+        sheet.set_formula("B4", "=Sales[Missing]").unwrap();
+        sheet.evaluate_formulas().unwrap();
+        let cached = |cell: &str| sheet.get_a1(cell).unwrap().cached_or_self().clone();
+        assert_eq!(cached("D4"), CellValue::from("Qty"));
+        assert_eq!(cached("C4"), CellValue::from("Total"));
+        assert_eq!(cached("B4"), CellValue::from("#REF!"));
+
+        for (name, range, err) in [
+            ("Other", "A1:B9", "outside"),
+            ("sales", "A1:A1", "exists"),
+            ("Other", "B2:C3", "overlaps"),
+        ] {
+            let table = Table::new(name, range).unwrap();
+            let message = sheet.add_table(table).unwrap_err().to_string();
+            assert!(message.contains(err), "{message}");
+        }
+        assert!(Table::new("A1", "A1:B2").is_err());
+
+        sheet.remove_table("Sales").unwrap();
+        assert!(sheet.tables().is_empty());
+        assert!(matches!(
+            sheet.remove_table("Sales"),
+            Err(SheetError::TableNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_book_tables_across_sheets() {
+        let mut book = Book::new();
+        book.add_sheet("Data", sales()).unwrap();
+        book.add_sheet("Report", Sheet::from_data(vec![vec![CellValue::Null]]))
+            .unwrap();
+        book.set_formula("Report", "A1", "=SUM(Sales[Price])")
+            .unwrap();
+        book.recalculate().unwrap();
+        let report = book.get_sheet("Report").unwrap();
+        assert_eq!(value(report, "A1"), Some(30.0));
+
+        assert_eq!(book.table("SALES").unwrap().0, "Data");
+        let err = book
+            .add_table("Report", Table::new("Sales", "A1:A1").unwrap())
+            .unwrap_err();
+        assert!(matches!(err, SheetError::InvalidTable { .. }));
+        book.remove_table("Sales").unwrap();
+        assert!(book.table("Sales").is_none());
+    }
+}
@@ -347,6 +347,7 @@ fn format_toon_value(value: &CellValue) -> String {
                 s.clone()
             }
         }
+        CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_) => value.as_str(),
     }
 }
 
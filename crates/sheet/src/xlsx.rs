@@ -1,12 +1,15 @@
 use crate::book::Book;
 use crate::cell::{CellValue, FormulaCell};
 use crate::error::{Result, SheetError};
+use crate::names::DefinedNames;
 use crate::sheet::Sheet;
 use calamine::{
     open_workbook, open_workbook_auto, Data, Error as CalamineError, Reader, Sheets, Xls, XlsError,
     Xlsx, XlsxError,
 };
-use rust_xlsxwriter::{Workbook, Worksheet};
+use chrono::NaiveTime;
+use piptable_formulas::quote_sheet_name;
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError as XlsxWriterError};
 use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
@@ -30,6 +33,56 @@ fn write_cell_formula(
         })
 }
 
+/// Copy a workbook's defined names into `book`. The reader does not report
+/// which sheet a name is scoped to, so every name is read as a workbook
+/// name. Excel's own names (`_xlnm.Print_Area`, ...) and definitions that
+/// no longer parse, such as references to a deleted sheet, are skipped.
+fn read_defined_names(book: &mut Book, names: &[(String, String)]) {
+    for (name, refers_to) in names {
+        if name.starts_with("_xlnm.") {
+            continue;
+        }
+        let _ = book.define_name(name, refers_to);
+    }
+}
+
+/// Write `names` to the workbook, scoped to worksheet `sheet` if given.
+/// Sheet-scoped references without a sheet are qualified with it, as Excel
+/// requires.
+fn write_defined_names(
+    workbook: &mut Workbook,
+    sheet: Option<&str>,
+    names: &DefinedNames,
+) -> Result<()> {
+    for defined in names.iter() {
+        let (name, refers_to) = match sheet {
+            Some(sheet) => {
+                let quoted = quote_sheet_name(sheet);
+                let refers_to = match defined.range() {
+                    Some((None, _)) => format!("{quoted}!{}", defined.refers_to),
+                    _ => defined.refers_to.clone(),
+                };
+                (format!("{quoted}!{}", defined.name), refers_to)
+            }
+            None => (defined.name.clone(), defined.refers_to.clone()),
+        };
+        workbook
+            .define_name(name, &format!("={refers_to}"))
+            .map_err(|e| {
+                SheetError::Io(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    e.to_string(),
+                ))
+            })?;
+    }
+    Ok(())
+}
+
+/// Number formats for temporal cells without a schema format.
+const DATE_FORMAT: &str = "yyyy-mm-dd";
+const DATETIME_FORMAT: &str = "yyyy-mm-dd hh:mm:ss";
+const DURATION_FORMAT: &str = "[h]:mm:ss";
+
 /// Options for reading Excel files
 #[derive(Debug, Clone, Default)]
 pub struct XlsxReadOptions {
@@ -54,13 +107,18 @@ fn data_to_cell_value(data: &Data) -> CellValue {
         Data::Int(i) => CellValue::Int(*i),
         Data::Float(f) => CellValue::Float(*f),
         Data::String(s) => CellValue::String(s.clone()),
-        Data::DateTime(dt) => {
-            // Convert Excel datetime to float
-            // Excel stores dates as days since 1899-12-30
-            CellValue::Float(dt.as_f64())
+        // Date-formatted cells; midnight values are plain dates
+        Data::DateTime(dt) if dt.is_duration() => dt
+            .as_duration()
+            .map_or(CellValue::Float(dt.as_f64()), CellValue::Duration),
+        Data::DateTime(dt) => match dt.as_datetime() {
+            Some(value) if value.time() == NaiveTime::MIN => CellValue::Date(value.date()),
+            Some(value) => CellValue::DateTime(value),
+            None => CellValue::Float(dt.as_f64()),
+        },
+        Data::DateTimeIso(s) | Data::DurationIso(s) => {
+            CellValue::parse_iso_temporal(s).unwrap_or_else(|| CellValue::String(s.clone()))
         }
-        Data::DateTimeIso(s) => CellValue::String(s.clone()),
-        Data::DurationIso(s) => CellValue::String(s.clone()),
         Data::Error(e) => CellValue::String(format!("#ERROR: {e:?}")),
     }
 }
@@ -195,6 +253,8 @@ impl Sheet {
         let worksheet = workbook.add_worksheet();
 
         self.write_to_worksheet(worksheet)?;
+        let worksheet_name = worksheet.name();
+        write_defined_names(&mut workbook, Some(&worksheet_name), &self.names)?;
 
         workbook.save(path.as_ref()).map_err(|e| {
             SheetError::Io(std::io::Error::new(
@@ -342,15 +402,39 @@ impl Sheet {
     }
 
     /// Write sheet data to a worksheet
+    ///
+    /// Columns with a schema are written as their declared type, using the
+    /// schema's number format when it has one. Dates, date-times and
+    /// durations become Excel serial numbers with a matching number format.
     fn write_to_worksheet(&self, worksheet: &mut Worksheet) -> Result<()> {
-        worksheet.set_name(self.name()).map_err(|e| {
+        let xlsx_err = |e: XlsxWriterError| {
             SheetError::Io(std::io::Error::new(
                 std::io::ErrorKind::Other,
                 e.to_string(),
             ))
-        })?;
-
-        for (row_idx, row) in self.data().iter().enumerate() {
+        };
+
+        let sheet = self.coerced_to_schema()?;
+        worksheet.set_name(sheet.name()).map_err(xlsx_err)?;
+
+        let start = sheet.data_start_row();
+        let formats: Vec<Option<Format>> = match (sheet.schema(), sheet.column_names()) {
+            (Some(schema), Some(names)) => names
+                .iter()
+                .map(|name| {
+                    schema
+                        .column(name)
+                        .and_then(|column| column.number_format())
+                        .map(|format| Format::new().set_num_format(format))
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let date_format = Format::new().set_num_format(DATE_FORMAT);
+        let datetime_format = Format::new().set_num_format(DATETIME_FORMAT);
+        let duration_format = Format::new().set_num_format(DURATION_FORMAT);
+
+        for (row_idx, row) in sheet.data().iter().enumerate() {
             for (col_idx, cell) in row.iter().enumerate() {
                 let row_num = u32::try_from(row_idx).map_err(|_| {
                     SheetError::Io(std::io::Error::new(
@@ -364,48 +448,81 @@ impl Sheet {
                         "Column index overflow",
                     ))
                 })?;
-
-                match cell {
-                    CellValue::Null => {} // Leave empty
-                    CellValue::Bool(b) => {
-                        worksheet.write_boolean(row_num, col_num, *b).map_err(|e| {
-                            SheetError::Io(std::io::Error::new(
-                                std::io::ErrorKind::Other,
-                                e.to_string(),
-                            ))
-                        })?;
+                let format = formats
+                    .get(col_idx)
+                    .and_then(Option::as_ref)
+                    .filter(|_| row_idx >= start);
+
+                match (cell, format) {
+                    (CellValue::Null, _) => {} // Leave empty
+                    (CellValue::Bool(b), Some(format)) => {
+                        worksheet
+                            .write_boolean_with_format(row_num, col_num, *b, format)
+                            .map_err(xlsx_err)?;
+                    }
+                    (CellValue::Bool(b), None) => {
+                        worksheet
+                            .write_boolean(row_num, col_num, *b)
+                            .map_err(xlsx_err)?;
+                    }
+                    // Note: Excel stores all numbers as f64, so integers > 2^53
+                    // (9,007,199,254,740,992) may lose precision
+                    (CellValue::Int(i), Some(format)) => {
+                        worksheet
+                            .write_number_with_format(row_num, col_num, *i as f64, format)
+                            .map_err(xlsx_err)?;
                     }
-                    CellValue::Int(i) => {
-                        // Note: Excel stores all numbers as f64, so integers > 2^53
-                        // (9,007,199,254,740,992) may lose precision
+                    (CellValue::Int(i), None) => {
                         worksheet
                             .write_number(row_num, col_num, *i as f64)
-                            .map_err(|e| {
-                                SheetError::Io(std::io::Error::new(
-                                    std::io::ErrorKind::Other,
-                                    e.to_string(),
-                                ))
-                            })?;
+                            .map_err(xlsx_err)?;
+                    }
+                    (CellValue::Float(f), Some(format)) => {
+                        worksheet
+                            .write_number_with_format(row_num, col_num, *f, format)
+                            .map_err(xlsx_err)?;
+                    }
+                    (CellValue::Float(f), None) => {
+                        worksheet
+                            .write_number(row_num, col_num, *f)
+                            .map_err(xlsx_err)?;
                     }
-                    CellValue::Float(f) => {
-                        worksheet.write_number(row_num, col_num, *f).map_err(|e| {
-                            SheetError::Io(std::io::Error::new(
-                                std::io::ErrorKind::Other,
-                                e.to_string(),
-                            ))
-                        })?;
+                    (CellValue::String(s), Some(format)) => {
+                        worksheet
+                            .write_string_with_format(row_num, col_num, s, format)
+                            .map_err(xlsx_err)?;
                     }
-                    CellValue::String(s) => {
-                        worksheet.write_string(row_num, col_num, s).map_err(|e| {
-                            SheetError::Io(std::io::Error::new(
-                                std::io::ErrorKind::Other,
-                                e.to_string(),
-                            ))
-                        })?;
+                    (CellValue::String(s), None) => {
+                        worksheet
+                            .write_string(row_num, col_num, s)
+                            .map_err(xlsx_err)?;
                     }
-                    CellValue::Formula(formula) => {
+                    (CellValue::Formula(formula), _) => {
                         write_cell_formula(worksheet, row_num, col_num, formula)?;
                     }
+                    (
+                        value @ (CellValue::Date(_)
+                        | CellValue::DateTime(_)
+                        | CellValue::Duration(_)),
+                        format,
+                    ) => {
+                        let serial = value.as_float().unwrap_or_default();
+                        if serial < 0.0 && !matches!(value, CellValue::Duration(_)) {
+                            // Excel cannot show dates before 1900
+                            worksheet
+                                .write_string(row_num, col_num, value.as_str())
+                                .map_err(xlsx_err)?;
+                        } else {
+                            let format = format.unwrap_or(match value {
+                                CellValue::Date(_) => &date_format,
+                                CellValue::DateTime(_) => &datetime_format,
+                                _ => &duration_format,
+                            });
+                            worksheet
+                                .write_number_with_format(row_num, col_num, serial, format)
+                                .map_err(xlsx_err)?;
+                        }
+                    }
                 }
             }
         }
@@ -466,6 +583,7 @@ impl Book {
             let sheet = build_sheet_lenient(&sheet_name, data, &options);
             book.add_sheet(&sheet_name, sheet)?;
         }
+        read_defined_names(&mut book, workbook.defined_names());
 
         Ok(book)
     }
@@ -480,72 +598,19 @@ impl Book {
 
         for (name, sheet) in self.sheets() {
             let worksheet = workbook.add_worksheet();
+            sheet.write_to_worksheet(worksheet)?;
+            // The book's key names the worksheet, not the sheet's own name
             worksheet.set_name(name).map_err(|e| {
                 SheetError::Io(std::io::Error::new(
                     std::io::ErrorKind::Other,
                     e.to_string(),
                 ))
             })?;
+        }
 
-            for (row_idx, row) in sheet.data().iter().enumerate() {
-                for (col_idx, cell) in row.iter().enumerate() {
-                    let row_num = u32::try_from(row_idx).map_err(|_| {
-                        SheetError::Io(std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            "Row index overflow",
-                        ))
-                    })?;
-                    let col_num = u16::try_from(col_idx).map_err(|_| {
-                        SheetError::Io(std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            "Column index overflow",
-                        ))
-                    })?;
-
-                    match cell {
-                        CellValue::Null => {}
-                        CellValue::Bool(b) => {
-                            worksheet.write_boolean(row_num, col_num, *b).map_err(|e| {
-                                SheetError::Io(std::io::Error::new(
-                                    std::io::ErrorKind::Other,
-                                    e.to_string(),
-                                ))
-                            })?;
-                        }
-                        CellValue::Int(i) => {
-                            // Note: Excel stores all numbers as f64, so integers > 2^53
-                            // (9,007,199,254,740,992) may lose precision
-                            worksheet
-                                .write_number(row_num, col_num, *i as f64)
-                                .map_err(|e| {
-                                    SheetError::Io(std::io::Error::new(
-                                        std::io::ErrorKind::Other,
-                                        e.to_string(),
-                                    ))
-                                })?;
-                        }
-                        CellValue::Float(f) => {
-                            worksheet.write_number(row_num, col_num, *f).map_err(|e| {
-                                SheetError::Io(std::io::Error::new(
-                                    std::io::ErrorKind::Other,
-                                    e.to_string(),
-                                ))
-                            })?;
-                        }
-                        CellValue::String(s) => {
-                            worksheet.write_string(row_num, col_num, s).map_err(|e| {
-                                SheetError::Io(std::io::Error::new(
-                                    std::io::ErrorKind::Other,
-                                    e.to_string(),
-                                ))
-                            })?;
-                        }
-                        CellValue::Formula(formula) => {
-                            write_cell_formula(worksheet, row_num, col_num, formula)?;
-                        }
-                    }
-                }
-            }
+        write_defined_names(&mut workbook, None, &self.names)?;
+        for (name, sheet) in self.sheets() {
+            write_defined_names(&mut workbook, Some(name), &sheet.names)?;
         }
 
         workbook.save(path.as_ref()).map_err(|e| {
@@ -634,6 +699,7 @@ impl Book {
             let sheet = build_sheet_lenient(&sheet_name, data, &options);
             book.add_sheet(&sheet_name, sheet)?;
         }
+        read_defined_names(&mut book, workbook.defined_names());
 
         Ok(book)
     }
@@ -710,6 +776,7 @@ impl Book {
             let sheet = build_sheet_lenient(&sheet_name, data, &options);
             book.add_sheet(&sheet_name, sheet)?;
         }
+        read_defined_names(&mut book, workbook.defined_names());
 
         Ok(book)
     }
@@ -798,6 +865,33 @@ mod tests {
         assert!(matches!(loaded.get(0, 3).unwrap(), CellValue::Bool(true)));
     }
 
+    #[test]
+    fn test_xlsx_temporal_roundtrip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("dates.xlsx");
+
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let at = date.and_hms_opt(13, 45, 30).unwrap();
+        let early = chrono::NaiveDate::from_ymd_opt(1900, 1, 1).unwrap();
+        let mut sheet = Sheet::new();
+        *sheet.data_mut() = vec![vec![
+            CellValue::Date(date),
+            CellValue::DateTime(at),
+            CellValue::Duration(chrono::TimeDelta::minutes(90)),
+            CellValue::Date(early),
+        ]];
+        sheet.save_as_xlsx(&path).unwrap();
+
+        let loaded = Sheet::from_xlsx(&path).unwrap();
+        assert_eq!(loaded.get(0, 0).unwrap(), &CellValue::Date(date));
+        assert_eq!(loaded.get(0, 1).unwrap(), &CellValue::DateTime(at));
+        assert_eq!(
+            loaded.get(0, 2).unwrap(),
+            &CellValue::Duration(chrono::TimeDelta::minutes(90))
+        );
+        assert_eq!(loaded.get(0, 3).unwrap(), &CellValue::Date(early));
+    }
+
     #[test]
     fn test_book_xlsx_roundtrip() {
         let dir = tempdir().unwrap();
@@ -822,6 +916,40 @@ mod tests {
         assert!(loaded.has_sheet("Letters"));
     }
 
+    #[test]
+    fn test_book_xlsx_defined_names() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("names.xlsx");
+
+        let mut book = Book::new();
+        book.add_sheet("Q1 Data", Sheet::from_data(vec![vec![1, 2, 3]]))
+            .unwrap();
+        book.add_sheet("Letters", Sheet::from_data(vec![vec!["a", "b"]]))
+            .unwrap();
+        book.define_name("Sales", "'Q1 Data'!$A$1:$C$1").unwrap();
+        book.define_name("TaxRate", "0.08").unwrap();
+        book.define_sheet_name("Letters", "Picks", "$A$1:$B$1")
+            .unwrap();
+        book.save_as_xlsx(&path).unwrap();
+
+        // Sheet scope is not read back, so every name is a workbook name
+        let loaded = Book::from_xlsx(&path).unwrap();
+        let names: Vec<(&str, &str)> = loaded
+            .names()
+            .iter()
+            .map(|defined| (defined.name.as_str(), defined.refers_to.as_str()))
+            .collect();
+        assert_eq!(names.len(), 3);
+        assert!(names.contains(&("Sales", "'Q1 Data'!$A$1:$C$1")));
+        assert!(names.contains(&("TaxRate", "0.08")));
+        assert!(names.contains(&("Picks", "Letters!$A$1:$B$1")));
+        let picks = loaded.name_range(None, "Picks").unwrap();
+        assert_eq!(
+            picks.get(0, 1).unwrap(),
+            &CellValue::String("b".to_string())
+        );
+    }
+
     #[test]
     fn test_xlsx_sheet_names() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,81 @@
+use std::alloc::System;
+
+use piptable_sheet::{CellValue, Sheet};
+use stats_alloc::{Region, StatsAlloc, INSTRUMENTED_SYSTEM};
+
+#[global_allocator]
+static GLOBAL: &StatsAlloc<System> = &INSTRUMENTED_SYSTEM;
+
+const ROWS: usize = 20_000;
+
+/// Bytes allocated since the region started and not yet freed.
+fn live(region: &Region<System>) -> isize {
+    let stats = region.change();
+    stats.bytes_allocated as isize - stats.bytes_deallocated as isize
+}
+
+fn sheet() -> Sheet {
+    let mut data = vec![vec![
+        CellValue::from("id"),
+        CellValue::from("score"),
+        CellValue::from("city"),
+    ]];
+    data.extend((0..ROWS).map(|i| {
+        vec![
+            CellValue::Int(i as i64),
+            CellValue::Float(i as f64 / 2.0),
+            CellValue::String(format!("city {}", i % 100)),
+        ]
+    }));
+    let mut sheet = Sheet::from_data(data);
+    sheet.name_columns_by_row(0).unwrap();
+    sheet
+}
+
+// Column reads decode from the Arrow arrays and leave nothing behind. The
+// row API builds a row view once, and compacting drops it again.
+#[test]
+fn test_columnar_reads_keep_no_rows() {
+    let region = Region::new(GLOBAL);
+    let mut sheet = sheet();
+    let as_rows = live(&region);
+
+    sheet.compact();
+    assert!(sheet.is_columnar());
+    let as_columns = live(&region);
+    assert!(
+        as_columns < as_rows / 2,
+        "columns use {as_columns} bytes, rows {as_rows}"
+    );
+
+    assert_eq!(sheet.column_by_name("score").unwrap().len(), ROWS + 1);
+    assert_eq!(sheet.data_start_row(), 1);
+    assert_eq!(sheet.to_record_batch().unwrap().num_rows(), ROWS);
+    let after_column_reads = live(&region);
+    assert!(
+        after_column_reads <= as_columns + 4096,
+        "column reads grew memory from {as_columns} to {after_column_reads} bytes"
+    );
+
+    for row in 0..sheet.row_count() {
+        for col in 0..sheet.col_count() {
+            let _ = sheet.get(row, col).unwrap();
+        }
+    }
+    assert_eq!(sheet.get(ROWS, 2).unwrap(), &CellValue::from("city 99"));
+    assert_eq!(sheet.rows().count(), ROWS + 1);
+    assert_eq!(sheet.data().len(), ROWS + 1);
+    let with_row_view = live(&region);
+    assert!(
+        with_row_view <= as_rows + as_columns + 4096,
+        "row reads grew memory from {as_columns} to {with_row_view} bytes"
+    );
+
+    sheet.compact();
+    let compacted = live(&region);
+    assert!(
+        compacted <= as_columns + 4096,
+        "compacting left {compacted} bytes, columns use {as_columns}"
+    );
+    assert!(sheet.is_columnar());
+}
@@ -1,4 +1,3 @@
-use chrono::TimeZone;
 use piptable_sheet::{CellValue, Sheet};
 
 #[test]
@@ -183,15 +182,8 @@ fn test_formula_with_date_functions() -> Result<(), Box<dyn std::error::Error>>
     sheet.set_formula("D1", "=DATE(A1, B1, C1)")?;
     sheet.evaluate_formulas()?;
 
-    // DATE returns an Excel serial date based on local midnight.
-    let result = sheet.get_a1("D1")?.as_float();
-    assert!(result.is_some());
-    let result = result.unwrap();
-    let expected = excel_serial_for_local_date(2024, 1, 15);
-    assert!(
-        (result - expected).abs() < 1e-6,
-        "Expected {expected}, got {result}"
-    );
+    // DATE returns a whole-day serial, whatever the local time zone
+    assert_eq!(sheet.get_a1("D1")?.as_float(), Some(45306.0));
 
     Ok(())
 }
@@ -232,20 +224,6 @@ fn test_formula_absolute_references() -> Result<(), Box<dyn std::error::Error>>
     Ok(())
 }
 
-fn excel_serial_for_local_date(year: i32, month: u32, day: u32) -> f64 {
-    let date = chrono::NaiveDate::from_ymd_opt(year, month, day).expect("valid date");
-    let naive = date.and_hms_opt(0, 0, 0).expect("valid time");
-    let local_dt = chrono::Local
-        .from_local_datetime(&naive)
-        .earliest()
-        .expect("local datetime");
-    let utc = local_dt.with_timezone(&chrono::Utc);
-    let unix_seconds = utc.timestamp();
-    let unix_days = unix_seconds / 86_400;
-    let time_fraction = (unix_seconds.rem_euclid(86_400)) as f64 / 86_400.0;
-    (unix_days + 25_569) as f64 + time_fraction
-}
-
 #[test]
 fn test_formula_row_column_operations() -> Result<(), Box<dyn std::error::Error>> {
     let mut sheet = Sheet::from_data(vec![
@@ -306,3 +284,24 @@ fn test_formula_replaced_and_recalculated() -> Result<(), Box<dyn std::error::Er
     assert_eq!(sheet.get_a1("C1")?.as_float(), Some(2.0));
     Ok(())
 }
+
+#[test]
+fn test_conditional_aggregates_recalculate() -> Result<(), Box<dyn std::error::Error>> {
+    let mut sheet = Sheet::from_data(vec![vec![0, 10, 0], vec![0, 20, 0], vec![0, 30, 0]]);
+    sheet.set_a1("A1", "East")?;
+    sheet.set_a1("A2", "West")?;
+    sheet.set_a1("A3", "East")?;
+    sheet.set_formula("C1", "=SUMIF(A1:A3, \"east\", B1)")?;
+    sheet.set_formula("C2", "=COUNTIFS(A1:A3, \"East\", B1:B3, \">15\")")?;
+    sheet.evaluate_formulas()?;
+    assert_eq!(sheet.get_a1("C1")?.as_float(), Some(40.0));
+    assert_eq!(sheet.get_a1("C2")?.as_float(), Some(1.0));
+
+    // Changing a criteria cell or a cell of the implicit sum range recalculates
+    sheet.set_a1("A2", "East")?;
+    sheet.set_a1("B3", 5)?;
+    sheet.evaluate_formulas()?;
+    assert_eq!(sheet.get_a1("C1")?.as_float(), Some(35.0));
+    assert_eq!(sheet.get_a1("C2")?.as_float(), Some(1.0));
+    Ok(())
+}
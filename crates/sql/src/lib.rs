@@ -5,11 +5,15 @@
 //! This crate provides:
 //! - SQL query execution
 //! - Data source registration (CSV, JSON, Parquet)
+//! - Schema registration for multi-table sources (e.g. workbooks)
 //! - Query optimization via DataFusion
 
 use arrow::array::RecordBatch;
+use datafusion::catalog::{CatalogProvider, SchemaProvider};
+use datafusion::catalog_common::MemorySchemaProvider;
 use datafusion::prelude::*;
-use piptable_core::{PipError, PipResult};
+use datafusion::scalar::ScalarValue;
+use piptable_core::{PipError, PipResult, Value};
 use std::sync::Arc;
 
 /// SQL execution engine wrapping DataFusion.
@@ -55,6 +59,73 @@ impl SqlEngine {
         Ok(())
     }
 
+    /// Register a group of tables under a schema name.
+    ///
+    /// Each table becomes addressable as `schema.table`. An existing schema
+    /// with the same name is replaced, so re-registering reflects tables that
+    /// were added or removed since the last call. Names are used verbatim, so
+    /// queries should quote them when they contain uppercase letters or spaces.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if a table has no batches or registration fails.
+    pub async fn register_schema(
+        &self,
+        name: &str,
+        tables: Vec<(String, Vec<RecordBatch>)>,
+    ) -> PipResult<()> {
+        let schema = MemorySchemaProvider::new();
+        for (table_name, batches) in tables {
+            if batches.is_empty() {
+                return Err(PipError::Sql(format!(
+                    "Cannot register empty table {name}.{table_name}"
+                )));
+            }
+            let provider =
+                datafusion::datasource::MemTable::try_new(batches[0].schema(), vec![batches])
+                    .map_err(|e| PipError::Sql(e.to_string()))?;
+            schema
+                .register_table(table_name, Arc::new(provider))
+                .map_err(|e| PipError::Sql(e.to_string()))?;
+        }
+
+        self.default_catalog()?
+            .register_schema(name, Arc::new(schema))
+            .map_err(|e| PipError::Sql(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Deregister (drop) a schema and all of its tables.
+    pub async fn deregister_schema(&self, name: &str) -> PipResult<()> {
+        self.default_catalog()?
+            .deregister_schema(name, true)
+            .map_err(|e| PipError::Sql(format!("Failed to deregister schema {}: {}", name, e)))?;
+        Ok(())
+    }
+
+    /// List the table names registered under a schema, if it exists.
+    #[must_use]
+    pub fn schema_table_names(&self, name: &str) -> Option<Vec<String>> {
+        let catalog = self.default_catalog().ok()?;
+        let mut names = catalog.schema(name)?.table_names();
+        names.sort();
+        Some(names)
+    }
+
+    /// Resolve the session's default catalog.
+    fn default_catalog(&self) -> PipResult<Arc<dyn CatalogProvider>> {
+        let catalog_name = self
+            .ctx
+            .copied_config()
+            .options()
+            .catalog
+            .default_catalog
+            .clone();
+        self.ctx
+            .catalog(&catalog_name)
+            .ok_or_else(|| PipError::Sql(format!("Default catalog '{catalog_name}' not found")))
+    }
+
     /// Register a CSV file as a table.
     ///
     /// # Errors
@@ -129,6 +200,73 @@ impl SqlEngine {
             .map_err(|e| PipError::Sql(e.to_string()))
     }
 
+    /// Execute a SQL query with positional parameters bound to `$1`, `$2`, ...
+    ///
+    /// Parameters are substituted into the logical plan as typed literals, so
+    /// their contents are never parsed as SQL.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if a parameter is not a scalar or query execution fails.
+    pub async fn query_with_params(
+        &self,
+        sql: &str,
+        params: &[Value],
+    ) -> PipResult<Vec<RecordBatch>> {
+        let df = self.query_df_with_params(sql, params).await?;
+        df.collect().await.map_err(|e| PipError::Sql(e.to_string()))
+    }
+
+    /// Plan a parameterized SQL query and return its output column names
+    /// without executing it.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if a parameter is not a scalar or planning fails.
+    pub async fn query_columns(&self, sql: &str, params: &[Value]) -> PipResult<Vec<String>> {
+        let df = self.query_df_with_params(sql, params).await?;
+        Ok(df
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect())
+    }
+
+    /// List the tables referenced by a SQL query, excluding CTE names.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the query cannot be parsed.
+    pub fn table_references(&self, sql: &str) -> PipResult<Vec<String>> {
+        let state = self.ctx.state();
+        let dialect = state.config().options().sql_parser.dialect.clone();
+        let statement = state
+            .sql_to_statement(sql, &dialect)
+            .map_err(|e| PipError::Sql(e.to_string()))?;
+        let references = state
+            .resolve_table_references(&statement)
+            .map_err(|e| PipError::Sql(e.to_string()))?;
+        Ok(references.iter().map(|r| r.to_string()).collect())
+    }
+
+    /// Check whether a table is registered under the given name.
+    #[must_use]
+    pub fn table_exists(&self, name: &str) -> bool {
+        self.ctx.table_exist(name).unwrap_or(false)
+    }
+
+    async fn query_df_with_params(&self, sql: &str, params: &[Value]) -> PipResult<DataFrame> {
+        let values = params
+            .iter()
+            .map(scalar_param)
+            .collect::<PipResult<Vec<_>>>()?;
+        self.query_df(sql)
+            .await?
+            .with_param_values(values)
+            .map_err(|e| PipError::Sql(e.to_string()))
+    }
+
     /// Get the underlying session context.
     #[must_use]
     pub fn context(&self) -> &SessionContext {
@@ -143,6 +281,21 @@ impl Default for SqlEngine {
     }
 }
 
+/// Convert a scalar runtime value into a query parameter.
+fn scalar_param(value: &Value) -> PipResult<ScalarValue> {
+    match value {
+        Value::Null => Ok(ScalarValue::Null),
+        Value::Bool(b) => Ok(ScalarValue::Boolean(Some(*b))),
+        Value::Int(n) => Ok(ScalarValue::Int64(Some(*n))),
+        Value::Float(f) => Ok(ScalarValue::Float64(Some(*f))),
+        Value::String(s) => Ok(ScalarValue::Utf8(Some(s.clone()))),
+        other => Err(PipError::Sql(format!(
+            "Cannot bind {} value as a query parameter",
+            other.type_name()
+        ))),
+    }
+}
+
 /// SQL engine tests.
 #[cfg(test)]
 mod tests {
@@ -338,6 +491,177 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    // ===== Register Schema Tests =====
+
+    #[tokio::test]
+    async fn test_register_schema_and_query() {
+        let engine = SqlEngine::new();
+        engine
+            .register_schema(
+                "sales",
+                vec![
+                    ("Q1".to_string(), vec![create_test_batch()]),
+                    ("Q2 Data".to_string(), vec![create_test_batch()]),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let batches = engine
+            .query(r#"SELECT a.id FROM "sales"."Q1" a JOIN "sales"."Q2 Data" b USING (id)"#)
+            .await
+            .unwrap();
+        let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(total_rows, 3);
+        assert_eq!(
+            engine.schema_table_names("sales"),
+            Some(vec!["Q1".to_string(), "Q2 Data".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_register_schema_replaces_tables() {
+        let engine = SqlEngine::new();
+        engine
+            .register_schema("book", vec![("a".to_string(), vec![create_test_batch()])])
+            .await
+            .unwrap();
+        engine
+            .register_schema("book", vec![("b".to_string(), vec![create_test_batch()])])
+            .await
+            .unwrap();
+
+        assert!(engine.query("SELECT * FROM book.a").await.is_err());
+        assert!(engine.query("SELECT * FROM book.b").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_deregister_schema() {
+        let engine = SqlEngine::new();
+        engine
+            .register_schema("book", vec![("a".to_string(), vec![create_test_batch()])])
+            .await
+            .unwrap();
+        engine.deregister_schema("book").await.unwrap();
+
+        assert!(engine.schema_table_names("book").is_none());
+        assert!(engine.query("SELECT * FROM book.a").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_register_schema_empty_table() {
+        let engine = SqlEngine::new();
+        let result = engine
+            .register_schema("book", vec![("a".to_string(), vec![])])
+            .await;
+        assert!(result.is_err());
+    }
+
+    // ===== Parameter Binding Tests =====
+
+    #[tokio::test]
+    async fn test_query_with_params() {
+        let engine = SqlEngine::new();
+        engine
+            .register_table("users", vec![create_test_batch()])
+            .await
+            .unwrap();
+
+        let batches = engine
+            .query_with_params(
+                "SELECT name FROM users WHERE id >= $1 AND name <> $2",
+                &[Value::Int(2), Value::String("bob".into())],
+            )
+            .await
+            .unwrap();
+        let names = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(batches[0].num_rows(), 1);
+        assert_eq!(names.value(0), "charlie");
+    }
+
+    #[tokio::test]
+    async fn test_query_with_params_not_parsed_as_sql() {
+        let engine = SqlEngine::new();
+        engine
+            .register_table("users", vec![create_test_batch()])
+            .await
+            .unwrap();
+
+        let batches = engine
+            .query_with_params(
+                "SELECT * FROM users WHERE name = $1",
+                &[Value::String("x' OR '1'='1".into())],
+            )
+            .await
+            .unwrap();
+        let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(total_rows, 0);
+    }
+
+    #[tokio::test]
+    async fn test_query_with_typed_params_in_select() {
+        let engine = SqlEngine::new();
+        engine
+            .register_table("users", vec![create_test_batch()])
+            .await
+            .unwrap();
+
+        let batches = engine
+            .query_with_params(
+                "SELECT CAST($1 AS VARCHAR) AS label, id * CAST($2 AS DOUBLE) AS scaled FROM users WHERE id > CAST($3 AS BIGINT)",
+                &[Value::String("héllo 'quoted'".into()), Value::Float(1.5), Value::Int(2)],
+            )
+            .await
+            .unwrap();
+        let labels = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(labels.value(0), "héllo 'quoted'");
+    }
+
+    #[tokio::test]
+    async fn test_query_with_params_rejects_non_scalar() {
+        let engine = SqlEngine::new();
+        let result = engine
+            .query_with_params("SELECT $1", &[Value::Array(vec![Value::Int(1)])])
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_query_columns() {
+        let engine = SqlEngine::new();
+        engine
+            .register_table("users", vec![create_test_batch()])
+            .await
+            .unwrap();
+
+        let columns = engine
+            .query_columns("SELECT * FROM users WHERE id = $1", &[Value::Int(1)])
+            .await
+            .unwrap();
+        assert_eq!(columns, vec!["id".to_string(), "name".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_table_references() {
+        let engine = SqlEngine::new();
+        let mut tables = engine
+            .table_references(
+                "WITH recent AS (SELECT * FROM orders) SELECT * FROM recent JOIN customers USING (id)",
+            )
+            .unwrap();
+        tables.sort();
+        assert_eq!(tables, vec!["customers".to_string(), "orders".to_string()]);
+        assert!(!engine.table_exists("orders"));
+    }
+
     // ===== Register CSV Tests =====
 
     #[tokio::test]
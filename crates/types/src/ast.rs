@@ -110,10 +110,20 @@ pub enum Statement {
         sources: Vec<Expr>,
         target: String,
         sheet_name: Option<Expr>,
+        /// SQL run against a database source: `import "postgres://..." query "SELECT ..." into x`
+        query: Option<Expr>,
         options: ImportOptions,
         line: usize,
     },
 
+    /// Attach statement: `attach "app.db" as app` makes the database's tables
+    /// queryable as `app.table` in SQL
+    Attach {
+        source: Expr,
+        alias: String,
+        line: usize,
+    },
+
     /// Append statement: `users append new_users` or `users append distinct new_users on "id"`
     Append {
         target: String,
@@ -281,6 +291,9 @@ pub enum Expr {
     /// SQL query: `query(SELECT ...)`
     Query(Box<SqlQuery>),
 
+    /// Data-modifying SQL: `query(UPDATE sales SET ...)`
+    Dml(Box<SqlDml>),
+
     /// HTTP fetch: `fetch(url, options)`
     Fetch {
         url: Box<Expr>,
@@ -313,7 +326,8 @@ pub enum Expr {
         options: Option<Box<Expr>>,
     },
 
-    /// Join expression: `sheet1 join sheet2 on "id"` or `sheet1 left join sheet2 on "id" = "user_id"`
+    /// Join expression: `sheet1 join sheet2 on "id"` or `sheet1 left join sheet2 on "id" = "user_id"`.
+    /// As-of joins are left joins and range joins inner joins, told apart by their condition.
     Join {
         left: Box<Expr>,
         right: Box<Expr>,
@@ -442,6 +456,8 @@ pub struct ImportOptions {
     pub detect_headers: Option<bool>,
     /// Extract document structure (PDF only)
     pub extract_structure: Option<bool>,
+    /// Infer cell types from text (CSV/TSV only, default: true)
+    pub infer_types: Option<bool>,
 }
 
 impl ImportOptions {
@@ -495,6 +511,39 @@ pub struct SqlQuery {
     pub trigger: Option<Trigger>,
 }
 
+/// Data-modifying SQL statement that writes back to a DSL variable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SqlDml {
+    /// `CREATE TABLE name AS SELECT ...`
+    CreateTableAs { name: String, query: Box<SqlQuery> },
+    /// `INSERT INTO table [(columns)] SELECT ...` or `... VALUES (...)`
+    Insert {
+        table: String,
+        columns: Option<Vec<String>>,
+        source: InsertSource,
+    },
+    /// `UPDATE table SET column = expr, ... [WHERE ...]`
+    Update {
+        table: String,
+        assignments: Vec<(String, Expr)>,
+        where_clause: Option<Box<Expr>>,
+    },
+    /// `DELETE FROM table [WHERE ...]`
+    Delete {
+        table: String,
+        where_clause: Option<Box<Expr>>,
+    },
+}
+
+/// Rows supplied to an `INSERT`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum InsertSource {
+    /// Rows produced by a query.
+    Query(Box<SqlQuery>),
+    /// Literal rows from a `VALUES` list.
+    Values(Vec<Vec<Expr>>),
+}
+
 /// WITH clause (CTEs).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WithClause {
@@ -569,6 +618,8 @@ pub struct JoinClause {
     pub table: TableRef,
     pub alias: Option<String>,
     pub on_clause: Option<Box<Expr>>,
+    /// Columns listed in `USING (...)`, if given instead of `ON`.
+    pub using_columns: Option<Vec<String>>,
 }
 
 /// JOIN types.
@@ -661,6 +712,33 @@ pub enum JoinCondition {
     On(String),
     /// Join on different columns: `on "left_col" = "right_col"`
     OnColumns { left: String, right: String },
+    /// As-of join: `asof join ... on "date" by "ccy"`; the other side's
+    /// latest (or next, or nearest) value, within equal `by` columns
+    AsOf {
+        left: String,
+        right: String,
+        by: Vec<String>,
+        direction: AsOfDirection,
+    },
+    /// Range join: `range join ... on "ts" between "start" and "end"`
+    Between {
+        column: String,
+        start: String,
+        end: String,
+    },
+}
+
+/// Which row an as-of join picks: `asof join`, `asof forward join` or
+/// `asof nearest join`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AsOfDirection {
+    /// Latest value at or before the left value
+    #[default]
+    Backward,
+    /// Earliest value at or after the left value
+    Forward,
+    /// Closest value either way
+    Nearest,
 }
 
 /// AST tests.
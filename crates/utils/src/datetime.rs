@@ -1,6 +1,6 @@
 //! Date and time utilities for spreadsheet operations
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 
 /// Excel epoch (January 1, 1900)
 /// Note: Excel incorrectly treats 1900 as a leap year
@@ -29,6 +29,95 @@ pub fn datetime_to_excel_date(dt: DateTime<Utc>) -> f64 {
     (unix_days + EXCEL_EPOCH as i64) as f64 + time_fraction
 }
 
+/// Serial date system of a workbook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateSystem {
+    /// Serial 1 is 1900-01-01. Like Lotus 1-2-3, Excel counts a phantom
+    /// 1900-02-29 as serial 60, so real dates from March 1900 on are one
+    /// day later than a plain day count.
+    #[default]
+    Excel1900,
+    /// Serial 0 is 1904-01-01, the classic Mac system.
+    Excel1904,
+}
+
+/// Days between the 1900 and 1904 epochs (1904-01-01 is 1900 serial 1462).
+const DATE1904_OFFSET: i64 = 1462;
+
+impl DateSystem {
+    fn epoch(self) -> NaiveDate {
+        match self {
+            DateSystem::Excel1900 => NaiveDate::from_ymd_opt(1899, 12, 31),
+            DateSystem::Excel1904 => NaiveDate::from_ymd_opt(1904, 1, 1),
+        }
+        .expect("valid epoch")
+    }
+
+    /// Serial of 9999-12-31, the last date a serial can hold.
+    pub fn max_serial(self) -> i64 {
+        match self {
+            DateSystem::Excel1900 => 2_958_465,
+            DateSystem::Excel1904 => 2_958_465 - DATE1904_OFFSET,
+        }
+    }
+
+    /// The same day as a 1900-system serial, for weekday arithmetic.
+    pub fn to_1900_serial(self, days: i64) -> i64 {
+        match self {
+            DateSystem::Excel1900 => days,
+            DateSystem::Excel1904 => days + DATE1904_OFFSET,
+        }
+    }
+
+    /// Whole-day serial for a date, or `None` outside the system's range.
+    pub fn date_to_serial(self, date: NaiveDate) -> Option<i64> {
+        if date.year() > 9999 {
+            return None;
+        }
+        let days = (date - self.epoch()).num_days();
+        match self {
+            DateSystem::Excel1900 if days < 1 => None,
+            DateSystem::Excel1900 if days >= 60 => Some(days + 1),
+            DateSystem::Excel1900 => Some(days),
+            DateSystem::Excel1904 if days < 0 => None,
+            DateSystem::Excel1904 => Some(days),
+        }
+    }
+
+    /// Date of a serial, ignoring its time fraction. `None` when out of
+    /// range and for the 1900 system's phantom days 1900-01-00 and 1900-02-29.
+    pub fn serial_to_date(self, serial: f64) -> Option<NaiveDate> {
+        if !serial.is_finite() || serial < 0.0 {
+            return None;
+        }
+        let days = serial.floor() as i64;
+        if days > self.max_serial() {
+            return None;
+        }
+        let offset = match self {
+            DateSystem::Excel1900 if days == 0 || days == 60 => return None,
+            DateSystem::Excel1900 if days > 60 => days - 1,
+            _ => days,
+        };
+        self.epoch()
+            .checked_add_days(chrono::Days::new(offset as u64))
+    }
+
+    /// Year, month and day of a serial as a spreadsheet shows them,
+    /// including the phantom 1900-01-00 (serial 0) and 1900-02-29 (serial 60).
+    pub fn serial_to_ymd(self, serial: f64) -> Option<(i32, u32, u32)> {
+        if self == DateSystem::Excel1900 && (0.0..61.0).contains(&serial) {
+            match serial.floor() as i64 {
+                0 => return Some((1900, 1, 0)),
+                60 => return Some((1900, 2, 29)),
+                _ => {}
+            }
+        }
+        self.serial_to_date(serial)
+            .map(|date| (date.year(), date.month(), date.day()))
+    }
+}
+
 /// Format date according to pattern
 pub fn format_date(dt: DateTime<Utc>, pattern: &str) -> String {
     // Common Excel date formats
@@ -71,6 +160,43 @@ mod tests {
         assert!((serial - back).abs() < 0.001);
     }
 
+    /// Verifies the 1900 leap-year bug around serial 60.
+    #[test]
+    fn test_date_system_1900_leap_bug() {
+        let system = DateSystem::Excel1900;
+        let feb28 = NaiveDate::from_ymd_opt(1900, 2, 28).expect("date");
+        let mar1 = NaiveDate::from_ymd_opt(1900, 3, 1).expect("date");
+        assert_eq!(system.date_to_serial(feb28), Some(59));
+        assert_eq!(system.date_to_serial(mar1), Some(61));
+        assert_eq!(system.serial_to_date(60.0), None);
+        assert_eq!(system.serial_to_ymd(60.0), Some((1900, 2, 29)));
+        assert_eq!(system.serial_to_ymd(0.0), Some((1900, 1, 0)));
+        assert_eq!(system.serial_to_date(61.5), Some(mar1));
+
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).expect("date");
+        assert_eq!(system.date_to_serial(date), Some(45306));
+        assert_eq!(system.serial_to_date(45306.75), Some(date));
+        let last = NaiveDate::from_ymd_opt(9999, 12, 31).expect("date");
+        assert_eq!(system.date_to_serial(last), Some(system.max_serial()));
+        assert_eq!(system.serial_to_date(-1.0), None);
+    }
+
+    /// Verifies the 1904 date system offsets.
+    #[test]
+    fn test_date_system_1904() {
+        let system = DateSystem::Excel1904;
+        let epoch = NaiveDate::from_ymd_opt(1904, 1, 1).expect("date");
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).expect("date");
+        assert_eq!(system.date_to_serial(epoch), Some(0));
+        assert_eq!(system.date_to_serial(date), Some(45306 - 1462));
+        assert_eq!(system.serial_to_date(0.0), Some(epoch));
+        assert_eq!(system.to_1900_serial(0), 1462);
+        let before = NaiveDate::from_ymd_opt(1903, 12, 31).expect("date");
+        assert_eq!(system.date_to_serial(before), None);
+        let last = NaiveDate::from_ymd_opt(9999, 12, 31).expect("date");
+        assert_eq!(system.date_to_serial(last), Some(system.max_serial()));
+    }
+
     /// Verifies format patterns.
     #[test]
     fn test_format_date_patterns() {
@@ -238,6 +238,9 @@ fn cell_to_json(cell: &CellValue) -> serde_json::Value {
             }
             serde_json::Value::Object(obj)
         }
+        CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_) => {
+            serde_json::Value::String(cell.as_str())
+        }
     }
 }
 
@@ -318,6 +321,9 @@ fn value_to_json(value: &Value) -> serde_json::Value {
         Value::Table(batches) => table_to_json(batches),
         Value::Function { name, .. } => serde_json::Value::String(format!("<function {}>", name)),
         Value::Lambda { .. } => serde_json::Value::String("<lambda>".to_string()),
+        Value::Stream(stream) => {
+            serde_json::Value::String(format!("<stream {}>", stream.path().display()))
+        }
     }
 }
 
@@ -332,6 +338,10 @@ fn validate_statement(stmt: &Statement) -> Result<(), String> {
             "Line {}: export is not supported in the playground",
             line
         )),
+        Statement::Attach { line, .. } => Err(format!(
+            "Line {}: attach is not supported in the playground",
+            line
+        )),
         Statement::Dim { value, .. } => validate_expr(value),
         Statement::Assignment { target, value, .. } => {
             validate_lvalue(target)?;
@@ -451,7 +461,7 @@ fn validate_expr(expr: &Expr) -> Result<(), String> {
             }
             Ok(())
         }
-        Expr::Query(_) => Err("SQL is not supported in the playground".to_string()),
+        Expr::Query(_) | Expr::Dml(_) => Err("SQL is not supported in the playground".to_string()),
         Expr::AsyncForEach { iterable, body, .. } => {
             validate_expr(iterable)?;
             for stmt in body {
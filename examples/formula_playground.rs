@@ -157,6 +157,7 @@ fn format_cell_value(value: &CellValue) -> String {
                 "=?".to_string()
             }
         }
+        CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Duration(_) => value.as_str(),
     }
 }
 